@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+ALTER TABLE questions
+    ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'closed-duplicate', 'closed-off-topic', 'locked')),
+    ADD COLUMN status_reason VARCHAR(255);
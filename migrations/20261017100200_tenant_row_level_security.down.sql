@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+DROP POLICY IF EXISTS answers_tenant_isolation ON answers;
+DROP POLICY IF EXISTS questions_tenant_isolation ON questions;
+
+ALTER TABLE answers NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE answers DISABLE ROW LEVEL SECURITY;
+ALTER TABLE questions NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE questions DISABLE ROW LEVEL SECURITY;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE questions DROP COLUMN IF EXISTS tenant_id;
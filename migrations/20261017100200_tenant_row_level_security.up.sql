@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Rows inherit the tenant of the session that inserts them. With multi-tenancy disabled
+-- `app.tenant_id` is never set, so rows keep a NULL tenant and stay visible.
+ALTER TABLE questions
+    ADD COLUMN tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid;
+
+ALTER TABLE answers
+    ADD COLUMN tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid;
+
+CREATE INDEX IF NOT EXISTS questions_tenant_id_idx ON questions (tenant_id);
+CREATE INDEX IF NOT EXISTS answers_tenant_id_idx ON answers (tenant_id);
+
+ALTER TABLE questions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE questions FORCE ROW LEVEL SECURITY;
+ALTER TABLE answers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE answers FORCE ROW LEVEL SECURITY;
+
+CREATE POLICY questions_tenant_isolation ON questions
+    USING (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid)
+    WITH CHECK (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid);
+
+CREATE POLICY answers_tenant_isolation ON answers
+    USING (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid)
+    WITH CHECK (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid);
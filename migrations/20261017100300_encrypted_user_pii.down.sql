@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS user_ip_history;
+
+ALTER TABLE users DROP COLUMN IF EXISTS email_encrypted;
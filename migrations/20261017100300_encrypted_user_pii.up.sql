@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- PII columns hold AES-GCM ciphertexts produced by the application (see src/crypto.rs).
+ALTER TABLE users ADD COLUMN email_encrypted BYTEA;
+
+CREATE TABLE IF NOT EXISTS user_ip_history (
+    id BIGSERIAL PRIMARY KEY,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    ip_encrypted BYTEA NOT NULL,
+    seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS user_ip_history_user_uuid_idx ON user_ip_history (user_uuid);
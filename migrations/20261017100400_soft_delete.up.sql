@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+ALTER TABLE questions ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE answers ADD COLUMN deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS questions_deleted_at_idx ON questions (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS answers_deleted_at_idx ON answers (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS answer_revisions, question_revisions;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS updated_at, DROP COLUMN IF EXISTS author_uuid;
+ALTER TABLE questions DROP COLUMN IF EXISTS updated_at, DROP COLUMN IF EXISTS author_uuid;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+
+ALTER TABLE questions
+    ADD COLUMN author_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE answers
+    ADD COLUMN author_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+-- Every version of an edited post, starting with the original as revision 1.
+CREATE TABLE IF NOT EXISTS question_revisions (
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    editor_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_uuid, revision)
+);
+
+CREATE TABLE IF NOT EXISTS answer_revisions (
+    answer_uuid uuid NOT NULL REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    content VARCHAR(255) NOT NULL,
+    editor_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (answer_uuid, revision)
+);
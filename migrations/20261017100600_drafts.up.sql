@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Work-in-progress questions; each user has at most one question draft, which is never listed publicly.
+CREATE TABLE IF NOT EXISTS drafts (
+    draft_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_uuid uuid NOT NULL UNIQUE REFERENCES users (user_uuid) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL DEFAULT '',
+    description VARCHAR(255) NOT NULL DEFAULT '',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS question_followers (
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_uuid, user_uuid)
+);
+
+-- One row per recipient; filled by fan-out when something happens on a followed question.
+CREATE TABLE IF NOT EXISTS notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    question_uuid uuid REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    actor_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_uuid_idx ON notifications (user_uuid, created_at DESC);
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+ALTER TABLE questions
+    DROP CONSTRAINT IF EXISTS questions_private_board_check,
+    DROP COLUMN IF EXISTS board_uuid,
+    DROP COLUMN IF EXISTS visibility;
+
+DROP TABLE IF EXISTS board_members, boards;
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS boards (
+    board_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS board_members (
+    board_uuid uuid NOT NULL REFERENCES boards (board_uuid) ON DELETE CASCADE,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (board_uuid, user_uuid)
+);
+
+-- public: listed for everyone; unlisted: readable by direct link only; private: board members only.
+ALTER TABLE questions
+    ADD COLUMN visibility VARCHAR(16) NOT NULL DEFAULT 'public' CHECK (visibility IN ('public', 'unlisted', 'private')),
+    ADD COLUMN board_uuid uuid REFERENCES boards (board_uuid),
+    ADD CONSTRAINT questions_private_board_check CHECK (visibility <> 'private' OR board_uuid IS NOT NULL);
+
+CREATE INDEX IF NOT EXISTS questions_board_uuid_idx ON questions (board_uuid);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS board_members_user_uuid_idx;
+
+ALTER TABLE board_members
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS role;
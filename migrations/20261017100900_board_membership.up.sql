@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Existing rows were added directly, so they stay active members.
+ALTER TABLE board_members
+    ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'member' CHECK (role IN ('owner', 'member')),
+    ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'active' CHECK (status IN ('invited', 'requested', 'active'));
+
+CREATE INDEX IF NOT EXISTS board_members_user_uuid_idx ON board_members (user_uuid);
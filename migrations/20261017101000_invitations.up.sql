@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS invitations (
+    invitation_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    board_uuid uuid REFERENCES boards (board_uuid) ON DELETE CASCADE,
+    role VARCHAR(32) CHECK (role IN ('user', 'moderator', 'admin')),
+    created_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    used_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
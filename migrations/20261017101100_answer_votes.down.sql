@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE questions DROP COLUMN IF EXISTS accepted_answer_uuid;
+
+DROP TABLE IF EXISTS answer_votes;
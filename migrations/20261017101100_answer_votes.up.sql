@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS answer_votes (
+    answer_uuid uuid NOT NULL REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    value SMALLINT NOT NULL CHECK (value IN (-1, 1)),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (answer_uuid, user_uuid)
+);
+
+ALTER TABLE questions
+    ADD COLUMN accepted_answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE SET NULL;
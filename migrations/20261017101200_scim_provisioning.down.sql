@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS active,
+    DROP COLUMN IF EXISTS external_id;
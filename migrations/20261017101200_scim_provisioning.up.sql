@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- external_id is the identity provider's id for users provisioned over SCIM.
+ALTER TABLE users
+    ADD COLUMN external_id VARCHAR(255) UNIQUE,
+    ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
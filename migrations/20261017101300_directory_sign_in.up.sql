@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Name shown for users whose profile comes from a directory (LDAP or SCIM).
+ALTER TABLE users
+    ADD COLUMN display_name VARCHAR(255);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS answers_author_created_at_idx;
+DROP INDEX IF EXISTS questions_author_created_at_idx;
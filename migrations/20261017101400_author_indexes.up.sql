@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Backs the per-user listings, which page through an author's posts newest first.
+CREATE INDEX IF NOT EXISTS questions_author_created_at_idx ON questions (author_uuid, created_at DESC) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS answers_author_created_at_idx ON answers (author_uuid, created_at DESC) WHERE deleted_at IS NULL;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- How far each batching webhook has delivered, so a restart neither drops nor repeats events.
+CREATE TABLE IF NOT EXISTS webhook_cursors (
+    webhook VARCHAR(64) PRIMARY KEY,
+    delivered_until TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
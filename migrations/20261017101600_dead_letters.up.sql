@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Deliveries and jobs that kept failing, parked for an admin to retry or purge.
+CREATE TABLE IF NOT EXISTS dead_letters (
+    dead_letter_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL,
+    errors TEXT[] NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_failed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
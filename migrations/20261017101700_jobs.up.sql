@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- Long-running admin jobs. Workers claim queued rows and report progress here.
+CREATE TABLE IF NOT EXISTS jobs (
+    job_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(64) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'queued',
+    progress INTEGER NOT NULL DEFAULT 0 CHECK (progress BETWEEN 0 AND 100),
+    result JSONB,
+    error TEXT,
+    requested_by uuid REFERENCES users(user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS jobs_queued_idx ON jobs (created_at) WHERE status = 'queued';
@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Users mentioned as `@username` in a question description or an answer, recorded when the post is created.
+CREATE TABLE IF NOT EXISTS mentions (
+    id BIGSERIAL PRIMARY KEY,
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    actor_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS mentions_user_uuid_idx ON mentions (user_uuid, created_at DESC);
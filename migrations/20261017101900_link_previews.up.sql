@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- One row per distinct URL linked from a post. Rows are queued as 'pending' when a post is first
+-- read and filled in by the link preview fetcher ('fetched' or 'failed').
+CREATE TABLE IF NOT EXISTS link_previews (
+    url TEXT PRIMARY KEY,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    title TEXT,
+    description TEXT,
+    image_url TEXT,
+    site_name TEXT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    claimed_at TIMESTAMP,
+    fetched_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS link_previews_pending_idx ON link_previews (created_at) WHERE status IN ('pending', 'fetching');
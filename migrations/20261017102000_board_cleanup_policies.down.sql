@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS board_cleanup_policies;
+
+UPDATE questions SET status = 'open' WHERE status = 'closed-inactive';
+
+ALTER TABLE questions
+    DROP CONSTRAINT IF EXISTS questions_status_check,
+    ADD CONSTRAINT questions_status_check
+        CHECK (status IN ('open', 'closed-duplicate', 'closed-off-topic', 'locked'));
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+ALTER TABLE questions
+    DROP CONSTRAINT IF EXISTS questions_status_check,
+    ADD CONSTRAINT questions_status_check
+        CHECK (status IN ('open', 'closed-duplicate', 'closed-off-topic', 'closed-inactive', 'locked'));
+
+-- Retention rules the scheduler applies to a board's questions; a NULL rule is disabled.
+CREATE TABLE IF NOT EXISTS board_cleanup_policies (
+    board_uuid uuid PRIMARY KEY REFERENCES boards (board_uuid) ON DELETE CASCADE,
+    close_inactive_after_days INTEGER CHECK (close_inactive_after_days > 0),
+    delete_unanswered_after_days INTEGER CHECK (delete_unanswered_after_days > 0),
+    updated_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
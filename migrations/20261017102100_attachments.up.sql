@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- Answer attachments also record the answer's question, which decides who may read them.
+CREATE TABLE IF NOT EXISTS attachments (
+    attachment_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    storage_key TEXT NOT NULL UNIQUE,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    uploader_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    question_uuid uuid REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (answer_uuid IS NULL OR question_uuid IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS attachments_question_uuid_idx ON attachments (question_uuid);
+CREATE INDEX IF NOT EXISTS attachments_answer_uuid_idx ON attachments (answer_uuid);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS accept_suggestion_opt_outs;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS accept_suggested_at;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+-- Set when the author was told an answer looks acceptable, so each question is suggested once.
+ALTER TABLE questions ADD COLUMN accept_suggested_at TIMESTAMP;
+
+CREATE TABLE IF NOT EXISTS accept_suggestion_opt_outs (
+    user_uuid uuid PRIMARY KEY REFERENCES users (user_uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- Prefix of the user's current avatar objects in the object store; one object is stored per size.
+ALTER TABLE users ADD COLUMN avatar_key TEXT;
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+-- Flags on answers also record the answer's question.
+CREATE TABLE IF NOT EXISTS flags (
+    flag_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    reporter_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    reason TEXT NOT NULL,
+    details TEXT,
+    status TEXT NOT NULL DEFAULT 'open',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    resolved_at TIMESTAMP,
+    resolution_note TEXT
+);
+
+-- A user can only have one open flag per post. Question flags have no answer, hence the COALESCE.
+CREATE UNIQUE INDEX IF NOT EXISTS flags_open_per_reporter_idx
+    ON flags (reporter_uuid, question_uuid, COALESCE(answer_uuid, '00000000-0000-0000-0000-000000000000'))
+    WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS flags_status_created_at_idx ON flags (status, created_at);
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- What moderators did to posts in the moderation queue. Actions on answers also record the question.
+CREATE TABLE IF NOT EXISTS moderation_actions (
+    action_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    moderator_uuid uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    resolved_flags BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS moderation_actions_post_idx ON moderation_actions (question_uuid, answer_uuid);
+CREATE INDEX IF NOT EXISTS moderation_actions_created_at_idx ON moderation_actions (created_at DESC);
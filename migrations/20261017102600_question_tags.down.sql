@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS questions_tags_idx;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS tags;
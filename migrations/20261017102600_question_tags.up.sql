@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Normalized tag names; see `Question::tags`.
+ALTER TABLE questions ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS questions_tags_idx ON questions USING GIN (tags);
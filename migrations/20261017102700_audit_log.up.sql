@@ -0,0 +1,38 @@
+-- Add up migration script here
+
+-- Privileged actions by moderators and admins. Rows are never changed or removed, so the actor is
+-- a plain uuid rather than a reference that would be rewritten when the user is deleted.
+CREATE TABLE IF NOT EXISTS audit_log (
+    audit_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_uuid uuid,
+    action TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_uuid TEXT,
+    payload JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_created_at_idx ON audit_log (created_at);
+CREATE INDEX IF NOT EXISTS audit_log_tenant_id_idx ON audit_log (tenant_id);
+
+ALTER TABLE audit_log ENABLE ROW LEVEL SECURITY;
+ALTER TABLE audit_log FORCE ROW LEVEL SECURITY;
+
+CREATE POLICY audit_log_tenant_isolation ON audit_log
+    USING (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid)
+    WITH CHECK (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid);
+
+CREATE OR REPLACE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update_or_delete
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_log_append_only();
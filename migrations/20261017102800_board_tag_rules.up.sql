@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Tags a board's questions must use: at least one of `required_tags`, if any, and none of
+-- `forbidden_tags`.
+CREATE TABLE IF NOT EXISTS board_tag_rules (
+    board_uuid uuid PRIMARY KEY REFERENCES boards (board_uuid) ON DELETE CASCADE,
+    required_tags TEXT[] NOT NULL DEFAULT '{}',
+    forbidden_tags TEXT[] NOT NULL DEFAULT '{}',
+    updated_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (NOT required_tags && forbidden_tags)
+);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Set on posts a spam checker held for moderation; they stay out of listings until approved.
+ALTER TABLE questions ADD COLUMN held_at TIMESTAMP;
+ALTER TABLE answers ADD COLUMN held_at TIMESTAMP;
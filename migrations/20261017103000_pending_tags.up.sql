@@ -0,0 +1,21 @@
+-- Add up migration script here
+
+-- Tags no question used yet, proposed by users below `NewTagPolicy::min_reputation`. Approving a
+-- tag adds it to the questions that proposed it; rejecting it drops the requests.
+CREATE TABLE IF NOT EXISTS pending_tags (
+    name VARCHAR(35) NOT NULL,
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    requested_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid,
+    PRIMARY KEY (name, question_uuid)
+);
+
+CREATE INDEX IF NOT EXISTS pending_tags_tenant_id_idx ON pending_tags (tenant_id);
+
+ALTER TABLE pending_tags ENABLE ROW LEVEL SECURITY;
+ALTER TABLE pending_tags FORCE ROW LEVEL SECURITY;
+
+CREATE POLICY pending_tags_tenant_isolation ON pending_tags
+    USING (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid)
+    WITH CHECK (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid);
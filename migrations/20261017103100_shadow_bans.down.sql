@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP FUNCTION IF EXISTS post_visible_to(uuid, uuid);
+ALTER TABLE users DROP COLUMN IF EXISTS shadow_banned_at;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Set on users a moderator shadow-banned: their posts are kept but only shown to them and to moderators.
+ALTER TABLE users ADD COLUMN shadow_banned_at TIMESTAMP;
+
+-- The shadow-ban check shared by every read query on posts. Anonymous posts are always visible.
+CREATE OR REPLACE FUNCTION post_visible_to(author uuid, viewer uuid) RETURNS boolean AS $$
+    SELECT author IS NULL
+        OR author IS NOT DISTINCT FROM viewer
+        OR NOT EXISTS (SELECT 1 FROM users WHERE user_uuid = author AND shadow_banned_at IS NOT NULL)
+        OR EXISTS (SELECT 1 FROM users WHERE user_uuid = viewer AND role IN ('moderator', 'admin'));
+$$ LANGUAGE sql STABLE;
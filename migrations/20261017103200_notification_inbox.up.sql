@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- Serves the unread filter of GET /notifications and POST /notifications/read-all.
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications (user_uuid, created_at DESC) WHERE read_at IS NULL;
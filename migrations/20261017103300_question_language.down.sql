@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS questions_language_idx;
+ALTER TABLE questions DROP COLUMN IF EXISTS language;
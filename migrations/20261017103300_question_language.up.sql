@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- ISO 639-3 code detected from the title and description, NULL when undetermined. Questions posted
+-- before detection existed get one on their next edit.
+ALTER TABLE questions ADD COLUMN language VARCHAR(3);
+
+CREATE INDEX IF NOT EXISTS questions_language_idx ON questions (language);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS notifications_email_pending_idx;
+ALTER TABLE notifications DROP COLUMN IF EXISTS email_claimed_at;
+ALTER TABLE notifications DROP COLUMN IF EXISTS email_error;
+ALTER TABLE notifications DROP COLUMN IF EXISTS email_attempts;
+ALTER TABLE notifications DROP COLUMN IF EXISTS email_status;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Notifications double as the email delivery queue. Rows that existed before email delivery are
+-- 'skipped'; new ones start 'pending' and are moved to 'sending' while claimed by the email worker,
+-- then to 'sent', 'skipped' (no email for that kind or recipient) or, after repeated errors, 'failed'.
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS email_status VARCHAR(16) NOT NULL DEFAULT 'skipped';
+ALTER TABLE notifications ALTER COLUMN email_status SET DEFAULT 'pending';
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS email_attempts INT NOT NULL DEFAULT 0;
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS email_error TEXT;
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS email_claimed_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS notifications_email_pending_idx ON notifications (created_at) WHERE email_status IN ('pending', 'sending');
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+-- Verification reports of right-to-be-forgotten erasures. The erased user's row is gone, so their
+-- uuid is kept as a plain value; `checks` holds one entry per column that referenced them.
+CREATE TABLE IF NOT EXISTS erasure_reports (
+    report_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_uuid uuid NOT NULL,
+    requested_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    checks JSONB NOT NULL,
+    deleted_objects TEXT[] NOT NULL DEFAULT '{}',
+    backup_retention_days INT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS erasure_reports_created_at_idx ON erasure_reports (created_at DESC);
@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Users who asked for an email digest of their unread notifications and new questions in `tags`.
+-- Unsubscribed users have no row.
+CREATE TABLE IF NOT EXISTS digest_subscriptions (
+    user_uuid uuid PRIMARY KEY REFERENCES users (user_uuid) ON DELETE CASCADE,
+    frequency VARCHAR(16) NOT NULL CHECK (frequency IN ('daily', 'weekly')),
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    last_sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS digest_subscriptions_last_sent_at_idx ON digest_subscriptions (last_sent_at);
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+-- The retention purge may delete audit entries older than AUDIT_LOG_RETENTION_DAYS. It opts in per
+-- transaction with `SET LOCAL app.audit_log_purge = 'on'`; everything else is still refused.
+CREATE OR REPLACE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('app.audit_log_purge', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+-- Rows removed by the retention purge, per category.
+CREATE TABLE IF NOT EXISTS retention_purges (
+    category TEXT PRIMARY KEY,
+    last_run_at TIMESTAMP NOT NULL,
+    last_removed BIGINT NOT NULL,
+    total_removed BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS user_ip_history_seen_at_idx ON user_ip_history (seen_at);
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Announcements are posted by moderators and take no answers, whatever their status.
+ALTER TABLE questions
+    ADD COLUMN kind VARCHAR(16) NOT NULL DEFAULT 'question'
+        CHECK (kind IN ('question', 'announcement'));
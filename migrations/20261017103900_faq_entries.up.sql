@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+-- Questions moderators curated for the FAQ, with the answer that resolves each of them.
+CREATE TABLE IF NOT EXISTS faq_entries (
+    question_uuid uuid PRIMARY KEY REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    answer_uuid uuid NOT NULL REFERENCES answers (answer_uuid) ON DELETE CASCADE,
+    curated_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+DROP VIEW IF EXISTS counted_answer_votes;
+DROP FUNCTION IF EXISTS contest_answer_visible_to(timestamp, uuid, uuid);
+DROP INDEX IF EXISTS questions_contest_undecided_idx;
+ALTER TABLE questions
+    DROP CONSTRAINT IF EXISTS questions_contest_check,
+    DROP COLUMN IF EXISTS contest_decided_at,
+    DROP COLUMN IF EXISTS contest_winner_uuid,
+    DROP COLUMN IF EXISTS contest_ends_at,
+    DROP COLUMN IF EXISTS contest_reveal_at;
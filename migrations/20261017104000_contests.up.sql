@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+-- Contest questions hide their answers until contest_reveal_at; votes cast from then until
+-- contest_ends_at pick contest_winner_uuid, set once contest_decided_at is.
+ALTER TABLE questions
+    ADD COLUMN contest_reveal_at TIMESTAMP,
+    ADD COLUMN contest_ends_at TIMESTAMP,
+    ADD COLUMN contest_winner_uuid uuid REFERENCES answers (answer_uuid) ON DELETE SET NULL,
+    ADD COLUMN contest_decided_at TIMESTAMP,
+    ADD CONSTRAINT questions_contest_check CHECK (
+        (contest_reveal_at IS NULL) = (contest_ends_at IS NULL) AND contest_ends_at > contest_reveal_at
+    );
+
+CREATE INDEX IF NOT EXISTS questions_contest_undecided_idx ON questions (contest_ends_at)
+    WHERE contest_ends_at IS NOT NULL AND contest_decided_at IS NULL;
+
+-- The contest check shared by every read query on answers: before the reveal, an answer is only
+-- shown to its author and to moderators.
+CREATE OR REPLACE FUNCTION contest_answer_visible_to(reveal_at timestamp, author uuid, viewer uuid) RETURNS boolean AS $$
+    SELECT reveal_at IS NULL
+        OR reveal_at <= CURRENT_TIMESTAMP
+        OR (author IS NOT NULL AND author = viewer)
+        OR EXISTS (SELECT 1 FROM users WHERE user_uuid = viewer AND role IN ('moderator', 'admin'));
+$$ LANGUAGE sql STABLE;
+
+-- Votes that count towards scores: on contest questions, only those cast after the reveal.
+CREATE OR REPLACE VIEW counted_answer_votes AS
+    SELECT v.* FROM answer_votes v
+    JOIN answers a ON a.answer_uuid = v.answer_uuid
+    JOIN questions q ON q.question_uuid = a.question_uuid
+    WHERE q.contest_reveal_at IS NULL OR v.created_at >= q.contest_reveal_at;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+
+-- Endpoints admins registered to receive post events, each signed with its own secret.
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    created_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per event and webhook, doubling as the delivery queue and the delivery log. Deliveries
+-- start 'pending' and end 'delivered' or, once retries are exhausted, 'failed'. A claimed delivery
+-- is 'sending' and becomes due again at next_attempt_at in case its worker stopped.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_uuid uuid NOT NULL REFERENCES webhooks (webhook_uuid) ON DELETE CASCADE,
+    event VARCHAR(32) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    response_status INT,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at)
+    WHERE status IN ('pending', 'sending');
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_idx ON webhook_deliveries (webhook_uuid, created_at);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS notifications_queue_webhook ON notifications;
+DROP TRIGGER IF EXISTS notifications_apply_settings ON notifications;
+DROP FUNCTION IF EXISTS queue_notification_webhook();
+DROP FUNCTION IF EXISTS apply_notification_settings();
+DELETE FROM webhooks WHERE owner_uuid IS NOT NULL;
+DROP INDEX IF EXISTS webhooks_owner_idx;
+ALTER TABLE webhooks DROP COLUMN IF EXISTS owner_uuid;
+ALTER TABLE notifications DROP COLUMN IF EXISTS in_app;
+DROP TABLE IF EXISTS notification_settings;
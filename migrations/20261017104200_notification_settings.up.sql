@@ -0,0 +1,83 @@
+-- Add up migration script here
+
+-- Channels each user chose per notification kind. Kinds without a row use the defaults: in-app and
+-- email on, webhook off.
+CREATE TABLE IF NOT EXISTS notification_settings (
+    user_uuid uuid NOT NULL REFERENCES users (user_uuid) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    in_app BOOLEAN NOT NULL,
+    email BOOLEAN NOT NULL,
+    webhook BOOLEAN NOT NULL,
+    PRIMARY KEY (user_uuid, kind)
+);
+
+-- Notifications the user only wants by email or webhook are kept out of the inbox.
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS in_app BOOLEAN NOT NULL DEFAULT true;
+
+-- A user's own webhook, receiving the notifications they send to the webhook channel. Admins'
+-- webhooks have no owner.
+ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS owner_uuid uuid REFERENCES users (user_uuid) ON DELETE CASCADE;
+CREATE UNIQUE INDEX IF NOT EXISTS webhooks_owner_idx ON webhooks (owner_uuid);
+
+-- Applies the recipient's settings to every notification as it is fanned out, whichever query
+-- creates it: notifications turned off on every channel are dropped, and the email of those turned
+-- off by email is skipped.
+CREATE OR REPLACE FUNCTION apply_notification_settings() RETURNS trigger AS $$
+DECLARE
+    settings notification_settings%ROWTYPE;
+BEGIN
+    SELECT * INTO settings FROM notification_settings WHERE user_uuid = NEW.user_uuid AND kind = NEW.kind;
+
+    IF NOT FOUND THEN
+        RETURN NEW;
+    END IF;
+
+    IF NOT (settings.in_app OR settings.email OR settings.webhook) THEN
+        RETURN NULL;
+    END IF;
+
+    NEW.in_app := settings.in_app;
+
+    IF NOT settings.email THEN
+        NEW.email_status := 'skipped';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notifications_apply_settings
+    BEFORE INSERT ON notifications
+    FOR EACH ROW EXECUTE FUNCTION apply_notification_settings();
+
+-- Queues a delivery of each new notification to the recipient's webhook when they send its kind
+-- there, unless the post is hidden from them.
+CREATE OR REPLACE FUNCTION queue_notification_webhook() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO webhook_deliveries (webhook_uuid, event, payload)
+    SELECT w.webhook_uuid, 'notification', jsonb_build_object(
+        'event', 'notification',
+        'notification_id', NEW.id,
+        'kind', NEW.kind,
+        'question_uuid', NEW.question_uuid,
+        'question_title', q.title,
+        'answer_uuid', NEW.answer_uuid,
+        'actor', u.username
+    )
+    FROM webhooks w
+    JOIN notification_settings s ON s.user_uuid = w.owner_uuid AND s.kind = NEW.kind AND s.webhook
+    LEFT JOIN questions q ON q.question_uuid = NEW.question_uuid
+    LEFT JOIN answers a ON a.answer_uuid = NEW.answer_uuid
+    LEFT JOIN users u ON u.user_uuid = NEW.actor_uuid
+    WHERE w.owner_uuid = NEW.user_uuid
+    AND (NEW.question_uuid IS NULL OR post_visible_to(q.author_uuid, NEW.user_uuid))
+    AND (NEW.answer_uuid IS NULL OR (post_visible_to(a.author_uuid, NEW.user_uuid)
+        AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, NEW.user_uuid)));
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notifications_queue_webhook
+    AFTER INSERT ON notifications
+    FOR EACH ROW EXECUTE FUNCTION queue_notification_webhook();
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS query_samples;
+DROP TABLE IF EXISTS query_sampling;
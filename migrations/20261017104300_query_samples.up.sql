@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Share of requests whose queries are sampled, set by admins. A single row, read by every server
+-- instance.
+CREATE TABLE IF NOT EXISTS query_sampling (
+    singleton BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (singleton),
+    percent DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (percent BETWEEN 0 AND 100),
+    updated_by uuid REFERENCES users (user_uuid) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO query_sampling (singleton) VALUES (TRUE) ON CONFLICT DO NOTHING;
+
+-- The queries of a sampled request, in the order they finished, each with its statement, timing
+-- and row counts. Purged after a week.
+CREATE TABLE IF NOT EXISTS query_samples (
+    sample_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    route TEXT NOT NULL,
+    status INT NOT NULL,
+    elapsed_ms DOUBLE PRECISION NOT NULL,
+    queries JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS query_samples_created_at_idx ON query_samples (created_at);
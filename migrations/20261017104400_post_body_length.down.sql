@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+ALTER TABLE drafts ALTER COLUMN description TYPE VARCHAR(255);
+ALTER TABLE answer_revisions ALTER COLUMN content TYPE VARCHAR(255);
+ALTER TABLE question_revisions ALTER COLUMN description TYPE VARCHAR(255);
+ALTER TABLE answers ALTER COLUMN content TYPE VARCHAR(255);
+ALTER TABLE questions ALTER COLUMN description TYPE VARCHAR(255);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Question descriptions and answers can be up to 30,000 characters, as validated on the way in.
+ALTER TABLE questions ALTER COLUMN description TYPE VARCHAR(30000);
+ALTER TABLE answers ALTER COLUMN content TYPE VARCHAR(30000);
+ALTER TABLE question_revisions ALTER COLUMN description TYPE VARCHAR(30000);
+ALTER TABLE answer_revisions ALTER COLUMN content TYPE VARCHAR(30000);
+ALTER TABLE drafts ALTER COLUMN description TYPE VARCHAR(30000);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- Kind-specific parameters of a job, such as where the dump of an import is stored.
+ALTER TABLE jobs ADD COLUMN input JSONB;
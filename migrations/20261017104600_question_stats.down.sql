@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS answers_track_activity ON answers;
+DROP TRIGGER IF EXISTS questions_track_activity ON questions;
+DROP FUNCTION IF EXISTS track_answer_activity();
+DROP FUNCTION IF EXISTS track_question_activity();
+DROP TABLE IF EXISTS question_viewers;
+DROP TABLE IF EXISTS question_stats;
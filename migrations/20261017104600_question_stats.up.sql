@@ -0,0 +1,67 @@
+-- Add up migration script here
+
+-- Counters of each question, kept up to date as it is viewed, answered and edited, so that
+-- reading them costs one row.
+CREATE TABLE IF NOT EXISTS question_stats (
+    question_uuid uuid PRIMARY KEY REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    views BIGINT NOT NULL DEFAULT 0,
+    unique_viewers BIGINT NOT NULL DEFAULT 0,
+    answers BIGINT NOT NULL DEFAULT 0,
+    last_activity_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Who viewed each question, by a hash of their user UUID or IP address, so that each is counted
+-- as a unique viewer once.
+CREATE TABLE IF NOT EXISTS question_viewers (
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    viewer_hash TEXT NOT NULL,
+    PRIMARY KEY (question_uuid, viewer_hash)
+);
+
+INSERT INTO question_stats (question_uuid, answers, last_activity_at)
+SELECT q.question_uuid, COUNT(a.answer_uuid) FILTER (WHERE a.deleted_at IS NULL), GREATEST(q.updated_at, MAX(a.updated_at))
+FROM questions q
+LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+GROUP BY q.question_uuid
+ON CONFLICT DO NOTHING;
+
+-- A question's last activity is when it or one of its answers was last posted or edited.
+CREATE OR REPLACE FUNCTION track_question_activity() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO question_stats (question_uuid, last_activity_at) VALUES (NEW.question_uuid, NEW.updated_at)
+    ON CONFLICT (question_uuid) DO UPDATE
+    SET last_activity_at = GREATEST(question_stats.last_activity_at, EXCLUDED.last_activity_at);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER questions_track_activity
+    AFTER INSERT OR UPDATE OF updated_at ON questions
+    FOR EACH ROW EXECUTE FUNCTION track_question_activity();
+
+-- Answers are counted while they are not deleted, so soft-deleting one and restoring it count too.
+CREATE OR REPLACE FUNCTION track_answer_activity() RETURNS trigger AS $$
+DECLARE
+    counted_before INT := CASE WHEN TG_OP <> 'INSERT' AND OLD.deleted_at IS NULL THEN 1 ELSE 0 END;
+BEGIN
+    -- Also fired as a question's answers are deleted with it, when there is no row to insert.
+    IF TG_OP = 'DELETE' THEN
+        UPDATE question_stats SET answers = answers - counted_before WHERE question_uuid = OLD.question_uuid;
+
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO question_stats (question_uuid, answers, last_activity_at)
+    VALUES (NEW.question_uuid, CASE WHEN NEW.deleted_at IS NULL THEN 1 ELSE 0 END - counted_before, NEW.updated_at)
+    ON CONFLICT (question_uuid) DO UPDATE
+    SET answers = question_stats.answers + EXCLUDED.answers,
+        last_activity_at = GREATEST(question_stats.last_activity_at, EXCLUDED.last_activity_at);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER answers_track_activity
+    AFTER INSERT OR UPDATE OF updated_at, deleted_at OR DELETE ON answers
+    FOR EACH ROW EXECUTE FUNCTION track_answer_activity();
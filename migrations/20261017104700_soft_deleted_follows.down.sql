@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DELETE FROM question_followers WHERE deleted_at IS NOT NULL;
+DELETE FROM pending_tags WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE question_followers DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE pending_tags DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Follows and tag proposals of a deleted question are stamped with its deletion time, like its
+-- answers, so that restoring the question brings them back.
+ALTER TABLE question_followers ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE pending_tags ADD COLUMN deleted_at TIMESTAMP;
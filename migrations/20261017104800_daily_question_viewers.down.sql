@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+ALTER FUNCTION track_question_activity() SECURITY INVOKER RESET search_path;
+ALTER FUNCTION track_answer_activity() SECURITY INVOKER RESET search_path;
+
+DROP TABLE IF EXISTS viewer_salts;
+
+DELETE FROM question_viewers;
+
+ALTER TABLE question_viewers DROP CONSTRAINT question_viewers_pkey;
+ALTER TABLE question_viewers DROP COLUMN IF EXISTS viewed_on;
+ALTER TABLE question_viewers ADD PRIMARY KEY (question_uuid, viewer_hash);
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- Viewers were kept as plain SHA-256 hashes of their user UUID or IP address, which anyone can
+-- reverse by hashing every IPv4 address. They are now HMACs keyed with a random salt of the day,
+-- and both are purged once the day is over, so a viewer is counted once a day and cannot be told
+-- apart afterwards.
+DELETE FROM question_viewers;
+
+ALTER TABLE question_viewers ADD COLUMN viewed_on DATE NOT NULL DEFAULT CURRENT_DATE;
+ALTER TABLE question_viewers DROP CONSTRAINT question_viewers_pkey;
+ALTER TABLE question_viewers ADD PRIMARY KEY (question_uuid, viewed_on, viewer_hash);
+
+CREATE TABLE IF NOT EXISTS viewer_salts (
+    day DATE PRIMARY KEY,
+    salt BYTEA NOT NULL
+);
+
+-- The counters are kept for whoever writes questions and answers, who need no grants on them.
+ALTER FUNCTION track_question_activity() SECURITY DEFINER SET search_path = public;
+ALTER FUNCTION track_answer_activity() SECURITY DEFINER SET search_path = public;
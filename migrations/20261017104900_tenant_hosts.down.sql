@@ -0,0 +1,24 @@
+-- Add down migration script here
+
+DO $$
+DECLARE
+    tbl TEXT;
+BEGIN
+    FOREACH tbl IN ARRAY ARRAY[
+        'question_revisions', 'question_followers', 'notifications', 'mentions', 'attachments', 'flags',
+        'moderation_actions', 'faq_entries', 'question_stats', 'question_viewers', 'answer_revisions',
+        'answer_votes', 'boards', 'board_members', 'invitations', 'board_cleanup_policies', 'board_tag_rules',
+        'drafts', 'accept_suggestion_opt_outs', 'digest_subscriptions', 'notification_settings',
+        'erasure_reports', 'jobs', 'webhooks', 'webhook_deliveries'
+    ]
+    LOOP
+        EXECUTE format('DROP POLICY IF EXISTS %I ON %I', tbl || '_tenant_isolation', tbl);
+        EXECUTE format('ALTER TABLE %I NO FORCE ROW LEVEL SECURITY', tbl);
+        EXECUTE format('ALTER TABLE %I DISABLE ROW LEVEL SECURITY', tbl);
+        EXECUTE format('ALTER TABLE %I DROP COLUMN IF EXISTS tenant_id', tbl);
+    END LOOP;
+END $$;
+
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
+
+DROP TABLE IF EXISTS tenants;
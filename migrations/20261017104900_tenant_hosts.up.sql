@@ -0,0 +1,108 @@
+-- Add up migration script here
+
+-- The tenants of a multi-tenant deployment, each served at its own host. Requests take their tenant
+-- from the host they are sent to, and background jobs run once for each tenant.
+CREATE TABLE IF NOT EXISTS tenants (
+    tenant_id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    host TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Users belong to the tenant they were created in, and their API tokens are only valid at its host.
+ALTER TABLE users
+    ADD COLUMN tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid;
+
+CREATE INDEX IF NOT EXISTS users_tenant_id_idx ON users (tenant_id);
+
+-- Tables added since questions and answers got row-level security are isolated the same way. Their
+-- rows take the tenant of the post, board or user they belong to; the owner running this is only
+-- subject to the policies of questions and answers while they are forced.
+ALTER TABLE questions NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE answers NO FORCE ROW LEVEL SECURITY;
+
+UPDATE users u SET tenant_id = COALESCE(
+    (SELECT q.tenant_id FROM questions q WHERE q.author_uuid = u.user_uuid AND q.tenant_id IS NOT NULL LIMIT 1),
+    (SELECT a.tenant_id FROM answers a WHERE a.author_uuid = u.user_uuid AND a.tenant_id IS NOT NULL LIMIT 1)
+);
+
+ALTER TABLE boards
+    ADD COLUMN tenant_id uuid DEFAULT NULLIF(current_setting('app.tenant_id', true), '')::uuid;
+
+UPDATE boards b SET tenant_id =
+    (SELECT q.tenant_id FROM questions q WHERE q.board_uuid = b.board_uuid AND q.tenant_id IS NOT NULL LIMIT 1);
+
+DO $$
+DECLARE
+    source RECORD;
+BEGIN
+    FOR source IN
+        SELECT * FROM (VALUES
+            ('question_revisions', 'questions', 'question_uuid', 'question_uuid'),
+            ('question_followers', 'questions', 'question_uuid', 'question_uuid'),
+            ('notifications', 'questions', 'question_uuid', 'question_uuid'),
+            ('mentions', 'questions', 'question_uuid', 'question_uuid'),
+            ('attachments', 'questions', 'question_uuid', 'question_uuid'),
+            ('flags', 'questions', 'question_uuid', 'question_uuid'),
+            ('moderation_actions', 'questions', 'question_uuid', 'question_uuid'),
+            ('faq_entries', 'questions', 'question_uuid', 'question_uuid'),
+            ('question_stats', 'questions', 'question_uuid', 'question_uuid'),
+            ('question_viewers', 'questions', 'question_uuid', 'question_uuid'),
+            ('answer_revisions', 'answers', 'answer_uuid', 'answer_uuid'),
+            ('answer_votes', 'answers', 'answer_uuid', 'answer_uuid'),
+            ('board_members', 'boards', 'board_uuid', 'board_uuid'),
+            ('invitations', 'boards', 'board_uuid', 'board_uuid'),
+            ('board_cleanup_policies', 'boards', 'board_uuid', 'board_uuid'),
+            ('board_tag_rules', 'boards', 'board_uuid', 'board_uuid'),
+            ('drafts', 'users', 'user_uuid', 'user_uuid'),
+            ('accept_suggestion_opt_outs', 'users', 'user_uuid', 'user_uuid'),
+            ('digest_subscriptions', 'users', 'user_uuid', 'user_uuid'),
+            ('notification_settings', 'users', 'user_uuid', 'user_uuid'),
+            ('erasure_reports', 'users', 'user_uuid', 'requested_by'),
+            ('jobs', 'users', 'user_uuid', 'requested_by'),
+            ('webhooks', 'users', 'user_uuid', 'created_by'),
+            ('webhook_deliveries', 'webhooks', 'webhook_uuid', 'webhook_uuid')
+        ) AS sources (tbl, parent, parent_key, own_key)
+    LOOP
+        EXECUTE format(
+            'ALTER TABLE %I ADD COLUMN tenant_id uuid DEFAULT NULLIF(current_setting(''app.tenant_id'', true), '''')::uuid',
+            source.tbl
+        );
+        EXECUTE format(
+            'UPDATE %I t SET tenant_id = p.tenant_id FROM %I p WHERE p.%I = t.%I',
+            source.tbl,
+            source.parent,
+            source.parent_key,
+            source.own_key
+        );
+        EXECUTE format('CREATE INDEX IF NOT EXISTS %I ON %I (tenant_id)', source.tbl || '_tenant_id_idx', source.tbl);
+    END LOOP;
+END $$;
+
+CREATE INDEX IF NOT EXISTS boards_tenant_id_idx ON boards (tenant_id);
+
+ALTER TABLE questions FORCE ROW LEVEL SECURITY;
+ALTER TABLE answers FORCE ROW LEVEL SECURITY;
+
+DO $$
+DECLARE
+    tbl TEXT;
+BEGIN
+    FOREACH tbl IN ARRAY ARRAY[
+        'question_revisions', 'question_followers', 'notifications', 'mentions', 'attachments', 'flags',
+        'moderation_actions', 'faq_entries', 'question_stats', 'question_viewers', 'answer_revisions',
+        'answer_votes', 'boards', 'board_members', 'invitations', 'board_cleanup_policies', 'board_tag_rules',
+        'drafts', 'accept_suggestion_opt_outs', 'digest_subscriptions', 'notification_settings',
+        'erasure_reports', 'jobs', 'webhooks', 'webhook_deliveries'
+    ]
+    LOOP
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', tbl);
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', tbl);
+        EXECUTE format(
+            'CREATE POLICY %I ON %I
+                USING (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting(''app.tenant_id'', true), '''')::uuid)
+                WITH CHECK (tenant_id IS NOT DISTINCT FROM NULLIF(current_setting(''app.tenant_id'', true), '''')::uuid)',
+            tbl || '_tenant_isolation',
+            tbl
+        );
+    END LOOP;
+END $$;
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    ldap::{LdapBackend, LdapConfig},
+    models::{ProvisionedUser, Role},
+    secrets::{SecretsError, SecretsProvider},
+};
+
+/// Generates a random, hex-encoded bearer token handed out to users once.
+pub fn generate_api_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Tokens are only ever stored and looked up by their SHA-256 digest.
+pub fn hash_api_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Maps directory group names to forum roles, configured as `group:role` pairs separated by
+/// commas, e.g. `forum-admins:admin,forum-mods:moderator`. Group names are matched case-insensitively.
+#[derive(Debug, Default, Clone)]
+pub struct GroupRoleMap {
+    roles: HashMap<String, Role>,
+}
+
+impl GroupRoleMap {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut roles = HashMap::new();
+
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (group, role) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| format!("expected group:role, got {}", entry))?;
+
+            roles.insert(group.trim().to_lowercase(), role.trim().parse()?);
+        }
+
+        Ok(GroupRoleMap { roles })
+    }
+
+    /// The most privileged role granted by any of `groups`; users in no mapped group get `Role::User`.
+    pub fn role_for<'a>(&self, groups: impl IntoIterator<Item = &'a str>) -> Role {
+        groups
+            .into_iter()
+            .filter_map(|group| self.roles.get(&group.to_lowercase()).copied())
+            .max_by_key(|role| match role {
+                Role::User => 0,
+                Role::Moderator => 1,
+                Role::Admin => 2,
+            })
+            .unwrap_or(Role::User)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AuthBackendError {
+    #[error("Invalid auth backend configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Failed to read auth backend secret: {0}")]
+    Secrets(#[from] SecretsError),
+    #[error("Directory request failed: {0}")]
+    Directory(String),
+}
+
+/// Verifies username and password sign-ins against an external user directory, for installs
+/// that manage accounts outside the forum. Implement this to add another directory.
+#[async_trait]
+pub trait AuthBackend: Send + Sync {
+    /// Returns the user as the directory describes them, or `None` when the credentials are wrong.
+    async fn authenticate(&self, username: &str, password: &str) -> Result<Option<ProvisionedUser>, AuthBackendError>;
+}
+
+/// Builds the backend selected by `AUTH_BACKEND`. The default, `token`, has no directory and
+/// users only sign up through `POST /users`.
+pub async fn backend_from_env(
+    secrets: &dyn SecretsProvider,
+) -> Result<Option<Box<dyn AuthBackend>>, AuthBackendError> {
+    match std::env::var("AUTH_BACKEND").as_deref() {
+        Err(_) | Ok("token") => Ok(None),
+        Ok("ldap") => Ok(Some(Box::new(LdapBackend::new(LdapConfig::from_env(secrets).await?)))),
+        Ok(other) => Err(AuthBackendError::InvalidConfig(format!("unknown AUTH_BACKEND {}", other))),
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageReader, ImageResult, Limits};
+
+use crate::{models::AvatarUrl, routes::API_V1};
+
+/// Edge lengths, in pixels, of the square PNGs stored for each avatar.
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+pub const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+/// Larger images are refused before decoding so a small, highly compressed file cannot exhaust memory.
+const MAX_SOURCE_PIXELS: u32 = 4096;
+const KEY_PREFIX: &str = "avatar-";
+
+/// Decodes an uploaded image, crops it to a centered square and renders it at every `AVATAR_SIZES`
+/// size as PNG. This is CPU-bound, so callers run it on a blocking thread.
+pub fn render_avatar(content: &[u8]) -> ImageResult<Vec<(u32, Vec<u8>)>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_PIXELS);
+    limits.max_image_height = Some(MAX_SOURCE_PIXELS);
+
+    let mut reader = ImageReader::new(Cursor::new(content)).with_guessed_format()?;
+    reader.limits(limits);
+
+    let image = reader.decode()?;
+    let side = image.width().min(image.height());
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+
+    AVATAR_SIZES
+        .iter()
+        .map(|size| Ok((*size, encode_png(&square.resize_exact(*size, *size, FilterType::Lanczos3))?)))
+        .collect()
+}
+
+fn encode_png(image: &DynamicImage) -> ImageResult<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    Ok(png)
+}
+
+/// A new prefix for a user's avatar objects. Each upload gets its own, so avatar URLs never change
+/// content and can be cached indefinitely.
+pub fn new_avatar_key() -> String {
+    format!("{}{}", KEY_PREFIX, hex::encode(rand::random::<[u8; 16]>()))
+}
+
+/// Object store key of one size of an avatar.
+pub fn avatar_object_key(avatar_key: &str, size: u32) -> String {
+    format!("{}-{}", avatar_key, size)
+}
+
+/// Public URLs of every size of the avatar stored under `avatar_key`.
+pub fn avatar_urls(avatar_key: Option<&str>) -> Vec<AvatarUrl> {
+    avatar_key
+        .map(|avatar_key| {
+            AVATAR_SIZES
+                .iter()
+                .map(|size| AvatarUrl {
+                    size: *size,
+                    url: format!("{}/avatars/{}", API_V1, avatar_object_key(avatar_key, *size)),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether `object_key` names an avatar rather than another object, such as an attachment, that
+/// must not be served without a signed URL.
+pub fn is_avatar_object_key(object_key: &str) -> bool {
+    let Some((avatar_key, size)) = object_key.rsplit_once('-') else {
+        return false;
+    };
+    let Some(id) = avatar_key.strip_prefix(KEY_PREFIX) else {
+        return false;
+    };
+
+    id.len() == 32
+        && id.chars().all(|c| c.is_ascii_hexdigit())
+        && size.parse().is_ok_and(|size: u32| AVATAR_SIZES.contains(&size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_avatar_should_crop_and_resize_to_every_size() {
+        let source = DynamicImage::new_rgb8(300, 200);
+        let mut content = Vec::new();
+        source.write_to(&mut Cursor::new(&mut content), ImageFormat::Jpeg).unwrap();
+
+        let rendered = render_avatar(&content).unwrap();
+
+        assert_eq!(rendered.iter().map(|(size, _)| *size).collect::<Vec<_>>(), AVATAR_SIZES);
+
+        for (size, png) in rendered {
+            let image = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+            assert_eq!((image.width(), image.height()), (size, size));
+        }
+    }
+
+    #[test]
+    fn render_avatar_should_reject_non_images() {
+        assert!(render_avatar(b"not an image").is_err());
+    }
+
+    #[test]
+    fn is_avatar_object_key_should_only_accept_avatar_keys() {
+        let key = new_avatar_key();
+
+        assert!(is_avatar_object_key(&avatar_object_key(&key, 64)));
+        assert!(!is_avatar_object_key(&avatar_object_key(&key, 65)));
+        assert!(!is_avatar_object_key("0123456789abcdef0123456789abcdef"));
+        assert!(!is_avatar_object_key("avatar-../x-64"));
+    }
+}
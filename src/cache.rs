@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{models::QuestionDetail, tenancy::current_tenant};
+
+/// Keeps computed values in memory for `ttl_seconds`. When full, expired entries are dropped
+/// first and, if that is not enough, the whole cache is cleared.
+pub struct TtlCache<V> {
+    ttl_seconds: u64,
+    capacity: usize,
+    /// Values with the time, in seconds since the Unix epoch, they were stored.
+    entries: Mutex<HashMap<String, (u64, V)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl_seconds: u64, capacity: usize) -> Self {
+        TtlCache {
+            ttl_seconds,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &str, now: u64) -> Option<V> {
+        let entries = self.entries.lock().expect("cache lock is not poisoned");
+
+        entries
+            .get(key)
+            .filter(|(stored_at, _)| now < stored_at + self.ttl_seconds)
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn insert(&self, key: String, value: V, now: u64) {
+        let mut entries = self.entries.lock().expect("cache lock is not poisoned");
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (stored_at, _)| now < *stored_at + self.ttl_seconds);
+
+            if entries.len() >= self.capacity {
+                entries.clear();
+            }
+        }
+
+        entries.insert(key, (now, value));
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("Cache store error: {0}")]
+    Store(#[from] redis::RedisError),
+}
+
+/// Where cached responses are kept, as JSON, each for its own time to live.
+#[async_trait]
+pub trait Cache {
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError>;
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), CacheError>;
+
+    /// Drops every entry whose key starts with `prefix`.
+    async fn remove_prefix(&self, prefix: &str) -> Result<(), CacheError>;
+}
+
+/// Keeps the entries in memory, so each server instance caches, and invalidates, on its own. When
+/// full, expired entries are dropped first and, if that is not enough, the whole cache is cleared.
+pub struct MemoryCache {
+    capacity: usize,
+    /// Values with the time they expire.
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let entries = self.entries.lock().expect("cache lock is not poisoned");
+
+        Ok(entries
+            .get(key)
+            .filter(|(expires_at, _)| Instant::now() < *expires_at)
+            .map(|(_, value)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), CacheError> {
+        let mut entries = self.entries.lock().expect("cache lock is not poisoned");
+        let now = Instant::now();
+
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            entries.retain(|_, (expires_at, _)| now < *expires_at);
+
+            if entries.len() >= self.capacity {
+                entries.clear();
+            }
+        }
+
+        entries.insert(key.to_owned(), (now + ttl, value));
+
+        Ok(())
+    }
+
+    async fn remove_prefix(&self, prefix: &str) -> Result<(), CacheError> {
+        let mut entries = self.entries.lock().expect("cache lock is not poisoned");
+
+        entries.retain(|key, _| !key.starts_with(prefix));
+
+        Ok(())
+    }
+}
+
+/// Keeps the entries in Redis, shared by every server instance, so that an invalidation by one
+/// applies to all.
+pub struct RedisCache {
+    connection: ConnectionManager,
+}
+
+impl RedisCache {
+    /// Prefix of the keys of the entries.
+    const KEY_PREFIX: &'static str = "forum:cache:";
+
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        let client = redis::Client::open(url)?;
+
+        Ok(RedisCache {
+            connection: ConnectionManager::new(client).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        Ok(self.connection.clone().get(format!("{}{}", Self::KEY_PREFIX, key)).await?)
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), CacheError> {
+        let seconds = ttl.as_secs().max(1);
+
+        self.connection
+            .clone()
+            .set_ex::<_, _, ()>(format!("{}{}", Self::KEY_PREFIX, key), value, seconds)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_prefix(&self, prefix: &str) -> Result<(), CacheError> {
+        let mut connection = self.connection.clone();
+        let mut keys: Vec<String> = Vec::new();
+
+        {
+            let mut scan = connection
+                .scan_match::<_, String>(format!("{}{}*", Self::KEY_PREFIX, prefix))
+                .await?;
+
+            while let Some(key) = scan.next_item().await {
+                keys.push(key);
+            }
+        }
+
+        if !keys.is_empty() {
+            connection.del::<_, ()>(keys).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `GET /questions` pages and questions as anonymous visitors see them, by tenant. Other viewers
+/// may see held or private posts, so their reads are never cached. The cache is best effort: when
+/// its store fails, reads go to the database.
+pub struct QuestionCache {
+    store: Arc<dyn Cache + Send + Sync>,
+    listing_ttl: Duration,
+    question_ttl: Duration,
+}
+
+impl QuestionCache {
+    pub fn new(store: Arc<dyn Cache + Send + Sync>, listing_ttl: Duration, question_ttl: Duration) -> Self {
+        QuestionCache {
+            store,
+            listing_ttl,
+            question_ttl,
+        }
+    }
+
+    fn tenant() -> String {
+        current_tenant().map(|tenant| tenant.to_string()).unwrap_or_default()
+    }
+
+    fn listing_key(language: Option<&str>) -> String {
+        format!("questions:{}:{}", Self::tenant(), language.unwrap_or_default())
+    }
+
+    /// The question comes first, so that its entries for every tenant can be dropped together.
+    fn question_key(question_uuid: &str) -> String {
+        format!("question:{}:{}", question_uuid, Self::tenant())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.store.get(key).await {
+            Ok(value) => value.and_then(|value| serde_json::from_str(&value).ok()),
+            Err(err) => {
+                warn!("Error to read {} from the cache: {}", key, err);
+                None
+            }
+        }
+    }
+
+    async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        let Ok(value) = serde_json::to_string(value) else {
+            return;
+        };
+
+        if let Err(err) = self.store.set(key, value, ttl).await {
+            warn!("Error to write {} to the cache: {}", key, err);
+        }
+    }
+
+    pub async fn questions(&self, language: Option<&str>) -> Option<Vec<QuestionDetail>> {
+        self.get(&Self::listing_key(language)).await
+    }
+
+    pub async fn set_questions(&self, language: Option<&str>, questions: &[QuestionDetail]) {
+        self.set(&Self::listing_key(language), &questions, self.listing_ttl).await
+    }
+
+    pub async fn question(&self, question_uuid: &str) -> Option<QuestionDetail> {
+        self.get(&Self::question_key(question_uuid)).await
+    }
+
+    pub async fn set_question(&self, question: &QuestionDetail) {
+        self.set(&Self::question_key(&question.question_uuid.to_string()), question, self.question_ttl)
+            .await
+    }
+
+    /// Drops the question, and every listing page since it may be on any of them, for every
+    /// tenant: the events it is called for do not carry theirs.
+    pub async fn invalidate(&self, question_uuid: &str) {
+        for prefix in [format!("question:{}:", question_uuid), "questions:".to_owned()] {
+            if let Err(err) = self.store.remove_prefix(&prefix).await {
+                error!("Error to invalidate {} in the cache: {}", prefix, err);
+            }
+        }
+    }
+
+    /// Drops every question and listing page, for every tenant, after changes to the visibility of
+    /// posts spread over many questions, such as shadow bans.
+    pub async fn invalidate_all(&self) {
+        for prefix in ["question:", "questions:"] {
+            if let Err(err) = self.store.remove_prefix(prefix).await {
+                error!("Error to invalidate {} in the cache: {}", prefix, err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{QuestionKind, QuestionStatus, Visibility};
+
+    #[test]
+    fn get_should_only_return_fresh_values() {
+        let cache = TtlCache::new(60, 1);
+
+        cache.insert("a".to_owned(), 1, 1000);
+        assert_eq!(cache.get("a", 1059), Some(1));
+        assert_eq!(cache.get("a", 1060), None);
+
+        cache.insert("b".to_owned(), 2, 1000);
+        assert_eq!(cache.get("a", 1000), None);
+        assert_eq!(cache.get("b", 1000), Some(2));
+    }
+
+    #[tokio::test]
+    async fn memory_cache_should_expire_entries() {
+        let cache = MemoryCache::new(10);
+
+        cache.set("a", "1".to_owned(), Duration::from_secs(60)).await.unwrap();
+        cache.set("b", "2".to_owned(), Duration::ZERO).await.unwrap();
+
+        assert_eq!(cache.get("a").await.unwrap(), Some("1".to_owned()));
+        assert_eq!(cache.get("b").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn memory_cache_should_remove_entries_by_prefix() {
+        let cache = MemoryCache::new(10);
+
+        cache.set("question:1:", "1".to_owned(), Duration::from_secs(60)).await.unwrap();
+        cache.set("question:2:", "2".to_owned(), Duration::from_secs(60)).await.unwrap();
+        cache.remove_prefix("question:1:").await.unwrap();
+
+        assert_eq!(cache.get("question:1:").await.unwrap(), None);
+        assert_eq!(cache.get("question:2:").await.unwrap(), Some("2".to_owned()));
+    }
+
+    fn question(question_uuid: u128) -> QuestionDetail {
+        QuestionDetail {
+            question_uuid: Uuid::from_u128(question_uuid),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            status: QuestionStatus::Open,
+            status_reason: None,
+            kind: QuestionKind::Question,
+            author_uuid: None,
+            visibility: Visibility::Public,
+            board_uuid: None,
+            tags: Vec::new(),
+            language: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+            description_html: None,
+            code_blocks: Vec::new(),
+            link_previews: Vec::new(),
+            held_for_review: false,
+            pending_tags: Vec::new(),
+            contest: None,
+        }
+    }
+
+    fn question_cache() -> QuestionCache {
+        QuestionCache::new(Arc::new(MemoryCache::new(10)), Duration::from_secs(60), Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn question_cache_should_keep_listings_by_language() {
+        let cache = question_cache();
+
+        cache.set_questions(Some("eng"), &[question(1)]).await;
+
+        assert_eq!(cache.questions(Some("eng")).await, Some(vec![question(1)]));
+        assert_eq!(cache.questions(None).await, None);
+    }
+
+    #[tokio::test]
+    async fn invalidate_should_drop_the_question_and_every_listing() {
+        let cache = question_cache();
+
+        cache.set_questions(None, &[question(1), question(2)]).await;
+        cache.set_questions(Some("eng"), &[question(2)]).await;
+        cache.set_question(&question(1)).await;
+        cache.set_question(&question(2)).await;
+
+        cache.invalidate(&Uuid::from_u128(1).to_string()).await;
+
+        assert_eq!(cache.questions(None).await, None);
+        assert_eq!(cache.questions(Some("eng")).await, None);
+        assert_eq!(cache.question(&Uuid::from_u128(1).to_string()).await, None);
+        assert_eq!(cache.question(&Uuid::from_u128(2).to_string()).await, Some(question(2)));
+    }
+
+    #[tokio::test]
+    async fn invalidate_all_should_drop_every_question_and_listing() {
+        let cache = question_cache();
+
+        cache.set_questions(None, &[question(1), question(2)]).await;
+        cache.set_question(&question(1)).await;
+        cache.set_question(&question(2)).await;
+
+        cache.invalidate_all().await;
+
+        assert_eq!(cache.questions(None).await, None);
+        assert_eq!(cache.question(&Uuid::from_u128(1).to_string()).await, None);
+        assert_eq!(cache.question(&Uuid::from_u128(2).to_string()).await, None);
+    }
+}
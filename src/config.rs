@@ -0,0 +1,407 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use figment::{
+    providers::{Data, Env, Format, Serialized, Toml},
+    Figment,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use thiserror::Error;
+
+use crate::{
+    models::{
+        AcceptSuggestionThresholds, NecroPostPolicy, NewTagPolicy, PostingQuotaPolicy, RetentionPolicy,
+        SimilarAnswerPolicy,
+    },
+    cors::CorsPolicy,
+    http_cache::CachePolicy,
+    persistance::{circuit_breaker::CircuitBreakerPolicy, retry::RetryPolicy, timing::TimingPolicy},
+    rate_limit::RateLimitRule,
+};
+
+/// Read when `CONFIG_FILE` is unset, if it exists.
+const DEFAULT_CONFIG_FILE: &str = "forum.toml";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Invalid settings: {0}")]
+    Invalid(String),
+}
+
+/// Settings of the server: the defaults, overridden by the TOML file at `CONFIG_FILE`, overridden
+/// by environment variables named after the fields in upper case, e.g. `BIND_PORT`.
+///
+/// Secrets such as `DATABASE_URL` are not settings; they come from the secrets provider, so they
+/// never sit in the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bind_host: IpAddr,
+    pub bind_port: u16,
+    /// Connections of the Postgres pool.
+    pub database_max_connections: u32,
+    /// Connections the pool keeps open even when idle.
+    pub database_min_connections: u32,
+    /// How long a request waits for a connection when all are in use, before failing with 503.
+    pub database_acquire_timeout_seconds: u64,
+    /// Idle connections above the minimum are closed after this long; `None` keeps them open.
+    pub database_idle_timeout_seconds: Option<u64>,
+    /// Connections are closed after this long, so that none outlives a failover; `None` keeps them.
+    pub database_max_lifetime_seconds: Option<u64>,
+    /// Tries of a DAO operation failing with transient errors, the first included.
+    pub database_retry_attempts: u32,
+    /// Backoff of the first retry, doubled for every retry after it up to the max, of which a
+    /// random part is waited.
+    pub database_retry_base_delay_millis: u64,
+    pub database_retry_max_delay_millis: u64,
+    /// Longest a statement may run before Postgres cancels it, except in streamed reads.
+    pub database_statement_timeout_millis: u64,
+    /// Database operations taking longer are logged as warnings.
+    pub slow_query_threshold_millis: u64,
+    /// Operations in a row failing to reach the database before the others fail fast with 503.
+    pub database_circuit_breaker_failures: u32,
+    /// How long operations fail fast before one is let through to try the database again.
+    pub database_circuit_breaker_open_seconds: u64,
+    pub multi_tenancy_enabled: bool,
+    pub profiling_enabled: bool,
+    pub legacy_body_routes: bool,
+    /// Sends JSON:API documents to clients whose `Accept` header does not list `application/json`,
+    /// not only to those listing `application/vnd.api+json`.
+    pub json_api_default: bool,
+    /// For links back to the forum.
+    pub forum_url: String,
+    pub long_poll_max_wait_seconds: u64,
+    /// How long anonymous visitors may be served a cached `GET /questions` page, or question.
+    pub questions_cache_ttl_seconds: u64,
+    pub question_cache_ttl_seconds: u64,
+    pub shutdown_grace_period_seconds: u64,
+    /// Largest request body, except on the upload routes, which have limits of their own.
+    pub request_body_limit_bytes: usize,
+    /// Longest a request may take, except for long polls and CPU profiles, which bound themselves.
+    pub request_timeout_seconds: u64,
+    /// Browser origins allowed to call the API cross-origin; none keeps it same-origin.
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
+    pub cors_max_age_seconds: u64,
+    /// Token bucket of each client IP, shared by the routes without a rule of their own.
+    pub rate_limit: RateLimitRule,
+    /// Rules of their own by method and route, e.g. `POST /api/v1/question`.
+    pub rate_limit_routes: HashMap<String, RateLimitRule>,
+    /// How long browsers and CDNs may cache the public read routes, by method and route, e.g.
+    /// `GET /api/v1/questions`. Routes left out get no `Cache-Control` header.
+    pub cache_control_routes: HashMap<String, CachePolicy>,
+    pub scim_group_roles: String,
+    pub digest_webhook_url: Option<String>,
+    pub digest_webhook_interval_seconds: u64,
+    pub slo_alert_webhook_url: Option<String>,
+    pub necro_post_warn_after_days: i64,
+    pub necro_post_review: bool,
+    pub similar_answer_threshold: f64,
+    pub similar_answer_review: bool,
+    pub new_tag_min_reputation: i64,
+    pub posting_quota_questions_per_hour: i64,
+    pub posting_quota_answers_per_hour: i64,
+    pub posting_quota_reputation_per_extra_post: i64,
+    pub backup_retention_days: Option<i32>,
+    pub audit_log_retention_days: Option<i32>,
+    pub ip_address_retention_days: Option<i32>,
+    pub soft_delete_retention_days: i32,
+    pub accept_suggestion_min_age_days: i32,
+    pub accept_suggestion_min_score: i64,
+    pub accept_suggestion_min_lead: i64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let necro_post = NecroPostPolicy::default();
+        let similar_answer = SimilarAnswerPolicy::default();
+        let retention = RetentionPolicy::default();
+        let accept_suggestion = AcceptSuggestionThresholds::default();
+        let posting_quota = PostingQuotaPolicy::default();
+        let cors = CorsPolicy::default();
+        let database_retry = RetryPolicy::default();
+        let database_circuit_breaker = CircuitBreakerPolicy::default();
+        let database_timing = TimingPolicy::default();
+
+        Settings {
+            bind_host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            bind_port: 8000,
+            database_max_connections: 5,
+            database_min_connections: 0,
+            database_acquire_timeout_seconds: 30,
+            database_idle_timeout_seconds: Some(10 * 60),
+            database_max_lifetime_seconds: Some(30 * 60),
+            database_retry_attempts: database_retry.max_attempts,
+            database_retry_base_delay_millis: database_retry.base_delay.as_millis() as u64,
+            database_retry_max_delay_millis: database_retry.max_delay.as_millis() as u64,
+            database_statement_timeout_millis: database_timing.statement_timeout.as_millis() as u64,
+            slow_query_threshold_millis: database_timing.slow_query_threshold.as_millis() as u64,
+            database_circuit_breaker_failures: database_circuit_breaker.failure_threshold,
+            database_circuit_breaker_open_seconds: database_circuit_breaker.open_for.as_secs(),
+            multi_tenancy_enabled: false,
+            profiling_enabled: false,
+            legacy_body_routes: false,
+            json_api_default: false,
+            forum_url: "http://127.0.0.1:8000".to_owned(),
+            long_poll_max_wait_seconds: 30,
+            questions_cache_ttl_seconds: 10,
+            question_cache_ttl_seconds: 30,
+            shutdown_grace_period_seconds: 30,
+            request_body_limit_bytes: 2 * 1024 * 1024,
+            request_timeout_seconds: 30,
+            cors_allowed_origins: cors.allowed_origins,
+            cors_allowed_methods: cors.allowed_methods,
+            cors_allowed_headers: cors.allowed_headers,
+            cors_max_age_seconds: cors.max_age.as_secs(),
+            rate_limit: RateLimitRule { burst: 120, per_minute: 600 },
+            rate_limit_routes: HashMap::from([
+                ("POST /api/v1/question".to_owned(), RateLimitRule { burst: 5, per_minute: 2 }),
+                ("POST /api/v1/answer".to_owned(), RateLimitRule { burst: 10, per_minute: 6 }),
+            ]),
+            cache_control_routes: HashMap::from([
+                ("GET /api/v1/questions".to_owned(), cache_policy(10, 30)),
+                ("GET /api/v1/question/:uuid".to_owned(), cache_policy(30, 60)),
+                ("GET /api/v1/question/:uuid/full".to_owned(), cache_policy(10, 30)),
+                ("GET /api/v1/faq".to_owned(), cache_policy(5 * 60, 60)),
+                ("GET /api/v1/tags/suggest".to_owned(), cache_policy(60, 60)),
+                ("GET /api/v1/tags/:name/stats".to_owned(), cache_policy(5 * 60, 60)),
+                // Feed readers poll often; five minutes spares the database without delaying new questions much.
+                ("GET /api/v1/feeds/questions.atom".to_owned(), cache_policy(5 * 60, 0)),
+                ("GET /api/v1/feeds/tag/:file".to_owned(), cache_policy(5 * 60, 0)),
+                // Crawlers fetch sitemaps daily at most, and counting millions of questions is not free.
+                ("GET /api/v1/sitemap.xml".to_owned(), cache_policy(60 * 60, 0)),
+                ("GET /api/v1/sitemaps/:file".to_owned(), cache_policy(60 * 60, 0)),
+            ]),
+            scim_group_roles: String::new(),
+            digest_webhook_url: None,
+            digest_webhook_interval_seconds: 300,
+            slo_alert_webhook_url: None,
+            necro_post_warn_after_days: necro_post.warn_after_days,
+            necro_post_review: necro_post.review,
+            similar_answer_threshold: similar_answer.threshold,
+            similar_answer_review: similar_answer.review,
+            new_tag_min_reputation: NewTagPolicy::default().min_reputation,
+            posting_quota_questions_per_hour: posting_quota.questions_per_hour,
+            posting_quota_answers_per_hour: posting_quota.answers_per_hour,
+            posting_quota_reputation_per_extra_post: posting_quota.reputation_per_extra_post,
+            backup_retention_days: None,
+            audit_log_retention_days: retention.audit_log_days,
+            ip_address_retention_days: retention.ip_address_days,
+            soft_delete_retention_days: retention.deleted_post_days,
+            accept_suggestion_min_age_days: accept_suggestion.min_age_days,
+            accept_suggestion_min_score: accept_suggestion.min_score,
+            accept_suggestion_min_lead: accept_suggestion.min_lead,
+        }
+    }
+}
+
+fn cache_policy(max_age_seconds: u64, stale_while_revalidate_seconds: u64) -> CachePolicy {
+    CachePolicy {
+        max_age_seconds,
+        shared_max_age_seconds: None,
+        stale_while_revalidate_seconds,
+    }
+}
+
+impl Settings {
+    /// Fails on a file or variable that does not parse, rather than falling back to the default.
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = std::env::var("CONFIG_FILE").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_owned());
+
+        Self::extract(Toml::file(file), Env::raw())
+    }
+
+    fn extract(file: Data<Toml>, env: Env) -> Result<Self, ConfigError> {
+        Figment::from(Serialized::defaults(Settings::default()))
+            .merge(file)
+            .merge(env)
+            .extract()
+            .map_err(|err| ConfigError::Invalid(err.to_string()))
+    }
+
+    pub fn bind_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_host, self.bind_port)
+    }
+
+    pub fn long_poll_max_wait(&self) -> Duration {
+        Duration::from_secs(self.long_poll_max_wait_seconds)
+    }
+
+    pub fn questions_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.questions_cache_ttl_seconds)
+    }
+
+    pub fn question_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.question_cache_ttl_seconds)
+    }
+
+    pub fn shutdown_grace_period(&self) -> Duration {
+        Duration::from_secs(self.shutdown_grace_period_seconds)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_seconds)
+    }
+
+    pub fn database_pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.database_max_connections)
+            .min_connections(self.database_min_connections)
+            .acquire_timeout(Duration::from_secs(self.database_acquire_timeout_seconds))
+            .idle_timeout(self.database_idle_timeout_seconds.map(Duration::from_secs))
+            .max_lifetime(self.database_max_lifetime_seconds.map(Duration::from_secs))
+    }
+
+    pub fn database_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.database_retry_attempts.max(1),
+            base_delay: Duration::from_millis(self.database_retry_base_delay_millis),
+            max_delay: Duration::from_millis(self.database_retry_max_delay_millis),
+        }
+    }
+
+    pub fn database_timing_policy(&self) -> TimingPolicy {
+        TimingPolicy {
+            statement_timeout: Duration::from_millis(self.database_statement_timeout_millis),
+            slow_query_threshold: Duration::from_millis(self.slow_query_threshold_millis),
+        }
+    }
+
+    pub fn database_circuit_breaker_policy(&self) -> CircuitBreakerPolicy {
+        CircuitBreakerPolicy {
+            failure_threshold: self.database_circuit_breaker_failures.max(1),
+            open_for: Duration::from_secs(self.database_circuit_breaker_open_seconds),
+        }
+    }
+
+    pub fn cors_policy(&self) -> CorsPolicy {
+        CorsPolicy {
+            allowed_origins: self.cors_allowed_origins.clone(),
+            allowed_methods: self.cors_allowed_methods.clone(),
+            allowed_headers: self.cors_allowed_headers.clone(),
+            max_age: Duration::from_secs(self.cors_max_age_seconds),
+        }
+    }
+
+    pub fn necro_post_policy(&self) -> NecroPostPolicy {
+        NecroPostPolicy {
+            warn_after_days: self.necro_post_warn_after_days,
+            review: self.necro_post_review,
+        }
+    }
+
+    pub fn similar_answer_policy(&self) -> SimilarAnswerPolicy {
+        SimilarAnswerPolicy {
+            threshold: self.similar_answer_threshold,
+            review: self.similar_answer_review,
+        }
+    }
+
+    pub fn new_tag_policy(&self) -> NewTagPolicy {
+        NewTagPolicy {
+            min_reputation: self.new_tag_min_reputation,
+        }
+    }
+
+    pub fn posting_quota_policy(&self) -> PostingQuotaPolicy {
+        PostingQuotaPolicy {
+            questions_per_hour: self.posting_quota_questions_per_hour,
+            answers_per_hour: self.posting_quota_answers_per_hour,
+            reputation_per_extra_post: self.posting_quota_reputation_per_extra_post,
+        }
+    }
+
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            audit_log_days: self.audit_log_retention_days,
+            ip_address_days: self.ip_address_retention_days,
+            deleted_post_days: self.soft_delete_retention_days,
+        }
+    }
+
+    pub fn accept_suggestion_thresholds(&self) -> AcceptSuggestionThresholds {
+        AcceptSuggestionThresholds {
+            min_age_days: self.accept_suggestion_min_age_days,
+            min_score: self.accept_suggestion_min_score,
+            min_lead: self.accept_suggestion_min_lead,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// No variable of the test environment applies.
+    fn no_env() -> Env {
+        Env::raw().only(&[])
+    }
+
+    #[test]
+    fn extract_should_fall_back_to_the_defaults() {
+        let settings = Settings::extract(Toml::file("does-not-exist.toml"), no_env()).unwrap();
+
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.bind_address(), "127.0.0.1:8000".parse().unwrap());
+        assert_eq!(settings.retention_policy(), RetentionPolicy::default());
+        assert_eq!(settings.database_retry_policy(), RetryPolicy::default());
+        assert_eq!(settings.database_circuit_breaker_policy(), CircuitBreakerPolicy::default());
+        assert_eq!(settings.database_timing_policy(), TimingPolicy::default());
+    }
+
+    #[test]
+    fn extract_should_read_the_file() {
+        let file = Toml::string(
+            r#"
+                bind_host = "0.0.0.0"
+                bind_port = 9000
+                legacy_body_routes = true
+                audit_log_retention_days = 365
+                database_min_connections = 2
+                database_idle_timeout_seconds = 60
+                cors_allowed_origins = ["https://forum.example.com"]
+
+                [rate_limit_routes."GET /api/v1/questions"]
+                burst = 10
+                per_minute = 30
+
+                [cache_control_routes."GET /api/v1/questions"]
+                max_age_seconds = 60
+                shared_max_age_seconds = 300
+            "#,
+        );
+
+        let settings = Settings::extract(file, no_env()).unwrap();
+
+        assert_eq!(settings.bind_address(), "0.0.0.0:9000".parse().unwrap());
+        assert!(settings.legacy_body_routes);
+        assert_eq!(settings.retention_policy().audit_log_days, Some(365));
+        assert_eq!(settings.database_max_connections, 5);
+        assert_eq!(settings.database_pool_options().get_acquire_timeout(), Duration::from_secs(30));
+        assert_eq!(settings.database_pool_options().get_min_connections(), 2);
+        assert_eq!(settings.database_pool_options().get_idle_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(settings.cors_policy().allowed_origins, vec!["https://forum.example.com".to_owned()]);
+        assert_eq!(settings.rate_limit_routes.len(), 3);
+        assert_eq!(settings.rate_limit_routes["GET /api/v1/questions"], RateLimitRule { burst: 10, per_minute: 30 });
+        assert_eq!(
+            settings.cache_control_routes["GET /api/v1/questions"],
+            CachePolicy {
+                max_age_seconds: 60,
+                shared_max_age_seconds: Some(300),
+                stale_while_revalidate_seconds: 30,
+            }
+        );
+    }
+
+    #[test]
+    fn extract_should_reject_invalid_values() {
+        let file = Toml::string("bind_port = \"eighty\"");
+
+        assert!(Settings::extract(file, no_env()).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use regex::Regex;
+use thiserror::Error;
+
+use crate::models::ContentPolicyViolation;
+
+#[derive(Error, Debug)]
+pub enum ContentPolicyError {
+    #[error("Invalid content policy configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Failed to read content policy blocklist: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid content policy pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentPolicyMode {
+    /// Posts containing blocked terms are refused with the offending terms.
+    #[default]
+    Reject,
+    /// Blocked terms are replaced with asterisks and the post goes through.
+    Mask,
+}
+
+/// Blocked words and patterns for question and answer text. Empty, and so allowing everything,
+/// unless `CONTENT_POLICY_FILE` is configured.
+#[derive(Debug, Clone, Default)]
+pub struct ContentPolicy {
+    rules: Vec<Regex>,
+    mode: ContentPolicyMode,
+}
+
+impl ContentPolicy {
+    /// Loads the blocklist at `CONTENT_POLICY_FILE`, enforced as selected by `CONTENT_POLICY_MODE`:
+    /// `reject` (the default) or `mask`.
+    pub fn from_env() -> Result<Self, ContentPolicyError> {
+        let mode = match std::env::var("CONTENT_POLICY_MODE").as_deref() {
+            Err(_) | Ok("reject") => ContentPolicyMode::Reject,
+            Ok("mask") => ContentPolicyMode::Mask,
+            Ok(other) => {
+                return Err(ContentPolicyError::InvalidConfig(format!("unknown CONTENT_POLICY_MODE {}", other)))
+            }
+        };
+
+        match std::env::var("CONTENT_POLICY_FILE") {
+            Ok(path) => ContentPolicy::parse(&std::fs::read_to_string(path)?, mode),
+            Err(_) => Ok(ContentPolicy { rules: Vec::new(), mode }),
+        }
+    }
+
+    /// One rule per line. Plain lines are words or phrases, matched as whole words ignoring case;
+    /// lines starting with `re:` are regular expressions. Blank lines and `#` comments are skipped.
+    pub fn parse(blocklist: &str, mode: ContentPolicyMode) -> Result<Self, ContentPolicyError> {
+        let rules = blocklist
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.strip_prefix("re:") {
+                Some(pattern) => Regex::new(pattern.trim()),
+                None => Regex::new(&format!(r"(?i)\b{}\b", regex::escape(line))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ContentPolicy { rules, mode })
+    }
+
+    /// Checks the text fields of one post together, so a rejection lists the terms from all of them.
+    /// Returns the fields unchanged, or masked in `Mask` mode.
+    pub fn apply<const N: usize>(&self, fields: [String; N]) -> Result<[String; N], ContentPolicyViolation> {
+        if self.mode == ContentPolicyMode::Mask {
+            return Ok(fields.map(|field| self.mask(field)));
+        }
+
+        let mut terms: Vec<String> = Vec::new();
+
+        for field in &fields {
+            for rule in &self.rules {
+                for found in rule.find_iter(field) {
+                    let term = found.as_str().to_lowercase();
+
+                    if !terms.contains(&term) {
+                        terms.push(term);
+                    }
+                }
+            }
+        }
+
+        if terms.is_empty() {
+            Ok(fields)
+        } else {
+            Err(ContentPolicyViolation {
+                message: "The post contains terms that are not allowed on this forum.".to_owned(),
+                terms,
+            })
+        }
+    }
+
+    fn mask(&self, field: String) -> String {
+        self.rules.iter().fold(field, |field, rule| {
+            rule.replace_all(&field, |found: &regex::Captures| "*".repeat(found[0].chars().count()))
+                .into_owned()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCKLIST: &str = "# Slurs and scams\ncrap\n\nfree money\nre:(?i)v[i1]agra";
+
+    #[test]
+    fn should_allow_posts_without_blocked_terms() {
+        let policy = ContentPolicy::parse(BLOCKLIST, ContentPolicyMode::Reject).unwrap();
+        let fields = ["Scrappy borrow checker".to_owned(), "Why does this not compile?".to_owned()];
+
+        assert_eq!(policy.apply(fields.clone()).unwrap(), fields);
+    }
+
+    #[test]
+    fn should_reject_blocked_terms_across_fields() {
+        let policy = ContentPolicy::parse(BLOCKLIST, ContentPolicyMode::Reject).unwrap();
+        let fields = ["Crap, FREE MONEY".to_owned(), "Buy v1agra, free money and crap".to_owned()];
+
+        assert_eq!(
+            policy.apply(fields).unwrap_err().terms,
+            vec!["crap".to_owned(), "free money".to_owned(), "v1agra".to_owned()]
+        );
+    }
+
+    #[test]
+    fn should_mask_blocked_terms() {
+        let policy = ContentPolicy::parse(BLOCKLIST, ContentPolicyMode::Mask).unwrap();
+
+        assert_eq!(
+            policy.apply(["This crap is free money".to_owned()]).unwrap(),
+            ["This **** is **********".to_owned()]
+        );
+    }
+
+    #[test]
+    fn should_refuse_invalid_patterns() {
+        assert!(matches!(
+            ContentPolicy::parse("re:(unclosed", ContentPolicyMode::Reject),
+            Err(ContentPolicyError::InvalidPattern(_))
+        ));
+    }
+}
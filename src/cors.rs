@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{config::ConfigError, problem::REQUEST_ID_HEADER};
+
+/// Which browser origins may call the API, and with which methods and request headers.
+///
+/// Clients authenticate with the `Authorization` header rather than cookies, so credentials are
+/// never allowed: a page can only act for a user whose token it holds already.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsPolicy {
+    /// Origins such as `https://forum.example.com`, or `*` for any.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// How long browsers may cache a preflight response.
+    pub max_age: Duration,
+}
+
+impl CorsPolicy {
+    /// None without allowed origins, so that the API stays same-origin. Preflight requests are
+    /// answered by the layer itself, before routing, so they need no `OPTIONS` routes.
+    pub fn layer(&self) -> Result<Option<CorsLayer>, ConfigError> {
+        if self.allowed_origins.is_empty() {
+            return Ok(None);
+        }
+
+        let origins = if self.allowed_origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin.trim_end_matches('/'))
+                        .map_err(|_| ConfigError::Invalid(format!("Invalid CORS origin: {}", origin)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        };
+
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_uppercase().as_bytes())
+                    .map_err(|_| ConfigError::Invalid(format!("Invalid CORS method: {}", method)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let headers = self
+            .allowed_headers
+            .iter()
+            .map(|name| {
+                HeaderName::from_bytes(name.to_lowercase().as_bytes())
+                    .map_err(|_| ConfigError::Invalid(format!("Invalid CORS header: {}", name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(
+            CorsLayer::new()
+                .allow_origin(origins)
+                .allow_methods(methods)
+                .allow_headers(headers)
+                .expose_headers([
+                    HeaderName::from_static(REQUEST_ID_HEADER),
+                    header::RETRY_AFTER,
+                    header::LOCATION,
+                    header::ETAG,
+                ])
+                .max_age(self.max_age),
+        ))
+    }
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        CorsPolicy {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"].map(str::to_owned).to_vec(),
+            allowed_headers: ["authorization", "content-type", "if-none-match", "x-request-id"]
+                .map(str::to_owned)
+                .to_vec(),
+            max_age: Duration::from_secs(600),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        routing::delete,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(policy: &CorsPolicy) -> Router {
+        Router::new()
+            .route("/answer", delete(|| async { StatusCode::OK }))
+            .layer(policy.layer().unwrap().unwrap())
+    }
+
+    #[tokio::test]
+    async fn layer_should_answer_preflights_for_json_deletes_from_allowed_origins() {
+        let policy = CorsPolicy {
+            allowed_origins: vec!["https://forum.example.com/".to_owned()],
+            ..CorsPolicy::default()
+        };
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/answer")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type,authorization")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app(&policy).oneshot(preflight("https://forum.example.com")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://forum.example.com");
+        assert!(response.headers()[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap().contains("DELETE"));
+        assert!(response.headers()[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap().contains("content-type"));
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+        let response = app(&policy).oneshot(preflight("https://evil.example.com")).await.unwrap();
+
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[test]
+    fn layer_should_be_off_without_origins_and_reject_invalid_methods() {
+        assert!(CorsPolicy::default().layer().unwrap().is_none());
+
+        let policy = CorsPolicy {
+            allowed_origins: vec!["*".to_owned()],
+            allowed_methods: vec!["NOT A METHOD".to_owned()],
+            ..CorsPolicy::default()
+        };
+
+        assert!(policy.layer().is_err());
+    }
+}
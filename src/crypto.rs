@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use thiserror::Error;
+
+const KEY_ID_LEN: usize = 4;
+const NONCE_LEN: usize = 12;
+
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("Invalid encryption key configuration: {0}")]
+    InvalidKeys(String),
+    #[error("Unknown encryption key id: {0}")]
+    UnknownKey(u32),
+    #[error("Malformed ciphertext")]
+    Malformed,
+    #[error("Encryption or decryption failed")]
+    Failed,
+}
+
+/// Source of the data keys used for field encryption. Implement this to fetch
+/// keys from a KMS; `StaticKeyProvider` reads them from configuration.
+pub trait KeyProvider: Send + Sync {
+    /// Id of the key new values are encrypted with.
+    fn current_key_id(&self) -> u32;
+    fn key(&self, key_id: u32) -> Option<Key<Aes256Gcm>>;
+}
+
+pub struct StaticKeyProvider {
+    keys: BTreeMap<u32, Key<Aes256Gcm>>,
+}
+
+impl StaticKeyProvider {
+    /// Parses `id:base64key` pairs separated by commas, e.g. `1:...,2:...`.
+    /// The highest id is the current key; older ones are kept for decryption.
+    pub fn parse(value: &str) -> Result<Self, CryptoError> {
+        let mut keys = BTreeMap::new();
+
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (id, key) = entry
+                .split_once(':')
+                .ok_or_else(|| CryptoError::InvalidKeys(format!("expected id:key, got {}", entry)))?;
+            let id: u32 = id
+                .parse()
+                .map_err(|_| CryptoError::InvalidKeys(format!("invalid key id {}", id)))?;
+            let key = STANDARD
+                .decode(key)
+                .map_err(|err| CryptoError::InvalidKeys(err.to_string()))?;
+
+            if key.len() != 32 {
+                return Err(CryptoError::InvalidKeys(format!("key {} must be 32 bytes", id)));
+            }
+
+            keys.insert(id, *Key::<Aes256Gcm>::from_slice(&key));
+        }
+
+        if keys.is_empty() {
+            return Err(CryptoError::InvalidKeys("no keys configured".to_owned()));
+        }
+
+        Ok(StaticKeyProvider { keys })
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> u32 {
+        *self.keys.keys().next_back().expect("at least one key is configured")
+    }
+
+    fn key(&self, key_id: u32) -> Option<Key<Aes256Gcm>> {
+        self.keys.get(&key_id).copied()
+    }
+}
+
+/// Encrypts individual column values with AES-256-GCM.
+///
+/// Ciphertexts are laid out as `key id (u32, big endian) | nonce | sealed data`
+/// so values encrypted under a retired key can still be read and rotated.
+#[derive(Clone)]
+pub struct FieldCipher {
+    keys: Arc<dyn KeyProvider>,
+}
+
+impl FieldCipher {
+    pub fn new(keys: Arc<dyn KeyProvider>) -> Self {
+        FieldCipher { keys }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<Vec<u8>, CryptoError> {
+        let key_id = self.keys.current_key_id();
+        let key = self.keys.key(key_id).ok_or(CryptoError::UnknownKey(key_id))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let sealed = Aes256Gcm::new(&key)
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| CryptoError::Failed)?;
+
+        let mut ciphertext = Vec::with_capacity(KEY_ID_LEN + NONCE_LEN + sealed.len());
+        ciphertext.extend_from_slice(&key_id.to_be_bytes());
+        ciphertext.extend_from_slice(&nonce);
+        ciphertext.extend_from_slice(&sealed);
+
+        Ok(ciphertext)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<String, CryptoError> {
+        let key_id = key_id_of(ciphertext)?;
+        let key = self.keys.key(key_id).ok_or(CryptoError::UnknownKey(key_id))?;
+        let nonce = Nonce::from_slice(&ciphertext[KEY_ID_LEN..KEY_ID_LEN + NONCE_LEN]);
+
+        let plaintext = Aes256Gcm::new(&key)
+            .decrypt(nonce, &ciphertext[KEY_ID_LEN + NONCE_LEN..])
+            .map_err(|_| CryptoError::Failed)?;
+
+        String::from_utf8(plaintext).map_err(|_| CryptoError::Malformed)
+    }
+
+    /// Whether the value was encrypted with a key other than the current one.
+    pub fn needs_rotation(&self, ciphertext: &[u8]) -> Result<bool, CryptoError> {
+        Ok(key_id_of(ciphertext)? != self.keys.current_key_id())
+    }
+
+    /// Re-encrypts the value under the current key.
+    pub fn rotate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        self.encrypt(&self.decrypt(ciphertext)?)
+    }
+}
+
+fn key_id_of(ciphertext: &[u8]) -> Result<u32, CryptoError> {
+    if ciphertext.len() < KEY_ID_LEN + NONCE_LEN {
+        return Err(CryptoError::Malformed);
+    }
+
+    let mut key_id = [0u8; KEY_ID_LEN];
+    key_id.copy_from_slice(&ciphertext[..KEY_ID_LEN]);
+
+    Ok(u32::from_be_bytes(key_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_1: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+    const KEY_2: &str = "ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
+
+    fn cipher(keys: &str) -> FieldCipher {
+        FieldCipher::new(Arc::new(StaticKeyProvider::parse(keys).unwrap()))
+    }
+
+    #[test]
+    fn encrypt_should_round_trip() {
+        let cipher = cipher(&format!("1:{}", KEY_1));
+
+        let ciphertext = cipher.encrypt("ferris@example.com").unwrap();
+
+        assert_ne!(&ciphertext[KEY_ID_LEN + NONCE_LEN..], b"ferris@example.com");
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), "ferris@example.com");
+    }
+
+    #[test]
+    fn rotate_should_move_values_to_the_current_key() {
+        let old = cipher(&format!("1:{}", KEY_1));
+        let new = cipher(&format!("1:{},2:{}", KEY_1, KEY_2));
+
+        let ciphertext = old.encrypt("127.0.0.1").unwrap();
+
+        assert!(new.needs_rotation(&ciphertext).unwrap());
+
+        let rotated = new.rotate(&ciphertext).unwrap();
+
+        assert!(!new.needs_rotation(&rotated).unwrap());
+        assert_eq!(new.decrypt(&rotated).unwrap(), "127.0.0.1");
+        assert!(old.decrypt(&rotated).is_err());
+    }
+
+    #[test]
+    fn parse_should_reject_short_keys() {
+        assert!(StaticKeyProvider::parse("1:c2hvcnQ=").is_err());
+    }
+}
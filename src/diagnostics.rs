@@ -0,0 +1,223 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use pprof::{protos::Message, ProfilerGuardBuilder};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::runtime::Handle;
+
+use crate::models::{
+    AllocatorDiagnostics, Diagnostics, PoolDiagnostics, ProcessDiagnostics, ProfileFormat, RuntimeDiagnostics,
+};
+
+#[derive(Error, Debug)]
+pub enum DiagnosticsError {
+    #[error("CPU profiling is disabled")]
+    Disabled,
+    #[error("A CPU profile is already being captured")]
+    Busy,
+    #[error("Failed to capture CPU profile: {0}")]
+    Profiler(#[from] pprof::Error),
+    #[error("Failed to encode CPU profile: {0}")]
+    Encode(String),
+}
+
+/// Longest CPU profile a single request may capture.
+pub const MAX_PROFILE_SECONDS: u64 = 60;
+
+/// Samples per second; off from 100 so that sampling does not run in lockstep with timers.
+const PROFILE_FREQUENCY_HZ: i32 = 99;
+
+/// Frames of these libraries are left out of profiles, as unwinding through them can crash.
+const PROFILE_BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Only one profiler can sample the process at a time.
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+/// The system allocator, counting live bytes and calls for `GET /admin/diagnostics`. Installed as
+/// the global allocator in `main`; counters are relaxed, so a snapshot may be off by in-flight calls.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+
+        ALLOCATED_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+
+        if !new_ptr.is_null() {
+            ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        }
+
+        new_ptr
+    }
+}
+
+fn record_allocation(size: usize) {
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Reads the internals of this server instance: its tokio runtime, allocator, process memory and
+/// connection pool.
+pub struct DiagnosticsProbe {
+    pool: PgPool,
+    profiling_enabled: bool,
+}
+
+impl DiagnosticsProbe {
+    pub fn new(pool: PgPool, profiling_enabled: bool) -> Self {
+        DiagnosticsProbe { pool, profiling_enabled }
+    }
+
+    /// Must be called from within the tokio runtime.
+    pub fn snapshot(&self) -> Diagnostics {
+        let metrics = Handle::current().metrics();
+        let memory = memory_stats::memory_stats();
+
+        Diagnostics {
+            runtime: RuntimeDiagnostics {
+                workers: metrics.num_workers(),
+                alive_tasks: metrics.num_alive_tasks(),
+                global_queue_depth: metrics.global_queue_depth(),
+            },
+            allocator: AllocatorDiagnostics {
+                allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+                allocations: ALLOCATIONS.load(Ordering::Relaxed),
+                deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            },
+            process: ProcessDiagnostics {
+                resident_bytes: memory.map(|memory| memory.physical_mem as u64),
+                virtual_bytes: memory.map(|memory| memory.virtual_mem as u64),
+            },
+            db_pool: self.pool(),
+            profiling_enabled: self.profiling_enabled,
+        }
+    }
+
+    /// The connection pool alone, also for `GET /metrics`.
+    pub fn pool(&self) -> PoolDiagnostics {
+        let options = self.pool.options();
+
+        PoolDiagnostics {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            min_connections: options.get_min_connections(),
+            max_connections: options.get_max_connections(),
+            acquire_timeout_ms: options.get_acquire_timeout().as_millis() as u64,
+            idle_timeout_ms: options.get_idle_timeout().map(|timeout| timeout.as_millis() as u64),
+            max_lifetime_ms: options.get_max_lifetime().map(|lifetime| lifetime.as_millis() as u64),
+            closed: self.pool.is_closed(),
+        }
+    }
+
+    /// Samples the CPU of every thread for `duration`, on a blocking thread so that the runtime
+    /// keeps serving the requests being profiled.
+    pub async fn profile(&self, duration: Duration, format: ProfileFormat) -> Result<Vec<u8>, DiagnosticsError> {
+        if !self.profiling_enabled {
+            return Err(DiagnosticsError::Disabled);
+        }
+
+        if PROFILING.swap(true, Ordering::AcqRel) {
+            return Err(DiagnosticsError::Busy);
+        }
+
+        let profile = tokio::task::spawn_blocking(move || capture_profile(duration, format))
+            .await
+            .map_err(|err| DiagnosticsError::Encode(err.to_string()));
+
+        PROFILING.store(false, Ordering::Release);
+
+        profile?
+    }
+}
+
+fn capture_profile(duration: Duration, format: ProfileFormat) -> Result<Vec<u8>, DiagnosticsError> {
+    let guard = ProfilerGuardBuilder::default()
+        .frequency(PROFILE_FREQUENCY_HZ)
+        .blocklist(&PROFILE_BLOCKLIST)
+        .build()?;
+
+    std::thread::sleep(duration);
+
+    let report = guard.report().build()?;
+
+    match format {
+        ProfileFormat::Flamegraph => {
+            let mut svg = Vec::new();
+            report.flamegraph(&mut svg)?;
+            Ok(svg)
+        }
+        ProfileFormat::Pprof => report
+            .pprof()?
+            .write_to_bytes()
+            .map_err(|err| DiagnosticsError::Encode(err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+
+    fn probe(profiling_enabled: bool) -> DiagnosticsProbe {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_lazy("postgres://localhost/forum")
+            .unwrap();
+
+        DiagnosticsProbe::new(pool, profiling_enabled)
+    }
+
+    #[tokio::test]
+    async fn snapshot_should_report_pool_settings_and_counted_allocations() {
+        let buffer = vec![0u8; 4096];
+        let diagnostics = probe(false).snapshot();
+
+        assert!(diagnostics.allocator.allocations > 0);
+        assert!(diagnostics.allocator.allocated_bytes >= buffer.len() as u64);
+        assert!(diagnostics.runtime.workers >= 1);
+        assert_eq!(diagnostics.db_pool.size, 0);
+        assert_eq!(diagnostics.db_pool.max_connections, 5);
+        assert!(!diagnostics.profiling_enabled);
+    }
+
+    #[tokio::test]
+    async fn profile_should_be_refused_unless_enabled() {
+        let result = probe(false).profile(Duration::from_secs(1), ProfileFormat::Flamegraph).await;
+
+        assert!(matches!(result, Err(DiagnosticsError::Disabled)));
+    }
+}
@@ -0,0 +1,86 @@
+use sha2::{Digest, Sha256};
+
+use crate::models::{BodyFormat, QuestionDetail};
+
+/// Weak ETag of `questions` rendered in `format`, from the id and `updated_at` of each question, so
+/// that it changes when a question is edited, closed or tagged, or when one enters or leaves a
+/// list. Link previews are fetched after a post is saved, so their count is part of it too.
+///
+/// Weak, since the same questions may be sent with other encodings or whitespace.
+pub fn questions_etag(questions: &[QuestionDetail], format: BodyFormat) -> String {
+    let mut hasher = Sha256::new();
+
+    hasher.update(format!("{:?}", format));
+
+    for question in questions {
+        hasher.update(question.question_uuid.as_bytes());
+        hasher.update(question.updated_at.unix_timestamp_nanos().to_be_bytes());
+        hasher.update((question.link_previews.len() as u64).to_be_bytes());
+    }
+
+    format!("W/\"{}\"", hex::encode(&hasher.finalize()[..16]))
+}
+
+/// Whether `if_none_match`, the value of an `If-None-Match` header, lists `etag` or is `*`. Tags
+/// are compared weakly, ignoring their `W/` prefix.
+pub fn if_none_match(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    let etag = opaque(etag);
+
+    if_none_match.split(',').any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::QuestionStatus;
+
+    fn question(updated_at: time::OffsetDateTime) -> QuestionDetail {
+        QuestionDetail {
+            question_uuid: Uuid::nil(),
+            title: "title".to_owned(),
+            description: "description".to_owned(),
+            status: QuestionStatus::Open,
+            status_reason: None,
+            kind: Default::default(),
+            contest: None,
+            author_uuid: None,
+            visibility: Default::default(),
+            board_uuid: None,
+            tags: Vec::new(),
+            language: None,
+            created_at: datetime!(2026-10-17 10:00:00.0 UTC),
+            updated_at,
+            description_html: None,
+            code_blocks: Vec::new(),
+            link_previews: Vec::new(),
+            held_for_review: false,
+            pending_tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn questions_etag_should_change_with_updates_format_and_membership() {
+        let original = question(datetime!(2026-10-17 10:00:00.0 UTC));
+        let edited = question(datetime!(2026-10-17 11:00:00.0 UTC));
+        let etag = questions_etag(std::slice::from_ref(&original), BodyFormat::Html);
+
+        assert!(etag.starts_with("W/\""));
+        assert_eq!(questions_etag(std::slice::from_ref(&original), BodyFormat::Html), etag);
+        assert_ne!(questions_etag(&[edited], BodyFormat::Html), etag);
+        assert_ne!(questions_etag(std::slice::from_ref(&original), BodyFormat::Raw), etag);
+        assert_ne!(questions_etag(&[], BodyFormat::Html), etag);
+        assert_ne!(questions_etag(&[original.clone(), original], BodyFormat::Html), etag);
+    }
+
+    #[test]
+    fn if_none_match_should_compare_weakly() {
+        assert!(if_none_match("W/\"abc\"", "W/\"abc\""));
+        assert!(if_none_match("\"xyz\", \"abc\"", "W/\"abc\""));
+        assert!(if_none_match("*", "W/\"abc\""));
+        assert!(!if_none_match("W/\"abd\"", "W/\"abc\""));
+    }
+}
@@ -0,0 +1,141 @@
+use std::{future::Future, sync::Mutex};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use uuid::Uuid;
+
+use crate::tenancy;
+
+/// Something that happened to a post, published by the handlers once it is saved and visible. Side
+/// effects such as notifications subscribe to these rather than being run by the handlers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DomainEvent {
+    QuestionCreated {
+        question_uuid: String,
+        author_uuid: Option<String>,
+        description: String,
+    },
+    AnswerCreated {
+        question_uuid: String,
+        answer_uuid: String,
+        author_uuid: Option<String>,
+        content: String,
+    },
+    AnswerUpdated {
+        question_uuid: String,
+        answer_uuid: String,
+    },
+    /// Edited, closed, reopened, or an answer to it accepted.
+    QuestionUpdated {
+        question_uuid: String,
+    },
+    QuestionDeleted {
+        question_uuid: String,
+    },
+}
+
+/// An event with the tenant of the request that published it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Published {
+    pub tenant: Option<Uuid>,
+    pub event: DomainEvent,
+}
+
+/// Delivers every event to every subscriber. Each subscriber has its own unbounded queue, so a slow
+/// one delays neither the handlers nor the other subscribers, and no event is skipped.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<Published>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<Published> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        self.subscribers.lock().expect("event bus lock is not poisoned").push(sender);
+
+        receiver
+    }
+
+    pub fn publish(&self, event: DomainEvent) {
+        let published = Published {
+            tenant: tenancy::current_tenant(),
+            event,
+        };
+        let mut subscribers = self.subscribers.lock().expect("event bus lock is not poisoned");
+
+        // Subscribers that stopped are dropped on the next event.
+        subscribers.retain(|subscriber| subscriber.send(published.clone()).is_ok());
+    }
+}
+
+/// Handles the events of `events` one at a time, each with the tenant it was published in, until the
+/// bus is dropped, including the ones still queued when it is dropped.
+pub fn spawn_subscriber<F, Fut>(
+    name: &'static str,
+    mut events: mpsc::UnboundedReceiver<Published>,
+    handle: F,
+) -> JoinHandle<()>
+where
+    F: Fn(DomainEvent) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        while let Some(Published { tenant, event }) = events.recv().await {
+            tenancy::with_tenant(tenant, handle(event)).await;
+        }
+
+        info!("Stopped {} event subscriber.", name);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer_updated(answer_uuid: &str) -> DomainEvent {
+        DomainEvent::AnswerUpdated {
+            question_uuid: "123".to_owned(),
+            answer_uuid: answer_uuid.to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_deliver_every_event_to_every_subscriber() {
+        let events = EventBus::default();
+        let mut first = events.subscribe();
+        let mut second = events.subscribe();
+
+        events.publish(answer_updated("1"));
+        events.publish(answer_updated("2"));
+
+        for receiver in [&mut first, &mut second] {
+            assert_eq!(receiver.try_recv().unwrap().event, answer_updated("1"));
+            assert_eq!(receiver.try_recv().unwrap().event, answer_updated("2"));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_deliver_events_with_the_tenant_they_were_published_in() {
+        let events = EventBus::default();
+        let mut receiver = events.subscribe();
+        let tenant = Uuid::from_u128(0x42);
+
+        tenancy::with_tenant(Some(tenant), async { events.publish(answer_updated("1")) }).await;
+        events.publish(answer_updated("2"));
+
+        assert_eq!(receiver.try_recv().unwrap().tenant, Some(tenant));
+        assert_eq!(receiver.try_recv().unwrap().tenant, None);
+    }
+
+    #[test]
+    fn should_drop_stopped_subscribers() {
+        let events = EventBus::default();
+
+        drop(events.subscribe());
+        let _receiver = events.subscribe();
+
+        events.publish(answer_updated("1"));
+
+        assert_eq!(events.subscribers.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,193 @@
+use axum::body::Bytes;
+use serde::Serialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::models::{AnswerDetail, QuestionDetail};
+
+/// Newline-delimited JSON: one object per line, so that exports can be read a line at a time.
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Columns of `GET /export/questions.csv`, in their default order.
+pub const QUESTION_COLUMNS: &[&str] = &[
+    "question_uuid",
+    "title",
+    "description",
+    "status",
+    "kind",
+    "author_uuid",
+    "visibility",
+    "board_uuid",
+    "tags",
+    "language",
+    "created_at",
+    "updated_at",
+    "held_for_review",
+];
+
+/// Columns of `GET /export/answers.csv`, in their default order.
+pub const ANSWER_COLUMNS: &[&str] = &[
+    "answer_uuid",
+    "question_uuid",
+    "content",
+    "author_uuid",
+    "created_at",
+    "held_for_review",
+];
+
+/// `item` as a line of NDJSON. Serializing records of ours cannot fail in practice, but a failure
+/// aborts the export rather than leaving a line out.
+pub fn ndjson_line<T: Serialize>(item: &T) -> Result<String, std::io::Error> {
+    let mut line = serde_json::to_string(item)?;
+    line.push('\n');
+
+    Ok(line)
+}
+
+/// The columns named in `requested`, a comma-separated list, in its order; all of `available`
+/// when it is left out. Unknown and repeated columns are refused.
+pub fn columns(requested: Option<&str>, available: &'static [&'static str]) -> Result<Vec<&'static str>, String> {
+    let Some(requested) = requested else {
+        return Ok(available.to_vec());
+    };
+
+    let mut columns = Vec::new();
+
+    for name in requested.split(',').map(str::trim) {
+        let Some(column) = available.iter().find(|column| **column == name) else {
+            return Err(format!("Unknown column {:?}; the columns are {}.", name, available.join(", ")));
+        };
+
+        if columns.contains(column) {
+            return Err(format!("Column {:?} is selected more than once.", name));
+        }
+
+        columns.push(*column);
+    }
+
+    Ok(columns)
+}
+
+/// Writes CSV a row at a time, handing out each row as it is written so that it can be streamed.
+pub struct CsvRows {
+    builder: csv::WriterBuilder,
+}
+
+impl Default for CsvRows {
+    fn default() -> Self {
+        let mut builder = csv::WriterBuilder::new();
+        builder.has_headers(false);
+
+        CsvRows { builder }
+    }
+}
+
+impl CsvRows {
+    /// `fields` as a row, quoted where they need to be.
+    pub fn row<I, F>(&mut self, fields: I) -> Result<Bytes, std::io::Error>
+    where
+        I: IntoIterator<Item = F>,
+        F: AsRef<[u8]>,
+    {
+        let mut writer = self.builder.from_writer(Vec::new());
+        writer.write_record(fields)?;
+
+        Ok(Bytes::from(writer.into_inner().map_err(|err| err.into_error())?))
+    }
+}
+
+/// `text` written by users, with a `'` before it when a spreadsheet would read it as a formula.
+fn text_cell(text: &str) -> String {
+    if text.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", text)
+    } else {
+        text.to_owned()
+    }
+}
+
+fn timestamp(at: OffsetDateTime) -> String {
+    at.format(&Rfc3339).unwrap_or_default()
+}
+
+fn optional_uuid(uuid: Option<Uuid>) -> String {
+    uuid.map(|uuid| uuid.to_string()).unwrap_or_default()
+}
+
+/// The `columns` of `question`, with its tags joined by `;`.
+pub fn question_row(question: &QuestionDetail, columns: &[&str]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| match *column {
+            "question_uuid" => question.question_uuid.to_string(),
+            "title" => text_cell(&question.title),
+            "description" => text_cell(&question.description),
+            "status" => question.status.as_str().to_owned(),
+            "kind" => question.kind.as_str().to_owned(),
+            "author_uuid" => optional_uuid(question.author_uuid),
+            "visibility" => question.visibility.as_str().to_owned(),
+            "board_uuid" => optional_uuid(question.board_uuid),
+            "tags" => text_cell(&question.tags.join(";")),
+            "language" => question.language.clone().unwrap_or_default(),
+            "created_at" => timestamp(question.created_at),
+            "updated_at" => timestamp(question.updated_at),
+            "held_for_review" => question.held_for_review.to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+/// The `columns` of `answer`.
+pub fn answer_row(answer: &AnswerDetail, columns: &[&str]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| match *column {
+            "answer_uuid" => answer.answer_uuid.to_string(),
+            "question_uuid" => answer.question_uuid.to_string(),
+            "content" => text_cell(&answer.content),
+            "author_uuid" => optional_uuid(answer.author_uuid),
+            "created_at" => timestamp(answer.created_at),
+            "held_for_review" => answer.held_for_review.to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ndjson_line_should_hold_one_object_on_one_line() {
+        let line = ndjson_line(&json!({ "title": "first\nsecond" })).unwrap();
+
+        assert_eq!(line, "{\"title\":\"first\\nsecond\"}\n");
+    }
+
+    #[test]
+    fn columns_should_keep_the_requested_order() {
+        assert_eq!(columns(None, ANSWER_COLUMNS).unwrap(), ANSWER_COLUMNS);
+        assert_eq!(columns(Some("content, answer_uuid"), ANSWER_COLUMNS).unwrap(), vec!["content", "answer_uuid"]);
+        assert!(columns(Some("title"), ANSWER_COLUMNS).is_err());
+        assert!(columns(Some("content,content"), ANSWER_COLUMNS).is_err());
+    }
+
+    #[test]
+    fn csv_rows_should_quote_fields_that_need_it() {
+        let mut rows = CsvRows::default();
+
+        assert_eq!(rows.row(["answer_uuid", "content"]).unwrap(), "answer_uuid,content\n");
+        assert_eq!(rows.row(["1", "a, \"quoted\"\nline"]).unwrap(), "1,\"a, \"\"quoted\"\"\nline\"\n");
+    }
+
+    #[test]
+    fn text_cells_should_not_start_formulas() {
+        assert_eq!(text_cell("=HYPERLINK(\"https://evil.example.com\")"), "'=HYPERLINK(\"https://evil.example.com\")");
+        assert_eq!(text_cell("+1"), "'+1");
+        assert_eq!(text_cell("-1"), "'-1");
+        assert_eq!(text_cell("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(text_cell("How do lifetimes work?"), "How do lifetimes work?");
+    }
+}
@@ -0,0 +1,147 @@
+use time::OffsetDateTime;
+
+use crate::models::FeedEntry;
+
+pub const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+/// Characters of the description shown as an entry's summary.
+const SUMMARY_CHARS: usize = 500;
+
+/// An Atom document of `entries`, served at `self_url`. Its `updated` is the newest entry's, or
+/// `now` when the feed is empty.
+pub fn atom_feed(title: &str, self_url: &str, forum_url: &str, entries: &[FeedEntry], now: u64) -> String {
+    let forum_url = forum_url.trim_end_matches('/');
+    let updated = entries.first().map_or_else(|| rfc3339(now), |entry| entry.published.clone());
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+
+    xml.push_str(&format!("  <id>{}</id>\n", xml_escape(self_url)));
+    xml.push_str(&format!("  <title>{}</title>\n", xml_escape(title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+    xml.push_str(&format!("  <link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>\n", xml_escape(self_url)));
+    xml.push_str(&format!("  <link rel=\"alternate\" href=\"{}\"/>\n", xml_escape(forum_url)));
+    // Entries of deleted accounts have no author of their own, and Atom needs one for each.
+    xml.push_str("  <author><name>Rust Programming Forum</name></author>\n");
+
+    for entry in entries {
+        let url = format!("{}/question/{}", forum_url, entry.question_uuid);
+
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", xml_escape(&url)));
+        xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&entry.title)));
+        xml.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", xml_escape(&url)));
+        xml.push_str(&format!("    <published>{}</published>\n", entry.published));
+        xml.push_str(&format!("    <updated>{}</updated>\n", entry.published));
+
+        if let Some(author) = &entry.author {
+            xml.push_str(&format!("    <author><name>{}</name></author>\n", xml_escape(author)));
+        }
+
+        for tag in &entry.tags {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", xml_escape(tag)));
+        }
+
+        xml.push_str(&format!("    <summary type=\"text\">{}</summary>\n", xml_escape(&summary(&entry.summary))));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn summary(description: &str) -> String {
+    let description = description.trim();
+
+    match description.char_indices().nth(SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}…", &description[..end]),
+        None => description.to_owned(),
+    }
+}
+
+fn rfc3339(unix_timestamp: u64) -> String {
+    let time = i64::try_from(unix_timestamp)
+        .ok()
+        .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Escapes text for both element content and attribute values. Characters XML 1.0 forbids, such as
+/// most control characters, are dropped.
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn entry(title: &str, author: Option<&str>) -> FeedEntry {
+        FeedEntry {
+            question_uuid: Uuid::from_u128(0x123),
+            title: title.to_owned(),
+            summary: "How do I <borrow> this?\u{1}".to_owned(),
+            author: author.map(str::to_owned),
+            tags: vec!["rust".to_owned()],
+            published: "2026-10-17T10:00:00Z".to_owned(),
+        }
+    }
+
+    #[test]
+    fn atom_feed_should_escape_entries_and_use_the_newest_as_updated() {
+        let entries = [entry("Lifetimes & \"borrowing\"", Some("jane")), entry("Older", None)];
+
+        let xml = atom_feed("Questions", "https://forum.example.com/feeds/questions.atom", "https://forum.example.com/", &entries, 0);
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(xml.contains("  <updated>2026-10-17T10:00:00Z</updated>\n"));
+        assert!(xml.contains("<title>Lifetimes &amp; &quot;borrowing&quot;</title>"));
+        assert!(xml.contains("<id>https://forum.example.com/question/00000000-0000-0000-0000-000000000123</id>"));
+        assert!(xml.contains("<author><name>jane</name></author>"));
+        assert!(xml.contains("<category term=\"rust\"/>"));
+        assert!(xml.contains("<summary type=\"text\">How do I &lt;borrow&gt; this?</summary>"));
+        assert_eq!(xml.matches("<entry>").count(), 2);
+        assert!(xml.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn atom_feed_should_be_updated_now_when_empty() {
+        let xml = atom_feed("Questions", "https://forum.example.com/feeds/questions.atom", "https://forum.example.com", &[], 1_792_231_200);
+
+        assert!(xml.contains("<updated>2026-10-17T10:00:00Z</updated>"));
+        assert!(!xml.contains("<entry>"));
+    }
+
+    #[test]
+    fn summary_should_truncate_long_descriptions() {
+        let description = "a".repeat(SUMMARY_CHARS + 1);
+
+        assert_eq!(summary(&description), format!("{}…", "a".repeat(SUMMARY_CHARS)));
+        assert_eq!(summary(" short "), "short");
+    }
+}
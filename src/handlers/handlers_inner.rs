@@ -1,12 +1,103 @@
+use std::{collections::BTreeMap, net::IpAddr, time::Duration};
+
+use axum::body::Bytes;
+use serde_json::json;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
 use crate::{
-  models::{Answer, AnswerDetail, AnswerId, DBError, Question, QuestionDetail, QuestionId},
-  persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+  auth::{generate_api_token, hash_api_token, AuthBackend},
+  avatars::{
+    avatar_object_key, is_avatar_object_key, new_avatar_key, render_avatar, AVATAR_SIZES, MAX_AVATAR_BYTES,
+  },
+  cache::{QuestionCache, TtlCache},
+  content_policy::ContentPolicy,
+  diagnostics::{DiagnosticsError, DiagnosticsProbe, MAX_PROFILE_SECONDS},
+  events::{DomainEvent, EventBus},
+  export,
+  feeds::atom_feed,
+  import::{self, ImportJob},
+  language::is_known_language,
+  live::{LiveEvent, LiveUpdates},
+  markdown::{links, mentions},
+  models::{
+    AcceptAnswer, AcceptSuggestionSettings, AdminStats, AdminStatsQuery, Answer, AnswerDetail, AnswerId, AnswerRevision, AnswerScore,
+    AnswerSort, AnswerUpdate, AnswerVote,
+    Attachment, AttachmentDetail, AuditAction, AuditEntry, AuditQuery, AuditRecord, AuditTarget, Board,
+    BoardCleanup, BoardCleanupPolicy, BoardCleanupPolicyDetail, BoardDetail, BoardInvite, BoardMember, BoardRole,
+    BoardTagRules, BoardTagRulesDetail, BulkDelete, BulkDeleteResult, CloseQuestion, ConflictCode, ConflictDetail,
+    ContentPolicyViolation, Contest, ContestDetail, DBError, DeadLetter, DeadLetterKind, DeadLetterRetryResult,
+    DeadLetterSelection, DeletedDrafts, Diagnostics, DigestSettings, DraftDetail, ErasureReport, ExportQuery,
+    ExportRange, FaqEntry, FaqGroup, FaqGrouping, FaqQuery, FaqSelection, FeedEntry, FieldError, Flag, FlagDetail,
+    FlagReason,
+    FlagStatus, FlagsQuery, HealthStatus, Invitation, InvitationAcceptance, InvitationDetail, InvitationLink,
+    JobDetail, JobKind, JobRequest, KbExport, KbSection, LanguageQuery, LinkPreview, LiveMessage, LivePoll, LiveQuery,
+    LongPollQuery, MembershipStatus, ModerationAction, ModerationActionDetail, ModerationActionKind,
+    ModerationItem, ModerationQueueQuery, MyContent, NecroPostPolicy, NewTagPolicy, NewWebhook, Notification,
+    NotificationChannels, NotificationKind, NotificationKindSettings, NotificationSettings, NotificationsQuery,
+    NotificationsRead, Pagination, PendingTag, PendingTagResolution, PostKind, PostingQuotaExceeded,
+    PostingQuotaPolicy, ProfileQuery, ProvisionedUserDetail, QuerySample, QuerySamplesQuery, QuerySampling,
+    Question, QuestionBatch, QuestionDeletion, QuestionDetail, QuestionDraft, QuestionFull, QuestionId, QuestionKind,
+    QuestionRevision, QuestionStats, QuestionStatus, Readiness, ReopenQuestion, ResolveFlag, RetentionCategory, RetentionPolicy,
+    RetentionStats, Role, SignIn, SignedUrl, SignedUrlRequest, SimilarAnswerPolicy, SitemapUrl, SloStatus,
+    TagRuleViolation, TagStats, TagSuggestQuery, TagUsage, Unsubscribed, Upload, User, UserCredentials, UserDetail,
+    UserProfile, Viewer, Visibility, WebhookDelivery, WebhookDetail, WebhookDigest, WebhookEvent,
+  },
+  persistance::{
+    answers_dao::AnswersDao, attachments_dao::AttachmentsDao, audit_dao::AuditDao, boards_dao::BoardsDao,
+    cleanup_policies_dao::CleanupPoliciesDao, content_dao::ContentDao, dead_letters_dao::DeadLettersDao,
+    drafts_dao::DraftsDao, email_digests_dao::EmailDigestsDao, erasure_dao::ErasureDao, faq_dao::FaqDao,
+    flags_dao::FlagsDao, follows_dao::FollowsDao, health_dao::HealthDao, invitations_dao::InvitationsDao,
+    jobs_dao::JobsDao, link_previews_dao::LinkPreviewsDao, moderation_dao::ModerationDao,
+    notifications_dao::NotificationsDao, query_samples_dao::QuerySamplesDao, questions_dao::QuestionsDao,
+    retention_dao::RetentionDao, stats_dao::StatsDao, tags_dao::TagsDao, users_dao::UsersDao, webhooks_dao::WebhooksDao,
+  },
+  query_log::QuerySampler,
+  rate_limit::RateLimiter,
+  routes::API_V1,
+  scim::{parse_user_name_filter, patched_active, ScimConfig, ScimListResponse, ScimPatch, ScimUser},
+  signing::{SigningError, UrlSignature, UrlSigner},
+  similarity::most_similar,
+  sitemap::{parse_sitemap_file, sitemap_index},
+  slo::SloTracker,
+  spam::{SpamCandidate, SpamChecker, SpamVerdict, RECENT_POSTS_WINDOW_MINUTES},
+  storage::ObjectStore,
+  tenancy::current_tenant,
+  validation::Validate,
+  views::ViewCounter,
+  webhooks::DigestWebhook,
 };
 
+/// Lifetime of the download link returned with a new upload.
+const DOWNLOAD_EXPIRES_IN_SECONDS: u64 = 60 * 60;
+/// How long `GET /ready` waits for the database, below the timeouts of common probes.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Debug, PartialEq)]
 pub enum HandlerError {
   BadRequest(String),
+  Unauthorized(String),
+  Forbidden(String),
+  NotFound(String),
+  Conflict(String),
   InternalError(String),
+  /// The message and how many seconds the client should wait before retrying.
+  TooManyRequests(String, u64),
+  /// A bad request answered with a machine-readable body.
+  TagRuleViolation(TagRuleViolation),
+  /// Answered with 422 and the blocked terms.
+  ContentPolicyViolation(ContentPolicyViolation),
+  /// A conflict answered with a machine-readable body.
+  ConflictDetail(ConflictDetail),
+  /// Answered with 422 and every invalid field of the request body.
+  ValidationFailed(Vec<FieldError>),
+  /// Answered with 429 and the quota the author is over.
+  PostingQuotaExceeded(PostingQuotaExceeded),
+  /// Answered with 503, when the database cannot be reached.
+  ServiceUnavailable(String),
+  /// Answered with 504, when the database took too long.
+  GatewayTimeout(String),
 }
 
 impl HandlerError {
@@ -15,489 +106,12055 @@ impl HandlerError {
   }
 }
 
+/// How database errors are answered when a handler has no more specific answer for them. Handlers
+/// taking UUIDs from the client answer `InvalidUUID` with 400 themselves; anywhere else it is a bug.
+impl From<DBError> for HandlerError {
+  fn from(err: DBError) -> Self {
+      match err {
+          DBError::NotFound => HandlerError::NotFound("Not found.".to_owned()),
+          DBError::UniqueViolation(_) => HandlerError::Conflict("Already exists.".to_owned()),
+          DBError::ForeignKeyViolation(_) => {
+            HandlerError::BadRequest("Refers to something that does not exist.".to_owned())
+          }
+          DBError::SerializationFailure => {
+            HandlerError::ServiceUnavailable("The database is busy. Please try again.".to_owned())
+          }
+          DBError::ConnectionError | DBError::CircuitOpen => {
+            HandlerError::ServiceUnavailable("The database is unavailable. Please try again later.".to_owned())
+          }
+          DBError::Timeout => {
+            HandlerError::GatewayTimeout("The database took too long to answer. Please try again.".to_owned())
+          }
+          DBError::PostingLimitReached => HandlerError::TooManyRequests(
+            "You are posting too fast. Please try again later.".to_owned(),
+            u64::from(PostingQuotaPolicy::WINDOW_MINUTES.unsigned_abs()) * 60,
+          ),
+          DBError::InvalidUUID(_) | DBError::Other(_) => HandlerError::default_internal_error(),
+      }
+  }
+}
+
+/// Questions with terms the `ContentPolicy` blocks are refused, or masked. Questions the spam
+/// checker flags are saved but held for moderation; their mentions are not notified. New tags from
+/// authors the `NewTagPolicy` does not trust are left off until approved. The question, its hold
+/// and its proposed tags are saved together or not at all. Only moderators can post
+/// announcements, and authors over their `PostingQuotaPolicy` are refused with
+/// `PostingQuotaExceeded`.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_question(
   question: Question,
+  author: Option<&UserDetail>,
   // We are using a trait object here so that inner handlers do not depend on concrete DAO implementations
   questions_dao: &(dyn QuestionsDao + Sync + Send),
+  boards_dao: &(dyn BoardsDao + Sync + Send),
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+  spam_checker: &dyn SpamChecker,
+  client_ip: IpAddr,
+  users_dao: &(dyn UsersDao + Send + Sync),
+  new_tag_policy: NewTagPolicy,
+  posting_quota_policy: PostingQuotaPolicy,
+  now: u64,
+  content_policy: &ContentPolicy,
+  events: &EventBus,
 ) -> Result<QuestionDetail, HandlerError> {
-  let question = questions_dao.create_question(question).await;
+  require_valid(&question)?;
+
+  if question.kind == QuestionKind::Announcement && !author.is_some_and(|author| author.role.can_moderate()) {
+    return Err(HandlerError::Forbidden("Only moderators can post announcements.".to_owned()));
+  }
+
+  let posting_limit =
+    require_posting_quota(author, PostKind::Question, posting_quota_policy, moderation_dao, users_dao, now).await?;
+
+  if let Some(contest) = question.contest {
+    require_contest(contest, question.kind)?;
+  }
+
+  let [title, description] = content_policy
+    .apply([question.title, question.description])
+    .map_err(HandlerError::ContentPolicyViolation)?;
+
+  let question = Question {
+    title,
+    description,
+    tags: normalized_tags(question.tags)?,
+    ..question
+  };
+
+  require_board_access(&question, author, boards_dao).await?;
+  require_board_tag_rules(&question, tags_dao).await?;
+
+  let (question, pending_tags) = split_new_tags(question, author, new_tag_policy, tags_dao, users_dao).await?;
+
+  let content = format!("{} {}", question.title, question.description);
+  let spam_details = match spam_verdict(content, author, client_ip, moderation_dao, spam_checker).await {
+      SpamVerdict::Spam(reason) => Some(reason),
+      SpamVerdict::Ham => None,
+  };
+
+  let question = questions_dao
+    .create_question(question, author.map(|author| author.user_uuid.to_string()), pending_tags, spam_details, posting_limit)
+    .await;
 
   match question {
-      Ok(question) => Ok(question),
+      Ok(question) => {
+        if !question.held_for_review {
+          events.publish(question_created(&question));
+        }
+
+        Ok(question)
+      }
+      Err(DBError::PostingLimitReached) => {
+        // A question posted meanwhile took the author's last place; explain the quota they are over.
+        require_posting_quota(author, PostKind::Question, posting_quota_policy, moderation_dao, users_dao, now).await?;
+        Err(DBError::PostingLimitReached.into())
+      }
       Err(err) => {
           error!("Error to create question: {}", err);
-          Err(HandlerError::default_internal_error())
+          Err(err.into())
       }
   }
 }
 
-pub async fn read_questions(
+/// Questions read anonymously are served from, and kept in, `question_cache`.
+#[allow(clippy::too_many_arguments)]
+pub async fn read_question(
+  question_uuid: String,
+  viewer: Viewer,
+  client_ip: IpAddr,
   questions_dao: &(dyn QuestionsDao + Sync + Send),
-) -> Result<Vec<QuestionDetail>, HandlerError> {
-  let questions = questions_dao.get_questions().await;
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+  view_counter: &ViewCounter,
+  question_cache: &QuestionCache,
+) -> Result<QuestionDetail, HandlerError> {
+  let cacheable = viewer == Viewer::Anonymous;
 
-  match questions {
-      Ok(questions) => Ok(questions),
-      Err(err) => {
-        error!("Error to list questions: {}", err);
-        Err(HandlerError::default_internal_error())
-      }
+  if cacheable {
+    if let Some(question) = question_cache.question(&question_uuid).await {
+      view_counter.record(question.question_uuid, &viewer, client_ip);
+      return Ok(question);
+    }
   }
-}
 
-pub async fn delete_question(
-  question_uuid: QuestionId,
-  questions_dao: &(dyn QuestionsDao + Sync + Send),
-) -> Result<(), HandlerError> {
-  let result = questions_dao.delete_question(question_uuid.question_uuid).await;
+  let question = questions_dao.get_question(question_uuid, viewer.clone()).await;
 
-  if result.is_err() {
-    error!("Error to delete question: {}", result.err().unwrap());
-    return Err(HandlerError::default_internal_error());
-  }
+  match question {
+      Ok(Some(question)) => {
+        let question = question_with_link_previews(question, link_previews_dao).await;
 
-  Ok(())
-}
+        if cacheable {
+          question_cache.set_question(&question).await;
+        }
 
-pub async fn create_answer(
-  answer: Answer,
-  answers_dao: &(dyn AnswersDao + Send + Sync),
-) -> Result<AnswerDetail, HandlerError> {
-  let answer = answers_dao.create_answer(answer).await;
+        view_counter.record(question.question_uuid, &viewer, client_ip);
 
-  match answer {
-      Ok(answer) => Ok(answer),
+        Ok(question)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
       Err(err) => {
-        error!("Error to create answer: {}", err);
+        error!("Error to read question: {}", err);
 
           match err {
               DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
-              _ => Err(HandlerError::default_internal_error()),
+              err => Err(err.into()),
           }
       }
   }
 }
 
-pub async fn read_answers(
+/// The question and its answers are read at once, and so are the previews of their links.
+#[allow(clippy::too_many_arguments)]
+pub async fn read_question_full(
   question_uuid: QuestionId,
+  sort: AnswerSort,
+  viewer: Viewer,
+  client_ip: IpAddr,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
   answers_dao: &(dyn AnswersDao + Send + Sync),
-) -> Result<Vec<AnswerDetail>, HandlerError> {
-  let answers = answers_dao.get_answers(question_uuid.question_uuid).await;
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+  view_counter: &ViewCounter,
+) -> Result<QuestionFull, HandlerError> {
+  let uuid = question_uuid.question_uuid.to_string();
 
-  match answers {
-      Ok(answers) => Ok(answers),
+  let (question, answers) = tokio::join!(
+    questions_dao.get_question(uuid.clone(), viewer.clone()),
+    answers_dao.get_answers(uuid, sort, viewer.clone()),
+  );
+
+  let (question, answers) = match (question, answers) {
+      (Ok(Some(question)), Ok(answers)) => (question, answers),
+      (Ok(None), _) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      (Err(err), _) | (_, Err(err)) => {
+        error!("Error to read question with its answers: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let (question, answers) = tokio::join!(
+    question_with_link_previews(question, link_previews_dao),
+    answers_with_link_previews(answers, link_previews_dao),
+  );
+
+  view_counter.record(question.question_uuid, &viewer, client_ip);
+
+  Ok(QuestionFull { question, answers })
+}
+
+/// The question's counters, for anyone who may read the question.
+pub async fn read_question_stats(
+  question_uuid: QuestionId,
+  viewer: Viewer,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  stats_dao: &(dyn StatsDao + Send + Sync),
+) -> Result<QuestionStats, HandlerError> {
+  let uuid = question_uuid.question_uuid.to_string();
+
+  let stats = match questions_dao.get_question(uuid.clone(), viewer).await {
+      Ok(Some(_)) => stats_dao.get_question_stats(uuid).await,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => Err(err),
+  };
+
+  match stats {
+      Ok(Some(stats)) => Ok(stats),
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
       Err(err) => {
-        error!("Error to list answers: {}", err);
-        Err(HandlerError::default_internal_error())
+        error!("Error to read question stats: {}", err);
+        Err(err.into())
       }
   }
 }
 
-pub async fn delete_answer(
-  answer_uuid: AnswerId,
-  answers_dao: &(dyn AnswersDao + Send + Sync),
-) -> Result<(), HandlerError> {
-  let result = answers_dao.delete_answer(answer_uuid.answer_uuid).await;
-
-  if result.is_err() {
-    error!("Error to delete answer: {}", result.err().unwrap());
-    return Err(HandlerError::default_internal_error());
+/// Pages read anonymously are served from, and kept in, `question_cache`.
+pub async fn read_questions(
+  viewer: Viewer,
+  query: LanguageQuery,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  question_cache: &QuestionCache,
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+  if let Some(lang) = query.lang.as_deref().filter(|lang| !is_known_language(lang)) {
+    return Err(HandlerError::BadRequest(format!("Unknown language code {}; use ISO 639-3, e.g. eng.", lang)));
   }
 
-  Ok(())
-}
+  let cacheable = viewer == Viewer::Anonymous;
 
-// ***********************************************************
-//                           Tests
-// ***********************************************************
+  if cacheable {
+    if let Some(questions) = question_cache.questions(query.lang.as_deref()).await {
+      return Ok(questions);
+    }
+  }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+  let questions = questions_dao.get_questions(viewer, query.lang.clone()).await;
 
-  use async_trait::async_trait;
-  use tokio::sync::Mutex;
+  match questions {
+      Ok(questions) => {
+        if cacheable {
+          question_cache.set_questions(query.lang.as_deref(), &questions).await;
+        }
 
-  struct QuestionsDaoMock {
-      create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
-      delete_question_response: Mutex<Option<Result<(), DBError>>>,
-      get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        Ok(questions)
+      }
+      Err(err) => {
+        error!("Error to list questions: {}", err);
+        Err(err.into())
+      }
   }
+}
 
-  impl QuestionsDaoMock {
-      pub fn new() -> Self {
-          QuestionsDaoMock {
-              create_question_response: Mutex::new(None),
-              delete_question_response: Mutex::new(None),
-              get_questions_response: Mutex::new(None),
+pub async fn read_questions_batch(
+  batch: QuestionBatch,
+  viewer: Viewer,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+  if batch.question_uuids.len() > QuestionBatch::MAX_QUESTIONS {
+    return Err(HandlerError::BadRequest(format!(
+      "At most {} questions can be fetched at once.",
+      QuestionBatch::MAX_QUESTIONS
+    )));
+  }
+
+  let question_uuids = batch.question_uuids.iter().map(Uuid::to_string).collect();
+  let questions = questions_dao.get_questions_by_uuid(question_uuids, viewer).await;
+
+  match questions {
+      Ok(questions) => Ok(questions),
+      Err(err) => {
+        error!("Error to fetch questions: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
           }
       }
-      pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
-          self.create_question_response = Mutex::new(Some(response));
+  }
+}
+
+pub async fn delete_question(
+  question_uuid: QuestionId,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDeletion, HandlerError> {
+  let question_uuid = question_uuid.question_uuid.to_string();
+
+  let current = match questions_dao.get_question(question_uuid.clone(), Some(user).into()).await {
+      Ok(Some(current)) => current,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read question for delete: {}", err);
+        return Err(err.into());
       }
-      pub fn mock_delete_question(&mut self, response: Result<(), DBError>) {
-          self.delete_question_response = Mutex::new(Some(response));
+  };
+
+  require_author_or_moderator(current.author_uuid, user)?;
+
+  let result = questions_dao.delete_question(question_uuid).await;
+
+  match result {
+      Ok(Some(deletion)) => {
+        let target_uuid = Some(deletion.question_uuid.to_string());
+        audit(user, AuditAction::DeleteQuestion, AuditTarget::Question, target_uuid, json!(deletion), audit_dao).await;
+
+        events.publish(DomainEvent::QuestionDeleted {
+          question_uuid: deletion.question_uuid.to_string(),
+        });
+
+        Ok(deletion)
       }
-      pub fn mock_get_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
-          self.get_questions_response = Mutex::new(Some(response));
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to delete question: {}", err);
+        Err(err.into())
       }
   }
+}
 
-  #[async_trait]
-  impl QuestionsDao for QuestionsDaoMock {
-      async fn create_question(&self, _: Question) -> Result<QuestionDetail, DBError> {
-          self.create_question_response
-              .lock()
-              .await
-              .take()
-              .expect("create_question_response should not be None.")
-      }
-      async fn delete_question(&self, _: String) -> Result<(), DBError> {
-          self.delete_question_response
-              .lock()
-              .await
-              .take()
-              .expect("delete_question_response should not be None.")
+pub async fn bulk_delete_questions(
+  request: BulkDelete,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<Vec<BulkDeleteResult>, HandlerError> {
+  require_moderator(user)?;
+  require_bulk_delete_size(&request)?;
+
+  let results = questions_dao.delete_questions(request.uuids).await;
+
+  match results {
+      Ok(results) => {
+        audit_deleted(user, AuditAction::DeleteQuestions, AuditTarget::Question, &results, audit_dao).await;
+
+        for result in results.iter().filter(|result| result.deleted) {
+          events.publish(DomainEvent::QuestionDeleted {
+            question_uuid: result.uuid.clone(),
+          });
+        }
+
+        Ok(results)
       }
-      async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
-          self.get_questions_response
-              .lock()
-              .await
-              .take()
-              .expect("get_questions_response should not be None.")
+      Err(err) => {
+        error!("Error to bulk delete questions: {}", err);
+        Err(err.into())
       }
   }
+}
 
-  struct AnswersDaoMock {
-      create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
-      delete_answer_response: Mutex<Option<Result<(), DBError>>>,
-      get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
-  }
+#[allow(clippy::too_many_arguments)]
+pub async fn update_question(
+  question_uuid: String,
+  question: Question,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  boards_dao: &(dyn BoardsDao + Sync + Send),
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  users_dao: &(dyn UsersDao + Send + Sync),
+  new_tag_policy: NewTagPolicy,
+  content_policy: &ContentPolicy,
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  require_valid(&question)?;
 
-  impl AnswersDaoMock {
-      pub fn new() -> Self {
-          AnswersDaoMock {
-              create_answer_response: Mutex::new(None),
-              delete_answer_response: Mutex::new(None),
-              get_answers_response: Mutex::new(None),
-          }
-      }
-      pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
-          self.create_answer_response = Mutex::new(Some(response));
+  let current = match questions_dao.get_question(question_uuid.clone(), Some(user).into()).await {
+      Ok(Some(current)) => current,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question for edit: {}", err);
+        return Err(err.into());
       }
-      pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
-          self.delete_answer_response = Mutex::new(Some(response));
+  };
+
+  require_author_or_moderator(current.author_uuid, user)?;
+
+  if current.status == QuestionStatus::Locked && !user.role.can_moderate() {
+    return Err(HandlerError::Conflict("Locked questions can only be edited by moderators.".to_owned()));
+  }
+
+  let [title, description] = content_policy
+    .apply([question.title, question.description])
+    .map_err(HandlerError::ContentPolicyViolation)?;
+
+  let question = Question {
+    title,
+    description,
+    tags: normalized_tags(question.tags)?,
+    ..question
+  };
+
+  require_board_access(&question, Some(user), boards_dao).await?;
+  require_board_tag_rules(&question, tags_dao).await?;
+
+  let (question, pending_tags) = split_new_tags(question, Some(user), new_tag_policy, tags_dao, users_dao).await?;
+
+  let question = questions_dao
+    .update_question(question_uuid, question, user.user_uuid.to_string())
+    .await;
+
+  match question {
+      Ok(Some(question)) => {
+        propose_tags(&question.question_uuid.to_string(), &pending_tags, Some(user), tags_dao).await;
+        events.publish(question_updated(&question));
+
+        Ok(QuestionDetail {
+          pending_tags,
+          ..question
+        })
       }
-      pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
-          self.get_answers_response = Mutex::new(Some(response));
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to update question: {}", err);
+        Err(err.into())
       }
   }
+}
 
-  #[async_trait]
-  impl AnswersDao for AnswersDaoMock {
-      async fn create_answer(&self, _: Answer) -> Result<AnswerDetail, DBError> {
-          self.create_answer_response
-              .lock()
-              .await
-              .take()
-              .expect("create_answer_response should not be None.")
+pub async fn read_question_revisions(
+  question_uuid: String,
+  viewer: Viewer,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionRevision>, HandlerError> {
+  let revisions = questions_dao.get_question_revisions(question_uuid.clone(), viewer.clone()).await;
+
+  match revisions {
+      Ok(revisions) if !revisions.is_empty() => Ok(revisions),
+      // Never edited: the current post is the only revision.
+      Ok(_) => match questions_dao.get_question(question_uuid, viewer).await {
+          Ok(Some(question)) => Ok(vec![QuestionRevision {
+            question_uuid: question.question_uuid,
+            revision: 1,
+            title: question.title,
+            description: question.description,
+            editor_uuid: question.author_uuid,
+            created_at: question.created_at,
+          }]),
+          Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+          Err(err) => {
+            error!("Error to read question revisions: {}", err);
+            Err(err.into())
+          }
+      },
+      Err(err) => {
+        error!("Error to read question revisions: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
       }
-      async fn delete_answer(&self, _: String) -> Result<(), DBError> {
-          self.delete_answer_response
-              .lock()
-              .await
-              .take()
-              .expect("delete_answer_response should not be None.")
+  }
+}
+
+pub async fn create_question_signed_url(
+  question_uuid: String,
+  request: SignedUrlRequest,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<SignedUrl, HandlerError> {
+  require_signed_url_expiry(&request)?;
+
+  let question = match questions_dao.get_question(question_uuid, Some(user).into()).await {
+      Ok(Some(question)) => question,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question for signed URL: {}", err);
+        return Err(err.into());
       }
-      async fn get_answers(&self, _: String) -> Result<Vec<AnswerDetail>, DBError> {
-          self.get_answers_response
-              .lock()
-              .await
-              .take()
-              .expect("get_answers_response should not be None.")
+  };
+
+  require_author_or_moderator(question.author_uuid, user)?;
+
+  Ok(signed_url(url_signer, &shared_question_path(&question.question_uuid.to_string()), now + request.expires_in_seconds))
+}
+
+pub async fn read_shared_question(
+  question_uuid: String,
+  signature: UrlSignature,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<QuestionDetail, HandlerError> {
+  match url_signer.verify(&shared_question_path(&question_uuid), &signature, now) {
+      Ok(()) => {}
+      Err(SigningError::Expired) => return Err(HandlerError::Forbidden("Signed URL has expired.".to_owned())),
+      Err(_) => return Err(HandlerError::Forbidden("Invalid signed URL.".to_owned())),
+  }
+
+  let question = questions_dao.get_question(question_uuid, Viewer::SignedLink).await;
+
+  match question {
+      Ok(Some(question)) => Ok(question_with_link_previews(question, link_previews_dao).await),
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read shared question: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
       }
   }
+}
 
-  #[tokio::test]
-  async fn create_question_should_return_question() {
-      let question = Question {
-          title: "test title".to_owned(),
-          description: "test description".to_owned(),
-      };
+fn shared_question_path(question_uuid: &str) -> String {
+  format!("{}/shared/question/{}", API_V1, question_uuid)
+}
 
-      let question_detail = QuestionDetail {
-          question_uuid: "123".to_owned(),
+fn signed_url(url_signer: &UrlSigner, path: &str, expires: u64) -> SignedUrl {
+  let signature = url_signer.sign(path, expires);
+
+  SignedUrl {
+    url: format!(
+      "{}?expires={}&key_id={}&signature={}",
+      path, signature.expires, signature.key_id, signature.signature
+    ),
+    expires: signature.expires,
+  }
+}
+
+/// Stores a file and records it as an attachment, linked to a question or answer when one is given.
+/// Only the post's author or a moderator can attach files to it.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_upload(
+  upload: Upload,
+  user: &UserDetail,
+  attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  object_store: &dyn ObjectStore,
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<AttachmentDetail, HandlerError> {
+  if upload.content.is_empty() || upload.content.len() > Upload::MAX_BYTES {
+    return Err(HandlerError::BadRequest(format!(
+      "Files must be between 1 byte and {} MB.",
+      Upload::MAX_BYTES / (1024 * 1024)
+    )));
+  }
+
+  let content_type = sniff_content_type(&upload.content).ok_or_else(|| {
+    HandlerError::BadRequest("Only PNG, JPEG, GIF, WebP, PDF and plain text files can be uploaded.".to_owned())
+  })?;
+
+  let question_uuid = match (upload.question_uuid, upload.answer_uuid.as_ref()) {
+      (Some(_), Some(_)) => {
+        return Err(HandlerError::BadRequest("Attach a file to a question or an answer, not both.".to_owned()))
+      }
+      (None, Some(answer_uuid)) => match answers_dao.get_answer(answer_uuid.to_string(), Some(user).into()).await {
+          Ok(Some(answer)) => {
+            require_author_or_moderator(answer.author_uuid, user)?;
+            Some(answer.question_uuid)
+          }
+          Ok(None) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+          Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+          Err(err) => {
+            error!("Error to read answer for upload: {}", err);
+            return Err(err.into());
+          }
+      },
+      (Some(question_uuid), None) => match questions_dao.get_question(question_uuid.to_string(), Some(user).into()).await {
+          Ok(Some(question)) => {
+            require_author_or_moderator(question.author_uuid, user)?;
+            Some(question.question_uuid)
+          }
+          Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+          Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+          Err(err) => {
+            error!("Error to read question for upload: {}", err);
+            return Err(err.into());
+          }
+      },
+      (None, None) => None,
+  };
+
+  let storage_key = hex::encode(rand::random::<[u8; 16]>());
+  let size_bytes = upload.content.len() as i64;
+
+  if let Err(err) = object_store.put(&storage_key, content_type, upload.content).await {
+    error!("Error to store upload: {}", err);
+    return Err(HandlerError::default_internal_error());
+  }
+
+  let attachment = Attachment {
+    storage_key: storage_key.clone(),
+    filename: sanitize_filename(&upload.filename),
+    content_type: content_type.to_owned(),
+    size_bytes,
+    question_uuid,
+    answer_uuid: upload.answer_uuid,
+  };
+
+  match attachments_dao.create_attachment(attachment, user.user_uuid.to_string()).await {
+      Ok(attachment) => {
+        let download = signed_url(url_signer, &upload_path(&attachment.attachment_uuid.to_string()), now + DOWNLOAD_EXPIRES_IN_SECONDS);
+
+        Ok(AttachmentDetail {
+          download: Some(download),
+          ..attachment
+        })
+      }
+      Err(err) => {
+        error!("Error to create attachment: {}", err);
+
+        if let Err(err) = object_store.delete(&storage_key).await {
+          error!("Error to delete unrecorded upload {}: {}", storage_key, err);
+        }
+
+        match err {
+            DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+            err => Err(err.into()),
+        }
+      }
+  }
+}
+
+/// A fresh download link for an attachment the user uploaded or whose question they can read.
+pub async fn create_attachment_signed_url(
+  attachment_uuid: String,
+  request: SignedUrlRequest,
+  user: &UserDetail,
+  attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<SignedUrl, HandlerError> {
+  require_signed_url_expiry(&request)?;
+
+  let attachment = match attachments_dao.get_attachment(attachment_uuid).await {
+      Ok(Some(attachment)) => attachment,
+      Ok(None) => return Err(HandlerError::NotFound("Attachment not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read attachment: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let readable = if attachment.uploader_uuid == Some(user.user_uuid) {
+    true
+  } else if let Some(question_uuid) = attachment.question_uuid {
+    match questions_dao.get_question(question_uuid.to_string(), Some(user).into()).await {
+        Ok(question) => question.is_some(),
+        Err(err) => {
+          error!("Error to read question for attachment: {}", err);
+          return Err(err.into());
+        }
+    }
+  } else {
+    false
+  };
+
+  // Attachments the user cannot read are reported as missing, like private questions.
+  if !readable {
+    return Err(HandlerError::NotFound("Attachment not found.".to_owned()));
+  }
+
+  Ok(signed_url(url_signer, &upload_path(&attachment.attachment_uuid.to_string()), now + request.expires_in_seconds))
+}
+
+/// Returns an attachment and its content to the holder of a signed download link.
+pub async fn read_upload(
+  attachment_uuid: String,
+  signature: UrlSignature,
+  attachments_dao: &(dyn AttachmentsDao + Send + Sync),
+  object_store: &dyn ObjectStore,
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<(AttachmentDetail, Vec<u8>), HandlerError> {
+  match url_signer.verify(&upload_path(&attachment_uuid), &signature, now) {
+      Ok(()) => {}
+      Err(SigningError::Expired) => return Err(HandlerError::Forbidden("Signed URL has expired.".to_owned())),
+      Err(_) => return Err(HandlerError::Forbidden("Invalid signed URL.".to_owned())),
+  }
+
+  let attachment = match attachments_dao.get_attachment(attachment_uuid).await {
+      Ok(Some(attachment)) => attachment,
+      Ok(None) => return Err(HandlerError::NotFound("Attachment not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read attachment: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  match object_store.get(&attachment.storage_key).await {
+      Ok(Some(content)) => Ok((attachment, content)),
+      Ok(None) => {
+        error!("Attachment {} has no stored object.", attachment.attachment_uuid);
+        Err(HandlerError::NotFound("Attachment not found.".to_owned()))
+      }
+      Err(err) => {
+        error!("Error to read stored upload: {}", err);
+        Err(HandlerError::default_internal_error())
+      }
+  }
+}
+
+fn upload_path(attachment_uuid: &str) -> String {
+  format!("{}/uploads/{}", API_V1, attachment_uuid)
+}
+
+pub async fn follow_question(
+  question_uuid: String,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  follows_dao: &(dyn FollowsDao + Sync + Send),
+) -> Result<(), HandlerError> {
+  match questions_dao.get_question(question_uuid.clone(), Some(user).into()).await {
+      Ok(Some(_)) => {}
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question to follow: {}", err);
+        return Err(err.into());
+      }
+  }
+
+  let result = follows_dao.follow_question(question_uuid, user.user_uuid.to_string()).await;
+
+  match result {
+      Ok(()) => Ok(()),
+      Err(err) => {
+        error!("Error to follow question: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn unfollow_question(
+  question_uuid: String,
+  user: &UserDetail,
+  follows_dao: &(dyn FollowsDao + Sync + Send),
+) -> Result<(), HandlerError> {
+  let result = follows_dao.unfollow_question(question_uuid, user.user_uuid.to_string()).await;
+
+  match result {
+      Ok(()) => Ok(()),
+      Err(err) => {
+        error!("Error to unfollow question: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn restore_question(
+  question_uuid: String,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  require_moderator(user)?;
+
+  let question = questions_dao.restore_question(question_uuid).await;
+
+  match question {
+      Ok(Some(question)) => {
+        let target_uuid = Some(question.question_uuid.to_string());
+        audit(user, AuditAction::RestoreQuestion, AuditTarget::Question, target_uuid, json!({}), audit_dao).await;
+        events.publish(question_updated(&question));
+        Ok(question)
+      }
+      Ok(None) => Err(HandlerError::NotFound("No deleted question found.".to_owned())),
+      Err(err) => {
+        error!("Error to restore question: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn close_question(
+  question_uuid: String,
+  close: CloseQuestion,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  if close.status == QuestionStatus::Open {
+    return Err(HandlerError::BadRequest("Use the reopen endpoint to reopen a question.".to_owned()));
+  }
+
+  change_question_status(question_uuid, close.status, close.reason, user, questions_dao, audit_dao, events).await
+}
+
+pub async fn reopen_question(
+  question_uuid: String,
+  reopen: ReopenQuestion,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  change_question_status(question_uuid, QuestionStatus::Open, reopen.reason, user, questions_dao, audit_dao, events).await
+}
+
+async fn change_question_status(
+  question_uuid: String,
+  status: QuestionStatus,
+  reason: String,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  require_moderator(user)?;
+
+  if reason.trim().is_empty() {
+    return Err(HandlerError::BadRequest("A reason is required.".to_owned()));
+  }
+
+  let question = questions_dao.update_question_status(question_uuid, status, reason.clone()).await;
+
+  match question {
+      Ok(Some(question)) => {
+        let action = match status {
+            QuestionStatus::Open => AuditAction::ReopenQuestion,
+            _ => AuditAction::CloseQuestion,
+        };
+        let payload = json!({ "status": status, "reason": reason });
+
+        audit(user, action, AuditTarget::Question, Some(question.question_uuid.to_string()), payload, audit_dao).await;
+        events.publish(question_updated(&question));
+
+        Ok(question)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to change question status: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Answers to questions older than the necro-post threshold are accepted, but come back with
+/// `question_age_warning` and, when the policy asks for review, are flagged for moderators.
+/// Near-duplicates of earlier answers are handled the same way, with `similar_answer_uuid`.
+/// Answers the spam checker flags are held for moderation, like questions, and are not sent to
+/// live viewers. Announcements are refused with `ConflictCode::AnswersDisabled`, and so are
+/// answers by authors over their `PostingQuotaPolicy`, with `PostingQuotaExceeded`.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_answer(
+  answer: Answer,
+  author: Option<&UserDetail>,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+  necro_post_policy: NecroPostPolicy,
+  similar_answer_policy: SimilarAnswerPolicy,
+  now: u64,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+  users_dao: &(dyn UsersDao + Send + Sync),
+  posting_quota_policy: PostingQuotaPolicy,
+  spam_checker: &dyn SpamChecker,
+  client_ip: IpAddr,
+  content_policy: &ContentPolicy,
+  events: &EventBus,
+) -> Result<AnswerDetail, HandlerError> {
+  require_valid(&answer)?;
+  let posting_limit =
+    require_posting_quota(author, PostKind::Answer, posting_quota_policy, moderation_dao, users_dao, now).await?;
+
+  let [content] = content_policy.apply([answer.content]).map_err(HandlerError::ContentPolicyViolation)?;
+  let answer = Answer { content, ..answer };
+
+  let question_age_days = match questions_dao.get_question(answer.question_uuid.to_string(), author.into()).await {
+      Ok(Some(question)) if !question.kind.accepts_answers() => {
+        return Err(HandlerError::ConflictDetail(ConflictDetail {
+          code: ConflictCode::AnswersDisabled,
+          message: "Announcements do not accept answers.".to_owned(),
+        }));
+      }
+      Ok(Some(question)) if question.contest.as_ref().is_some_and(|contest| contest_revealed(contest, now)) => {
+        return Err(HandlerError::ConflictDetail(ConflictDetail {
+          code: ConflictCode::ContestClosed,
+          message: "The contest answers were revealed; it no longer accepts answers.".to_owned(),
+        }));
+      }
+      Ok(Some(question)) if !question.status.accepts_answers() => {
+        return Err(HandlerError::Conflict(format!(
+          "Question is {} and does not accept new answers.",
+          question.status.as_str()
+        )));
+      }
+      Ok(Some(question)) => age_in_days(question.created_at, now),
+      Ok(None) => return Err(HandlerError::BadRequest("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question for answer: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let verdict = spam_verdict(answer.content.clone(), author, client_ip, moderation_dao, spam_checker).await;
+
+  let answer = answers_dao
+    .create_answer(answer, author.map(|author| author.user_uuid.to_string()), posting_limit)
+    .await;
+
+  match answer {
+      Ok(answer) => {
+        let held_for_review =
+          hold_if_spam(verdict, &answer.question_uuid.to_string(), Some(answer.answer_uuid.to_string()), moderation_dao).await;
+
+        if !held_for_review {
+          events.publish(DomainEvent::AnswerCreated {
+            question_uuid: answer.question_uuid.to_string(),
+            answer_uuid: answer.answer_uuid.to_string(),
+            author_uuid: answer.author_uuid.map(|uuid| uuid.to_string()),
+            content: answer.content.clone(),
+          });
+        }
+
+        let question_age_days = question_age_days.filter(|days| *days > necro_post_policy.warn_after_days);
+
+        if let (Some(days), true) = (question_age_days, necro_post_policy.review) {
+          let flag = Flag {
+            reason: FlagReason::OldQuestion,
+            details: Some(format!("Answer to a question asked {} days ago.", days)),
+          };
+          let flagged = flags_dao
+            .create_flag(answer.question_uuid.to_string(), Some(answer.answer_uuid.to_string()), None, flag)
+            .await;
+
+          if let Err(err) = flagged {
+            error!("Error to flag answer to old question for review: {}", err);
+          }
+        }
+
+        let similar_answer = find_similar_answer(&answer, author.into(), similar_answer_policy, answers_dao).await;
+
+        if let (Some((similar_uuid, score)), true) = (&similar_answer, similar_answer_policy.review) {
+          let flag = Flag {
+            reason: FlagReason::DuplicateAnswer,
+            details: Some(format!("{:.0}% similar to answer {}.", score * 100.0, similar_uuid)),
+          };
+          let flagged = flags_dao
+            .create_flag(answer.question_uuid.to_string(), Some(answer.answer_uuid.to_string()), None, flag)
+            .await;
+
+          if let Err(err) = flagged {
+            error!("Error to flag near-duplicate answer for review: {}", err);
+          }
+        }
+
+        Ok(AnswerDetail {
+          question_age_warning: question_age_days.is_some(),
+          similar_answer_uuid: similar_answer.map(|(similar_uuid, _)| similar_uuid),
+          held_for_review,
+          ..answer
+        })
+      }
+      Err(err) => {
+        error!("Error to create answer: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              DBError::PostingLimitReached => {
+                // An answer posted meanwhile took the author's last place; explain the quota they are over.
+                require_posting_quota(author, PostKind::Answer, posting_quota_policy, moderation_dao, users_dao, now).await?;
+                Err(DBError::PostingLimitReached.into())
+              }
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn read_answers(
+  question_uuid: QuestionId,
+  sort: AnswerSort,
+  viewer: Viewer,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+) -> Result<Vec<AnswerDetail>, HandlerError> {
+  let answers = answers_dao.get_answers(question_uuid.question_uuid.to_string(), sort, viewer).await;
+
+  match answers {
+      Ok(answers) => Ok(answers_with_link_previews(answers, link_previews_dao).await),
+      Err(err) => {
+        error!("Error to list answers: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn delete_answer(
+  answer_uuid: AnswerId,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  let answer_uuid = answer_uuid.answer_uuid.to_string();
+
+  let current = match answers_dao.get_answer(answer_uuid.clone(), Some(user).into()).await {
+      Ok(Some(current)) => current,
+      Ok(None) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read answer for delete: {}", err);
+        return Err(HandlerError::default_internal_error());
+      }
+  };
+
+  require_author_or_moderator(current.author_uuid, user)?;
+
+  let result = answers_dao.delete_answer(answer_uuid.clone()).await;
+
+  if result.is_err() {
+    error!("Error to delete answer: {}", result.err().unwrap());
+    return Err(HandlerError::default_internal_error());
+  }
+
+  audit(user, AuditAction::DeleteAnswer, AuditTarget::Answer, Some(answer_uuid), json!({}), audit_dao).await;
+
+  Ok(())
+}
+
+pub async fn bulk_delete_answers(
+  request: BulkDelete,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<Vec<BulkDeleteResult>, HandlerError> {
+  require_moderator(user)?;
+  require_bulk_delete_size(&request)?;
+
+  let results = answers_dao.delete_answers(request.uuids).await;
+
+  match results {
+      Ok(results) => {
+        audit_deleted(user, AuditAction::DeleteAnswers, AuditTarget::Answer, &results, audit_dao).await;
+        Ok(results)
+      }
+      Err(err) => {
+        error!("Error to bulk delete answers: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn update_answer(
+  answer_uuid: String,
+  update: AnswerUpdate,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  content_policy: &ContentPolicy,
+  events: &EventBus,
+) -> Result<AnswerDetail, HandlerError> {
+  require_valid(&update)?;
+
+  let current = match answers_dao.get_answer(answer_uuid.clone(), Some(user).into()).await {
+      Ok(Some(current)) => current,
+      Ok(None) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read answer for edit: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  require_author_or_moderator(current.author_uuid, user)?;
+
+  let [content] = content_policy.apply([update.content]).map_err(HandlerError::ContentPolicyViolation)?;
+
+  let answer = answers_dao
+    .update_answer(answer_uuid, content, user.user_uuid.to_string())
+    .await;
+
+  match answer {
+      Ok(Some(answer)) => {
+        events.publish(DomainEvent::AnswerUpdated {
+          question_uuid: answer.question_uuid.to_string(),
+          answer_uuid: answer.answer_uuid.to_string(),
+        });
+        Ok(answer)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(err) => {
+        error!("Error to update answer: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Subscribes to the live updates of a question the viewer can read.
+pub async fn subscribe_live_updates(
+  query: LiveQuery,
+  viewer: Viewer,
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+  live_updates: &LiveUpdates,
+) -> Result<broadcast::Receiver<LiveEvent>, HandlerError> {
+  let question = questions_dao.get_question(query.question_uuid.to_string(), viewer).await;
+
+  match question {
+      Ok(Some(question)) => Ok(live_updates.subscribe(&question.question_uuid.to_string())),
+      Ok(None) => Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read question for live updates: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// The question's messages after `query.since`, waiting up to `max_wait` for one. Events the viewer
+/// may not see are left out, so a poll can return early without messages.
+pub async fn poll_live_updates(
+  question_uuid: String,
+  query: LongPollQuery,
+  viewer: Viewer,
+  max_wait: Duration,
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  live_updates: &LiveUpdates,
+) -> Result<LivePoll, HandlerError> {
+  let question = match questions_dao.get_question(question_uuid, viewer.clone()).await {
+      Ok(Some(question)) => question,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question to poll: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let polled = live_updates.poll(&question.question_uuid.to_string(), query.since, max_wait).await;
+  let mut messages = Vec::new();
+
+  if polled.skipped > 0 {
+    messages.push(LiveMessage::Lagged { skipped: polled.skipped });
+  }
+
+  for event in polled.events {
+    if let Some(message) = live_message(question.question_uuid.to_string(), event, viewer.clone(), answers_dao).await {
+      messages.push(message);
+    }
+  }
+
+  Ok(LivePoll { cursor: polled.cursor, messages })
+}
+
+/// The message for `event` as the viewer sees it, or None when the answer is not in their listing:
+/// held for moderation, by a shadow-banned user, or deleted since.
+pub async fn live_message(
+  question_uuid: String,
+  event: LiveEvent,
+  viewer: Viewer,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Option<LiveMessage> {
+  let answer_uuid = match &event {
+      LiveEvent::AnswerCreated { answer_uuid } | LiveEvent::AnswerUpdated { answer_uuid } => answer_uuid,
+  };
+
+  let answers = match answers_dao.get_answers(question_uuid, AnswerSort::default(), viewer).await {
+      Ok(answers) => answers,
+      Err(err) => {
+        error!("Error to read answers for live updates: {}", err);
+        return None;
+      }
+  };
+
+  let answer = answers.into_iter().find(|answer| answer.answer_uuid.to_string() == *answer_uuid)?;
+
+  match event {
+      LiveEvent::AnswerCreated { .. } => Some(LiveMessage::AnswerCreated { answer }),
+      LiveEvent::AnswerUpdated { .. } => Some(LiveMessage::AnswerUpdated { answer }),
+  }
+}
+
+pub async fn read_answer_revisions(
+  answer_uuid: String,
+  viewer: Viewer,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<Vec<AnswerRevision>, HandlerError> {
+  let revisions = answers_dao.get_answer_revisions(answer_uuid.clone(), viewer.clone()).await;
+
+  match revisions {
+      Ok(revisions) if !revisions.is_empty() => Ok(revisions),
+      // Never edited: the current post is the only revision.
+      Ok(_) => match answers_dao.get_answer(answer_uuid, viewer).await {
+          Ok(Some(answer)) => Ok(vec![AnswerRevision {
+            answer_uuid: answer.answer_uuid,
+            revision: 1,
+            content: answer.content,
+            editor_uuid: answer.author_uuid,
+            created_at: answer.created_at,
+          }]),
+          Ok(None) => Err(HandlerError::NotFound("Answer not found.".to_owned())),
+          Err(err) => {
+            error!("Error to read answer revisions: {}", err);
+            Err(err.into())
+          }
+      },
+      Err(err) => {
+        error!("Error to read answer revisions: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn restore_answer(
+  answer_uuid: String,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<AnswerDetail, HandlerError> {
+  require_moderator(user)?;
+
+  let answer = answers_dao.restore_answer(answer_uuid).await;
+
+  match answer {
+      Ok(Some(answer)) => {
+        let target_uuid = Some(answer.answer_uuid.to_string());
+        audit(user, AuditAction::RestoreAnswer, AuditTarget::Answer, target_uuid, json!({}), audit_dao).await;
+        Ok(answer)
+      }
+      Ok(None) => Err(HandlerError::NotFound("No deleted answer found.".to_owned())),
+      Err(err) => {
+        error!("Error to restore answer: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Reports a question the user can read to the moderators.
+pub async fn flag_question(
+  question_uuid: String,
+  flag: Flag,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+  require_valid_flag(&flag)?;
+
+  let question = match questions_dao.get_question(question_uuid, Some(user).into()).await {
+      Ok(Some(question)) => question,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question to flag: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  require_not_author(question.author_uuid, user)?;
+
+  create_flag(question.question_uuid.to_string(), None, flag, user, flags_dao).await
+}
+
+/// Reports an answer the user can read to the moderators.
+pub async fn flag_answer(
+  answer_uuid: String,
+  flag: Flag,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+  require_valid_flag(&flag)?;
+
+  let answer = match answers_dao.get_answer(answer_uuid, Some(user).into()).await {
+      Ok(Some(answer)) => answer,
+      Ok(None) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read answer to flag: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  require_not_author(answer.author_uuid, user)?;
+
+  create_flag(answer.question_uuid.to_string(), Some(answer.answer_uuid.to_string()), flag, user, flags_dao).await
+}
+
+async fn create_flag(
+  question_uuid: String,
+  answer_uuid: Option<String>,
+  flag: Flag,
+  user: &UserDetail,
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+  let flag = flags_dao
+    .create_flag(question_uuid, answer_uuid, Some(user.user_uuid.to_string()), flag)
+    .await;
+
+  match flag {
+      Ok(flag) => Ok(flag),
+      Err(DBError::UniqueViolation(_)) => {
+        Err(HandlerError::Conflict("You already have an open flag on this post.".to_owned()))
+      }
+      Err(err) => {
+        error!("Error to create flag: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Records the user's vote on an answer they can read, other than their own. On contest questions,
+/// votes only count once the answers are revealed.
+pub async fn vote_answer(
+  answer_uuid: String,
+  vote: AnswerVote,
+  user: &UserDetail,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<AnswerScore, HandlerError> {
+  if !(-1..=1).contains(&vote.value) {
+    return Err(HandlerError::BadRequest("A vote is 1, -1, or 0 to withdraw it.".to_owned()));
+  }
+
+  let answer = match answers_dao.get_answer(answer_uuid, Some(user).into()).await {
+      Ok(Some(answer)) => answer,
+      Ok(None) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read answer to vote on: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  if answer.author_uuid == Some(user.user_uuid) {
+    return Err(HandlerError::BadRequest("You cannot vote on your own answer.".to_owned()));
+  }
+
+  let score = answers_dao
+    .vote_answer(answer.answer_uuid.to_string(), user.user_uuid.to_string(), vote.value)
+    .await;
+
+  match score {
+      Ok(score) => Ok(AnswerScore { answer_uuid: answer.answer_uuid, score }),
+      Err(err) => {
+        error!("Error to vote on answer: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Only the question's author accepts one of its answers, which `sort=accepted_first` then lists
+/// first and which counts towards its author's trust for new tags.
+pub async fn accept_answer(
+  question_uuid: String,
+  accept: AcceptAnswer,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  events: &EventBus,
+) -> Result<(), HandlerError> {
+  let question = match questions_dao.get_question(question_uuid, Some(user).into()).await {
+      Ok(Some(question)) => question,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question to accept an answer of: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  if question.author_uuid != Some(user.user_uuid) {
+    return Err(HandlerError::Forbidden("Only the author of the question can accept an answer.".to_owned()));
+  }
+
+  let accepted = questions_dao.accept_answer(question.question_uuid.to_string(), accept.answer_uuid).await;
+
+  match accepted {
+      Ok(true) => {
+        events.publish(question_updated(&question));
+
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("Answer not found.".to_owned())),
+      Err(err) => {
+        error!("Error to accept answer: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// The moderation queue, open flags by default.
+pub async fn read_flags(
+  user: &UserDetail,
+  query: FlagsQuery,
+  page: Pagination,
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<Vec<FlagDetail>, HandlerError> {
+  require_moderator(user)?;
+  require_page_limit(&page)?;
+
+  let flags = flags_dao.get_flags(query.status, page).await;
+
+  match flags {
+      Ok(flags) => Ok(flags),
+      Err(err) => {
+        error!("Error to list flags: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Upholds or dismisses an open flag. Acting on the flagged post is left to the existing
+/// moderation endpoints, such as closing or deleting it.
+pub async fn resolve_flag(
+  flag_uuid: String,
+  resolution: ResolveFlag,
+  user: &UserDetail,
+  flags_dao: &(dyn FlagsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+  require_moderator(user)?;
+
+  if resolution.status == FlagStatus::Open {
+    return Err(HandlerError::BadRequest("A flag can only be resolved as upheld or dismissed.".to_owned()));
+  }
+
+  let payload = json!({ "status": resolution.status, "note": resolution.note });
+  let flag = flags_dao
+    .resolve_flag(flag_uuid, user.user_uuid.to_string(), resolution.status, resolution.note)
+    .await;
+
+  match flag {
+      Ok(Some(flag)) => {
+        audit(user, AuditAction::ResolveFlag, AuditTarget::Flag, Some(flag.flag_uuid.to_string()), payload, audit_dao).await;
+        Ok(flag)
+      }
+      Ok(None) => Err(HandlerError::NotFound("No open flag found.".to_owned())),
+      Err(err) => {
+        error!("Error to resolve flag: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Posts waiting for review: those with open flags and those by new users no moderator has acted on.
+pub async fn read_moderation_queue(
+  user: &UserDetail,
+  query: ModerationQueueQuery,
+  page: Pagination,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+) -> Result<Vec<ModerationItem>, HandlerError> {
+  require_moderator(user)?;
+  require_page_limit(&page)?;
+
+  let items = moderation_dao.get_moderation_queue(query, page).await;
+
+  match items {
+      Ok(items) => Ok(items),
+      Err(err) => {
+        error!("Error to list moderation queue: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Applies a moderator's action to a post, then records it in the moderation log and resolves the
+/// post's open flags with it. Approving a post takes it out of the queue without changing it.
+#[allow(clippy::too_many_arguments)]
+pub async fn moderate_post(
+  action: ModerationAction,
+  user: &UserDetail,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  events: &EventBus,
+) -> Result<ModerationActionDetail, HandlerError> {
+  require_moderator(user)?;
+
+  if action.reason.trim().is_empty() {
+    return Err(HandlerError::BadRequest("A reason is required for moderation actions.".to_owned()));
+  }
+
+  let question = match questions_dao.get_question(action.question_uuid.to_string(), Some(user).into()).await {
+      Ok(Some(question)) => question,
+      Ok(None) => return Err(HandlerError::NotFound("Question not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read question to moderate: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let author_uuid = match &action.answer_uuid {
+      Some(answer_uuid) => match answers_dao.get_answer(answer_uuid.to_string(), Some(user).into()).await {
+          Ok(Some(answer)) if answer.question_uuid == question.question_uuid => answer.author_uuid,
+          Ok(_) => return Err(HandlerError::NotFound("Answer not found.".to_owned())),
+          Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+          Err(err) => {
+            error!("Error to read answer to moderate: {}", err);
+            return Err(err.into());
+          }
+      },
+      None => question.author_uuid,
+  };
+
+  let applied = match (action.action, action.answer_uuid) {
+      (ModerationActionKind::Approve, _) => Ok(()),
+      (ModerationActionKind::Delete, Some(answer_uuid)) => answers_dao.delete_answer(answer_uuid.to_string()).await,
+      (ModerationActionKind::Delete, None) => {
+        questions_dao.delete_question(question.question_uuid.to_string()).await.map(|_| ())
+      }
+      (ModerationActionKind::Edit, Some(answer_uuid)) => {
+        let Some(content) = action.content.clone().filter(|content| !content.trim().is_empty()) else {
+          return Err(HandlerError::BadRequest("Editing an answer requires its new content.".to_owned()));
+        };
+
+        require_valid(&AnswerUpdate { content: content.clone() })?;
+
+        answers_dao
+          .update_answer(answer_uuid.to_string(), content, user.user_uuid.to_string())
+          .await
+          .map(|_| ())
+      }
+      (ModerationActionKind::Edit, None) => {
+        let title = action.title.clone().filter(|title| !title.trim().is_empty());
+        let description = action.content.clone().filter(|content| !content.trim().is_empty());
+
+        if title.is_none() && description.is_none() {
+          return Err(HandlerError::BadRequest("Editing a question requires a new title or content.".to_owned()));
+        }
+
+        let edit = Question {
+          title: title.unwrap_or_else(|| question.title.clone()),
+          description: description.unwrap_or_else(|| question.description.clone()),
+          visibility: question.visibility,
+          board_uuid: question.board_uuid,
+          tags: question.tags.clone(),
+          kind: question.kind,
+          // Edits keep the contest whatever this says.
+          contest: None,
+        };
+
+        require_valid(&edit)?;
+
+        questions_dao
+          .update_question(question.question_uuid.to_string(), edit, user.user_uuid.to_string())
+          .await
+          .map(|_| ())
+      }
+      (ModerationActionKind::Lock, _) => questions_dao
+        .update_question_status(question.question_uuid.to_string(), QuestionStatus::Locked, action.reason.clone())
+        .await
+        .map(|_| ()),
+      (ModerationActionKind::Warn, answer_uuid) => {
+        let Some(author_uuid) = author_uuid else {
+          return Err(HandlerError::BadRequest("The post has no author to warn.".to_owned()));
+        };
+
+        notifications_dao
+          .notify_user(
+            author_uuid.to_string(),
+            NotificationKind::ModerationWarning,
+            question.question_uuid.to_string(),
+            answer_uuid.map(|uuid| uuid.to_string()),
+            Some(user.user_uuid.to_string()),
+          )
+          .await
+      }
+  };
+
+  if let Err(err) = applied {
+    error!("Error to apply moderation action: {}", err);
+    return Err(err.into());
+  }
+
+  let recorded = moderation_dao
+    .record_moderation_action(
+      user.user_uuid.to_string(),
+      question.question_uuid.to_string(),
+      action.answer_uuid.map(|uuid| uuid.to_string()),
+      action.action,
+      action.reason,
+    )
+    .await;
+
+  match recorded {
+      Ok(recorded) => {
+        let (target_type, target_uuid) = match &recorded.answer_uuid {
+            Some(answer_uuid) => (AuditTarget::Answer, *answer_uuid),
+            None => (AuditTarget::Question, recorded.question_uuid),
+        };
+        let payload = json!({
+          "action": recorded.action,
+          "reason": recorded.reason,
+          "moderation_action_uuid": recorded.action_uuid,
+        });
+
+        audit(user, AuditAction::ModeratePost, target_type, Some(target_uuid.to_string()), payload, audit_dao).await;
+
+        // Approving releases a held post, so every action but a warning changes what is shown.
+        match (recorded.action, recorded.answer_uuid) {
+            (ModerationActionKind::Warn, _) => {}
+            (ModerationActionKind::Delete, None) => events.publish(DomainEvent::QuestionDeleted {
+              question_uuid: recorded.question_uuid.to_string(),
+            }),
+            _ => events.publish(question_updated(&question)),
+        }
+
+        Ok(recorded)
+      }
+      Err(err) => {
+        error!("Error to record moderation action: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// The moderation log, newest first.
+pub async fn read_moderation_actions(
+  user: &UserDetail,
+  page: Pagination,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+) -> Result<Vec<ModerationActionDetail>, HandlerError> {
+  require_moderator(user)?;
+  require_page_limit(&page)?;
+
+  let actions = moderation_dao.get_moderation_actions(page).await;
+
+  match actions {
+      Ok(actions) => Ok(actions),
+      Err(err) => {
+        error!("Error to list moderation actions: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// The UTC time of `value`, the query parameter `name`, if it is set.
+fn timestamp_param(name: &str, value: Option<String>) -> Result<Option<PrimitiveDateTime>, HandlerError> {
+  let Some(value) = value else {
+    return Ok(None);
+  };
+
+  match OffsetDateTime::parse(&value, &Rfc3339) {
+      Ok(at) => {
+        let at = at.to_offset(UtcOffset::UTC);
+        Ok(Some(PrimitiveDateTime::new(at.date(), at.time())))
+      }
+      Err(_) => Err(HandlerError::BadRequest(format!(
+        "{} must be an RFC 3339 timestamp, such as 2024-01-31T12:00:00Z.",
+        name
+      ))),
+  }
+}
+
+/// The audit log of privileged actions, oldest first from `since`.
+pub async fn read_audit_log(
+  user: &UserDetail,
+  query: AuditQuery,
+  page: Pagination,
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<Vec<AuditRecord>, HandlerError> {
+  require_admin(user)?;
+  require_page_limit(&page)?;
+
+  let since = timestamp_param("since", query.since)?;
+
+  let records = audit_dao.get_audit_log(since, page).await;
+
+  match records {
+      Ok(records) => Ok(records),
+      Err(err) => {
+        error!("Error to read audit log: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Tag autocompletion. Widgets call this on every keystroke, so each client is rate limited and
+/// results are cached briefly per tenant and prefix.
+pub async fn suggest_tags(
+  query: TagSuggestQuery,
+  client_ip: IpAddr,
+  now: u64,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  cache: &TtlCache<Vec<TagUsage>>,
+  limiter: &RateLimiter,
+) -> Result<Vec<TagUsage>, HandlerError> {
+  if let Err(retry_after) = limiter.check(client_ip, now) {
+    return Err(HandlerError::TooManyRequests("Too many tag suggestion requests.".to_owned(), retry_after));
+  }
+
+  let prefix = query.q.trim().to_lowercase();
+
+  // Nothing can match a prefix no tag could start with; this also keeps LIKE wildcards out.
+  if prefix.chars().count() > Question::MAX_TAG_CHARS || !prefix.chars().all(is_tag_char) {
+    return Ok(Vec::new());
+  }
+
+  let key = format!("{}:{}", current_tenant().map(|tenant| tenant.to_string()).unwrap_or_default(), prefix);
+
+  if let Some(tags) = cache.get(&key, now) {
+    return Ok(tags);
+  }
+
+  let tags = tags_dao.get_tag_suggestions(prefix, TagUsage::MAX_SUGGESTIONS).await;
+
+  match tags {
+      Ok(tags) => {
+        cache.insert(key, tags.clone(), now);
+        Ok(tags)
+      }
+      Err(err) => {
+        error!("Error to suggest tags: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Statistics for a tag's landing page. They take several aggregate queries, so they are cached per
+/// tenant and tag.
+pub async fn read_tag_stats(
+  name: String,
+  now: u64,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  cache: &TtlCache<TagStats>,
+) -> Result<TagStats, HandlerError> {
+  let name = normalized_tags(vec![name])?.remove(0);
+  let key = format!("{}:{}", current_tenant().map(|tenant| tenant.to_string()).unwrap_or_default(), name);
+
+  if let Some(stats) = cache.get(&key, now) {
+    return Ok(stats);
+  }
+
+  let stats = tags_dao.get_tag_stats(name).await;
+
+  match stats {
+      Ok(Some(stats)) => {
+        cache.insert(key, stats.clone(), now);
+        Ok(stats)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Tag not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read tag stats: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Compiles the tag's accepted answers into documentation sections linking back to `forum_url`.
+/// The Atom feed of the newest public questions, or of those tagged `tag`.
+pub async fn read_questions_feed(
+  tag: Option<String>,
+  forum_url: &str,
+  now: u64,
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+) -> Result<String, HandlerError> {
+  let tag = tag.map(|tag| normalized_tags(vec![tag])).transpose()?.map(|mut tags| tags.remove(0));
+  let entries = questions_dao.get_feed_entries(tag.clone(), FeedEntry::MAX_ENTRIES).await;
+
+  let entries = match entries {
+      Ok(entries) => entries,
+      Err(err) => {
+        error!("Error to read questions feed: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  let base_url = forum_url.trim_end_matches('/');
+  let (title, self_url) = match &tag {
+      Some(tag) => (
+        format!("Rust Programming Forum: questions tagged {}", tag),
+        format!("{}{}/feeds/tag/{}.atom", base_url, API_V1, tag),
+      ),
+      None => (
+        "Rust Programming Forum: newest questions".to_owned(),
+        format!("{}{}/feeds/questions.atom", base_url, API_V1),
+      ),
+  };
+
+  Ok(atom_feed(&title, &self_url, forum_url, &entries, now))
+}
+
+/// Lists one sitemap per `SitemapUrl::PER_SITEMAP` public questions.
+pub async fn read_sitemap_index(
+  forum_url: &str,
+  questions_dao: &(dyn QuestionsDao + Send + Sync),
+) -> Result<String, HandlerError> {
+  let count = questions_dao.count_sitemap_questions().await;
+
+  match count {
+      Ok(count) => Ok(sitemap_index(forum_url, (count + SitemapUrl::PER_SITEMAP - 1) / SitemapUrl::PER_SITEMAP)),
+      Err(err) => {
+        error!("Error to count sitemap questions: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Exports hold every question, private and held ones included, so only admins may make them.
+pub fn authorize_export(user: &UserDetail) -> Result<(), HandlerError> {
+  require_admin(user)
+}
+
+/// The columns, out of `available`, and the date range of a CSV export; see `ExportQuery`.
+pub fn export_selection(
+  user: &UserDetail,
+  query: ExportQuery,
+  available: &'static [&'static str],
+) -> Result<(Vec<&'static str>, ExportRange), HandlerError> {
+  authorize_export(user)?;
+
+  let columns = export::columns(query.columns.as_deref(), available).map_err(HandlerError::BadRequest)?;
+  let range = ExportRange {
+    from: timestamp_param("from", query.from)?,
+    to: timestamp_param("to", query.to)?,
+  };
+
+  Ok((columns, range))
+}
+
+/// The page of sitemap `file`, such as `questions-1.xml`.
+pub fn sitemap_page(file: &str) -> Result<i64, HandlerError> {
+  parse_sitemap_file(file).ok_or_else(|| HandlerError::NotFound("Sitemap not found.".to_owned()))
+}
+
+pub async fn export_knowledge_base(
+  name: String,
+  forum_url: &str,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<KbExport, HandlerError> {
+  let name = normalized_tags(vec![name])?.remove(0);
+  let answers = tags_dao.get_accepted_answers(name.clone()).await;
+
+  match answers {
+      Ok(answers) => Ok(KbExport {
+        tag: name,
+        sections: answers
+          .into_iter()
+          .map(|answer| {
+            let source_url = format!("{}/question/{}", forum_url.trim_end_matches('/'), answer.question_uuid);
+
+            KbSection {
+              markdown: format!("## {}\n\n{}\n\nSource: <{}>\n", answer.title, answer.answer.trim(), source_url),
+              question_uuid: answer.question_uuid,
+              answer_uuid: answer.answer_uuid,
+              title: answer.title,
+              source_url,
+            }
+          })
+          .collect(),
+      }),
+      Err(err) => {
+        error!("Error to export knowledge base: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn set_faq_entry(
+  question_uuid: String,
+  selection: FaqSelection,
+  user: &UserDetail,
+  faq_dao: &(dyn FaqDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  require_moderator(user)?;
+
+  let set = faq_dao
+    .set_faq_entry(question_uuid.clone(), selection.answer_uuid.to_string(), user.user_uuid.to_string())
+    .await;
+
+  match set {
+      Ok(true) => {
+        let payload = json!({ "answer_uuid": selection.answer_uuid });
+        audit(user, AuditAction::SetFaqEntry, AuditTarget::Question, Some(question_uuid), payload, audit_dao).await;
+
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("Answer not found on this question.".to_owned())),
+      Err(err) => {
+        error!("Error to set FAQ entry: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn remove_faq_entry(
+  question_uuid: String,
+  user: &UserDetail,
+  faq_dao: &(dyn FaqDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  require_moderator(user)?;
+
+  let removed = faq_dao.remove_faq_entry(question_uuid.clone()).await;
+
+  match removed {
+      Ok(true) => {
+        audit(user, AuditAction::RemoveFaqEntry, AuditTarget::Question, Some(question_uuid), json!({}), audit_dao).await;
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("Question is not in the FAQ.".to_owned())),
+      Err(err) => {
+        error!("Error to remove FAQ entry: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Cached per tenant and grouping, so curation changes show up once the cached FAQ expires.
+pub async fn read_faq(
+  query: FaqQuery,
+  now: u64,
+  faq_dao: &(dyn FaqDao + Send + Sync),
+  cache: &TtlCache<Vec<FaqGroup>>,
+) -> Result<Vec<FaqGroup>, HandlerError> {
+  let key = format!(
+    "{}:{}",
+    current_tenant().map(|tenant| tenant.to_string()).unwrap_or_default(),
+    query.group_by.as_str()
+  );
+
+  if let Some(faq) = cache.get(&key, now) {
+    return Ok(faq);
+  }
+
+  let entries = faq_dao.get_faq_entries().await;
+
+  match entries {
+      Ok(entries) => {
+        let faq = faq_groups(entries, query.group_by);
+        cache.insert(key, faq.clone(), now);
+        Ok(faq)
+      }
+      Err(err) => {
+        error!("Error to read FAQ: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+fn faq_groups(entries: Vec<FaqEntry>, group_by: FaqGrouping) -> Vec<FaqGroup> {
+  let mut groups: BTreeMap<String, Vec<FaqEntry>> = BTreeMap::new();
+  let mut ungrouped = Vec::new();
+
+  for entry in entries {
+    let names = match group_by {
+        FaqGrouping::Tag => entry.tags.clone(),
+        FaqGrouping::Board => entry.board_name.clone().into_iter().collect(),
+    };
+
+    if names.is_empty() {
+      ungrouped.push(entry);
+      continue;
+    }
+
+    for name in names {
+      groups.entry(name).or_default().push(entry.clone());
+    }
+  }
+
+  let mut faq: Vec<FaqGroup> = groups
+    .into_iter()
+    .map(|(name, entries)| FaqGroup { name: Some(name), entries })
+    .collect();
+
+  if !ungrouped.is_empty() {
+    faq.push(FaqGroup { name: None, entries: ungrouped });
+  }
+
+  faq
+}
+
+pub async fn read_pending_tags(
+  user: &UserDetail,
+  page: Pagination,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<Vec<PendingTag>, HandlerError> {
+  require_moderator(user)?;
+  require_page_limit(&page)?;
+
+  let tags = tags_dao.get_pending_tags(page).await;
+
+  match tags {
+      Ok(tags) => Ok(tags),
+      Err(err) => {
+        error!("Error to list pending tags: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Approving a tag adds it to the questions that proposed it, after which anyone can use it.
+pub async fn approve_pending_tag(
+  name: String,
+  user: &UserDetail,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<PendingTagResolution, HandlerError> {
+  require_moderator(user)?;
+
+  let name = normalized_tags(vec![name])?.remove(0);
+  let resolution = tags_dao.approve_pending_tag(name.clone()).await;
+
+  match resolution {
+      Ok(Some(resolution)) => {
+        let payload = json!({ "questions": resolution.questions });
+        audit(user, AuditAction::ApproveTag, AuditTarget::Tag, Some(name), payload, audit_dao).await;
+
+        Ok(resolution)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Pending tag not found.".to_owned())),
+      Err(err) => {
+        error!("Error to approve pending tag: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn reject_pending_tag(
+  name: String,
+  user: &UserDetail,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<PendingTagResolution, HandlerError> {
+  require_moderator(user)?;
+
+  let name = normalized_tags(vec![name])?.remove(0);
+  let resolution = tags_dao.reject_pending_tag(name.clone()).await;
+
+  match resolution {
+      Ok(Some(resolution)) => {
+        let payload = json!({ "questions": resolution.questions });
+        audit(user, AuditAction::RejectTag, AuditTarget::Tag, Some(name), payload, audit_dao).await;
+
+        Ok(resolution)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Pending tag not found.".to_owned())),
+      Err(err) => {
+        error!("Error to reject pending tag: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn save_question_draft(
+  draft: QuestionDraft,
+  user: &UserDetail,
+  drafts_dao: &(dyn DraftsDao + Send + Sync),
+) -> Result<DraftDetail, HandlerError> {
+  require_valid(&draft)?;
+
+  let draft = drafts_dao.save_question_draft(user.user_uuid.to_string(), draft).await;
+
+  match draft {
+      Ok(draft) => Ok(draft),
+      Err(err) => {
+        error!("Error to save question draft: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn publish_draft(
+  draft_uuid: String,
+  user: &UserDetail,
+  drafts_dao: &(dyn DraftsDao + Send + Sync),
+  events: &EventBus,
+) -> Result<QuestionDetail, HandlerError> {
+  // Drafts belonging to other users are reported as missing rather than forbidden.
+  let draft = match drafts_dao.get_draft(draft_uuid.clone(), user.user_uuid.to_string()).await {
+      Ok(Some(draft)) => draft,
+      Ok(None) => return Err(HandlerError::NotFound("Draft not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read draft: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  if draft.title.trim().is_empty() || draft.description.trim().is_empty() {
+    return Err(HandlerError::BadRequest("A title and description are required to publish a draft.".to_owned()));
+  }
+
+  let question = drafts_dao.publish_draft(draft_uuid, user.user_uuid.to_string()).await;
+
+  match question {
+      Ok(Some(question)) => {
+        events.publish(question_created(&question));
+
+        Ok(question)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Draft not found.".to_owned())),
+      Err(err) => {
+        error!("Error to publish draft: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn create_board(
+  board: Board,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<BoardDetail, HandlerError> {
+  if board.name.trim().is_empty() {
+    return Err(HandlerError::BadRequest("Board name must not be empty.".to_owned()));
+  }
+
+  let board = boards_dao.create_board(board, user.user_uuid.to_string()).await;
+
+  match board {
+      Ok(board) => Ok(board),
+      Err(err) => {
+        error!("Error to create board: {}", err);
+
+          match err {
+              DBError::UniqueViolation(_) => Err(HandlerError::Conflict("Board name is already taken.".to_owned())),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn read_board_members(
+  board_uuid: String,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<Vec<BoardMember>, HandlerError> {
+  require_board_owner(&board_uuid, user, boards_dao).await?;
+
+  let members = boards_dao.get_board_members(board_uuid).await;
+
+  match members {
+      Ok(members) => Ok(members),
+      Err(err) => {
+        error!("Error to list board members: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn invite_board_member(
+  board_uuid: String,
+  invite: BoardInvite,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<BoardMember, HandlerError> {
+  require_board_owner(&board_uuid, user, boards_dao).await?;
+
+  let member = boards_dao.invite_board_member(board_uuid, invite.user_uuid.to_string(), invite.role).await;
+
+  match member {
+      Ok(member) => Ok(member),
+      Err(err) => {
+        error!("Error to invite board member: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn join_board(
+  board_uuid: String,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<BoardMember, HandlerError> {
+  let member = boards_dao.request_board_membership(board_uuid, user.user_uuid.to_string()).await;
+
+  match member {
+      Ok(member) => Ok(member),
+      Err(err) => {
+        error!("Error to join board: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn approve_board_member(
+  board_uuid: String,
+  member_uuid: String,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<BoardMember, HandlerError> {
+  require_board_owner(&board_uuid, user, boards_dao).await?;
+
+  let member = boards_dao.approve_board_member(board_uuid, member_uuid).await;
+
+  match member {
+      Ok(Some(member)) => Ok(member),
+      Ok(None) => Err(HandlerError::NotFound("No pending join request found.".to_owned())),
+      Err(err) => {
+        error!("Error to approve board member: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn remove_board_member(
+  board_uuid: String,
+  member_uuid: String,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  // Members may always leave a board; removing anyone else takes an owner.
+  if member_uuid != user.user_uuid.to_string() {
+    require_board_owner(&board_uuid, user, boards_dao).await?;
+  }
+
+  let result = boards_dao.remove_board_member(board_uuid, member_uuid).await;
+
+  match result {
+      Ok(()) => Ok(()),
+      Err(err) => {
+        error!("Error to remove board member: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn read_board_tag_rules(
+  board_uuid: String,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<BoardTagRulesDetail, HandlerError> {
+  let rules = tags_dao.get_board_tag_rules(board_uuid).await;
+
+  match rules {
+      Ok(Some(rules)) => Ok(rules),
+      Ok(None) => Err(HandlerError::NotFound("Board has no tag rules.".to_owned())),
+      Err(err) => {
+        error!("Error to read board tag rules: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Replaces the board's tag rules. Existing questions are checked against them on their next edit.
+pub async fn set_board_tag_rules(
+  board_uuid: String,
+  rules: BoardTagRules,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<BoardTagRulesDetail, HandlerError> {
+  require_board_owner(&board_uuid, user, boards_dao).await?;
+
+  let rules = normalized_tag_rules(rules)?;
+  let rules = tags_dao.set_board_tag_rules(board_uuid, rules, user.user_uuid.to_string()).await;
+
+  match rules {
+      Ok(Some(rules)) => Ok(rules),
+      Ok(None) => Err(HandlerError::NotFound("Board not found.".to_owned())),
+      Err(err) => {
+        error!("Error to save board tag rules: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn delete_board_tag_rules(
+  board_uuid: String,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  require_board_owner(&board_uuid, user, boards_dao).await?;
+
+  let deleted = tags_dao.delete_board_tag_rules(board_uuid).await;
+
+  match deleted {
+      Ok(true) => Ok(()),
+      Ok(false) => Err(HandlerError::NotFound("Board has no tag rules.".to_owned())),
+      Err(err) => {
+        error!("Error to delete board tag rules: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn create_user(
+  user: User,
+  client_ip: String,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<UserCredentials, HandlerError> {
+  if user.username.trim().is_empty() {
+    return Err(HandlerError::BadRequest("Username must not be empty.".to_owned()));
+  }
+
+  let api_token = generate_api_token();
+  let user = users_dao.create_user(user, hash_api_token(&api_token)).await;
+
+  match user {
+      Ok(user) => {
+        if let Err(err) = users_dao.record_user_ip(user.user_uuid.to_string(), client_ip).await {
+          error!("Error to record user IP: {}", err);
+        }
+
+        Ok(UserCredentials { user, api_token })
+      }
+      Err(err) => {
+        error!("Error to create user: {}", err);
+
+          match err {
+              DBError::UniqueViolation(_) => Err(HandlerError::Conflict("Username is already taken.".to_owned())),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn authenticate(
+  api_token: &str,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<UserDetail, HandlerError> {
+  let user = users_dao.get_user_by_token_hash(hash_api_token(api_token)).await;
+
+  match user {
+      Ok(Some(user)) => Ok(user),
+      Ok(None) => Err(HandlerError::Unauthorized("Invalid API token.".to_owned())),
+      Err(err) => {
+        error!("Error to authenticate user: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub fn authenticate_scim(token: &str, config: Option<&ScimConfig>) -> Result<(), HandlerError> {
+  let Some(config) = config else {
+    return Err(HandlerError::NotFound("SCIM provisioning is not enabled.".to_owned()));
+  };
+
+  if hash_api_token(token) != config.token_hash {
+    return Err(HandlerError::Unauthorized("Invalid provisioning token.".to_owned()));
+  }
+
+  Ok(())
+}
+
+/// Provisioned users get an API token like everyone else, but it is never disclosed over SCIM.
+pub async fn scim_create_user(
+  user: ScimUser,
+  config: &ScimConfig,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ScimUser, HandlerError> {
+  if user.user_name.trim().is_empty() {
+    return Err(HandlerError::BadRequest("userName must not be empty.".to_owned()));
+  }
+
+  let user = users_dao
+    .provision_user(user.into_provisioned(&config.group_roles), hash_api_token(&generate_api_token()))
+    .await;
+
+  match user {
+      Ok(user) => Ok(user.into()),
+      Err(err) => {
+        error!("Error to provision user: {}", err);
+
+          match err {
+              DBError::UniqueViolation(_) => Err(HandlerError::Conflict("User already exists.".to_owned())),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn scim_read_user(
+  user_uuid: String,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ScimUser, HandlerError> {
+  let user = users_dao.get_provisioned_user(user_uuid).await;
+
+  match user {
+      Ok(Some(user)) => Ok(user.into()),
+      Ok(None) => Err(HandlerError::NotFound("User not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read provisioned user: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn scim_read_users(
+  filter: Option<String>,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ScimListResponse, HandlerError> {
+  let username = filter
+    .as_deref()
+    .map(parse_user_name_filter)
+    .transpose()
+    .map_err(HandlerError::BadRequest)?;
+
+  let users = users_dao.get_provisioned_users(username).await;
+
+  match users {
+      Ok(users) => Ok(users.into_iter().map(ScimUser::from).collect::<Vec<_>>().into()),
+      Err(err) => {
+        error!("Error to list provisioned users: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn scim_replace_user(
+  user_uuid: String,
+  user: ScimUser,
+  config: &ScimConfig,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ScimUser, HandlerError> {
+  if user.user_name.trim().is_empty() {
+    return Err(HandlerError::BadRequest("userName must not be empty.".to_owned()));
+  }
+
+  let user = users_dao
+    .update_provisioned_user(user_uuid, user.into_provisioned(&config.group_roles))
+    .await;
+
+  match user {
+      Ok(Some(user)) => Ok(user.into()),
+      Ok(None) => Err(HandlerError::NotFound("User not found.".to_owned())),
+      Err(err) => {
+        error!("Error to update provisioned user: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              DBError::UniqueViolation(_) => Err(HandlerError::Conflict("User already exists.".to_owned())),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn scim_patch_user(
+  user_uuid: String,
+  patch: ScimPatch,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ScimUser, HandlerError> {
+  let active = patched_active(&patch).map_err(HandlerError::BadRequest)?;
+
+  set_provisioned_user_active(user_uuid, active, users_dao)
+    .await
+    .map(ScimUser::from)
+}
+
+/// Deprovisioning deactivates the account so the user's posts keep their author.
+pub async fn scim_delete_user(
+  user_uuid: String,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  set_provisioned_user_active(user_uuid, false, users_dao)
+    .await
+    .map(|_| ())
+}
+
+async fn set_provisioned_user_active(
+  user_uuid: String,
+  active: bool,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<ProvisionedUserDetail, HandlerError> {
+  let user = users_dao.set_user_active(user_uuid, active).await;
+
+  match user {
+      Ok(Some(user)) => Ok(user),
+      Ok(None) => Err(HandlerError::NotFound("User not found.".to_owned())),
+      Err(err) => {
+        error!("Error to change user activation: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn create_invitation(
+  invitation: Invitation,
+  user: &UserDetail,
+  invitations_dao: &(dyn InvitationsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<InvitationLink, HandlerError> {
+  require_admin(user)?;
+
+  if invitation.expires_in_seconds == 0 || invitation.expires_in_seconds > Invitation::MAX_EXPIRES_IN_SECONDS {
+    return Err(HandlerError::BadRequest(format!(
+      "expires_in_seconds must be between 1 and {}.",
+      Invitation::MAX_EXPIRES_IN_SECONDS
+    )));
+  }
+
+  let expires = now + invitation.expires_in_seconds;
+  let invitation = invitations_dao.create_invitation(invitation, user.user_uuid.to_string()).await;
+
+  let invitation = match invitation {
+      Ok(invitation) => invitation,
+      Err(err) => {
+        error!("Error to create invitation: {}", err);
+
+          return match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          };
+      }
+  };
+
+  let target_uuid = Some(invitation.invitation_uuid.to_string());
+  let payload = json!({ "board_uuid": invitation.board_uuid, "role": invitation.role });
+  audit(user, AuditAction::CreateInvitation, AuditTarget::Invitation, target_uuid, payload, audit_dao).await;
+
+  let signature = url_signer.sign(&invitation_path(&invitation.invitation_uuid.to_string()), expires);
+
+  Ok(InvitationLink {
+    url: format!(
+      "{}/users?invitation_uuid={}&expires={}&key_id={}&signature={}",
+      API_V1, invitation.invitation_uuid, signature.expires, signature.key_id, signature.signature
+    ),
+    expires: signature.expires,
+    invitation,
+  })
+}
+
+pub async fn read_invitations(
+  user: &UserDetail,
+  invitations_dao: &(dyn InvitationsDao + Send + Sync),
+) -> Result<Vec<InvitationDetail>, HandlerError> {
+  require_admin(user)?;
+
+  let invitations = invitations_dao.get_invitations().await;
+
+  match invitations {
+      Ok(invitations) => Ok(invitations),
+      Err(err) => {
+        error!("Error to list invitations: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Registers a user through an invitation link, then grants the invitation's role and board.
+pub async fn create_invited_user(
+  user: User,
+  client_ip: String,
+  acceptance: InvitationAcceptance,
+  users_dao: &(dyn UsersDao + Send + Sync),
+  invitations_dao: &(dyn InvitationsDao + Send + Sync),
+  url_signer: &UrlSigner,
+  now: u64,
+) -> Result<UserCredentials, HandlerError> {
+  let signature = UrlSignature {
+    expires: acceptance.expires,
+    key_id: acceptance.key_id,
+    signature: acceptance.signature,
+  };
+
+  match url_signer.verify(&invitation_path(&acceptance.invitation_uuid.to_string()), &signature, now) {
+      Ok(()) => {}
+      Err(SigningError::Expired) => return Err(HandlerError::Forbidden("Invitation has expired.".to_owned())),
+      Err(_) => return Err(HandlerError::Forbidden("Invalid invitation link.".to_owned())),
+  }
+
+  match invitations_dao.get_pending_invitation(acceptance.invitation_uuid.to_string()).await {
+      Ok(Some(_)) => {}
+      Ok(None) => return Err(HandlerError::Conflict("Invitation has already been used or has expired.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read invitation: {}", err);
+        return Err(err.into());
+      }
+  }
+
+  let mut credentials = create_user(user, client_ip, users_dao).await?;
+
+  // The account exists at this point, so its credentials are returned even if another
+  // registration used the invitation first.
+  match invitations_dao
+    .accept_invitation(acceptance.invitation_uuid.to_string(), credentials.user.user_uuid.to_string())
+    .await
+  {
+      Ok(Some(invitation)) => {
+        if let Some(role) = invitation.role {
+          credentials.user.role = role;
+        }
+      }
+      Ok(None) => warn!("Invitation was used before user {} could accept it.", credentials.user.user_uuid),
+      Err(err) => error!("Error to accept invitation: {}", err),
+  }
+
+  Ok(credentials)
+}
+
+/// Signs a user in through the configured directory, creating their account on first sign-in and
+/// refreshing their profile and role afterwards. Every sign-in issues a new API token.
+pub async fn sign_in(
+  credentials: SignIn,
+  client_ip: String,
+  auth_backend: Option<&dyn AuthBackend>,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<UserCredentials, HandlerError> {
+  let Some(auth_backend) = auth_backend else {
+    return Err(HandlerError::NotFound("Directory sign-in is not enabled.".to_owned()));
+  };
+
+  let user = match auth_backend.authenticate(&credentials.username, &credentials.password).await {
+      Ok(Some(user)) => user,
+      Ok(None) => return Err(HandlerError::Unauthorized("Invalid username or password.".to_owned())),
+      Err(err) => {
+        error!("Error to authenticate with directory: {}", err);
+        return Err(HandlerError::default_internal_error());
+      }
+  };
+
+  let api_token = generate_api_token();
+
+  match users_dao.upsert_directory_user(user, hash_api_token(&api_token)).await {
+      Ok(Some(user)) => {
+        if let Err(err) = users_dao.record_user_ip(user.user.user_uuid.to_string(), client_ip).await {
+          error!("Error to record user IP: {}", err);
+        }
+
+        Ok(UserCredentials { user: user.user, api_token })
+      }
+      Ok(None) => Err(HandlerError::Forbidden("Account has been deactivated.".to_owned())),
+      Err(err) => {
+        error!("Error to sign in user: {}", err);
+
+          match err {
+              DBError::UniqueViolation(_) => Err(HandlerError::Conflict("Username is already taken.".to_owned())),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// A user's questions for their profile page, newest first.
+pub async fn read_user_questions(
+  user_uuid: String,
+  page: Pagination,
+  viewer: Viewer,
+  questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+  require_page_limit(&page)?;
+
+  let questions = questions_dao.get_questions_by_author(user_uuid, page, viewer).await;
+
+  match questions {
+      Ok(questions) => Ok(questions),
+      Err(err) => {
+        error!("Error to list user questions: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn read_accept_suggestion_settings(
+  user: &UserDetail,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<AcceptSuggestionSettings, HandlerError> {
+  let enabled = notifications_dao.get_accept_suggestions_enabled(user.user_uuid.to_string()).await;
+
+  match enabled {
+      Ok(enabled) => Ok(AcceptSuggestionSettings { enabled }),
+      Err(err) => {
+        error!("Error to read accept suggestion settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Opts the user in or out of accepted-answer suggestions for their questions.
+pub async fn update_accept_suggestion_settings(
+  user: &UserDetail,
+  settings: AcceptSuggestionSettings,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<AcceptSuggestionSettings, HandlerError> {
+  let updated = notifications_dao
+    .set_accept_suggestions_enabled(user.user_uuid.to_string(), settings.enabled)
+    .await;
+
+  match updated {
+      Ok(()) => Ok(settings),
+      Err(err) => {
+        error!("Error to update accept suggestion settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Every notification kind with its channels, the defaults included.
+pub async fn read_notification_settings(
+  user: &UserDetail,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<NotificationSettings, HandlerError> {
+  let settings = notifications_dao.get_notification_settings(user.user_uuid.to_string()).await;
+
+  match settings {
+      Ok(settings) => Ok(NotificationSettings {
+        kinds: NotificationKind::ALL
+          .into_iter()
+          .map(|kind| {
+            let configured = settings.kinds.iter().find(|settings| settings.kind == kind);
+
+            NotificationKindSettings {
+              kind,
+              channels: configured.map_or_else(NotificationChannels::default, |settings| settings.channels),
+            }
+          })
+          .collect(),
+        ..settings
+      }),
+      Err(err) => {
+        error!("Error to read notification settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Sets the channels of each listed notification kind; the others get the default channels back.
+/// The webhook channel needs a `webhook_url`, whose signing secret is returned when it changes.
+pub async fn update_notification_settings(
+  user: &UserDetail,
+  settings: NotificationSettings,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<NotificationSettings, HandlerError> {
+  for (i, kind_settings) in settings.kinds.iter().enumerate() {
+    if settings.kinds[..i].iter().any(|other| other.kind == kind_settings.kind) {
+      return Err(HandlerError::BadRequest(format!(
+        "Notification kind {} is listed more than once.",
+        kind_settings.kind.as_str()
+      )));
+    }
+  }
+
+  match &settings.webhook_url {
+      Some(url) => require_webhook_url(url)?,
+      None if settings.kinds.iter().any(|settings| settings.channels.webhook) => {
+        return Err(HandlerError::BadRequest("The webhook channel needs a webhook_url.".to_owned()));
+      }
+      None => {}
+  }
+
+  let secret = hex::encode(rand::random::<[u8; 32]>());
+  let updated = notifications_dao
+    .set_notification_settings(user.user_uuid.to_string(), settings.clone(), secret)
+    .await;
+
+  match updated {
+      Ok(webhook_secret) => {
+        let settings = read_notification_settings(user, notifications_dao).await?;
+
+        Ok(NotificationSettings { webhook_secret, ..settings })
+      }
+      Err(err) => {
+        error!("Error to update notification settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_digest_settings(
+  user: &UserDetail,
+  email_digests_dao: &(dyn EmailDigestsDao + Send + Sync),
+) -> Result<DigestSettings, HandlerError> {
+  let settings = email_digests_dao.get_digest_settings(user.user_uuid.to_string()).await;
+
+  match settings {
+      Ok(settings) => Ok(settings),
+      Err(err) => {
+        error!("Error to read digest settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Subscribes the user to a daily or weekly email digest, or unsubscribes them with `off`.
+pub async fn update_digest_settings(
+  user: &UserDetail,
+  settings: DigestSettings,
+  email_digests_dao: &(dyn EmailDigestsDao + Send + Sync),
+) -> Result<DigestSettings, HandlerError> {
+  let tags = deduplicated_tags(settings.tags)?;
+
+  if tags.len() > DigestSettings::MAX_TAGS {
+    return Err(HandlerError::BadRequest(format!(
+      "A digest can follow at most {} tags.",
+      DigestSettings::MAX_TAGS
+    )));
+  }
+
+  let settings = DigestSettings { tags, ..settings };
+  let updated = email_digests_dao.set_digest_settings(user.user_uuid.to_string(), settings.clone()).await;
+
+  match updated {
+      Ok(()) => Ok(settings),
+      Err(err) => {
+        error!("Error to update digest settings: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// The user's own questions, answers and drafts, including those hidden from everyone else.
+pub async fn read_my_content(
+  user: &UserDetail,
+  page: Pagination,
+  content_dao: &(dyn ContentDao + Send + Sync),
+) -> Result<MyContent, HandlerError> {
+  require_page_limit(&page)?;
+
+  let content = content_dao.get_user_content(user.user_uuid.to_string(), page).await;
+
+  match content {
+      Ok(content) => Ok(content),
+      Err(err) => {
+        error!("Error to read user content: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn delete_my_drafts(
+  user: &UserDetail,
+  content_dao: &(dyn ContentDao + Send + Sync),
+) -> Result<DeletedDrafts, HandlerError> {
+  let deleted = content_dao.delete_user_drafts(user.user_uuid.to_string()).await;
+
+  match deleted {
+      Ok(deleted) => Ok(DeletedDrafts { deleted }),
+      Err(err) => {
+        error!("Error to delete user drafts: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn unsubscribe_from_everything(
+  user: &UserDetail,
+  content_dao: &(dyn ContentDao + Send + Sync),
+) -> Result<Unsubscribed, HandlerError> {
+  let unsubscribed = content_dao.unsubscribe_user(user.user_uuid.to_string()).await;
+
+  match unsubscribed {
+      Ok(unsubscribed) => Ok(unsubscribed),
+      Err(err) => {
+        error!("Error to unsubscribe user: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_notifications(
+  user: &UserDetail,
+  query: NotificationsQuery,
+  page: Pagination,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<Vec<Notification>, HandlerError> {
+  require_page_limit(&page)?;
+
+  let notifications = notifications_dao
+    .get_notifications(user.user_uuid.to_string(), query.unread, page)
+    .await;
+
+  match notifications {
+      Ok(notifications) => Ok(notifications),
+      Err(err) => {
+        error!("Error to list notifications: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn mark_notification_read(
+  id: i64,
+  user: &UserDetail,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  let marked = notifications_dao.mark_notification_read(user.user_uuid.to_string(), id).await;
+
+  match marked {
+      Ok(true) => Ok(()),
+      Ok(false) => Err(HandlerError::NotFound("Notification not found.".to_owned())),
+      Err(err) => {
+        error!("Error to mark notification read: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn mark_all_notifications_read(
+  user: &UserDetail,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) -> Result<NotificationsRead, HandlerError> {
+  let marked = notifications_dao.mark_all_notifications_read(user.user_uuid.to_string()).await;
+
+  match marked {
+      Ok(read) => Ok(NotificationsRead { read }),
+      Err(err) => {
+        error!("Error to mark notifications read: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_user_profile(
+  user_uuid: String,
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<UserProfile, HandlerError> {
+  match users_dao.get_user_profile(user_uuid).await {
+      Ok(Some(profile)) => Ok(profile),
+      Ok(None) => Err(HandlerError::NotFound("User not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read user profile: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Replaces the user's avatar with `content` rendered at every standard size. The previous avatar's
+/// objects are removed once the new ones are in place.
+pub async fn update_avatar(
+  content: Vec<u8>,
+  user: &UserDetail,
+  users_dao: &(dyn UsersDao + Send + Sync),
+  object_store: &dyn ObjectStore,
+) -> Result<UserProfile, HandlerError> {
+  if content.is_empty() || content.len() > MAX_AVATAR_BYTES {
+    return Err(HandlerError::BadRequest(format!(
+      "Avatars must be between 1 byte and {} MB.",
+      MAX_AVATAR_BYTES / (1024 * 1024)
+    )));
+  }
+
+  if !sniff_content_type(&content).is_some_and(|content_type| content_type.starts_with("image/")) {
+    return Err(HandlerError::BadRequest("Avatars must be PNG, JPEG, GIF or WebP images.".to_owned()));
+  }
+
+  let rendered = match tokio::task::spawn_blocking(move || render_avatar(&content)).await {
+      Ok(Ok(rendered)) => rendered,
+      Ok(Err(err)) => return Err(HandlerError::BadRequest(format!("Invalid avatar image: {}", err))),
+      Err(err) => {
+        error!("Error to render avatar: {}", err);
+        return Err(HandlerError::default_internal_error());
+      }
+  };
+
+  let avatar_key = new_avatar_key();
+
+  for (size, png) in rendered {
+    if let Err(err) = object_store.put(&avatar_object_key(&avatar_key, size), "image/png", png).await {
+      error!("Error to store avatar: {}", err);
+      delete_avatar_objects(&avatar_key, object_store).await;
+      return Err(HandlerError::default_internal_error());
+    }
+  }
+
+  match users_dao.set_avatar_key(user.user_uuid.to_string(), Some(avatar_key.clone())).await {
+      Ok(Some(previous_key)) => delete_avatar_objects(&previous_key, object_store).await,
+      Ok(None) => {}
+      Err(err) => {
+        error!("Error to set avatar: {}", err);
+        delete_avatar_objects(&avatar_key, object_store).await;
+        return Err(err.into());
+      }
+  }
+
+  read_user_profile(user.user_uuid.to_string(), users_dao).await
+}
+
+/// Best effort: a leftover object only wastes storage, so failures are logged and otherwise ignored.
+async fn delete_avatar_objects(avatar_key: &str, object_store: &dyn ObjectStore) {
+  for size in AVATAR_SIZES {
+    if let Err(err) = object_store.delete(&avatar_object_key(avatar_key, size)).await {
+      error!("Error to delete avatar object {}: {}", avatar_object_key(avatar_key, size), err);
+    }
+  }
+}
+
+/// Avatars are public, but only keys in the avatar format are served so other objects stay private.
+pub async fn read_avatar(object_key: String, object_store: &dyn ObjectStore) -> Result<Vec<u8>, HandlerError> {
+  if !is_avatar_object_key(&object_key) {
+    return Err(HandlerError::NotFound("Avatar not found.".to_owned()));
+  }
+
+  match object_store.get(&object_key).await {
+      Ok(Some(content)) => Ok(content),
+      Ok(None) => Err(HandlerError::NotFound("Avatar not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read avatar: {}", err);
+        Err(HandlerError::default_internal_error())
+      }
+  }
+}
+
+/// A user's answers for their profile page, newest first.
+pub async fn read_user_answers(
+  user_uuid: String,
+  page: Pagination,
+  viewer: Viewer,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<Vec<AnswerDetail>, HandlerError> {
+  require_page_limit(&page)?;
+
+  let answers = answers_dao.get_answers_by_author(user_uuid, page, viewer).await;
+
+  match answers {
+      Ok(answers) => Ok(answers),
+      Err(err) => {
+        error!("Error to list user answers: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Shadow-banned users keep posting as usual, but their posts are only shown to them and to
+/// moderators. `banned: false` lifts the shadow ban.
+pub async fn set_shadow_ban(
+  user_uuid: String,
+  banned: bool,
+  user: &UserDetail,
+  users_dao: &(dyn UsersDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+  question_cache: &QuestionCache,
+) -> Result<(), HandlerError> {
+  require_moderator(user)?;
+
+  let updated = users_dao.set_shadow_banned(user_uuid.clone(), banned).await;
+
+  match updated {
+      Ok(true) => {
+        let action = if banned { AuditAction::ShadowBanUser } else { AuditAction::LiftShadowBan };
+        audit(user, action, AuditTarget::User, Some(user_uuid), json!({}), audit_dao).await;
+        // The user's posts may be on any question.
+        question_cache.invalidate_all().await;
+
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("User not found, or is a moderator.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to set shadow ban: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Erases a user for a right-to-be-forgotten request. Their posts stay up without an author; everything
+/// else about them is deleted. The returned report stays downloadable with `read_erasure_report`.
+pub async fn erase_user(
+  user_uuid: String,
+  user: &UserDetail,
+  backup_retention_days: Option<i32>,
+  erasure_dao: &(dyn ErasureDao + Send + Sync),
+  object_store: &dyn ObjectStore,
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<ErasureReport, HandlerError> {
+  require_admin(user)?;
+
+  if user_uuid == user.user_uuid.to_string() {
+    return Err(HandlerError::BadRequest("Admins cannot erase their own account.".to_owned()));
+  }
+
+  let report = erasure_dao.erase_user(user_uuid.clone(), user.user_uuid.to_string(), backup_retention_days).await;
+
+  match report {
+      Ok(Some(report)) => {
+        // The user is already gone, so a failed delete only leaves an unreferenced object behind.
+        for object_key in &report.deleted_objects {
+          if let Err(err) = object_store.delete(object_key).await {
+            error!("Error to delete object {} of erased user: {}", object_key, err);
+          }
+        }
+
+        let payload = json!({ "report_uuid": report.report_uuid });
+        audit(user, AuditAction::EraseUser, AuditTarget::User, Some(user_uuid), payload, audit_dao).await;
+
+        Ok(report)
+      }
+      Ok(None) => Err(HandlerError::NotFound("User not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to erase user: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_erasure_reports(
+  user: &UserDetail,
+  page: Pagination,
+  erasure_dao: &(dyn ErasureDao + Send + Sync),
+) -> Result<Vec<ErasureReport>, HandlerError> {
+  require_admin(user)?;
+  require_page_limit(&page)?;
+
+  let reports = erasure_dao.get_erasure_reports(page).await;
+
+  match reports {
+      Ok(reports) => Ok(reports),
+      Err(err) => {
+        error!("Error to list erasure reports: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_erasure_report(
+  user: &UserDetail,
+  report_uuid: String,
+  erasure_dao: &(dyn ErasureDao + Send + Sync),
+) -> Result<ErasureReport, HandlerError> {
+  require_admin(user)?;
+
+  let report = erasure_dao.get_erasure_report(report_uuid).await;
+
+  match report {
+      Ok(Some(report)) => Ok(report),
+      Ok(None) => Err(HandlerError::NotFound("Erasure report not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read erasure report: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Every category with its configured period, including those not purged yet.
+pub async fn read_admin_stats(
+  user: &UserDetail,
+  query: AdminStatsQuery,
+  stats_dao: &(dyn StatsDao + Send + Sync),
+) -> Result<AdminStats, HandlerError> {
+  require_admin(user)?;
+
+  let days = query.days.unwrap_or(AdminStats::DEFAULT_DAYS);
+
+  if !(1..=AdminStats::MAX_DAYS).contains(&days) {
+    return Err(HandlerError::BadRequest(format!("days must be between 1 and {}.", AdminStats::MAX_DAYS)));
+  }
+
+  let stats = stats_dao.get_admin_stats(days).await;
+
+  match stats {
+      Ok(stats) => Ok(stats),
+      Err(err) => {
+        error!("Error to read admin stats: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_retention_stats(
+  user: &UserDetail,
+  policy: RetentionPolicy,
+  retention_dao: &(dyn RetentionDao + Send + Sync),
+) -> Result<Vec<RetentionStats>, HandlerError> {
+  require_admin(user)?;
+
+  let stats = retention_dao.get_retention_stats().await;
+
+  match stats {
+      Ok(stats) => Ok(
+        RetentionCategory::ALL
+          .into_iter()
+          .map(|category| {
+            let purged = stats.iter().find(|stats| stats.category == category);
+
+            RetentionStats {
+              category,
+              retention_days: policy.days(category),
+              last_run_at: purged.and_then(|stats| stats.last_run_at),
+              last_removed: purged.map_or(0, |stats| stats.last_removed),
+              total_removed: purged.map_or(0, |stats| stats.total_removed),
+            }
+          })
+          .collect()
+      ),
+      Err(err) => {
+        error!("Error to read retention stats: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Burn rates of every route with an SLO target, as seen by this server instance.
+pub fn read_slo_status(user: &UserDetail, slo_tracker: &SloTracker, now: u64) -> Result<Vec<SloStatus>, HandlerError> {
+  require_admin(user)?;
+
+  Ok(slo_tracker.status(now))
+}
+
+pub async fn read_query_sampling(
+  user: &UserDetail,
+  query_samples_dao: &(dyn QuerySamplesDao + Send + Sync),
+) -> Result<QuerySampling, HandlerError> {
+  require_admin(user)?;
+
+  match query_samples_dao.get_sampling_percent().await {
+      Ok(percent) => Ok(QuerySampling { percent }),
+      Err(err) => {
+        error!("Error to read query sampling: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Applies to this server instance at once, and to the others when they next reload the rate.
+pub async fn update_query_sampling(
+  user: &UserDetail,
+  sampling: QuerySampling,
+  query_sampler: &QuerySampler,
+  query_samples_dao: &(dyn QuerySamplesDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<QuerySampling, HandlerError> {
+  require_admin(user)?;
+
+  if !(0.0..=100.0).contains(&sampling.percent) {
+    return Err(HandlerError::BadRequest("Percent must be between 0 and 100.".to_owned()));
+  }
+
+  match query_samples_dao.set_sampling_percent(sampling.percent, user.user_uuid.to_string()).await {
+      Ok(()) => {
+        query_sampler.set_percent(sampling.percent);
+        let payload = json!({ "percent": sampling.percent });
+        audit(user, AuditAction::SetQuerySampling, AuditTarget::Setting, None, payload, audit_dao).await;
+        Ok(sampling)
+      }
+      Err(err) => {
+        error!("Error to update query sampling: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_query_samples(
+  user: &UserDetail,
+  query: QuerySamplesQuery,
+  page: Pagination,
+  query_samples_dao: &(dyn QuerySamplesDao + Send + Sync),
+) -> Result<Vec<QuerySample>, HandlerError> {
+  require_admin(user)?;
+  require_page_limit(&page)?;
+
+  match query_samples_dao.get_query_samples(query, page).await {
+      Ok(samples) => Ok(samples),
+      Err(err) => {
+        error!("Error to read query samples: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Runtime, allocator, process and connection pool internals of the server instance that answers.
+pub fn read_diagnostics(user: &UserDetail, probe: &DiagnosticsProbe) -> Result<Diagnostics, HandlerError> {
+  require_admin(user)?;
+
+  Ok(probe.snapshot())
+}
+
+/// Samples the CPU of the server instance that answers for the requested seconds. Unavailable
+/// unless `PROFILING_ENABLED` is set, and one capture at a time.
+pub async fn capture_cpu_profile(
+  user: &UserDetail,
+  query: ProfileQuery,
+  probe: &DiagnosticsProbe,
+) -> Result<Vec<u8>, HandlerError> {
+  require_admin(user)?;
+
+  if !(1..=MAX_PROFILE_SECONDS).contains(&query.seconds) {
+    return Err(HandlerError::BadRequest(format!(
+      "Profiles last from 1 to {} seconds.",
+      MAX_PROFILE_SECONDS
+    )));
+  }
+
+  match probe.profile(Duration::from_secs(query.seconds), query.format).await {
+      Ok(profile) => Ok(profile),
+      Err(DiagnosticsError::Disabled) => Err(HandlerError::NotFound("CPU profiling is not enabled.".to_owned())),
+      Err(DiagnosticsError::Busy) => Err(HandlerError::Conflict("A CPU profile is already being captured.".to_owned())),
+      Err(err) => {
+          error!("Error to capture CPU profile: {}", err);
+          Err(HandlerError::default_internal_error())
+      }
+  }
+}
+
+/// Ready once the database answers. Pending migrations are reported, but leave the server instance
+/// ready: it is the deployment that applies them.
+pub async fn read_readiness(health_dao: &(dyn HealthDao + Send + Sync)) -> Readiness {
+  let checks = async {
+    health_dao.ping().await?;
+    health_dao.get_pending_migrations().await
+  };
+
+  match tokio::time::timeout(READINESS_TIMEOUT, checks).await {
+      Ok(Ok(pending_migrations)) => Readiness {
+        status: HealthStatus::Ok,
+        database_reachable: true,
+        pending_migrations,
+      },
+      failed => {
+        match failed {
+            Ok(Err(err)) => error!("Error to reach the database: {}", err),
+            _ => error!("The database did not answer within {:?}.", READINESS_TIMEOUT),
+        }
+
+        Readiness {
+          status: HealthStatus::Unavailable,
+          database_reachable: false,
+          pending_migrations: None,
+        }
+      }
+  }
+}
+
+/// Queues a job for the job worker; its progress is then read with `read_job`.
+pub async fn create_job(
+  user: &UserDetail,
+  request: JobRequest,
+  jobs_dao: &(dyn JobsDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<JobDetail, HandlerError> {
+  require_admin(user)?;
+
+  if request.kind == JobKind::Import {
+    return Err(HandlerError::BadRequest("Imports are started with POST /import.".to_owned()));
+  }
+
+  let job = jobs_dao.create_job(request.kind, user.user_uuid.to_string(), None).await;
+
+  match job {
+      Ok(job) => {
+        let payload = json!({ "kind": job.kind });
+        audit(user, AuditAction::CreateJob, AuditTarget::Job, Some(job.job_uuid.to_string()), payload, audit_dao).await;
+        Ok(job)
+      }
+      Err(err) => {
+        error!("Error to create job: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_job(
+  user: &UserDetail,
+  job_uuid: String,
+  jobs_dao: &(dyn JobsDao + Send + Sync),
+) -> Result<JobDetail, HandlerError> {
+  require_admin(user)?;
+
+  let job = jobs_dao.get_job(job_uuid).await;
+
+  match job {
+      Ok(Some(job)) => Ok(job),
+      Ok(None) => Err(HandlerError::NotFound("Job not found.".to_owned())),
+      Err(err) => {
+        error!("Error to read job: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Checks every record of `dump` and stores it for the job worker, which imports it in batches.
+/// Nothing is imported unless the whole dump is valid.
+pub async fn start_import(
+  user: &UserDetail,
+  content_type: &str,
+  dump: Bytes,
+  jobs_dao: &(dyn JobsDao + Send + Sync),
+  object_store: &dyn ObjectStore,
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<JobDetail, HandlerError> {
+  require_admin(user)?;
+
+  let content_type = content_type.to_owned();
+  let read = tokio::task::spawn_blocking(move || {
+      import::read_dump(&content_type, &dump).map(|records| (records.len(), import::to_ndjson(&records)))
+  });
+
+  let (records, ndjson) = match read.await {
+      Ok(Ok(read)) => read,
+      Ok(Err(err)) => return Err(HandlerError::BadRequest(err.to_string())),
+      Err(err) => {
+        error!("Error to read import dump: {}", err);
+        return Err(HandlerError::default_internal_error());
+      }
+  };
+
+  let input = ImportJob {
+    object_key: format!("import-{}", Uuid::new_v4().simple()),
+    tenant: current_tenant(),
+  };
+
+  if let Err(err) = object_store.put(&input.object_key, export::NDJSON_CONTENT_TYPE, ndjson).await {
+    error!("Error to store import dump: {}", err);
+    return Err(HandlerError::default_internal_error());
+  }
+
+  let job = jobs_dao.create_job(JobKind::Import, user.user_uuid.to_string(), Some(json!(input))).await;
+
+  match job {
+      Ok(job) => {
+        let payload = json!({ "kind": job.kind, "records": records });
+        audit(user, AuditAction::CreateJob, AuditTarget::Job, Some(job.job_uuid.to_string()), payload, audit_dao).await;
+        Ok(job)
+      }
+      Err(err) => {
+        error!("Error to create import job: {}", err);
+
+        if let Err(err) = object_store.delete(&input.object_key).await {
+          error!("Error to delete unqueued import dump {}: {}", input.object_key, err);
+        }
+
+        Err(err.into())
+      }
+  }
+}
+
+/// Like `read_job`, for import jobs only.
+pub async fn read_import(
+  user: &UserDetail,
+  job_uuid: String,
+  jobs_dao: &(dyn JobsDao + Send + Sync),
+) -> Result<JobDetail, HandlerError> {
+  match read_job(user, job_uuid, jobs_dao).await {
+      Ok(job) if job.kind != JobKind::Import => Err(HandlerError::NotFound("Import not found.".to_owned())),
+      result => result,
+  }
+}
+
+pub async fn read_dead_letters(
+  user: &UserDetail,
+  dead_letters_dao: &(dyn DeadLettersDao + Send + Sync),
+) -> Result<Vec<DeadLetter>, HandlerError> {
+  require_admin(user)?;
+
+  let dead_letters = dead_letters_dao.get_dead_letters().await;
+
+  match dead_letters {
+      Ok(dead_letters) => Ok(dead_letters),
+      Err(err) => {
+        error!("Error to list dead letters: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Redelivers each selected dead letter once. Delivered items are removed; failed ones are kept
+/// with the new error added to their history, so the request itself only fails on access or size.
+pub async fn retry_dead_letters(
+  user: &UserDetail,
+  selection: DeadLetterSelection,
+  dead_letters_dao: &(dyn DeadLettersDao + Send + Sync),
+  digest_webhook: Option<&DigestWebhook>,
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<Vec<DeadLetterRetryResult>, HandlerError> {
+  require_admin(user)?;
+  require_dead_letter_selection_size(&selection)?;
+
+  let mut results = Vec::with_capacity(selection.dead_letter_uuids.len());
+
+  for dead_letter_uuid in selection.dead_letter_uuids {
+    let error = retry_dead_letter(&dead_letter_uuid.to_string(), dead_letters_dao, digest_webhook)
+      .await
+      .err();
+
+    results.push(DeadLetterRetryResult {
+      dead_letter_uuid,
+      delivered: error.is_none(),
+      error,
+    });
+  }
+
+  let payload = json!({ "results": results });
+  audit(user, AuditAction::RetryDeadLetters, AuditTarget::DeadLetter, None, payload, audit_dao).await;
+
+  Ok(results)
+}
+
+async fn retry_dead_letter(
+  dead_letter_uuid: &str,
+  dead_letters_dao: &(dyn DeadLettersDao + Send + Sync),
+  digest_webhook: Option<&DigestWebhook>,
+) -> Result<(), String> {
+  let dead_letter = match dead_letters_dao.get_dead_letter(dead_letter_uuid.to_owned()).await {
+      Ok(Some(dead_letter)) => dead_letter,
+      Ok(None) => return Err("Dead letter not found.".to_owned()),
+      Err(DBError::InvalidUUID(s)) => return Err(s),
+      Err(err) => {
+        error!("Error to read dead letter: {}", err);
+        return Err("Something went wrong! Please try again.".to_owned());
+      }
+  };
+
+  let delivery = match dead_letter.kind {
+      DeadLetterKind::WebhookDigest => match digest_webhook {
+          Some(digest_webhook) => match serde_json::from_value::<WebhookDigest>(dead_letter.payload) {
+              Ok(digest) => digest_webhook.deliver(&digest).await.map_err(|err| err.to_string()),
+              Err(err) => Err(format!("Invalid webhook digest payload: {}", err)),
+          },
+          None => Err("No webhook is configured for this delivery.".to_owned()),
+      },
+  };
+
+  match delivery {
+      Ok(()) => {
+        if let Err(err) = dead_letters_dao.delete_dead_letters(vec![dead_letter_uuid.to_owned()]).await {
+          error!("Error to delete delivered dead letter: {}", err);
+        }
+
+        Ok(())
+      }
+      Err(delivery_error) => {
+        let recorded = dead_letters_dao
+          .record_dead_letter_failure(dead_letter_uuid.to_owned(), delivery_error.clone())
+          .await;
+
+        if let Err(err) = recorded {
+          error!("Error to record dead letter failure: {}", err);
+        }
+
+        Err(delivery_error)
+      }
+  }
+}
+
+pub async fn purge_dead_letters(
+  user: &UserDetail,
+  selection: DeadLetterSelection,
+  dead_letters_dao: &(dyn DeadLettersDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<Vec<BulkDeleteResult>, HandlerError> {
+  require_admin(user)?;
+  require_dead_letter_selection_size(&selection)?;
+
+  let dead_letter_uuids = selection.dead_letter_uuids.iter().map(Uuid::to_string).collect();
+  let results = dead_letters_dao.delete_dead_letters(dead_letter_uuids).await;
+
+  match results {
+      Ok(results) => {
+        audit_deleted(user, AuditAction::PurgeDeadLetters, AuditTarget::DeadLetter, &results, audit_dao).await;
+        Ok(results)
+      }
+      Err(err) => {
+        error!("Error to purge dead letters: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Registers a webhook for post events. The generated signing secret is only returned here.
+pub async fn create_webhook(
+  user: &UserDetail,
+  webhook: NewWebhook,
+  webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<WebhookDetail, HandlerError> {
+  require_admin(user)?;
+
+  require_webhook_url(&webhook.url)?;
+
+  if webhook.events.is_empty() {
+    return Err(HandlerError::BadRequest("Webhooks must subscribe to at least one event.".to_owned()));
+  }
+
+  if webhook.events.contains(&WebhookEvent::Notification) {
+    return Err(HandlerError::BadRequest("Notifications are only sent to users' own webhooks.".to_owned()));
+  }
+
+  let mut events = Vec::new();
+  for event in webhook.events {
+    if !events.contains(&event) {
+      events.push(event);
+    }
+  }
+
+  let secret = hex::encode(rand::random::<[u8; 32]>());
+  let created = webhooks_dao.create_webhook(NewWebhook { events, ..webhook }, secret, user.user_uuid.to_string()).await;
+
+  match created {
+      Ok(webhook) => {
+        let target_uuid = Some(webhook.webhook_uuid.to_string());
+        let payload = json!({ "url": webhook.url, "events": webhook.events });
+        audit(user, AuditAction::CreateWebhook, AuditTarget::Webhook, target_uuid, payload, audit_dao).await;
+        Ok(webhook)
+      }
+      Err(err) => {
+        error!("Error to create webhook: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_webhooks(
+  user: &UserDetail,
+  webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<Vec<WebhookDetail>, HandlerError> {
+  require_admin(user)?;
+
+  let webhooks = webhooks_dao.get_webhooks().await;
+
+  match webhooks {
+      Ok(webhooks) => Ok(webhooks),
+      Err(err) => {
+        error!("Error to list webhooks: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn delete_webhook(
+  user: &UserDetail,
+  webhook_uuid: String,
+  webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  require_admin(user)?;
+
+  let deleted = webhooks_dao.delete_webhook(webhook_uuid.clone()).await;
+
+  match deleted {
+      Ok(true) => {
+        audit(user, AuditAction::DeleteWebhook, AuditTarget::Webhook, Some(webhook_uuid), json!({}), audit_dao).await;
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("Webhook not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to delete webhook: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_webhook_deliveries(
+  user: &UserDetail,
+  webhook_uuid: String,
+  page: Pagination,
+  webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<Vec<WebhookDelivery>, HandlerError> {
+  require_admin(user)?;
+  require_page_limit(&page)?;
+
+  let deliveries = webhooks_dao.get_webhook_deliveries(webhook_uuid, page).await;
+
+  match deliveries {
+      Ok(Some(deliveries)) => Ok(deliveries),
+      Ok(None) => Err(HandlerError::NotFound("Webhook not found.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to list webhook deliveries: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+pub async fn read_cleanup_policy(
+  user: &UserDetail,
+  board_uuid: String,
+  cleanup_policies_dao: &(dyn CleanupPoliciesDao + Send + Sync),
+) -> Result<BoardCleanupPolicyDetail, HandlerError> {
+  require_admin(user)?;
+
+  let policy = cleanup_policies_dao.get_cleanup_policy(board_uuid).await;
+
+  match policy {
+      Ok(Some(policy)) => Ok(policy),
+      Ok(None) => Err(HandlerError::NotFound("Board has no cleanup policy.".to_owned())),
+      Err(err) => {
+        error!("Error to read cleanup policy: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Replaces the board's cleanup policy; the scheduler applies it on its next run.
+pub async fn set_cleanup_policy(
+  user: &UserDetail,
+  board_uuid: String,
+  policy: BoardCleanupPolicy,
+  cleanup_policies_dao: &(dyn CleanupPoliciesDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<BoardCleanupPolicyDetail, HandlerError> {
+  require_admin(user)?;
+  require_valid_cleanup_policy(&policy)?;
+
+  let payload = json!(policy);
+  let policy = cleanup_policies_dao
+    .set_cleanup_policy(board_uuid.clone(), policy, user.user_uuid.to_string())
+    .await;
+
+  match policy {
+      Ok(Some(policy)) => {
+        audit(user, AuditAction::SetCleanupPolicy, AuditTarget::Board, Some(board_uuid), payload, audit_dao).await;
+        Ok(policy)
+      }
+      Ok(None) => Err(HandlerError::NotFound("Board not found.".to_owned())),
+      Err(err) => {
+        error!("Error to save cleanup policy: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+pub async fn delete_cleanup_policy(
+  user: &UserDetail,
+  board_uuid: String,
+  cleanup_policies_dao: &(dyn CleanupPoliciesDao + Send + Sync),
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  require_admin(user)?;
+
+  let deleted = cleanup_policies_dao.delete_cleanup_policy(board_uuid.clone()).await;
+
+  match deleted {
+      Ok(true) => {
+        audit(user, AuditAction::DeleteCleanupPolicy, AuditTarget::Board, Some(board_uuid), json!({}), audit_dao).await;
+        Ok(())
+      }
+      Ok(false) => Err(HandlerError::NotFound("Board has no cleanup policy.".to_owned())),
+      Err(err) => {
+        error!("Error to delete cleanup policy: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// Dry run of a proposed policy: lists the questions it would close and delete now, so an admin
+/// can check it before saving it.
+pub async fn preview_cleanup(
+  user: &UserDetail,
+  board_uuid: String,
+  policy: BoardCleanupPolicy,
+  cleanup_policies_dao: &(dyn CleanupPoliciesDao + Send + Sync),
+) -> Result<BoardCleanup, HandlerError> {
+  require_admin(user)?;
+  require_valid_cleanup_policy(&policy)?;
+
+  let cleanup = cleanup_policies_dao.preview_cleanup(board_uuid, policy).await;
+
+  match cleanup {
+      Ok(cleanup) => Ok(cleanup),
+      Err(err) => {
+        error!("Error to preview cleanup: {}", err);
+
+          match err {
+              DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+              err => Err(err.into()),
+          }
+      }
+  }
+}
+
+/// The earlier answer to the same question that `answer` most closely repeats, with its similarity,
+/// if it reaches the policy's threshold. Best effort: when the answers cannot be read, none is found.
+async fn find_similar_answer(
+  answer: &AnswerDetail,
+  viewer: Viewer,
+  policy: SimilarAnswerPolicy,
+  answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Option<(Uuid, f64)> {
+  let answers = match answers_dao.get_answers(answer.question_uuid.to_string(), AnswerSort::default(), viewer).await {
+      Ok(answers) => answers,
+      Err(err) => {
+        error!("Error to read answers to compare with new answer: {}", err);
+        return None;
+      }
+  };
+
+  let earlier = answers
+    .iter()
+    .filter(|other| other.answer_uuid != answer.answer_uuid)
+    .map(|other| (other.answer_uuid, other.content.as_str()));
+
+  most_similar(&answer.content, earlier, policy.threshold)
+}
+
+/// Refuses a post of `kind` by an author who already made as many as their quota allows in the
+/// window, and otherwise returns their limit for the insert to check again, with the posts made
+/// since counted. A failed check lets the post through without a limit, as the per-IP rate limits
+/// still apply.
+async fn require_posting_quota(
+  author: Option<&UserDetail>,
+  kind: PostKind,
+  policy: PostingQuotaPolicy,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+  users_dao: &(dyn UsersDao + Send + Sync),
+  now: u64,
+) -> Result<Option<i64>, HandlerError> {
+  let Some(author) = author.filter(|author| !author.role.can_moderate()) else {
+    return Ok(None);
+  };
+
+  let post_times = match moderation_dao
+    .get_recent_post_times(author.user_uuid.to_string(), kind, PostingQuotaPolicy::WINDOW_MINUTES)
+    .await
+  {
+      Ok(post_times) => post_times,
+      Err(err) => {
+        error!("Error to read recent posts for posting quota: {}", err);
+        return Ok(None);
+      }
+  };
+
+  let posted = post_times.len() as i64;
+
+  let reputation = match users_dao.get_reputation(author.user_uuid.to_string()).await {
+      Ok(reputation) => reputation,
+      Err(err) => {
+        error!("Error to read reputation for posting quota: {}", err);
+        0
+      }
+  };
+  let limit = policy.limit(kind, reputation);
+
+  if posted < limit {
+    return Ok(Some(limit));
+  }
+
+  let window_seconds = i64::from(PostingQuotaPolicy::WINDOW_MINUTES) * 60;
+  // Once this post leaves the window, the author is under the limit again.
+  let freed_at = post_times[(posted - limit) as usize].unix_timestamp() + window_seconds;
+  let retry_after_seconds = u64::try_from(freed_at).unwrap_or(0).saturating_sub(now).max(1);
+  let kind_name = match kind {
+      PostKind::Question => "questions",
+      PostKind::Answer => "answers",
+  };
+
+  Err(HandlerError::PostingQuotaExceeded(PostingQuotaExceeded {
+    message: format!(
+      "You can post {} {} per hour at your reputation of {}; try again in {} seconds.",
+      limit, kind_name, reputation, retry_after_seconds
+    ),
+    kind,
+    limit,
+    window_seconds,
+    reputation,
+    next_limit_reputation: policy.next_limit_reputation(reputation),
+    retry_after_seconds,
+  }))
+}
+
+/// Asks the spam checker about a new post by `author`. Moderators are trusted, and a failed check
+/// lets the post through so that an unavailable spam service does not stop people posting.
+async fn spam_verdict(
+  content: String,
+  author: Option<&UserDetail>,
+  client_ip: IpAddr,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+  spam_checker: &dyn SpamChecker,
+) -> SpamVerdict {
+  if author.is_some_and(|author| author.role.can_moderate()) {
+    return SpamVerdict::Ham;
+  }
+
+  let recent_posts = match author {
+      Some(author) => {
+        match moderation_dao.get_recent_posts(author.user_uuid.to_string(), RECENT_POSTS_WINDOW_MINUTES).await {
+            Ok(posts) => posts,
+            Err(err) => {
+              error!("Error to read recent posts for spam check: {}", err);
+              Vec::new()
+            }
+        }
+      }
+      None => Vec::new(),
+  };
+
+  let candidate = SpamCandidate {
+    content,
+    author_uuid: author.map(|author| author.user_uuid.to_string()),
+    client_ip: client_ip.to_string(),
+    recent_posts,
+  };
+
+  match spam_checker.check(&candidate).await {
+      Ok(verdict) => verdict,
+      Err(err) => {
+        error!("Error to check post for spam: {}", err);
+        SpamVerdict::Ham
+      }
+  }
+}
+
+/// Holds a just-saved post for moderation when it was judged spam, returning whether it is held.
+/// The post is already saved, so a failure to hold it is logged and the post stays published.
+async fn hold_if_spam(
+  verdict: SpamVerdict,
+  question_uuid: &str,
+  answer_uuid: Option<String>,
+  moderation_dao: &(dyn ModerationDao + Send + Sync),
+) -> bool {
+  let SpamVerdict::Spam(reason) = verdict else {
+    return false;
+  };
+
+  match moderation_dao.hold_post(question_uuid.to_owned(), answer_uuid, reason).await {
+      Ok(()) => true,
+      Err(err) => {
+        error!("Error to hold post for moderation: {}", err);
+        false
+      }
+  }
+}
+
+/// Appends a privileged action to the audit log. The action has already been applied, so a failure
+/// to record it is logged rather than returned.
+async fn audit(
+  user: &UserDetail,
+  action: AuditAction,
+  target_type: AuditTarget,
+  target_uuid: Option<String>,
+  payload: serde_json::Value,
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) {
+  let entry = AuditEntry {
+    actor_uuid: user.user_uuid,
+    action,
+    target_type,
+    target_uuid,
+    payload,
+  };
+
+  if let Err(err) = audit_dao.record_audit_entry(entry).await {
+    error!("Error to record audit entry: {}", err);
+  }
+}
+
+/// Audits a bulk delete with the records it removed, if any.
+async fn audit_deleted(
+  user: &UserDetail,
+  action: AuditAction,
+  target_type: AuditTarget,
+  results: &[BulkDeleteResult],
+  audit_dao: &(dyn AuditDao + Send + Sync),
+) {
+  let deleted: Vec<&str> = results
+    .iter()
+    .filter(|result| result.deleted)
+    .map(|result| result.uuid.as_str())
+    .collect();
+
+  if !deleted.is_empty() {
+    audit(user, action, target_type, None, json!({ "deleted": deleted }), audit_dao).await;
+  }
+}
+
+fn question_created(question: &QuestionDetail) -> DomainEvent {
+  DomainEvent::QuestionCreated {
+    question_uuid: question.question_uuid.to_string(),
+    author_uuid: question.author_uuid.map(|uuid| uuid.to_string()),
+    description: question.description.clone(),
+  }
+}
+
+fn question_updated(question: &QuestionDetail) -> DomainEvent {
+  DomainEvent::QuestionUpdated {
+    question_uuid: question.question_uuid.to_string(),
+  }
+}
+
+/// Subscriber notifying followers and mentioned users of new posts.
+pub async fn notify_on_event(event: DomainEvent, notifications_dao: &(dyn NotificationsDao + Send + Sync)) {
+  match event {
+      DomainEvent::QuestionCreated { question_uuid, author_uuid, description } => {
+        notify_mentions(&description, &question_uuid, None, author_uuid, notifications_dao).await;
+      }
+      DomainEvent::AnswerCreated { question_uuid, answer_uuid, author_uuid, content } => {
+        let notified = notifications_dao
+          .notify_question_followers(
+            question_uuid.clone(),
+            NotificationKind::NewAnswer,
+            Some(answer_uuid.clone()),
+            author_uuid.clone(),
+          )
+          .await;
+
+        if let Err(err) = notified {
+          error!("Error to notify question followers: {}", err);
+        }
+
+        notify_mentions(&content, &question_uuid, Some(answer_uuid), author_uuid, notifications_dao).await;
+      }
+      DomainEvent::AnswerUpdated { .. } | DomainEvent::QuestionUpdated { .. } | DomainEvent::QuestionDeleted { .. } => {}
+  }
+}
+
+/// Subscriber queuing the event for the webhooks subscribed to it; the delivery worker sends them.
+pub async fn queue_webhook_deliveries(event: DomainEvent, forum_url: &str, webhooks_dao: &(dyn WebhooksDao + Send + Sync)) {
+  let (event, question_uuid, answer_uuid) = match event {
+      DomainEvent::QuestionCreated { question_uuid, .. } => (WebhookEvent::QuestionCreated, question_uuid, None),
+      DomainEvent::AnswerCreated { question_uuid, answer_uuid, .. } => {
+        (WebhookEvent::AnswerCreated, question_uuid, Some(answer_uuid))
+      }
+      DomainEvent::AnswerUpdated { question_uuid, answer_uuid } => {
+        (WebhookEvent::AnswerUpdated, question_uuid, Some(answer_uuid))
+      }
+      DomainEvent::QuestionUpdated { .. } | DomainEvent::QuestionDeleted { .. } => return,
+  };
+
+  let forum_url = forum_url.trim_end_matches('/').to_owned();
+
+  if let Err(err) = webhooks_dao.queue_webhook_deliveries(event, question_uuid, answer_uuid, forum_url).await {
+    error!("Error to queue {} webhook deliveries: {}", event.as_str(), err);
+  }
+}
+
+/// Subscriber forwarding answer changes to the viewers of their question.
+pub fn publish_live_update(event: DomainEvent, live_updates: &LiveUpdates) {
+  match event {
+      DomainEvent::AnswerCreated { question_uuid, answer_uuid, .. } => {
+        live_updates.publish(&question_uuid, LiveEvent::AnswerCreated { answer_uuid });
+      }
+      DomainEvent::AnswerUpdated { question_uuid, answer_uuid } => {
+        live_updates.publish(&question_uuid, LiveEvent::AnswerUpdated { answer_uuid });
+      }
+      DomainEvent::QuestionCreated { .. } | DomainEvent::QuestionUpdated { .. } | DomainEvent::QuestionDeleted { .. } => {}
+  }
+}
+
+/// Subscriber dropping the cached copies of questions that changed, and the listings they are on.
+pub async fn invalidate_cached_questions(event: DomainEvent, question_cache: &QuestionCache) {
+  match event {
+      DomainEvent::QuestionCreated { question_uuid, .. }
+      | DomainEvent::QuestionUpdated { question_uuid }
+      | DomainEvent::QuestionDeleted { question_uuid } => question_cache.invalidate(&question_uuid).await,
+      DomainEvent::AnswerCreated { .. } | DomainEvent::AnswerUpdated { .. } => {}
+  }
+}
+
+/// Notifies the users mentioned in a new post, best effort like follower notifications: the post is
+/// already saved.
+async fn notify_mentions(
+  markdown: &str,
+  question_uuid: &str,
+  answer_uuid: Option<String>,
+  author_uuid: Option<String>,
+  notifications_dao: &(dyn NotificationsDao + Send + Sync),
+) {
+  let usernames = mentions(markdown);
+
+  if usernames.is_empty() {
+    return;
+  }
+
+  let notified = notifications_dao
+    .notify_mentioned_users(question_uuid.to_owned(), answer_uuid, usernames, author_uuid)
+    .await;
+
+  if let Err(err) = notified {
+    error!("Error to notify mentioned users: {}", err);
+  }
+}
+
+async fn question_with_link_previews(
+  question: QuestionDetail,
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+) -> QuestionDetail {
+  let urls = links(&question.description);
+  let previews = fetched_link_previews(urls.clone(), link_previews_dao).await;
+
+  QuestionDetail {
+    link_previews: previews_of(&urls, &previews),
+    ..question
+  }
+}
+
+/// Looks the previews of every answer up at once.
+async fn answers_with_link_previews(
+  answers: Vec<AnswerDetail>,
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+) -> Vec<AnswerDetail> {
+  let answer_urls: Vec<Vec<String>> = answers.iter().map(|answer| links(&answer.content)).collect();
+
+  let mut urls: Vec<String> = answer_urls.iter().flatten().cloned().collect();
+  urls.sort();
+  urls.dedup();
+
+  let previews = fetched_link_previews(urls, link_previews_dao).await;
+
+  answers
+    .into_iter()
+    .zip(answer_urls)
+    .map(|(answer, urls)| AnswerDetail {
+      link_previews: previews_of(&urls, &previews),
+      ..answer
+    })
+    .collect()
+}
+
+/// Previews are an extra, so a failed lookup leaves them out instead of failing the read.
+async fn fetched_link_previews(
+  urls: Vec<String>,
+  link_previews_dao: &(dyn LinkPreviewsDao + Send + Sync),
+) -> Vec<LinkPreview> {
+  if urls.is_empty() {
+    return Vec::new();
+  }
+
+  match link_previews_dao.get_link_previews(urls).await {
+      Ok(previews) => previews,
+      Err(err) => {
+        error!("Error to read link previews: {}", err);
+        Vec::new()
+      }
+  }
+}
+
+/// The previews of `urls`, in the order the post links to them.
+fn previews_of(urls: &[String], previews: &[LinkPreview]) -> Vec<LinkPreview> {
+  urls
+    .iter()
+    .filter_map(|url| previews.iter().find(|preview| &preview.url == url).cloned())
+    .collect()
+}
+
+fn invitation_path(invitation_uuid: &str) -> String {
+  format!("/invitations/{}", invitation_uuid)
+}
+
+fn require_moderator(user: &UserDetail) -> Result<(), HandlerError> {
+  if user.role.can_moderate() {
+    Ok(())
+  } else {
+    Err(HandlerError::Forbidden("Only moderators can perform this action.".to_owned()))
+  }
+}
+
+fn require_valid_flag(flag: &Flag) -> Result<(), HandlerError> {
+  let details = flag.details.as_deref().unwrap_or_default().trim();
+
+  if flag.reason.is_automatic() {
+    return Err(HandlerError::BadRequest(format!(
+      "{} flags are only raised automatically.",
+      flag.reason.as_str()
+    )));
+  }
+
+  if flag.reason == FlagReason::Other && details.is_empty() {
+    return Err(HandlerError::BadRequest("Details are required when the reason is other.".to_owned()));
+  }
+
+  if details.chars().count() > Flag::MAX_DETAILS_CHARS {
+    return Err(HandlerError::BadRequest(format!(
+      "Details must be at most {} characters.",
+      Flag::MAX_DETAILS_CHARS
+    )));
+  }
+
+  Ok(())
+}
+
+fn require_not_author(author_uuid: Option<Uuid>, user: &UserDetail) -> Result<(), HandlerError> {
+  if author_uuid == Some(user.user_uuid) {
+    Err(HandlerError::BadRequest("You cannot flag your own post.".to_owned()))
+  } else {
+    Ok(())
+  }
+}
+
+fn is_tag_char(c: char) -> bool {
+  c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '#' | '.' | '-')
+}
+
+/// Lowercases and deduplicates a question's tags, keeping their order. Tags start with a letter or
+/// digit and otherwise use `+`, `#`, `.` and `-`, as in `c++`, `c#` or `async-std`.
+fn normalized_tags(tags: Vec<String>) -> Result<Vec<String>, HandlerError> {
+  let normalized = deduplicated_tags(tags)?;
+
+  if normalized.len() > Question::MAX_TAGS {
+    return Err(HandlerError::BadRequest(format!("A question can have at most {} tags.", Question::MAX_TAGS)));
+  }
+
+  Ok(normalized)
+}
+
+/// Lowercases and validates each tag, keeping the first of any repeats.
+fn deduplicated_tags(tags: Vec<String>) -> Result<Vec<String>, HandlerError> {
+  let mut normalized: Vec<String> = Vec::new();
+
+  for tag in tags {
+    let tag = tag.trim().to_lowercase();
+
+    let valid = tag.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+      && tag.chars().count() <= Question::MAX_TAG_CHARS
+      && tag.chars().all(is_tag_char);
+
+    if !valid {
+      return Err(HandlerError::BadRequest(format!("Invalid tag: {}", tag)));
+    }
+
+    if !normalized.contains(&tag) {
+      normalized.push(tag);
+    }
+  }
+
+  Ok(normalized)
+}
+
+fn normalized_tag_rules(rules: BoardTagRules) -> Result<BoardTagRules, HandlerError> {
+  let rules = BoardTagRules {
+    required_tags: deduplicated_tags(rules.required_tags)?,
+    forbidden_tags: deduplicated_tags(rules.forbidden_tags)?,
+  };
+
+  if rules.required_tags.len() + rules.forbidden_tags.len() > BoardTagRules::MAX_TAGS {
+    return Err(HandlerError::BadRequest(format!(
+      "Tag rules can list at most {} tags.",
+      BoardTagRules::MAX_TAGS
+    )));
+  }
+
+  if let Some(tag) = rules.required_tags.iter().find(|tag| rules.forbidden_tags.contains(tag)) {
+    return Err(HandlerError::BadRequest(format!("A tag cannot be both required and forbidden: {}", tag)));
+  }
+
+  Ok(rules)
+}
+
+fn require_admin(user: &UserDetail) -> Result<(), HandlerError> {
+  if user.role == Role::Admin {
+    Ok(())
+  } else {
+    Err(HandlerError::Forbidden("Only admins can perform this action.".to_owned()))
+  }
+}
+
+fn require_bulk_delete_size(request: &BulkDelete) -> Result<(), HandlerError> {
+  if request.uuids.len() > BulkDelete::MAX_ITEMS {
+    return Err(HandlerError::BadRequest(format!(
+      "At most {} items can be deleted at once.",
+      BulkDelete::MAX_ITEMS
+    )));
+  }
+
+  Ok(())
+}
+
+fn require_dead_letter_selection_size(selection: &DeadLetterSelection) -> Result<(), HandlerError> {
+  if selection.dead_letter_uuids.len() > DeadLetterSelection::MAX_ITEMS {
+    return Err(HandlerError::BadRequest(format!(
+      "At most {} dead letters can be selected at once.",
+      DeadLetterSelection::MAX_ITEMS
+    )));
+  }
+
+  Ok(())
+}
+
+fn require_valid_cleanup_policy(policy: &BoardCleanupPolicy) -> Result<(), HandlerError> {
+  let rules = [policy.close_inactive_after_days, policy.delete_unanswered_after_days];
+
+  if rules.iter().all(Option::is_none) {
+    return Err(HandlerError::BadRequest("A cleanup policy needs at least one rule.".to_owned()));
+  }
+
+  if rules.iter().flatten().any(|days| *days < 1 || *days > BoardCleanupPolicy::MAX_DAYS) {
+    return Err(HandlerError::BadRequest(format!(
+      "Days must be between 1 and {}.",
+      BoardCleanupPolicy::MAX_DAYS
+    )));
+  }
+
+  Ok(())
+}
+
+fn require_signed_url_expiry(request: &SignedUrlRequest) -> Result<(), HandlerError> {
+  if request.expires_in_seconds == 0 || request.expires_in_seconds > SignedUrlRequest::MAX_EXPIRES_IN_SECONDS {
+    return Err(HandlerError::BadRequest(format!(
+      "expires_in_seconds must be between 1 and {}.",
+      SignedUrlRequest::MAX_EXPIRES_IN_SECONDS
+    )));
+  }
+
+  Ok(())
+}
+
+/// The type of an upload, read from its content rather than trusted from the client.
+fn sniff_content_type(content: &[u8]) -> Option<&'static str> {
+  match content {
+      [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => Some("image/png"),
+      [0xff, 0xd8, 0xff, ..] => Some("image/jpeg"),
+      [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+      [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+      [b'%', b'P', b'D', b'F', b'-', ..] => Some("application/pdf"),
+      _ if !content.contains(&0) && std::str::from_utf8(content).is_ok() => Some("text/plain; charset=utf-8"),
+      _ => None,
+  }
+}
+
+/// Keeps the last path segment of a client file name, restricted to characters that are safe in a
+/// `Content-Disposition` header.
+fn sanitize_filename(filename: &str) -> String {
+  let name: String = filename
+    .rsplit(['/', '\\'])
+    .next()
+    .unwrap_or_default()
+    .chars()
+    .map(|c| if (c.is_ascii_graphic() && c != '"') || c == ' ' { c } else { '_' })
+    .take(Upload::MAX_FILENAME_CHARS)
+    .collect();
+
+  match name.trim() {
+      "" | "." | ".." => "upload".to_owned(),
+      name => name.to_owned(),
+  }
+}
+
+/// Whole days between the dates of `created_at` and the `now` Unix timestamp. `None` when `now` is
+/// out of range.
+fn age_in_days(created_at: OffsetDateTime, now: u64) -> Option<i64> {
+  let today = OffsetDateTime::from_unix_timestamp(i64::try_from(now).ok()?).ok()?.date();
+
+  Some((today - created_at.date()).whole_days())
+}
+
+fn require_contest(contest: Contest, kind: QuestionKind) -> Result<(), HandlerError> {
+  if !kind.accepts_answers() {
+    return Err(HandlerError::BadRequest("Announcements cannot be contests.".to_owned()));
+  }
+
+  for hours in [contest.answer_hours, contest.voting_hours] {
+    if !(1..=Contest::MAX_HOURS).contains(&hours) {
+      return Err(HandlerError::BadRequest(format!(
+        "Contest periods must be between 1 and {} hours.",
+        Contest::MAX_HOURS
+      )));
+    }
+  }
+
+  Ok(())
+}
+
+/// Whether answers are shown at the `now` Unix timestamp.
+fn contest_revealed(contest: &ContestDetail, now: u64) -> bool {
+  u64::try_from(contest.reveal_at.unix_timestamp()).is_ok_and(|reveal_at| reveal_at <= now)
+}
+
+fn require_webhook_url(url: &str) -> Result<(), HandlerError> {
+  let is_http_url = reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+
+  if !is_http_url || url.len() > NewWebhook::MAX_URL_LENGTH {
+    return Err(HandlerError::BadRequest(format!(
+      "Webhook URL must be an http(s) URL of at most {} characters.",
+      NewWebhook::MAX_URL_LENGTH
+    )));
+  }
+
+  Ok(())
+}
+
+fn require_valid(payload: &impl Validate) -> Result<(), HandlerError> {
+  payload.validate().map_err(HandlerError::ValidationFailed)
+}
+
+fn require_page_limit(page: &Pagination) -> Result<(), HandlerError> {
+  if page.limit == 0 || page.limit > Pagination::MAX_LIMIT {
+    return Err(HandlerError::BadRequest(format!(
+      "Limit must be between 1 and {}.",
+      Pagination::MAX_LIMIT
+    )));
+  }
+
+  Ok(())
+}
+
+/// Private questions must be posted to a board the author belongs to.
+async fn require_board_access(
+  question: &Question,
+  author: Option<&UserDetail>,
+  boards_dao: &(dyn BoardsDao + Sync + Send),
+) -> Result<(), HandlerError> {
+  if question.visibility != Visibility::Private {
+    return Ok(());
+  }
+
+  let Some(author) = author else {
+    return Err(HandlerError::Unauthorized("Sign in to post private questions.".to_owned()));
+  };
+
+  let Some(board_uuid) = question.board_uuid else {
+    return Err(HandlerError::BadRequest("Private questions require a board_uuid.".to_owned()));
+  };
+
+  match boards_dao.is_board_member(board_uuid.to_string(), author.user_uuid.to_string()).await {
+      Ok(true) => Ok(()),
+      Ok(false) => Err(HandlerError::Forbidden("Only board members can post private questions to this board.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to check board membership: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Questions on a board must follow its tag rules, if it has any.
+async fn require_board_tag_rules(
+  question: &Question,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  let Some(board_uuid) = question.board_uuid else {
+    return Ok(());
+  };
+
+  match tags_dao.get_board_tag_rules(board_uuid.to_string()).await {
+      Ok(Some(rules)) => BoardTagRules::from(&rules)
+        .check(&question.tags)
+        .map_err(HandlerError::TagRuleViolation),
+      Ok(None) => Ok(()),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read board tag rules: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+/// Splits the question's tags that no question uses yet off into the returned pending tags, unless
+/// `author` is trusted to create tags by the `NewTagPolicy`.
+async fn split_new_tags(
+  question: Question,
+  author: Option<&UserDetail>,
+  policy: NewTagPolicy,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+  users_dao: &(dyn UsersDao + Send + Sync),
+) -> Result<(Question, Vec<String>), HandlerError> {
+  if question.tags.is_empty() || author.is_some_and(|author| author.role.can_moderate()) {
+    return Ok((question, Vec::new()));
+  }
+
+  let new_tags = match tags_dao.get_unused_tags(question.tags.clone()).await {
+      Ok(new_tags) => new_tags,
+      Err(err) => {
+        error!("Error to read unused tags: {}", err);
+        return Err(err.into());
+      }
+  };
+
+  if new_tags.is_empty() {
+    return Ok((question, Vec::new()));
+  }
+
+  if let Some(author) = author {
+    match users_dao.get_reputation(author.user_uuid.to_string()).await {
+        Ok(reputation) if reputation >= policy.min_reputation => return Ok((question, Vec::new())),
+        Ok(_) => {}
+        Err(err) => {
+          error!("Error to read reputation for new tags: {}", err);
+          return Err(err.into());
+        }
+    }
+  }
+
+  let question = Question {
+    tags: question.tags.into_iter().filter(|tag| !new_tags.contains(tag)).collect(),
+    ..question
+  };
+
+  Ok((question, new_tags))
+}
+
+/// Records the question's new tags for moderators. The question is already saved, so a failure is
+/// logged rather than returned.
+async fn propose_tags(
+  question_uuid: &str,
+  tags: &[String],
+  author: Option<&UserDetail>,
+  tags_dao: &(dyn TagsDao + Send + Sync),
+) {
+  if tags.is_empty() {
+    return;
+  }
+
+  let proposed = tags_dao
+    .add_pending_tags(question_uuid.to_owned(), tags.to_vec(), author.map(|author| author.user_uuid.to_string()))
+    .await;
+
+  if let Err(err) = proposed {
+    error!("Error to record pending tags: {}", err);
+  }
+}
+
+/// Boards are administered by their active owners and by site admins.
+async fn require_board_owner(
+  board_uuid: &str,
+  user: &UserDetail,
+  boards_dao: &(dyn BoardsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+  if user.role == Role::Admin {
+    return Ok(());
+  }
+
+  match boards_dao.get_board_member(board_uuid.to_owned(), user.user_uuid.to_string()).await {
+      Ok(Some(BoardMember { role: BoardRole::Owner, status: MembershipStatus::Active, .. })) => Ok(()),
+      Ok(_) => Err(HandlerError::Forbidden("Only board owners can manage this board.".to_owned())),
+      Err(DBError::InvalidUUID(s)) => Err(HandlerError::BadRequest(s)),
+      Err(err) => {
+        error!("Error to read board membership: {}", err);
+        Err(err.into())
+      }
+  }
+}
+
+fn require_author_or_moderator(author_uuid: Option<Uuid>, user: &UserDetail) -> Result<(), HandlerError> {
+  if author_uuid == Some(user.user_uuid) || user.role.can_moderate() {
+    Ok(())
+  } else {
+    Err(HandlerError::Forbidden("Only the author or a moderator can perform this action.".to_owned()))
+  }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use time::macros::datetime;
+
+  use crate::{
+      auth::{AuthBackendError, GroupRoleMap},
+      cache::MemoryCache,
+      content_policy::ContentPolicyMode,
+      models::{
+          AcceptSuggestionThresholds, DigestFrequency, EmailDigest, ErasureAction, ErasureBackups, ErasureCheck,
+          InvitationStatus, JobStatus, NewQuerySample, PendingEmail, PendingWebhookDelivery,
+          ProvisionedUser, QuestionView, QuestionViewer, TagAcceptedAnswer, TagAnswerer, TagRuleViolationCode, TagWeek,
+          UserIpRecord,
+      },
+      scim::ScimPatchOperation,
+      spam::{HeuristicSpamChecker, NoSpamChecker},
+      storage::StorageError,
+  };
+
+  use async_trait::async_trait;
+  use tokio::sync::Mutex;
+
+  struct QuestionsDaoMock {
+      create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+      delete_question_response: Mutex<Option<Result<Option<QuestionDeletion>, DBError>>>,
+      restore_question_response: Mutex<Option<Result<Option<QuestionDetail>, DBError>>>,
+      get_question_response: Mutex<Option<Result<Option<QuestionDetail>, DBError>>>,
+      get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+      get_questions_by_uuid_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+      get_questions_by_author_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+      update_question_status_response: Mutex<Option<Result<Option<QuestionDetail>, DBError>>>,
+      update_question_response: Mutex<Option<Result<Option<QuestionDetail>, DBError>>>,
+      get_question_revisions_response: Mutex<Option<Result<Vec<QuestionRevision>, DBError>>>,
+      get_feed_entries_response: Mutex<Option<Result<Vec<FeedEntry>, DBError>>>,
+      count_sitemap_questions_response: Mutex<Option<Result<i64, DBError>>>,
+      accept_answer_response: Mutex<Option<Result<bool, DBError>>>,
+      /// Pending tags and spam details of the last created question.
+      created_with: Mutex<Option<(Vec<String>, Option<String>)>>,
+  }
+
+  impl QuestionsDaoMock {
+      pub fn new() -> Self {
+          QuestionsDaoMock {
+              create_question_response: Mutex::new(None),
+              delete_question_response: Mutex::new(None),
+              restore_question_response: Mutex::new(None),
+              get_question_response: Mutex::new(None),
+              get_questions_response: Mutex::new(None),
+              get_questions_by_uuid_response: Mutex::new(None),
+              get_questions_by_author_response: Mutex::new(None),
+              update_question_status_response: Mutex::new(None),
+              update_question_response: Mutex::new(None),
+              get_question_revisions_response: Mutex::new(None),
+              get_feed_entries_response: Mutex::new(None),
+              count_sitemap_questions_response: Mutex::new(None),
+              accept_answer_response: Mutex::new(None),
+              created_with: Mutex::new(None),
+          }
+      }
+      pub async fn created_with(&self) -> Option<(Vec<String>, Option<String>)> {
+          self.created_with.lock().await.clone()
+      }
+      pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
+          self.create_question_response = Mutex::new(Some(response));
+      }
+      pub fn mock_delete_question(&mut self, response: Result<Option<QuestionDeletion>, DBError>) {
+          self.delete_question_response = Mutex::new(Some(response));
+      }
+      pub fn mock_restore_question(&mut self, response: Result<Option<QuestionDetail>, DBError>) {
+          self.restore_question_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_question(&mut self, response: Result<Option<QuestionDetail>, DBError>) {
+          self.get_question_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_feed_entries(&mut self, response: Result<Vec<FeedEntry>, DBError>) {
+          self.get_feed_entries_response = Mutex::new(Some(response));
+      }
+      pub fn mock_count_sitemap_questions(&mut self, response: Result<i64, DBError>) {
+          self.count_sitemap_questions_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+          self.get_questions_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_questions_by_uuid(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+          self.get_questions_by_uuid_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_questions_by_author(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+          self.get_questions_by_author_response = Mutex::new(Some(response));
+      }
+      pub fn mock_update_question_status(&mut self, response: Result<Option<QuestionDetail>, DBError>) {
+          self.update_question_status_response = Mutex::new(Some(response));
+      }
+      pub fn mock_update_question(&mut self, response: Result<Option<QuestionDetail>, DBError>) {
+          self.update_question_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_question_revisions(&mut self, response: Result<Vec<QuestionRevision>, DBError>) {
+          self.get_question_revisions_response = Mutex::new(Some(response));
+      }
+      pub fn mock_accept_answer(&mut self, response: Result<bool, DBError>) {
+          self.accept_answer_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl QuestionsDao for QuestionsDaoMock {
+      async fn create_question(
+          &self,
+          _: Question,
+          _: Option<String>,
+          pending_tags: Vec<String>,
+          spam_details: Option<String>,
+          _: Option<i64>,
+      ) -> Result<QuestionDetail, DBError> {
+          *self.created_with.lock().await = Some((pending_tags.clone(), spam_details.clone()));
+
+          self.create_question_response
+              .lock()
+              .await
+              .take()
+              .expect("create_question_response should not be None.")
+              .map(|question| QuestionDetail {
+                  held_for_review: spam_details.is_some(),
+                  pending_tags,
+                  ..question
+              })
+      }
+      async fn delete_question(&self, _: String) -> Result<Option<QuestionDeletion>, DBError> {
+          self.delete_question_response
+              .lock()
+              .await
+              .take()
+              .expect("delete_question_response should not be None.")
+      }
+      async fn delete_questions(&self, _: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+          unimplemented!()
+      }
+      async fn decide_contests(&self) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn get_feed_entries(&self, _: Option<String>, _: i64) -> Result<Vec<FeedEntry>, DBError> {
+          self.get_feed_entries_response
+              .lock()
+              .await
+              .take()
+              .expect("get_feed_entries_response should not be None.")
+      }
+      async fn count_sitemap_questions(&self) -> Result<i64, DBError> {
+          self.count_sitemap_questions_response
+              .lock()
+              .await
+              .take()
+              .expect("count_sitemap_questions_response should not be None.")
+      }
+      async fn stream_sitemap_urls(&self, _: i64, _: tokio::sync::mpsc::Sender<SitemapUrl>) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn stream_questions(&self, _: ExportRange, _: tokio::sync::mpsc::Sender<QuestionDetail>) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn restore_question(&self, _: String) -> Result<Option<QuestionDetail>, DBError> {
+          self.restore_question_response
+              .lock()
+              .await
+              .take()
+              .expect("restore_question_response should not be None.")
+      }
+      async fn purge_deleted_questions(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn get_question(&self, _: String, _: Viewer) -> Result<Option<QuestionDetail>, DBError> {
+          self.get_question_response
+              .lock()
+              .await
+              .take()
+              .expect("get_question_response should not be None.")
+      }
+      async fn get_questions(&self, _: Viewer, _: Option<String>) -> Result<Vec<QuestionDetail>, DBError> {
+          self.get_questions_response
+              .lock()
+              .await
+              .take()
+              .expect("get_questions_response should not be None.")
+      }
+      async fn get_questions_by_uuid(&self, _: Vec<String>, _: Viewer) -> Result<Vec<QuestionDetail>, DBError> {
+          self.get_questions_by_uuid_response
+              .lock()
+              .await
+              .take()
+              .expect("get_questions_by_uuid_response should not be None.")
+      }
+      async fn get_questions_by_author(&self, _: String, _: Pagination, _: Viewer) -> Result<Vec<QuestionDetail>, DBError> {
+          self.get_questions_by_author_response
+              .lock()
+              .await
+              .take()
+              .expect("get_questions_by_author_response should not be None.")
+      }
+      async fn update_question_status(
+          &self,
+          _: String,
+          _: QuestionStatus,
+          _: String,
+      ) -> Result<Option<QuestionDetail>, DBError> {
+          self.update_question_status_response
+              .lock()
+              .await
+              .take()
+              .expect("update_question_status_response should not be None.")
+      }
+      async fn update_question(
+          &self,
+          _: String,
+          _: Question,
+          _: String,
+      ) -> Result<Option<QuestionDetail>, DBError> {
+          self.update_question_response
+              .lock()
+              .await
+              .take()
+              .expect("update_question_response should not be None.")
+      }
+      async fn get_question_revisions(&self, _: String, _: Viewer) -> Result<Vec<QuestionRevision>, DBError> {
+          self.get_question_revisions_response
+              .lock()
+              .await
+              .take()
+              .expect("get_question_revisions_response should not be None.")
+      }
+      async fn accept_answer(&self, _: String, _: Option<Uuid>) -> Result<bool, DBError> {
+          self.accept_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("accept_answer_response should not be None.")
+      }
+  }
+
+  struct AnswersDaoMock {
+      create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+      delete_answer_response: Mutex<Option<Result<(), DBError>>>,
+      delete_answers_response: Mutex<Option<Result<Vec<BulkDeleteResult>, DBError>>>,
+      restore_answer_response: Mutex<Option<Result<Option<AnswerDetail>, DBError>>>,
+      get_answer_response: Mutex<Option<Result<Option<AnswerDetail>, DBError>>>,
+      get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+      update_answer_response: Mutex<Option<Result<Option<AnswerDetail>, DBError>>>,
+      get_answer_revisions_response: Mutex<Option<Result<Vec<AnswerRevision>, DBError>>>,
+      vote_answer_response: Mutex<Option<Result<i64, DBError>>>,
+  }
+
+  impl AnswersDaoMock {
+      pub fn new() -> Self {
+          AnswersDaoMock {
+              create_answer_response: Mutex::new(None),
+              delete_answer_response: Mutex::new(None),
+              delete_answers_response: Mutex::new(None),
+              restore_answer_response: Mutex::new(None),
+              get_answer_response: Mutex::new(None),
+              get_answers_response: Mutex::new(None),
+              update_answer_response: Mutex::new(None),
+              get_answer_revisions_response: Mutex::new(None),
+              vote_answer_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+          self.create_answer_response = Mutex::new(Some(response));
+      }
+      pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
+          self.delete_answer_response = Mutex::new(Some(response));
+      }
+      pub fn mock_delete_answers(&mut self, response: Result<Vec<BulkDeleteResult>, DBError>) {
+          self.delete_answers_response = Mutex::new(Some(response));
+      }
+      pub fn mock_restore_answer(&mut self, response: Result<Option<AnswerDetail>, DBError>) {
+          self.restore_answer_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_answer(&mut self, response: Result<Option<AnswerDetail>, DBError>) {
+          self.get_answer_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
+          self.get_answers_response = Mutex::new(Some(response));
+      }
+      pub fn mock_update_answer(&mut self, response: Result<Option<AnswerDetail>, DBError>) {
+          self.update_answer_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_answer_revisions(&mut self, response: Result<Vec<AnswerRevision>, DBError>) {
+          self.get_answer_revisions_response = Mutex::new(Some(response));
+      }
+      pub fn mock_vote_answer(&mut self, response: Result<i64, DBError>) {
+          self.vote_answer_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl AnswersDao for AnswersDaoMock {
+      async fn create_answer(&self, _: Answer, _: Option<String>, _: Option<i64>) -> Result<AnswerDetail, DBError> {
+          self.create_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("create_answer_response should not be None.")
+      }
+      async fn delete_answer(&self, _: String) -> Result<(), DBError> {
+          self.delete_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("delete_answer_response should not be None.")
+      }
+      async fn delete_answers(&self, _: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+          self.delete_answers_response
+              .lock()
+              .await
+              .take()
+              .expect("delete_answers_response should not be None.")
+      }
+      async fn restore_answer(&self, _: String) -> Result<Option<AnswerDetail>, DBError> {
+          self.restore_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("restore_answer_response should not be None.")
+      }
+      async fn purge_deleted_answers(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn get_answers(&self, _: String, _: AnswerSort, _: Viewer) -> Result<Vec<AnswerDetail>, DBError> {
+          self.get_answers_response
+              .lock()
+              .await
+              .take()
+              .expect("get_answers_response should not be None.")
+      }
+      async fn get_answers_by_author(&self, _: String, _: Pagination, _: Viewer) -> Result<Vec<AnswerDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn get_answer(&self, _: String, _: Viewer) -> Result<Option<AnswerDetail>, DBError> {
+          self.get_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("get_answer_response should not be None.")
+      }
+      async fn update_answer(
+          &self,
+          _: String,
+          _: String,
+          _: String,
+      ) -> Result<Option<AnswerDetail>, DBError> {
+          self.update_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("update_answer_response should not be None.")
+      }
+      async fn get_answer_revisions(&self, _: String, _: Viewer) -> Result<Vec<AnswerRevision>, DBError> {
+          self.get_answer_revisions_response
+              .lock()
+              .await
+              .take()
+              .expect("get_answer_revisions_response should not be None.")
+      }
+      async fn vote_answer(&self, _: String, _: String, _: i16) -> Result<i64, DBError> {
+          self.vote_answer_response
+              .lock()
+              .await
+              .take()
+              .expect("vote_answer_response should not be None.")
+      }
+      async fn stream_answers(&self, _: ExportRange, _: tokio::sync::mpsc::Sender<AnswerDetail>) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct DraftsDaoMock {
+      save_question_draft_response: Mutex<Option<Result<DraftDetail, DBError>>>,
+      get_draft_response: Mutex<Option<Result<Option<DraftDetail>, DBError>>>,
+      publish_draft_response: Mutex<Option<Result<Option<QuestionDetail>, DBError>>>,
+  }
+
+  impl DraftsDaoMock {
+      pub fn new() -> Self {
+          DraftsDaoMock {
+              save_question_draft_response: Mutex::new(None),
+              get_draft_response: Mutex::new(None),
+              publish_draft_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_save_question_draft(&mut self, response: Result<DraftDetail, DBError>) {
+          self.save_question_draft_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_draft(&mut self, response: Result<Option<DraftDetail>, DBError>) {
+          self.get_draft_response = Mutex::new(Some(response));
+      }
+      pub fn mock_publish_draft(&mut self, response: Result<Option<QuestionDetail>, DBError>) {
+          self.publish_draft_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl DraftsDao for DraftsDaoMock {
+      async fn save_question_draft(&self, _: String, _: QuestionDraft) -> Result<DraftDetail, DBError> {
+          self.save_question_draft_response
+              .lock()
+              .await
+              .take()
+              .expect("save_question_draft_response should not be None.")
+      }
+      async fn get_draft(&self, _: String, _: String) -> Result<Option<DraftDetail>, DBError> {
+          self.get_draft_response
+              .lock()
+              .await
+              .take()
+              .expect("get_draft_response should not be None.")
+      }
+      async fn publish_draft(&self, _: String, _: String) -> Result<Option<QuestionDetail>, DBError> {
+          self.publish_draft_response
+              .lock()
+              .await
+              .take()
+              .expect("publish_draft_response should not be None.")
+      }
+  }
+
+  struct NotificationsDaoMock {
+      notify_question_followers_response: Mutex<Option<Result<u64, DBError>>>,
+      notify_mentioned_users_response: Mutex<Option<Result<u64, DBError>>>,
+      notify_user_response: Mutex<Option<Result<(), DBError>>>,
+      set_accept_suggestions_enabled_response: Mutex<Option<Result<(), DBError>>>,
+      get_notification_settings_response: Mutex<Option<Result<NotificationSettings, DBError>>>,
+      set_notification_settings_response: Mutex<Option<Result<Option<String>, DBError>>>,
+      get_notifications_response: Mutex<Option<Result<Vec<Notification>, DBError>>>,
+      mark_notification_read_response: Mutex<Option<Result<bool, DBError>>>,
+      mark_all_notifications_read_response: Mutex<Option<Result<u64, DBError>>>,
+  }
+
+  impl NotificationsDaoMock {
+      pub fn new() -> Self {
+          NotificationsDaoMock {
+              notify_question_followers_response: Mutex::new(None),
+              notify_mentioned_users_response: Mutex::new(None),
+              notify_user_response: Mutex::new(None),
+              set_accept_suggestions_enabled_response: Mutex::new(None),
+              get_notification_settings_response: Mutex::new(None),
+              set_notification_settings_response: Mutex::new(None),
+              get_notifications_response: Mutex::new(None),
+              mark_notification_read_response: Mutex::new(None),
+              mark_all_notifications_read_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_notify_question_followers(&mut self, response: Result<u64, DBError>) {
+          self.notify_question_followers_response = Mutex::new(Some(response));
+      }
+      pub fn mock_notify_mentioned_users(&mut self, response: Result<u64, DBError>) {
+          self.notify_mentioned_users_response = Mutex::new(Some(response));
+      }
+      pub fn mock_notify_user(&mut self, response: Result<(), DBError>) {
+          self.notify_user_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_notification_settings(&mut self, response: Result<NotificationSettings, DBError>) {
+          self.get_notification_settings_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_notification_settings(&mut self, response: Result<Option<String>, DBError>) {
+          self.set_notification_settings_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_accept_suggestions_enabled(&mut self, response: Result<(), DBError>) {
+          self.set_accept_suggestions_enabled_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_notifications(&mut self, response: Result<Vec<Notification>, DBError>) {
+          self.get_notifications_response = Mutex::new(Some(response));
+      }
+      pub fn mock_mark_notification_read(&mut self, response: Result<bool, DBError>) {
+          self.mark_notification_read_response = Mutex::new(Some(response));
+      }
+      pub fn mock_mark_all_notifications_read(&mut self, response: Result<u64, DBError>) {
+          self.mark_all_notifications_read_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl NotificationsDao for NotificationsDaoMock {
+      async fn notify_question_followers(
+          &self,
+          _: String,
+          _: NotificationKind,
+          _: Option<String>,
+          _: Option<String>,
+      ) -> Result<u64, DBError> {
+          self.notify_question_followers_response
+              .lock()
+              .await
+              .take()
+              .expect("notify_question_followers_response should not be None.")
+      }
+      async fn notify_mentioned_users(
+          &self,
+          _: String,
+          _: Option<String>,
+          _: Vec<String>,
+          _: Option<String>,
+      ) -> Result<u64, DBError> {
+          self.notify_mentioned_users_response
+              .lock()
+              .await
+              .take()
+              .expect("notify_mentioned_users_response should not be None.")
+      }
+      async fn notify_user(
+          &self,
+          _: String,
+          _: NotificationKind,
+          _: String,
+          _: Option<String>,
+          _: Option<String>,
+      ) -> Result<(), DBError> {
+          self.notify_user_response
+              .lock()
+              .await
+              .take()
+              .expect("notify_user_response should not be None.")
+      }
+      async fn notify_accept_suggestions(&self, _: AcceptSuggestionThresholds) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn get_accept_suggestions_enabled(&self, _: String) -> Result<bool, DBError> {
+          unimplemented!()
+      }
+      async fn set_accept_suggestions_enabled(&self, _: String, _: bool) -> Result<(), DBError> {
+          self.set_accept_suggestions_enabled_response
+              .lock()
+              .await
+              .take()
+              .expect("set_accept_suggestions_enabled_response should not be None.")
+      }
+      async fn get_notification_settings(&self, _: String) -> Result<NotificationSettings, DBError> {
+          self.get_notification_settings_response
+              .lock()
+              .await
+              .take()
+              .expect("get_notification_settings_response should not be None.")
+      }
+      async fn set_notification_settings(&self, _: String, _: NotificationSettings, _: String) -> Result<Option<String>, DBError> {
+          self.set_notification_settings_response
+              .lock()
+              .await
+              .take()
+              .expect("set_notification_settings_response should not be None.")
+      }
+      async fn get_notifications(&self, _: String, _: bool, _: Pagination) -> Result<Vec<Notification>, DBError> {
+          self.get_notifications_response
+              .lock()
+              .await
+              .take()
+              .expect("get_notifications_response should not be None.")
+      }
+      async fn mark_notification_read(&self, _: String, _: i64) -> Result<bool, DBError> {
+          self.mark_notification_read_response
+              .lock()
+              .await
+              .take()
+              .expect("mark_notification_read_response should not be None.")
+      }
+      async fn mark_all_notifications_read(&self, _: String) -> Result<u64, DBError> {
+          self.mark_all_notifications_read_response
+              .lock()
+              .await
+              .take()
+              .expect("mark_all_notifications_read_response should not be None.")
+      }
+      async fn claim_pending_emails(&self, _: i64) -> Result<Vec<PendingEmail>, DBError> {
+          unimplemented!()
+      }
+      async fn mark_email_sent(&self, _: i64) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn skip_email(&self, _: i64) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn fail_email(&self, _: i64, _: String, _: i32) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct FollowsDaoMock {
+      follow_question_response: Mutex<Option<Result<(), DBError>>>,
+  }
+
+  impl FollowsDaoMock {
+      pub fn new() -> Self {
+          FollowsDaoMock {
+              follow_question_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_follow_question(&mut self, response: Result<(), DBError>) {
+          self.follow_question_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl FollowsDao for FollowsDaoMock {
+      async fn follow_question(&self, _: String, _: String) -> Result<(), DBError> {
+          self.follow_question_response
+              .lock()
+              .await
+              .take()
+              .expect("follow_question_response should not be None.")
+      }
+      async fn unfollow_question(&self, _: String, _: String) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct ContentDaoMock {
+      get_user_content_response: Mutex<Option<Result<MyContent, DBError>>>,
+  }
+
+  impl ContentDaoMock {
+      pub fn new() -> Self {
+          ContentDaoMock {
+              get_user_content_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_user_content(&mut self, response: Result<MyContent, DBError>) {
+          self.get_user_content_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl ContentDao for ContentDaoMock {
+      async fn get_user_content(&self, _: String, _: Pagination) -> Result<MyContent, DBError> {
+          self.get_user_content_response
+              .lock()
+              .await
+              .take()
+              .expect("get_user_content_response should not be None.")
+      }
+      async fn delete_user_drafts(&self, _: String) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn unsubscribe_user(&self, _: String) -> Result<Unsubscribed, DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct AttachmentsDaoMock {
+      create_attachment_response: Mutex<Option<Result<AttachmentDetail, DBError>>>,
+      get_attachment_response: Mutex<Option<Result<Option<AttachmentDetail>, DBError>>>,
+  }
+
+  impl AttachmentsDaoMock {
+      pub fn new() -> Self {
+          AttachmentsDaoMock {
+              create_attachment_response: Mutex::new(None),
+              get_attachment_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_attachment(&mut self, response: Result<AttachmentDetail, DBError>) {
+          self.create_attachment_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_attachment(&mut self, response: Result<Option<AttachmentDetail>, DBError>) {
+          self.get_attachment_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl AttachmentsDao for AttachmentsDaoMock {
+      async fn create_attachment(&self, _: Attachment, _: String) -> Result<AttachmentDetail, DBError> {
+          self.create_attachment_response
+              .lock()
+              .await
+              .take()
+              .expect("create_attachment_response should not be None.")
+      }
+      async fn get_attachment(&self, _: String) -> Result<Option<AttachmentDetail>, DBError> {
+          self.get_attachment_response
+              .lock()
+              .await
+              .take()
+              .expect("get_attachment_response should not be None.")
+      }
+  }
+
+  /// Keeps objects in memory so tests can check what was stored.
+  struct AuditDaoMock {
+      entries: std::sync::Mutex<Vec<AuditEntry>>,
+      get_audit_log_response: Mutex<Option<Result<Vec<AuditRecord>, DBError>>>,
+  }
+
+  impl AuditDaoMock {
+      pub fn new() -> Self {
+          AuditDaoMock {
+              entries: std::sync::Mutex::new(Vec::new()),
+              get_audit_log_response: Mutex::new(None),
+          }
+      }
+      pub fn entries(&self) -> Vec<AuditEntry> {
+          self.entries.lock().unwrap().clone()
+      }
+      pub fn mock_get_audit_log(&mut self, response: Result<Vec<AuditRecord>, DBError>) {
+          self.get_audit_log_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl AuditDao for AuditDaoMock {
+      async fn record_audit_entry(&self, entry: AuditEntry) -> Result<(), DBError> {
+          self.entries.lock().unwrap().push(entry);
+          Ok(())
+      }
+      async fn get_audit_log(&self, _: Option<PrimitiveDateTime>, _: Pagination) -> Result<Vec<AuditRecord>, DBError> {
+          self.get_audit_log_response
+              .lock()
+              .await
+              .take()
+              .expect("get_audit_log_response should not be None.")
+      }
+  }
+
+  struct ObjectStoreMock {
+      objects: std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>,
+  }
+
+  impl ObjectStoreMock {
+      pub fn new() -> Self {
+          ObjectStoreMock {
+              objects: std::sync::Mutex::new(std::collections::HashMap::new()),
+          }
+      }
+      pub fn len(&self) -> usize {
+          self.objects.lock().unwrap().len()
+      }
+  }
+
+  #[async_trait]
+  impl ObjectStore for ObjectStoreMock {
+      async fn put(&self, key: &str, _: &str, body: Vec<u8>) -> Result<(), StorageError> {
+          self.objects.lock().unwrap().insert(key.to_owned(), body);
+          Ok(())
+      }
+      async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+          Ok(self.objects.lock().unwrap().get(key).cloned())
+      }
+      async fn delete(&self, key: &str) -> Result<(), StorageError> {
+          self.objects.lock().unwrap().remove(key);
+          Ok(())
+      }
+  }
+
+  struct EmailDigestsDaoMock {
+      set_digest_settings_response: Mutex<Option<Result<(), DBError>>>,
+  }
+
+  impl EmailDigestsDaoMock {
+      pub fn new() -> Self {
+          EmailDigestsDaoMock {
+              set_digest_settings_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_set_digest_settings(&mut self, response: Result<(), DBError>) {
+          self.set_digest_settings_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl EmailDigestsDao for EmailDigestsDaoMock {
+      async fn get_digest_settings(&self, _: String) -> Result<DigestSettings, DBError> {
+          unimplemented!()
+      }
+      async fn set_digest_settings(&self, _: String, _: DigestSettings) -> Result<(), DBError> {
+          self.set_digest_settings_response
+              .lock()
+              .await
+              .take()
+              .expect("set_digest_settings_response should not be None.")
+      }
+      async fn claim_due_digests(&self, _: i64) -> Result<Vec<EmailDigest>, DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct RetentionDaoMock {
+      get_retention_stats_response: Mutex<Option<Result<Vec<RetentionStats>, DBError>>>,
+  }
+
+  impl RetentionDaoMock {
+      pub fn new() -> Self {
+          RetentionDaoMock {
+              get_retention_stats_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_retention_stats(&mut self, response: Result<Vec<RetentionStats>, DBError>) {
+          self.get_retention_stats_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl RetentionDao for RetentionDaoMock {
+      async fn purge_audit_log(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn purge_ip_addresses(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn purge_question_viewers(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn record_purge(&self, _: RetentionCategory, _: u64) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn get_retention_stats(&self) -> Result<Vec<RetentionStats>, DBError> {
+          self.get_retention_stats_response
+              .lock()
+              .await
+              .take()
+              .expect("get_retention_stats_response should not be None.")
+      }
+  }
+
+  struct QuerySamplesDaoMock {
+      set_sampling_percent_response: Mutex<Option<Result<(), DBError>>>,
+  }
+
+  impl QuerySamplesDaoMock {
+      pub fn new() -> Self {
+          QuerySamplesDaoMock {
+              set_sampling_percent_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_set_sampling_percent(&mut self, response: Result<(), DBError>) {
+          self.set_sampling_percent_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl QuerySamplesDao for QuerySamplesDaoMock {
+      async fn get_sampling_percent(&self) -> Result<f64, DBError> {
+          unimplemented!()
+      }
+      async fn set_sampling_percent(&self, _: f64, _: String) -> Result<(), DBError> {
+          self.set_sampling_percent_response
+              .lock()
+              .await
+              .take()
+              .expect("set_sampling_percent_response should not be None.")
+      }
+      async fn create_query_sample(&self, _: NewQuerySample) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn get_query_samples(&self, _: QuerySamplesQuery, _: Pagination) -> Result<Vec<QuerySample>, DBError> {
+          unimplemented!()
+      }
+      async fn purge_query_samples(&self, _: i32) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct HealthDaoMock {
+      ping_response: Mutex<Option<Result<(), DBError>>>,
+      get_pending_migrations_response: Mutex<Option<Result<Option<Vec<i64>>, DBError>>>,
+  }
+
+  impl HealthDaoMock {
+      pub fn new() -> Self {
+          HealthDaoMock {
+              ping_response: Mutex::new(None),
+              get_pending_migrations_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_ping(&mut self, response: Result<(), DBError>) {
+          self.ping_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_pending_migrations(&mut self, response: Result<Option<Vec<i64>>, DBError>) {
+          self.get_pending_migrations_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl HealthDao for HealthDaoMock {
+      async fn ping(&self) -> Result<(), DBError> {
+          self.ping_response
+              .lock()
+              .await
+              .take()
+              .expect("ping_response should not be None.")
+      }
+      async fn get_pending_migrations(&self) -> Result<Option<Vec<i64>>, DBError> {
+          self.get_pending_migrations_response
+              .lock()
+              .await
+              .take()
+              .expect("get_pending_migrations_response should not be None.")
+      }
+  }
+
+  struct FaqDaoMock {
+      set_faq_entry_response: Mutex<Option<Result<bool, DBError>>>,
+      get_faq_entries_response: Mutex<Option<Result<Vec<FaqEntry>, DBError>>>,
+  }
+
+  impl FaqDaoMock {
+      pub fn new() -> Self {
+          FaqDaoMock {
+              set_faq_entry_response: Mutex::new(None),
+              get_faq_entries_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_set_faq_entry(&mut self, response: Result<bool, DBError>) {
+          self.set_faq_entry_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_faq_entries(&mut self, response: Result<Vec<FaqEntry>, DBError>) {
+          self.get_faq_entries_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl FaqDao for FaqDaoMock {
+      async fn set_faq_entry(&self, _: String, _: String, _: String) -> Result<bool, DBError> {
+          self.set_faq_entry_response
+              .lock()
+              .await
+              .take()
+              .expect("set_faq_entry_response should not be None.")
+      }
+      async fn remove_faq_entry(&self, _: String) -> Result<bool, DBError> {
+          unimplemented!()
+      }
+      async fn get_faq_entries(&self) -> Result<Vec<FaqEntry>, DBError> {
+          self.get_faq_entries_response
+              .lock()
+              .await
+              .take()
+              .expect("get_faq_entries_response should not be None.")
+      }
+  }
+
+  /// Event, question UUID, answer UUID and forum URL of a queued delivery.
+  type QueuedDelivery = (WebhookEvent, String, Option<String>, String);
+
+  /// Creates webhooks as asked and records queued deliveries.
+  struct WebhooksDaoMock {
+      queued: std::sync::Mutex<Vec<QueuedDelivery>>,
+      get_webhook_deliveries_response: Mutex<Option<Result<Option<Vec<WebhookDelivery>>, DBError>>>,
+  }
+
+  impl WebhooksDaoMock {
+      pub fn new() -> Self {
+          WebhooksDaoMock {
+              queued: std::sync::Mutex::new(Vec::new()),
+              get_webhook_deliveries_response: Mutex::new(None),
+          }
+      }
+      pub fn queued(&self) -> Vec<QueuedDelivery> {
+          self.queued.lock().unwrap().clone()
+      }
+      pub fn mock_get_webhook_deliveries(&mut self, response: Result<Option<Vec<WebhookDelivery>>, DBError>) {
+          self.get_webhook_deliveries_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl WebhooksDao for WebhooksDaoMock {
+      async fn get_pending_digest(&self, _: String) -> Result<WebhookDigest, DBError> {
+          unimplemented!()
+      }
+      async fn mark_digest_delivered(&self, _: String, _: String) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn create_webhook(&self, webhook: NewWebhook, secret: String, _: String) -> Result<WebhookDetail, DBError> {
+          Ok(WebhookDetail {
+              webhook_uuid: Uuid::from_u128(0x123),
+              url: webhook.url,
+              events: webhook.events,
+              secret: Some(secret),
+              created_at: datetime!(2026-01-01 00:00:00.0 UTC),
+          })
+      }
+      async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn delete_webhook(&self, _: String) -> Result<bool, DBError> {
+          unimplemented!()
+      }
+      async fn get_webhook_deliveries(&self, _: String, _: Pagination) -> Result<Option<Vec<WebhookDelivery>>, DBError> {
+          self.get_webhook_deliveries_response
+              .lock()
+              .await
+              .take()
+              .expect("get_webhook_deliveries_response should not be None.")
+      }
+      async fn queue_webhook_deliveries(
+          &self,
+          event: WebhookEvent,
+          question_uuid: String,
+          answer_uuid: Option<String>,
+          forum_url: String,
+      ) -> Result<u64, DBError> {
+          self.queued.lock().unwrap().push((event, question_uuid, answer_uuid, forum_url));
+          Ok(1)
+      }
+      async fn claim_webhook_deliveries(&self, _: i64, _: i32) -> Result<Vec<PendingWebhookDelivery>, DBError> {
+          unimplemented!()
+      }
+      async fn mark_webhook_delivered(&self, _: String, _: i32) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn fail_webhook_delivery(&self, _: String, _: Option<i32>, _: String, _: Option<i32>) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct ErasureDaoMock {
+      erase_user_response: Mutex<Option<Result<Option<ErasureReport>, DBError>>>,
+      get_erasure_report_response: Mutex<Option<Result<Option<ErasureReport>, DBError>>>,
+  }
+
+  impl ErasureDaoMock {
+      pub fn new() -> Self {
+          ErasureDaoMock {
+              erase_user_response: Mutex::new(None),
+              get_erasure_report_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_erase_user(&mut self, response: Result<Option<ErasureReport>, DBError>) {
+          self.erase_user_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_erasure_report(&mut self, response: Result<Option<ErasureReport>, DBError>) {
+          self.get_erasure_report_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl ErasureDao for ErasureDaoMock {
+      async fn erase_user(&self, _: String, _: String, _: Option<i32>) -> Result<Option<ErasureReport>, DBError> {
+          self.erase_user_response
+              .lock()
+              .await
+              .take()
+              .expect("erase_user_response should not be None.")
+      }
+      async fn get_erasure_reports(&self, _: Pagination) -> Result<Vec<ErasureReport>, DBError> {
+          unimplemented!()
+      }
+      async fn get_erasure_report(&self, _: String) -> Result<Option<ErasureReport>, DBError> {
+          self.get_erasure_report_response
+              .lock()
+              .await
+              .take()
+              .expect("get_erasure_report_response should not be None.")
+      }
+  }
+
+  struct BoardsDaoMock {
+      create_board_response: Mutex<Option<Result<BoardDetail, DBError>>>,
+      is_board_member_response: Mutex<Option<Result<bool, DBError>>>,
+      get_board_member_response: Mutex<Option<Result<Option<BoardMember>, DBError>>>,
+      approve_board_member_response: Mutex<Option<Result<Option<BoardMember>, DBError>>>,
+      remove_board_member_response: Mutex<Option<Result<(), DBError>>>,
+  }
+
+  impl BoardsDaoMock {
+      pub fn new() -> Self {
+          BoardsDaoMock {
+              create_board_response: Mutex::new(None),
+              is_board_member_response: Mutex::new(None),
+              get_board_member_response: Mutex::new(None),
+              approve_board_member_response: Mutex::new(None),
+              remove_board_member_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_board(&mut self, response: Result<BoardDetail, DBError>) {
+          self.create_board_response = Mutex::new(Some(response));
+      }
+      pub fn mock_is_board_member(&mut self, response: Result<bool, DBError>) {
+          self.is_board_member_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_board_member(&mut self, response: Result<Option<BoardMember>, DBError>) {
+          self.get_board_member_response = Mutex::new(Some(response));
+      }
+      pub fn mock_approve_board_member(&mut self, response: Result<Option<BoardMember>, DBError>) {
+          self.approve_board_member_response = Mutex::new(Some(response));
+      }
+      pub fn mock_remove_board_member(&mut self, response: Result<(), DBError>) {
+          self.remove_board_member_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl BoardsDao for BoardsDaoMock {
+      async fn create_board(&self, _: Board, _: String) -> Result<BoardDetail, DBError> {
+          self.create_board_response
+              .lock()
+              .await
+              .take()
+              .expect("create_board_response should not be None.")
+      }
+      async fn is_board_member(&self, _: String, _: String) -> Result<bool, DBError> {
+          self.is_board_member_response
+              .lock()
+              .await
+              .take()
+              .expect("is_board_member_response should not be None.")
+      }
+      async fn get_board_member(&self, _: String, _: String) -> Result<Option<BoardMember>, DBError> {
+          self.get_board_member_response
+              .lock()
+              .await
+              .take()
+              .expect("get_board_member_response should not be None.")
+      }
+      async fn get_board_members(&self, _: String) -> Result<Vec<BoardMember>, DBError> {
+          unimplemented!()
+      }
+      async fn invite_board_member(&self, _: String, _: String, _: BoardRole) -> Result<BoardMember, DBError> {
+          unimplemented!()
+      }
+      async fn request_board_membership(&self, _: String, _: String) -> Result<BoardMember, DBError> {
+          unimplemented!()
+      }
+      async fn approve_board_member(&self, _: String, _: String) -> Result<Option<BoardMember>, DBError> {
+          self.approve_board_member_response
+              .lock()
+              .await
+              .take()
+              .expect("approve_board_member_response should not be None.")
+      }
+      async fn remove_board_member(&self, _: String, _: String) -> Result<(), DBError> {
+          self.remove_board_member_response
+              .lock()
+              .await
+              .take()
+              .expect("remove_board_member_response should not be None.")
+      }
+  }
+
+  struct InvitationsDaoMock {
+      create_invitation_response: Mutex<Option<Result<InvitationDetail, DBError>>>,
+      get_pending_invitation_response: Mutex<Option<Result<Option<InvitationDetail>, DBError>>>,
+      accept_invitation_response: Mutex<Option<Result<Option<InvitationDetail>, DBError>>>,
+  }
+
+  impl InvitationsDaoMock {
+      pub fn new() -> Self {
+          InvitationsDaoMock {
+              create_invitation_response: Mutex::new(None),
+              get_pending_invitation_response: Mutex::new(None),
+              accept_invitation_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_invitation(&mut self, response: Result<InvitationDetail, DBError>) {
+          self.create_invitation_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_pending_invitation(&mut self, response: Result<Option<InvitationDetail>, DBError>) {
+          self.get_pending_invitation_response = Mutex::new(Some(response));
+      }
+      pub fn mock_accept_invitation(&mut self, response: Result<Option<InvitationDetail>, DBError>) {
+          self.accept_invitation_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl InvitationsDao for InvitationsDaoMock {
+      async fn create_invitation(&self, _: Invitation, _: String) -> Result<InvitationDetail, DBError> {
+          self.create_invitation_response
+              .lock()
+              .await
+              .take()
+              .expect("create_invitation_response should not be None.")
+      }
+      async fn get_invitations(&self) -> Result<Vec<InvitationDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn get_pending_invitation(&self, _: String) -> Result<Option<InvitationDetail>, DBError> {
+          self.get_pending_invitation_response
+              .lock()
+              .await
+              .take()
+              .expect("get_pending_invitation_response should not be None.")
+      }
+      async fn accept_invitation(&self, _: String, _: String) -> Result<Option<InvitationDetail>, DBError> {
+          self.accept_invitation_response
+              .lock()
+              .await
+              .take()
+              .expect("accept_invitation_response should not be None.")
+      }
+  }
+
+  struct DeadLettersDaoMock {
+      get_dead_letter_response: Mutex<Option<Result<Option<DeadLetter>, DBError>>>,
+      record_dead_letter_failure_response: Mutex<Option<Result<(), DBError>>>,
+      delete_dead_letters_response: Mutex<Option<Result<Vec<BulkDeleteResult>, DBError>>>,
+  }
+
+  impl DeadLettersDaoMock {
+      pub fn new() -> Self {
+          DeadLettersDaoMock {
+              get_dead_letter_response: Mutex::new(None),
+              record_dead_letter_failure_response: Mutex::new(None),
+              delete_dead_letters_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_dead_letter(&mut self, response: Result<Option<DeadLetter>, DBError>) {
+          self.get_dead_letter_response = Mutex::new(Some(response));
+      }
+      pub fn mock_record_dead_letter_failure(&mut self, response: Result<(), DBError>) {
+          self.record_dead_letter_failure_response = Mutex::new(Some(response));
+      }
+      pub fn mock_delete_dead_letters(&mut self, response: Result<Vec<BulkDeleteResult>, DBError>) {
+          self.delete_dead_letters_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl DeadLettersDao for DeadLettersDaoMock {
+      async fn create_dead_letter(&self, _: DeadLetterKind, _: serde_json::Value, _: Vec<String>) -> Result<DeadLetter, DBError> {
+          unimplemented!()
+      }
+      async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, DBError> {
+          unimplemented!()
+      }
+      async fn get_dead_letter(&self, _: String) -> Result<Option<DeadLetter>, DBError> {
+          self.get_dead_letter_response
+              .lock()
+              .await
+              .take()
+              .expect("get_dead_letter_response should not be None.")
+      }
+      async fn record_dead_letter_failure(&self, _: String, _: String) -> Result<(), DBError> {
+          self.record_dead_letter_failure_response
+              .lock()
+              .await
+              .take()
+              .expect("record_dead_letter_failure_response should not be None.")
+      }
+      async fn delete_dead_letters(&self, _: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+          self.delete_dead_letters_response
+              .lock()
+              .await
+              .take()
+              .expect("delete_dead_letters_response should not be None.")
+      }
+  }
+
+  struct StatsDaoMock {
+      get_admin_stats_response: Mutex<Option<Result<AdminStats, DBError>>>,
+      get_question_stats_response: Mutex<Option<Result<Option<QuestionStats>, DBError>>>,
+  }
+
+  impl StatsDaoMock {
+      pub fn new() -> Self {
+          StatsDaoMock {
+              get_admin_stats_response: Mutex::new(None),
+              get_question_stats_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_admin_stats(&mut self, response: Result<AdminStats, DBError>) {
+          self.get_admin_stats_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_question_stats(&mut self, response: Result<Option<QuestionStats>, DBError>) {
+          self.get_question_stats_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl StatsDao for StatsDaoMock {
+      async fn get_admin_stats(&self, _: i32) -> Result<AdminStats, DBError> {
+          self.get_admin_stats_response
+              .lock()
+              .await
+              .take()
+              .expect("get_admin_stats_response should not be None.")
+      }
+      async fn record_question_views(&self, _: Vec<QuestionView>) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn get_question_stats(&self, _: String) -> Result<Option<QuestionStats>, DBError> {
+          self.get_question_stats_response
+              .lock()
+              .await
+              .take()
+              .expect("get_question_stats_response should not be None.")
+      }
+  }
+
+  struct JobsDaoMock {
+      create_job_response: Mutex<Option<Result<JobDetail, DBError>>>,
+      get_job_response: Mutex<Option<Result<Option<JobDetail>, DBError>>>,
+  }
+
+  impl JobsDaoMock {
+      pub fn new() -> Self {
+          JobsDaoMock {
+              create_job_response: Mutex::new(None),
+              get_job_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_job(&mut self, response: Result<JobDetail, DBError>) {
+          self.create_job_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_job(&mut self, response: Result<Option<JobDetail>, DBError>) {
+          self.get_job_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl JobsDao for JobsDaoMock {
+      async fn create_job(&self, _: JobKind, _: String, _: Option<serde_json::Value>) -> Result<JobDetail, DBError> {
+          self.create_job_response
+              .lock()
+              .await
+              .take()
+              .expect("create_job_response should not be None.")
+      }
+      async fn get_job(&self, _: String) -> Result<Option<JobDetail>, DBError> {
+          self.get_job_response
+              .lock()
+              .await
+              .take()
+              .expect("get_job_response should not be None.")
+      }
+      async fn claim_next_job(&self) -> Result<Option<JobDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn update_job_progress(&self, _: String, _: i32) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn complete_job(&self, _: String, _: serde_json::Value) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn fail_job(&self, _: String, _: String) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct LinkPreviewsDaoMock {
+      get_link_previews_response: Mutex<Option<Result<Vec<LinkPreview>, DBError>>>,
+  }
+
+  impl LinkPreviewsDaoMock {
+      pub fn new() -> Self {
+          LinkPreviewsDaoMock {
+              get_link_previews_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_link_previews(&mut self, response: Result<Vec<LinkPreview>, DBError>) {
+          self.get_link_previews_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl LinkPreviewsDao for LinkPreviewsDaoMock {
+      async fn get_link_previews(&self, _: Vec<String>) -> Result<Vec<LinkPreview>, DBError> {
+          self.get_link_previews_response
+              .lock()
+              .await
+              .take()
+              .expect("get_link_previews_response should not be None.")
+      }
+      async fn claim_pending_link_previews(&self, _: i64) -> Result<Vec<String>, DBError> {
+          unimplemented!()
+      }
+      async fn save_link_preview(&self, _: LinkPreview) -> Result<(), DBError> {
+          unimplemented!()
+      }
+      async fn fail_link_preview(&self, _: String, _: String) -> Result<(), DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct CleanupPoliciesDaoMock {
+      set_cleanup_policy_response: Mutex<Option<Result<Option<BoardCleanupPolicyDetail>, DBError>>>,
+      preview_cleanup_response: Mutex<Option<Result<BoardCleanup, DBError>>>,
+  }
+
+  impl CleanupPoliciesDaoMock {
+      pub fn new() -> Self {
+          CleanupPoliciesDaoMock {
+              set_cleanup_policy_response: Mutex::new(None),
+              preview_cleanup_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_set_cleanup_policy(&mut self, response: Result<Option<BoardCleanupPolicyDetail>, DBError>) {
+          self.set_cleanup_policy_response = Mutex::new(Some(response));
+      }
+      pub fn mock_preview_cleanup(&mut self, response: Result<BoardCleanup, DBError>) {
+          self.preview_cleanup_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl CleanupPoliciesDao for CleanupPoliciesDaoMock {
+      async fn get_cleanup_policies(&self) -> Result<Vec<BoardCleanupPolicyDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn get_cleanup_policy(&self, _: String) -> Result<Option<BoardCleanupPolicyDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn set_cleanup_policy(
+          &self,
+          _: String,
+          _: BoardCleanupPolicy,
+          _: String,
+      ) -> Result<Option<BoardCleanupPolicyDetail>, DBError> {
+          self.set_cleanup_policy_response
+              .lock()
+              .await
+              .take()
+              .expect("set_cleanup_policy_response should not be None.")
+      }
+      async fn delete_cleanup_policy(&self, _: String) -> Result<bool, DBError> {
+          unimplemented!()
+      }
+      async fn preview_cleanup(&self, _: String, _: BoardCleanupPolicy) -> Result<BoardCleanup, DBError> {
+          self.preview_cleanup_response
+              .lock()
+              .await
+              .take()
+              .expect("preview_cleanup_response should not be None.")
+      }
+      async fn run_cleanup(&self, _: String, _: BoardCleanupPolicy) -> Result<BoardCleanup, DBError> {
+          unimplemented!()
+      }
+  }
+
+  struct FlagsDaoMock {
+      create_flag_response: Mutex<Option<Result<FlagDetail, DBError>>>,
+      get_flags_response: Mutex<Option<Result<Vec<FlagDetail>, DBError>>>,
+      resolve_flag_response: Mutex<Option<Result<Option<FlagDetail>, DBError>>>,
+  }
+
+  impl FlagsDaoMock {
+      pub fn new() -> Self {
+          FlagsDaoMock {
+              create_flag_response: Mutex::new(None),
+              get_flags_response: Mutex::new(None),
+              resolve_flag_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_flag(&mut self, response: Result<FlagDetail, DBError>) {
+          self.create_flag_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_flags(&mut self, response: Result<Vec<FlagDetail>, DBError>) {
+          self.get_flags_response = Mutex::new(Some(response));
+      }
+      pub fn mock_resolve_flag(&mut self, response: Result<Option<FlagDetail>, DBError>) {
+          self.resolve_flag_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl FlagsDao for FlagsDaoMock {
+      async fn create_flag(&self, _: String, _: Option<String>, _: Option<String>, _: Flag) -> Result<FlagDetail, DBError> {
+          self.create_flag_response
+              .lock()
+              .await
+              .take()
+              .expect("create_flag_response should not be None.")
+      }
+      async fn get_flags(&self, _: FlagStatus, _: Pagination) -> Result<Vec<FlagDetail>, DBError> {
+          self.get_flags_response
+              .lock()
+              .await
+              .take()
+              .expect("get_flags_response should not be None.")
+      }
+      async fn resolve_flag(
+          &self,
+          _: String,
+          _: String,
+          _: FlagStatus,
+          _: Option<String>,
+      ) -> Result<Option<FlagDetail>, DBError> {
+          self.resolve_flag_response
+              .lock()
+              .await
+              .take()
+              .expect("resolve_flag_response should not be None.")
+      }
+  }
+
+  struct ModerationDaoMock {
+      get_moderation_queue_response: Mutex<Option<Result<Vec<ModerationItem>, DBError>>>,
+      record_moderation_action_response: Mutex<Option<Result<ModerationActionDetail, DBError>>>,
+      recent_posts: std::sync::Mutex<Vec<String>>,
+      recent_post_times: std::sync::Mutex<Vec<OffsetDateTime>>,
+      held_posts: std::sync::Mutex<Vec<(String, Option<String>, String)>>,
+  }
+
+  impl ModerationDaoMock {
+      pub fn new() -> Self {
+          ModerationDaoMock {
+              get_moderation_queue_response: Mutex::new(None),
+              record_moderation_action_response: Mutex::new(None),
+              recent_posts: std::sync::Mutex::new(Vec::new()),
+              recent_post_times: std::sync::Mutex::new(Vec::new()),
+              held_posts: std::sync::Mutex::new(Vec::new()),
+          }
+      }
+      pub fn mock_recent_posts(&mut self, posts: Vec<String>) {
+          self.recent_posts = std::sync::Mutex::new(posts);
+      }
+      pub fn mock_recent_post_times(&mut self, times: Vec<OffsetDateTime>) {
+          self.recent_post_times = std::sync::Mutex::new(times);
+      }
+      pub fn held_posts(&self) -> Vec<(String, Option<String>, String)> {
+          self.held_posts.lock().unwrap().clone()
+      }
+      pub fn mock_get_moderation_queue(&mut self, response: Result<Vec<ModerationItem>, DBError>) {
+          self.get_moderation_queue_response = Mutex::new(Some(response));
+      }
+      pub fn mock_record_moderation_action(&mut self, response: Result<ModerationActionDetail, DBError>) {
+          self.record_moderation_action_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl ModerationDao for ModerationDaoMock {
+      async fn get_moderation_queue(&self, _: ModerationQueueQuery, _: Pagination) -> Result<Vec<ModerationItem>, DBError> {
+          self.get_moderation_queue_response
+              .lock()
+              .await
+              .take()
+              .expect("get_moderation_queue_response should not be None.")
+      }
+      async fn record_moderation_action(
+          &self,
+          _: String,
+          _: String,
+          _: Option<String>,
+          _: ModerationActionKind,
+          _: String,
+      ) -> Result<ModerationActionDetail, DBError> {
+          self.record_moderation_action_response
+              .lock()
+              .await
+              .take()
+              .expect("record_moderation_action_response should not be None.")
+      }
+      async fn get_moderation_actions(&self, _: Pagination) -> Result<Vec<ModerationActionDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn get_recent_posts(&self, _: String, _: i32) -> Result<Vec<String>, DBError> {
+          Ok(self.recent_posts.lock().unwrap().clone())
+      }
+      async fn get_recent_post_times(&self, _: String, _: PostKind, _: i32) -> Result<Vec<OffsetDateTime>, DBError> {
+          Ok(self.recent_post_times.lock().unwrap().clone())
+      }
+      async fn hold_post(&self, question_uuid: String, answer_uuid: Option<String>, details: String) -> Result<(), DBError> {
+          self.held_posts.lock().unwrap().push((question_uuid, answer_uuid, details));
+          Ok(())
+      }
+  }
+
+  struct TagsDaoMock {
+      get_tag_suggestions_response: Mutex<Option<Result<Vec<TagUsage>, DBError>>>,
+      get_tag_stats_response: Mutex<Option<Result<Option<TagStats>, DBError>>>,
+      get_board_tag_rules_response: Mutex<Option<Result<Option<BoardTagRulesDetail>, DBError>>>,
+      set_board_tag_rules_response: Mutex<Option<Result<Option<BoardTagRulesDetail>, DBError>>>,
+      get_unused_tags_response: Mutex<Option<Result<Vec<String>, DBError>>>,
+      pending_tags: std::sync::Mutex<Vec<(String, Vec<String>)>>,
+      approve_pending_tag_response: Mutex<Option<Result<Option<PendingTagResolution>, DBError>>>,
+      get_accepted_answers_response: Mutex<Option<Result<Vec<TagAcceptedAnswer>, DBError>>>,
+  }
+
+  impl TagsDaoMock {
+      pub fn new() -> Self {
+          TagsDaoMock {
+              get_tag_suggestions_response: Mutex::new(None),
+              get_tag_stats_response: Mutex::new(None),
+              get_board_tag_rules_response: Mutex::new(None),
+              set_board_tag_rules_response: Mutex::new(None),
+              get_unused_tags_response: Mutex::new(None),
+              pending_tags: std::sync::Mutex::new(Vec::new()),
+              approve_pending_tag_response: Mutex::new(None),
+              get_accepted_answers_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_get_tag_suggestions(&mut self, response: Result<Vec<TagUsage>, DBError>) {
+          self.get_tag_suggestions_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_tag_stats(&mut self, response: Result<Option<TagStats>, DBError>) {
+          self.get_tag_stats_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_board_tag_rules(&mut self, response: Result<Option<BoardTagRulesDetail>, DBError>) {
+          self.get_board_tag_rules_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_board_tag_rules(&mut self, response: Result<Option<BoardTagRulesDetail>, DBError>) {
+          self.set_board_tag_rules_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_unused_tags(&mut self, response: Result<Vec<String>, DBError>) {
+          self.get_unused_tags_response = Mutex::new(Some(response));
+      }
+      pub fn pending_tags(&self) -> Vec<(String, Vec<String>)> {
+          self.pending_tags.lock().unwrap().clone()
+      }
+      pub fn mock_approve_pending_tag(&mut self, response: Result<Option<PendingTagResolution>, DBError>) {
+          self.approve_pending_tag_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_accepted_answers(&mut self, response: Result<Vec<TagAcceptedAnswer>, DBError>) {
+          self.get_accepted_answers_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl TagsDao for TagsDaoMock {
+      async fn get_tag_suggestions(&self, _: String, _: i64) -> Result<Vec<TagUsage>, DBError> {
+          self.get_tag_suggestions_response
+              .lock()
+              .await
+              .take()
+              .expect("get_tag_suggestions_response should not be None.")
+      }
+      async fn get_tag_stats(&self, _: String) -> Result<Option<TagStats>, DBError> {
+          self.get_tag_stats_response
+              .lock()
+              .await
+              .take()
+              .expect("get_tag_stats_response should not be None.")
+      }
+      async fn get_board_tag_rules(&self, _: String) -> Result<Option<BoardTagRulesDetail>, DBError> {
+          self.get_board_tag_rules_response
+              .lock()
+              .await
+              .take()
+              .expect("get_board_tag_rules_response should not be None.")
+      }
+      async fn set_board_tag_rules(
+          &self,
+          _: String,
+          _: BoardTagRules,
+          _: String,
+      ) -> Result<Option<BoardTagRulesDetail>, DBError> {
+          self.set_board_tag_rules_response
+              .lock()
+              .await
+              .take()
+              .expect("set_board_tag_rules_response should not be None.")
+      }
+      async fn delete_board_tag_rules(&self, _: String) -> Result<bool, DBError> {
+          unimplemented!()
+      }
+      async fn get_unused_tags(&self, _: Vec<String>) -> Result<Vec<String>, DBError> {
+          self.get_unused_tags_response
+              .lock()
+              .await
+              .take()
+              .expect("get_unused_tags_response should not be None.")
+      }
+      async fn add_pending_tags(&self, question_uuid: String, tags: Vec<String>, _: Option<String>) -> Result<(), DBError> {
+          self.pending_tags.lock().unwrap().push((question_uuid, tags));
+          Ok(())
+      }
+      async fn get_pending_tags(&self, _: Pagination) -> Result<Vec<PendingTag>, DBError> {
+          unimplemented!()
+      }
+      async fn approve_pending_tag(&self, _: String) -> Result<Option<PendingTagResolution>, DBError> {
+          self.approve_pending_tag_response
+              .lock()
+              .await
+              .take()
+              .expect("approve_pending_tag_response should not be None.")
+      }
+      async fn reject_pending_tag(&self, _: String) -> Result<Option<PendingTagResolution>, DBError> {
+          unimplemented!()
+      }
+      async fn get_accepted_answers(&self, _: String) -> Result<Vec<TagAcceptedAnswer>, DBError> {
+          self.get_accepted_answers_response
+              .lock()
+              .await
+              .take()
+              .expect("get_accepted_answers_response should not be None.")
+      }
+  }
+
+  struct UsersDaoMock {
+      create_user_response: Mutex<Option<Result<UserDetail, DBError>>>,
+      get_user_by_token_hash_response: Mutex<Option<Result<Option<UserDetail>, DBError>>>,
+      record_user_ip_response: Mutex<Option<Result<(), DBError>>>,
+      provision_user_response: Mutex<Option<Result<ProvisionedUserDetail, DBError>>>,
+      set_user_active_response: Mutex<Option<Result<Option<ProvisionedUserDetail>, DBError>>>,
+      upsert_directory_user_response: Mutex<Option<Result<Option<ProvisionedUserDetail>, DBError>>>,
+      get_user_profile_response: Mutex<Option<Result<Option<UserProfile>, DBError>>>,
+      set_avatar_key_response: Mutex<Option<Result<Option<String>, DBError>>>,
+      get_reputation_response: Mutex<Option<Result<i64, DBError>>>,
+      set_shadow_banned_response: Mutex<Option<Result<bool, DBError>>>,
+  }
+
+  impl UsersDaoMock {
+      pub fn new() -> Self {
+          UsersDaoMock {
+              create_user_response: Mutex::new(None),
+              get_user_by_token_hash_response: Mutex::new(None),
+              record_user_ip_response: Mutex::new(None),
+              provision_user_response: Mutex::new(None),
+              set_user_active_response: Mutex::new(None),
+              upsert_directory_user_response: Mutex::new(None),
+              get_user_profile_response: Mutex::new(None),
+              set_avatar_key_response: Mutex::new(None),
+              get_reputation_response: Mutex::new(None),
+              set_shadow_banned_response: Mutex::new(None),
+          }
+      }
+      pub fn mock_create_user(&mut self, response: Result<UserDetail, DBError>) {
+          self.create_user_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_user_by_token_hash(&mut self, response: Result<Option<UserDetail>, DBError>) {
+          self.get_user_by_token_hash_response = Mutex::new(Some(response));
+      }
+      pub fn mock_record_user_ip(&mut self, response: Result<(), DBError>) {
+          self.record_user_ip_response = Mutex::new(Some(response));
+      }
+      pub fn mock_provision_user(&mut self, response: Result<ProvisionedUserDetail, DBError>) {
+          self.provision_user_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_user_active(&mut self, response: Result<Option<ProvisionedUserDetail>, DBError>) {
+          self.set_user_active_response = Mutex::new(Some(response));
+      }
+      pub fn mock_upsert_directory_user(&mut self, response: Result<Option<ProvisionedUserDetail>, DBError>) {
+          self.upsert_directory_user_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_user_profile(&mut self, response: Result<Option<UserProfile>, DBError>) {
+          self.get_user_profile_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_avatar_key(&mut self, response: Result<Option<String>, DBError>) {
+          self.set_avatar_key_response = Mutex::new(Some(response));
+      }
+      pub fn mock_get_reputation(&mut self, response: Result<i64, DBError>) {
+          self.get_reputation_response = Mutex::new(Some(response));
+      }
+      pub fn mock_set_shadow_banned(&mut self, response: Result<bool, DBError>) {
+          self.set_shadow_banned_response = Mutex::new(Some(response));
+      }
+  }
+
+  #[async_trait]
+  impl UsersDao for UsersDaoMock {
+      async fn create_user(&self, _: User, _: String) -> Result<UserDetail, DBError> {
+          self.create_user_response
+              .lock()
+              .await
+              .take()
+              .expect("create_user_response should not be None.")
+      }
+      async fn get_user_by_token_hash(&self, _: String) -> Result<Option<UserDetail>, DBError> {
+          self.get_user_by_token_hash_response
+              .lock()
+              .await
+              .take()
+              .expect("get_user_by_token_hash_response should not be None.")
+      }
+      async fn record_user_ip(&self, _: String, _: String) -> Result<(), DBError> {
+          self.record_user_ip_response
+              .lock()
+              .await
+              .take()
+              .expect("record_user_ip_response should not be None.")
+      }
+      async fn get_user_ip_history(&self, _: String) -> Result<Vec<UserIpRecord>, DBError> {
+          unimplemented!()
+      }
+      async fn rotate_encryption_keys(&self) -> Result<u64, DBError> {
+          unimplemented!()
+      }
+      async fn provision_user(&self, _: ProvisionedUser, _: String) -> Result<ProvisionedUserDetail, DBError> {
+          self.provision_user_response
+              .lock()
+              .await
+              .take()
+              .expect("provision_user_response should not be None.")
+      }
+      async fn get_provisioned_user(&self, _: String) -> Result<Option<ProvisionedUserDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn get_provisioned_users(&self, _: Option<String>) -> Result<Vec<ProvisionedUserDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn update_provisioned_user(&self, _: String, _: ProvisionedUser) -> Result<Option<ProvisionedUserDetail>, DBError> {
+          unimplemented!()
+      }
+      async fn set_user_active(&self, _: String, active: bool) -> Result<Option<ProvisionedUserDetail>, DBError> {
+          self.set_user_active_response
+              .lock()
+              .await
+              .take()
+              .expect("set_user_active_response should not be None.")
+              .map(|user| user.map(|user| ProvisionedUserDetail { active, ..user }))
+      }
+      async fn upsert_directory_user(&self, _: ProvisionedUser, _: String) -> Result<Option<ProvisionedUserDetail>, DBError> {
+          self.upsert_directory_user_response
+              .lock()
+              .await
+              .take()
+              .expect("upsert_directory_user_response should not be None.")
+      }
+      async fn get_user_profile(&self, _: String) -> Result<Option<UserProfile>, DBError> {
+          self.get_user_profile_response
+              .lock()
+              .await
+              .take()
+              .expect("get_user_profile_response should not be None.")
+      }
+      async fn set_avatar_key(&self, _: String, _: Option<String>) -> Result<Option<String>, DBError> {
+          self.set_avatar_key_response
+              .lock()
+              .await
+              .take()
+              .expect("set_avatar_key_response should not be None.")
+      }
+      async fn get_reputation(&self, _: String) -> Result<i64, DBError> {
+          // The posting quota and the new tag policy both read it, so a reputation is kept.
+          let mut response = self.get_reputation_response.lock().await;
+
+          match response.take().expect("get_reputation_response should not be None.") {
+              Ok(reputation) => {
+                  *response = Some(Ok(reputation));
+                  Ok(reputation)
+              }
+              Err(err) => Err(err),
+          }
+      }
+      async fn set_shadow_banned(&self, _: String, _: bool) -> Result<bool, DBError> {
+          self.set_shadow_banned_response
+              .lock()
+              .await
+              .take()
+              .expect("set_shadow_banned_response should not be None.")
+      }
+      async fn get_user_email(&self, _: String) -> Result<Option<String>, DBError> {
+          unimplemented!()
+      }
+  }
+
+  fn user_with_role(role: Role) -> UserDetail {
+      UserDetail {
+          user_uuid: Uuid::from_u128(0x789),
+          username: "test user".to_owned(),
+          email: None,
+          role,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  fn question_cache() -> QuestionCache {
+      QuestionCache::new(std::sync::Arc::new(MemoryCache::new(10)), Duration::from_secs(60), Duration::from_secs(60))
+  }
+
+  fn question_with_status(status: QuestionStatus) -> QuestionDetail {
+      QuestionDetail {
+          question_uuid: Uuid::from_u128(0x123),
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          status,
+          status_reason: None,
+          kind: QuestionKind::Question,
+          author_uuid: None,
+          visibility: Visibility::Public,
+          board_uuid: None,
+          tags: Vec::new(),
+          language: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+          description_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          held_for_review: false,
+          pending_tags: Vec::new(),
+          contest: None,
+      }
+  }
+
+  #[tokio::test]
+  async fn create_question_should_return_question() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          ..Default::default()
+      };
+
+      let question_detail = QuestionDetail {
+          question_uuid: Uuid::from_u128(0x123),
           title: question.title.clone(),
           description: question.description.clone(),
-          created_at: "now".to_owned(),
+          status: QuestionStatus::Open,
+          status_reason: None,
+          kind: QuestionKind::Question,
+          author_uuid: None,
+          visibility: Visibility::Public,
+          board_uuid: None,
+          tags: Vec::new(),
+          language: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+          description_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          held_for_review: false,
+          pending_tags: Vec::new(),
+          contest: None,
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_create_question(Ok(question_detail.clone()));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = create_question(question, None, questions_dao.as_ref(), boards_dao.as_ref(), &TagsDaoMock::new(), &ModerationDaoMock::new(), &NoSpamChecker, [203, 0, 113, 1].into(), &UsersDaoMock::new(), NewTagPolicy::default(), PostingQuotaPolicy::default(), 0, &ContentPolicy::default(), &EventBus::default()).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), question_detail);
+  }
+
+  #[tokio::test]
+  async fn create_question_should_return_error() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          ..Default::default()
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = create_question(question, None, questions_dao.as_ref(), boards_dao.as_ref(), &TagsDaoMock::new(), &ModerationDaoMock::new(), &NoSpamChecker, [203, 0, 113, 1].into(), &UsersDaoMock::new(), NewTagPolicy::default(), PostingQuotaPolicy::default(), 0, &ContentPolicy::default(), &EventBus::default()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_should_reject_invalid_fields_before_storing() {
+      let question = Question {
+          title: " ".to_owned(),
+          description: "test description".to_owned(),
+          ..Default::default()
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = create_question(question, None, questions_dao.as_ref(), boards_dao.as_ref(), &TagsDaoMock::new(), &ModerationDaoMock::new(), &NoSpamChecker, [203, 0, 113, 1].into(), &UsersDaoMock::new(), NewTagPolicy::default(), PostingQuotaPolicy::default(), 0, &ContentPolicy::default(), &EventBus::default()).await;
+
+      match result {
+          Err(HandlerError::ValidationFailed(errors)) => {
+              assert_eq!(errors.len(), 1);
+              assert_eq!(errors[0].field, "title");
+          }
+          _ => panic!("Expected the invalid title to be reported."),
+      }
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_return_questions() {
+      let question_detail = QuestionDetail {
+          question_uuid: Uuid::from_u128(0x123),
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          status: QuestionStatus::Open,
+          status_reason: None,
+          kind: QuestionKind::Question,
+          author_uuid: None,
+          visibility: Visibility::Public,
+          board_uuid: None,
+          tags: Vec::new(),
+          language: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+          description_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          held_for_review: false,
+          pending_tags: Vec::new(),
+          contest: None,
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_questions(Viewer::Anonymous, LanguageQuery::default(), questions_dao.as_ref(), &question_cache()).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), vec![question_detail]);
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_serve_anonymous_viewers_from_the_cache() {
+      let question_cache = question_cache();
+
+      question_cache.set_questions(None, &[question_with_status(QuestionStatus::Open)]).await;
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let result = read_questions(Viewer::Anonymous, LanguageQuery::default(), questions_dao.as_ref(), &question_cache).await;
+
+      assert_eq!(result.unwrap(), vec![question_with_status(QuestionStatus::Open)]);
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_not_cache_for_signed_in_viewers() {
+      let question_cache = question_cache();
+
+      question_cache.set_questions(None, &[question_with_status(QuestionStatus::Open)]).await;
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions(Ok(vec![question_with_status(QuestionStatus::Locked)]));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let viewer = Viewer::User(Uuid::from_u128(0x789));
+      let result = read_questions(viewer, LanguageQuery::default(), questions_dao.as_ref(), &question_cache).await;
+
+      assert_eq!(result.unwrap(), vec![question_with_status(QuestionStatus::Locked)]);
+      assert_eq!(question_cache.questions(None).await, Some(vec![question_with_status(QuestionStatus::Open)]));
+  }
+
+  #[tokio::test]
+  async fn invalidate_cached_questions_should_drop_updated_questions() {
+      let question_cache = question_cache();
+      let question = question_with_status(QuestionStatus::Open);
+
+      question_cache.set_question(&question).await;
+      question_cache.set_questions(None, std::slice::from_ref(&question)).await;
+
+      let event = DomainEvent::QuestionUpdated {
+          question_uuid: question.question_uuid.to_string(),
+      };
+      invalidate_cached_questions(event, &question_cache).await;
+
+      assert_eq!(question_cache.question(&question.question_uuid.to_string()).await, None);
+      assert_eq!(question_cache.questions(None).await, None);
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_return_error() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_questions(Viewer::Anonymous, LanguageQuery::default(), questions_dao.as_ref(), &question_cache()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_return_service_unavailable_when_database_is_down() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions(Err(DBError::ConnectionError));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_questions(Viewer::Anonymous, LanguageQuery::default(), questions_dao.as_ref(), &question_cache()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::ServiceUnavailable("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_return_gateway_timeout_when_database_times_out() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions(Err(DBError::Timeout));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_questions(Viewer::Anonymous, LanguageQuery::default(), questions_dao.as_ref(), &question_cache()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::GatewayTimeout("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_questions_should_reject_unknown_languages() {
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let query = LanguageQuery { lang: Some("en".to_owned()) };
+
+      let result = read_questions(Viewer::Anonymous, query, questions_dao.as_ref(), &question_cache()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_questions_batch_should_return_questions() {
+      let question = question_with_status(QuestionStatus::Open);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions_by_uuid(Ok(vec![question.clone()]));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let batch = QuestionBatch {
+          question_uuids: vec![question.question_uuid],
+      };
+
+      let result = read_questions_batch(batch, Viewer::Anonymous, questions_dao.as_ref()).await;
+
+      assert_eq!(result.unwrap(), vec![question]);
+  }
+
+  #[tokio::test]
+  async fn read_questions_batch_should_reject_too_many_uuids() {
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let batch = QuestionBatch {
+          question_uuids: vec![Uuid::from_u128(0x123); QuestionBatch::MAX_QUESTIONS + 1],
+      };
+
+      let result = read_questions_batch(batch, Viewer::Anonymous, questions_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_user_questions_should_return_questions() {
+      let question = question_with_status(QuestionStatus::Open);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_questions_by_author(Ok(vec![question.clone()]));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_user_questions(
+          "789".to_owned(),
+          Pagination::default(),
+          Viewer::Anonymous,
+          questions_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap(), vec![question]);
+  }
+
+  #[tokio::test]
+  async fn read_user_answers_should_reject_oversized_limit() {
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+
+      let page = Pagination {
+          offset: 0,
+          limit: Pagination::MAX_LIMIT + 1,
+      };
+
+      let result = read_user_answers("789".to_owned(), page, Viewer::Anonymous, answers_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn delete_question_should_succeed() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let user = user_with_role(Role::User);
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      let deletion = QuestionDeletion {
+          question_uuid: Uuid::from_u128(0x123),
+          answers: 2,
+          votes: 3,
+          followers: 1,
+          pending_tags: 0,
+      };
+
+      questions_dao.mock_get_question(Ok(Some(QuestionDetail {
+          author_uuid: Some(user.user_uuid),
+          ..question_with_status(QuestionStatus::Open)
+      })));
+      questions_dao.mock_delete_question(Ok(Some(deletion.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let audit_dao = AuditDaoMock::new();
+
+      let result = delete_question(question_id, &user, questions_dao.as_ref(), &audit_dao, &EventBus::default()).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), deletion);
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::DeleteQuestion);
+      assert_eq!(entries[0].target_uuid, Some(Uuid::from_u128(0x123).to_string()));
+      assert_eq!(entries[0].payload["answers"], 2);
+  }
+
+  #[tokio::test]
+  async fn delete_question_should_be_forbidden_to_other_users() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(QuestionDetail {
+          author_uuid: Some(Uuid::from_u128(0x999)),
+          ..question_with_status(QuestionStatus::Open)
+      })));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let audit_dao = AuditDaoMock::new();
+
+      let result = delete_question(
+          question_id,
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          &audit_dao,
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(matches!(result.unwrap_err(), HandlerError::Forbidden(_)));
+      assert!(audit_dao.entries().is_empty());
+  }
+
+  #[tokio::test]
+  async fn delete_question_should_let_moderators_delete_any_question() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(QuestionDetail {
+          author_uuid: Some(Uuid::from_u128(0x999)),
+          ..question_with_status(QuestionStatus::Open)
+      })));
+      questions_dao.mock_delete_question(Ok(Some(QuestionDeletion {
+          question_uuid: Uuid::from_u128(0x123),
+          answers: 0,
+          votes: 0,
+          followers: 0,
+          pending_tags: 0,
+      })));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let audit_dao = AuditDaoMock::new();
+
+      let result = delete_question(
+          question_id,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &audit_dao,
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(audit_dao.entries().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn delete_question_should_return_not_found_for_missing_question() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = delete_question(
+          question_id,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap_err(), HandlerError::NotFound("Question not found.".to_owned()));
+  }
+
+  #[tokio::test]
+  async fn delete_question_should_return_error() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = delete_question(
+          question_id,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_answer() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let answer_detail = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: answer.question_uuid,
+          content: answer.content.clone(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let events = EventBus::default();
+      let mut published = events.subscribe();
+
+      let result = create_answer(
+          answer,
+          None,
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &events,
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), answer_detail);
+      assert_eq!(
+          published.try_recv().unwrap().event,
+          DomainEvent::AnswerCreated {
+              question_uuid: "00000000-0000-0000-0000-000000000123".to_owned(),
+              answer_uuid: "00000000-0000-0000-0000-000000000456".to_owned(),
+              author_uuid: None,
+              content: "test content".to_owned(),
+          }
+      );
+  }
+
+  /// Post times `count` minutes apart, the first `first_age_seconds` before `now`.
+  fn post_times(now: u64, first_age_seconds: i64, count: i64) -> Vec<OffsetDateTime> {
+      (0..count)
+          .map(|i| OffsetDateTime::from_unix_timestamp(now as i64 - first_age_seconds + i * 60).unwrap())
+          .collect()
+  }
+
+  #[tokio::test]
+  async fn create_question_should_refuse_authors_over_their_posting_quota() {
+      let now = 1_767_225_600;
+      let mut moderation_dao = ModerationDaoMock::new();
+      let mut users_dao = UsersDaoMock::new();
+
+      moderation_dao.mock_recent_post_times(post_times(now, 3000, 5));
+      users_dao.mock_get_reputation(Ok(24));
+
+      let result = create_question(
+          Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          },
+          Some(&user_with_role(Role::User)),
+          &QuestionsDaoMock::new(),
+          &BoardsDaoMock::new(),
+          &TagsDaoMock::new(),
+          &moderation_dao,
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          now,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(
+          result,
+          Err(HandlerError::PostingQuotaExceeded(PostingQuotaExceeded {
+              message: "You can post 5 questions per hour at your reputation of 24; try again in 600 seconds.".to_owned(),
+              kind: PostKind::Question,
+              limit: 5,
+              window_seconds: 3600,
+              reputation: 24,
+              next_limit_reputation: 25,
+              retry_after_seconds: 600,
+          }))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_relax_the_posting_quota_with_reputation() {
+      let now = 1_767_225_600;
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+      let mut users_dao = UsersDaoMock::new();
+
+      // Past the quota check, the question is looked up.
+      questions_dao.mock_get_question(Ok(None));
+      moderation_dao.mock_recent_post_times(post_times(now, 3500, 21));
+      users_dao.mock_get_reputation(Ok(50));
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: "test content".to_owned(),
+          },
+          Some(&user_with_role(Role::User)),
+          &AnswersDaoMock::new(),
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          now,
+          &moderation_dao,
+          &users_dao,
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(result, Err(HandlerError::BadRequest("Question not found.".to_owned())));
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_refuse_answers_posted_over_the_limit_meanwhile() {
+      let now = 1_767_225_600;
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+      let mut users_dao = UsersDaoMock::new();
+
+      // The quota check lets the answer through, but another one took the last place before it was saved.
+      answers_dao.mock_create_answer(Err(DBError::PostingLimitReached));
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      moderation_dao.mock_recent_post_times(post_times(now, 3500, 19));
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: "test content".to_owned(),
+          },
+          Some(&user_with_role(Role::User)),
+          &answers_dao,
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          now,
+          &moderation_dao,
+          &users_dao,
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(matches!(result, Err(HandlerError::TooManyRequests(_, 3600))));
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_bad_request_error() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let result = create_answer(
+          answer,
+          None,
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_internal_error() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::other(
+          "oh no!",
+      )))));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let result = create_answer(
+          answer,
+          None,
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_answers_should_return_answers() {
+      let answer_detail = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let link_previews_dao: Box<dyn LinkPreviewsDao + Send + Sync> = Box::new(LinkPreviewsDaoMock::new());
+
+      let result = read_answers(
+          question_id,
+          AnswerSort::Oldest,
+          Viewer::Anonymous,
+          answers_dao.as_ref(),
+          link_previews_dao.as_ref(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), vec![answer_detail]);
+  }
+
+  #[tokio::test]
+  async fn read_answers_should_return_error() {
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let link_previews_dao: Box<dyn LinkPreviewsDao + Send + Sync> = Box::new(LinkPreviewsDaoMock::new());
+
+      let result = read_answers(
+          question_id,
+          AnswerSort::Oldest,
+          Viewer::Anonymous,
+          answers_dao.as_ref(),
+          link_previews_dao.as_ref(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn delete_answer_should_succeed() {
+      let answer_id = AnswerId {
+          answer_uuid: Uuid::from_u128(0x456),
+      };
+
+      let user = user_with_role(Role::User);
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(user.user_uuid)))));
+      answers_dao.mock_delete_answer(Ok(()));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let audit_dao = AuditDaoMock::new();
+
+      let result = delete_answer(answer_id, &user, answers_dao.as_ref(), &audit_dao).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), ());
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::DeleteAnswer);
+      assert_eq!(entries[0].target_uuid, Some(Uuid::from_u128(0x456).to_string()));
+  }
+
+  #[tokio::test]
+  async fn delete_answer_should_be_forbidden_to_other_users() {
+      let answer_id = AnswerId {
+          answer_uuid: Uuid::from_u128(0x456),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::from_u128(0x999))))));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let audit_dao = AuditDaoMock::new();
+
+      let result = delete_answer(answer_id, &user_with_role(Role::User), answers_dao.as_ref(), &audit_dao).await;
+
+      assert!(matches!(result.unwrap_err(), HandlerError::Forbidden(_)));
+      assert!(audit_dao.entries().is_empty());
+  }
+
+  #[tokio::test]
+  async fn delete_answer_should_return_error() {
+      let answer_id = AnswerId {
+          answer_uuid: Uuid::from_u128(0x456),
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(None))));
+      answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = delete_answer(answer_id, &user_with_role(Role::Moderator), answers_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_conflict_for_closed_question() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::ClosedOffTopic))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let result = create_answer(
+          answer,
+          None,
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn live_message_should_only_send_answers_in_the_viewers_listing() {
+      let answer_detail = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+      let event = LiveEvent::AnswerUpdated { answer_uuid: "00000000-0000-0000-0000-000000000456".to_owned() };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+
+      let message = live_message("00000000-0000-0000-0000-000000000123".to_owned(), event.clone(), Viewer::Anonymous, &answers_dao).await;
+
+      assert_eq!(message, Some(LiveMessage::AnswerUpdated { answer: answer_detail }));
+
+      // Held, shadow-banned or deleted since.
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+
+      let message = live_message("00000000-0000-0000-0000-000000000123".to_owned(), event, Viewer::Anonymous, &answers_dao).await;
+
+      assert_eq!(message, None);
+  }
+
+  #[tokio::test]
+  async fn read_questions_feed_should_validate_the_tag_and_render_atom() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      let result = read_questions_feed(Some("not a tag".to_owned()), "https://forum.example.com", 0, &questions_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      questions_dao.mock_get_feed_entries(Ok(vec![FeedEntry {
+          question_uuid: Uuid::from_u128(0x123),
+          title: "test title".to_owned(),
+          summary: "test description".to_owned(),
+          author: None,
+          tags: vec!["rust".to_owned()],
+          published: "2026-10-17T10:00:00Z".to_owned(),
+      }]));
+
+      let xml = read_questions_feed(Some("Rust".to_owned()), "https://forum.example.com/", 0, &questions_dao).await.unwrap();
+
+      assert!(xml.contains("<title>Rust Programming Forum: questions tagged rust</title>"));
+      assert!(xml.contains("<id>https://forum.example.com/api/v1/feeds/tag/rust.atom</id>"));
+      assert!(xml.contains("<id>https://forum.example.com/question/00000000-0000-0000-0000-000000000123</id>"));
+  }
+
+  #[tokio::test]
+  async fn read_sitemap_index_should_list_a_sitemap_per_page_of_questions() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_count_sitemap_questions(Ok(SitemapUrl::PER_SITEMAP + 1));
+
+      let xml = read_sitemap_index("https://forum.example.com", &questions_dao).await.unwrap();
+
+      assert_eq!(xml.matches("<sitemap>").count(), 2);
+      assert!(xml.contains("<loc>https://forum.example.com/api/v1/sitemaps/questions-2.xml</loc>"));
+
+      assert_eq!(sitemap_page("questions-2.xml"), Ok(1));
+      assert_eq!(sitemap_page("questions.xml"), Err(HandlerError::NotFound("Sitemap not found.".to_owned())));
+  }
+
+  #[tokio::test]
+  async fn poll_live_updates_should_return_the_events_after_the_cursor() {
+      let answer_detail = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut answers_dao = AnswersDaoMock::new();
+      let live_updates = LiveUpdates::default();
+      let max_wait = Duration::from_millis(10);
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let result = poll_live_updates(
+          "00000000-0000-0000-0000-000000000123".to_owned(),
+          LongPollQuery::default(),
+          Viewer::Anonymous,
+          max_wait,
+          &questions_dao,
+          &answers_dao,
+          &live_updates,
+      )
+      .await;
+
+      assert_eq!(result, Err(HandlerError::NotFound("Question not found.".to_owned())));
+
+      let question = question_with_status(QuestionStatus::Open);
+
+      live_updates.publish(&question.question_uuid.to_string(), LiveEvent::AnswerCreated { answer_uuid: "00000000-0000-0000-0000-000000000456".to_owned() });
+      questions_dao.mock_get_question(Ok(Some(question)));
+      answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+
+      let result = poll_live_updates(
+          "00000000-0000-0000-0000-000000000123".to_owned(),
+          LongPollQuery { since: Some(0) },
+          Viewer::Anonymous,
+          max_wait,
+          &questions_dao,
+          &answers_dao,
+          &live_updates,
+      )
+      .await;
+
+      assert_eq!(result, Ok(LivePoll { cursor: 1, messages: vec![LiveMessage::AnswerCreated { answer: answer_detail }] }));
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_answers_disabled_for_announcements() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(QuestionDetail {
+          kind: QuestionKind::Announcement,
+          ..question_with_status(QuestionStatus::Open)
+      })));
+
+      let result = create_answer(
+          answer,
+          None,
+          &AnswersDaoMock::new(),
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(
+          result,
+          Err(HandlerError::ConflictDetail(ConflictDetail {
+              code: ConflictCode::AnswersDisabled,
+              message: "Announcements do not accept answers.".to_owned(),
+          }))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_contest_closed_after_reveal() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(QuestionDetail {
+          contest: Some(ContestDetail {
+              reveal_at: datetime!(2025-12-31 23:00:00.0 UTC),
+              ends_at: datetime!(2026-01-01 23:00:00.0 UTC),
+              winner_uuid: None,
+          }),
+          ..question_with_status(QuestionStatus::Open)
+      })));
+
+      let result = create_answer(
+          answer,
+          None,
+          &AnswersDaoMock::new(),
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          1_767_225_600,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(
+          result,
+          Err(HandlerError::ConflictDetail(ConflictDetail {
+              code: ConflictCode::ContestClosed,
+              message: "The contest answers were revealed; it no longer accepts answers.".to_owned(),
+          }))
+      );
+  }
+
+  #[test]
+  fn contest_revealed_should_compare_reveal_time() {
+      let contest = |reveal_at: OffsetDateTime| ContestDetail {
+          reveal_at,
+          ends_at: datetime!(2026-01-02 00:00:00.0 UTC),
+          winner_uuid: None,
+      };
+
+      assert!(contest_revealed(&contest(datetime!(2025-12-31 23:59:59.5 UTC)), 1_767_225_600));
+      assert!(!contest_revealed(&contest(datetime!(2026-01-01 00:00:01.0 UTC)), 1_767_225_600));
+  }
+
+  #[tokio::test]
+  async fn close_question_should_return_question() {
+      let close = CloseQuestion {
+          status: QuestionStatus::ClosedDuplicate,
+          reason: "duplicate of 456".to_owned(),
+      };
+
+      let mut question_detail = question_with_status(QuestionStatus::ClosedDuplicate);
+      question_detail.status_reason = Some(close.reason.clone());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_update_question_status(Ok(Some(question_detail.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = close_question(
+          "123".to_owned(),
+          close,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), question_detail);
+  }
+
+  #[tokio::test]
+  async fn close_question_should_return_forbidden_for_regular_users() {
+      let close = CloseQuestion {
+          status: QuestionStatus::Locked,
+          reason: "heated discussion".to_owned(),
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let result = close_question(
+          "123".to_owned(),
+          close,
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn close_question_should_reject_open_status() {
+      let close = CloseQuestion {
+          status: QuestionStatus::Open,
+          reason: "not a close".to_owned(),
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let result = close_question(
+          "123".to_owned(),
+          close,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn reopen_question_should_return_not_found() {
+      let reopen = ReopenQuestion {
+          reason: "edited to be on topic".to_owned(),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_update_question_status(Ok(None));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = reopen_question(
+          "123".to_owned(),
+          reopen,
+          &user_with_role(Role::Admin),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_user_should_return_credentials() {
+      let user = User {
+          username: "test user".to_owned(),
+          email: Some("test@example.com".to_owned()),
+      };
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_create_user(Ok(user_with_role(Role::User)));
+      users_dao.mock_record_user_ip(Ok(()));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = create_user(user, "127.0.0.1".to_owned(), users_dao.as_ref()).await;
+
+      assert!(result.is_ok());
+
+      let credentials = result.unwrap();
+
+      assert_eq!(credentials.user, user_with_role(Role::User));
+      assert_eq!(credentials.api_token.len(), 64);
+  }
+
+  #[tokio::test]
+  async fn create_user_should_return_conflict_for_taken_username() {
+      let user = User {
+          username: "test user".to_owned(),
+          email: None,
+      };
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_create_user(Err(DBError::UniqueViolation("test".to_owned())));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = create_user(user, "127.0.0.1".to_owned(), users_dao.as_ref()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn authenticate_should_return_user() {
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_user_by_token_hash(Ok(Some(user_with_role(Role::User))));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = authenticate("token", users_dao.as_ref()).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), user_with_role(Role::User));
+  }
+
+  #[tokio::test]
+  async fn authenticate_should_return_unauthorized_for_unknown_token() {
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_user_by_token_hash(Ok(None));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = authenticate("token", users_dao.as_ref()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Unauthorized("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_return_bad_request_for_missing_question() {
+      let answer = Answer {
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+      };
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let result = create_answer(
+          answer,
+          None,
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &ModerationDaoMock::new(),
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn restore_question_should_return_question() {
+      let question_detail = question_with_status(QuestionStatus::Open);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_restore_question(Ok(Some(question_detail.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let events = EventBus::default();
+      let mut published = events.subscribe();
+
+      let result = restore_question(
+          "123".to_owned(),
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &events,
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), question_detail);
+      assert_eq!(
+          published.try_recv().unwrap().event,
+          DomainEvent::QuestionUpdated {
+              question_uuid: question_detail.question_uuid.to_string(),
+          }
+      );
+  }
+
+  #[tokio::test]
+  async fn restore_question_should_return_forbidden_for_regular_users() {
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let result = restore_question(
+          "123".to_owned(),
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[test]
+  fn authorize_export_should_only_allow_admins() {
+      assert!(authorize_export(&user_with_role(Role::Admin)).is_ok());
+      assert!(
+          std::mem::discriminant(&authorize_export(&user_with_role(Role::Moderator)).unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[test]
+  fn export_selection_should_parse_the_columns_and_range() {
+      let query = ExportQuery {
+          columns: Some("content,answer_uuid".to_owned()),
+          from: Some("2026-01-31T12:00:00+01:00".to_owned()),
+          to: None,
+      };
+
+      let (columns, range) = export_selection(&user_with_role(Role::Admin), query, export::ANSWER_COLUMNS).unwrap();
+
+      assert_eq!(columns, vec!["content", "answer_uuid"]);
+      assert_eq!(range.from, Some(datetime!(2026-01-31 11:00:00)));
+      assert_eq!(range.to, None);
+
+      let query = ExportQuery {
+          to: Some("yesterday".to_owned()),
+          ..ExportQuery::default()
+      };
+
+      assert_eq!(
+          export_selection(&user_with_role(Role::Admin), query, export::ANSWER_COLUMNS),
+          Err(HandlerError::BadRequest("to must be an RFC 3339 timestamp, such as 2024-01-31T12:00:00Z.".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn restore_answer_should_return_answer() {
+      let answer_detail = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_restore_answer(Ok(Some(answer_detail.clone())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = restore_answer(
+          "456".to_owned(),
+          &user_with_role(Role::Moderator),
+          answers_dao.as_ref(),
+          &AuditDaoMock::new(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), answer_detail);
+  }
+
+  #[tokio::test]
+  async fn restore_answer_should_return_not_found() {
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_restore_answer(Ok(None));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = restore_answer(
+          "456".to_owned(),
+          &user_with_role(Role::Admin),
+          answers_dao.as_ref(),
+          &AuditDaoMock::new(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  fn answer_by(author_uuid: Option<Uuid>) -> AnswerDetail {
+      AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: Uuid::from_u128(0x123),
+          content: "test content".to_owned(),
+          author_uuid,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      }
+  }
+
+  #[tokio::test]
+  async fn update_question_should_return_question_for_author() {
+      let question = Question {
+          title: "new title".to_owned(),
+          description: "new description".to_owned(),
+          ..Default::default()
+      };
+
+      let mut current = question_with_status(QuestionStatus::Open);
+      current.author_uuid = Some(Uuid::from_u128(0x789));
+
+      let mut updated = current.clone();
+      updated.title = question.title.clone();
+      updated.description = question.description.clone();
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(current)));
+      questions_dao.mock_update_question(Ok(Some(updated.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = update_question(
+          "123".to_owned(),
+          question,
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), updated);
+  }
+
+  #[tokio::test]
+  async fn update_question_should_return_forbidden_for_other_users() {
+      let question = Question {
+          title: "new title".to_owned(),
+          description: "new description".to_owned(),
+          ..Default::default()
+      };
+
+      let mut current = question_with_status(QuestionStatus::Open);
+      current.author_uuid = Some(Uuid::max());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(current)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = update_question(
+          "123".to_owned(),
+          question,
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn update_question_should_return_conflict_for_locked_question() {
+      let question = Question {
+          title: "new title".to_owned(),
+          description: "new description".to_owned(),
+          ..Default::default()
+      };
+
+      let mut current = question_with_status(QuestionStatus::Locked);
+      current.author_uuid = Some(Uuid::from_u128(0x789));
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(current)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let result = update_question(
+          "123".to_owned(),
+          question,
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_question_revisions_should_return_revisions() {
+      let revision = QuestionRevision {
+          question_uuid: Uuid::from_u128(0x123),
+          revision: 2,
+          title: "new title".to_owned(),
+          description: "new description".to_owned(),
+          editor_uuid: Some(Uuid::from_u128(0x789)),
+          created_at: OffsetDateTime::UNIX_EPOCH,
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question_revisions(Ok(vec![revision.clone()]));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_question_revisions("123".to_owned(), Viewer::Anonymous, questions_dao.as_ref()).await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), vec![revision]);
+  }
+
+  #[tokio::test]
+  async fn read_question_revisions_should_return_original_for_unedited_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question_revisions(Ok(vec![]));
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = read_question_revisions("123".to_owned(), Viewer::Anonymous, questions_dao.as_ref()).await;
+
+      assert!(result.is_ok());
+
+      let revisions = result.unwrap();
+
+      assert_eq!(revisions.len(), 1);
+      assert_eq!(revisions[0].revision, 1);
+      assert_eq!(revisions[0].title, "test title");
+  }
+
+  #[tokio::test]
+  async fn update_answer_should_return_answer_for_moderator() {
+      let mut updated = answer_by(Some(Uuid::max()));
+      updated.content = "new content".to_owned();
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::max())))));
+      answers_dao.mock_update_answer(Ok(Some(updated.clone())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = update_answer(
+          "456".to_owned(),
+          AnswerUpdate { content: "new content".to_owned() },
+          &user_with_role(Role::Moderator),
+          answers_dao.as_ref(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), updated);
+  }
+
+  #[tokio::test]
+  async fn update_answer_should_return_forbidden_for_anonymous_answers() {
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(None))));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = update_answer(
+          "456".to_owned(),
+          AnswerUpdate { content: "new content".to_owned() },
+          &user_with_role(Role::User),
+          answers_dao.as_ref(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_answer_revisions_should_return_not_found() {
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer_revisions(Ok(vec![]));
+      answers_dao.mock_get_answer(Ok(None));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let result = read_answer_revisions("456".to_owned(), Viewer::Anonymous, answers_dao.as_ref()).await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  fn draft(title: &str, description: &str) -> DraftDetail {
+      DraftDetail {
+          draft_uuid: Uuid::from_u128(0x321),
+          title: title.to_owned(),
+          description: description.to_owned(),
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  #[tokio::test]
+  async fn save_question_draft_should_return_draft() {
+      let saved = draft("test title", "");
+
+      let mut drafts_dao = DraftsDaoMock::new();
+
+      drafts_dao.mock_save_question_draft(Ok(saved.clone()));
+
+      let drafts_dao: Box<dyn DraftsDao + Send + Sync> = Box::new(drafts_dao);
+
+      let result = save_question_draft(
+          QuestionDraft {
+              title: "test title".to_owned(),
+              description: "".to_owned(),
+          },
+          &user_with_role(Role::User),
+          drafts_dao.as_ref(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), saved);
+  }
+
+  #[tokio::test]
+  async fn publish_draft_should_return_question() {
+      let question = question_with_status(QuestionStatus::Open);
+
+      let mut drafts_dao = DraftsDaoMock::new();
+
+      drafts_dao.mock_get_draft(Ok(Some(draft("test title", "test description"))));
+      drafts_dao.mock_publish_draft(Ok(Some(question.clone())));
+
+      let drafts_dao: Box<dyn DraftsDao + Send + Sync> = Box::new(drafts_dao);
+
+      let result = publish_draft(
+          "321".to_owned(),
+          &user_with_role(Role::User),
+          drafts_dao.as_ref(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(result.unwrap(), question);
+  }
+
+  #[tokio::test]
+  async fn publish_draft_should_return_bad_request_for_incomplete_draft() {
+      let mut drafts_dao = DraftsDaoMock::new();
+
+      drafts_dao.mock_get_draft(Ok(Some(draft("test title", "  "))));
+
+      let drafts_dao: Box<dyn DraftsDao + Send + Sync> = Box::new(drafts_dao);
+
+      let result = publish_draft(
+          "321".to_owned(),
+          &user_with_role(Role::User),
+          drafts_dao.as_ref(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn publish_draft_should_return_not_found_for_other_users_draft() {
+      let mut drafts_dao = DraftsDaoMock::new();
+
+      drafts_dao.mock_get_draft(Ok(None));
+
+      let drafts_dao: Box<dyn DraftsDao + Send + Sync> = Box::new(drafts_dao);
+
+      let result = publish_draft(
+          "321".to_owned(),
+          &user_with_role(Role::User),
+          drafts_dao.as_ref(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.is_err());
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  const SIGNING_KEYS: &str = "1:test-url-signing-key-0123456789abcdef";
+
+  #[tokio::test]
+  async fn signed_question_url_should_grant_access_until_it_expires() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.question_uuid = Uuid::from_u128(0x123);
+      question.author_uuid = Some(Uuid::from_u128(0x789));
+
+      let url_signer = UrlSigner::parse(SIGNING_KEYS).unwrap();
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let signed = create_question_signed_url(
+          "123".to_owned(),
+          SignedUrlRequest { expires_in_seconds: 60 },
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          &url_signer,
+          1_000,
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(signed.expires, 1_060);
+      assert!(signed.url.starts_with("/api/v1/shared/question/00000000-0000-0000-0000-000000000123?expires=1060&key_id=1&signature="));
+
+      let signature = UrlSignature {
+          expires: signed.expires,
+          key_id: 1,
+          signature: signed.url.rsplit('=').next().unwrap().to_owned(),
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question.clone())));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let link_previews_dao: Box<dyn LinkPreviewsDao + Send + Sync> = Box::new(LinkPreviewsDaoMock::new());
+
+      let result = read_shared_question(
+          "00000000-0000-0000-0000-000000000123".to_owned(),
+          signature.clone(),
+          questions_dao.as_ref(),
+          link_previews_dao.as_ref(),
+          &url_signer,
+          1_059,
+      )
+      .await;
+
+      assert_eq!(result, Ok(question));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let result = read_shared_question(
+          "123".to_owned(),
+          signature,
+          questions_dao.as_ref(),
+          link_previews_dao.as_ref(),
+          &url_signer,
+          1_060,
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_signed_url_should_return_forbidden_for_other_users() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(Uuid::max());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = create_question_signed_url(
+          "123".to_owned(),
+          SignedUrlRequest { expires_in_seconds: 60 },
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+          1_000,
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn notify_on_event_should_notify_mentions_when_notifying_followers_fails() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+
+      notifications_dao.mock_notify_question_followers(Err(DBError::Other(Box::new(std::io::Error::other(
+          "oh no!",
+      )))));
+      notifications_dao.mock_notify_mentioned_users(Ok(1));
+
+      let event = DomainEvent::AnswerCreated {
+          question_uuid: "123".to_owned(),
+          answer_uuid: "456".to_owned(),
+          author_uuid: Some("789".to_owned()),
+          content: "cc @jane".to_owned(),
+      };
+
+      notify_on_event(event, &notifications_dao).await;
+
+      assert!(notifications_dao.notify_question_followers_response.lock().await.is_none());
+      assert!(notifications_dao.notify_mentioned_users_response.lock().await.is_none());
+  }
+
+  #[tokio::test]
+  async fn notify_on_event_should_notify_mentioned_users_best_effort() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+
+      notifications_dao.mock_notify_mentioned_users(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+      let event = DomainEvent::QuestionCreated {
+          question_uuid: "123".to_owned(),
+          author_uuid: Some("789".to_owned()),
+          description: "cc @jane".to_owned(),
+      };
+
+      notify_on_event(event, &notifications_dao).await;
+
+      assert!(notifications_dao.notify_mentioned_users_response.lock().await.is_none());
+  }
+
+  #[test]
+  fn publish_live_update_should_forward_answer_events() {
+      let live_updates = LiveUpdates::default();
+      let mut events = live_updates.subscribe("123");
+
+      publish_live_update(
+          DomainEvent::QuestionCreated {
+              question_uuid: "123".to_owned(),
+              author_uuid: None,
+              description: "test description".to_owned(),
+          },
+          &live_updates,
+      );
+      publish_live_update(
+          DomainEvent::AnswerUpdated {
+              question_uuid: "123".to_owned(),
+              answer_uuid: "456".to_owned(),
+          },
+          &live_updates,
+      );
+
+      assert_eq!(events.try_recv().unwrap(), LiveEvent::AnswerUpdated { answer_uuid: "456".to_owned() });
+      assert!(events.try_recv().is_err());
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_warn_and_flag_answers_to_old_questions() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.created_at = datetime!(2024-01-01 12:00:00.0 UTC);
+
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut flags_dao = FlagsDaoMock::new();
+
+      answers_dao.mock_create_answer(Ok(answer_by(Some(Uuid::from_u128(0x789)))));
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+      questions_dao.mock_get_question(Ok(Some(question)));
+      flags_dao.mock_create_flag(Ok(flag_detail(Some(Uuid::from_u128(0x456)))));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let policy = NecroPostPolicy {
+          warn_after_days: 365,
+          review: true,
+      };
+
+      // 2026-01-01, 731 days after the question was asked.
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: "test content".to_owned(),
+          },
+          Some(&user_with_role(Role::User)),
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          &flags_dao,
+          policy,
+          SimilarAnswerPolicy::default(),
+          1_767_225_600,
+          &ModerationDaoMock::new(),
+          &users_dao,
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.unwrap().question_age_warning);
+      assert!(flags_dao.create_flag_response.lock().await.is_none());
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_warn_and_flag_near_duplicate_answers() {
+      let earlier = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x111),
+          content: "You can clone the Rc to get another owner of the same value.".to_owned(),
+          ..answer_by(Some(Uuid::from_u128(0x222)))
+      };
+      let copy = AnswerDetail {
+          content: "You can clone the Rc to get another owner of the same value!".to_owned(),
+          ..answer_by(Some(Uuid::from_u128(0x789)))
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut flags_dao = FlagsDaoMock::new();
+
+      answers_dao.mock_create_answer(Ok(copy.clone()));
+      answers_dao.mock_get_answers(Ok(vec![earlier, copy.clone()]));
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      flags_dao.mock_create_flag(Ok(flag_detail(Some(Uuid::from_u128(0x456)))));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let policy = SimilarAnswerPolicy {
+          threshold: 0.6,
+          review: true,
+      };
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: copy.content,
+          },
+          Some(&user_with_role(Role::User)),
+          answers_dao.as_ref(),
+          questions_dao.as_ref(),
+          &flags_dao,
+          NecroPostPolicy::default(),
+          policy,
+          0,
+          &ModerationDaoMock::new(),
+          &users_dao,
+          PostingQuotaPolicy::default(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap().similar_answer_uuid, Some(Uuid::from_u128(0x111)));
+      assert!(flags_dao.create_flag_response.lock().await.is_none());
+  }
+
+  #[test]
+  fn age_in_days_should_count_whole_days() {
+      assert_eq!(age_in_days(datetime!(2025-12-31 23:59:59.5 UTC), 1_767_225_600), Some(1));
+      assert_eq!(age_in_days(datetime!(2026-01-01 00:00:00.0 UTC), 1_767_225_600), Some(0));
+  }
+
+  #[tokio::test]
+  async fn create_question_should_hold_spam_for_moderation() {
+      let description = (1..=6).map(|n| format!("https://example.com/{}", n)).collect::<Vec<_>>().join(" ");
+      let question = Question {
+          title: "cheap watches".to_owned(),
+          description: description.clone(),
+          ..Default::default()
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_create_question(Ok(QuestionDetail {
+          description,
+          ..question_with_status(QuestionStatus::Open)
+      }));
+
+      let moderation_dao = ModerationDaoMock::new();
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          &questions_dao,
+          &BoardsDaoMock::new(),
+          &TagsDaoMock::new(),
+          &moderation_dao,
+          &HeuristicSpamChecker::default(),
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.unwrap().held_for_review);
+      assert_eq!(questions_dao.created_with().await, Some((Vec::new(), Some("Contains 6 links.".to_owned()))));
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_hold_fast_posters_without_notifying() {
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+
+      answers_dao.mock_create_answer(Ok(answer_by(Some(Uuid::from_u128(0x789)))));
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      moderation_dao.mock_recent_posts(["one", "two", "three", "four", "five"].map(str::to_owned).to_vec());
+
+      // Followers are not mocked, so notifying them would panic.
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: "test content".to_owned(),
+          },
+          Some(&user_with_role(Role::User)),
+          &answers_dao,
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &moderation_dao,
+          &users_dao,
+          PostingQuotaPolicy::default(),
+          &HeuristicSpamChecker::default(),
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(result.unwrap().held_for_review);
+      assert_eq!(
+          moderation_dao.held_posts(),
+          vec![("00000000-0000-0000-0000-000000000123".to_owned(), Some("00000000-0000-0000-0000-000000000456".to_owned()), "6 posts within 10 minutes.".to_owned())]
+      );
+  }
+
+  #[tokio::test]
+  async fn create_answer_should_not_check_moderators_for_spam() {
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+
+      answers_dao.mock_create_answer(Ok(answer_by(Some(Uuid::from_u128(0x789)))));
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      moderation_dao.mock_recent_posts(["one", "two", "three", "four", "five"].map(str::to_owned).to_vec());
+
+      let result = create_answer(
+          Answer {
+              question_uuid: Uuid::from_u128(0x123),
+              content: "test content".to_owned(),
+          },
+          Some(&user_with_role(Role::Moderator)),
+          &answers_dao,
+          &questions_dao,
+          &FlagsDaoMock::new(),
+          NecroPostPolicy::default(),
+          SimilarAnswerPolicy::default(),
+          0,
+          &moderation_dao,
+          &UsersDaoMock::new(),
+          PostingQuotaPolicy::default(),
+          &HeuristicSpamChecker::default(),
+          [203, 0, 113, 1].into(),
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(!result.unwrap().held_for_review);
+      assert!(moderation_dao.held_posts().is_empty());
+  }
+
+  #[tokio::test]
+  async fn follow_question_should_return_not_found_for_unknown_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let follows_dao: Box<dyn FollowsDao + Send + Sync> = Box::new(FollowsDaoMock::new());
+
+      let result = follow_question(
+          "123".to_owned(),
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          follows_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn follow_question_should_succeed() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let mut follows_dao = FollowsDaoMock::new();
+
+      follows_dao.mock_follow_question(Ok(()));
+
+      let follows_dao: Box<dyn FollowsDao + Send + Sync> = Box::new(follows_dao);
+
+      let result = follow_question(
+          "123".to_owned(),
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          follows_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result, Ok(()));
+  }
+
+  #[tokio::test]
+  async fn create_question_should_return_forbidden_for_private_question_outside_board() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          visibility: Visibility::Private,
+          board_uuid: Some(Uuid::from_u128(0x321)),
+          tags: Vec::new(),
+          kind: QuestionKind::Question,
+          contest: None,
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_is_board_member(Ok(false));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_should_return_forbidden_for_announcements_by_users() {
+      let question = Question {
+          title: "Forum rules".to_owned(),
+          description: "Be kind.".to_owned(),
+          kind: QuestionKind::Announcement,
+          ..Default::default()
+      };
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          &QuestionsDaoMock::new(),
+          &BoardsDaoMock::new(),
+          &TagsDaoMock::new(),
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_should_reject_invalid_contests() {
+      let contests = [
+          (QuestionKind::Question, Contest { answer_hours: 0, voting_hours: 24 }),
+          (QuestionKind::Question, Contest { answer_hours: 24, voting_hours: Contest::MAX_HOURS + 1 }),
+          (QuestionKind::Announcement, Contest { answer_hours: 24, voting_hours: 24 }),
+      ];
+
+      for (kind, contest) in contests {
+          let question = Question {
+              title: "Fastest parser".to_owned(),
+              description: "Post your fastest parser.".to_owned(),
+              kind,
+              contest: Some(contest),
+              ..Default::default()
+          };
+
+          let result = create_question(
+              question,
+              Some(&user_with_role(Role::Moderator)),
+              &QuestionsDaoMock::new(),
+              &BoardsDaoMock::new(),
+              &TagsDaoMock::new(),
+              &ModerationDaoMock::new(),
+              &NoSpamChecker,
+              [203, 0, 113, 1].into(),
+              &UsersDaoMock::new(),
+              NewTagPolicy::default(),
+              PostingQuotaPolicy::default(),
+              0,
+              &ContentPolicy::default(),
+              &EventBus::default(),
+          )
+          .await;
+
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+  }
+
+  #[tokio::test]
+  async fn create_question_should_return_bad_request_for_private_question_without_board() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          visibility: Visibility::Private,
+          board_uuid: None,
+          tags: Vec::new(),
+          kind: QuestionKind::Question,
+          contest: None,
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_get_reputation(Ok(0));
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_question_should_return_not_found_for_hidden_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let link_previews_dao: Box<dyn LinkPreviewsDao + Send + Sync> = Box::new(LinkPreviewsDaoMock::new());
+
+      let result = read_question(
+          "123".to_owned(),
+          Viewer::Anonymous,
+          "203.0.113.1".parse().unwrap(),
+          questions_dao.as_ref(),
+          link_previews_dao.as_ref(),
+          &ViewCounter::default(),
+          &question_cache(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_question_full_should_return_the_question_with_its_answers() {
+      let question = question_with_status(QuestionStatus::Open);
+      let answer = AnswerDetail {
+          answer_uuid: Uuid::from_u128(0x456),
+          question_uuid: question.question_uuid,
+          content: "test content".to_owned(),
+          author_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          content_html: None,
+          code_blocks: Vec::new(),
+          link_previews: Vec::new(),
+          signals: None,
+          question_age_warning: false,
+          similar_answer_uuid: None,
+          held_for_review: false,
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+      questions_dao.mock_get_question(Ok(Some(question.clone())));
+
+      let mut answers_dao = AnswersDaoMock::new();
+      answers_dao.mock_get_answers(Ok(vec![answer.clone()]));
+
+      let view_counter = ViewCounter::default();
+
+      let result = read_question_full(
+          QuestionId { question_uuid: question.question_uuid },
+          AnswerSort::Votes,
+          Viewer::Anonymous,
+          "203.0.113.1".parse().unwrap(),
+          &questions_dao,
+          &answers_dao,
+          &LinkPreviewsDaoMock::new(),
+          &view_counter,
+      )
+      .await;
+
+      assert_eq!(
+          view_counter.take()[&None],
+          vec![QuestionView { question_uuid: question.question_uuid, viewer: QuestionViewer::Ip("203.0.113.1".parse().unwrap()) }]
+      );
+      assert_eq!(result, Ok(QuestionFull { question, answers: vec![answer] }));
+  }
+
+  #[tokio::test]
+  async fn read_question_full_should_return_not_found_for_hidden_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      questions_dao.mock_get_question(Ok(None));
+
+      let mut answers_dao = AnswersDaoMock::new();
+      answers_dao.mock_get_answers(Ok(Vec::new()));
+
+      let view_counter = ViewCounter::default();
+
+      let result = read_question_full(
+          QuestionId { question_uuid: Uuid::from_u128(0x123) },
+          AnswerSort::Votes,
+          Viewer::Anonymous,
+          "203.0.113.1".parse().unwrap(),
+          &questions_dao,
+          &answers_dao,
+          &LinkPreviewsDaoMock::new(),
+          &view_counter,
+      )
+      .await;
+
+      assert_eq!(result, Err(HandlerError::NotFound("Question not found.".to_owned())));
+      assert!(view_counter.take().is_empty());
+  }
+
+  #[tokio::test]
+  async fn read_question_stats_should_return_the_counters() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let stats = QuestionStats {
+          question_uuid: Uuid::from_u128(0x123),
+          views: 3,
+          unique_viewers: 2,
+          answers: 1,
+          last_activity_at: OffsetDateTime::UNIX_EPOCH,
+      };
+
+      let mut stats_dao = StatsDaoMock::new();
+      stats_dao.mock_get_question_stats(Ok(Some(stats.clone())));
+
+      let result = read_question_stats(
+          QuestionId { question_uuid: Uuid::from_u128(0x123) },
+          Viewer::Anonymous,
+          &questions_dao,
+          &stats_dao,
+      )
+      .await;
+
+      assert_eq!(result, Ok(stats));
+  }
+
+  #[tokio::test]
+  async fn read_question_stats_should_return_not_found_for_hidden_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      questions_dao.mock_get_question(Ok(None));
+
+      let result = read_question_stats(
+          QuestionId { question_uuid: Uuid::from_u128(0x123) },
+          Viewer::Anonymous,
+          &questions_dao,
+          &StatsDaoMock::new(),
+      )
+      .await;
+
+      assert_eq!(result, Err(HandlerError::NotFound("Question not found.".to_owned())));
+  }
+
+  fn board_member(role: BoardRole, status: MembershipStatus) -> BoardMember {
+      BoardMember {
+          board_uuid: Uuid::from_u128(0x321),
+          user_uuid: user_with_role(Role::User).user_uuid,
+          role,
+          status,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  #[tokio::test]
+  async fn create_board_should_return_conflict_for_taken_name() {
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_create_board(Err(DBError::UniqueViolation("test".to_owned())));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let board = Board {
+          name: "team".to_owned(),
+      };
+
+      let result = create_board(board, &user_with_role(Role::User), boards_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_board_members_should_return_forbidden_for_non_owners() {
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_get_board_member(Ok(Some(board_member(BoardRole::Member, MembershipStatus::Active))));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let result = read_board_members("321".to_owned(), &user_with_role(Role::User), boards_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn approve_board_member_should_return_not_found_without_pending_request() {
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_get_board_member(Ok(Some(board_member(BoardRole::Owner, MembershipStatus::Active))));
+      boards_dao.mock_approve_board_member(Ok(None));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let result = approve_board_member(
+          "321".to_owned(),
+          "456".to_owned(),
+          &user_with_role(Role::User),
+          boards_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn remove_board_member_should_let_members_leave() {
+      let user = user_with_role(Role::User);
+
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_remove_board_member(Ok(()));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let result = remove_board_member("321".to_owned(), user.user_uuid.to_string(), &user, boards_dao.as_ref()).await;
+
+      assert!(result.is_ok());
+  }
+
+  #[tokio::test]
+  async fn set_board_tag_rules_should_save_rules_for_board_owners() {
+      let detail = BoardTagRulesDetail {
+          board_uuid: Uuid::from_u128(0x321),
+          required_tags: vec!["rust".to_owned()],
+          forbidden_tags: Vec::new(),
+          updated_by: Some(Uuid::from_u128(0x789)),
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+      };
+
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_get_board_member(Ok(Some(board_member(BoardRole::Member, MembershipStatus::Active))));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let mut tags_dao = TagsDaoMock::new();
+
+      tags_dao.mock_set_board_tag_rules(Ok(Some(detail.clone())));
+
+      let rules = BoardTagRules {
+          required_tags: vec!["Rust".to_owned()],
+          forbidden_tags: Vec::new(),
+      };
+
+      let result = set_board_tag_rules("321".to_owned(), rules.clone(), &user_with_role(Role::User), boards_dao.as_ref(), &tags_dao).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let result = set_board_tag_rules("321".to_owned(), rules, &user_with_role(Role::Admin), boards_dao.as_ref(), &tags_dao).await;
+
+      assert_eq!(result.unwrap(), detail);
+  }
+
+  #[tokio::test]
+  async fn set_board_tag_rules_should_reject_tags_both_required_and_forbidden() {
+      let mut boards_dao = BoardsDaoMock::new();
+
+      boards_dao.mock_get_board_member(Ok(Some(board_member(BoardRole::Owner, MembershipStatus::Active))));
+
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(boards_dao);
+
+      let rules = BoardTagRules {
+          required_tags: vec!["rust".to_owned(), "Go".to_owned()],
+          forbidden_tags: vec!["go".to_owned()],
+      };
+
+      let result = set_board_tag_rules(
+          "321".to_owned(),
+          rules,
+          &user_with_role(Role::User),
+          boards_dao.as_ref(),
+          &TagsDaoMock::new(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_should_enforce_board_tag_rules() {
+      let rules = BoardTagRulesDetail {
+          board_uuid: Uuid::from_u128(0x321),
+          required_tags: vec!["rust".to_owned(), "cargo".to_owned()],
+          forbidden_tags: vec!["off-topic".to_owned()],
+          updated_by: None,
+          updated_at: OffsetDateTime::UNIX_EPOCH,
+      };
+      let question = |tags: &[&str]| Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          board_uuid: Some(Uuid::from_u128(0x321)),
+          tags: tags.iter().map(|tag| tag.to_string()).collect(),
+          ..Default::default()
+      };
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+      let mut tags_dao = TagsDaoMock::new();
+
+      tags_dao.mock_get_board_tag_rules(Ok(Some(rules.clone())));
+
+      let result = create_question(
+          question(&["async"]),
+          None,
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &tags_dao,
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(
+          result.unwrap_err(),
+          HandlerError::TagRuleViolation(TagRuleViolation {
+              code: TagRuleViolationCode::MissingRequiredTag,
+              message: "Questions on this board need at least one of these tags.".to_owned(),
+              tags: vec!["rust".to_owned(), "cargo".to_owned()],
+          })
+      );
+
+      tags_dao.mock_get_board_tag_rules(Ok(Some(rules)));
+
+      let result = create_question(
+          question(&["Rust", "off-topic"]),
+          None,
+          questions_dao.as_ref(),
+          boards_dao.as_ref(),
+          &tags_dao,
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &UsersDaoMock::new(),
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert_eq!(
+          result.unwrap_err(),
+          HandlerError::TagRuleViolation(TagRuleViolation {
+              code: TagRuleViolationCode::ForbiddenTag,
+              message: "These tags are not allowed on this board.".to_owned(),
+              tags: vec!["off-topic".to_owned()],
+          })
+      );
+  }
+
+  #[tokio::test]
+  async fn create_question_should_hold_back_new_tags_from_untrusted_authors() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          tags: vec!["rust".to_owned(), "brand-new".to_owned()],
+          ..Default::default()
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut tags_dao = TagsDaoMock::new();
+      let mut users_dao = UsersDaoMock::new();
+
+      questions_dao.mock_create_question(Ok(QuestionDetail {
+          tags: vec!["rust".to_owned()],
+          ..question_with_status(QuestionStatus::Open)
+      }));
+      tags_dao.mock_get_unused_tags(Ok(vec!["brand-new".to_owned()]));
+      users_dao.mock_get_reputation(Ok(99));
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          &questions_dao,
+          &BoardsDaoMock::new(),
+          &tags_dao,
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(result.tags, vec!["rust".to_owned()]);
+      assert_eq!(result.pending_tags, vec!["brand-new".to_owned()]);
+      assert_eq!(questions_dao.created_with().await, Some((vec!["brand-new".to_owned()], None)));
+  }
+
+  #[tokio::test]
+  async fn create_question_should_let_trusted_authors_create_tags() {
+      let question = Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          tags: vec!["brand-new".to_owned()],
+          ..Default::default()
+      };
+
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut tags_dao = TagsDaoMock::new();
+      let mut users_dao = UsersDaoMock::new();
+
+      questions_dao.mock_create_question(Ok(QuestionDetail {
+          tags: vec!["brand-new".to_owned()],
+          ..question_with_status(QuestionStatus::Open)
+      }));
+      tags_dao.mock_get_unused_tags(Ok(vec!["brand-new".to_owned()]));
+      users_dao.mock_get_reputation(Ok(100));
+
+      let result = create_question(
+          question,
+          Some(&user_with_role(Role::User)),
+          &questions_dao,
+          &BoardsDaoMock::new(),
+          &tags_dao,
+          &ModerationDaoMock::new(),
+          &NoSpamChecker,
+          [203, 0, 113, 1].into(),
+          &users_dao,
+          NewTagPolicy::default(),
+          PostingQuotaPolicy::default(),
+          0,
+          &ContentPolicy::default(),
+          &EventBus::default(),
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(result.tags, vec!["brand-new".to_owned()]);
+      assert!(result.pending_tags.is_empty());
+      assert!(tags_dao.pending_tags().is_empty());
+  }
+
+  #[tokio::test]
+  async fn approve_pending_tag_should_require_moderator_and_audit() {
+      let resolution = PendingTagResolution {
+          name: "brand-new".to_owned(),
+          questions: 2,
+      };
+
+      let mut tags_dao = TagsDaoMock::new();
+      let audit_dao = AuditDaoMock::new();
+
+      let result = approve_pending_tag("Brand-New".to_owned(), &user_with_role(Role::User), &tags_dao, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      tags_dao.mock_approve_pending_tag(Ok(None));
+
+      let result = approve_pending_tag("brand-new".to_owned(), &user_with_role(Role::Moderator), &tags_dao, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+
+      tags_dao.mock_approve_pending_tag(Ok(Some(resolution.clone())));
+
+      let result = approve_pending_tag("Brand-New".to_owned(), &user_with_role(Role::Moderator), &tags_dao, &audit_dao).await;
+
+      assert_eq!(result, Ok(resolution));
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::ApproveTag);
+      assert_eq!(entries[0].target_uuid.as_deref(), Some("brand-new"));
+  }
+
+  #[tokio::test]
+  async fn set_shadow_ban_should_require_moderator_and_record_an_audit_entry() {
+      let mut users_dao = UsersDaoMock::new();
+      let audit_dao = AuditDaoMock::new();
+      let question_cache = question_cache();
+
+      let result = set_shadow_ban("321".to_owned(), true, &user_with_role(Role::User), &users_dao, &audit_dao, &question_cache).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      users_dao.mock_set_shadow_banned(Ok(false));
+
+      let result = set_shadow_ban("321".to_owned(), true, &user_with_role(Role::Moderator), &users_dao, &audit_dao, &question_cache).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+
+      users_dao.mock_set_shadow_banned(Ok(true));
+      question_cache.set_question(&question_with_status(QuestionStatus::Open)).await;
+
+      let result = set_shadow_ban("321".to_owned(), false, &user_with_role(Role::Moderator), &users_dao, &audit_dao, &question_cache).await;
+
+      assert_eq!(result, Ok(()));
+      assert_eq!(question_cache.question(&Uuid::from_u128(0x123).to_string()).await, None);
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::LiftShadowBan);
+      assert_eq!(entries[0].target_uuid.as_deref(), Some("321"));
+  }
+
+  fn erasure_report(deleted_objects: Vec<String>) -> ErasureReport {
+      ErasureReport {
+          report_uuid: Uuid::from_u128(0x654),
+          user_uuid: Uuid::from_u128(0x321),
+          requested_by: Some(Uuid::from_u128(0x789)),
+          erased_at: datetime!(2026-10-17 10:00:00.0 UTC),
+          checks: vec![ErasureCheck {
+              table: "users".to_owned(),
+              column: "user_uuid".to_owned(),
+              action: ErasureAction::Purged,
+              rows: 1,
+          }],
+          deleted_objects,
+          backups: ErasureBackups::new(Some(30), Some(datetime!(2026-11-16 10:00:00.0 UTC))),
+      }
+  }
+
+  #[tokio::test]
+  async fn erase_user_should_require_admin_and_refuse_own_account() {
+      let erasure_dao = ErasureDaoMock::new();
+      let object_store = ObjectStoreMock::new();
+      let audit_dao = AuditDaoMock::new();
+
+      for (user_uuid, role) in [("321", Role::Moderator), ("00000000-0000-0000-0000-000000000789", Role::Admin)] {
+          let result = erase_user(user_uuid.to_owned(), &user_with_role(role), None, &erasure_dao, &object_store, &audit_dao).await;
+
+          assert!(result.is_err());
+      }
+
+      assert!(audit_dao.entries().is_empty());
+  }
+
+  #[tokio::test]
+  async fn erase_user_should_delete_avatar_objects_and_record_an_audit_entry() {
+      let mut erasure_dao = ErasureDaoMock::new();
+      let object_store = ObjectStoreMock::new();
+      let audit_dao = AuditDaoMock::new();
+      let avatar_objects: Vec<String> = AVATAR_SIZES.iter().map(|size| avatar_object_key("avatar-1", *size)).collect();
+
+      for object_key in &avatar_objects {
+          object_store.put(object_key, "image/png", vec![1]).await.unwrap();
+      }
+      object_store.put("attachment-1", "text/plain", vec![1]).await.unwrap();
+
+      erasure_dao.mock_erase_user(Ok(Some(erasure_report(avatar_objects.clone()))));
+
+      let result = erase_user("321".to_owned(), &user_with_role(Role::Admin), Some(30), &erasure_dao, &object_store, &audit_dao).await;
+
+      assert_eq!(result, Ok(erasure_report(avatar_objects)));
+      assert_eq!(object_store.len(), 1);
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::EraseUser);
+      assert_eq!(entries[0].target_uuid.as_deref(), Some("321"));
+
+      erasure_dao.mock_erase_user(Ok(None));
+
+      let result = erase_user("321".to_owned(), &user_with_role(Role::Admin), Some(30), &erasure_dao, &object_store, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_erasure_report_should_return_not_found_for_unknown_reports() {
+      let mut erasure_dao = ErasureDaoMock::new();
+
+      erasure_dao.mock_get_erasure_report(Ok(None));
+
+      let result = read_erasure_report(&user_with_role(Role::Admin), "654".to_owned(), &erasure_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn export_knowledge_base_should_link_each_section_to_its_question() {
+      let mut tags_dao = TagsDaoMock::new();
+
+      tags_dao.mock_get_accepted_answers(Ok(vec![TagAcceptedAnswer {
+          question_uuid: Uuid::from_u128(0x123),
+          title: "How do I share an Rc?".to_owned(),
+          answer_uuid: Uuid::from_u128(0x456),
+          answer: "Clone it.\n".to_owned(),
+      }]));
+
+      let export = export_knowledge_base("Rust".to_owned(), "https://forum.example/", &tags_dao).await.unwrap();
+
+      assert_eq!(
+          export,
+          KbExport {
+              tag: "rust".to_owned(),
+              sections: vec![KbSection {
+                  question_uuid: Uuid::from_u128(0x123),
+                  answer_uuid: Uuid::from_u128(0x456),
+                  title: "How do I share an Rc?".to_owned(),
+                  source_url: "https://forum.example/question/00000000-0000-0000-0000-000000000123".to_owned(),
+                  markdown: "## How do I share an Rc?\n\nClone it.\n\nSource: <https://forum.example/question/00000000-0000-0000-0000-000000000123>\n".to_owned(),
+              }],
+          }
+      );
+  }
+
+  #[tokio::test]
+  async fn set_faq_entry_should_require_moderator_and_an_answer_of_the_question() {
+      let mut faq_dao = FaqDaoMock::new();
+      let audit_dao = AuditDaoMock::new();
+      let selection = FaqSelection { answer_uuid: Uuid::from_u128(0x456) };
+
+      let result = set_faq_entry("123".to_owned(), selection.clone(), &user_with_role(Role::User), &faq_dao, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      faq_dao.mock_set_faq_entry(Ok(false));
+
+      let result = set_faq_entry("123".to_owned(), selection.clone(), &user_with_role(Role::Moderator), &faq_dao, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+      assert!(audit_dao.entries().is_empty());
+
+      faq_dao.mock_set_faq_entry(Ok(true));
+
+      let result = set_faq_entry("123".to_owned(), selection, &user_with_role(Role::Moderator), &faq_dao, &audit_dao).await;
+
+      assert_eq!(result, Ok(()));
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::SetFaqEntry);
+      assert_eq!(entries[0].target_uuid.as_deref(), Some("123"));
+  }
+
+  #[tokio::test]
+  async fn create_webhook_should_validate_and_return_the_secret_once() {
+      let webhooks_dao = WebhooksDaoMock::new();
+      let audit_dao = AuditDaoMock::new();
+      let webhook = |url: &str, events: Vec<WebhookEvent>| NewWebhook { url: url.to_owned(), events };
+      let events = vec![WebhookEvent::QuestionCreated, WebhookEvent::AnswerCreated, WebhookEvent::QuestionCreated];
+
+      let result = create_webhook(
+          &user_with_role(Role::Moderator),
+          webhook("https://hooks.example.com/forum", events.clone()),
+          &webhooks_dao,
+          &audit_dao,
+      )
+      .await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let invalid_webhooks = [
+          webhook("ftp://hooks.example.com", events.clone()),
+          webhook("https://hooks.example.com", vec![]),
+          webhook("https://hooks.example.com", vec![WebhookEvent::Notification]),
+      ];
+
+      for invalid in invalid_webhooks {
+          let result = create_webhook(&user_with_role(Role::Admin), invalid, &webhooks_dao, &audit_dao).await;
+
+          assert_eq!(
+              std::mem::discriminant(&result.unwrap_err()),
+              std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+
+      let created = create_webhook(
+          &user_with_role(Role::Admin),
+          webhook("https://hooks.example.com/forum", events),
+          &webhooks_dao,
+          &audit_dao,
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(created.events, vec![WebhookEvent::QuestionCreated, WebhookEvent::AnswerCreated]);
+      assert_eq!(created.secret.map(|secret| secret.len()), Some(64));
+
+      let entries = audit_dao.entries();
+
+      assert_eq!(entries.len(), 1);
+      assert_eq!(entries[0].action, AuditAction::CreateWebhook);
+      assert_eq!(entries[0].target_uuid.as_deref(), Some("00000000-0000-0000-0000-000000000123"));
+  }
+
+  #[tokio::test]
+  async fn read_webhook_deliveries_should_return_not_found_for_unknown_webhooks() {
+      let mut webhooks_dao = WebhooksDaoMock::new();
+
+      webhooks_dao.mock_get_webhook_deliveries(Ok(None));
+
+      let result = read_webhook_deliveries(&user_with_role(Role::Admin), "123".to_owned(), Pagination::default(), &webhooks_dao).await;
+
+      assert_eq!(result, Err(HandlerError::NotFound("Webhook not found.".to_owned())));
+  }
+
+  #[tokio::test]
+  async fn queue_webhook_deliveries_should_queue_every_post_event() {
+      let webhooks_dao = WebhooksDaoMock::new();
+      let events = [
+          DomainEvent::QuestionCreated {
+              question_uuid: "123".to_owned(),
+              author_uuid: None,
+              description: "test description".to_owned(),
+          },
+          DomainEvent::AnswerCreated {
+              question_uuid: "123".to_owned(),
+              answer_uuid: "456".to_owned(),
+              author_uuid: None,
+              content: "test content".to_owned(),
+          },
+          DomainEvent::AnswerUpdated {
+              question_uuid: "123".to_owned(),
+              answer_uuid: "456".to_owned(),
+          },
+      ];
+
+      for event in events {
+          queue_webhook_deliveries(event, "https://forum.example.com/", &webhooks_dao).await;
+      }
+
+      let forum_url = "https://forum.example.com".to_owned();
+
+      assert_eq!(
+          webhooks_dao.queued(),
+          vec![
+              (WebhookEvent::QuestionCreated, "123".to_owned(), None, forum_url.clone()),
+              (WebhookEvent::AnswerCreated, "123".to_owned(), Some("456".to_owned()), forum_url.clone()),
+              (WebhookEvent::AnswerUpdated, "123".to_owned(), Some("456".to_owned()), forum_url),
+          ]
+      );
+  }
+
+  #[tokio::test]
+  async fn read_faq_should_group_entries_and_serve_them_from_the_cache() {
+      let entry = |title: &str, tags: Vec<&str>, board_name: Option<&str>| FaqEntry {
+          question_uuid: Uuid::from_u128(title.len() as u128),
+          title: title.to_owned(),
+          description: "description".to_owned(),
+          answer_uuid: Uuid::from_u128(0x456),
+          answer: "answer".to_owned(),
+          tags: tags.into_iter().map(str::to_owned).collect(),
+          board_uuid: board_name.map(|_| Uuid::from_u128(0x789)),
+          board_name: board_name.map(str::to_owned),
+          curated_at: datetime!(2026-10-17 10:00:00.0 UTC),
+      };
+      let entries = vec![
+          entry("Borrowing", vec!["rust", "lifetimes"], Some("Help")),
+          entry("Cargo", vec!["rust"], None),
+          entry("Offtopic", vec![], None),
+      ];
+      let mut faq_dao = FaqDaoMock::new();
+      let cache = TtlCache::new(60, 10);
+
+      faq_dao.mock_get_faq_entries(Ok(entries.clone()));
+
+      let faq = read_faq(FaqQuery::default(), 1000, &faq_dao, &cache).await.unwrap();
+      let names: Vec<_> = faq.iter().map(|group| group.name.as_deref()).collect();
+
+      assert_eq!(names, vec![Some("lifetimes"), Some("rust"), None]);
+      assert_eq!(faq[1].entries.len(), 2);
+      assert_eq!(faq[2].entries[0].title, "Offtopic");
+
+      // Served from the cache, as the mock has no response left.
+      assert_eq!(read_faq(FaqQuery::default(), 1010, &faq_dao, &cache).await.unwrap(), faq);
+
+      faq_dao.mock_get_faq_entries(Ok(entries));
+
+      let faq = read_faq(FaqQuery { group_by: FaqGrouping::Board }, 1010, &faq_dao, &cache).await.unwrap();
+      let names: Vec<_> = faq.iter().map(|group| group.name.as_deref()).collect();
+
+      assert_eq!(names, vec![Some("Help"), None]);
+      assert_eq!(faq[1].entries.len(), 2);
+  }
+
+  #[test]
+  fn read_slo_status_should_be_for_admins() {
+      let slo_tracker = SloTracker::new(crate::slo::parse_targets("GET /questions 99.9").unwrap());
+      slo_tracker.record("GET /questions", 500, 10, 60_000);
+
+      let result = read_slo_status(&user_with_role(Role::Moderator), &slo_tracker, 60_000);
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let status = read_slo_status(&user_with_role(Role::Admin), &slo_tracker, 60_000).unwrap();
+
+      assert_eq!(status.len(), 1);
+      assert_eq!(status[0].route, "GET /questions");
+      assert_eq!(status[0].windows[0].errors, 1);
+  }
+
+  #[tokio::test]
+  async fn update_query_sampling_should_apply_the_rate_at_once_and_audit_it() {
+      let mut query_samples_dao = QuerySamplesDaoMock::new();
+      let query_sampler = QuerySampler::new(std::sync::Arc::new(QuerySamplesDaoMock::new()));
+      let audit_dao = AuditDaoMock::new();
+      let admin = user_with_role(Role::Admin);
+
+      let result = update_query_sampling(
+        &user_with_role(Role::Moderator),
+        QuerySampling { percent: 5.0 },
+        &query_sampler,
+        &query_samples_dao,
+        &audit_dao,
+      )
+      .await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let result =
+        update_query_sampling(&admin, QuerySampling { percent: 150.0 }, &query_sampler, &query_samples_dao, &audit_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      query_samples_dao.mock_set_sampling_percent(Ok(()));
+
+      let sampling =
+        update_query_sampling(&admin, QuerySampling { percent: 5.0 }, &query_sampler, &query_samples_dao, &audit_dao)
+          .await
+          .unwrap();
+
+      assert_eq!(sampling.percent, 5.0);
+      assert_eq!(query_sampler.percent(), 5.0);
+      assert_eq!(audit_dao.entries()[0].action, AuditAction::SetQuerySampling);
+      assert_eq!(audit_dao.entries()[0].payload, json!({ "percent": 5.0 }));
+  }
+
+  #[tokio::test]
+  async fn diagnostics_should_be_for_admins() {
+      let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/forum").unwrap();
+      let probe = DiagnosticsProbe::new(pool, false);
+
+      let result = read_diagnostics(&user_with_role(Role::Moderator), &probe);
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let diagnostics = read_diagnostics(&user_with_role(Role::Admin), &probe).unwrap();
+
+      assert!(!diagnostics.profiling_enabled);
+
+      let query = ProfileQuery { seconds: MAX_PROFILE_SECONDS + 1, format: Default::default() };
+      let result = capture_cpu_profile(&user_with_role(Role::Admin), query, &probe).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      let query = ProfileQuery { seconds: 1, format: Default::default() };
+      let result = capture_cpu_profile(&user_with_role(Role::Admin), query, &probe).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_retention_stats_should_list_every_category_with_its_period() {
+      let mut retention_dao = RetentionDaoMock::new();
+
+      let result = read_retention_stats(&user_with_role(Role::Moderator), RetentionPolicy::default(), &retention_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      retention_dao.mock_get_retention_stats(Ok(vec![RetentionStats {
+          category: RetentionCategory::DeletedAnswers,
+          retention_days: None,
+          last_run_at: Some(datetime!(2026-10-17 10:00:00.0 UTC)),
+          last_removed: 2,
+          total_removed: 7,
+      }]));
+
+      let policy = RetentionPolicy {
+          ip_address_days: Some(90),
+          ..Default::default()
+      };
+      let stats = read_retention_stats(&user_with_role(Role::Admin), policy, &retention_dao).await.unwrap();
+
+      assert_eq!(stats.len(), RetentionCategory::ALL.len());
+      assert_eq!(stats[0].category, RetentionCategory::AuditLog);
+      assert_eq!(stats[0].retention_days, None);
+      assert_eq!(stats[0].last_run_at, None);
+      assert_eq!(stats[1].retention_days, Some(90));
+      assert_eq!(stats[2].retention_days, Some(1));
+      assert_eq!(
+          stats[3],
+          RetentionStats {
+              category: RetentionCategory::DeletedAnswers,
+              retention_days: Some(30),
+              last_run_at: Some(datetime!(2026-10-17 10:00:00.0 UTC)),
+              last_removed: 2,
+              total_removed: 7,
+          }
+      );
+  }
+
+  #[tokio::test]
+  async fn update_digest_settings_should_normalize_tags() {
+      let mut email_digests_dao = EmailDigestsDaoMock::new();
+
+      let settings = DigestSettings {
+          frequency: DigestFrequency::Weekly,
+          tags: vec!["not a tag".to_owned()],
+      };
+      let result = update_digest_settings(&user_with_role(Role::User), settings, &email_digests_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      email_digests_dao.mock_set_digest_settings(Ok(()));
+
+      let settings = DigestSettings {
+          frequency: DigestFrequency::Weekly,
+          tags: vec!["Tokio".to_owned(), "serde".to_owned(), "tokio".to_owned()],
+      };
+      let result = update_digest_settings(&user_with_role(Role::User), settings, &email_digests_dao).await;
+
+      assert_eq!(
+          result,
+          Ok(DigestSettings {
+              frequency: DigestFrequency::Weekly,
+              tags: vec!["tokio".to_owned(), "serde".to_owned()],
+          })
+      );
+  }
+
+  #[tokio::test]
+  async fn read_notifications_should_return_the_inbox() {
+      let notification = Notification {
+          id: 1,
+          kind: NotificationKind::Mention,
+          question_uuid: Some(Uuid::from_u128(0x123)),
+          answer_uuid: None,
+          actor_uuid: Some(Uuid::from_u128(0x321)),
+          created_at: datetime!(2026-10-17 10:00:00.0 UTC),
+          read_at: None,
+      };
+      let mut notifications_dao = NotificationsDaoMock::new();
+
+      notifications_dao.mock_get_notifications(Ok(vec![notification.clone()]));
+
+      let too_many = Pagination { offset: 0, limit: Pagination::MAX_LIMIT + 1 };
+      let result = read_notifications(&user_with_role(Role::User), NotificationsQuery::default(), too_many, &notifications_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      let result = read_notifications(&user_with_role(Role::User), NotificationsQuery { unread: true }, Pagination::default(), &notifications_dao).await;
+
+      assert_eq!(result, Ok(vec![notification]));
+  }
+
+  #[tokio::test]
+  async fn mark_notifications_read_should_report_missing_and_read_counts() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+
+      notifications_dao.mock_mark_notification_read(Ok(false));
+      notifications_dao.mock_mark_all_notifications_read(Ok(3));
+
+      let result = mark_notification_read(7, &user_with_role(Role::User), &notifications_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+
+      let result = mark_all_notifications_read(&user_with_role(Role::User), &notifications_dao).await;
+
+      assert_eq!(result, Ok(NotificationsRead { read: 3 }));
+  }
+
+  #[tokio::test]
+  async fn bulk_delete_questions_should_require_moderator() {
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+
+      let request = BulkDelete {
+          uuids: vec!["123".to_owned()],
+      };
+
+      let result = bulk_delete_questions(request, &user_with_role(Role::User), questions_dao.as_ref(), &AuditDaoMock::new(), &EventBus::default()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn bulk_delete_answers_should_report_per_item_results() {
+      let results = vec![
+          BulkDeleteResult {
+              uuid: "123".to_owned(),
+              deleted: true,
+              error: None,
+          },
+          BulkDeleteResult {
+              uuid: "456".to_owned(),
+              deleted: false,
+              error: Some("Not found or already deleted.".to_owned()),
+          },
+      ];
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_delete_answers(Ok(results.clone()));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let request = BulkDelete {
+          uuids: vec!["123".to_owned(), "456".to_owned()],
+      };
+
+      let result = bulk_delete_answers(request, &user_with_role(Role::Moderator), answers_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert_eq!(result.unwrap(), results);
+  }
+
+  #[tokio::test]
+  async fn bulk_delete_answers_should_reject_too_many_uuids() {
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+
+      let request = BulkDelete {
+          uuids: vec!["123".to_owned(); BulkDelete::MAX_ITEMS + 1],
+      };
+
+      let result = bulk_delete_answers(request, &user_with_role(Role::Moderator), answers_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  fn invitation(role: Option<Role>) -> InvitationDetail {
+      InvitationDetail {
+          invitation_uuid: Uuid::from_u128(0x123),
+          board_uuid: None,
+          role,
+          status: InvitationStatus::Pending,
+          created_by: Some(Uuid::from_u128(0x789)),
+          used_by: None,
+          expires_at: OffsetDateTime::UNIX_EPOCH,
+          used_at: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  #[tokio::test]
+  async fn create_invitation_should_require_admin() {
+      let invitations_dao: Box<dyn InvitationsDao + Send + Sync> = Box::new(InvitationsDaoMock::new());
+
+      let request = Invitation {
+          board_uuid: None,
+          role: None,
+          expires_in_seconds: 60,
+      };
+
+      let result = create_invitation(
+          request,
+          &user_with_role(Role::Moderator),
+          invitations_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+          1_000,
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn invitation_link_should_register_user_with_invited_role() {
+      let url_signer = UrlSigner::parse(SIGNING_KEYS).unwrap();
+
+      let mut invitations_dao = InvitationsDaoMock::new();
+
+      invitations_dao.mock_create_invitation(Ok(invitation(Some(Role::Moderator))));
+
+      let invitations_dao: Box<dyn InvitationsDao + Send + Sync> = Box::new(invitations_dao);
+
+      let request = Invitation {
+          board_uuid: None,
+          role: Some(Role::Moderator),
+          expires_in_seconds: 60,
+      };
+
+      let link = create_invitation(
+          request,
+          &user_with_role(Role::Admin),
+          invitations_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &url_signer,
+          1_000,
+      )
+      .await
+      .unwrap();
+
+      assert!(link.url.starts_with("/api/v1/users?invitation_uuid=00000000-0000-0000-0000-000000000123&expires=1060&key_id=1&signature="));
+
+      let acceptance = InvitationAcceptance {
+          invitation_uuid: Uuid::from_u128(0x123),
+          expires: link.expires,
+          key_id: 1,
+          signature: link.url.rsplit('=').next().unwrap().to_owned(),
+      };
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_create_user(Ok(user_with_role(Role::User)));
+      users_dao.mock_record_user_ip(Ok(()));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let mut invitations_dao = InvitationsDaoMock::new();
+
+      invitations_dao.mock_get_pending_invitation(Ok(Some(invitation(Some(Role::Moderator)))));
+      invitations_dao.mock_accept_invitation(Ok(Some(invitation(Some(Role::Moderator)))));
+
+      let invitations_dao: Box<dyn InvitationsDao + Send + Sync> = Box::new(invitations_dao);
+
+      let user = User {
+          username: "test user".to_owned(),
+          email: None,
+      };
+
+      let credentials = create_invited_user(
+          user,
+          "127.0.0.1".to_owned(),
+          acceptance,
+          users_dao.as_ref(),
+          invitations_dao.as_ref(),
+          &url_signer,
+          1_030,
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(credentials.user.role, Role::Moderator);
+  }
+
+  #[tokio::test]
+  async fn create_invited_user_should_reject_used_invitation() {
+      let url_signer = UrlSigner::parse(SIGNING_KEYS).unwrap();
+      let signature = url_signer.sign(&invitation_path("00000000-0000-0000-0000-000000000123"), 1_060);
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(UsersDaoMock::new());
+
+      let mut invitations_dao = InvitationsDaoMock::new();
+
+      invitations_dao.mock_get_pending_invitation(Ok(None));
+
+      let invitations_dao: Box<dyn InvitationsDao + Send + Sync> = Box::new(invitations_dao);
+
+      let acceptance = InvitationAcceptance {
+          invitation_uuid: Uuid::from_u128(0x123),
+          expires: signature.expires,
+          key_id: signature.key_id,
+          signature: signature.signature,
+      };
+
+      let user = User {
+          username: "test user".to_owned(),
+          email: None,
+      };
+
+      let result = create_invited_user(
+          user,
+          "127.0.0.1".to_owned(),
+          acceptance,
+          users_dao.as_ref(),
+          invitations_dao.as_ref(),
+          &url_signer,
+          1_030,
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  fn scim_config() -> ScimConfig {
+      ScimConfig {
+          token_hash: hash_api_token("provisioning-token"),
+          group_roles: GroupRoleMap::parse("forum-admins:admin").unwrap(),
+      }
+  }
+
+  fn scim_user(user_name: &str) -> ScimUser {
+      ScimUser {
+          schemas: Vec::new(),
+          id: None,
+          external_id: Some("idp-1".to_owned()),
+          user_name: user_name.to_owned(),
+          display_name: None,
+          active: true,
+          emails: Vec::new(),
+          groups: Vec::new(),
+          meta: None,
+      }
+  }
+
+  #[test]
+  fn authenticate_scim_should_check_provisioning_token() {
+      assert!(authenticate_scim("provisioning-token", Some(&scim_config())).is_ok());
+      assert!(
+          std::mem::discriminant(&authenticate_scim("other-token", Some(&scim_config())).unwrap_err())
+              == std::mem::discriminant(&HandlerError::Unauthorized("".to_owned()))
+      );
+      assert!(
+          std::mem::discriminant(&authenticate_scim("provisioning-token", None).unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn scim_create_user_should_return_conflict_for_existing_user() {
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_provision_user(Err(DBError::UniqueViolation("test".to_owned())));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = scim_create_user(scim_user("test user"), &scim_config(), users_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.err().unwrap())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn scim_patch_user_should_deactivate_user() {
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_set_user_active(Ok(Some(ProvisionedUserDetail {
+          user: user_with_role(Role::User),
+          display_name: None,
+          external_id: Some("idp-1".to_owned()),
+          active: true,
+      })));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let patch = ScimPatch {
+          operations: vec![ScimPatchOperation {
+              op: "replace".to_owned(),
+              path: Some("active".to_owned()),
+              value: serde_json::Value::Bool(false),
+          }],
+      };
+
+      let user = scim_patch_user("789".to_owned(), patch, users_dao.as_ref()).await.ok().unwrap();
+
+      assert!(!user.active);
+      assert_eq!(user.id.as_deref(), Some("00000000-0000-0000-0000-000000000789"));
+  }
+
+  /// Accepts only the password `correct horse`.
+  struct AuthBackendMock;
+
+  #[async_trait]
+  impl AuthBackend for AuthBackendMock {
+      async fn authenticate(&self, username: &str, password: &str) -> Result<Option<ProvisionedUser>, AuthBackendError> {
+          Ok((password == "correct horse").then(|| ProvisionedUser {
+              username: username.to_owned(),
+              display_name: None,
+              email: None,
+              external_id: Some(format!("uid={},dc=example,dc=com", username)),
+              role: Role::User,
+              active: true,
+          }))
+      }
+  }
+
+  fn sign_in_request(password: &str) -> SignIn {
+      SignIn {
+          username: "test user".to_owned(),
+          password: password.to_owned(),
+      }
+  }
+
+  #[tokio::test]
+  async fn sign_in_should_return_not_found_without_auth_backend() {
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(UsersDaoMock::new());
+
+      let result = sign_in(sign_in_request("correct horse"), "127.0.0.1".to_owned(), None, users_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.err().unwrap())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn sign_in_should_return_unauthorized_for_wrong_password() {
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(UsersDaoMock::new());
+
+      let result = sign_in(
+          sign_in_request("wrong"),
+          "127.0.0.1".to_owned(),
+          Some(&AuthBackendMock),
+          users_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.err().unwrap())
+              == std::mem::discriminant(&HandlerError::Unauthorized("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn sign_in_should_return_forbidden_for_deactivated_user() {
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_upsert_directory_user(Ok(None));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let result = sign_in(
+          sign_in_request("correct horse"),
+          "127.0.0.1".to_owned(),
+          Some(&AuthBackendMock),
+          users_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.err().unwrap())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  fn dead_letter() -> DeadLetter {
+      DeadLetter {
+          dead_letter_uuid: Uuid::from_u128(0x123),
+          kind: DeadLetterKind::WebhookDigest,
+          payload: serde_json::json!({ "since": "", "until": "", "questions": [], "answers": [] }),
+          attempts: 5,
+          errors: vec!["connection refused".to_owned()],
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          last_failed_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  #[tokio::test]
+  async fn retry_dead_letters_should_keep_item_when_no_webhook_is_configured() {
+      let mut dead_letters_dao = DeadLettersDaoMock::new();
+
+      dead_letters_dao.mock_get_dead_letter(Ok(Some(dead_letter())));
+      dead_letters_dao.mock_record_dead_letter_failure(Ok(()));
+
+      let dead_letters_dao: Box<dyn DeadLettersDao + Send + Sync> = Box::new(dead_letters_dao);
+
+      let selection = DeadLetterSelection {
+          dead_letter_uuids: vec![Uuid::from_u128(0x123)],
+      };
+
+      let results = retry_dead_letters(&user_with_role(Role::Admin), selection, dead_letters_dao.as_ref(), None, &AuditDaoMock::new())
+          .await
+          .unwrap();
+
+      assert_eq!(
+          results,
+          vec![DeadLetterRetryResult {
+              dead_letter_uuid: Uuid::from_u128(0x123),
+              delivered: false,
+              error: Some("No webhook is configured for this delivery.".to_owned()),
+          }]
+      );
+  }
+
+  #[tokio::test]
+  async fn retry_dead_letters_should_report_missing_items() {
+      let mut dead_letters_dao = DeadLettersDaoMock::new();
+
+      dead_letters_dao.mock_get_dead_letter(Ok(None));
+
+      let dead_letters_dao: Box<dyn DeadLettersDao + Send + Sync> = Box::new(dead_letters_dao);
+
+      let selection = DeadLetterSelection {
+          dead_letter_uuids: vec![Uuid::from_u128(0x123)],
+      };
+
+      let results = retry_dead_letters(&user_with_role(Role::Admin), selection, dead_letters_dao.as_ref(), None, &AuditDaoMock::new())
+          .await
+          .unwrap();
+
+      assert!(!results[0].delivered);
+      assert_eq!(results[0].error.as_deref(), Some("Dead letter not found."));
+  }
+
+  #[tokio::test]
+  async fn purge_dead_letters_should_return_results() {
+      let mut dead_letters_dao = DeadLettersDaoMock::new();
+
+      let deleted = vec![BulkDeleteResult {
+          uuid: "123".to_owned(),
+          deleted: true,
+          error: None,
+      }];
+
+      dead_letters_dao.mock_delete_dead_letters(Ok(deleted.clone()));
+
+      let dead_letters_dao: Box<dyn DeadLettersDao + Send + Sync> = Box::new(dead_letters_dao);
+
+      let selection = DeadLetterSelection {
+          dead_letter_uuids: vec![Uuid::from_u128(0x123)],
+      };
+
+      let results = purge_dead_letters(&user_with_role(Role::Admin), selection, dead_letters_dao.as_ref(), &AuditDaoMock::new())
+          .await
+          .unwrap();
+
+      assert_eq!(results, deleted);
+  }
+
+  #[tokio::test]
+  async fn purge_dead_letters_should_forbid_non_admins() {
+      let dead_letters_dao: Box<dyn DeadLettersDao + Send + Sync> = Box::new(DeadLettersDaoMock::new());
+
+      let selection = DeadLetterSelection {
+          dead_letter_uuids: vec![Uuid::from_u128(0x123)],
+      };
+
+      let result = purge_dead_letters(&user_with_role(Role::Moderator), selection, dead_letters_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  fn job() -> JobDetail {
+      JobDetail {
+          job_uuid: Uuid::from_u128(0x123),
+          kind: JobKind::PurgeDeletedPosts,
+          status: JobStatus::Queued,
+          progress: 0,
+          result: None,
+          error: None,
+          requested_by: Some(Uuid::from_u128(0x2)),
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          started_at: None,
+          finished_at: None,
+          input: None,
+      }
+  }
+
+  #[tokio::test]
+  async fn create_job_should_queue_job() {
+      let mut jobs_dao = JobsDaoMock::new();
+
+      jobs_dao.mock_create_job(Ok(job()));
+
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(jobs_dao);
+
+      let request = JobRequest {
+          kind: JobKind::PurgeDeletedPosts,
+      };
+
+      let result = create_job(&user_with_role(Role::Admin), request, jobs_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert_eq!(result, Ok(job()));
+  }
+
+  #[tokio::test]
+  async fn create_job_should_forbid_non_admins() {
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(JobsDaoMock::new());
+
+      let request = JobRequest {
+          kind: JobKind::PurgeDeletedPosts,
+      };
+
+      let result = create_job(&user_with_role(Role::Moderator), request, jobs_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn create_job_should_refuse_imports() {
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(JobsDaoMock::new());
+
+      let request = JobRequest {
+          kind: JobKind::Import,
+      };
+
+      let result = create_job(&user_with_role(Role::Admin), request, jobs_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn start_import_should_store_the_dump_and_queue_a_job() {
+      let import_job = JobDetail {
+          kind: JobKind::Import,
+          ..job()
+      };
+
+      let mut jobs_dao = JobsDaoMock::new();
+
+      jobs_dao.mock_create_job(Ok(import_job.clone()));
+
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(jobs_dao);
+      let object_store = ObjectStoreMock::new();
+
+      let dump = br#"{"type":"user","user_uuid":"00000000-0000-0000-0000-000000000001","username":"ada","created_at":"2015-03-01T10:00:00Z"}"#;
+
+      let result = start_import(
+          &user_with_role(Role::Admin),
+          "application/x-ndjson",
+          Bytes::from_static(dump),
+          jobs_dao.as_ref(),
+          &object_store,
+          &AuditDaoMock::new(),
+      )
+      .await;
+
+      assert_eq!(result, Ok(import_job));
+      assert_eq!(object_store.len(), 1);
+  }
+
+  #[tokio::test]
+  async fn start_import_should_refuse_invalid_dumps() {
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(JobsDaoMock::new());
+      let object_store = ObjectStoreMock::new();
+
+      let dump = br#"{"type":"user","user_uuid":"00000000-0000-0000-0000-000000000001","username":"","created_at":"2015-03-01T10:00:00Z"}"#;
+
+      let result = start_import(
+          &user_with_role(Role::Admin),
+          "application/x-ndjson",
+          Bytes::from_static(dump),
+          jobs_dao.as_ref(),
+          &object_store,
+          &AuditDaoMock::new(),
+      )
+      .await;
+
+      assert_eq!(
+          result,
+          Err(HandlerError::BadRequest("Line 1: username must not be empty.".to_owned()))
+      );
+      assert_eq!(object_store.len(), 0);
+  }
+
+  #[tokio::test]
+  async fn read_import_should_not_return_other_jobs() {
+      let mut jobs_dao = JobsDaoMock::new();
+
+      jobs_dao.mock_get_job(Ok(Some(job())));
+
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(jobs_dao);
+
+      let result = read_import(&user_with_role(Role::Admin), "123".to_owned(), jobs_dao.as_ref()).await;
+
+      assert_eq!(result, Err(HandlerError::NotFound("Import not found.".to_owned())));
+  }
+
+  #[tokio::test]
+  async fn read_admin_stats_should_return_stats_to_admins() {
+      let stats = AdminStats {
+          questions: 2,
+          answers: 1,
+          users: 1,
+          answer_rate: Some(0.5),
+          median_seconds_to_first_answer: Some(60.0),
+          per_day: Vec::new(),
+      };
+
+      let mut stats_dao = StatsDaoMock::new();
+
+      stats_dao.mock_get_admin_stats(Ok(stats.clone()));
+
+      let stats_dao: Box<dyn StatsDao + Send + Sync> = Box::new(stats_dao);
+
+      let result = read_admin_stats(&user_with_role(Role::Admin), AdminStatsQuery::default(), stats_dao.as_ref()).await;
+
+      assert_eq!(result, Ok(stats));
+
+      let result = read_admin_stats(&user_with_role(Role::Moderator), AdminStatsQuery::default(), stats_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_admin_stats_should_limit_days() {
+      let stats_dao: Box<dyn StatsDao + Send + Sync> = Box::new(StatsDaoMock::new());
+
+      for days in [0, AdminStats::MAX_DAYS + 1] {
+          let result = read_admin_stats(&user_with_role(Role::Admin), AdminStatsQuery { days: Some(days) }, stats_dao.as_ref()).await;
+
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+  }
+
+  #[tokio::test]
+  async fn read_job_should_return_not_found() {
+      let mut jobs_dao = JobsDaoMock::new();
+
+      jobs_dao.mock_get_job(Ok(None));
+
+      let jobs_dao: Box<dyn JobsDao + Send + Sync> = Box::new(jobs_dao);
+
+      let result = read_job(&user_with_role(Role::Admin), "123".to_owned(), jobs_dao.as_ref()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn read_answers_should_attach_link_previews_in_link_order() {
+      let answer = AnswerDetail {
+          content: "See https://b.example and [a](https://a.example), not https://missing.example".to_owned(),
+          ..answer_by(None)
+      };
+
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answers(Ok(vec![answer.clone(), answer_by(None)]));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+      let preview = |url: &str| LinkPreview {
+          url: url.to_owned(),
+          title: Some(url.to_owned()),
+          ..Default::default()
+      };
+
+      let mut link_previews_dao = LinkPreviewsDaoMock::new();
+
+      link_previews_dao.mock_get_link_previews(Ok(vec![preview("https://a.example"), preview("https://b.example")]));
+
+      let link_previews_dao: Box<dyn LinkPreviewsDao + Send + Sync> = Box::new(link_previews_dao);
+
+      let question_id = QuestionId {
+          question_uuid: Uuid::from_u128(0x123),
+      };
+
+      let answers = read_answers(
+          question_id,
+          AnswerSort::Oldest,
+          Viewer::Anonymous,
+          answers_dao.as_ref(),
+          link_previews_dao.as_ref(),
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(answers[0].link_previews, vec![preview("https://b.example"), preview("https://a.example")]);
+      assert!(answers[1].link_previews.is_empty());
+  }
+
+  #[tokio::test]
+  async fn set_cleanup_policy_should_reject_invalid_policies() {
+      let cleanup_policies_dao: Box<dyn CleanupPoliciesDao + Send + Sync> = Box::new(CleanupPoliciesDaoMock::new());
+
+      let policies = [
+          BoardCleanupPolicy::default(),
+          BoardCleanupPolicy {
+              close_inactive_after_days: Some(0),
+              delete_unanswered_after_days: None,
+          },
+          BoardCleanupPolicy {
+              close_inactive_after_days: Some(365),
+              delete_unanswered_after_days: Some(BoardCleanupPolicy::MAX_DAYS + 1),
+          },
+      ];
+
+      for policy in policies {
+          let result = set_cleanup_policy(
+              &user_with_role(Role::Admin),
+              "123".to_owned(),
+              policy,
+              cleanup_policies_dao.as_ref(),
+              &AuditDaoMock::new(),
+          )
+          .await;
+
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+  }
+
+  #[tokio::test]
+  async fn set_cleanup_policy_should_return_not_found_for_missing_board() {
+      let mut cleanup_policies_dao = CleanupPoliciesDaoMock::new();
+
+      cleanup_policies_dao.mock_set_cleanup_policy(Ok(None));
+
+      let cleanup_policies_dao: Box<dyn CleanupPoliciesDao + Send + Sync> = Box::new(cleanup_policies_dao);
+
+      let policy = BoardCleanupPolicy {
+          close_inactive_after_days: Some(365),
+          delete_unanswered_after_days: None,
+      };
+
+      let result = set_cleanup_policy(
+          &user_with_role(Role::Admin),
+          "123".to_owned(),
+          policy,
+          cleanup_policies_dao.as_ref(),
+          &AuditDaoMock::new(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn preview_cleanup_should_forbid_non_admins() {
+      let cleanup_policies_dao: Box<dyn CleanupPoliciesDao + Send + Sync> = Box::new(CleanupPoliciesDaoMock::new());
+
+      let policy = BoardCleanupPolicy {
+          close_inactive_after_days: None,
+          delete_unanswered_after_days: Some(30),
+      };
+
+      let result = preview_cleanup(
+          &user_with_role(Role::Moderator),
+          "123".to_owned(),
+          policy,
+          cleanup_policies_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn preview_cleanup_should_return_dry_run() {
+      let cleanup = BoardCleanup {
+          board_uuid: Uuid::from_u128(0x123),
+          dry_run: true,
+          closed_question_uuids: vec![Uuid::from_u128(0x456)],
+          deleted_question_uuids: vec![],
+      };
+
+      let mut cleanup_policies_dao = CleanupPoliciesDaoMock::new();
+
+      cleanup_policies_dao.mock_preview_cleanup(Ok(cleanup.clone()));
+
+      let cleanup_policies_dao: Box<dyn CleanupPoliciesDao + Send + Sync> = Box::new(cleanup_policies_dao);
+
+      let policy = BoardCleanupPolicy {
+          close_inactive_after_days: Some(365),
+          delete_unanswered_after_days: None,
+      };
+
+      let result = preview_cleanup(
+          &user_with_role(Role::Admin),
+          "123".to_owned(),
+          policy,
+          cleanup_policies_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result, Ok(cleanup));
+  }
+
+  fn flag_detail(answer_uuid: Option<Uuid>) -> FlagDetail {
+      FlagDetail {
+          flag_uuid: Uuid::from_u128(0x999),
+          question_uuid: Uuid::from_u128(0x123),
+          answer_uuid,
+          reporter_uuid: Some(Uuid::from_u128(0x789)),
+          reason: FlagReason::Spam,
+          details: None,
+          status: FlagStatus::Open,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          resolved_by: None,
+          resolved_at: None,
+          resolution_note: None,
+      }
+  }
+
+  fn spam_flag() -> Flag {
+      Flag {
+          reason: FlagReason::Spam,
+          details: None,
+      }
+  }
+
+  #[tokio::test]
+  async fn flag_question_should_create_flag() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(Uuid::max());
+
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut flags_dao = FlagsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question)));
+      flags_dao.mock_create_flag(Ok(flag_detail(None)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+      let result = flag_question(
+          "123".to_owned(),
+          spam_flag(),
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          flags_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap(), flag_detail(None));
+  }
+
+  #[tokio::test]
+  async fn flag_question_should_reject_own_questions_and_other_without_details() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(Uuid::from_u128(0x789));
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(FlagsDaoMock::new());
+
+      let other = Flag {
+          reason: FlagReason::Other,
+          details: Some("  ".to_owned()),
+      };
+
+      for flag in [spam_flag(), other] {
+          let result = flag_question(
+              "123".to_owned(),
+              flag,
+              &user_with_role(Role::User),
+              questions_dao.as_ref(),
+              flags_dao.as_ref(),
+          )
+          .await;
+
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+  }
+
+  #[tokio::test]
+  async fn flag_answer_should_return_conflict_for_repeated_flags() {
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut flags_dao = FlagsDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::max())))));
+      flags_dao.mock_create_flag(Err(DBError::UniqueViolation("flags_open_per_reporter_idx".to_owned())));
+
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+      let result = flag_answer(
+          "456".to_owned(),
+          spam_flag(),
+          &user_with_role(Role::User),
+          answers_dao.as_ref(),
+          flags_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn vote_answer_should_return_the_score() {
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::max())))));
+      answers_dao.mock_vote_answer(Ok(3));
+
+      let result = vote_answer("456".to_owned(), AnswerVote { value: 1 }, &user_with_role(Role::User), &answers_dao).await;
+
+      assert_eq!(result.unwrap(), AnswerScore { answer_uuid: Uuid::from_u128(0x456), score: 3 });
+  }
+
+  #[tokio::test]
+  async fn vote_answer_should_reject_own_answers_and_other_values() {
+      let user = user_with_role(Role::User);
+      let mut answers_dao = AnswersDaoMock::new();
+
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(user.user_uuid)))));
+
+      let own = vote_answer("456".to_owned(), AnswerVote { value: 1 }, &user, &answers_dao).await;
+      let other = vote_answer("456".to_owned(), AnswerVote { value: 2 }, &user, &answers_dao).await;
+
+      for result in [own, other] {
+          assert!(matches!(result.unwrap_err(), HandlerError::BadRequest(_)));
+      }
+  }
+
+  #[tokio::test]
+  async fn accept_answer_should_publish_the_update() {
+      let user = user_with_role(Role::User);
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(user.user_uuid);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question)));
+      questions_dao.mock_accept_answer(Ok(true));
+
+      let events = EventBus::default();
+      let mut published = events.subscribe();
+
+      let accept = AcceptAnswer { answer_uuid: Some(Uuid::from_u128(0x456)) };
+      let result = accept_answer("123".to_owned(), accept, &user, &questions_dao, &events).await;
+
+      assert!(result.is_ok());
+      assert_eq!(
+          published.try_recv().unwrap().event,
+          DomainEvent::QuestionUpdated { question_uuid: Uuid::from_u128(0x123).to_string() }
+      );
+  }
+
+  #[tokio::test]
+  async fn accept_answer_should_only_allow_the_author_of_the_question() {
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+
+      let accept = AcceptAnswer { answer_uuid: Some(Uuid::from_u128(0x456)) };
+      let result = accept_answer(
+          "123".to_owned(),
+          accept,
+          &user_with_role(Role::Moderator),
+          &questions_dao,
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(matches!(result.unwrap_err(), HandlerError::Forbidden(_)));
+  }
+
+  #[tokio::test]
+  async fn accept_answer_should_return_not_found_for_answers_of_other_questions() {
+      let user = user_with_role(Role::User);
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(user.user_uuid);
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question)));
+      questions_dao.mock_accept_answer(Ok(false));
+
+      let accept = AcceptAnswer { answer_uuid: Some(Uuid::max()) };
+      let result = accept_answer("123".to_owned(), accept, &user, &questions_dao, &EventBus::default()).await;
+
+      assert!(matches!(result.unwrap_err(), HandlerError::NotFound(_)));
+  }
+
+  #[tokio::test]
+  async fn read_flags_should_only_allow_moderators() {
+      let mut flags_dao = FlagsDaoMock::new();
+
+      flags_dao.mock_get_flags(Ok(vec![flag_detail(Some(Uuid::from_u128(0x456)))]));
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+      let result = read_flags(
+          &user_with_role(Role::User),
+          FlagsQuery::default(),
+          Pagination::default(),
+          flags_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let result = read_flags(
+          &user_with_role(Role::Moderator),
+          FlagsQuery::default(),
+          Pagination::default(),
+          flags_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap(), vec![flag_detail(Some(Uuid::from_u128(0x456)))]);
+  }
+
+  #[tokio::test]
+  async fn resolve_flag_should_return_not_found_for_resolved_flags() {
+      let mut flags_dao = FlagsDaoMock::new();
+
+      flags_dao.mock_resolve_flag(Ok(None));
+
+      let flags_dao: Box<dyn FlagsDao + Send + Sync> = Box::new(flags_dao);
+
+      let reopen = ResolveFlag {
+          status: FlagStatus::Open,
+          note: None,
+      };
+      let result = resolve_flag("999".to_owned(), reopen, &user_with_role(Role::Moderator), flags_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      let dismiss = ResolveFlag {
+          status: FlagStatus::Dismissed,
+          note: Some("Not spam.".to_owned()),
+      };
+      let result = resolve_flag("999".to_owned(), dismiss, &user_with_role(Role::Moderator), flags_dao.as_ref(), &AuditDaoMock::new()).await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
+
+  fn moderation_action(action: ModerationActionKind, answer_uuid: Option<Uuid>) -> ModerationAction {
+      ModerationAction {
+          question_uuid: Uuid::from_u128(0x123),
+          answer_uuid,
+          action,
+          reason: "Rude.".to_owned(),
+          title: None,
+          content: None,
+      }
+  }
+
+  fn moderation_action_detail(action: ModerationActionKind) -> ModerationActionDetail {
+      ModerationActionDetail {
+          action_uuid: Uuid::from_u128(0x999),
+          moderator_uuid: Some(Uuid::from_u128(0x789)),
+          action,
+          question_uuid: Uuid::from_u128(0x123),
+          answer_uuid: Some(Uuid::from_u128(0x456)),
+          reason: "Rude.".to_owned(),
+          resolved_flags: 1,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+      }
+  }
+
+  #[tokio::test]
+  async fn read_moderation_queue_should_only_allow_moderators() {
+      let item = ModerationItem {
+          question_uuid: Uuid::from_u128(0x123),
+          answer_uuid: None,
+          author_uuid: Some(Uuid::from_u128(0x111)),
+          title: "test title".to_owned(),
+          content: "test description".to_owned(),
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          open_flags: 0,
+          flag_reasons: Vec::new(),
+          new_author: true,
+      };
+      let mut moderation_dao = ModerationDaoMock::new();
+
+      moderation_dao.mock_get_moderation_queue(Ok(vec![item.clone()]));
+
+      let moderation_dao: Box<dyn ModerationDao + Send + Sync> = Box::new(moderation_dao);
+
+      let result = read_moderation_queue(
+          &user_with_role(Role::User),
+          ModerationQueueQuery::default(),
+          Pagination::default(),
+          moderation_dao.as_ref(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let result = read_moderation_queue(
+          &user_with_role(Role::Moderator),
+          ModerationQueueQuery::default(),
+          Pagination::default(),
+          moderation_dao.as_ref(),
+      )
+      .await;
+
+      assert_eq!(result.unwrap(), vec![item]);
+  }
+
+  #[tokio::test]
+  async fn moderate_post_should_warn_the_author_and_record_the_action() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut answers_dao = AnswersDaoMock::new();
+      let mut notifications_dao = NotificationsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::from_u128(0x111))))));
+      notifications_dao.mock_notify_user(Ok(()));
+      moderation_dao.mock_record_moderation_action(Ok(moderation_action_detail(ModerationActionKind::Warn)));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(notifications_dao);
+      let moderation_dao: Box<dyn ModerationDao + Send + Sync> = Box::new(moderation_dao);
+
+      let events = EventBus::default();
+      let mut published = events.subscribe();
+
+      let result = moderate_post(
+          moderation_action(ModerationActionKind::Warn, Some(Uuid::from_u128(0x456))),
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          notifications_dao.as_ref(),
+          moderation_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &events,
+      )
+      .await;
+
+      assert_eq!(result.unwrap(), moderation_action_detail(ModerationActionKind::Warn));
+      assert!(published.try_recv().is_err());
+  }
+
+  #[tokio::test]
+  async fn moderate_post_should_only_allow_moderators_with_a_reason() {
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(NotificationsDaoMock::new());
+      let moderation_dao: Box<dyn ModerationDao + Send + Sync> = Box::new(ModerationDaoMock::new());
+
+      let result = moderate_post(
+          moderation_action(ModerationActionKind::Delete, None),
+          &user_with_role(Role::User),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          notifications_dao.as_ref(),
+          moderation_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+
+      let mut action = moderation_action(ModerationActionKind::Delete, None);
+      action.reason = " ".to_owned();
+
+      let result = moderate_post(
+          action,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          notifications_dao.as_ref(),
+          moderation_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn moderate_post_should_require_content_to_edit_an_answer() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut answers_dao = AnswersDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::from_u128(0x111))))));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(NotificationsDaoMock::new());
+      let moderation_dao: Box<dyn ModerationDao + Send + Sync> = Box::new(ModerationDaoMock::new());
+
+      let result = moderate_post(
+          moderation_action(ModerationActionKind::Edit, Some(Uuid::from_u128(0x456))),
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          notifications_dao.as_ref(),
+          moderation_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &EventBus::default(),
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
+
+  #[tokio::test]
+  async fn moderate_post_should_publish_the_changes_to_questions() {
+      let mut questions_dao = QuestionsDaoMock::new();
+      let mut moderation_dao = ModerationDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(Some(question_with_status(QuestionStatus::Open))));
+      questions_dao.mock_delete_question(Ok(Some(QuestionDeletion {
+          question_uuid: Uuid::from_u128(0x123),
+          answers: 0,
+          votes: 0,
+          followers: 0,
+          pending_tags: 0,
+      })));
+      moderation_dao.mock_record_moderation_action(Ok(ModerationActionDetail {
+          answer_uuid: None,
+          ..moderation_action_detail(ModerationActionKind::Delete)
+      }));
+
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(NotificationsDaoMock::new());
+      let moderation_dao: Box<dyn ModerationDao + Send + Sync> = Box::new(moderation_dao);
+      let events = EventBus::default();
+      let mut published = events.subscribe();
+
+      let result = moderate_post(
+          moderation_action(ModerationActionKind::Delete, None),
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          notifications_dao.as_ref(),
+          moderation_dao.as_ref(),
+          &AuditDaoMock::new(),
+          &events,
+      )
+      .await;
+
+      assert!(result.is_ok());
+      assert_eq!(
+          published.try_recv().unwrap().event,
+          DomainEvent::QuestionDeleted {
+              question_uuid: Uuid::from_u128(0x123).to_string(),
+          }
+      );
+  }
+
+  #[tokio::test]
+  async fn close_question_should_record_an_audit_entry() {
+      let close = CloseQuestion {
+          status: QuestionStatus::Locked,
+          reason: "heated discussion".to_owned(),
       };
 
       let mut questions_dao = QuestionsDaoMock::new();
 
-      questions_dao.mock_create_question(Ok(question_detail.clone()));
+      questions_dao.mock_update_question_status(Ok(Some(question_with_status(QuestionStatus::Locked))));
 
       let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let audit_dao = AuditDaoMock::new();
 
-      let result = create_question(question, questions_dao.as_ref()).await;
+      close_question(
+          "123".to_owned(),
+          close,
+          &user_with_role(Role::Moderator),
+          questions_dao.as_ref(),
+          &audit_dao,
+          &EventBus::default(),
+      )
+      .await
+      .unwrap();
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), question_detail);
+      assert_eq!(
+          audit_dao.entries(),
+          vec![AuditEntry {
+              actor_uuid: Uuid::from_u128(0x789),
+              action: AuditAction::CloseQuestion,
+              target_type: AuditTarget::Question,
+              target_uuid: Some("00000000-0000-0000-0000-000000000123".to_owned()),
+              payload: json!({ "status": "locked", "reason": "heated discussion" }),
+          }]
+      );
   }
 
   #[tokio::test]
-  async fn create_question_should_return_error() {
-      let question = Question {
-          title: "test title".to_owned(),
-          description: "test description".to_owned(),
+  async fn read_audit_log_should_only_allow_admins_with_a_valid_since() {
+      let audit_dao = AuditDaoMock::new();
+      let query = AuditQuery {
+          since: Some("2024-01-31T12:00:00Z".to_owned()),
       };
 
-      let mut questions_dao = QuestionsDaoMock::new();
+      let result = read_audit_log(&user_with_role(Role::Moderator), query, Pagination::default(), &audit_dao).await;
 
-      questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
 
-      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let query = AuditQuery {
+          since: Some("yesterday".to_owned()),
+      };
 
-      let result = create_question(question, questions_dao.as_ref()).await;
+      let result = read_audit_log(&user_with_role(Role::Admin), query, Pagination::default(), &audit_dao).await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
       );
   }
 
   #[tokio::test]
-  async fn read_questions_should_return_questions() {
-      let question_detail = QuestionDetail {
-          question_uuid: "123".to_owned(),
+  async fn read_audit_log_should_return_records() {
+      let record = AuditRecord {
+          audit_uuid: Uuid::from_u128(0x999),
+          actor_uuid: Some(Uuid::from_u128(0x789)),
+          action: AuditAction::RestoreAnswer,
+          target_type: AuditTarget::Answer,
+          target_uuid: Some("456".to_owned()),
+          payload: json!({}),
+          created_at: datetime!(2024-01-31 12:00:00.0 UTC),
+      };
+
+      let mut audit_dao = AuditDaoMock::new();
+
+      audit_dao.mock_get_audit_log(Ok(vec![record.clone()]));
+
+      let query = AuditQuery {
+          since: Some("2024-01-31T13:00:00+01:00".to_owned()),
+      };
+
+      let result = read_audit_log(&user_with_role(Role::Admin), query, Pagination::default(), &audit_dao).await;
+
+      assert_eq!(result.unwrap(), vec![record]);
+  }
+
+  #[test]
+  fn normalized_tags_should_lowercase_deduplicate_and_validate() {
+      let tags = vec!["Rust".to_owned(), " async-std ".to_owned(), "rust".to_owned(), "C++".to_owned()];
+
+      assert_eq!(normalized_tags(tags).unwrap(), vec!["rust", "async-std", "c++"]);
+
+      for invalid in ["", "-rust", "rust lang", "rust_lang", &"a".repeat(Question::MAX_TAG_CHARS + 1)] {
+          assert!(normalized_tags(vec![invalid.to_owned()]).is_err(), "{} should be rejected", invalid);
+      }
+
+      let too_many = (0..=Question::MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+
+      assert!(normalized_tags(too_many).is_err());
+  }
+
+  #[tokio::test]
+  async fn create_question_should_reject_invalid_tags() {
+      let question = Question {
           title: "test title".to_owned(),
           description: "test description".to_owned(),
-          created_at: "now".to_owned(),
+          tags: vec!["50%".to_owned()],
+          ..Default::default()
       };
 
-      let mut questions_dao = QuestionsDaoMock::new();
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+      let result = create_question(question, None, questions_dao.as_ref(), boards_dao.as_ref(), &TagsDaoMock::new(), &ModerationDaoMock::new(), &NoSpamChecker, [203, 0, 113, 1].into(), &UsersDaoMock::new(), NewTagPolicy::default(), PostingQuotaPolicy::default(), 0, &ContentPolicy::default(), &EventBus::default()).await;
 
-      questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+  }
 
-      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+  #[tokio::test]
+  async fn create_question_should_reject_blocked_terms() {
+      let question = Question {
+          title: "Free money".to_owned(),
+          description: "Get free money with this crate".to_owned(),
+          ..Default::default()
+      };
+      let content_policy = ContentPolicy::parse("free money", ContentPolicyMode::Reject).unwrap();
 
-      let result = read_questions(questions_dao.as_ref()).await;
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let boards_dao: Box<dyn BoardsDao + Send + Sync> = Box::new(BoardsDaoMock::new());
+      let result = create_question(question, None, questions_dao.as_ref(), boards_dao.as_ref(), &TagsDaoMock::new(), &ModerationDaoMock::new(), &NoSpamChecker, [203, 0, 113, 1].into(), &UsersDaoMock::new(), NewTagPolicy::default(), PostingQuotaPolicy::default(), 0, &content_policy, &EventBus::default()).await;
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), vec![question_detail]);
+      assert_eq!(
+          result.unwrap_err(),
+          HandlerError::ContentPolicyViolation(ContentPolicyViolation {
+              message: "The post contains terms that are not allowed on this forum.".to_owned(),
+              terms: vec!["free money".to_owned()],
+          })
+      );
   }
 
   #[tokio::test]
-  async fn read_questions_should_return_error() {
-      let mut questions_dao = QuestionsDaoMock::new();
+  async fn update_answer_should_reject_blocked_terms() {
+      let mut answers_dao = AnswersDaoMock::new();
 
-      questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+      answers_dao.mock_get_answer(Ok(Some(answer_by(Some(Uuid::from_u128(0x789))))));
 
-      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let content_policy = ContentPolicy::parse("re:(?i)v[i1]agra", ContentPolicyMode::Reject).unwrap();
 
-      let result = read_questions(questions_dao.as_ref()).await;
+      let result = update_answer(
+          "456".to_owned(),
+          AnswerUpdate { content: "Cheap V1agra here".to_owned() },
+          &user_with_role(Role::User),
+          answers_dao.as_ref(),
+          &content_policy,
+          &EventBus::default(),
+      )
+      .await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+              == std::mem::discriminant(&HandlerError::ContentPolicyViolation(ContentPolicyViolation {
+                  message: "".to_owned(),
+                  terms: vec![],
+              }))
       );
   }
 
   #[tokio::test]
-  async fn delete_question_should_succeed() {
-      let question_id = QuestionId {
-          question_uuid: "123".to_owned(),
-      };
+  async fn suggest_tags_should_cache_results_and_rate_limit_clients() {
+      let usage = vec![TagUsage {
+          name: "rust".to_owned(),
+          questions: 3,
+      }];
+      let mut tags_dao = TagsDaoMock::new();
 
-      let mut questions_dao = QuestionsDaoMock::new();
+      // Only the first request reaches the database.
+      tags_dao.mock_get_tag_suggestions(Ok(usage.clone()));
 
-      questions_dao.mock_delete_question(Ok(()));
+      let tags_dao: Box<dyn TagsDao + Send + Sync> = Box::new(tags_dao);
+      let cache = TtlCache::new(60, 10);
+      let limiter = RateLimiter::new(3, 60);
+      let client: IpAddr = "203.0.113.1".parse().unwrap();
+      let query = |q: &str| TagSuggestQuery { q: q.to_owned() };
 
-      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let result = suggest_tags(query("Ru"), client, 1000, tags_dao.as_ref(), &cache, &limiter).await;
+      assert_eq!(result.unwrap(), usage);
 
-      let result = delete_question(question_id, questions_dao.as_ref()).await;
+      let result = suggest_tags(query("ru"), client, 1001, tags_dao.as_ref(), &cache, &limiter).await;
+      assert_eq!(result.unwrap(), usage);
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), ());
+      let result = suggest_tags(query("r_%"), client, 1002, tags_dao.as_ref(), &cache, &limiter).await;
+      assert_eq!(result.unwrap(), Vec::new());
+
+      let result = suggest_tags(query("ru"), client, 1003, tags_dao.as_ref(), &cache, &limiter).await;
+      assert_eq!(result.unwrap_err(), HandlerError::TooManyRequests("Too many tag suggestion requests.".to_owned(), 57));
   }
 
   #[tokio::test]
-  async fn delete_question_should_return_error() {
-      let question_id = QuestionId {
-          question_uuid: "123".to_owned(),
+  async fn read_tag_stats_should_cache_stats_per_tag() {
+      let stats = TagStats {
+          name: "rust".to_owned(),
+          questions: 4,
+          unanswered_rate: 0.25,
+          questions_per_week: vec![TagWeek {
+              week: "2024-01-29".to_owned(),
+              questions: 4,
+          }],
+          top_answerers: vec![TagAnswerer {
+              user_uuid: Uuid::from_u128(0x789),
+              username: "test user".to_owned(),
+              answers: 3,
+          }],
       };
+      let mut tags_dao = TagsDaoMock::new();
 
-      let mut questions_dao = QuestionsDaoMock::new();
+      // Only the first request reaches the database.
+      tags_dao.mock_get_tag_stats(Ok(Some(stats.clone())));
 
-      questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+      let tags_dao: Box<dyn TagsDao + Send + Sync> = Box::new(tags_dao);
+      let cache = TtlCache::new(60, 10);
 
-      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let result = read_tag_stats("Rust".to_owned(), 1000, tags_dao.as_ref(), &cache).await;
+      assert_eq!(result.unwrap(), stats);
 
-      let result = delete_question(question_id, questions_dao.as_ref()).await;
+      let result = read_tag_stats("rust".to_owned(), 1059, tags_dao.as_ref(), &cache).await;
+      assert_eq!(result.unwrap(), stats);
 
-      assert!(result.is_err());
+      let result = read_tag_stats("50%".to_owned(), 1059, tags_dao.as_ref(), &cache).await;
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
       );
   }
 
   #[tokio::test]
-  async fn create_answer_should_return_answer() {
-      let answer = Answer {
-          question_uuid: "123".to_owned(),
-          content: "test content".to_owned(),
-      };
+  async fn read_tag_stats_should_return_not_found_for_unused_tags() {
+      let mut tags_dao = TagsDaoMock::new();
 
-      let answer_detail = AnswerDetail {
-          answer_uuid: "456".to_owned(),
-          question_uuid: answer.question_uuid.clone(),
-          content: answer.content.clone(),
-          created_at: "now".to_owned(),
-      };
+      tags_dao.mock_get_tag_stats(Ok(None));
 
-      let mut answers_dao = AnswersDaoMock::new();
+      let tags_dao: Box<dyn TagsDao + Send + Sync> = Box::new(tags_dao);
 
-      answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+      let result = read_tag_stats("cobol".to_owned(), 1000, tags_dao.as_ref(), &TtlCache::new(60, 10)).await;
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
+      );
+  }
 
-      let result = create_answer(answer, answers_dao.as_ref()).await;
+  const PNG: &[u8] = b"\x89PNG\r\n\x1a\n rest of the image";
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), answer_detail);
+  fn attachment() -> AttachmentDetail {
+      AttachmentDetail {
+          attachment_uuid: Uuid::from_u128(0x321),
+          storage_key: "key".to_owned(),
+          filename: "diagram.png".to_owned(),
+          content_type: "image/png".to_owned(),
+          size_bytes: PNG.len() as i64,
+          uploader_uuid: Some(Uuid::from_u128(0x789)),
+          question_uuid: None,
+          answer_uuid: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          download: None,
+      }
+  }
+
+  fn upload(content: &[u8]) -> Upload {
+      Upload {
+          filename: "diagram.png".to_owned(),
+          content: content.to_vec(),
+          question_uuid: None,
+          answer_uuid: None,
+      }
   }
 
   #[tokio::test]
-  async fn create_answer_should_return_bad_request_error() {
-      let answer = Answer {
-          question_uuid: "123".to_owned(),
-          content: "test content".to_owned(),
-      };
+  async fn create_upload_should_store_file_and_return_download_link() {
+      let mut attachments_dao = AttachmentsDaoMock::new();
 
-      let mut answers_dao = AnswersDaoMock::new();
+      attachments_dao.mock_create_attachment(Ok(attachment()));
 
-      answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+      let attachments_dao: Box<dyn AttachmentsDao + Send + Sync> = Box::new(attachments_dao);
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+      let object_store = ObjectStoreMock::new();
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let result = create_upload(
+          upload(PNG),
+          &user_with_role(Role::User),
+          attachments_dao.as_ref(),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          &object_store,
+          &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+          1_000,
+      )
+      .await
+      .unwrap();
+
+      assert_eq!(object_store.len(), 1);
+      assert!(result.download.unwrap().url.starts_with("/api/v1/uploads/00000000-0000-0000-0000-000000000321?expires=4600&key_id=1&signature="));
+  }
 
-      let result = create_answer(answer, answers_dao.as_ref()).await;
+  fn profile(avatar_key: Option<&str>) -> UserProfile {
+      UserProfile {
+          user_uuid: Uuid::from_u128(0x789),
+          username: "test user".to_owned(),
+          display_name: None,
+          created_at: OffsetDateTime::UNIX_EPOCH,
+          avatar_urls: crate::avatars::avatar_urls(avatar_key),
+      }
+  }
+
+  #[tokio::test]
+  async fn update_avatar_should_store_every_size_and_remove_previous_avatar() {
+      let previous_key = new_avatar_key();
+      let object_store = ObjectStoreMock::new();
+
+      for size in AVATAR_SIZES {
+          object_store.put(&avatar_object_key(&previous_key, size), "image/png", vec![1]).await.unwrap();
+      }
+
+      let mut users_dao = UsersDaoMock::new();
+
+      users_dao.mock_set_avatar_key(Ok(Some(previous_key.clone())));
+      users_dao.mock_get_user_profile(Ok(Some(profile(Some("avatar-new")))));
+
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
+
+      let mut content = Vec::new();
+      image::DynamicImage::new_rgb8(40, 30)
+          .write_to(&mut std::io::Cursor::new(&mut content), image::ImageFormat::Png)
+          .unwrap();
+
+      let result = update_avatar(content, &user_with_role(Role::User), users_dao.as_ref(), &object_store)
+          .await
+          .unwrap();
+
+      assert_eq!(result.avatar_urls.len(), AVATAR_SIZES.len());
+      assert_eq!(object_store.len(), AVATAR_SIZES.len());
+      assert_eq!(object_store.get(&avatar_object_key(&previous_key, 64)).await.unwrap(), None);
+  }
+
+  #[tokio::test]
+  async fn update_avatar_should_reject_non_images() {
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(UsersDaoMock::new());
+      let object_store = ObjectStoreMock::new();
+
+      for content in [&b""[..], b"plain text", PNG] {
+          let result = update_avatar(content.to_vec(), &user_with_role(Role::User), users_dao.as_ref(), &object_store).await;
+
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+
+      assert_eq!(object_store.len(), 0);
+  }
+
+  #[tokio::test]
+  async fn read_avatar_should_not_serve_other_objects() {
+      let object_store = ObjectStoreMock::new();
+      object_store.put("0123456789abcdef0123456789abcdef", "image/png", vec![1]).await.unwrap();
+
+      let result = read_avatar("0123456789abcdef0123456789abcdef".to_owned(), &object_store).await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
       );
   }
 
   #[tokio::test]
-  async fn create_answer_should_return_internal_error() {
-      let answer = Answer {
-          question_uuid: "123".to_owned(),
-          content: "test content".to_owned(),
-      };
-
-      let mut answers_dao = AnswersDaoMock::new();
+  async fn read_user_profile_should_return_not_found_for_unknown_users() {
+      let mut users_dao = UsersDaoMock::new();
 
-      answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::new(
-          std::io::ErrorKind::Other,
-          "oh no!",
-      )))));
+      users_dao.mock_get_user_profile(Ok(None));
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      let users_dao: Box<dyn UsersDao + Send + Sync> = Box::new(users_dao);
 
-      let result = create_answer(answer, answers_dao.as_ref()).await;
+      let result = read_user_profile("789".to_owned(), users_dao.as_ref()).await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
       );
   }
 
   #[tokio::test]
-  async fn read_answers_should_return_answers() {
-      let answer_detail = AnswerDetail {
-          answer_uuid: "456".to_owned(),
-          question_uuid: "123".to_owned(),
-          content: "test content".to_owned(),
-          created_at: "now".to_owned(),
-      };
+  async fn create_upload_should_reject_unsupported_files() {
+      let attachments_dao: Box<dyn AttachmentsDao + Send + Sync> = Box::new(AttachmentsDaoMock::new());
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(QuestionsDaoMock::new());
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+      let object_store = ObjectStoreMock::new();
 
-      let question_id = QuestionId {
-          question_uuid: "123".to_owned(),
-      };
+      for content in [&b""[..], b"MZ\x90\x00 executable"] {
+          let result = create_upload(
+              upload(content),
+              &user_with_role(Role::User),
+              attachments_dao.as_ref(),
+              questions_dao.as_ref(),
+              answers_dao.as_ref(),
+              &object_store,
+              &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+              1_000,
+          )
+          .await;
 
-      let mut answers_dao = AnswersDaoMock::new();
+          assert!(
+              std::mem::discriminant(&result.unwrap_err())
+                  == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
 
-      answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+      assert_eq!(object_store.len(), 0);
+  }
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+  #[tokio::test]
+  async fn create_upload_should_forbid_attaching_to_other_users_posts() {
+      let mut question = question_with_status(QuestionStatus::Open);
+      question.author_uuid = Some(Uuid::max());
 
-      let result = read_answers(question_id, answers_dao.as_ref()).await;
+      let mut questions_dao = QuestionsDaoMock::new();
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), vec![answer_detail]);
+      questions_dao.mock_get_question(Ok(Some(question)));
+
+      let attachments_dao: Box<dyn AttachmentsDao + Send + Sync> = Box::new(AttachmentsDaoMock::new());
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(AnswersDaoMock::new());
+      let object_store = ObjectStoreMock::new();
+
+      let result = create_upload(
+          Upload {
+              question_uuid: Some(Uuid::from_u128(0x123)),
+              ..upload(PNG)
+          },
+          &user_with_role(Role::User),
+          attachments_dao.as_ref(),
+          questions_dao.as_ref(),
+          answers_dao.as_ref(),
+          &object_store,
+          &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+          1_000,
+      )
+      .await;
+
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+      assert_eq!(object_store.len(), 0);
   }
 
   #[tokio::test]
-  async fn read_answers_should_return_error() {
-      let question_id = QuestionId {
-          question_uuid: "123".to_owned(),
-      };
+  async fn read_upload_should_reject_invalid_signature() {
+      let attachments_dao: Box<dyn AttachmentsDao + Send + Sync> = Box::new(AttachmentsDaoMock::new());
+      let url_signer = UrlSigner::parse(SIGNING_KEYS).unwrap();
+      let signature = url_signer.sign("/uploads/other", 2_000);
 
-      let mut answers_dao = AnswersDaoMock::new();
+      let result = read_upload(
+          "321".to_owned(),
+          signature,
+          attachments_dao.as_ref(),
+          &ObjectStoreMock::new(),
+          &url_signer,
+          1_000,
+      )
+      .await;
 
-      answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+      assert!(
+          std::mem::discriminant(&result.unwrap_err())
+              == std::mem::discriminant(&HandlerError::Forbidden("".to_owned()))
+      );
+  }
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+  #[tokio::test]
+  async fn create_attachment_signed_url_should_hide_attachments_of_unreadable_questions() {
+      let mut attachments_dao = AttachmentsDaoMock::new();
 
-      let result = read_answers(question_id, answers_dao.as_ref()).await;
+      attachments_dao.mock_get_attachment(Ok(Some(AttachmentDetail {
+          uploader_uuid: Some(Uuid::max()),
+          question_uuid: Some(Uuid::from_u128(0x123)),
+          ..attachment()
+      })));
+
+      let mut questions_dao = QuestionsDaoMock::new();
+
+      questions_dao.mock_get_question(Ok(None));
+
+      let attachments_dao: Box<dyn AttachmentsDao + Send + Sync> = Box::new(attachments_dao);
+      let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+      let result = create_attachment_signed_url(
+          "321".to_owned(),
+          SignedUrlRequest { expires_in_seconds: 60 },
+          &user_with_role(Role::User),
+          attachments_dao.as_ref(),
+          questions_dao.as_ref(),
+          &UrlSigner::parse(SIGNING_KEYS).unwrap(),
+          1_000,
+      )
+      .await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
-              == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+              == std::mem::discriminant(&HandlerError::NotFound("".to_owned()))
       );
   }
 
+  #[test]
+  fn sanitize_filename_should_keep_header_safe_base_name() {
+      assert_eq!(sanitize_filename("C:\\Users\\me\\report \"final\".pdf"), "report _final_.pdf");
+      assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+      assert_eq!(sanitize_filename("résumé.txt"), "r_sum_.txt");
+      assert_eq!(sanitize_filename(".."), "upload");
+  }
+
   #[tokio::test]
-  async fn delete_answer_should_succeed() {
-      let answer_id = AnswerId {
-          answer_uuid: "123".to_owned(),
+  async fn read_my_content_should_reject_oversized_limit_and_return_content() {
+      let mut content_dao = ContentDaoMock::new();
+      let user = user_with_role(Role::User);
+
+      let result = read_my_content(&user, Pagination { offset: 0, limit: Pagination::MAX_LIMIT + 1 }, &content_dao).await;
+
+      assert_eq!(
+          std::mem::discriminant(&result.unwrap_err()),
+          std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+      );
+
+      let content = MyContent {
+          drafts: vec![DraftDetail {
+              draft_uuid: Uuid::from_u128(0x123),
+              title: "title".to_owned(),
+              description: "description".to_owned(),
+              updated_at: OffsetDateTime::UNIX_EPOCH,
+          }],
+          ..Default::default()
       };
 
-      let mut answers_dao = AnswersDaoMock::new();
+      content_dao.mock_get_user_content(Ok(content.clone()));
 
-      answers_dao.mock_delete_answer(Ok(()));
+      let result = read_my_content(&user, Pagination::default(), &content_dao).await;
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      assert_eq!(result.unwrap(), content);
+  }
+
+  #[tokio::test]
+  async fn read_notification_settings_should_fill_in_default_channels() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+      let muted = NotificationChannels { in_app: false, email: false, webhook: false };
 
-      let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+      notifications_dao.mock_get_notification_settings(Ok(NotificationSettings {
+          kinds: vec![NotificationKindSettings { kind: NotificationKind::Mention, channels: muted }],
+          ..Default::default()
+      }));
 
-      assert!(result.is_ok());
-      assert_eq!(result.unwrap(), ());
+      let result = read_notification_settings(&user_with_role(Role::User), &notifications_dao).await.unwrap();
+
+      let channels: Vec<_> = result.kinds.iter().map(|settings| (settings.kind, settings.channels)).collect();
+
+      assert_eq!(
+          channels,
+          vec![
+              (NotificationKind::NewAnswer, NotificationChannels::default()),
+              (NotificationKind::Mention, muted),
+              (NotificationKind::AcceptSuggestion, NotificationChannels::default()),
+              (NotificationKind::ModerationWarning, NotificationChannels::default()),
+          ]
+      );
   }
 
   #[tokio::test]
-  async fn delete_answer_should_return_error() {
-      let answer_id = AnswerId {
-          answer_uuid: "123".to_owned(),
+  async fn update_notification_settings_should_validate_and_return_a_new_webhook_secret() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+      let webhook_only = NotificationChannels { in_app: false, email: false, webhook: true };
+      let kind_settings = |kind| NotificationKindSettings { kind, channels: webhook_only };
+
+      let invalid_settings = [
+          NotificationSettings {
+              kinds: vec![kind_settings(NotificationKind::Mention), kind_settings(NotificationKind::Mention)],
+              webhook_url: Some("https://hooks.example.com".to_owned()),
+              webhook_secret: None,
+          },
+          NotificationSettings {
+              kinds: vec![kind_settings(NotificationKind::Mention)],
+              webhook_url: None,
+              webhook_secret: None,
+          },
+          NotificationSettings {
+              kinds: vec![],
+              webhook_url: Some("hooks.example.com".to_owned()),
+              webhook_secret: None,
+          },
+      ];
+
+      for settings in invalid_settings {
+          let result = update_notification_settings(&user_with_role(Role::User), settings, &notifications_dao).await;
+
+          assert_eq!(
+              std::mem::discriminant(&result.unwrap_err()),
+              std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+          );
+      }
+
+      let settings = NotificationSettings {
+          kinds: vec![kind_settings(NotificationKind::Mention)],
+          webhook_url: Some("https://hooks.example.com".to_owned()),
+          webhook_secret: None,
       };
 
-      let mut answers_dao = AnswersDaoMock::new();
+      notifications_dao.mock_set_notification_settings(Ok(Some("secret".to_owned())));
+      notifications_dao.mock_get_notification_settings(Ok(settings.clone()));
 
-      answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
+      let result = update_notification_settings(&user_with_role(Role::User), settings, &notifications_dao).await.unwrap();
 
-      let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+      assert_eq!(result.kinds.len(), NotificationKind::ALL.len());
+      assert_eq!(result.webhook_url.as_deref(), Some("https://hooks.example.com"));
+      assert_eq!(result.webhook_secret.as_deref(), Some("secret"));
+  }
+
+  #[tokio::test]
+  async fn update_accept_suggestion_settings_should_return_new_settings() {
+      let mut notifications_dao = NotificationsDaoMock::new();
 
-      let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+      notifications_dao.mock_set_accept_suggestions_enabled(Ok(()));
+
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(notifications_dao);
+
+      let settings = AcceptSuggestionSettings { enabled: false };
+
+      let result = update_accept_suggestion_settings(&user_with_role(Role::User), settings, notifications_dao.as_ref()).await;
+
+      assert_eq!(result, Ok(settings));
+  }
+
+  #[tokio::test]
+  async fn update_accept_suggestion_settings_should_fail_when_dao_fails() {
+      let mut notifications_dao = NotificationsDaoMock::new();
+
+      notifications_dao.mock_set_accept_suggestions_enabled(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+      let notifications_dao: Box<dyn NotificationsDao + Send + Sync> = Box::new(notifications_dao);
+
+      let result = update_accept_suggestion_settings(
+          &user_with_role(Role::User),
+          AcceptSuggestionSettings { enabled: true },
+          notifications_dao.as_ref(),
+      )
+      .await;
 
-      assert!(result.is_err());
       assert!(
           std::mem::discriminant(&result.unwrap_err())
               == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
       );
   }
+
+  #[tokio::test]
+  async fn read_readiness_should_report_pending_migrations_but_stay_ready() {
+      let mut health_dao = HealthDaoMock::new();
+
+      health_dao.mock_ping(Ok(()));
+      health_dao.mock_get_pending_migrations(Ok(Some(vec![20240101000000])));
+
+      let health_dao: Box<dyn HealthDao + Send + Sync> = Box::new(health_dao);
+
+      let result = read_readiness(health_dao.as_ref()).await;
+
+      assert_eq!(
+          result,
+          Readiness {
+              status: HealthStatus::Ok,
+              database_reachable: true,
+              pending_migrations: Some(vec![20240101000000]),
+          }
+      );
+  }
+
+  #[tokio::test]
+  async fn read_readiness_should_be_unavailable_when_the_database_is_unreachable() {
+      let mut health_dao = HealthDaoMock::new();
+
+      health_dao.mock_ping(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+      let health_dao: Box<dyn HealthDao + Send + Sync> = Box::new(health_dao);
+
+      let result = read_readiness(health_dao.as_ref()).await;
+
+      assert_eq!(
+          result,
+          Readiness {
+              status: HealthStatus::Unavailable,
+              database_reachable: false,
+              pending_migrations: None,
+          }
+      );
+  }
 }
@@ -1,75 +1,4056 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
 
-use crate::{models::*, AppState};
+use async_trait::async_trait;
+use axum::{
+    body::{Body, Bytes},
+    extract::{
+        multipart::MultipartError,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, FromRequestParts, Multipart, Path, Query, State,
+    },
+    http::{header, header::AUTHORIZATION, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::json;
+use time::OffsetDateTime;
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{
+    cache::QuestionCache,
+    etag,
+    events::{spawn_subscriber, EventBus},
+    export,
+    http_cache,
+    feeds,
+    json_api::{self, ResponseFormat},
+    live::{LiveEvent, LiveUpdates},
+    markdown::Render,
+    metrics,
+    models::*,
+    persistance::{answers_dao::AnswersDao, notifications_dao::NotificationsDao, webhooks_dao::WebhooksDao},
+    problem::{problem, status_problem},
+    redaction::redact,
+    scim::{ScimConfig, ScimListQuery, ScimListResponse, ScimPatch, ScimUser},
+    signing::{unix_timestamp, UrlSignature},
+    sitemap,
+    tenancy,
+    AppState,
+};
 
 mod handlers_inner;
 
+/// Sitemap URLs read ahead of the client.
+const SITEMAP_BUFFER_URLS: usize = 1_000;
+
+/// Rows of an export read ahead of the client.
+const EXPORT_BUFFER_ROWS: usize = 1_000;
+
+/// Problem types of the errors clients are expected to handle; each adds members to the problem.
+const TAG_RULE_VIOLATION_TYPE: &str = "urn:forum:problem:tag-rule-violation";
+const CONTENT_POLICY_VIOLATION_TYPE: &str = "urn:forum:problem:content-policy-violation";
+const CONFLICT_TYPE: &str = "urn:forum:problem:conflict";
+const VALIDATION_FAILED_TYPE: &str = "urn:forum:problem:validation-failed";
+const POSTING_QUOTA_EXCEEDED_TYPE: &str = "urn:forum:problem:posting-quota-exceeded";
+
 impl IntoResponse for handlers_inner::HandlerError {
     fn into_response(self) -> axum::response::Response {
+        // Error details may echo user input or database messages, so scrub PII before responding.
         match self {
-            handlers_inner::HandlerError::BadRequest(msg) => {
-                (StatusCode::BAD_REQUEST, msg).into_response()
-            }
+            handlers_inner::HandlerError::BadRequest(msg) => status_problem(StatusCode::BAD_REQUEST, redact(&msg)),
+            handlers_inner::HandlerError::Unauthorized(msg) => status_problem(StatusCode::UNAUTHORIZED, redact(&msg)),
+            handlers_inner::HandlerError::Forbidden(msg) => status_problem(StatusCode::FORBIDDEN, redact(&msg)),
+            handlers_inner::HandlerError::NotFound(msg) => status_problem(StatusCode::NOT_FOUND, redact(&msg)),
+            handlers_inner::HandlerError::Conflict(msg) => status_problem(StatusCode::CONFLICT, redact(&msg)),
             handlers_inner::HandlerError::InternalError(msg) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
+                status_problem(StatusCode::INTERNAL_SERVER_ERROR, redact(&msg))
+            }
+            handlers_inner::HandlerError::ServiceUnavailable(msg) => {
+                status_problem(StatusCode::SERVICE_UNAVAILABLE, redact(&msg))
             }
+            handlers_inner::HandlerError::GatewayTimeout(msg) => status_problem(StatusCode::GATEWAY_TIMEOUT, redact(&msg)),
+            handlers_inner::HandlerError::TooManyRequests(msg, retry_after) => (
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                status_problem(StatusCode::TOO_MANY_REQUESTS, redact(&msg)),
+            )
+                .into_response(),
+            handlers_inner::HandlerError::TagRuleViolation(violation) => problem(
+                StatusCode::BAD_REQUEST,
+                TAG_RULE_VIOLATION_TYPE,
+                "Tags break the board's rules",
+                violation.message,
+                members([("code", json!(violation.code)), ("tags", json!(violation.tags))]),
+            ),
+            handlers_inner::HandlerError::ContentPolicyViolation(violation) => problem(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                CONTENT_POLICY_VIOLATION_TYPE,
+                "Blocked by the content policy",
+                violation.message,
+                members([("terms", json!(violation.terms))]),
+            ),
+            handlers_inner::HandlerError::ConflictDetail(conflict) => problem(
+                StatusCode::CONFLICT,
+                CONFLICT_TYPE,
+                "Conflicts with the current state",
+                conflict.message,
+                members([("code", json!(conflict.code))]),
+            ),
+            handlers_inner::HandlerError::ValidationFailed(errors) => problem(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                VALIDATION_FAILED_TYPE,
+                "Invalid request fields",
+                format!("{} field(s) of the request body are invalid.", errors.len()),
+                members([("errors", json!(errors))]),
+            ),
+            handlers_inner::HandlerError::PostingQuotaExceeded(quota) => (
+                [(header::RETRY_AFTER, quota.retry_after_seconds.to_string())],
+                problem(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    POSTING_QUOTA_EXCEEDED_TYPE,
+                    "Over the posting quota",
+                    quota.message,
+                    members([
+                        ("kind", json!(quota.kind)),
+                        ("limit", json!(quota.limit)),
+                        ("window_seconds", json!(quota.window_seconds)),
+                        ("reputation", json!(quota.reputation)),
+                        ("next_limit_reputation", json!(quota.next_limit_reputation)),
+                        ("retry_after_seconds", json!(quota.retry_after_seconds)),
+                    ]),
+                ),
+            )
+                .into_response(),
         }
     }
 }
 
+fn members<const N: usize>(members: [(&str, serde_json::Value); N]) -> serde_json::Map<String, serde_json::Value> {
+    members.into_iter().map(|(name, value)| (name.to_owned(), value)).collect()
+}
+
+/// The user authenticated by the `Authorization: Bearer <api token>` header.
+pub struct AuthUser(pub UserDetail);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = handlers_inner::HandlerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let api_token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| handlers_inner::HandlerError::Unauthorized("Missing bearer token.".to_owned()))?;
+
+        handlers_inner::authenticate(api_token, state.users_dao.as_ref())
+            .await
+            .map(AuthUser)
+    }
+}
+
+/// An identity provider authenticated by the SCIM provisioning token.
+pub struct ScimClient(Arc<ScimConfig>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ScimClient {
+    type Rejection = handlers_inner::HandlerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+
+        handlers_inner::authenticate_scim(token, state.scim.as_deref())?;
+
+        Ok(ScimClient(state.scim.clone().expect("SCIM is configured once authenticated")))
+    }
+}
+
+fn viewer_of(user: &Option<AuthUser>) -> Viewer {
+    user.as_ref().map(|AuthUser(user)| user).into()
+}
+
+// ---- Event subscribers ----
+
+/// Subscribes the side effects of new and edited posts to `events`; call once at startup. The
+/// subscribers stop once `events` is dropped and they handled the events published until then.
+pub fn spawn_event_subscribers(
+    events: &EventBus,
+    notifications_dao: Arc<dyn NotificationsDao + Send + Sync>,
+    live_updates: Arc<LiveUpdates>,
+    webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    forum_url: String,
+    question_cache: Arc<QuestionCache>,
+) -> Vec<JoinHandle<()>> {
+    vec![
+        spawn_subscriber("notifications", events.subscribe(), move |event| {
+            let notifications_dao = notifications_dao.clone();
+            async move { handlers_inner::notify_on_event(event, notifications_dao.as_ref()).await }
+        }),
+        spawn_subscriber("live updates", events.subscribe(), move |event| {
+            handlers_inner::publish_live_update(event, live_updates.as_ref());
+            async {}
+        }),
+        spawn_subscriber("webhooks", events.subscribe(), move |event| {
+            let webhooks_dao = webhooks_dao.clone();
+            let forum_url = forum_url.clone();
+            async move { handlers_inner::queue_webhook_deliveries(event, &forum_url, webhooks_dao.as_ref()).await }
+        }),
+        spawn_subscriber("question cache", events.subscribe(), move |event| {
+            let question_cache = question_cache.clone();
+            async move { handlers_inner::invalidate_cached_questions(event, question_cache.as_ref()).await }
+        }),
+    ]
+}
+
 // ---- CRUD for Questions ----
 
+#[utoipa::path(
+    post,
+    path = "/question",
+    tag = "questions",
+    request_body = Question,
+    responses(
+        (status = 200, description = "The new question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 429, description = "Over the author's posting quota", body = PostingQuotaExceeded),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
 pub async fn create_question(
-    State(AppState { questions_dao, .. }): State<AppState>,
+    State(AppState { questions_dao, boards_dao, tags_dao, moderation_dao, users_dao, spam_checker, new_tag_policy, posting_quota_policy, content_policy, events, .. }): State<AppState>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    author: Option<AuthUser>,
     Json(question): Json<Question>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_question(question, questions_dao.as_ref())
-        .await
-        .map(Json)
+    handlers_inner::create_question(
+        question,
+        author.as_ref().map(|AuthUser(user)| user),
+        questions_dao.as_ref(),
+        boards_dao.as_ref(),
+        tags_dao.as_ref(),
+        moderation_dao.as_ref(),
+        spam_checker.as_ref(),
+        client_addr.ip(),
+        users_dao.as_ref(),
+        new_tag_policy,
+        posting_quota_policy,
+        unix_timestamp(),
+        content_policy.as_ref(),
+        events.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/question/{uuid}",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+        FormatQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the question the client has"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified date of the question the client has; ignored with If-None-Match"),
+    ),
+    responses(
+        (status = 200, description = "The question, with its weak ETag and Last-Modified date", body = QuestionDetail),
+        (status = 304, description = "Not modified since the ETag in If-None-Match, or the date in If-Modified-Since"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_question(
+    State(AppState { questions_dao, link_previews_dao, view_counter, question_cache, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    response_format: ResponseFormat,
+    Path(question_uuid): Path<String>,
+    Query(query): Query<FormatQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_question(
+        question_uuid,
+        viewer_of(&viewer),
+        client_addr.ip(),
+        questions_dao.as_ref(),
+        link_previews_dao.as_ref(),
+        view_counter.as_ref(),
+        question_cache.as_ref(),
+    )
+    .await
+    .map(|question| {
+        let etag = response_format.etag(etag::questions_etag(std::slice::from_ref(&question), query.format));
+
+        conditional_response(
+            &headers,
+            etag,
+            Some(question.updated_at),
+            response_format.respond(question.render(query.format), json_api::document),
+        )
+    })
 }
 
+#[utoipa::path(
+    get,
+    path = "/questions",
+    tag = "questions",
+    params(
+        LanguageQuery,
+        FormatQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the list the client has"),
+    ),
+    responses(
+        (status = 200, description = "Questions the viewer can read, with their weak ETag", body = [QuestionDetail]),
+        (status = 304, description = "Not modified since the ETag in If-None-Match"),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
 pub async fn read_questions(
+    State(AppState { questions_dao, question_cache, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    response_format: ResponseFormat,
+    Query(language): Query<LanguageQuery>,
+    Query(query): Query<FormatQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_questions(viewer_of(&viewer), language, questions_dao.as_ref(), question_cache.as_ref())
+        .await
+        .map(|questions| {
+            let etag = response_format.etag(etag::questions_etag(&questions, query.format));
+            let body = response_format.respond(questions.render(query.format), |questions, uri| {
+                json_api::collection_document(questions, uri)
+            });
+
+            conditional_response(&headers, etag, None, body)
+        })
+}
+
+/// `304 Not Modified` when `If-None-Match` has `etag`, or, without `If-None-Match`, when
+/// `If-Modified-Since` is no older than `last_modified`, so that polling clients do not download
+/// what they have already; otherwise `body`. Both carry the ETag, and the `Last-Modified` date if
+/// any. Lists have none, as a question leaving one does not change the newest date.
+fn conditional_response(
+    headers: &HeaderMap,
+    etag: String,
+    last_modified: Option<OffsetDateTime>,
+    body: Response,
+) -> Response {
+    let if_none_match: Vec<_> = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    let not_modified = if if_none_match.is_empty() {
+        last_modified.is_some_and(|last_modified| {
+            headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|since| http_cache::not_modified_since(since, last_modified))
+        })
+    } else {
+        if_none_match.iter().any(|value| etag::if_none_match(value, &etag))
+    };
+
+    let mut response = if not_modified {
+        (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+    } else {
+        ([(header::ETAG, etag)], body).into_response()
+    };
+
+    if let Some(last_modified) = last_modified.and_then(|at| HeaderValue::from_str(&http_cache::http_date(at)).ok()) {
+        response.headers_mut().insert(header::LAST_MODIFIED, last_modified);
+    }
+
+    response
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/batch",
+    tag = "questions",
+    params(
+        FormatQuery,
+    ),
+    request_body = QuestionBatch,
+    responses(
+        (status = 200, description = "The requested questions the viewer can read", body = [QuestionDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_questions_batch(
     State(AppState { questions_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Query(query): Query<FormatQuery>,
+    Json(batch): Json<QuestionBatch>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::read_questions(questions_dao.as_ref())
+    handlers_inner::read_questions_batch(batch, viewer_of(&viewer), questions_dao.as_ref())
         .await
-        .map(Json)
+        .map(|questions| Json(questions.render(query.format)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/question/{uuid}/full",
+    tag = "questions",
+    params(
+        ("uuid" = Uuid, Path, description = "Question UUID"),
+        AnswersQuery,
+    ),
+    responses(
+        (status = 200, description = "The question with its tags, and its answers with their vote scores", body = QuestionFull),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_question_full(
+    State(AppState { questions_dao, answers_dao, link_previews_dao, view_counter, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    response_format: ResponseFormat,
+    Path(question_uuid): Path<Uuid>,
+    Query(query): Query<AnswersQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_question_full(
+        QuestionId { question_uuid },
+        query.sort,
+        viewer_of(&viewer),
+        client_addr.ip(),
+        questions_dao.as_ref(),
+        answers_dao.as_ref(),
+        link_previews_dao.as_ref(),
+        view_counter.as_ref(),
+    )
+    .await
+    .map(|full| response_format.respond(full.render(query.format), json_api::full_document))
 }
 
+#[utoipa::path(
+    get,
+    path = "/question/{uuid}/stats",
+    tag = "questions",
+    params(
+        ("uuid" = Uuid, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "Views, unique viewers, answers and last activity of the question", body = QuestionStats),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Question not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_question_stats(
+    State(AppState { questions_dao, stats_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Path(question_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_question_stats(
+        QuestionId { question_uuid },
+        viewer_of(&viewer),
+        questions_dao.as_ref(),
+        stats_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/question/{uuid}",
+    tag = "questions",
+    params(
+        ("uuid" = Uuid, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "The question was deleted, with what was removed along with it", body = QuestionDeletion),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Question not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
 pub async fn delete_question(
-    State(AppState { questions_dao, .. }): State<AppState>,
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_question(QuestionId { question_uuid }, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Legacy form of `DELETE /question/{uuid}`, only served with `LEGACY_BODY_ROUTES`.
+#[utoipa::path(
+    delete,
+    path = "/question",
+    tag = "questions",
+    request_body = QuestionId,
+    responses(
+        (status = 200, description = "The question was deleted, with what was removed along with it", body = QuestionDeletion),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Question not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_question_by_body(
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
     Json(question_uuid): Json<QuestionId>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::delete_question(question_uuid, questions_dao.as_ref())
+    handlers_inner::delete_question(question_uuid, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/bulk-delete",
+    tag = "questions",
+    request_body = BulkDelete,
+    responses(
+        (status = 200, description = "The outcome for each question", body = [BulkDeleteResult]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn bulk_delete_questions(
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<BulkDelete>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::bulk_delete_questions(request, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/question/{uuid}",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = Question,
+    responses(
+        (status = 200, description = "The edited question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_question(
+    State(AppState { questions_dao, boards_dao, tags_dao, users_dao, new_tag_policy, content_policy, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(question): Json<Question>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_question(
+        question_uuid,
+        question,
+        &user,
+        questions_dao.as_ref(),
+        boards_dao.as_ref(),
+        tags_dao.as_ref(),
+        users_dao.as_ref(),
+        new_tag_policy,
+        content_policy.as_ref(),
+        events.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/question/{uuid}/revisions",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "Revisions of the question, newest first", body = [QuestionRevision]),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_question_revisions(
+    State(AppState { questions_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Path(question_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_question_revisions(question_uuid, viewer_of(&viewer), questions_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/signed-url",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = SignedUrlRequest,
+    responses(
+        (status = 200, description = "A link to the question that opens without authentication until it expires", body = SignedUrl),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_question_signed_url(
+    State(AppState { questions_dao, url_signer, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(request): Json<SignedUrlRequest>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_question_signed_url(
+        question_uuid,
+        request,
+        &user,
+        questions_dao.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/shared/question/{uuid}",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+        UrlSignature,
+        FormatQuery,
+    ),
+    responses(
+        (status = 200, description = "The question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_shared_question(
+    State(AppState { questions_dao, link_previews_dao, url_signer, .. }): State<AppState>,
+    Path(question_uuid): Path<String>,
+    Query(signature): Query<UrlSignature>,
+    Query(query): Query<FormatQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_shared_question(
+        question_uuid,
+        signature,
+        questions_dao.as_ref(),
+        link_previews_dao.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(|question| Json(question.render(query.format)))
+}
+
+// ---- Uploads ----
+
+/// Multipart form with a `file` part and an optional `question_uuid` or `answer_uuid` field.
+#[utoipa::path(
+    post,
+    path = "/uploads",
+    tag = "uploads",
+    request_body(content_type = "multipart/form-data", description = "A `file` part and an optional `question_uuid` or `answer_uuid` field"),
+    responses(
+        (status = 201, description = "The stored attachment", body = AttachmentDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_upload(
+    State(AppState { attachments_dao, questions_dao, answers_dao, object_store, url_signer, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, handlers_inner::HandlerError> {
+    let upload = upload_from_form(multipart).await?;
+
+    handlers_inner::create_upload(
+        upload,
+        &user,
+        attachments_dao.as_ref(),
+        questions_dao.as_ref(),
+        answers_dao.as_ref(),
+        object_store.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(|attachment| (StatusCode::CREATED, Json(attachment)))
+}
+
+async fn upload_from_form(mut multipart: Multipart) -> Result<Upload, handlers_inner::HandlerError> {
+    let invalid = |err: MultipartError| handlers_inner::HandlerError::BadRequest(format!("Invalid upload form: {}", err));
+    let uuid = |text: String| {
+        Uuid::parse_str(&text).map_err(|err| handlers_inner::HandlerError::BadRequest(format!("Invalid upload form: {}", err)))
+    };
+
+    let mut file = None;
+    let mut question_uuid = None;
+    let mut answer_uuid = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+        match field.name() {
+            Some("file") => {
+                let filename = field.file_name().unwrap_or_default().to_owned();
+                file = Some((filename, field.bytes().await.map_err(invalid)?.to_vec()));
+            }
+            Some("question_uuid") => question_uuid = Some(uuid(field.text().await.map_err(invalid)?)?),
+            Some("answer_uuid") => answer_uuid = Some(uuid(field.text().await.map_err(invalid)?)?),
+            _ => {}
+        }
+    }
+
+    let (filename, content) =
+        file.ok_or_else(|| handlers_inner::HandlerError::BadRequest("The upload form has no file part.".to_owned()))?;
+
+    Ok(Upload {
+        filename,
+        content,
+        question_uuid,
+        answer_uuid,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/uploads/{uuid}/signed-url",
+    tag = "uploads",
+    params(
+        ("uuid" = String, Path, description = "Attachment UUID"),
+    ),
+    request_body = SignedUrlRequest,
+    responses(
+        (status = 200, description = "A link to the file that opens without authentication until it expires", body = SignedUrl),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_attachment_signed_url(
+    State(AppState { attachments_dao, questions_dao, url_signer, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(attachment_uuid): Path<String>,
+    Json(request): Json<SignedUrlRequest>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_attachment_signed_url(
+        attachment_uuid,
+        request,
+        &user,
+        attachments_dao.as_ref(),
+        questions_dao.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(Json)
+}
+
+/// Serves the file as a download so browsers never render uploaded content in the API's origin.
+#[utoipa::path(
+    get,
+    path = "/uploads/{uuid}",
+    tag = "uploads",
+    params(
+        ("uuid" = String, Path, description = "Attachment UUID"),
+        UrlSignature,
+    ),
+    responses(
+        (status = 200, description = "The file, as a download with its own content type", content_type = "application/octet-stream"),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_upload(
+    State(AppState { attachments_dao, object_store, url_signer, .. }): State<AppState>,
+    Path(attachment_uuid): Path<String>,
+    Query(signature): Query<UrlSignature>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_upload(
+        attachment_uuid,
+        signature,
+        attachments_dao.as_ref(),
+        object_store.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(|(attachment, content)| {
+        (
+            [
+                (header::CONTENT_TYPE, attachment.content_type),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", attachment.filename)),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_owned()),
+            ],
+            content,
+        )
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/follow",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "The user follows the question"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn follow_question(
+    State(AppState { questions_dao, follows_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::follow_question(question_uuid, &user, questions_dao.as_ref(), follows_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/question/{uuid}/follow",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "The user no longer follows the question"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn unfollow_question(
+    State(AppState { follows_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::unfollow_question(question_uuid, &user, follows_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/restore",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "The restored question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn restore_question(
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::restore_question(question_uuid, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/accept",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = AcceptAnswer,
+    responses(
+        (status = 204, description = "The answer is accepted, or none is with a null `answer_uuid`"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn accept_answer(
+    State(AppState { questions_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(accept): Json<AcceptAnswer>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::accept_answer(question_uuid, accept, &user, questions_dao.as_ref(), events.as_ref())
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/close",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = CloseQuestion,
+    responses(
+        (status = 200, description = "The closed question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn close_question(
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(close): Json<CloseQuestion>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::close_question(question_uuid, close, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/reopen",
+    tag = "questions",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = ReopenQuestion,
+    responses(
+        (status = 200, description = "The reopened question", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn reopen_question(
+    State(AppState { questions_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(reopen): Json<ReopenQuestion>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::reopen_question(question_uuid, reopen, &user, questions_dao.as_ref(), audit_dao.as_ref(), events.as_ref())
         .await
         .map(Json)
 }
 
 // ---- CRUD for Answers ----
 
+#[utoipa::path(
+    post,
+    path = "/answer",
+    tag = "answers",
+    request_body = Answer,
+    responses(
+        (status = 200, description = "The new answer", body = AnswerDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 429, description = "Over the author's posting quota", body = PostingQuotaExceeded),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
 pub async fn create_answer(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    State(AppState { answers_dao, questions_dao, flags_dao, moderation_dao, users_dao, necro_post_policy, similar_answer_policy, posting_quota_policy, spam_checker, content_policy, events, .. }): State<AppState>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    author: Option<AuthUser>,
     Json(answer): Json<Answer>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_answer(answer, answers_dao.as_ref())
+    handlers_inner::create_answer(
+        answer,
+        author.as_ref().map(|AuthUser(user)| user),
+        answers_dao.as_ref(),
+        questions_dao.as_ref(),
+        flags_dao.as_ref(),
+        necro_post_policy,
+        similar_answer_policy,
+        unix_timestamp(),
+        moderation_dao.as_ref(),
+        users_dao.as_ref(),
+        posting_quota_policy,
+        spam_checker.as_ref(),
+        client_addr.ip(),
+        content_policy.as_ref(),
+        events.as_ref(),
+    )
         .await
         .map(Json)
 }
 
+#[utoipa::path(
+    get,
+    path = "/questions/{uuid}/answers",
+    tag = "answers",
+    params(
+        ("uuid" = Uuid, Path, description = "Question UUID"),
+        AnswersQuery,
+    ),
+    responses(
+        (status = 200, description = "Answers to the question the viewer can read", body = [AnswerDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_question_answers(
+    State(AppState { answers_dao, link_previews_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    response_format: ResponseFormat,
+    Path(question_uuid): Path<Uuid>,
+    Query(query): Query<AnswersQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_answers(
+        QuestionId { question_uuid },
+        query.sort,
+        viewer_of(&viewer),
+        answers_dao.as_ref(),
+        link_previews_dao.as_ref(),
+    )
+        .await
+        .map(|answers| {
+            response_format.respond(answers.render(query.format), |answers, uri| {
+                json_api::collection_document(answers, uri)
+            })
+        })
+}
+
+/// Legacy form of `GET /questions/{uuid}/answers`, only served with `LEGACY_BODY_ROUTES`.
+#[utoipa::path(
+    get,
+    path = "/answers",
+    tag = "answers",
+    params(
+        AnswersQuery,
+    ),
+    request_body = QuestionId,
+    responses(
+        (status = 200, description = "Answers to the question the viewer can read", body = [AnswerDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
 pub async fn read_answers(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    State(AppState { answers_dao, link_previews_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Query(query): Query<AnswersQuery>,
     Json(question_uuid): Json<QuestionId>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::read_answers(question_uuid, answers_dao.as_ref())
+    handlers_inner::read_answers(
+        question_uuid,
+        query.sort,
+        viewer_of(&viewer),
+        answers_dao.as_ref(),
+        link_previews_dao.as_ref(),
+    )
         .await
-        .map(Json)
+        .map(|answers| Json(answers.render(query.format)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/answer/{uuid}",
+    tag = "answers",
+    params(
+        ("uuid" = Uuid, Path, description = "Answer UUID"),
+    ),
+    responses(
+        (status = 200, description = "The answer was deleted"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Answer not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
 pub async fn delete_answer(
-    State(AppState { answers_dao, .. }): State<AppState>,
+    State(AppState { answers_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(answer_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_answer(AnswerId { answer_uuid }, &user, answers_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Legacy form of `DELETE /answer/{uuid}`, only served with `LEGACY_BODY_ROUTES`.
+#[utoipa::path(
+    delete,
+    path = "/answer",
+    tag = "answers",
+    request_body = AnswerId,
+    responses(
+        (status = 200, description = "The answer was deleted"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Answer not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_answer_by_body(
+    State(AppState { answers_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
     Json(answer_uuid): Json<AnswerId>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::delete_answer(answer_uuid, answers_dao.as_ref())
+    handlers_inner::delete_answer(answer_uuid, &user, answers_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/answers/bulk-delete",
+    tag = "answers",
+    request_body = BulkDelete,
+    responses(
+        (status = 200, description = "The outcome for each answer", body = [BulkDeleteResult]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn bulk_delete_answers(
+    State(AppState { answers_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<BulkDelete>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::bulk_delete_answers(request, &user, answers_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/answer/{uuid}",
+    tag = "answers",
+    params(
+        ("uuid" = String, Path, description = "Answer UUID"),
+    ),
+    request_body = AnswerUpdate,
+    responses(
+        (status = 200, description = "The edited answer", body = AnswerDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_answer(
+    State(AppState { answers_dao, content_policy, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(answer_uuid): Path<String>,
+    Json(update): Json<AnswerUpdate>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_answer(answer_uuid, update, &user, answers_dao.as_ref(), content_policy.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Upgrades to a WebSocket that pushes the answers posted or edited on one question. The stream
+/// is send-only; the connection ends when the client closes it.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "live",
+    params(
+        LiveQuery,
+    ),
+    responses(
+        (status = 101, description = "WebSocket whose text messages are `LiveMessage` JSON"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn live_updates(
+    State(AppState { questions_dao, answers_dao, live_updates, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Query(query): Query<LiveQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let question_uuid = query.question_uuid.to_string();
+    let viewer = viewer_of(&viewer);
+    let events = handlers_inner::subscribe_live_updates(query, viewer.clone(), questions_dao.as_ref(), live_updates.as_ref()).await?;
+
+    Ok::<_, handlers_inner::HandlerError>(
+        ws.on_upgrade(move |socket| forward_live_updates(socket, events, question_uuid.to_string(), viewer, answers_dao)),
+    )
+}
+
+async fn forward_live_updates(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<LiveEvent>,
+    question_uuid: String,
+    viewer: Viewer,
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+) {
+    loop {
+        let message = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    handlers_inner::live_message(question_uuid.clone(), event, viewer.clone(), answers_dao.as_ref()).await
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => Some(LiveMessage::Lagged { skipped }),
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            // Pings are answered by the socket itself; anything else from the client is ignored.
+            received = socket.recv() => match received {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => None,
+            },
+        };
+
+        let Some(message) = message else {
+            continue;
+        };
+
+        let text = match serde_json::to_string(&message) {
+            Ok(text) => text,
+            Err(err) => {
+                error!("Error to serialize live update: {}", err);
+                continue;
+            }
+        };
+
+        if socket.send(Message::Text(text)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Same messages as `GET /ws`, for clients that only need to listen.
+#[utoipa::path(
+    get,
+    path = "/questions/{uuid}/events",
+    tag = "live",
+    params(
+        ("uuid" = Uuid, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent events whose data is `LiveMessage` JSON", content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn question_events(
+    State(AppState { questions_dao, answers_dao, live_updates, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Path(question_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let viewer = viewer_of(&viewer);
+    let query = LiveQuery { question_uuid };
+    let events = handlers_inner::subscribe_live_updates(query, viewer.clone(), questions_dao.as_ref(), live_updates.as_ref()).await?;
+
+    Ok::<_, handlers_inner::HandlerError>(
+        Sse::new(live_update_events(events, question_uuid.to_string(), viewer, answers_dao)).keep_alive(KeepAlive::default()),
+    )
+}
+
+/// For clients that can use neither `GET /ws` nor `GET /questions/:uuid/events`.
+#[utoipa::path(
+    get,
+    path = "/questions/{uuid}/poll",
+    tag = "live",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+        LongPollQuery,
+    ),
+    responses(
+        (status = 200, description = "Messages since the cursor, or none once the wait ends", body = LivePoll),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn poll_question_events(
+    State(AppState { questions_dao, answers_dao, live_updates, long_poll_max_wait, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Path(question_uuid): Path<String>,
+    Query(query): Query<LongPollQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::poll_live_updates(
+        question_uuid,
+        query,
+        viewer_of(&viewer),
+        long_poll_max_wait,
+        questions_dao.as_ref(),
+        answers_dao.as_ref(),
+        live_updates.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+fn live_update_events(
+    events: broadcast::Receiver<LiveEvent>,
+    question_uuid: String,
+    viewer: Viewer,
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(events, move |mut events| {
+        let question_uuid = question_uuid.clone();
+        let viewer = viewer.clone();
+        let answers_dao = answers_dao.clone();
+
+        async move {
+            loop {
+                let message = match events.recv().await {
+                    Ok(event) => handlers_inner::live_message(question_uuid.clone(), event, viewer.clone(), answers_dao.as_ref()).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => Some(LiveMessage::Lagged { skipped }),
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                };
+
+                let Some(message) = message else {
+                    continue;
+                };
+
+                match Event::default().json_data(&message) {
+                    Ok(event) => return Some((Ok(event), events)),
+                    Err(err) => error!("Error to serialize live update: {}", err),
+                }
+            }
+        }
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/answer/{uuid}/revisions",
+    tag = "answers",
+    params(
+        ("uuid" = String, Path, description = "Answer UUID"),
+    ),
+    responses(
+        (status = 200, description = "Revisions of the answer, newest first", body = [AnswerRevision]),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_answer_revisions(
+    State(AppState { answers_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    Path(answer_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_answer_revisions(answer_uuid, viewer_of(&viewer), answers_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/answer/{uuid}/restore",
+    tag = "answers",
+    params(
+        ("uuid" = String, Path, description = "Answer UUID"),
+    ),
+    responses(
+        (status = 200, description = "The restored answer", body = AnswerDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn restore_answer(
+    State(AppState { answers_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(answer_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::restore_answer(answer_uuid, &user, answers_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Moderation ----
+
+#[utoipa::path(
+    post,
+    path = "/question/{uuid}/flag",
+    tag = "moderation",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = Flag,
+    responses(
+        (status = 201, description = "The new flag", body = FlagDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn flag_question(
+    State(AppState { questions_dao, flags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(question_uuid): Path<String>,
+    Json(flag): Json<Flag>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::flag_question(question_uuid, flag, &user, questions_dao.as_ref(), flags_dao.as_ref())
+        .await
+        .map(|flag| (StatusCode::CREATED, Json(flag)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/answer/{uuid}/flag",
+    tag = "moderation",
+    params(
+        ("uuid" = String, Path, description = "Answer UUID"),
+    ),
+    request_body = Flag,
+    responses(
+        (status = 201, description = "The new flag", body = FlagDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn flag_answer(
+    State(AppState { answers_dao, flags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(answer_uuid): Path<String>,
+    Json(flag): Json<Flag>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::flag_answer(answer_uuid, flag, &user, answers_dao.as_ref(), flags_dao.as_ref())
+        .await
+        .map(|flag| (StatusCode::CREATED, Json(flag)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/answer/{uuid}/vote",
+    tag = "answers",
+    params(
+        ("uuid" = String, Path, description = "Answer UUID"),
+    ),
+    request_body = AnswerVote,
+    responses(
+        (status = 200, description = "The score of the answer", body = AnswerScore),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn vote_answer(
+    State(AppState { answers_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(answer_uuid): Path<String>,
+    Json(vote): Json<AnswerVote>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::vote_answer(answer_uuid, vote, &user, answers_dao.as_ref()).await.map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/moderation/flags",
+    tag = "moderation",
+    params(
+        FlagsQuery,
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Flags with the status", body = [FlagDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_flags(
+    State(AppState { flags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<FlagsQuery>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_flags(&user, query, page, flags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/moderation/flags/{uuid}/resolve",
+    tag = "moderation",
+    params(
+        ("uuid" = String, Path, description = "Flag UUID"),
+    ),
+    request_body = ResolveFlag,
+    responses(
+        (status = 200, description = "The resolved flag", body = FlagDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn resolve_flag(
+    State(AppState { flags_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(flag_uuid): Path<String>,
+    Json(resolution): Json<ResolveFlag>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::resolve_flag(flag_uuid, resolution, &user, flags_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/moderation/queue",
+    tag = "moderation",
+    params(
+        ModerationQueueQuery,
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Posts waiting for a moderator", body = [ModerationItem]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_moderation_queue(
+    State(AppState { moderation_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<ModerationQueueQuery>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_moderation_queue(&user, query, page, moderation_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/moderation/actions",
+    tag = "moderation",
+    request_body = ModerationAction,
+    responses(
+        (status = 201, description = "The recorded action", body = ModerationActionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn moderate_post(
+    State(AppState { questions_dao, answers_dao, notifications_dao, moderation_dao, audit_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(action): Json<ModerationAction>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::moderate_post(
+        action,
+        &user,
+        questions_dao.as_ref(),
+        answers_dao.as_ref(),
+        notifications_dao.as_ref(),
+        moderation_dao.as_ref(),
+        audit_dao.as_ref(),
+        events.as_ref(),
+    )
+    .await
+    .map(|action| (StatusCode::CREATED, Json(action)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/moderation/actions",
+    tag = "moderation",
+    params(
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Moderation actions, newest first", body = [ModerationActionDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_moderation_actions(
+    State(AppState { moderation_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_moderation_actions(&user, page, moderation_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/audit",
+    tag = "admin",
+    params(
+        AuditQuery,
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Audit log entries, newest first", body = [AuditRecord]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_audit_log(
+    State(AppState { audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<AuditQuery>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_audit_log(&user, query, page, audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Tags ----
+
+#[utoipa::path(
+    get,
+    path = "/tags/suggest",
+    tag = "tags",
+    params(
+        TagSuggestQuery,
+    ),
+    responses(
+        (status = 200, description = "Tags starting with the query, most used first", body = [TagUsage]),
+        (status = 400, description = "Invalid request"),
+        (status = 429, description = "Too many requests; retry after `Retry-After` seconds"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn suggest_tags(
+    State(AppState { tags_dao, tag_suggestions, tag_suggest_limiter, .. }): State<AppState>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<TagSuggestQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::suggest_tags(
+        query,
+        client_addr.ip(),
+        unix_timestamp(),
+        tags_dao.as_ref(),
+        tag_suggestions.as_ref(),
+        tag_suggest_limiter.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/{name}/stats",
+    tag = "tags",
+    params(
+        ("name" = String, Path, description = "Tag name"),
+    ),
+    responses(
+        (status = 200, description = "Statistics of the tag", body = TagStats),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_tag_stats(
+    State(AppState { tags_dao, tag_stats, .. }): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_tag_stats(name, unix_timestamp(), tags_dao.as_ref(), tag_stats.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/feeds/questions.atom",
+    tag = "feeds",
+    responses(
+        (status = 200, description = "Atom feed of the newest questions", body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_questions_feed(
+    State(AppState { questions_dao, forum_url, .. }): State<AppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_questions_feed(None, &forum_url, unix_timestamp(), questions_dao.as_ref())
+        .await
+        .map(atom_response)
+}
+
+/// `file` is the tag followed by `.atom`, as routes cannot match part of a path segment.
+#[utoipa::path(
+    get,
+    path = "/feeds/tag/{file}",
+    tag = "feeds",
+    params(
+        ("file" = String, Path, description = "The tag followed by `.atom`"),
+    ),
+    responses(
+        (status = 200, description = "Atom feed of the newest questions of the tag", body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_tag_feed(
+    State(AppState { questions_dao, forum_url, .. }): State<AppState>,
+    Path(file): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let Some(tag) = file.strip_suffix(".atom") else {
+        return Err(handlers_inner::HandlerError::NotFound("Feed not found.".to_owned()));
+    };
+
+    handlers_inner::read_questions_feed(Some(tag.to_owned()), &forum_url, unix_timestamp(), questions_dao.as_ref())
+        .await
+        .map(atom_response)
+}
+
+fn atom_response(xml: String) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, feeds::ATOM_CONTENT_TYPE)],
+        xml,
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/sitemap.xml",
+    tag = "feeds",
+    responses(
+        (status = 200, description = "Sitemap index listing every sitemap page", body = String, content_type = "application/xml"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_sitemap_index(
+    State(AppState { questions_dao, forum_url, .. }): State<AppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_sitemap_index(&forum_url, questions_dao.as_ref())
+        .await
+        .map(|xml| {
+            (
+                [(header::CONTENT_TYPE, sitemap::XML_CONTENT_TYPE)],
+                xml,
+            )
+        })
+}
+
+/// Streams the sitemap as its questions are read, so that a page of 50,000 URLs is never held in
+/// memory. A database error aborts the response rather than ending it as a shorter sitemap.
+#[utoipa::path(
+    get,
+    path = "/sitemaps/{file}",
+    tag = "feeds",
+    params(
+        ("file" = String, Path, description = "`questions-<n>.xml`, counting from 1"),
+    ),
+    responses(
+        (status = 200, description = "Sitemap of public questions", body = String, content_type = "application/xml"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_sitemap(
+    State(AppState { questions_dao, forum_url, .. }): State<AppState>,
+    Path(file): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let page = handlers_inner::sitemap_page(&file)?;
+    let (urls, receiver) = mpsc::channel(SITEMAP_BUFFER_URLS);
+    let query = tokio::spawn(tenancy::with_tenant(tenancy::current_tenant(), async move {
+        questions_dao.stream_sitemap_urls(page, urls).await
+    }));
+
+    let elements = received(receiver, query, "sitemap")
+        .map(move |url| url.map(|url| sitemap::url_element(&forum_url, &url)));
+
+    let body = stream::once(async { Ok(sitemap::URLSET_START.to_owned()) })
+        .chain(elements)
+        .chain(stream::once(async { Ok(sitemap::URLSET_END.to_owned()) }));
+
+    Ok::<_, handlers_inner::HandlerError>((
+        [(header::CONTENT_TYPE, sitemap::XML_CONTENT_TYPE)],
+        Body::from_stream(body),
+    ))
+}
+
+/// Items sent to `receiver`, then the outcome of the `query` sending them. A database error ends
+/// the stream with an error, which aborts the response rather than ending it short.
+fn received<T: Send + 'static>(
+    receiver: mpsc::Receiver<T>,
+    query: JoinHandle<Result<(), DBError>>,
+    what: &'static str,
+) -> impl Stream<Item = Result<T, std::io::Error>> {
+    stream::unfold((receiver, Some(query)), move |(mut receiver, query)| async move {
+        if let Some(item) = receiver.recv().await {
+            return Some((Ok(item), (receiver, query)));
+        }
+
+        let failure = match query?.await {
+            Ok(Ok(())) => return None,
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => err.to_string(),
+        };
+
+        error!("Error to stream {}: {}", what, failure);
+
+        Some((Err(std::io::Error::other(failure)), (receiver, None)))
+    })
+}
+
+/// Streams every question as it is read, one JSON object per line, so that exports of any size
+/// are never held in memory. Descriptions are left as Markdown.
+#[utoipa::path(
+    get,
+    path = "/export/questions.ndjson",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Every question that is not deleted, oldest first, one per line", body = String, content_type = "application/x-ndjson"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn export_questions_ndjson(
+    State(AppState { questions_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::authorize_export(&user)?;
+
+    let (questions, receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+    let query = tokio::spawn(tenancy::with_tenant(tenancy::current_tenant(), async move {
+        questions_dao.stream_questions(ExportRange::default(), questions).await
+    }));
+
+    let lines = received(receiver, query, "questions export")
+        .map(|question| question.and_then(|question| export::ndjson_line(&question)));
+
+    Ok::<_, handlers_inner::HandlerError>((
+        [
+            (header::CONTENT_TYPE, export::NDJSON_CONTENT_TYPE),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"questions.ndjson\""),
+        ],
+        Body::from_stream(lines),
+    ))
+}
+
+/// Streams the selected columns of the questions created in the range as they are read, after a
+/// header row, so that exports of any size are never held in memory.
+#[utoipa::path(
+    get,
+    path = "/export/questions.csv",
+    tag = "admin",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Questions that are not deleted, oldest first", body = String, content_type = "text/csv"),
+        (status = 400, description = "Unknown column or invalid timestamp"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn export_questions_csv(
+    State(AppState { questions_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let (columns, range) = handlers_inner::export_selection(&user, query, export::QUESTION_COLUMNS)?;
+
+    let (questions, receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+    let query = tokio::spawn(tenancy::with_tenant(tenancy::current_tenant(), async move {
+        questions_dao.stream_questions(range, questions).await
+    }));
+
+    let mut rows = export::CsvRows::default();
+    let header_row = rows.row(&columns);
+    let body = stream::once(async { header_row }).chain(
+        received(receiver, query, "questions export")
+            .map(move |question| question.and_then(|question| rows.row(export::question_row(&question, &columns)))),
+    );
+
+    Ok::<_, handlers_inner::HandlerError>((
+        [
+            (header::CONTENT_TYPE, export::CSV_CONTENT_TYPE),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"questions.csv\""),
+        ],
+        Body::from_stream(body),
+    ))
+}
+
+/// Streams the selected columns of the answers created in the range as they are read, after a
+/// header row. Answers to deleted questions are left out.
+#[utoipa::path(
+    get,
+    path = "/export/answers.csv",
+    tag = "admin",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Answers that are not deleted, oldest first", body = String, content_type = "text/csv"),
+        (status = 400, description = "Unknown column or invalid timestamp"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn export_answers_csv(
+    State(AppState { answers_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let (columns, range) = handlers_inner::export_selection(&user, query, export::ANSWER_COLUMNS)?;
+
+    let (answers, receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+    let query = tokio::spawn(tenancy::with_tenant(tenancy::current_tenant(), async move {
+        answers_dao.stream_answers(range, answers).await
+    }));
+
+    let mut rows = export::CsvRows::default();
+    let header_row = rows.row(&columns);
+    let body = stream::once(async { header_row }).chain(
+        received(receiver, query, "answers export")
+            .map(move |answer| answer.and_then(|answer| rows.row(export::answer_row(&answer, &columns)))),
+    );
+
+    Ok::<_, handlers_inner::HandlerError>((
+        [
+            (header::CONTENT_TYPE, export::CSV_CONTENT_TYPE),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"answers.csv\""),
+        ],
+        Body::from_stream(body),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/{name}/kb-export",
+    tag = "tags",
+    params(
+        ("name" = String, Path, description = "Tag name"),
+    ),
+    responses(
+        (status = 200, description = "The answered questions of the tag", body = KbExport),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn export_knowledge_base(
+    State(AppState { tags_dao, forum_url, .. }): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::export_knowledge_base(name, &forum_url, tags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/question/{uuid}/faq",
+    tag = "faq",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    request_body = FaqSelection,
+    responses(
+        (status = 200, description = "The question is in the FAQ"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn set_faq_entry(
+    State(AppState { faq_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(uuid): Path<String>,
+    Json(selection): Json<FaqSelection>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_faq_entry(uuid, selection, &user, faq_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/question/{uuid}/faq",
+    tag = "faq",
+    params(
+        ("uuid" = String, Path, description = "Question UUID"),
+    ),
+    responses(
+        (status = 200, description = "The question is no longer in the FAQ"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn remove_faq_entry(
+    State(AppState { faq_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::remove_faq_entry(uuid, &user, faq_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/faq",
+    tag = "faq",
+    params(
+        FaqQuery,
+    ),
+    responses(
+        (status = 200, description = "The FAQ, grouped", body = [FaqGroup]),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_faq(
+    State(AppState { faq_dao, faq, .. }): State<AppState>,
+    Query(query): Query<FaqQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_faq(query, unix_timestamp(), faq_dao.as_ref(), faq.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/pending",
+    tag = "tags",
+    params(
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "New tags waiting for approval", body = [PendingTag]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_pending_tags(
+    State(AppState { tags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_pending_tags(&user, page, tags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/tags/pending/{name}/approve",
+    tag = "tags",
+    params(
+        ("name" = String, Path, description = "Tag name"),
+    ),
+    responses(
+        (status = 200, description = "The tag was approved", body = PendingTagResolution),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn approve_pending_tag(
+    State(AppState { tags_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::approve_pending_tag(name, &user, tags_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tags/pending/{name}",
+    tag = "tags",
+    params(
+        ("name" = String, Path, description = "Tag name"),
+    ),
+    responses(
+        (status = 200, description = "The tag was rejected", body = PendingTagResolution),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn reject_pending_tag(
+    State(AppState { tags_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::reject_pending_tag(name, &user, tags_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Drafts ----
+
+#[utoipa::path(
+    put,
+    path = "/drafts/question",
+    tag = "drafts",
+    request_body = QuestionDraft,
+    responses(
+        (status = 200, description = "The saved draft", body = DraftDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn save_question_draft(
+    State(AppState { drafts_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(draft): Json<QuestionDraft>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::save_question_draft(draft, &user, drafts_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/drafts/{uuid}/publish",
+    tag = "drafts",
+    params(
+        ("uuid" = String, Path, description = "Draft UUID"),
+    ),
+    responses(
+        (status = 200, description = "The question published from the draft", body = QuestionDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn publish_draft(
+    State(AppState { drafts_dao, events, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(draft_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::publish_draft(draft_uuid, &user, drafts_dao.as_ref(), events.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Boards ----
+
+#[utoipa::path(
+    post,
+    path = "/boards",
+    tag = "boards",
+    request_body = Board,
+    responses(
+        (status = 200, description = "The new board", body = BoardDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_board(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(board): Json<Board>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_board(board, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/boards/{uuid}/members",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 200, description = "Members of the board", body = [BoardMember]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_board_members(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_board_members(board_uuid, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/boards/{uuid}/members",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    request_body = BoardInvite,
+    responses(
+        (status = 200, description = "The invited member", body = BoardMember),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn invite_board_member(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+    Json(invite): Json<BoardInvite>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::invite_board_member(board_uuid, invite, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/boards/{uuid}/join",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 200, description = "The membership, pending unless the board is open", body = BoardMember),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn join_board(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::join_board(board_uuid, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/boards/{uuid}/members/{user_uuid}/approve",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+        ("user_uuid" = String, Path, description = "Member's user UUID"),
+    ),
+    responses(
+        (status = 200, description = "The approved member", body = BoardMember),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn approve_board_member(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((board_uuid, member_uuid)): Path<(String, String)>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::approve_board_member(board_uuid, member_uuid, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/boards/{uuid}/members/{user_uuid}",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+        ("user_uuid" = String, Path, description = "Member's user UUID"),
+    ),
+    responses(
+        (status = 200, description = "The member was removed"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn remove_board_member(
+    State(AppState { boards_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((board_uuid, member_uuid)): Path<(String, String)>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::remove_board_member(board_uuid, member_uuid, &user, boards_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/boards/{uuid}/tag-rules",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 200, description = "Tag rules of the board", body = BoardTagRulesDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_board_tag_rules(
+    State(AppState { tags_dao, .. }): State<AppState>,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_board_tag_rules(board_uuid, tags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/boards/{uuid}/tag-rules",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    request_body = BoardTagRules,
+    responses(
+        (status = 200, description = "The new tag rules", body = BoardTagRulesDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn set_board_tag_rules(
+    State(AppState { boards_dao, tags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+    Json(rules): Json<BoardTagRules>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_board_tag_rules(board_uuid, rules, &user, boards_dao.as_ref(), tags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/boards/{uuid}/tag-rules",
+    tag = "boards",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 200, description = "The board no longer has tag rules"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_board_tag_rules(
+    State(AppState { boards_dao, tags_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_board_tag_rules(board_uuid, &user, boards_dao.as_ref(), tags_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+// ---- Users ----
+
+/// Registration; an invitation link is this endpoint with the signed invitation as its query.
+#[utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    params(
+        ("invitation_uuid" = Option<String>, Query, description = "Invitation being accepted; with the fields below, the signed query of an invitation link"),
+        ("expires" = Option<u64>, Query, description = "Expiry of the invitation link, in seconds since the Unix epoch"),
+        ("key_id" = Option<u32>, Query, description = "Key that signed the invitation link"),
+        ("signature" = Option<String>, Query, description = "Signature of the invitation link"),
+    ),
+    request_body = User,
+    responses(
+        (status = 200, description = "The new user and their API token", body = UserCredentials),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn create_user(
+    State(AppState { users_dao, invitations_dao, url_signer, .. }): State<AppState>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    invitation: Option<Query<InvitationAcceptance>>,
+    Json(user): Json<User>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match invitation {
+        Some(Query(acceptance)) => handlers_inner::create_invited_user(
+            user,
+            client_addr.ip().to_string(),
+            acceptance,
+            users_dao.as_ref(),
+            invitations_dao.as_ref(),
+            url_signer.as_ref(),
+            unix_timestamp(),
+        )
+        .await
+        .map(Json),
+        None => handlers_inner::create_user(user, client_addr.ip().to_string(), users_dao.as_ref())
+            .await
+            .map(Json),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions",
+    tag = "users",
+    request_body = SignIn,
+    responses(
+        (status = 200, description = "The user and a new API token", body = UserCredentials),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn sign_in(
+    State(AppState { users_dao, auth_backend, .. }): State<AppState>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    Json(credentials): Json<SignIn>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::sign_in(
+        credentials,
+        client_addr.ip().to_string(),
+        auth_backend.as_deref(),
+        users_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{uuid}/questions",
+    tag = "users",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+        Pagination,
+        FormatQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the page the client has"),
+    ),
+    responses(
+        (status = 200, description = "Questions of the user the viewer can read, with their weak ETag", body = [QuestionDetail]),
+        (status = 304, description = "Not modified since the ETag in If-None-Match"),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_user_questions(
+    State(AppState { questions_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    response_format: ResponseFormat,
+    Path(user_uuid): Path<String>,
+    Query(page): Query<Pagination>,
+    Query(query): Query<FormatQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_user_questions(user_uuid, page, viewer_of(&viewer), questions_dao.as_ref())
+        .await
+        .map(|questions| {
+            let etag = response_format.etag(etag::questions_etag(&questions, query.format));
+            let body = response_format.respond(questions.render(query.format), |questions, uri| {
+                json_api::page_document(questions, uri, page)
+            });
+
+            conditional_response(&headers, etag, None, body)
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/accept-suggestions",
+    tag = "me",
+    responses(
+        (status = 200, description = "The user's settings", body = AcceptSuggestionSettings),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_accept_suggestion_settings(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_accept_suggestion_settings(&user, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/me/accept-suggestions",
+    tag = "me",
+    request_body = AcceptSuggestionSettings,
+    responses(
+        (status = 200, description = "The saved settings", body = AcceptSuggestionSettings),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_accept_suggestion_settings(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(settings): Json<AcceptSuggestionSettings>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_accept_suggestion_settings(&user, settings, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/notification-settings",
+    tag = "me",
+    responses(
+        (status = 200, description = "The user's notification settings", body = NotificationSettings),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_notification_settings(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_notification_settings(&user, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/me/notification-settings",
+    tag = "me",
+    request_body = NotificationSettings,
+    responses(
+        (status = 200, description = "The saved settings", body = NotificationSettings),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_notification_settings(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(settings): Json<NotificationSettings>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_notification_settings(&user, settings, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/digest",
+    tag = "me",
+    responses(
+        (status = 200, description = "The user's email digest settings", body = DigestSettings),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_digest_settings(
+    State(AppState { email_digests_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_digest_settings(&user, email_digests_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/me/digest",
+    tag = "me",
+    request_body = DigestSettings,
+    responses(
+        (status = 200, description = "The saved settings", body = DigestSettings),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_digest_settings(
+    State(AppState { email_digests_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(settings): Json<DigestSettings>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_digest_settings(&user, settings, email_digests_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/content",
+    tag = "me",
+    params(
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "The user's questions, answers and drafts", body = MyContent),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_my_content(
+    State(AppState { content_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_my_content(&user, page, content_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/drafts",
+    tag = "me",
+    responses(
+        (status = 200, description = "How many drafts were deleted", body = DeletedDrafts),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_my_drafts(
+    State(AppState { content_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_my_drafts(&user, content_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/subscriptions",
+    tag = "me",
+    responses(
+        (status = 200, description = "What the user was unsubscribed from", body = Unsubscribed),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn unsubscribe_from_everything(
+    State(AppState { content_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::unsubscribe_from_everything(&user, content_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    tag = "notifications",
+    params(
+        NotificationsQuery,
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "The user's notifications, newest first", body = [Notification]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_notifications(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<NotificationsQuery>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_notifications(&user, query, page, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/{id}/read",
+    tag = "notifications",
+    params(
+        ("id" = i64, Path, description = "Notification id"),
+    ),
+    responses(
+        (status = 200, description = "The notification is read"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn mark_notification_read(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::mark_notification_read(id, &user, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/read-all",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "How many notifications were unread", body = NotificationsRead),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn mark_all_notifications_read(
+    State(AppState { notifications_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::mark_all_notifications_read(&user, notifications_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{uuid}",
+    tag = "users",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 200, description = "The user's public profile", body = UserProfile),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_user_profile(
+    State(AppState { users_dao, .. }): State<AppState>,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_user_profile(user_uuid, users_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// The request body is the image itself, with any image content type.
+#[utoipa::path(
+    put,
+    path = "/users/me/avatar",
+    tag = "me",
+    request_body(content_type = "image/*", description = "The image itself"),
+    responses(
+        (status = 200, description = "The profile with its new avatar", body = UserProfile),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_avatar(
+    State(AppState { users_dao, object_store, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    content: Bytes,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_avatar(content.to_vec(), &user, users_dao.as_ref(), object_store.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Each avatar key is only ever written once, so responses can be cached indefinitely.
+#[utoipa::path(
+    get,
+    path = "/avatars/{key}",
+    tag = "users",
+    params(
+        ("key" = String, Path, description = "Avatar object key"),
+    ),
+    responses(
+        (status = 200, description = "The avatar image", content_type = "image/png"),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+)]
+pub async fn read_avatar(
+    State(AppState { object_store, .. }): State<AppState>,
+    Path(object_key): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_avatar(object_key, object_store.as_ref())
+        .await
+        .map(|content| {
+            (
+                [
+                    (header::CONTENT_TYPE, "image/png"),
+                    (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+                    (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+                ],
+                content,
+            )
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{uuid}/answers",
+    tag = "users",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+        Pagination,
+        FormatQuery,
+    ),
+    responses(
+        (status = 200, description = "Answers of the user the viewer can read", body = [AnswerDetail]),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal error"),
+    ),
+    security((), ("api_token" = [])),
+)]
+pub async fn read_user_answers(
+    State(AppState { answers_dao, .. }): State<AppState>,
+    viewer: Option<AuthUser>,
+    response_format: ResponseFormat,
+    Path(user_uuid): Path<String>,
+    Query(page): Query<Pagination>,
+    Query(query): Query<FormatQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_user_answers(user_uuid, page, viewer_of(&viewer), answers_dao.as_ref())
+        .await
+        .map(|answers| {
+            response_format.respond(answers.render(query.format), |answers, uri| {
+                json_api::page_document(answers, uri, page)
+            })
+        })
+}
+
+#[utoipa::path(
+    put,
+    path = "/moderation/users/{uuid}/shadow-ban",
+    tag = "moderation",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 200, description = "The user is shadow banned"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn shadow_ban_user(
+    State(AppState { users_dao, audit_dao, question_cache, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_shadow_ban(user_uuid, true, &user, users_dao.as_ref(), audit_dao.as_ref(), question_cache.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/moderation/users/{uuid}/shadow-ban",
+    tag = "moderation",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 200, description = "The user is no longer shadow banned"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn lift_shadow_ban(
+    State(AppState { users_dao, audit_dao, question_cache, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_shadow_ban(user_uuid, false, &user, users_dao.as_ref(), audit_dao.as_ref(), question_cache.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/users/{uuid}/erase",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 200, description = "Report of what was erased", body = ErasureReport),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn erase_user(
+    State(AppState { erasure_dao, object_store, audit_dao, backup_retention_days, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::erase_user(
+        user_uuid,
+        &user,
+        backup_retention_days,
+        erasure_dao.as_ref(),
+        object_store.as_ref(),
+        audit_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/erasure-reports",
+    tag = "admin",
+    params(
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Erasure reports, newest first", body = [ErasureReport]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_erasure_reports(
+    State(AppState { erasure_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_erasure_reports(&user, page, erasure_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Served as a download, to be filed with the request it answers.
+#[utoipa::path(
+    get,
+    path = "/admin/erasure-reports/{uuid}",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Erasure report UUID"),
+    ),
+    responses(
+        (status = 200, description = "The report, as a download", body = ErasureReport),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_erasure_report(
+    State(AppState { erasure_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(report_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_erasure_report(&user, report_uuid, erasure_dao.as_ref())
+        .await
+        .map(|report| {
+            let filename = format!("attachment; filename=\"erasure-report-{}.json\"", report.report_uuid);
+
+            ([(header::CONTENT_DISPOSITION, filename)], Json(report))
+        })
+}
+
+/// Computed on every request by aggregate queries over every post, so it is meant for dashboards
+/// refreshed every few minutes, not more.
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    tag = "admin",
+    params(AdminStatsQuery),
+    responses(
+        (status = 200, description = "Totals and activity per day", body = AdminStats),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_admin_stats(
+    State(AppState { stats_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<AdminStatsQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_admin_stats(&user, query, stats_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/retention",
+    tag = "admin",
+    responses(
+        (status = 200, description = "What the retention policy keeps and purges", body = [RetentionStats]),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_retention_stats(
+    State(AppState { retention_dao, retention_policy, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_retention_stats(&user, retention_policy, retention_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Counts are per server instance, so behind a load balancer each reports the traffic it served.
+#[utoipa::path(
+    get,
+    path = "/admin/slo",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Burn rates of every route with an SLO target", body = [SloStatus]),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_slo_status(
+    State(AppState { slo_tracker, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_slo_status(&user, slo_tracker.as_ref(), unix_timestamp()).map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/query-sampling",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Share of requests whose queries are sampled", body = QuerySampling),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_query_sampling(
+    State(AppState { query_samples_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_query_sampling(&user, query_samples_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/query-sampling",
+    tag = "admin",
+    request_body = QuerySampling,
+    responses(
+        (status = 200, description = "The saved sampling rate", body = QuerySampling),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn update_query_sampling(
+    State(AppState { query_sampler, query_samples_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(sampling): Json<QuerySampling>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::update_query_sampling(
+        &user,
+        sampling,
+        query_sampler.as_ref(),
+        query_samples_dao.as_ref(),
+        audit_dao.as_ref(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/query-samples",
+    tag = "admin",
+    params(
+        QuerySamplesQuery,
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Sampled requests with their queries, newest first", body = [QuerySample]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_query_samples(
+    State(AppState { query_samples_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<QuerySamplesQuery>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_query_samples(&user, query, page, query_samples_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Reports on the server instance that answers, so behind a load balancer each call may reach a
+/// different one.
+#[utoipa::path(
+    get,
+    path = "/admin/diagnostics",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Runtime, allocator, process and connection pool internals", body = Diagnostics),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_diagnostics(
+    State(AppState { diagnostics, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_diagnostics(&user, diagnostics.as_ref()).map(Json)
+}
+
+/// Holds the request open while sampling; the response is an SVG flame graph or a pprof protobuf.
+#[utoipa::path(
+    get,
+    path = "/admin/diagnostics/profile",
+    tag = "admin",
+    params(ProfileQuery),
+    responses(
+        (status = 200, description = "A CPU profile", content(("image/svg+xml"), ("application/octet-stream"))),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn capture_cpu_profile(
+    State(AppState { diagnostics, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Query(query): Query<ProfileQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let content_type = match query.format {
+        ProfileFormat::Flamegraph => "image/svg+xml",
+        ProfileFormat::Pprof => "application/octet-stream",
+    };
+
+    handlers_inner::capture_cpu_profile(&user, query, diagnostics.as_ref())
+        .await
+        .map(|profile| ([(header::CONTENT_TYPE, content_type)], profile))
+}
+
+// ---- SCIM provisioning ----
+
+#[utoipa::path(
+    post,
+    path = "/scim/v2/Users",
+    tag = "scim",
+    request_body = ScimUser,
+    responses(
+        (status = 201, description = "The provisioned user", body = ScimUser),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_create_user(
+    State(AppState { users_dao, .. }): State<AppState>,
+    ScimClient(config): ScimClient,
+    Json(user): Json<ScimUser>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_create_user(user, &config, users_dao.as_ref())
+        .await
+        .map(|user| (StatusCode::CREATED, Json(user)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Users/{uuid}",
+    tag = "scim",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 200, description = "The provisioned user", body = ScimUser),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_read_user(
+    State(AppState { users_dao, .. }): State<AppState>,
+    _: ScimClient,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_read_user(user_uuid, users_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Users",
+    tag = "scim",
+    params(
+        ScimListQuery,
+    ),
+    responses(
+        (status = 200, description = "Provisioned users", body = ScimListResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_read_users(
+    State(AppState { users_dao, .. }): State<AppState>,
+    _: ScimClient,
+    Query(query): Query<ScimListQuery>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_read_users(query.filter, users_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/scim/v2/Users/{uuid}",
+    tag = "scim",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    request_body = ScimUser,
+    responses(
+        (status = 200, description = "The updated user", body = ScimUser),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Conflicts with the current state"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_replace_user(
+    State(AppState { users_dao, .. }): State<AppState>,
+    ScimClient(config): ScimClient,
+    Path(user_uuid): Path<String>,
+    Json(user): Json<ScimUser>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_replace_user(user_uuid, user, &config, users_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    patch,
+    path = "/scim/v2/Users/{uuid}",
+    tag = "scim",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    request_body = ScimPatch,
+    responses(
+        (status = 200, description = "The updated user", body = ScimUser),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_patch_user(
+    State(AppState { users_dao, .. }): State<AppState>,
+    _: ScimClient,
+    Path(user_uuid): Path<String>,
+    Json(patch): Json<ScimPatch>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_patch_user(user_uuid, patch, users_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/scim/v2/Users/{uuid}",
+    tag = "scim",
+    params(
+        ("uuid" = String, Path, description = "User UUID"),
+    ),
+    responses(
+        (status = 204, description = "The user was deprovisioned"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("scim_token" = [])),
+)]
+pub async fn scim_delete_user(
+    State(AppState { users_dao, .. }): State<AppState>,
+    _: ScimClient,
+    Path(user_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::scim_delete_user(user_uuid, users_dao.as_ref())
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+}
+
+// ---- Invitations ----
+
+#[utoipa::path(
+    post,
+    path = "/invitations",
+    tag = "invitations",
+    request_body = Invitation,
+    responses(
+        (status = 200, description = "The signed invitation link", body = InvitationLink),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_invitation(
+    State(AppState { invitations_dao, audit_dao, url_signer, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(invitation): Json<Invitation>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_invitation(
+        invitation,
+        &user,
+        invitations_dao.as_ref(),
+        audit_dao.as_ref(),
+        url_signer.as_ref(),
+        unix_timestamp(),
+    )
+    .await
+    .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/invitations",
+    tag = "invitations",
+    responses(
+        (status = 200, description = "Invitations created so far", body = [InvitationDetail]),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_invitations(
+    State(AppState { invitations_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_invitations(&user, invitations_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// Liveness probe: answers as long as the server serves requests, without touching the database,
+/// so an unreachable database does not get the instance restarted.
+pub async fn read_health() -> impl IntoResponse {
+    Json(Health { status: HealthStatus::Ok })
+}
+
+/// Readiness probe: 503 while the database is unreachable, so the instance gets no traffic.
+pub async fn read_readiness(State(AppState { health_dao, .. }): State<AppState>) -> impl IntoResponse {
+    let readiness = handlers_inner::read_readiness(health_dao.as_ref()).await;
+    let status = match readiness.status {
+        HealthStatus::Ok => StatusCode::OK,
+        HealthStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(readiness))
+}
+
+/// Prometheus scrape target, like the probes left out of the rate limits, SLOs and query samples.
+pub async fn read_metrics(State(AppState { diagnostics, .. }): State<AppState>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], metrics::render(&diagnostics.pool()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/jobs",
+    tag = "admin",
+    request_body = JobRequest,
+    responses(
+        (status = 202, description = "The queued job", body = JobDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_job(
+    State(AppState { jobs_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<JobRequest>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_job(&user, request, jobs_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(|job| (StatusCode::ACCEPTED, Json(job)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/{uuid}",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Job UUID"),
+    ),
+    responses(
+        (status = 200, description = "The job and its progress", body = JobDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_job(
+    State(AppState { jobs_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(job_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_job(&user, job_uuid, jobs_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+/// The request body is the dump itself: NDJSON with a `type` on each record, or a zip of
+/// `users.json`, `questions.json` and `answers.json`. A zip of the `Posts.xml` and `Users.xml` of a
+/// Stack Exchange data dump is imported too. The whole dump is checked before the job is queued.
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "admin",
+    request_body(content_type = "application/x-ndjson", description = "The dump itself, or `application/zip` for a zipped dump"),
+    responses(
+        (status = 202, description = "The queued import job", body = JobDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 413, description = "Dump too large"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn start_import(
+    State(AppState { jobs_dao, object_store, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    headers: HeaderMap,
+    dump: Bytes,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).unwrap_or_default();
+
+    handlers_inner::start_import(&user, content_type, dump, jobs_dao.as_ref(), object_store.as_ref(), audit_dao.as_ref())
+        .await
+        .map(|job| (StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Progress is reported after each batch; the `result` of a finished import counts the records
+/// imported and those skipped as imported before.
+#[utoipa::path(
+    get,
+    path = "/import/{uuid}",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Import job UUID"),
+    ),
+    responses(
+        (status = 200, description = "The import job and its progress", body = JobDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_import(
+    State(AppState { jobs_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(job_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_import(&user, job_uuid, jobs_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/dead-letters",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Deliveries that ran out of attempts", body = [DeadLetter]),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_dead_letters(
+    State(AppState { dead_letters_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_dead_letters(&user, dead_letters_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/dead-letters/retry",
+    tag = "admin",
+    request_body = DeadLetterSelection,
+    responses(
+        (status = 200, description = "The outcome for each dead letter", body = [DeadLetterRetryResult]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn retry_dead_letters(
+    State(AppState { dead_letters_dao, digest_webhook, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(selection): Json<DeadLetterSelection>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::retry_dead_letters(
+        &user,
+        selection,
+        dead_letters_dao.as_ref(),
+        digest_webhook.as_deref(),
+        audit_dao.as_ref(),
+    )
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/dead-letters/purge",
+    tag = "admin",
+    request_body = DeadLetterSelection,
+    responses(
+        (status = 200, description = "The outcome for each dead letter", body = [BulkDeleteResult]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn purge_dead_letters(
+    State(AppState { dead_letters_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(selection): Json<DeadLetterSelection>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::purge_dead_letters(&user, selection, dead_letters_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/webhooks",
+    tag = "admin",
+    request_body = NewWebhook,
+    responses(
+        (status = 201, description = "The new webhook, with its signing secret", body = WebhookDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn create_webhook(
+    State(AppState { webhooks_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(webhook): Json<NewWebhook>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::create_webhook(&user, webhook, webhooks_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(|webhook| (StatusCode::CREATED, Json(webhook)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Registered webhooks", body = [WebhookDetail]),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_webhooks(
+    State(AppState { webhooks_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_webhooks(&user, webhooks_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/webhooks/{uuid}",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Webhook UUID"),
+    ),
+    responses(
+        (status = 204, description = "The webhook was deleted"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_webhook(
+    State(AppState { webhooks_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(webhook_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_webhook(&user, webhook_uuid, webhooks_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks/{uuid}/deliveries",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Webhook UUID"),
+        Pagination,
+    ),
+    responses(
+        (status = 200, description = "Deliveries of the webhook, newest first", body = [WebhookDelivery]),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_webhook_deliveries(
+    State(AppState { webhooks_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(webhook_uuid): Path<String>,
+    Query(page): Query<Pagination>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_webhook_deliveries(&user, webhook_uuid, page, webhooks_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/boards/{uuid}/cleanup-policy",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 200, description = "The board's cleanup policy", body = BoardCleanupPolicyDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn read_cleanup_policy(
+    State(AppState { cleanup_policies_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::read_cleanup_policy(&user, board_uuid, cleanup_policies_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/boards/{uuid}/cleanup-policy",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    request_body = BoardCleanupPolicy,
+    responses(
+        (status = 200, description = "The saved policy", body = BoardCleanupPolicyDetail),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn set_cleanup_policy(
+    State(AppState { cleanup_policies_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+    Json(policy): Json<BoardCleanupPolicy>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::set_cleanup_policy(&user, board_uuid, policy, cleanup_policies_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/boards/{uuid}/cleanup-policy",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    responses(
+        (status = 204, description = "The board no longer has a cleanup policy"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 404, description = "Not found"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn delete_cleanup_policy(
+    State(AppState { cleanup_policies_dao, audit_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::delete_cleanup_policy(&user, board_uuid, cleanup_policies_dao.as_ref(), audit_dao.as_ref())
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/boards/{uuid}/cleanup-policy/preview",
+    tag = "admin",
+    params(
+        ("uuid" = String, Path, description = "Board UUID"),
+    ),
+    request_body = BoardCleanupPolicy,
+    responses(
+        (status = 200, description = "What the policy would clean up now", body = BoardCleanup),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Not allowed for this user"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 422, description = "Unprocessable request body"),
+        (status = 500, description = "Internal error"),
+    ),
+    security(("api_token" = [])),
+)]
+pub async fn preview_cleanup(
+    State(AppState { cleanup_policies_dao, .. }): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(board_uuid): Path<String>,
+    Json(policy): Json<BoardCleanupPolicy>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    handlers_inner::preview_cleanup(&user, board_uuid, policy, cleanup_policies_dao.as_ref())
         .await
         .map(Json)
 }
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, fmt::Write, sync::Arc};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::FormatItem, macros::format_description, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+/// The IMF-fixdate of HTTP, e.g. `Sat, 17 Oct 2026 10:00:00 GMT`.
+const HTTP_DATE: &[FormatItem<'static>] =
+    format_description!("[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT");
+
+/// Responses differ by format and token, so shared caches must keep a copy for each. Tenants are
+/// told apart by host, which caches already key on.
+const VARY: &str = "accept, authorization";
+
+/// How long browsers and shared caches, such as CDNs, may reuse the responses of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CachePolicy {
+    pub max_age_seconds: u64,
+    /// For shared caches only, which can be purged, unlike browsers; `max_age_seconds` when unset.
+    #[serde(default)]
+    pub shared_max_age_seconds: Option<u64>,
+    /// How long a stale response may still be served while it is fetched again in the background.
+    #[serde(default)]
+    pub stale_while_revalidate_seconds: u64,
+}
+
+impl CachePolicy {
+    /// Responses to signed-in users may hold posts only they can see, so only their browser keeps
+    /// them, and it checks them with the server each time.
+    fn cache_control(&self, signed_in: bool) -> String {
+        if signed_in {
+            return "private, no-cache".to_owned();
+        }
+
+        let mut value = format!("public, max-age={}", self.max_age_seconds);
+
+        if let Some(shared_max_age) = self.shared_max_age_seconds {
+            let _ = write!(value, ", s-maxage={}", shared_max_age);
+        }
+
+        if self.stale_while_revalidate_seconds > 0 {
+            let _ = write!(value, ", stale-while-revalidate={}", self.stale_while_revalidate_seconds);
+        }
+
+        value
+    }
+}
+
+/// The `CachePolicy` of each public read route, keyed by method and route, e.g.
+/// `GET /api/v1/questions`. Routes without one get no caching headers.
+pub struct CachePolicies {
+    routes: HashMap<String, CachePolicy>,
+}
+
+impl CachePolicies {
+    pub fn new(routes: HashMap<String, CachePolicy>) -> Self {
+        CachePolicies { routes }
+    }
+}
+
+/// Adds `Cache-Control` and `Vary` to the successful and not-modified responses of routes with a
+/// policy. Handlers that set `Cache-Control` themselves, such as for immutable avatars, keep theirs.
+pub async fn cache_headers(State(policies): State<Arc<CachePolicies>>, request: Request, next: Next) -> Response {
+    let policy = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| policies.routes.get(&format!("{} {}", request.method(), path.as_str())))
+        .copied();
+    let signed_in = request.headers().contains_key(header::AUTHORIZATION);
+
+    let mut response = next.run(request).await;
+
+    let Some(policy) = policy else {
+        return response;
+    };
+
+    if !response.status().is_success() && response.status() != StatusCode::NOT_MODIFIED {
+        return response;
+    }
+
+    let headers = response.headers_mut();
+
+    if !headers.contains_key(header::CACHE_CONTROL) {
+        if let Ok(value) = HeaderValue::from_str(&policy.cache_control(signed_in)) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+    }
+
+    headers.append(header::VARY, HeaderValue::from_static(VARY));
+
+    response
+}
+
+/// `at` as a `Last-Modified` value, to the second.
+pub fn http_date(at: OffsetDateTime) -> String {
+    at.to_offset(UtcOffset::UTC).format(HTTP_DATE).unwrap_or_default()
+}
+
+/// Whether something last modified at `modified` is unchanged since `if_modified_since`, the value
+/// of an `If-Modified-Since` header. Dates that do not parse never match.
+pub fn not_modified_since(if_modified_since: &str, modified: OffsetDateTime) -> bool {
+    PrimitiveDateTime::parse(if_modified_since.trim(), HTTP_DATE)
+        .is_ok_and(|since| modified.unix_timestamp() <= since.assume_utc().unix_timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::get, Router};
+    use time::macros::datetime;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn policy() -> CachePolicy {
+        CachePolicy {
+            max_age_seconds: 10,
+            shared_max_age_seconds: Some(60),
+            stale_while_revalidate_seconds: 30,
+        }
+    }
+
+    #[test]
+    fn cache_control_should_be_private_for_signed_in_users() {
+        assert_eq!(policy().cache_control(false), "public, max-age=10, s-maxage=60, stale-while-revalidate=30");
+        assert_eq!(policy().cache_control(true), "private, no-cache");
+    }
+
+    #[test]
+    fn http_dates_should_round_trip_to_the_second() {
+        let modified = datetime!(2026-10-17 10:00:00.5 +02:00);
+        let date = http_date(modified);
+
+        assert_eq!(date, "Sat, 17 Oct 2026 08:00:00 GMT");
+        assert!(not_modified_since(&date, modified));
+        assert!(!not_modified_since("Sat, 17 Oct 2026 07:59:59 GMT", modified));
+        assert!(!not_modified_since("yesterday", modified));
+    }
+
+    #[tokio::test]
+    async fn cache_headers_should_only_apply_to_successful_responses_of_routes_with_a_policy() {
+        let policies = CachePolicies::new(HashMap::from([("GET /questions".to_owned(), policy())]));
+        let app = Router::new()
+            .route("/questions", get(|| async { "questions" }))
+            .route("/question", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/answers", get(|| async { "answers" }))
+            .route_layer(axum::middleware::from_fn_with_state(Arc::new(policies), cache_headers));
+        let send = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+
+        let response = send("/questions").await.unwrap();
+
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=10, s-maxage=60, stale-while-revalidate=30");
+        assert_eq!(response.headers()[header::VARY], VARY);
+        assert!(!send("/question").await.unwrap().headers().contains_key(header::CACHE_CONTROL));
+        assert!(!send("/answers").await.unwrap().headers().contains_key(header::CACHE_CONTROL));
+    }
+}
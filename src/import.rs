@@ -0,0 +1,267 @@
+use std::io::{Cursor, Read};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+use zip::{result::ZipError, ZipArchive};
+
+use crate::{
+    export::NDJSON_CONTENT_TYPE,
+    models::{FieldError, ImportRecord},
+    stack_exchange,
+    validation::Validate,
+};
+
+/// Largest dump `POST /import` takes, zipped or not.
+pub const MAX_DUMP_BYTES: usize = 256 * 1024 * 1024;
+
+/// Largest a file of a zipped dump may be once unzipped; dumps with a larger one are refused.
+const MAX_UNZIPPED_BYTES: u64 = 4 * MAX_DUMP_BYTES as u64;
+
+/// Records imported per transaction: a failed batch is rolled back whole, and progress is reported
+/// after each one.
+pub const BATCH_SIZE: usize = 500;
+
+pub const ZIP_CONTENT_TYPE: &str = "application/zip";
+
+/// Files of a zipped dump, each a JSON array of the records of one type, without their `type`.
+/// They are imported in this order, so that posts come after their authors and questions. A zip
+/// holding `Posts.xml` is read as a Stack Exchange data dump instead.
+const USERS_FILE: &str = "users.json";
+const QUESTIONS_FILE: &str = "questions.json";
+const ANSWERS_FILE: &str = "answers.json";
+
+/// The `input` of an import job: where its dump is stored, and the tenant it is imported into.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportJob {
+    pub object_key: String,
+    pub tenant: Option<Uuid>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DumpError {
+    #[error("Dumps are NDJSON ({}) or zipped JSON ({}).", NDJSON_CONTENT_TYPE, ZIP_CONTENT_TYPE)]
+    UnsupportedFormat,
+    #[error("Line {line}: {message}")]
+    Line { line: usize, message: String },
+    #[error("{file}: {message}")]
+    File { file: &'static str, message: String },
+    #[error("{file}, record {record}: {message}")]
+    Record { file: &'static str, record: usize, message: String },
+    #[error("Invalid zip archive: {0}")]
+    Zip(String),
+    #[error("{file} is larger than the {} bytes a file may have unzipped.", MAX_UNZIPPED_BYTES)]
+    TooLarge { file: String },
+}
+
+/// Every record of `dump`, in the order they are to be imported, after checking that each is
+/// valid. NDJSON dumps are imported line by line, so records must come after those they refer to.
+pub fn read_dump(content_type: &str, dump: &[u8]) -> Result<Vec<ImportRecord>, DumpError> {
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+
+    match essence.as_str() {
+        NDJSON_CONTENT_TYPE | "application/ndjson" => read_ndjson(dump),
+        ZIP_CONTENT_TYPE => read_zip(dump),
+        _ => Err(DumpError::UnsupportedFormat),
+    }
+}
+
+/// `records` as an NDJSON dump, as which dumps are stored until they are imported.
+pub fn to_ndjson(records: &[ImportRecord]) -> Vec<u8> {
+    let mut dump = Vec::new();
+
+    for record in records {
+        // Records of ours always serialize.
+        if serde_json::to_writer(&mut dump, record).is_ok() {
+            dump.push(b'\n');
+        }
+    }
+
+    dump
+}
+
+fn read_ndjson(dump: &[u8]) -> Result<Vec<ImportRecord>, DumpError> {
+    let mut records = Vec::new();
+
+    for (index, line) in dump.split(|byte| *byte == b'\n').enumerate() {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_slice::<ImportRecord>(line)
+            .map_err(|err| err.to_string())
+            .and_then(|record| record.validate().map(|_| record).map_err(invalid))
+            .map_err(|message| DumpError::Line { line: index + 1, message })?;
+
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn read_zip(dump: &[u8]) -> Result<Vec<ImportRecord>, DumpError> {
+    let mut archive = ZipArchive::new(Cursor::new(dump)).map_err(|err| DumpError::Zip(err.to_string()))?;
+
+    if let Some(posts) = read_zip_entry(&mut archive, stack_exchange::POSTS_FILE)? {
+        let users = read_zip_entry(&mut archive, stack_exchange::USERS_FILE)?;
+
+        return stack_exchange::read_dump(users.as_deref(), &posts);
+    }
+
+    let mut records = read_zip_file(&mut archive, USERS_FILE, ImportRecord::User)?;
+
+    records.extend(read_zip_file(&mut archive, QUESTIONS_FILE, ImportRecord::Question)?);
+    records.extend(read_zip_file(&mut archive, ANSWERS_FILE, ImportRecord::Answer)?);
+
+    Ok(records)
+}
+
+/// The records of `file`, if the archive has it, as `ImportRecord`s of the type `record` makes.
+fn read_zip_file<T: DeserializeOwned>(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    file: &'static str,
+    record: fn(T) -> ImportRecord,
+) -> Result<Vec<ImportRecord>, DumpError> {
+    let Some(json) = read_zip_entry(archive, file)? else {
+        return Ok(Vec::new());
+    };
+
+    let items: Vec<T> = serde_json::from_slice(&json).map_err(|err| DumpError::File { file, message: err.to_string() })?;
+
+    items
+        .into_iter()
+        .map(record)
+        .enumerate()
+        .map(|(index, record)| {
+            record.validate().map_err(|errors| DumpError::Record {
+                file,
+                record: index + 1,
+                message: invalid(errors),
+            })?;
+
+            Ok(record)
+        })
+        .collect()
+}
+
+/// The unzipped content of `file`, if the archive has it.
+fn read_zip_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, file: &str) -> Result<Option<Vec<u8>>, DumpError> {
+    let entry = match archive.by_name(file) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(DumpError::Zip(err.to_string())),
+    };
+
+    read_to_limit(entry, file, MAX_UNZIPPED_BYTES).map(Some)
+}
+
+/// All of `reader`, unless it holds more than `max_bytes`. One byte past the limit is read, so that
+/// a larger file is refused rather than cut short.
+fn read_to_limit(reader: impl Read, file: &str, max_bytes: u64) -> Result<Vec<u8>, DumpError> {
+    let mut content = Vec::new();
+    reader
+        .take(max_bytes + 1)
+        .read_to_end(&mut content)
+        .map_err(|err| DumpError::Zip(err.to_string()))?;
+
+    if content.len() as u64 > max_bytes {
+        return Err(DumpError::TooLarge { file: file.to_owned() });
+    }
+
+    Ok(content)
+}
+
+/// `errors` as one message, such as "username must not be empty.".
+pub fn invalid(errors: Vec<FieldError>) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{} {}", error.field, error.message.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use time::macros::datetime;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+    use crate::models::{ImportedQuestion, ImportedUser};
+
+    fn user() -> ImportedUser {
+        ImportedUser {
+            user_uuid: Uuid::from_u128(0x1),
+            username: "ada".to_owned(),
+            created_at: datetime!(2015-03-01 10:00:00 UTC),
+        }
+    }
+
+    fn question() -> ImportedQuestion {
+        ImportedQuestion {
+            question_uuid: Uuid::from_u128(0x2),
+            title: "How do lifetimes work?".to_owned(),
+            description: "In detail.".to_owned(),
+            author_uuid: Some(Uuid::from_u128(0x1)),
+            tags: vec!["rust".to_owned()],
+            created_at: datetime!(2015-03-02 10:00:00 UTC),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn ndjson_dumps_should_round_trip() {
+        let records = vec![ImportRecord::User(user()), ImportRecord::Question(question())];
+
+        let dump = to_ndjson(&records);
+
+        assert_eq!(read_dump("application/x-ndjson; charset=utf-8", &dump), Ok(records));
+    }
+
+    #[test]
+    fn ndjson_dumps_should_report_the_invalid_line() {
+        let blank_username = ImportRecord::User(ImportedUser { username: " ".to_owned(), ..user() });
+        let mut dump = to_ndjson(&[ImportRecord::User(user()), blank_username]);
+
+        assert_eq!(
+            read_dump(NDJSON_CONTENT_TYPE, &dump),
+            Err(DumpError::Line { line: 2, message: "username must not be empty.".to_owned() })
+        );
+
+        dump = to_ndjson(&[ImportRecord::User(user())]);
+        dump.extend_from_slice(b"\n{\"type\":\"vote\"}\n");
+
+        assert!(matches!(read_dump(NDJSON_CONTENT_TYPE, &dump), Err(DumpError::Line { line: 3, .. })));
+    }
+
+    #[test]
+    fn zipped_dumps_should_import_users_before_posts() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(QUESTIONS_FILE, SimpleFileOptions::default()).unwrap();
+        zip.write_all(serde_json::to_string(&[question()]).unwrap().as_bytes()).unwrap();
+        zip.start_file(USERS_FILE, SimpleFileOptions::default()).unwrap();
+        zip.write_all(serde_json::to_string(&[user()]).unwrap().as_bytes()).unwrap();
+        let dump = zip.finish().unwrap().into_inner();
+
+        assert_eq!(
+            read_dump(ZIP_CONTENT_TYPE, &dump),
+            Ok(vec![ImportRecord::User(user()), ImportRecord::Question(question())])
+        );
+    }
+
+    #[test]
+    fn oversized_files_should_be_refused_whole() {
+        assert_eq!(read_to_limit(&b"1234"[..], USERS_FILE, 4), Ok(b"1234".to_vec()));
+        assert_eq!(
+            read_to_limit(&b"12345"[..], USERS_FILE, 4),
+            Err(DumpError::TooLarge { file: USERS_FILE.to_owned() })
+        );
+    }
+
+    #[test]
+    fn other_formats_should_be_refused() {
+        assert_eq!(read_dump("application/json", b"[]"), Err(DumpError::UnsupportedFormat));
+        assert!(matches!(read_dump(ZIP_CONTENT_TYPE, b"not a zip"), Err(DumpError::Zip(_))));
+    }
+}
@@ -0,0 +1,719 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    export::NDJSON_CONTENT_TYPE,
+    import::{self, ImportJob},
+    link_previews::LinkPreviewFetcher,
+    mailer::{self, Mailer},
+    models::{
+        AcceptSuggestionThresholds, DBError, DeadLetterKind, ImportCounts, JobDetail, JobKind, PendingEmail,
+        RetentionCategory, RetentionPolicy,
+    },
+    persistance::{
+        answers_dao::AnswersDao, cleanup_policies_dao::CleanupPoliciesDao, dead_letters_dao::DeadLettersDao,
+        email_digests_dao::EmailDigestsDao, import_dao::ImportDao, jobs_dao::JobsDao, link_previews_dao::LinkPreviewsDao,
+        notifications_dao::NotificationsDao, query_samples_dao::QuerySamplesDao, questions_dao::QuestionsDao,
+        retention_dao::RetentionDao, stats_dao::StatsDao, tenants_dao::TenantsDao, users_dao::UsersDao,
+        webhooks_dao::WebhooksDao,
+    },
+    query_log::QuerySampler,
+    shutdown::ShutdownSignal,
+    signing::unix_timestamp,
+    slo::{self, SloTracker},
+    storage::ObjectStore,
+    tenancy,
+    views::ViewCounter,
+    webhooks::{self, DigestWebhook, EventWebhooks, SloAlertWebhook},
+};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Cursor name of the digest webhook in `webhook_cursors`.
+const DIGEST_WEBHOOK: &str = "digest";
+/// Consecutive failed deliveries of a digest before it becomes a dead letter.
+const MAX_DELIVERY_ATTEMPTS: usize = 5;
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const LINK_PREVIEW_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Link previews fetched per poll.
+const LINK_PREVIEW_BATCH_SIZE: i64 = 10;
+const BOARD_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const ACCEPT_SUGGESTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const CONTEST_JUDGING_INTERVAL: Duration = Duration::from_secs(60);
+const WEBHOOK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Webhook deliveries sent per poll, one after another.
+const WEBHOOK_BATCH_SIZE: i64 = 20;
+/// How long a claimed delivery stays claimed: well above a batch of timed out deliveries.
+const WEBHOOK_LEASE_SECONDS: i32 = 10 * 60;
+/// Attempts at a webhook delivery before it is given up, the last one about an hour after the first.
+const MAX_WEBHOOK_ATTEMPTS: i32 = 8;
+const EMAIL_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Notification emails sent per poll.
+const EMAIL_BATCH_SIZE: i64 = 50;
+/// Failed deliveries of a notification email before it is given up.
+const MAX_EMAIL_ATTEMPTS: i32 = 5;
+const DIGEST_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Digests collected and sent per claim; a poll keeps claiming until none are due.
+const DIGEST_BATCH_SIZE: i64 = 50;
+/// Burn rates change by the minute, as requests are counted per minute.
+const SLO_ALERT_INTERVAL: Duration = Duration::from_secs(60);
+/// How soon a sampling rate set on another server instance applies to this one.
+const QUERY_SAMPLING_INTERVAL: Duration = Duration::from_secs(30);
+const QUERY_SAMPLE_RETENTION_DAYS: i32 = 7;
+const VIEW_COUNTING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Periodically removes the data of every category in `policy` that is older than its retention
+/// period, for every tenant, and records how many rows each category lost.
+pub fn spawn_retention_purge(
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+    retention_dao: Arc<dyn RetentionDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    policy: RetentionPolicy,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            let tenants = tenancy::job_tenants(tenants_dao.as_ref()).await;
+
+            if tenants.is_empty() {
+                continue;
+            }
+
+            for category in RetentionCategory::ALL {
+                let Some(retention_days) = policy.days(category) else {
+                    continue;
+                };
+
+                let mut purged = 0;
+
+                for &tenant in &tenants {
+                    let purged_for_tenant = tenancy::with_tenant(tenant, async {
+                        match category {
+                            RetentionCategory::AuditLog => retention_dao.purge_audit_log(retention_days).await,
+                            RetentionCategory::IpAddresses => retention_dao.purge_ip_addresses(retention_days).await,
+                            RetentionCategory::QuestionViewers => retention_dao.purge_question_viewers(retention_days).await,
+                            RetentionCategory::DeletedAnswers => answers_dao.purge_deleted_answers(retention_days).await,
+                            RetentionCategory::DeletedQuestions => questions_dao.purge_deleted_questions(retention_days).await,
+                        }
+                    })
+                    .await;
+
+                    match purged_for_tenant {
+                        Ok(purged_for_tenant) => purged += purged_for_tenant,
+                        Err(err) => error!("Error to purge {}: {}", category.as_str(), err),
+                    }
+                }
+
+                info!("Purged {} rows of {}.", purged, category.as_str());
+
+                if let Err(err) = retention_dao.record_purge(category, purged).await {
+                    error!("Error to record purge of {}: {}", category.as_str(), err);
+                }
+            }
+        }
+    })
+}
+
+/// Every `interval`, posts the public questions and answers created since the last successful
+/// delivery as one payload. Failed deliveries are retried with a larger batch on the next tick;
+/// after `MAX_DELIVERY_ATTEMPTS` failures the batch is parked as a dead letter for an admin. The
+/// webhook serves the whole deployment, so with multi-tenancy it only gets the posts without a tenant.
+pub fn spawn_digest_webhook(
+    webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    dead_letters_dao: Arc<dyn DeadLettersDao + Send + Sync>,
+    webhook: Arc<DigestWebhook>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(webhook.interval);
+        let mut errors = Vec::new();
+
+        while shutdown.tick(&mut interval).await {
+            let digest = match webhooks_dao.get_pending_digest(DIGEST_WEBHOOK.to_owned()).await {
+                Ok(digest) => digest,
+                Err(err) => {
+                    error!("Error to collect webhook digest: {}", err);
+                    continue;
+                }
+            };
+
+            if !digest.is_empty() {
+                match webhook.deliver(&digest).await {
+                    Ok(()) => info!(
+                        "Delivered webhook digest with {} questions and {} answers.",
+                        digest.questions.len(),
+                        digest.answers.len()
+                    ),
+                    Err(err) => {
+                        error!("Error to deliver webhook digest: {}", err);
+                        errors.push(err.to_string());
+
+                        if errors.len() < MAX_DELIVERY_ATTEMPTS {
+                            continue;
+                        }
+
+                        let payload = serde_json::to_value(&digest).expect("digest serializes to JSON");
+
+                        if let Err(err) = dead_letters_dao
+                            .create_dead_letter(DeadLetterKind::WebhookDigest, payload, std::mem::take(&mut errors))
+                            .await
+                        {
+                            error!("Error to park webhook digest as a dead letter: {}", err);
+                            continue;
+                        }
+
+                        warn!("Parked webhook digest until {} as a dead letter.", digest.until);
+                    }
+                }
+            }
+
+            errors.clear();
+
+            if let Err(err) = webhooks_dao.mark_digest_delivered(DIGEST_WEBHOOK.to_owned(), digest.until).await {
+                error!("Error to advance webhook digest cursor: {}", err);
+            }
+        }
+    })
+}
+
+/// Runs the admin jobs queued in the `jobs` table one at a time, each in the tenant it was queued in,
+/// recording progress and the outcome on the job row so `GET /admin/jobs/:uuid` can report it.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_job_worker(
+    jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+    import_dao: Arc<dyn ImportDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    object_store: Arc<dyn ObjectStore>,
+    retention_days: i32,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(JOB_POLL_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                let run = run_queued_jobs(
+                    jobs_dao.as_ref(),
+                    questions_dao.as_ref(),
+                    answers_dao.as_ref(),
+                    import_dao.as_ref(),
+                    object_store.as_ref(),
+                    retention_days,
+                );
+
+                tenancy::with_tenant(tenant, run).await;
+            }
+        }
+    })
+}
+
+async fn run_queued_jobs(
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+    import_dao: &(dyn ImportDao + Send + Sync),
+    object_store: &dyn ObjectStore,
+    retention_days: i32,
+) {
+    loop {
+        let job = match jobs_dao.claim_next_job().await {
+            Ok(Some(job)) => job,
+            Ok(None) => break,
+            Err(err) => {
+                error!("Error to claim job: {}", err);
+                break;
+            }
+        };
+
+        info!("Running {} job {}.", job.kind.as_str(), job.job_uuid);
+
+        let outcome = match job.kind {
+            JobKind::PurgeDeletedPosts => purge_deleted_posts(&job, jobs_dao, questions_dao, answers_dao, retention_days)
+                .await
+                .map_err(|err| error_details(&err)),
+            JobKind::Import => import_dump(&job, jobs_dao, import_dao, object_store).await,
+        };
+
+        let recorded = match outcome {
+            Ok(result) => jobs_dao.complete_job(job.job_uuid.to_string(), result).await,
+            Err(err) => {
+                error!("Error to run {} job {}: {}", job.kind.as_str(), job.job_uuid, err);
+                jobs_dao.fail_job(job.job_uuid.to_string(), err).await
+            }
+        };
+
+        if let Err(err) = recorded {
+            error!("Error to record outcome of job {}: {}", job.job_uuid, err);
+        }
+    }
+}
+
+async fn purge_deleted_posts(
+    job: &JobDetail,
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+    retention_days: i32,
+) -> Result<serde_json::Value, DBError> {
+    let purged_answers = answers_dao.purge_deleted_answers(retention_days).await?;
+
+    if let Err(err) = jobs_dao.update_job_progress(job.job_uuid.to_string(), 50).await {
+        warn!("Error to report progress of job {}: {}", job.job_uuid, err);
+    }
+
+    let purged_questions = questions_dao.purge_deleted_questions(retention_days).await?;
+
+    Ok(serde_json::json!({
+        "purged_answers": purged_answers,
+        "purged_questions": purged_questions,
+    }))
+}
+
+/// Imports the dump stored by `POST /import` into the tenant it was posted to, a transaction per
+/// batch. When a batch fails, the batches before it stay imported: importing the dump again skips
+/// their records. The dump is removed either way.
+async fn import_dump(
+    job: &JobDetail,
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+    import_dao: &(dyn ImportDao + Send + Sync),
+    object_store: &dyn ObjectStore,
+) -> Result<serde_json::Value, String> {
+    let input: ImportJob = job
+        .input
+        .clone()
+        .and_then(|input| serde_json::from_value(input).ok())
+        .ok_or("The job has no dump to import.")?;
+
+    let dump = object_store
+        .get(&input.object_key)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("Dump {} not found.", input.object_key))?;
+
+    let imported = import_records(job, dump, input.tenant, jobs_dao, import_dao).await;
+
+    if let Err(err) = object_store.delete(&input.object_key).await {
+        warn!("Error to delete import dump {}: {}", input.object_key, err);
+    }
+
+    Ok(serde_json::json!(imported?))
+}
+
+async fn import_records(
+    job: &JobDetail,
+    dump: Vec<u8>,
+    tenant: Option<Uuid>,
+    jobs_dao: &(dyn JobsDao + Send + Sync),
+    import_dao: &(dyn ImportDao + Send + Sync),
+) -> Result<ImportCounts, String> {
+    let records = tokio::task::spawn_blocking(move || import::read_dump(NDJSON_CONTENT_TYPE, &dump))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())?;
+    let batches = records.chunks(import::BATCH_SIZE).len();
+    let mut counts = ImportCounts::default();
+
+    for (index, batch) in records.chunks(import::BATCH_SIZE).enumerate() {
+        counts += tenancy::with_tenant(tenant, import_dao.import_records(batch))
+            .await
+            .map_err(|err| format!("Batch {} of {}: {}", index + 1, batches, error_details(&err)))?;
+
+        let progress = ((index + 1) * 100 / batches) as i32;
+
+        if let Err(err) = jobs_dao.update_job_progress(job.job_uuid.to_string(), progress).await {
+            warn!("Error to report progress of job {}: {}", job.job_uuid, err);
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Fetches the link previews queued when posts are read. A failed fetch is recorded and not retried,
+/// so a dead link costs one request.
+pub fn spawn_link_preview_fetcher(
+    link_previews_dao: Arc<dyn LinkPreviewsDao + Send + Sync>,
+    fetcher: Arc<LinkPreviewFetcher>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LINK_PREVIEW_POLL_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            let urls = match link_previews_dao.claim_pending_link_previews(LINK_PREVIEW_BATCH_SIZE).await {
+                Ok(urls) => urls,
+                Err(err) => {
+                    error!("Error to claim link previews: {}", err);
+                    continue;
+                }
+            };
+
+            for url in urls {
+                let recorded = match fetcher.fetch(&url).await {
+                    Ok(preview) => link_previews_dao.save_link_preview(preview).await,
+                    Err(err) => {
+                        info!("No link preview for {}: {}", url, err);
+                        link_previews_dao.fail_link_preview(url.clone(), err.to_string()).await
+                    }
+                };
+
+                if let Err(err) = recorded {
+                    error!("Error to record link preview of {}: {}", url, err);
+                }
+            }
+        }
+    })
+}
+
+/// Applies every board's cleanup policy, in every tenant, closing inactive questions and
+/// soft-deleting old unanswered ones. Deleted questions are then hard-deleted by the soft-delete purge.
+pub fn spawn_board_cleanup(
+    cleanup_policies_dao: Arc<dyn CleanupPoliciesDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BOARD_CLEANUP_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                tenancy::with_tenant(tenant, clean_up_boards(cleanup_policies_dao.as_ref())).await;
+            }
+        }
+    })
+}
+
+async fn clean_up_boards(cleanup_policies_dao: &(dyn CleanupPoliciesDao + Send + Sync)) {
+    let policies = match cleanup_policies_dao.get_cleanup_policies().await {
+        Ok(policies) => policies,
+        Err(err) => {
+            error!("Error to load board cleanup policies: {}", err);
+            return;
+        }
+    };
+
+    for policy in policies {
+        match cleanup_policies_dao.run_cleanup(policy.board_uuid.to_string(), (&policy).into()).await {
+            Ok(cleanup) => info!(
+                "Board {} cleanup closed {} and deleted {} questions.",
+                cleanup.board_uuid,
+                cleanup.closed_question_uuids.len(),
+                cleanup.deleted_question_uuids.len()
+            ),
+            Err(err) => error!("Error to clean up board {}: {}", policy.board_uuid, err),
+        }
+    }
+}
+
+/// Picks the winner of every contest whose voting ended, in every tenant, once a minute.
+pub fn spawn_contest_judging(
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CONTEST_JUDGING_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                match tenancy::with_tenant(tenant, questions_dao.decide_contests()).await {
+                    Ok(0) => {}
+                    Ok(decided) => info!("Decided {} contests.", decided),
+                    Err(err) => error!("Error to decide contests: {}", err),
+                }
+            }
+        }
+    })
+}
+
+/// Sends the deliveries queued for registered webhooks of every tenant, retrying failed ones with
+/// exponential backoff.
+pub fn spawn_webhook_delivery(
+    webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    event_webhooks: EventWebhooks,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(WEBHOOK_POLL_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                tenancy::with_tenant(tenant, deliver_webhook_events(webhooks_dao.as_ref(), &event_webhooks)).await;
+            }
+        }
+    })
+}
+
+async fn deliver_webhook_events(webhooks_dao: &(dyn WebhooksDao + Send + Sync), event_webhooks: &EventWebhooks) {
+    let deliveries = match webhooks_dao.claim_webhook_deliveries(WEBHOOK_BATCH_SIZE, WEBHOOK_LEASE_SECONDS).await {
+        Ok(deliveries) => deliveries,
+        Err(err) => {
+            error!("Error to claim webhook deliveries: {}", err);
+            return;
+        }
+    };
+
+    for delivery in deliveries {
+        let recorded = match event_webhooks.deliver(&delivery).await {
+            Ok(status) => webhooks_dao.mark_webhook_delivered(delivery.delivery_uuid.to_string(), status).await,
+            Err(err) => {
+                warn!("Error to deliver webhook event {}: {}", delivery.delivery_uuid, err);
+                let retry_in = webhooks::retry_delay(delivery.attempts + 1, MAX_WEBHOOK_ATTEMPTS);
+                webhooks_dao
+                    .fail_webhook_delivery(delivery.delivery_uuid.to_string(), err.status(), err.to_string(), retry_in)
+                    .await
+            }
+        };
+
+        if let Err(err) = recorded {
+            error!("Error to record webhook delivery {}: {}", delivery.delivery_uuid, err);
+        }
+    }
+}
+
+/// Periodically suggests accepting the leading answer to authors of old unresolved questions, in
+/// every tenant.
+pub fn spawn_accept_suggestions(
+    notifications_dao: Arc<dyn NotificationsDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    thresholds: AcceptSuggestionThresholds,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ACCEPT_SUGGESTION_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                match tenancy::with_tenant(tenant, notifications_dao.notify_accept_suggestions(thresholds)).await {
+                    Ok(notified) => info!("Suggested accepting an answer on {} questions.", notified),
+                    Err(err) => error!("Error to suggest accepted answers: {}", err),
+                }
+            }
+        }
+    })
+}
+
+/// Emails new answers on followed questions and mentions to the recipients with an email address,
+/// in every tenant. Handlers only create the notifications, so a slow or unreachable SMTP server
+/// never delays them.
+pub fn spawn_email_delivery(
+    notifications_dao: Arc<dyn NotificationsDao + Send + Sync>,
+    users_dao: Arc<dyn UsersDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    mailer: Arc<dyn Mailer>,
+    forum_url: String,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EMAIL_POLL_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                let delivery = deliver_emails(notifications_dao.as_ref(), users_dao.as_ref(), mailer.as_ref(), &forum_url);
+
+                tenancy::with_tenant(tenant, delivery).await;
+            }
+        }
+    })
+}
+
+async fn deliver_emails(
+    notifications_dao: &(dyn NotificationsDao + Send + Sync),
+    users_dao: &(dyn UsersDao + Send + Sync),
+    mailer: &dyn Mailer,
+    forum_url: &str,
+) {
+    let pending = match notifications_dao.claim_pending_emails(EMAIL_BATCH_SIZE).await {
+        Ok(pending) => pending,
+        Err(err) => {
+            error!("Error to claim notification emails: {}", err);
+            return;
+        }
+    };
+
+    for notification in pending {
+        let recorded = match deliver_email(&notification, users_dao, mailer, forum_url).await {
+            Ok(true) => notifications_dao.mark_email_sent(notification.id).await,
+            Ok(false) => notifications_dao.skip_email(notification.id).await,
+            Err(err) => {
+                warn!("Error to email notification {}: {}", notification.id, err);
+                notifications_dao.fail_email(notification.id, err, MAX_EMAIL_ATTEMPTS).await
+            }
+        };
+
+        if let Err(err) = recorded {
+            error!("Error to record email delivery of notification {}: {}", notification.id, err);
+        }
+    }
+}
+
+/// Returns false when the notification is not emailed.
+async fn deliver_email(
+    notification: &PendingEmail,
+    users_dao: &(dyn UsersDao + Send + Sync),
+    mailer: &dyn Mailer,
+    forum_url: &str,
+) -> Result<bool, String> {
+    let Some(to) = users_dao.get_user_email(notification.user_uuid.to_string()).await.map_err(|err| err.to_string())? else {
+        return Ok(false);
+    };
+
+    let Some(email) = mailer::notification_email(notification, to, forum_url) else {
+        return Ok(false);
+    };
+
+    mailer.send(&email).await.map_err(|err| err.to_string())?;
+
+    Ok(true)
+}
+
+/// Emails each subscribed user, in every tenant, their daily or weekly digest. Users without an email
+/// address and digests with nothing to report are skipped until the next period.
+pub fn spawn_email_digests(
+    email_digests_dao: Arc<dyn EmailDigestsDao + Send + Sync>,
+    users_dao: Arc<dyn UsersDao + Send + Sync>,
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    mailer: Arc<dyn Mailer>,
+    forum_url: String,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DIGEST_POLL_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            for tenant in tenancy::job_tenants(tenants_dao.as_ref()).await {
+                let sending = send_due_digests(email_digests_dao.as_ref(), users_dao.as_ref(), mailer.as_ref(), &forum_url);
+
+                tenancy::with_tenant(tenant, sending).await;
+            }
+        }
+    })
+}
+
+async fn send_due_digests(
+    email_digests_dao: &(dyn EmailDigestsDao + Send + Sync),
+    users_dao: &(dyn UsersDao + Send + Sync),
+    mailer: &dyn Mailer,
+    forum_url: &str,
+) {
+    loop {
+        let digests = match email_digests_dao.claim_due_digests(DIGEST_BATCH_SIZE).await {
+            Ok(digests) if digests.is_empty() => break,
+            Ok(digests) => digests,
+            Err(err) => {
+                error!("Error to claim email digests: {}", err);
+                break;
+            }
+        };
+
+        for digest in digests {
+            let to = match users_dao.get_user_email(digest.user_uuid.to_string()).await {
+                Ok(Some(to)) => to,
+                Ok(None) => continue,
+                Err(err) => {
+                    error!("Error to read email address for digest of {}: {}", digest.user_uuid, err);
+                    continue;
+                }
+            };
+
+            let Some(email) = mailer::digest_email(&digest, to, forum_url) else { continue };
+
+            if let Err(err) = mailer.send(&email).await {
+                warn!("Error to email digest of {}: {}", digest.user_uuid, err);
+            }
+        }
+    }
+}
+
+/// Every minute, posts an alert for each SLO objective that started or stopped burning its error
+/// budget. An alert that fails to deliver is tried again on the next tick while it still applies.
+pub fn spawn_slo_alerts(
+    slo_tracker: Arc<SloTracker>,
+    webhook: Arc<SloAlertWebhook>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SLO_ALERT_INTERVAL);
+        let mut severities = HashMap::new();
+
+        while shutdown.tick(&mut interval).await {
+            let now = unix_timestamp();
+
+            for alert in slo::alerts(&mut severities, &slo_tracker.status(now), now) {
+                match webhook.deliver(&alert).await {
+                    Ok(()) => info!("Alerted {:?} of {} as {:?}.", alert.objective, alert.route, alert.severity),
+                    Err(err) => {
+                        warn!("Error to deliver SLO alert for {}: {}", alert.route, err);
+                        severities.insert((alert.route, alert.objective), alert.previous);
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Keeps the query sampling rate of this server instance in line with the one admins set, and
+/// removes samples older than a week.
+pub fn spawn_query_sampling(
+    query_sampler: Arc<QuerySampler>,
+    query_samples_dao: Arc<dyn QuerySamplesDao + Send + Sync>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(QUERY_SAMPLING_INTERVAL);
+
+        while shutdown.tick(&mut interval).await {
+            match query_samples_dao.get_sampling_percent().await {
+                Ok(percent) => query_sampler.set_percent(percent),
+                Err(err) => error!("Error to read query sampling: {}", err),
+            }
+
+            match query_samples_dao.purge_query_samples(QUERY_SAMPLE_RETENTION_DAYS).await {
+                Ok(0) => {}
+                Ok(removed) => info!("Purged {} query samples.", removed),
+                Err(err) => error!("Error to purge query samples: {}", err),
+            }
+        }
+    })
+}
+
+/// Writes the question views queued by reads, and those still queued once shutdown is requested.
+pub fn spawn_view_counting(
+    view_counter: Arc<ViewCounter>,
+    stats_dao: Arc<dyn StatsDao + Send + Sync>,
+    mut shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(VIEW_COUNTING_INTERVAL);
+
+        loop {
+            let running = shutdown.tick(&mut interval).await;
+
+            for (tenant, views) in view_counter.take() {
+                let count = views.len();
+
+                if let Err(err) = tenancy::with_tenant(tenant, stats_dao.record_question_views(views)).await {
+                    error!("Error to count {} question views: {}", count, err);
+                }
+            }
+
+            if !running {
+                break;
+            }
+        }
+    })
+}
+
+/// Job errors are only shown to admins, so they include the underlying cause that `DBError` hides.
+fn error_details(err: &DBError) -> String {
+    match err {
+        DBError::Other(source) => format!("{}: {}", err, source),
+        err => err.to_string(),
+    }
+}
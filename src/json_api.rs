@@ -0,0 +1,298 @@
+use std::convert::Infallible;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, OriginalUri},
+    http::{header, request::Parts},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    models::{AnswerDetail, Pagination, QuestionDetail, QuestionFull},
+    routes::API_V1,
+    AppState,
+};
+
+pub const JSON_API_CONTENT_TYPE: &str = "application/vnd.api+json";
+
+const JSON_API_VERSION: &str = "1.1";
+
+/// How resources are sent to a client: as the plain JSON of the API, or as JSON:API documents
+/// when its `Accept` header lists `application/vnd.api+json`. With `json_api_default`, clients
+/// get JSON:API unless they list `application/json`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseFormat {
+    Json,
+    /// With the path and query of the request, which the document links to as `self`.
+    JsonApi { uri: String },
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let accept = parts.headers.get(header::ACCEPT).and_then(|value| value.to_str().ok());
+
+        if !wants_json_api(accept, state.settings.json_api_default) {
+            return Ok(ResponseFormat::Json);
+        }
+
+        // Nested routers see the URI without the `/api/v1` prefix.
+        let uri = parts.extensions.get::<OriginalUri>().map(|OriginalUri(uri)| uri).unwrap_or(&parts.uri);
+
+        Ok(ResponseFormat::JsonApi {
+            uri: uri.path_and_query().map(ToString::to_string).unwrap_or_else(|| uri.path().to_owned()),
+        })
+    }
+}
+
+fn wants_json_api(accept: Option<&str>, json_api_default: bool) -> bool {
+    let media_types: Vec<String> = accept
+        .unwrap_or_default()
+        .split(',')
+        .map(|media_type| media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+        .collect();
+
+    media_types.iter().any(|media_type| media_type == JSON_API_CONTENT_TYPE)
+        || (json_api_default && !media_types.iter().any(|media_type| media_type == "application/json"))
+}
+
+impl ResponseFormat {
+    /// `body` as JSON, or as the JSON:API document `document` makes of it for the request URI.
+    pub fn respond<T: Serialize>(&self, body: T, document: impl FnOnce(&T, &str) -> Value) -> Response {
+        match self {
+            ResponseFormat::Json => Json(body).into_response(),
+            ResponseFormat::JsonApi { uri } => {
+                ([(header::CONTENT_TYPE, JSON_API_CONTENT_TYPE)], Json(document(&body, uri))).into_response()
+            }
+        }
+    }
+
+    /// `etag`, told apart from that of the other format, since both are sent from the same URI.
+    pub fn etag(&self, etag: String) -> String {
+        match self {
+            ResponseFormat::Json => etag,
+            ResponseFormat::JsonApi { .. } => match etag.strip_suffix('"') {
+                Some(opaque) => format!("{}-jsonapi\"", opaque),
+                None => etag,
+            },
+        }
+    }
+}
+
+/// A type of the API as a JSON:API resource object.
+pub trait Resource {
+    fn resource(&self) -> Value;
+}
+
+/// The fields of `item` as the `attributes` of a resource of `kind`, except for `id_field`, which
+/// is its `id`, and the fields of `relationships`, each naming the UUID of a resource of another
+/// type as a `(field, relationship, type)` triple.
+fn resource<T: Serialize>(kind: &str, id_field: &str, item: &T, relationships: &[(&str, &str, &str)]) -> Map<String, Value> {
+    let mut attributes = match serde_json::to_value(item) {
+        Ok(Value::Object(attributes)) => attributes,
+        _ => Map::new(),
+    };
+    let id = attributes.remove(id_field).unwrap_or(Value::Null);
+    let mut related = Map::new();
+
+    for (field, relationship, related_kind) in relationships {
+        let data = match attributes.remove(*field) {
+            Some(Value::String(related_id)) => json!({ "type": related_kind, "id": related_id }),
+            _ => Value::Null,
+        };
+
+        related.insert(relationship.to_string(), json!({ "data": data }));
+    }
+
+    let mut resource = Map::new();
+    resource.insert("type".to_owned(), json!(kind));
+    resource.insert("id".to_owned(), id);
+    resource.insert("attributes".to_owned(), Value::Object(attributes));
+    resource.insert("relationships".to_owned(), Value::Object(related));
+
+    resource
+}
+
+/// Questions link to their answers rather than list them; `GET /question/{uuid}/full` includes them.
+impl Resource for QuestionDetail {
+    fn resource(&self) -> Value {
+        let mut resource = resource(
+            "questions",
+            "question_uuid",
+            self,
+            &[("author_uuid", "author", "users"), ("board_uuid", "board", "boards")],
+        );
+
+        if let Some(Value::Object(relationships)) = resource.get_mut("relationships") {
+            relationships.insert(
+                "answers".to_owned(),
+                json!({ "links": { "related": format!("{}/questions/{}/answers", API_V1, self.question_uuid) } }),
+            );
+        }
+
+        resource.insert("links".to_owned(), json!({ "self": format!("{}/question/{}", API_V1, self.question_uuid) }));
+
+        Value::Object(resource)
+    }
+}
+
+impl Resource for AnswerDetail {
+    fn resource(&self) -> Value {
+        Value::Object(resource(
+            "answers",
+            "answer_uuid",
+            self,
+            &[("question_uuid", "question", "questions"), ("author_uuid", "author", "users")],
+        ))
+    }
+}
+
+fn top_level(data: Value, links: Value) -> Value {
+    json!({
+        "jsonapi": { "version": JSON_API_VERSION },
+        "links": links,
+        "data": data,
+    })
+}
+
+/// A document of one resource.
+pub fn document<R: Resource>(item: &R, uri: &str) -> Value {
+    top_level(item.resource(), json!({ "self": uri }))
+}
+
+/// A document of a whole collection.
+pub fn collection_document<R: Resource>(items: &[R], uri: &str) -> Value {
+    top_level(items.iter().map(Resource::resource).collect(), json!({ "self": uri }))
+}
+
+/// A document of the `page` of a collection at `uri`, linking to the first, previous and next
+/// pages. Listings are not counted, so there is no `last` link, and `next` is left out after a
+/// page that is not full.
+pub fn page_document<R: Resource>(items: &[R], uri: &str, page: Pagination) -> Value {
+    let limit = page.limit.min(Pagination::MAX_LIMIT);
+    let previous = (page.offset > 0).then(|| page_link(uri, page.offset.saturating_sub(limit), limit));
+    let next = (items.len() as u32 >= limit).then(|| page_link(uri, page.offset + limit, limit));
+
+    top_level(
+        items.iter().map(Resource::resource).collect(),
+        json!({
+            "self": uri,
+            "first": page_link(uri, 0, limit),
+            "prev": previous,
+            "next": next,
+        }),
+    )
+}
+
+/// The question, with its answers as a relationship and as `included` resources.
+pub fn full_document(full: &QuestionFull, uri: &str) -> Value {
+    let mut document = document(&full.question, uri);
+    let answers: Vec<Value> = full
+        .answers
+        .iter()
+        .map(|answer| json!({ "type": "answers", "id": answer.answer_uuid }))
+        .collect();
+
+    if let Some(Value::Object(relationships)) = document.pointer_mut("/data/relationships") {
+        if let Some(Value::Object(relationship)) = relationships.get_mut("answers") {
+            relationship.insert("data".to_owned(), Value::Array(answers));
+        }
+    }
+
+    document["included"] = full.answers.iter().map(Resource::resource).collect();
+
+    document
+}
+
+/// `uri` with its `offset` and `limit` replaced, keeping the rest of its query.
+fn page_link(uri: &str, offset: u32, limit: u32) -> String {
+    let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && name != "offset" && name != "limit"
+        })
+        .map(str::to_owned)
+        .collect();
+
+    params.push(format!("offset={}", offset));
+    params.push(format!("limit={}", limit));
+
+    format!("{}?{}", path, params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn answer(answer_uuid: Uuid) -> AnswerDetail {
+        AnswerDetail {
+            answer_uuid,
+            question_uuid: Uuid::from_u128(0x1),
+            content: "content".to_owned(),
+            author_uuid: None,
+            created_at: datetime!(2026-10-17 10:00:00 UTC),
+            content_html: None,
+            code_blocks: Vec::new(),
+            link_previews: Vec::new(),
+            signals: None,
+            question_age_warning: false,
+            similar_answer_uuid: None,
+            held_for_review: false,
+        }
+    }
+
+    #[test]
+    fn wants_json_api_should_follow_accept_then_the_default() {
+        assert!(wants_json_api(Some("application/json, application/vnd.api+json"), false));
+        assert!(!wants_json_api(Some("application/json"), false));
+        assert!(!wants_json_api(None, false));
+        assert!(wants_json_api(None, true));
+        assert!(wants_json_api(Some("*/*"), true));
+        assert!(!wants_json_api(Some("application/json"), true));
+    }
+
+    #[test]
+    fn answers_should_relate_to_their_question() {
+        let answer_uuid = Uuid::from_u128(0x2);
+
+        assert_eq!(
+            answer(answer_uuid).resource(),
+            json!({
+                "type": "answers",
+                "id": answer_uuid,
+                "attributes": { "content": "content", "created_at": "2026-10-17T10:00:00Z" },
+                "relationships": {
+                    "question": { "data": { "type": "questions", "id": Uuid::from_u128(0x1) } },
+                    "author": { "data": null },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn page_document_should_link_to_neighbouring_pages() {
+        let answers = vec![answer(Uuid::from_u128(0x2)), answer(Uuid::from_u128(0x3))];
+        let uri = "/api/v1/users/u/answers?format=raw&offset=2&limit=2";
+
+        let document = page_document(&answers, uri, Pagination { offset: 2, limit: 2 });
+
+        assert_eq!(document["links"]["self"], uri);
+        assert_eq!(document["links"]["first"], "/api/v1/users/u/answers?format=raw&offset=0&limit=2");
+        assert_eq!(document["links"]["prev"], "/api/v1/users/u/answers?format=raw&offset=0&limit=2");
+        assert_eq!(document["links"]["next"], "/api/v1/users/u/answers?format=raw&offset=4&limit=2");
+
+        let last = page_document(&answers[..1], uri, Pagination { offset: 2, limit: 2 });
+
+        assert!(last["links"]["next"].is_null());
+    }
+}
@@ -0,0 +1,43 @@
+use whatlang::Lang;
+
+use crate::markdown;
+
+/// The ISO 639-3 code of the language a question is written in, e.g. `eng` or `por`, or `None`
+/// when the text is too short or too mixed to tell. Code blocks are ignored.
+pub fn detect_language(title: &str, description: &str) -> Option<String> {
+    let text = format!("{} {}", title, markdown::prose(description));
+
+    whatlang::detect(&text)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code().to_owned())
+}
+
+/// Whether `code` is an ISO 639-3 code `detect_language` can return.
+pub fn is_known_language(code: &str) -> bool {
+    Lang::from_code(code).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_the_language_of_the_prose() {
+        let description = "Estou tentando compartilhar um vetor entre várias threads, mas o compilador reclama \
+            que o valor foi movido.\n\n```rust\nlet data = vec![1, 2, 3];\nthread::spawn(move || println!(\"{:?}\", data));\n```";
+
+        assert_eq!(
+            detect_language("Como compartilhar dados entre threads?", description).as_deref(),
+            Some("por")
+        );
+        assert_eq!(
+            detect_language("How do I share data between threads?", "The compiler says the value was moved into the closure.").as_deref(),
+            Some("eng")
+        );
+    }
+
+    #[test]
+    fn should_not_guess_the_language_of_code() {
+        assert_eq!(detect_language("", "```rust\nfn main() {}\n```"), None);
+    }
+}
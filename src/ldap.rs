@@ -0,0 +1,215 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use ldap3::{ldap_escape, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+
+use crate::{
+    auth::{AuthBackend, AuthBackendError, GroupRoleMap},
+    models::ProvisionedUser,
+    secrets::SecretsProvider,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// `invalidCredentials` (RFC 4511), returned when the user's bind is rejected.
+const INVALID_CREDENTIALS: u32 = 49;
+
+pub struct LdapConfig {
+    /// e.g. `ldaps://ldap.example.com`.
+    pub url: String,
+    /// Service account used to look users up; searches anonymously when unset.
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    pub user_base_dn: String,
+    /// Filter that finds exactly one user, with `{username}` replaced by the escaped sign-in name,
+    /// e.g. `(uid={username})` or `(sAMAccountName={username})` for Active Directory.
+    pub user_filter: String,
+    pub display_name_attribute: String,
+    pub email_attribute: String,
+    /// Attribute listing the user's group DNs, `memberOf` on Active Directory and OpenLDAP.
+    pub group_attribute: String,
+    pub group_roles: GroupRoleMap,
+}
+
+impl LdapConfig {
+    pub async fn from_env(secrets: &dyn SecretsProvider) -> Result<Self, AuthBackendError> {
+        let required = |name: &str| {
+            std::env::var(name).map_err(|_| AuthBackendError::InvalidConfig(format!("{} must be set", name)))
+        };
+        let optional = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_owned());
+
+        Ok(LdapConfig {
+            url: required("LDAP_URL")?,
+            bind_dn: std::env::var("LDAP_BIND_DN").ok(),
+            bind_password: secrets.get("LDAP_BIND_PASSWORD").await?,
+            user_base_dn: required("LDAP_USER_BASE_DN")?,
+            user_filter: optional("LDAP_USER_FILTER", "(uid={username})"),
+            display_name_attribute: optional("LDAP_DISPLAY_NAME_ATTRIBUTE", "displayName"),
+            email_attribute: optional("LDAP_EMAIL_ATTRIBUTE", "mail"),
+            group_attribute: optional("LDAP_GROUP_ATTRIBUTE", "memberOf"),
+            group_roles: GroupRoleMap::parse(&optional("LDAP_GROUP_ROLES", ""))
+                .map_err(AuthBackendError::InvalidConfig)?,
+        })
+    }
+}
+
+/// Verifies passwords by binding to the directory as the user, after finding their DN with
+/// the service account.
+pub struct LdapBackend {
+    config: LdapConfig,
+}
+
+impl LdapBackend {
+    pub fn new(config: LdapConfig) -> Self {
+        LdapBackend { config }
+    }
+
+    /// Maps the directory entry to a forum user. The entry's DN becomes the external id that links
+    /// later sign-ins to the same forum account.
+    fn provisioned_user(&self, username: &str, entry: SearchEntry) -> ProvisionedUser {
+        let groups = attribute_values(&entry.attrs, &self.config.group_attribute);
+        let role = self
+            .config
+            .group_roles
+            .role_for(groups.iter().flat_map(|group| [group.as_str(), group_name(group)]));
+
+        ProvisionedUser {
+            username: username.to_owned(),
+            display_name: attribute_values(&entry.attrs, &self.config.display_name_attribute).first().cloned(),
+            email: attribute_values(&entry.attrs, &self.config.email_attribute).first().cloned(),
+            external_id: Some(entry.dn),
+            role,
+            active: true,
+        }
+    }
+}
+
+#[async_trait]
+impl AuthBackend for LdapBackend {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<Option<ProvisionedUser>, AuthBackendError> {
+        // Most servers treat a bind with an empty password as an anonymous bind and accept it.
+        if username.trim().is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+
+        let settings = LdapConnSettings::new().set_conn_timeout(CONNECT_TIMEOUT);
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.config.url)
+            .await
+            .map_err(directory_error)?;
+        ldap3::drive!(conn);
+
+        if let Some(bind_dn) = &self.config.bind_dn {
+            ldap.simple_bind(bind_dn, self.config.bind_password.as_deref().unwrap_or_default())
+                .await
+                .and_then(|result| result.success())
+                .map_err(directory_error)?;
+        }
+
+        let filter = self.config.user_filter.replace("{username}", &ldap_escape(username.trim()));
+        let attributes = [
+            self.config.display_name_attribute.as_str(),
+            self.config.email_attribute.as_str(),
+            self.config.group_attribute.as_str(),
+        ];
+
+        let (entries, _) = ldap
+            .search(&self.config.user_base_dn, Scope::Subtree, &filter, attributes)
+            .await
+            .and_then(|result| result.success())
+            .map_err(directory_error)?;
+
+        // Unknown and ambiguous usernames are reported the same way as a wrong password.
+        let mut entries = entries.into_iter();
+        let (Some(entry), None) = (entries.next(), entries.next()) else {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        };
+        let entry = SearchEntry::construct(entry);
+
+        let bind = ldap.simple_bind(&entry.dn, password).await.map_err(directory_error)?;
+        let _ = ldap.unbind().await;
+
+        if bind.rc == INVALID_CREDENTIALS {
+            return Ok(None);
+        }
+
+        bind.success().map_err(directory_error)?;
+
+        Ok(Some(self.provisioned_user(username.trim(), entry)))
+    }
+}
+
+fn directory_error(err: LdapError) -> AuthBackendError {
+    AuthBackendError::Directory(err.to_string())
+}
+
+/// Attribute names are case-insensitive and servers do not always echo the requested casing.
+fn attribute_values(attrs: &HashMap<String, Vec<String>>, name: &str) -> Vec<String> {
+    attrs
+        .iter()
+        .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+        .map(|(_, values)| values.clone())
+        .unwrap_or_default()
+}
+
+/// The first RDN value of a group DN, `Forum Admins` for `CN=Forum Admins,OU=Groups,DC=example,DC=com`,
+/// so group roles can be configured by name as well as by full DN.
+fn group_name(dn: &str) -> &str {
+    dn.split(',')
+        .next()
+        .and_then(|rdn| rdn.split_once('='))
+        .map(|(_, value)| value.trim())
+        .unwrap_or(dn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Role;
+
+    fn backend(group_roles: &str) -> LdapBackend {
+        LdapBackend::new(LdapConfig {
+            url: "ldap://localhost".to_owned(),
+            bind_dn: None,
+            bind_password: None,
+            user_base_dn: "ou=people,dc=example,dc=com".to_owned(),
+            user_filter: "(uid={username})".to_owned(),
+            display_name_attribute: "displayName".to_owned(),
+            email_attribute: "mail".to_owned(),
+            group_attribute: "memberOf".to_owned(),
+            group_roles: GroupRoleMap::parse(group_roles).unwrap(),
+        })
+    }
+
+    #[test]
+    fn provisioned_user_should_map_attributes_and_groups() {
+        let entry = SearchEntry {
+            dn: "uid=jane,ou=people,dc=example,dc=com".to_owned(),
+            attrs: HashMap::from([
+                ("displayname".to_owned(), vec!["Jane Doe".to_owned()]),
+                ("mail".to_owned(), vec!["jane@example.com".to_owned()]),
+                (
+                    "memberOf".to_owned(),
+                    vec![
+                        "cn=staff,ou=groups,dc=example,dc=com".to_owned(),
+                        "CN=Forum Admins,OU=Groups,DC=example,DC=com".to_owned(),
+                    ],
+                ),
+            ]),
+            bin_attrs: HashMap::new(),
+        };
+
+        let user = backend("forum admins:admin,staff:moderator").provisioned_user("jane", entry);
+
+        assert_eq!(user.role, Role::Admin);
+        assert_eq!(user.display_name.as_deref(), Some("Jane Doe"));
+        assert_eq!(user.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(user.external_id.as_deref(), Some("uid=jane,ou=people,dc=example,dc=com"));
+    }
+
+    #[tokio::test]
+    async fn authenticate_should_reject_empty_password_without_connecting() {
+        let result = backend("").authenticate("jane", "").await;
+
+        assert!(matches!(result, Ok(None)));
+    }
+}
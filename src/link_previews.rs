@@ -0,0 +1,327 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use reqwest::{header, redirect, Response, Url};
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::models::LinkPreview;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REDIRECTS: usize = 3;
+/// Only the start of a page is read; the metadata we need is in `<head>`.
+const MAX_BODY_BYTES: usize = 512 * 1024;
+const MAX_TITLE_CHARS: usize = 300;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+const USER_AGENT: &str = "rust-programming-forum-api link preview";
+
+#[derive(Error, Debug)]
+pub enum LinkPreviewError {
+    #[error("Unsupported URL: {0}")]
+    UnsupportedUrl(String),
+    #[error("{0} does not resolve to a public address")]
+    NonPublicAddress(String),
+    #[error("Failed to resolve {0}: {1}")]
+    Resolve(String, std::io::Error),
+    #[error("More than {} redirects", MAX_REDIRECTS)]
+    TooManyRedirects,
+    #[error("Not an HTML page: {0}")]
+    UnsupportedContentType(String),
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+/// Fetches the Open Graph and oEmbed metadata of pages linked from posts.
+///
+/// Post authors choose the URLs, so every request (including each redirect) is only sent after
+/// checking that the host resolves to public addresses, and the connection is pinned to those
+/// addresses. Without this, a post could make the server probe hosts on its internal network.
+pub struct LinkPreviewFetcher;
+
+impl LinkPreviewFetcher {
+    pub async fn fetch(&self, url: &str) -> Result<LinkPreview, LinkPreviewError> {
+        let (page_url, response) = get(url, "text/html,application/xhtml+xml").await?;
+
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+
+        if !content_type.starts_with("text/html") && !content_type.starts_with("application/xhtml+xml") {
+            return Err(LinkPreviewError::UnsupportedContentType(content_type));
+        }
+
+        let body = read_body(response).await?;
+        let (mut preview, oembed_url) = parse_page(&page_url, &String::from_utf8_lossy(&body));
+        preview.url = url.to_owned();
+
+        // oEmbed providers such as video sites often describe their pages better than the page's own tags.
+        if let Some(oembed_url) = oembed_url.filter(|_| preview.title.is_none() || preview.image_url.is_none()) {
+            match fetch_oembed(oembed_url.as_str()).await {
+                Ok(oembed) => apply_oembed(&mut preview, &page_url, oembed),
+                Err(err) => warn!("Error to fetch oEmbed data of {}: {}", url, err),
+            }
+        }
+
+        Ok(preview)
+    }
+}
+
+/// Sends a GET request, following redirects by hand so each hop is checked like the first.
+async fn get(url: &str, accept: &str) -> Result<(Url, Response), LinkPreviewError> {
+    let mut url = Url::parse(url).map_err(|_| LinkPreviewError::UnsupportedUrl(url.to_owned()))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let response = pinned_client(&url)
+            .await?
+            .get(url.clone())
+            .header(header::ACCEPT, accept)
+            .send()
+            .await?;
+
+        if !response.status().is_redirection() {
+            return Ok((url, response.error_for_status()?));
+        }
+
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| url.join(location).ok())
+            .ok_or_else(|| LinkPreviewError::UnsupportedUrl(format!("redirect from {}", url)))?;
+
+        url = location;
+    }
+
+    Err(LinkPreviewError::TooManyRedirects)
+}
+
+/// A client that can only connect to the public addresses `url`'s host resolves to right now, so a
+/// DNS answer that changes between the check and the request cannot point it elsewhere.
+async fn pinned_client(url: &Url) -> Result<reqwest::Client, LinkPreviewError> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(LinkPreviewError::UnsupportedUrl(url.to_string()));
+    }
+
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Err(LinkPreviewError::UnsupportedUrl(url.to_string()));
+    };
+
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|err| LinkPreviewError::Resolve(host.to_owned(), err))?
+        .collect();
+
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public(address.ip())) {
+        return Err(LinkPreviewError::NonPublicAddress(host.to_owned()));
+    }
+
+    Ok(reqwest::Client::builder()
+        .redirect(redirect::Policy::none())
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(USER_AGENT)
+        .resolve_to_addrs(host, &addresses)
+        .build()?)
+}
+
+async fn read_body(mut response: Response) -> Result<Vec<u8>, LinkPreviewError> {
+    let mut body = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+
+        if body.len() >= MAX_BODY_BYTES {
+            body.truncate(MAX_BODY_BYTES);
+            break;
+        }
+    }
+
+    Ok(body)
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // "This network", carrier-grade NAT and reserved ranges.
+                || first == 0
+                || (first == 100 && second & 0xc0 == 64)
+                || first >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+
+                // Unique local (fc00::/7) and link-local (fe80::/10) addresses are not routable.
+                !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Reads the preview from the page's Open Graph, Twitter card and standard tags, in that order of
+/// preference, and returns the page's oEmbed endpoint if it advertises one.
+fn parse_page(page_url: &Url, html: &str) -> (LinkPreview, Option<Url>) {
+    let document = Html::parse_document(html);
+
+    let meta = |name: &str| {
+        let selector = Selector::parse(&format!("meta[property=\"{0}\"], meta[name=\"{0}\"]", name))
+            .expect("meta selector is valid");
+
+        document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("content"))
+            .map(clean_text)
+            .find(|content| !content.is_empty())
+    };
+    let first = |selector: &str| {
+        let selector = Selector::parse(selector).expect("selector is valid");
+
+        document.select(&selector).next()
+    };
+
+    let title = meta("og:title")
+        .or_else(|| meta("twitter:title"))
+        .or_else(|| first("title").map(|title| clean_text(&title.text().collect::<String>())))
+        .filter(|title| !title.is_empty());
+    let description = meta("og:description")
+        .or_else(|| meta("twitter:description"))
+        .or_else(|| meta("description"));
+    let image_url = meta("og:image")
+        .or_else(|| meta("twitter:image"))
+        .and_then(|image| http_url(page_url, &image));
+    let oembed_url = first("link[rel=\"alternate\"][type=\"application/json+oembed\"]")
+        .and_then(|link| link.value().attr("href"))
+        .and_then(|href| http_url(page_url, href))
+        .and_then(|href| Url::parse(&href).ok());
+
+    let preview = LinkPreview {
+        url: page_url.to_string(),
+        title: title.map(|title| truncate(title, MAX_TITLE_CHARS)),
+        description: description.map(|description| truncate(description, MAX_DESCRIPTION_CHARS)),
+        image_url,
+        site_name: meta("og:site_name").map(|site_name| truncate(site_name, MAX_TITLE_CHARS)),
+    };
+
+    (preview, oembed_url)
+}
+
+/// The parts of an oEmbed response (https://oembed.com) a preview uses. The provider's `html`
+/// is never used, as it would embed third-party markup in the forum.
+#[derive(Deserialize)]
+struct OEmbed {
+    title: Option<String>,
+    provider_name: Option<String>,
+    thumbnail_url: Option<String>,
+}
+
+async fn fetch_oembed(url: &str) -> Result<OEmbed, LinkPreviewError> {
+    let (_, response) = get(url, "application/json").await?;
+    let body = read_body(response).await?;
+
+    serde_json::from_slice(&body).map_err(|err| LinkPreviewError::UnsupportedContentType(err.to_string()))
+}
+
+fn apply_oembed(preview: &mut LinkPreview, page_url: &Url, oembed: OEmbed) {
+    preview.title = preview
+        .title
+        .take()
+        .or_else(|| oembed.title.map(|title| truncate(clean_text(&title), MAX_TITLE_CHARS)));
+    preview.image_url = preview
+        .image_url
+        .take()
+        .or_else(|| oembed.thumbnail_url.and_then(|thumbnail| http_url(page_url, &thumbnail)));
+    preview.site_name = preview
+        .site_name
+        .take()
+        .or_else(|| oembed.provider_name.map(|name| truncate(clean_text(&name), MAX_TITLE_CHARS)));
+}
+
+/// Resolves a possibly relative URL found on the page, keeping only `http` and `https` ones.
+fn http_url(page_url: &Url, url: &str) -> Option<String> {
+    page_url
+        .join(url.trim())
+        .ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+        .map(String::from)
+}
+
+fn clean_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(text: String, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_page_should_prefer_open_graph_tags() {
+        let page_url = Url::parse("https://example.com/posts/1").unwrap();
+        let html = r#"<html><head>
+            <title>Fallback title</title>
+            <meta property="og:title" content="  Ownership
+                explained ">
+            <meta name="description" content="All about the borrow checker.">
+            <meta property="og:image" content="/images/cover.png">
+            <link rel="alternate" type="application/json+oembed" href="https://example.com/oembed?url=1">
+        </head><body></body></html>"#;
+
+        let (preview, oembed_url) = parse_page(&page_url, html);
+
+        assert_eq!(preview.title.as_deref(), Some("Ownership explained"));
+        assert_eq!(preview.description.as_deref(), Some("All about the borrow checker."));
+        assert_eq!(preview.image_url.as_deref(), Some("https://example.com/images/cover.png"));
+        assert_eq!(preview.site_name, None);
+        assert_eq!(oembed_url.map(String::from).as_deref(), Some("https://example.com/oembed?url=1"));
+    }
+
+    #[test]
+    fn parse_page_should_ignore_non_http_images() {
+        let page_url = Url::parse("https://example.com").unwrap();
+        let html = r#"<title>Hi</title><meta property="og:image" content="javascript:alert(1)">"#;
+
+        let (preview, _) = parse_page(&page_url, html);
+
+        assert_eq!(preview.title.as_deref(), Some("Hi"));
+        assert_eq!(preview.image_url, None);
+    }
+
+    #[test]
+    fn is_public_should_reject_internal_addresses() {
+        for address in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:10.0.0.1"] {
+            assert!(!is_public(address.parse().unwrap()), "{} should not be public", address);
+        }
+
+        for address in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public(address.parse().unwrap()), "{} should be public", address);
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_should_refuse_internal_hosts() {
+        let result = LinkPreviewFetcher.fetch("http://127.0.0.1:9/").await;
+
+        assert!(matches!(result, Err(LinkPreviewError::NonPublicAddress(_))));
+    }
+}
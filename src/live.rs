@@ -0,0 +1,208 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::broadcast;
+
+/// Events kept for a question's slowest subscriber; one that falls further behind skips ahead.
+const CHANNEL_CAPACITY: usize = 64;
+/// How long events stay available to clients that poll. Only each question's latest cursor is kept
+/// afterwards.
+const RECENT_EVENTS_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Something that changed on a question. Events only carry uuids: each subscriber reads the post
+/// as its own viewer, so posts it may not see are never sent to it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveEvent {
+    AnswerCreated { answer_uuid: String },
+    AnswerUpdated { answer_uuid: String },
+}
+
+/// A question's latest events, numbered from 1 by cursor.
+#[derive(Default)]
+struct RecentEvents {
+    cursor: u64,
+    events: VecDeque<(u64, Instant, LiveEvent)>,
+}
+
+/// The events of a question after a client's cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventsSince {
+    /// To poll from next.
+    pub cursor: u64,
+    pub events: Vec<LiveEvent>,
+    /// Events after the client's cursor that are no longer kept.
+    pub skipped: u64,
+}
+
+/// Broadcast channels of the questions being viewed, keyed by question uuid, and their latest
+/// events for clients that poll. Kept in memory, so clients only hear about changes made through
+/// the same server instance.
+#[derive(Default)]
+pub struct LiveUpdates {
+    channels: Mutex<HashMap<String, broadcast::Sender<LiveEvent>>>,
+    recent: Mutex<HashMap<String, RecentEvents>>,
+}
+
+impl LiveUpdates {
+    pub fn subscribe(&self, question_uuid: &str) -> broadcast::Receiver<LiveEvent> {
+        let mut channels = self.channels.lock().expect("live updates lock is not poisoned");
+
+        // Channels of questions nobody views anymore are dropped here rather than on unsubscribe.
+        channels.retain(|_, sender| sender.receiver_count() > 0);
+
+        channels
+            .entry(question_uuid.to_owned())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sent to the question's subscribers, if any, and kept for clients that poll.
+    pub fn publish(&self, question_uuid: &str, event: LiveEvent) {
+        {
+            let mut recent = self.recent.lock().expect("live updates lock is not poisoned");
+            let now = Instant::now();
+
+            for question in recent.values_mut() {
+                question.events.retain(|(_, published_at, _)| now.duration_since(*published_at) < RECENT_EVENTS_TTL);
+            }
+
+            let question = recent.entry(question_uuid.to_owned()).or_default();
+
+            question.cursor += 1;
+            question.events.push_back((question.cursor, now, event.clone()));
+
+            if question.events.len() > CHANNEL_CAPACITY {
+                question.events.pop_front();
+            }
+        }
+
+        let channels = self.channels.lock().expect("live updates lock is not poisoned");
+
+        if let Some(sender) = channels.get(question_uuid) {
+            let _ = sender.send(event);
+        }
+    }
+
+    /// The question's events after `since`. Without a cursor, or with one this instance never
+    /// returned, only the current cursor is returned.
+    pub fn events_since(&self, question_uuid: &str, since: Option<u64>) -> EventsSince {
+        let recent = self.recent.lock().expect("live updates lock is not poisoned");
+
+        let Some(question) = recent.get(question_uuid) else {
+            return EventsSince { cursor: 0, events: Vec::new(), skipped: 0 };
+        };
+
+        let since = since.filter(|since| *since <= question.cursor).unwrap_or(question.cursor);
+        let oldest = question.events.front().map_or(question.cursor + 1, |(cursor, _, _)| *cursor);
+
+        EventsSince {
+            cursor: question.cursor,
+            events: question
+                .events
+                .iter()
+                .filter(|(cursor, _, _)| *cursor > since)
+                .map(|(_, _, event)| event.clone())
+                .collect(),
+            skipped: oldest.saturating_sub(since + 1),
+        }
+    }
+
+    /// Like `events_since`, but waits up to `max_wait` for the next event when there is none yet.
+    pub async fn poll(&self, question_uuid: &str, since: Option<u64>, max_wait: Duration) -> EventsSince {
+        // Subscribed first, so that an event published right after the check still wakes us.
+        let mut receiver = self.subscribe(question_uuid);
+        let current = self.events_since(question_uuid, since);
+
+        if !current.events.is_empty() || current.skipped > 0 {
+            return current;
+        }
+
+        match tokio::time::timeout(max_wait, receiver.recv()).await {
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {
+                self.events_since(question_uuid, Some(current.cursor))
+            }
+            Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => current,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_only_deliver_events_of_the_subscribed_question() {
+        let live_updates = LiveUpdates::default();
+        let mut receiver = live_updates.subscribe("123");
+
+        live_updates.publish("456", LiveEvent::AnswerCreated { answer_uuid: "1".to_owned() });
+        live_updates.publish("123", LiveEvent::AnswerCreated { answer_uuid: "2".to_owned() });
+
+        assert_eq!(receiver.recv().await.unwrap(), LiveEvent::AnswerCreated { answer_uuid: "2".to_owned() });
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn events_since_should_return_newer_events_and_count_the_expired_ones() {
+        let live_updates = LiveUpdates::default();
+        let created = |answer_uuid: &str| LiveEvent::AnswerCreated { answer_uuid: answer_uuid.to_owned() };
+
+        assert_eq!(live_updates.events_since("123", None), EventsSince { cursor: 0, events: vec![], skipped: 0 });
+
+        for answer_uuid in 0..CHANNEL_CAPACITY + 2 {
+            live_updates.publish("123", created(&answer_uuid.to_string()));
+        }
+
+        let latest = CHANNEL_CAPACITY as u64 + 2;
+
+        assert_eq!(live_updates.events_since("123", None), EventsSince { cursor: latest, events: vec![], skipped: 0 });
+        assert_eq!(
+            live_updates.events_since("123", Some(latest - 1)),
+            EventsSince { cursor: latest, events: vec![created(&(CHANNEL_CAPACITY + 1).to_string())], skipped: 0 }
+        );
+
+        let lagged = live_updates.events_since("123", Some(0));
+
+        assert_eq!(lagged.events.len(), CHANNEL_CAPACITY);
+        assert_eq!(lagged.skipped, 2);
+
+        // A cursor from another instance starts over from the current one.
+        assert_eq!(live_updates.events_since("123", Some(latest + 10)).cursor, latest);
+    }
+
+    #[tokio::test]
+    async fn poll_should_wait_for_the_next_event_or_time_out_empty() {
+        let live_updates = std::sync::Arc::new(LiveUpdates::default());
+        let event = LiveEvent::AnswerUpdated { answer_uuid: "1".to_owned() };
+
+        let timed_out = live_updates.poll("123", None, Duration::from_millis(10)).await;
+
+        assert_eq!(timed_out, EventsSince { cursor: 0, events: vec![], skipped: 0 });
+
+        let poll = tokio::spawn({
+            let live_updates = live_updates.clone();
+            async move { live_updates.poll("123", Some(0), Duration::from_secs(5)).await }
+        });
+
+        while live_updates.channels.lock().unwrap().get("123").map_or(0, |sender| sender.receiver_count()) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        live_updates.publish("123", event.clone());
+
+        assert_eq!(poll.await.unwrap(), EventsSince { cursor: 1, events: vec![event], skipped: 0 });
+    }
+
+    #[test]
+    fn should_drop_channels_without_subscribers() {
+        let live_updates = LiveUpdates::default();
+
+        drop(live_updates.subscribe("123"));
+        let _receiver = live_updates.subscribe("456");
+
+        assert_eq!(live_updates.channels.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    io::Write,
+    net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use thiserror::Error;
+use time::OffsetDateTime;
+use tracing::Level;
+
+/// Records waiting to be sent. When the sink is slower than the server logs, newer records are
+/// dropped rather than blocking the request that logged them.
+const BUFFER_RECORDS: usize = 10_000;
+/// Larger datagrams may be fragmented or dropped on the way, so longer messages are cut.
+const MAX_UDP_MESSAGE_BYTES: usize = 8_000;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// After a failed connection, records are dropped for this long before reconnecting.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum LogShippingError {
+    #[error("Invalid log shipping configuration: {0}")]
+    InvalidConfig(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// RFC 5424.
+    Syslog,
+    /// GELF 1.1, as read by Graylog.
+    Gelf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogTransport {
+    Udp,
+    /// Syslog messages are framed by octet counting and GELF messages end with a null byte.
+    Tcp,
+}
+
+/// How records are written for the sink.
+#[derive(Debug, Clone)]
+struct Framing {
+    format: LogFormat,
+    transport: LogTransport,
+    hostname: String,
+}
+
+impl Framing {
+    fn frame(&self, level: Level, target: &str, message: &str, time: OffsetDateTime) -> Vec<u8> {
+        let message = match self.transport {
+            LogTransport::Udp => truncated(message, MAX_UDP_MESSAGE_BYTES),
+            LogTransport::Tcp => message,
+        };
+
+        match (self.format, self.transport) {
+            (LogFormat::Syslog, LogTransport::Udp) => self.syslog(level, target, message, time).into_bytes(),
+            (LogFormat::Syslog, LogTransport::Tcp) => {
+                let syslog = self.syslog(level, target, message, time);
+                format!("{} {}", syslog.len(), syslog).into_bytes()
+            }
+            (LogFormat::Gelf, LogTransport::Udp) => self.gelf(level, target, message, time).into_bytes(),
+            (LogFormat::Gelf, LogTransport::Tcp) => {
+                let mut gelf = self.gelf(level, target, message, time).into_bytes();
+                gelf.push(0);
+                gelf
+            }
+        }
+    }
+
+    fn syslog(&self, level: Level, target: &str, message: &str, time: OffsetDateTime) -> String {
+        // Facility 1, user-level messages.
+        let priority = 8 + severity(level);
+
+        format!(
+            "<{}>1 {} {} {} {} {} - {}",
+            priority,
+            timestamp(time),
+            self.hostname,
+            env!("CARGO_PKG_NAME"),
+            std::process::id(),
+            syslog_msgid(target),
+            message
+        )
+    }
+
+    fn gelf(&self, level: Level, target: &str, message: &str, time: OffsetDateTime) -> String {
+        json!({
+            "version": "1.1",
+            "host": self.hostname,
+            "short_message": message,
+            "timestamp": time.unix_timestamp_nanos() as f64 / 1e9,
+            "level": severity(level),
+            "_target": target,
+        })
+        .to_string()
+    }
+}
+
+/// Syslog severity of a log level.
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// MSGID is printable ASCII without spaces, at most 32 characters.
+fn syslog_msgid(target: &str) -> String {
+    let msgid: String = target.chars().filter(|c| c.is_ascii_graphic()).take(32).collect();
+
+    if msgid.is_empty() {
+        "-".to_owned()
+    } else {
+        msgid
+    }
+}
+
+fn timestamp(time: OffsetDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.millisecond()
+    )
+}
+
+fn truncated(message: &str, max_bytes: usize) -> &str {
+    if message.len() <= max_bytes {
+        return message;
+    }
+
+    let mut end = max_bytes;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &message[..end]
+}
+
+/// Records that did not reach the sink.
+#[derive(Default)]
+struct Counters {
+    /// The buffer was full.
+    dropped: AtomicU64,
+    /// The sink could not be reached.
+    failed: AtomicU64,
+}
+
+/// Sends log records to a syslog or GELF collector from a background thread, in addition to stdout.
+pub struct LogShipper {
+    framing: Framing,
+    sender: SyncSender<Vec<u8>>,
+    counters: Arc<Counters>,
+}
+
+/// Builds the shipper selected by `LOG_SINK`: `syslog`, `gelf` or `stdout` (the default), which
+/// ships nothing. `LOG_SINK_ADDR` is the collector's `host:port` and `LOG_SINK_PROTOCOL` is `udp`
+/// (the default) or `tcp`.
+pub fn shipper_from_env() -> Result<Option<LogShipper>, LogShippingError> {
+    let format = match std::env::var("LOG_SINK").as_deref() {
+        Err(_) | Ok("stdout") => return Ok(None),
+        Ok("syslog") => LogFormat::Syslog,
+        Ok("gelf") => LogFormat::Gelf,
+        Ok(other) => return Err(LogShippingError::InvalidConfig(format!("unknown LOG_SINK {}", other))),
+    };
+
+    let transport = match std::env::var("LOG_SINK_PROTOCOL").as_deref() {
+        Err(_) | Ok("udp") => LogTransport::Udp,
+        Ok("tcp") => LogTransport::Tcp,
+        Ok(other) => return Err(LogShippingError::InvalidConfig(format!("unknown LOG_SINK_PROTOCOL {}", other))),
+    };
+
+    let address = std::env::var("LOG_SINK_ADDR")
+        .map_err(|_| LogShippingError::InvalidConfig("LOG_SINK_ADDR must be set".to_owned()))?;
+
+    let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
+
+    Ok(Some(LogShipper::spawn(address, Framing { format, transport, hostname })))
+}
+
+impl LogShipper {
+    fn spawn(address: String, framing: Framing) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(BUFFER_RECORDS);
+        let counters = Arc::new(Counters::default());
+
+        let shipper = LogShipper { framing: framing.clone(), sender, counters: counters.clone() };
+
+        thread::Builder::new()
+            .name("log-shipper".to_owned())
+            .spawn(move || send_records(address, framing, receiver, counters))
+            .expect("Failed to start the log shipper thread!");
+
+        shipper
+    }
+
+    /// Queues the record without waiting; it is dropped and counted when the buffer is full.
+    pub fn ship(&self, level: Level, target: &str, message: &str) {
+        let frame = self.framing.frame(level, target, message, OffsetDateTime::now_utc());
+
+        match self.sender.try_send(frame) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+impl Connection {
+    fn open(address: &str, transport: LogTransport) -> std::io::Result<Connection> {
+        let address: SocketAddr = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "LOG_SINK_ADDR did not resolve"))?;
+
+        match transport {
+            LogTransport::Udp => {
+                let local: SocketAddr = if address.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+                let socket = UdpSocket::bind(local)?;
+
+                socket.connect(address)?;
+                Ok(Connection::Udp(socket))
+            }
+            LogTransport::Tcp => Ok(Connection::Tcp(TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?)),
+        }
+    }
+
+    fn send(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        match self {
+            Connection::Udp(socket) => socket.send(frame).map(|_| ()),
+            Connection::Tcp(stream) => stream.write_all(frame),
+        }
+    }
+}
+
+/// Runs until the shipper is dropped. The log macros cannot be used here: their records would be
+/// shipped again, so problems are reported to stderr and, once the sink is reachable, to the sink.
+fn send_records(address: String, framing: Framing, receiver: Receiver<Vec<u8>>, counters: Arc<Counters>) {
+    let mut connection: Option<Connection> = None;
+    let mut retry_at: Option<Instant> = None;
+    let mut reported = (0, 0);
+
+    for frame in receiver {
+        if connection.is_none() && retry_at.is_none_or(|retry_at| Instant::now() >= retry_at) {
+            match Connection::open(&address, framing.transport) {
+                Ok(opened) => {
+                    connection = Some(opened);
+                    retry_at = None;
+                }
+                Err(err) => {
+                    eprintln!("Failed to connect to log sink {}: {}", address, err);
+                    retry_at = Some(Instant::now() + RECONNECT_DELAY);
+                }
+            }
+        }
+
+        let Some(open) = connection.as_mut() else {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            continue;
+        };
+
+        let lost = (counters.dropped.load(Ordering::Relaxed), counters.failed.load(Ordering::Relaxed));
+
+        if lost != reported {
+            let message = format!(
+                "Log shipping lost {} records to a full buffer and {} to send failures so far.",
+                lost.0, lost.1
+            );
+
+            eprintln!("{}", message);
+
+            let report = framing.frame(Level::WARN, module_path!(), &message, OffsetDateTime::now_utc());
+
+            if open.send(&report).is_ok() {
+                reported = lost;
+            }
+        }
+
+        if let Err(err) = open.send(&frame) {
+            eprintln!("Failed to send log record to {}: {}", address, err);
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            connection = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framing(format: LogFormat, transport: LogTransport) -> Framing {
+        Framing { format, transport, hostname: "web-1".to_owned() }
+    }
+
+    fn time() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_792_231_200).unwrap()
+    }
+
+    #[test]
+    fn syslog_should_be_rfc_5424_and_octet_counted_over_tcp() {
+        let udp = framing(LogFormat::Syslog, LogTransport::Udp).frame(Level::ERROR, "forum::jobs", "Job failed", time());
+        let udp = String::from_utf8(udp).unwrap();
+
+        assert_eq!(
+            udp,
+            format!(
+                "<11>1 2026-10-17T10:00:00.000Z web-1 {} {} forum::jobs - Job failed",
+                env!("CARGO_PKG_NAME"),
+                std::process::id()
+            )
+        );
+
+        let tcp = framing(LogFormat::Syslog, LogTransport::Tcp).frame(Level::ERROR, "forum::jobs", "Job failed", time());
+
+        assert_eq!(String::from_utf8(tcp).unwrap(), format!("{} {}", udp.len(), udp));
+    }
+
+    #[test]
+    fn gelf_should_be_json_and_null_terminated_over_tcp() {
+        let tcp = framing(LogFormat::Gelf, LogTransport::Tcp).frame(Level::INFO, "forum", "Listening", time());
+
+        assert_eq!(tcp.last(), Some(&0));
+
+        let gelf: serde_json::Value = serde_json::from_slice(&tcp[..tcp.len() - 1]).unwrap();
+
+        assert_eq!(
+            gelf,
+            json!({
+                "version": "1.1",
+                "host": "web-1",
+                "short_message": "Listening",
+                "timestamp": 1_792_231_200.0,
+                "level": 6,
+                "_target": "forum",
+            })
+        );
+    }
+
+    #[test]
+    fn udp_messages_should_be_truncated_on_a_char_boundary() {
+        let message = "é".repeat(MAX_UDP_MESSAGE_BYTES);
+
+        assert_eq!(truncated(&message, MAX_UDP_MESSAGE_BYTES).len(), MAX_UDP_MESSAGE_BYTES);
+        assert_eq!(truncated(&message, 3), "é");
+        assert_eq!(truncated("short", 3), "sho");
+    }
+
+    #[test]
+    fn ship_should_count_records_dropped_by_a_full_buffer() {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let shipper = LogShipper { framing: framing(LogFormat::Gelf, LogTransport::Udp), sender, counters: Arc::default() };
+
+        shipper.ship(Level::INFO, "forum", "first");
+        shipper.ship(Level::INFO, "forum", "second");
+
+        assert_eq!(shipper.counters.dropped.load(Ordering::Relaxed), 1);
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn shipper_should_send_records_to_the_sink() {
+        let sink = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sink.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let shipper = LogShipper::spawn(sink.local_addr().unwrap().to_string(), framing(LogFormat::Syslog, LogTransport::Udp));
+
+        shipper.ship(Level::WARN, "forum", "Disk almost full");
+
+        let mut buffer = [0; 1024];
+        let received = sink.recv(&mut buffer).unwrap();
+
+        assert!(String::from_utf8_lossy(&buffer[..received]).ends_with("forum - Disk almost full"));
+    }
+}
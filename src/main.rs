@@ -1,65 +1,620 @@
 #[macro_use]
-extern crate log;
+extern crate tracing;
 
-extern crate pretty_env_logger;
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc, time::Duration};
 
-use std::sync::Arc;
-
-use axum::{
-    routing::{delete, get, post},
-    Router,
-};
+use axum::middleware;
 use dotenvy::dotenv;
+use tower_http::{
+    compression::{predicate::SizeAbove, CompressionLayer, DefaultPredicate, Predicate},
+    decompression::RequestDecompressionLayer,
+    trace::TraceLayer,
+};
 
 use persistance::{
     answers_dao::{AnswersDao, AnswersDaoImpl},
+    attachments_dao::{AttachmentsDao, AttachmentsDaoImpl},
+    audit_dao::{AuditDao, AuditDaoImpl},
+    boards_dao::{BoardsDao, BoardsDaoImpl},
+    cleanup_policies_dao::{CleanupPoliciesDao, CleanupPoliciesDaoImpl},
+    content_dao::{ContentDao, ContentDaoImpl},
+    dead_letters_dao::{DeadLettersDao, DeadLettersDaoImpl},
+    drafts_dao::{DraftsDao, DraftsDaoImpl},
+    email_digests_dao::{EmailDigestsDao, EmailDigestsDaoImpl},
+    erasure_dao::{ErasureDao, ErasureDaoImpl},
+    faq_dao::{FaqDao, FaqDaoImpl},
+    flags_dao::{FlagsDao, FlagsDaoImpl},
+    follows_dao::{FollowsDao, FollowsDaoImpl},
+    health_dao::{HealthDao, HealthDaoImpl},
+    import_dao::{ImportDao, ImportDaoImpl},
+    invitations_dao::{InvitationsDao, InvitationsDaoImpl},
+    jobs_dao::{JobsDao, JobsDaoImpl},
+    link_previews_dao::{LinkPreviewsDao, LinkPreviewsDaoImpl},
+    moderation_dao::{ModerationDao, ModerationDaoImpl},
+    notifications_dao::{NotificationsDao, NotificationsDaoImpl},
+    query_samples_dao::{QuerySamplesDao, QuerySamplesDaoImpl},
     questions_dao::{QuestionsDao, QuestionsDaoImpl},
+    replica::ReadReplica,
+    retention_dao::{RetentionDao, RetentionDaoImpl},
+    stats_dao::{StatsDao, StatsDaoImpl},
+    tags_dao::{TagsDao, TagsDaoImpl},
+    tenants_dao::{TenantsDao, TenantsDaoImpl},
+    users_dao::{UsersDao, UsersDaoImpl},
+    webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
 };
-use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::PgConnectOptions;
 
+use auth::{hash_api_token, AuthBackend, GroupRoleMap};
+use cache::{Cache, MemoryCache, QuestionCache, RedisCache, TtlCache};
+use config::Settings;
+use content_policy::ContentPolicy;
+use crypto::{FieldCipher, StaticKeyProvider};
+use diagnostics::{CountingAllocator, DiagnosticsProbe};
+use link_previews::LinkPreviewFetcher;
+use events::EventBus;
+use live::LiveUpdates;
+use mailer::Mailer;
+use query_log::QuerySampler;
+use rate_limit::{MemoryRateLimitStore, RateLimitStore, RateLimiter, RateLimits, RedisRateLimitStore};
+use scim::ScimConfig;
+use secrets::SecretsProvider;
+use signing::UrlSigner;
+use slo::SloTracker;
+use spam::SpamChecker;
+use storage::ObjectStore;
+use tenancy::TenantHosts;
+use views::ViewCounter;
+use webhooks::{DigestWebhook, EventWebhooks, SloAlertWebhook};
+
+mod auth;
+mod avatars;
+mod cache;
+mod config;
+mod content_policy;
+mod cors;
+mod crypto;
+mod diagnostics;
+mod etag;
+mod events;
+mod export;
+mod feeds;
 mod handlers;
+mod http_cache;
+mod import;
+mod jobs;
+mod json_api;
+mod language;
+mod ldap;
+mod link_previews;
+mod live;
+mod log_shipping;
+mod mailer;
+mod markdown;
+mod metrics;
 mod models;
+mod openapi;
 mod persistance;
+mod problem;
+mod query_log;
+mod rate_limit;
+mod redaction;
+mod routes;
+mod scim;
+mod secrets;
+mod shutdown;
+mod signing;
+mod similarity;
+mod sitemap;
+mod slo;
+mod spam;
+mod stack_exchange;
+mod storage;
+mod tenancy;
+mod validation;
+mod views;
+mod webhooks;
+
+use handlers::spawn_event_subscribers;
 
-use handlers::*;
+const TAG_SUGGESTIONS_TTL_SECONDS: u64 = 60;
+const TAG_SUGGESTIONS_CAPACITY: usize = 10_000;
+const TAG_SUGGEST_REQUESTS_PER_MINUTE: u32 = 60;
+const TAG_STATS_TTL_SECONDS: u64 = 5 * 60;
+const TAG_STATS_CAPACITY: usize = 1_000;
+const FAQ_TTL_SECONDS: u64 = 5 * 60;
+const FAQ_CAPACITY: usize = 1_000;
+/// Listing pages and questions kept in memory when `CACHE_REDIS_URL` is not set.
+const QUESTION_CACHE_CAPACITY: usize = 10_000;
+/// Smaller responses are sent uncompressed, since compressing them saves little or nothing.
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[derive(Clone)]
 pub struct AppState {
     pub questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
     pub answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+    pub attachments_dao: Arc<dyn AttachmentsDao + Send + Sync>,
+    pub audit_dao: Arc<dyn AuditDao + Send + Sync>,
+    pub boards_dao: Arc<dyn BoardsDao + Send + Sync>,
+    pub cleanup_policies_dao: Arc<dyn CleanupPoliciesDao + Send + Sync>,
+    pub content_dao: Arc<dyn ContentDao + Send + Sync>,
+    pub dead_letters_dao: Arc<dyn DeadLettersDao + Send + Sync>,
+    pub drafts_dao: Arc<dyn DraftsDao + Send + Sync>,
+    pub email_digests_dao: Arc<dyn EmailDigestsDao + Send + Sync>,
+    pub erasure_dao: Arc<dyn ErasureDao + Send + Sync>,
+    pub faq_dao: Arc<dyn FaqDao + Send + Sync>,
+    pub flags_dao: Arc<dyn FlagsDao + Send + Sync>,
+    pub follows_dao: Arc<dyn FollowsDao + Send + Sync>,
+    pub health_dao: Arc<dyn HealthDao + Send + Sync>,
+    pub import_dao: Arc<dyn ImportDao + Send + Sync>,
+    pub invitations_dao: Arc<dyn InvitationsDao + Send + Sync>,
+    pub jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+    pub link_previews_dao: Arc<dyn LinkPreviewsDao + Send + Sync>,
+    pub moderation_dao: Arc<dyn ModerationDao + Send + Sync>,
+    pub notifications_dao: Arc<dyn NotificationsDao + Send + Sync>,
+    pub query_samples_dao: Arc<dyn QuerySamplesDao + Send + Sync>,
+    pub retention_dao: Arc<dyn RetentionDao + Send + Sync>,
+    pub stats_dao: Arc<dyn StatsDao + Send + Sync>,
+    pub tags_dao: Arc<dyn TagsDao + Send + Sync>,
+    pub tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    pub users_dao: Arc<dyn UsersDao + Send + Sync>,
+    pub webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    pub url_signer: Arc<UrlSigner>,
+    /// Selected by `OBJECT_STORE`; local disk by default.
+    pub object_store: Arc<dyn ObjectStore>,
+    /// Selected by `SPAM_CHECKER`; heuristics by default.
+    pub spam_checker: Arc<dyn SpamChecker>,
+    /// From `CONTENT_POLICY_FILE` and `CONTENT_POLICY_MODE`.
+    pub content_policy: Arc<ContentPolicy>,
+    /// Directory used by `POST /sessions`; `None` unless `AUTH_BACKEND` selects one.
+    pub auth_backend: Option<Arc<dyn AuthBackend>>,
+    /// `None` unless `SCIM_TOKEN` is configured.
+    pub scim: Option<Arc<ScimConfig>>,
+    /// `None` unless `DIGEST_WEBHOOK_URL` is configured.
+    pub digest_webhook: Option<Arc<DigestWebhook>>,
+    /// From `necro_post_warn_after_days` and `necro_post_review`.
+    pub necro_post_policy: models::NecroPostPolicy,
+    /// From `similar_answer_threshold` and `similar_answer_review`.
+    pub similar_answer_policy: models::SimilarAnswerPolicy,
+    /// From `new_tag_min_reputation`.
+    pub new_tag_policy: models::NewTagPolicy,
+    /// From the `posting_quota_*` settings.
+    pub posting_quota_policy: models::PostingQuotaPolicy,
+    /// From `backup_retention_days`, for erasure reports.
+    pub backup_retention_days: Option<i32>,
+    /// From `audit_log_retention_days`, `ip_address_retention_days` and `soft_delete_retention_days`.
+    pub retention_policy: models::RetentionPolicy,
+    /// From `forum_url`, for links back to the forum.
+    pub forum_url: String,
+    /// `GET /tags/suggest` results by tenant and prefix.
+    pub tag_suggestions: Arc<TtlCache<Vec<models::TagUsage>>>,
+    pub tag_suggest_limiter: Arc<RateLimiter>,
+    pub tag_stats: Arc<TtlCache<models::TagStats>>,
+    /// `GET /faq` groups by tenant and grouping.
+    pub faq: Arc<TtlCache<Vec<models::FaqGroup>>>,
+    /// Questions and `GET /questions` pages as anonymous visitors see them, in Redis when
+    /// `CACHE_REDIS_URL` is set.
+    pub question_cache: Arc<QuestionCache>,
+    /// Answers posted or edited through this instance, for `GET /ws` and `GET /questions/:uuid/events`.
+    pub live_updates: Arc<LiveUpdates>,
+    /// Posts created or edited, for the subscribers started by `spawn_event_subscribers`.
+    pub events: Arc<EventBus>,
+    /// From `long_poll_max_wait_seconds`: how long `GET /questions/:uuid/poll` waits for an event.
+    pub long_poll_max_wait: Duration,
+    /// From `SLO_FILE`: request outcomes of the routes with a target, for `GET /admin/slo`.
+    pub slo_tracker: Arc<SloTracker>,
+    /// Internals for `GET /admin/diagnostics`; CPU profiles only with `profiling_enabled`.
+    pub diagnostics: Arc<DiagnosticsProbe>,
+    /// Picks the requests whose queries are recorded, at the rate set with `PUT /admin/query-sampling`.
+    pub query_sampler: Arc<QuerySampler>,
+    /// Question views read but not yet counted, for `jobs::spawn_view_counting`.
+    pub view_counter: Arc<ViewCounter>,
+    /// Token buckets of each client IP, in Redis when `RATE_LIMIT_REDIS_URL` is set.
+    pub rate_limits: Arc<RateLimits>,
+    /// From defaults, `CONFIG_FILE` and the environment, as loaded at startup.
+    pub settings: Arc<Settings>,
 }
 
 #[tokio::main]
 async fn main() {
-  pretty_env_logger::init();
+  redaction::init_tracing();
   dotenv().ok();
 
-  let pool = PgPoolOptions::new()
-      .max_connections(5)
-      .connect(&std::env::var("DATABASE_URL").expect("DATABASE_URL must be set."))
+  let settings = Settings::load().expect("Failed to load settings!");
+
+  persistance::retry::configure(settings.database_retry_policy());
+  persistance::circuit_breaker::configure(settings.database_circuit_breaker_policy());
+  persistance::timing::configure(settings.database_timing_policy());
+
+  let mut pool_options = settings.database_pool_options();
+
+  if settings.multi_tenancy_enabled {
+    pool_options = tenancy::with_tenant_hooks(pool_options);
+  }
+
+  let secrets = secrets::from_env()
+      .await
+      .expect("Failed to configure secrets provider!");
+
+  let connect_options: PgConnectOptions = secrets
+      .require("DATABASE_URL")
+      .await
+      .expect("DATABASE_URL must be set.")
+      .parse()
+      .expect("Invalid DATABASE_URL!");
+
+  let statement_timeout = settings.database_timing_policy().statement_timeout;
+
+  let pool = pool_options
+      .clone()
+      .connect_with(connect_options.options([("statement_timeout", statement_timeout.as_millis())]))
       .await
       .expect("Failed to create Postgres connection pool!");
 
-  let questions_dao = QuestionsDaoImpl::new(pool.clone());
-  let answers_dao = AnswersDaoImpl::new(pool.clone());
+  // Connected lazily, so that the server starts while the replica is down and reads from the primary.
+  let replica = secrets
+      .get("DATABASE_REPLICA_URL")
+      .await
+      .expect("Failed to read DATABASE_REPLICA_URL!")
+      .map(|url| {
+        let connect_options: PgConnectOptions = url.parse().expect("Invalid DATABASE_REPLICA_URL!");
+        let pool = pool_options
+            .clone()
+            .connect_lazy_with(connect_options.options([("statement_timeout", statement_timeout.as_millis())]));
+
+        Arc::new(ReadReplica::new(pool, settings.database_circuit_breaker_policy()))
+      });
+
+  let mut questions_dao = QuestionsDaoImpl::new(pool.clone());
+  let mut answers_dao = AnswersDaoImpl::new(pool.clone());
+  let mut stats_dao = StatsDaoImpl::new(pool.clone());
+
+  if let Some(replica) = replica {
+    questions_dao = questions_dao.with_replica(replica.clone());
+    answers_dao = answers_dao.with_replica(replica.clone());
+    stats_dao = stats_dao.with_replica(replica);
+  }
+
+  let attachments_dao = AttachmentsDaoImpl::new(pool.clone());
+  let audit_dao = AuditDaoImpl::new(pool.clone());
+  let boards_dao = BoardsDaoImpl::new(pool.clone());
+  let cleanup_policies_dao = CleanupPoliciesDaoImpl::new(pool.clone());
+  let content_dao = ContentDaoImpl::new(pool.clone());
+  let dead_letters_dao = DeadLettersDaoImpl::new(pool.clone());
+  let drafts_dao = DraftsDaoImpl::new(pool.clone());
+  let email_digests_dao = EmailDigestsDaoImpl::new(pool.clone());
+  let erasure_dao = ErasureDaoImpl::new(pool.clone());
+  let faq_dao = FaqDaoImpl::new(pool.clone());
+  let flags_dao = FlagsDaoImpl::new(pool.clone());
+  let follows_dao = FollowsDaoImpl::new(pool.clone());
+  let health_dao = HealthDaoImpl::new(pool.clone());
+  let import_dao = ImportDaoImpl::new(pool.clone());
+  let invitations_dao = InvitationsDaoImpl::new(pool.clone());
+  let jobs_dao = JobsDaoImpl::new(pool.clone());
+  let link_previews_dao = LinkPreviewsDaoImpl::new(pool.clone());
+  let moderation_dao = ModerationDaoImpl::new(pool.clone());
+  let notifications_dao = NotificationsDaoImpl::new(pool.clone());
+  let query_samples_dao = Arc::new(QuerySamplesDaoImpl::new(pool.clone()));
+  let retention_dao = RetentionDaoImpl::new(pool.clone());
+  let tags_dao = TagsDaoImpl::new(pool.clone());
+  let tenants_dao = TenantsDaoImpl::new(pool.clone());
+  let key_provider = StaticKeyProvider::parse(
+      &secrets.require("PII_ENCRYPTION_KEYS").await.expect("PII_ENCRYPTION_KEYS must be set."),
+    )
+    .expect("Failed to load PII encryption keys!");
+
+  let users_dao = UsersDaoImpl::new(pool.clone(), FieldCipher::new(Arc::new(key_provider)));
+  let webhooks_dao = WebhooksDaoImpl::new(pool.clone());
+
+  let url_signer = UrlSigner::parse(
+      &secrets.require("URL_SIGNING_KEYS").await.expect("URL_SIGNING_KEYS must be set."),
+    )
+    .expect("Failed to load URL signing keys!");
+
+  let scim = secrets
+      .get("SCIM_TOKEN")
+      .await
+      .expect("Failed to read SCIM_TOKEN!")
+      .map(|token| ScimConfig {
+        token_hash: hash_api_token(&token),
+        group_roles: GroupRoleMap::parse(&settings.scim_group_roles)
+          .expect("Failed to parse SCIM_GROUP_ROLES!"),
+      });
+
+  let object_store = storage::store_from_env(&secrets)
+      .await
+      .expect("Failed to configure object store!");
+
+  let rate_limit_store: Arc<dyn RateLimitStore + Send + Sync> = match secrets
+      .get("RATE_LIMIT_REDIS_URL")
+      .await
+      .expect("Failed to read RATE_LIMIT_REDIS_URL!")
+  {
+    Some(url) => Arc::new(RedisRateLimitStore::connect(&url).await.expect("Failed to connect to Redis!")),
+    None => Arc::new(MemoryRateLimitStore::new()),
+  };
+
+  let cache_store: Arc<dyn Cache + Send + Sync> = match secrets
+      .get("CACHE_REDIS_URL")
+      .await
+      .expect("Failed to read CACHE_REDIS_URL!")
+  {
+    Some(url) => Arc::new(RedisCache::connect(&url).await.expect("Failed to connect to Redis!")),
+    None => Arc::new(MemoryCache::new(QUESTION_CACHE_CAPACITY)),
+  };
+
+  let spam_checker = spam::checker_from_env(&secrets)
+      .await
+      .expect("Failed to configure spam checker!");
+
+  let mailer = mailer::mailer_from_env(&secrets)
+      .await
+      .expect("Failed to configure mailer!");
+
+  let content_policy = ContentPolicy::from_env().expect("Failed to load content policy!");
+
+  let slo_tracker = SloTracker::from_env().expect("Failed to load SLO targets!");
+
+  let auth_backend = auth::backend_from_env(&secrets)
+      .await
+      .expect("Failed to configure auth backend!");
+
+  let digest_webhook = match settings.digest_webhook_url.clone() {
+    Some(url) => {
+      let secret = secrets
+          .get("DIGEST_WEBHOOK_SECRET")
+          .await
+          .expect("Failed to read DIGEST_WEBHOOK_SECRET!");
+
+      Some(DigestWebhook::new(url, secret, Duration::from_secs(settings.digest_webhook_interval_seconds)))
+    }
+    None => None,
+  };
+
+  if std::env::args().nth(1).as_deref() == Some("rotate-pii-keys") {
+    let rotated = users_dao
+        .rotate_encryption_keys()
+        .await
+        .expect("Failed to rotate PII encryption keys!");
+
+    info!("Re-encrypted {} PII values under the current key.", rotated);
+    return;
+  }
+
+  let retention_policy = settings.retention_policy();
 
   let app_state = AppState {
     questions_dao: Arc::new(questions_dao),
     answers_dao: Arc::new(answers_dao),
+    attachments_dao: Arc::new(attachments_dao),
+    audit_dao: Arc::new(audit_dao),
+    boards_dao: Arc::new(boards_dao),
+    cleanup_policies_dao: Arc::new(cleanup_policies_dao),
+    content_dao: Arc::new(content_dao),
+    dead_letters_dao: Arc::new(dead_letters_dao),
+    drafts_dao: Arc::new(drafts_dao),
+    email_digests_dao: Arc::new(email_digests_dao),
+    erasure_dao: Arc::new(erasure_dao),
+    faq_dao: Arc::new(faq_dao),
+    flags_dao: Arc::new(flags_dao),
+    follows_dao: Arc::new(follows_dao),
+    health_dao: Arc::new(health_dao),
+    import_dao: Arc::new(import_dao),
+    invitations_dao: Arc::new(invitations_dao),
+    jobs_dao: Arc::new(jobs_dao),
+    link_previews_dao: Arc::new(link_previews_dao),
+    moderation_dao: Arc::new(moderation_dao),
+    notifications_dao: Arc::new(notifications_dao),
+    query_samples_dao: query_samples_dao.clone(),
+    retention_dao: Arc::new(retention_dao),
+    stats_dao: Arc::new(stats_dao),
+    tags_dao: Arc::new(tags_dao),
+    tenants_dao: Arc::new(tenants_dao),
+    users_dao: Arc::new(users_dao),
+    webhooks_dao: Arc::new(webhooks_dao),
+    url_signer: Arc::new(url_signer),
+    object_store: Arc::from(object_store),
+    spam_checker: Arc::from(spam_checker),
+    content_policy: Arc::new(content_policy),
+    auth_backend: auth_backend.map(Arc::from),
+    scim: scim.map(Arc::new),
+    digest_webhook: digest_webhook.map(Arc::new),
+    necro_post_policy: settings.necro_post_policy(),
+    similar_answer_policy: settings.similar_answer_policy(),
+    new_tag_policy: settings.new_tag_policy(),
+    posting_quota_policy: settings.posting_quota_policy(),
+    backup_retention_days: settings.backup_retention_days,
+    retention_policy,
+    forum_url: settings.forum_url.clone(),
+    tag_suggestions: Arc::new(TtlCache::new(TAG_SUGGESTIONS_TTL_SECONDS, TAG_SUGGESTIONS_CAPACITY)),
+    tag_suggest_limiter: Arc::new(RateLimiter::new(TAG_SUGGEST_REQUESTS_PER_MINUTE, 60)),
+    tag_stats: Arc::new(TtlCache::new(TAG_STATS_TTL_SECONDS, TAG_STATS_CAPACITY)),
+    faq: Arc::new(TtlCache::new(FAQ_TTL_SECONDS, FAQ_CAPACITY)),
+    question_cache: Arc::new(QuestionCache::new(
+      cache_store,
+      settings.questions_cache_ttl(),
+      settings.question_cache_ttl(),
+    )),
+    live_updates: Arc::new(LiveUpdates::default()),
+    events: Arc::new(EventBus::default()),
+    long_poll_max_wait: settings.long_poll_max_wait(),
+    slo_tracker: Arc::new(slo_tracker),
+    diagnostics: Arc::new(DiagnosticsProbe::new(
+      pool.clone(),
+      settings.profiling_enabled,
+    )),
+    query_sampler: Arc::new(QuerySampler::new(query_samples_dao)),
+    view_counter: Arc::new(ViewCounter::default()),
+    rate_limits: Arc::new(RateLimits::new(
+      settings.rate_limit,
+      settings.rate_limit_routes.clone(),
+      rate_limit_store,
+    )),
+    settings: Arc::new(settings),
   };
 
-  let app = Router::new()
-      .route("/question", post(create_question))
-      .route("/questions", get(read_questions))
-      .route("/question", delete(delete_question))
-      .route("/answer", post(create_answer))
-      .route("/answers", get(read_answers))
-      .route("/answer", delete(delete_answer))
-      .with_state(app_state);
+  let (shutdown, shutdown_signal) = shutdown::channel();
+
+  let mut background_tasks = spawn_event_subscribers(
+    &app_state.events,
+    app_state.notifications_dao.clone(),
+    app_state.live_updates.clone(),
+    app_state.webhooks_dao.clone(),
+    app_state.forum_url.clone(),
+    app_state.question_cache.clone(),
+  );
+
+  background_tasks.push(jobs::spawn_retention_purge(
+    app_state.questions_dao.clone(),
+    app_state.answers_dao.clone(),
+    app_state.retention_dao.clone(),
+    app_state.tenants_dao.clone(),
+    retention_policy,
+    shutdown_signal.clone(),
+  ));
+
+  background_tasks.push(jobs::spawn_job_worker(
+    app_state.jobs_dao.clone(),
+    app_state.questions_dao.clone(),
+    app_state.answers_dao.clone(),
+    app_state.import_dao.clone(),
+    app_state.tenants_dao.clone(),
+    app_state.object_store.clone(),
+    retention_policy.deleted_post_days,
+    shutdown_signal.clone(),
+  ));
+
+  background_tasks.extend([
+    jobs::spawn_link_preview_fetcher(
+      app_state.link_previews_dao.clone(),
+      Arc::new(LinkPreviewFetcher),
+      shutdown_signal.clone(),
+    ),
+    jobs::spawn_board_cleanup(
+      app_state.cleanup_policies_dao.clone(),
+      app_state.tenants_dao.clone(),
+      shutdown_signal.clone(),
+    ),
+    jobs::spawn_contest_judging(app_state.questions_dao.clone(), app_state.tenants_dao.clone(), shutdown_signal.clone()),
+    jobs::spawn_webhook_delivery(
+      app_state.webhooks_dao.clone(),
+      app_state.tenants_dao.clone(),
+      EventWebhooks::default(),
+      shutdown_signal.clone(),
+    ),
+    jobs::spawn_query_sampling(
+      app_state.query_sampler.clone(),
+      app_state.query_samples_dao.clone(),
+      shutdown_signal.clone(),
+    ),
+    jobs::spawn_view_counting(app_state.view_counter.clone(), app_state.stats_dao.clone(), shutdown_signal.clone()),
+  ]);
+
+  if let Some(url) = app_state.settings.slo_alert_webhook_url.clone() {
+    let secret = secrets
+        .get("SLO_ALERT_WEBHOOK_SECRET")
+        .await
+        .expect("Failed to read SLO_ALERT_WEBHOOK_SECRET!");
 
-  let listener = tokio::net::TcpListener::bind("127.0.0.1:8000")
+    background_tasks.push(jobs::spawn_slo_alerts(
+      app_state.slo_tracker.clone(),
+      Arc::new(SloAlertWebhook::new(url, secret)),
+      shutdown_signal.clone(),
+    ));
+  }
+
+  let mailer: Arc<dyn Mailer> = Arc::from(mailer);
+  background_tasks.push(jobs::spawn_email_delivery(
+    app_state.notifications_dao.clone(),
+    app_state.users_dao.clone(),
+    app_state.tenants_dao.clone(),
+    mailer.clone(),
+    app_state.forum_url.clone(),
+    shutdown_signal.clone(),
+  ));
+  background_tasks.push(jobs::spawn_email_digests(
+    app_state.email_digests_dao.clone(),
+    app_state.users_dao.clone(),
+    app_state.tenants_dao.clone(),
+    mailer,
+    app_state.forum_url.clone(),
+    shutdown_signal.clone(),
+  ));
+
+  background_tasks.push(jobs::spawn_accept_suggestions(
+    app_state.notifications_dao.clone(),
+    app_state.tenants_dao.clone(),
+    app_state.settings.accept_suggestion_thresholds(),
+    shutdown_signal.clone(),
+  ));
+
+  if let Some(digest_webhook) = &app_state.digest_webhook {
+    background_tasks.push(jobs::spawn_digest_webhook(
+      app_state.webhooks_dao.clone(),
+      app_state.dead_letters_dao.clone(),
+      digest_webhook.clone(),
+      shutdown_signal.clone(),
+    ));
+  }
+
+  let settings = app_state.settings.clone();
+  let tenants_dao = app_state.tenants_dao.clone();
+  let mut app = routes::router(app_state);
+
+  if settings.multi_tenancy_enabled {
+    app = app.layer(middleware::from_fn_with_state(Arc::new(TenantHosts::new(tenants_dao)), tenancy::tenant_middleware));
+  }
+
+  // Within the problem middleware, so that request spans carry the request id.
+  app = app.layer(TraceLayer::new_for_http().make_span_with(problem::request_span));
+
+  // Around the routes, so that their body limits count decompressed bytes, and within the problem
+  // middleware, so that bodies in an unsupported encoding get a 415 problem.
+  app = app.layer(RequestDecompressionLayer::new());
+
+  // Within the CORS layer, so that tenancy rejections are problems with a request id too.
+  app = app.layer(middleware::from_fn(problem::problem_middleware));
+
+  // Around the problem middleware, which reads the plain error bodies it turns into problems.
+  // Encodings are negotiated with Accept-Encoding; small bodies and event streams go as they are.
+  let compress_when = DefaultPredicate::new().and(SizeAbove::new(COMPRESSION_MIN_BYTES));
+  app = app.layer(CompressionLayer::new().compress_when(compress_when));
+
+  // Outermost, so that every response, problems included, can be read cross-origin.
+  if let Some(cors) = settings.cors_policy().layer().expect("Invalid CORS settings!") {
+    app = app.layer(cors);
+  }
+
+  let listener = tokio::net::TcpListener::bind(settings.bind_address())
       .await
-      .unwrap();
+      .expect("Failed to bind the listener!");
+
+  info!("Listening on {}.", settings.bind_address());
+
+  let grace_period = settings.shutdown_grace_period();
+
+  // Stops accepting connections on SIGTERM or SIGINT, then waits for the requests in flight.
+  let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+      .with_graceful_shutdown(async move {
+        shutdown::termination().await;
+        shutdown.request();
+      });
+
+  // Streams such as `GET /ws` stay open until the client leaves, so they only get the grace period.
+  let mut connections_closing = shutdown_signal;
+  tokio::select! {
+    result = server.into_future() => result.unwrap(),
+    _ = async {
+      connections_closing.requested().await;
+      tokio::time::sleep(grace_period).await;
+    } => warn!("Connections still open after {:?}, closing them.", grace_period),
+  }
+
+  // The handlers are gone with the server, so the event subscribers handle what is left and stop.
+  shutdown::drain(background_tasks, grace_period).await;
 
-  axum::serve(listener, app).await.unwrap();
+  pool.close().await;
+  info!("Closed the database connections.");
 }
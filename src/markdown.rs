@@ -0,0 +1,372 @@
+use std::sync::OnceLock;
+
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, TextMergeStream};
+use syntect::{
+    html::{ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::models::{AnswerDetail, BodyFormat, CodeBlock, QuestionDetail, QuestionFull};
+
+/// Prefix of the CSS classes on highlighted tokens, e.g. `hl-keyword hl-control`.
+const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+/// Further mentions in a post are ignored, so one post cannot notify the whole forum.
+pub const MAX_MENTIONS: usize = 20;
+/// Further links in a post get no preview.
+pub const MAX_LINK_PREVIEWS: usize = 5;
+const MAX_URL_LENGTH: usize = 2048;
+
+fn options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// The language of a fenced block is the first word of its info string, e.g. `rust` for ```` ```rust,ignore ````.
+fn language_of(info: &str) -> Option<String> {
+    info.split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .filter(|language| !language.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Renders CommonMark (plus tables and strikethrough) and sanitizes the result, so raw HTML,
+/// scripts and `javascript:` links in a post never reach the client. With `highlight`, fenced
+/// code blocks in a known language are tokenized into `<span>`s carrying `hl-` CSS classes.
+pub fn to_safe_html(markdown: &str, highlight: bool) -> String {
+    let mut unsafe_html = String::new();
+    let mut code: Option<(String, String)> = None;
+
+    let events = Parser::new_ext(markdown, options()).filter_map(|event| {
+        if let Some((_, source)) = code.as_mut() {
+            return match event {
+                Event::Text(text) => {
+                    source.push_str(&text);
+                    None
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    let (language, source) = code.take().expect("inside a highlighted code block");
+                    Some(Event::Html(CowStr::from(highlighted_block(&language, &source))))
+                }
+                event => Some(event),
+            };
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if highlight => {
+                match language_of(&info).filter(|language| syntax_set().find_syntax_by_token(language).is_some()) {
+                    Some(language) => {
+                        code = Some((language, String::new()));
+                        None
+                    }
+                    None => Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))),
+                }
+            }
+            event => Some(event),
+        }
+    });
+
+    html::push_html(&mut unsafe_html, events);
+
+    ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("span", &["class"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+fn highlighted_block(language: &str, source: &str) -> String {
+    let syntax = syntax_set()
+        .find_syntax_by_token(language)
+        .expect("only known languages are highlighted");
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        syntax_set(),
+        ClassStyle::SpacedPrefixed { prefix: HIGHLIGHT_CLASS_PREFIX },
+    );
+
+    for line in LinesWithEndings::from(source) {
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            // Fall back to the plain block rather than failing the whole post.
+            return format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>\n",
+                language,
+                html_escape(source)
+            );
+        }
+    }
+
+    format!(
+        "<pre><code class=\"language-{}\">{}</code></pre>\n",
+        language,
+        generator.finalize()
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Every code block in the post, in order, so clients can pick a highlighter per block.
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    Parser::new_ext(markdown, options())
+        .filter_map(|event| match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => Some(CodeBlock { language: language_of(&info) }),
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => Some(CodeBlock { language: None }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Usernames mentioned as `@username` in the prose of a post, in order and without duplicates.
+/// Code is skipped, and so are `@`s inside words such as email addresses. Only usernames made of
+/// letters, digits, `_`, `.` and `-` can be mentioned.
+pub fn mentions(markdown: &str) -> Vec<String> {
+    let mut usernames: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for event in TextMergeStream::new(Parser::new_ext(markdown, options())) {
+        let text = match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                continue;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                continue;
+            }
+            Event::Text(text) if !in_code_block => text,
+            _ => continue,
+        };
+        let mut previous = None;
+
+        for (index, c) in text.char_indices() {
+            let starts_mention = c == '@' && !previous.is_some_and(|previous| previous == '@' || is_username_char(previous));
+            previous = Some(c);
+
+            if !starts_mention {
+                continue;
+            }
+
+            let username = text[index + 1..]
+                .split(|c: char| !is_username_char(c))
+                .next()
+                .unwrap_or_default()
+                // Trailing punctuation ends the sentence, as in "thanks @jane."
+                .trim_end_matches(['.', '-']);
+
+            if !username.is_empty() && !usernames.iter().any(|known| known == username) {
+                usernames.push(username.to_owned());
+            }
+        }
+    }
+
+    usernames.truncate(MAX_MENTIONS);
+    usernames
+}
+
+/// The prose of a post without its code, as plain text for language detection.
+pub fn prose(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(prose) if !in_code_block => {
+                text.push_str(&prose);
+                text.push(' ');
+            }
+            _ => {}
+        }
+    }
+
+    text
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// The `http` and `https` URLs a post links to, in order and without duplicates: Markdown links,
+/// autolinks and bare URLs in the prose. URLs in code are skipped. Only the first
+/// `MAX_LINK_PREVIEWS` are returned.
+pub fn links(markdown: &str) -> Vec<String> {
+    let mut urls = all_links(markdown);
+
+    urls.truncate(MAX_LINK_PREVIEWS);
+    urls
+}
+
+/// How many different URLs a post links to, found like `links` but without its limit.
+pub fn link_count(markdown: &str) -> usize {
+    all_links(markdown).len()
+}
+
+fn all_links(markdown: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    let mut add = |url: &str| {
+        let is_http = ["http://", "https://"]
+            .iter()
+            .any(|scheme| url.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme)));
+
+        if is_http && url.len() <= MAX_URL_LENGTH && !urls.iter().any(|known| known == url) {
+            urls.push(url.to_owned());
+        }
+    };
+
+    for event in TextMergeStream::new(Parser::new_ext(markdown, options())) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Link { dest_url, .. }) => add(&dest_url),
+            Event::Text(text) if !in_code_block => {
+                for word in text.split_whitespace() {
+                    if let Some(start) = word.find("http://").or_else(|| word.find("https://")) {
+                        add(trim_url_punctuation(&word[start..]));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    urls
+}
+
+/// Drops punctuation that ends the sentence rather than the URL, as in "see https://example.com/a)."
+fn trim_url_punctuation(url: &str) -> &str {
+    let mut url = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+
+    while url.ends_with(')') && url.matches('(').count() < url.matches(')').count() {
+        url = url[..url.len() - 1].trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+    }
+
+    url
+}
+
+/// Fills in the HTML and code block annotations of question and answer bodies. The HTML is
+/// left out when the client asked for raw Markdown only.
+pub trait Render {
+    fn render(self, format: BodyFormat) -> Self;
+}
+
+fn html_of(markdown: &str, format: BodyFormat) -> Option<String> {
+    match format {
+        BodyFormat::Raw => None,
+        BodyFormat::Html => Some(to_safe_html(markdown, false)),
+        BodyFormat::Highlighted => Some(to_safe_html(markdown, true)),
+    }
+}
+
+impl Render for QuestionDetail {
+    fn render(self, format: BodyFormat) -> Self {
+        QuestionDetail {
+            description_html: html_of(&self.description, format),
+            code_blocks: code_blocks(&self.description),
+            ..self
+        }
+    }
+}
+
+impl Render for AnswerDetail {
+    fn render(self, format: BodyFormat) -> Self {
+        AnswerDetail {
+            content_html: html_of(&self.content, format),
+            code_blocks: code_blocks(&self.content),
+            ..self
+        }
+    }
+}
+
+impl Render for QuestionFull {
+    fn render(self, format: BodyFormat) -> Self {
+        QuestionFull {
+            question: self.question.render(format),
+            answers: self.answers.render(format),
+        }
+    }
+}
+
+impl<T: Render> Render for Vec<T> {
+    fn render(self, format: BodyFormat) -> Self {
+        self.into_iter().map(|item| item.render(format)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_safe_html_should_render_markdown() {
+        assert_eq!(
+            to_safe_html("Use `Vec<T>` **here**", false),
+            "<p>Use <code>Vec&lt;T&gt;</code> <strong>here</strong></p>\n"
+        );
+    }
+
+    #[test]
+    fn to_safe_html_should_strip_scripts_and_unsafe_links() {
+        let html = to_safe_html("<script>alert(1)</script>\n\n[click](javascript:alert(1)) <img src=x onerror=alert(1)>", false);
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onerror"));
+    }
+
+    #[test]
+    fn to_safe_html_should_highlight_known_languages_only() {
+        let html = to_safe_html("```rust\nfn main() {}\n```\n\n```nope\n<b>x</b>\n```", true);
+
+        assert!(html.contains("<code class=\"language-rust\"><span class=\"hl-source hl-rust\">"));
+        assert!(html.contains("<code class=\"language-nope\">&lt;b&gt;x&lt;/b&gt;\n</code>"));
+    }
+
+    #[test]
+    fn mentions_should_skip_code_and_email_addresses() {
+        let usernames = mentions("Thanks @jane_doe and @bob.\n\nMail jane@example.com, `@not_me`, @jane_doe again\n\n```\n@nope\n```");
+
+        assert_eq!(usernames, vec!["jane_doe".to_owned(), "bob".to_owned()]);
+    }
+
+    #[test]
+    fn links_should_find_links_and_bare_urls_outside_code() {
+        let urls = links(
+            "See [the book](https://doc.rust-lang.org/book/), <https://crates.io> and \
+             https://en.wikipedia.org/wiki/Rust_(programming_language).\n\n\
+             (also http://example.com/a), `https://in.code` ftp://example.com [x](https://crates.io)",
+        );
+
+        assert_eq!(
+            urls,
+            vec![
+                "https://doc.rust-lang.org/book/".to_owned(),
+                "https://crates.io".to_owned(),
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)".to_owned(),
+                "http://example.com/a".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_blocks_should_annotate_languages() {
+        let blocks = code_blocks("```Rust,ignore\nlet x = 1;\n```\n\n    indented\n\n```\nplain\n```");
+
+        assert_eq!(
+            blocks,
+            vec![
+                CodeBlock { language: Some("rust".to_owned()) },
+                CodeBlock { language: None },
+                CodeBlock { language: None },
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    sync::Mutex,
+    time::Duration,
+};
+
+use crate::models::PoolDiagnostics;
+
+/// Content type of `GET /metrics`: the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the buckets of the duration histograms, in seconds.
+const DURATION_BUCKETS: [f64; 12] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Durations of DAO operations by name, since the server started.
+static QUERY_DURATIONS: Mutex<BTreeMap<&'static str, Histogram>> = Mutex::new(BTreeMap::new());
+
+/// Waits for a pooled connection to begin a transaction on, since the server started.
+static POOL_ACQUIRE_DURATIONS: Mutex<Histogram> = Mutex::new(Histogram::new());
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Histogram {
+    /// Observations per bucket, each counted in the first bucket it fits.
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Histogram {
+            buckets: [0; DURATION_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    /// Renders the `_bucket`, `_sum` and `_count` series, with cumulative buckets as Prometheus
+    /// expects them. `labels` are comma-separated, or empty.
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let (bucket_labels, series_labels) = match labels {
+            "" => (String::new(), String::new()),
+            labels => (format!("{},", labels), format!("{{{}}}", labels)),
+        };
+        let mut cumulative = 0;
+
+        for (bound, count) in DURATION_BUCKETS.iter().zip(self.buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{{}le=\"{}\"}} {}", name, bucket_labels, bound, cumulative);
+        }
+
+        let _ = writeln!(out, "{}_bucket{{{}le=\"+Inf\"}} {}", name, bucket_labels, self.count);
+        let _ = writeln!(out, "{}_sum{} {}", name, series_labels, self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, series_labels, self.count);
+    }
+}
+
+/// Records how long the DAO operation `query` took, successful or not.
+pub fn observe_query(query: &'static str, duration: Duration) {
+    QUERY_DURATIONS
+      .lock()
+      .unwrap()
+      .entry(query)
+      .or_default()
+      .observe(duration.as_secs_f64());
+}
+
+/// Records how long it took to get a connection from the pool and begin a transaction on it.
+pub fn observe_pool_acquire(duration: Duration) {
+    POOL_ACQUIRE_DURATIONS.lock().unwrap().observe(duration.as_secs_f64());
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Every metric of this server instance, for `GET /metrics`, with the state of its connection pool
+/// as of `pool`.
+pub fn render(pool: &PoolDiagnostics) -> String {
+    let mut out = String::new();
+
+    gauge(&mut out, "db_pool_connections", "Open connections of the pool, idle or in use.", pool.size);
+    gauge(&mut out, "db_pool_idle_connections", "Open connections of the pool waiting to be used.", pool.idle);
+    gauge(&mut out, "db_pool_min_connections", "Connections the pool keeps open.", pool.min_connections);
+    gauge(&mut out, "db_pool_max_connections", "Connections the pool may open.", pool.max_connections);
+
+    out.push_str("# HELP db_pool_acquire_duration_seconds Time taken to get a connection and begin a transaction.\n");
+    out.push_str("# TYPE db_pool_acquire_duration_seconds histogram\n");
+    POOL_ACQUIRE_DURATIONS.lock().unwrap().render(&mut out, "db_pool_acquire_duration_seconds", "");
+
+    out.push_str("# HELP db_query_duration_seconds Time taken by each try of a database operation.\n");
+    out.push_str("# TYPE db_query_duration_seconds histogram\n");
+
+    for (query, histogram) in QUERY_DURATIONS.lock().unwrap().iter() {
+        histogram.render(&mut out, "db_query_duration_seconds", &format!("query=\"{}\"", query));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_should_render_cumulative_buckets() {
+        let mut histogram = Histogram::default();
+
+        histogram.observe(0.003);
+        histogram.observe(0.2);
+        histogram.observe(20.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "query=\"test\"");
+
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.001\"} 0\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.005\"} 1\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.25\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"10\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_seconds_count{query=\"test\"} 3\n"));
+    }
+
+    #[test]
+    fn histogram_should_render_without_labels() {
+        let mut histogram = Histogram::new();
+
+        histogram.observe(0.003);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "");
+
+        assert!(out.contains("test_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(out.contains("test_seconds_sum 0.003\n"));
+        assert!(out.contains("test_seconds_count 1\n"));
+    }
+}
@@ -1,57 +1,3082 @@
+use std::{fmt, net::IpAddr, str::FromStr};
+
 use thiserror::Error;
 use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime, PrimitiveDateTime};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::redaction::REDACTED;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default, ToSchema)]
 pub struct Question {
     pub title: String,
     pub description: String,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Required for private questions, which only members of this board can read.
+    #[serde(default)]
+    pub board_uuid: Option<Uuid>,
+    /// Lowercased on save; see `Question::MAX_TAGS` and `Question::MAX_TAG_CHARS`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only moderators can post announcements. Set when posting; edits keep the kind.
+    #[serde(default)]
+    pub kind: QuestionKind,
+    /// Makes the question a contest. Set when posting; edits keep it.
+    #[serde(default)]
+    pub contest: Option<Contest>,
+}
+
+impl Question {
+    pub const MAX_TAGS: usize = 5;
+    pub const MAX_TAG_CHARS: usize = 35;
+    pub const MAX_TITLE_CHARS: usize = 255;
+    pub const MAX_DESCRIPTION_CHARS: usize = 30_000;
+}
+
+/// Answers to a contest stay hidden from everyone but their authors and moderators for
+/// `answer_hours`. Answering then closes, and votes cast in the following `voting_hours` pick the
+/// winner: the answer with the highest score, the earliest on ties. Without an answer scoring above 0,
+/// the contest has no winner.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+pub struct Contest {
+  pub answer_hours: i32,
+  pub voting_hours: i32,
+}
+
+impl Contest {
+    pub const MAX_HOURS: i32 = 90 * 24;
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ContestDetail {
+  /// When answers are shown and voting starts.
+  #[serde(with = "time::serde::rfc3339")]
+  pub reveal_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339")]
+  pub ends_at: OffsetDateTime,
+  /// Set once the contest is decided; stays empty when no answer scored above 0.
+  pub winner_uuid: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
 pub struct QuestionDetail {
-    pub question_uuid: String,
+    pub question_uuid: Uuid,
     pub title: String,
     pub description: String,
-    pub created_at: String,
+    pub status: QuestionStatus,
+    pub status_reason: Option<String>,
+    pub kind: QuestionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contest: Option<ContestDetail>,
+    pub author_uuid: Option<Uuid>,
+    pub visibility: Visibility,
+    pub board_uuid: Option<Uuid>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// ISO 639-3 code of the language detected when the question was posted or last edited, if any.
+    pub language: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// When the question was last edited, closed, reopened or tagged, an answer to it accepted, or
+    /// its contest decided.
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// `description` rendered from Markdown and sanitized; left out with `?format=raw`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_html: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_blocks: Vec<CodeBlock>,
+    /// Previews of the links in `description` that have been fetched so far.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_previews: Vec<LinkPreview>,
+    /// Only set on a new question the spam checker held for moderation; it is left out of listings
+    /// until a moderator approves it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub held_for_review: bool,
+    /// Only set on a created or edited question: new tags that are added once a moderator approves
+    /// them; see `NewTagPolicy`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_tags: Vec<String>,
+}
+
+/// `?lang=` of question listings: an ISO 639-3 code such as `eng`, matched against
+/// `QuestionDetail::language`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LanguageQuery {
+  pub lang: Option<String>,
+}
+
+/// `?format=` of endpoints returning questions or answers. `html` (the default) returns the sanitized
+/// HTML next to the raw Markdown, `raw` only the Markdown, and `highlighted` also tokenizes fenced
+/// code blocks server-side so clients only need a stylesheet for the `hl-` classes.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyFormat {
+    Raw,
+    #[default]
+    Html,
+    Highlighted,
+}
+
+/// A code block of a question or answer body, in the order they appear.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, ToSchema)]
+pub struct CodeBlock {
+  /// First word of a fenced block's info string, lowercased; `None` for indented or unlabeled blocks.
+  pub language: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Title, description and image of a linked page, fetched by the server so clients do not have to
+/// contact third-party sites.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default, ToSchema)]
+pub struct LinkPreview {
+  pub url: String,
+  pub title: Option<String>,
+  pub description: Option<String>,
+  pub image_url: Option<String>,
+  pub site_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FormatQuery {
+  #[serde(default)]
+  pub format: BodyFormat,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct QuestionId {
-  pub question_uuid: String
+  pub question_uuid: Uuid
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuestionBatch {
+  pub question_uuids: Vec<Uuid>,
+}
+
+impl QuestionBatch {
+    pub const MAX_QUESTIONS: usize = 100;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuestionStatus {
+    Open,
+    ClosedDuplicate,
+    ClosedOffTopic,
+    /// Closed by the board's cleanup policy after a period without activity.
+    ClosedInactive,
+    Locked,
+}
+
+impl QuestionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionStatus::Open => "open",
+            QuestionStatus::ClosedDuplicate => "closed-duplicate",
+            QuestionStatus::ClosedOffTopic => "closed-off-topic",
+            QuestionStatus::ClosedInactive => "closed-inactive",
+            QuestionStatus::Locked => "locked",
+        }
+    }
+
+    pub fn accepts_answers(&self) -> bool {
+        *self == QuestionStatus::Open
+    }
+}
+
+impl FromStr for QuestionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(QuestionStatus::Open),
+            "closed-duplicate" => Ok(QuestionStatus::ClosedDuplicate),
+            "closed-off-topic" => Ok(QuestionStatus::ClosedOffTopic),
+            "closed-inactive" => Ok(QuestionStatus::ClosedInactive),
+            "locked" => Ok(QuestionStatus::Locked),
+            other => Err(format!("Unknown question status: {}", other)),
+        }
+    }
+}
+
+/// Announcements, such as forum rules or FAQs, never take answers.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuestionKind {
+    #[default]
+    Question,
+    Announcement,
+}
+
+impl QuestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionKind::Question => "question",
+            QuestionKind::Announcement => "announcement",
+        }
+    }
+
+    pub fn accepts_answers(&self) -> bool {
+        *self == QuestionKind::Question
+    }
+}
+
+impl FromStr for QuestionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "question" => Ok(QuestionKind::Question),
+            "announcement" => Ok(QuestionKind::Announcement),
+            other => Err(format!("Unknown question kind: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Visibility {
+    /// Listed and readable by everyone.
+    #[default]
+    Public,
+    /// Readable by anyone with the link, but left out of listings.
+    Unlisted,
+    /// Readable only by members of the question's board.
+    Private,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Private => "private",
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Visibility::Public),
+            "unlisted" => Ok(Visibility::Unlisted),
+            "private" => Ok(Visibility::Private),
+            other => Err(format!("Unknown visibility: {}", other)),
+        }
+    }
+}
+
+/// Who is reading; every read query in the DAOs uses it to enforce question visibility.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Viewer {
+    Anonymous,
+    User(Uuid),
+    /// Holder of a verified signed URL, which grants access whatever the visibility.
+    SignedLink,
+}
+
+impl From<Option<&UserDetail>> for Viewer {
+    fn from(user: Option<&UserDetail>) -> Self {
+        match user {
+            Some(user) => Viewer::User(user.user_uuid),
+            None => Viewer::Anonymous,
+        }
+    }
+}
+
+/// One version of a question; revision 1 is the original post.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+pub struct QuestionRevision {
+    pub question_uuid: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub description: String,
+    pub editor_uuid: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SignedUrlRequest {
+  #[serde(default = "SignedUrlRequest::default_expires_in_seconds")]
+  pub expires_in_seconds: u64,
+}
+
+impl SignedUrlRequest {
+    pub const MAX_EXPIRES_IN_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+    fn default_expires_in_seconds() -> u64 {
+        60 * 60
+    }
+}
+
+/// A relative URL that grants read access without authentication until `expires` (Unix seconds).
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+pub struct SignedUrl {
+  pub url: String,
+  pub expires: u64,
+}
+
+/// A file received by `POST /uploads`, optionally attached to one of the uploader's posts.
+pub struct Upload {
+  pub filename: String,
+  pub content: Vec<u8>,
+  pub question_uuid: Option<Uuid>,
+  pub answer_uuid: Option<Uuid>,
+}
+
+impl Upload {
+    pub const MAX_BYTES: usize = 10 * 1024 * 1024;
+    pub const MAX_FILENAME_CHARS: usize = 255;
+}
+
+/// A stored upload to record. `question_uuid` is also set for answer attachments.
+pub struct Attachment {
+  pub storage_key: String,
+  pub filename: String,
+  pub content_type: String,
+  pub size_bytes: i64,
+  pub question_uuid: Option<Uuid>,
+  pub answer_uuid: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+pub struct AttachmentDetail {
+  pub attachment_uuid: Uuid,
+  /// Object store key; internal, so it is never serialized.
+  #[serde(skip)]
+  pub storage_key: String,
+  pub filename: String,
+  pub content_type: String,
+  pub size_bytes: i64,
+  pub uploader_uuid: Option<Uuid>,
+  pub question_uuid: Option<Uuid>,
+  pub answer_uuid: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  /// Signed download link, added by the handlers that return one.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub download: Option<SignedUrl>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CloseQuestion {
+  pub status: QuestionStatus,
+  pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReopenQuestion {
+  pub reason: String,
 }
 
 // ----------
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct Answer {
-  pub question_uuid: String,
+  pub question_uuid: Uuid,
   pub content: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl Answer {
+    pub const MAX_CONTENT_CHARS: usize = 30_000;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
 pub struct AnswerDetail {
-  pub answer_uuid: String,
-  pub question_uuid: String,
+  pub answer_uuid: Uuid,
+  pub question_uuid: Uuid,
   pub content: String,
-  pub created_at: String,
+  pub author_uuid: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  /// `content` rendered from Markdown and sanitized; left out with `?format=raw`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content_html: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub code_blocks: Vec<CodeBlock>,
+  /// Previews of the links in `content` that have been fetched so far.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub link_previews: Vec<LinkPreview>,
+  /// Trust cues computed when answers are listed; left out of single-answer responses.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub signals: Option<AnswerSignals>,
+  /// Only set on a new answer, when its question is older than the `NecroPostPolicy` threshold.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub question_age_warning: bool,
+  /// Only set on a new answer that is a near-duplicate of this earlier answer to the same question;
+  /// see `SimilarAnswerPolicy`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub similar_answer_uuid: Option<Uuid>,
+  /// Only set on a new answer the spam checker held for moderation; it is left out of listings
+  /// until a moderator approves it.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub held_for_review: bool,
+}
+
+/// A question with its answers, for clients that show them together; see
+/// `GET /question/{uuid}/full`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuestionFull {
+  pub question: QuestionDetail,
+  /// In the requested order, each with its vote score in `signals`.
+  pub answers: Vec<AnswerDetail>,
+}
+
+/// Answers to questions older than `warn_after_days` are returned with `question_age_warning`;
+/// with `review`, they are also flagged for moderators.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NecroPostPolicy {
+  pub warn_after_days: i64,
+  pub review: bool,
+}
+
+impl Default for NecroPostPolicy {
+    fn default() -> Self {
+        NecroPostPolicy {
+            warn_after_days: 365,
+            review: false,
+        }
+    }
+}
+
+/// New answers whose similarity to an earlier answer on the question reaches `threshold`, from 0
+/// to 1, are returned with `similar_answer_uuid`; with `review`, they are also flagged for moderators.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarAnswerPolicy {
+  pub threshold: f64,
+  pub review: bool,
+}
+
+impl Default for SimilarAnswerPolicy {
+    fn default() -> Self {
+        SimilarAnswerPolicy {
+            threshold: 0.6,
+            review: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnswerSignals {
+  pub score: i64,
+  /// Share of the question's answers, in percent, that score no higher than this one.
+  pub score_percentile: i32,
+  /// `None` for answers posted anonymously.
+  pub author_reputation_band: Option<ReputationBand>,
+  /// Whether the answer was changed after it was posted.
+  pub edited: bool,
+  pub age_days: i32,
+}
+
+/// Coarse reputation of an author, from the total score of their answers.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReputationBand {
+    /// Below 10.
+    New,
+    /// 10 to 99.
+    Established,
+    /// 100 to 999.
+    Trusted,
+    /// 1000 and above.
+    Expert,
+}
+
+impl ReputationBand {
+    pub fn from_reputation(reputation: i64) -> Self {
+        match reputation {
+            i64::MIN..=9 => ReputationBand::New,
+            10..=99 => ReputationBand::Established,
+            100..=999 => ReputationBand::Trusted,
+            _ => ReputationBand::Expert,
+        }
+    }
+}
+
+/// Body of `PUT /question/:uuid/faq`: the answer on the question that goes in the FAQ.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FaqSelection {
+  pub answer_uuid: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FaqGrouping {
+    #[default]
+    Tag,
+    Board,
+}
+
+impl FaqGrouping {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaqGrouping::Tag => "tag",
+            FaqGrouping::Board => "board",
+        }
+    }
+}
+
+/// `?group_by=` of `GET /faq`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FaqQuery {
+  #[serde(default)]
+  pub group_by: FaqGrouping,
+}
+
+/// A curated question with the answer that resolves it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FaqEntry {
+  pub question_uuid: Uuid,
+  pub title: String,
+  pub description: String,
+  pub answer_uuid: Uuid,
+  pub answer: String,
+  pub tags: Vec<String>,
+  pub board_uuid: Option<Uuid>,
+  pub board_name: Option<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub curated_at: OffsetDateTime,
+}
+
+/// The FAQ entries of one tag or board. `name` is None for the entries without one, which are
+/// listed last. An entry with several tags is listed under each of them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FaqGroup {
+  pub name: Option<String>,
+  pub entries: Vec<FaqEntry>,
+}
+
+/// `GET /ws?question_uuid=` streams the live updates of one question.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LiveQuery {
+  pub question_uuid: Uuid,
+}
+
+/// A message of the `GET /ws` stream. Answers are sent as the viewer would list them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum LiveMessage {
+    AnswerCreated { answer: AnswerDetail },
+    AnswerUpdated { answer: AnswerDetail },
+    /// The client fell behind and missed `skipped` updates; it should reload the answers.
+    Lagged { skipped: u64 },
+}
+
+/// `GET /questions/:uuid/poll?since=`, with the cursor of the previous poll; omitted on the first.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LongPollQuery {
+  pub since: Option<u64>,
+}
+
+/// Messages after the cursor the client polled from; empty when the poll timed out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct LivePoll {
+  pub cursor: u64,
+  pub messages: Vec<LiveMessage>,
+}
+
+/// Order of `GET /answers`; `sort` is passed as a query parameter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerSort {
+    /// Highest score first, oldest first among ties.
+    Votes,
+    Newest,
+    #[default]
+    Oldest,
+    /// The accepted answer, then the rest by score.
+    AcceptedFirst,
+}
+
+impl AnswerSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnswerSort::Votes => "votes",
+            AnswerSort::Newest => "newest",
+            AnswerSort::Oldest => "oldest",
+            AnswerSort::AcceptedFirst => "accepted_first",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnswersQuery {
+  #[serde(default)]
+  pub sort: AnswerSort,
+  #[serde(default)]
+  pub format: BodyFormat,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct AnswerId {
-  pub answer_uuid: String
+  pub answer_uuid: Uuid
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AnswerUpdate {
+  pub content: String,
+}
+
+/// A user's vote on an answer: 1 up, -1 down, or 0 to withdraw it.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AnswerVote {
+  pub value: i16,
+}
+
+/// The score of an answer once a vote is recorded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnswerScore {
+  pub answer_uuid: Uuid,
+  pub score: i64,
+}
+
+/// The answer a question's author accepts, or `None` to withdraw the acceptance.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AcceptAnswer {
+  pub answer_uuid: Option<Uuid>,
+}
+
+/// One version of an answer; revision 1 is the original post.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnswerRevision {
+  pub answer_uuid: Uuid,
+  pub revision: i32,
+  pub content: String,
+  pub editor_uuid: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
 }
 
 // ----------
 
-#[derive(Error, Debug)]
-pub enum DBError {
-    #[error("Invalid UUID provided: {0}")]
-    InvalidUUID(String),
-    #[error("Database error occurred")]
-    Other(#[from] Box<dyn std::error::Error + Send + Sync>),
+/// UUIDs of questions or answers for a moderator to delete in one go.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BulkDelete {
+  pub uuids: Vec<String>,
+}
+
+impl BulkDelete {
+    pub const MAX_ITEMS: usize = 100;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BulkDeleteResult {
+  pub uuid: String,
+  pub deleted: bool,
+  /// Why the item was not deleted.
+  pub error: Option<String>,
+}
+
+/// What deleting a question took with it. Its answers, follows and tag proposals are soft-deleted
+/// along with it, and come back when it is restored, with the votes on those answers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuestionDeletion {
+  pub question_uuid: Uuid,
+  /// Answers deleted with the question; answers deleted before are not counted.
+  pub answers: i64,
+  /// Votes on those answers, which stop counting while they are deleted.
+  pub votes: i64,
+  /// Users who followed the question, and are not notified while it is deleted.
+  pub followers: i64,
+  /// New tags the question proposed, not up for approval while it is deleted.
+  pub pending_tags: i64,
+}
+
+// ----------
+
+/// `?q=` of tag autocompletion: the start of the tag name.
+#[derive(Serialize, Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TagSuggestQuery {
+  #[serde(default)]
+  pub q: String,
+}
+
+/// A tag with the number of public questions using it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagUsage {
+  pub name: String,
+  pub questions: i64,
+}
+
+impl TagUsage {
+    pub const MAX_SUGGESTIONS: i64 = 10;
+}
+
+/// Activity of a tag's public questions, for the tag's landing page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagStats {
+  pub name: String,
+  pub questions: i64,
+  /// Share of the questions without an answer, from 0 to 1.
+  pub unanswered_rate: f64,
+  /// Questions asked in each of the last `TagStats::WEEKS` weeks, oldest first, including this one.
+  pub questions_per_week: Vec<TagWeek>,
+  /// Users with the most answers to the questions, most first.
+  pub top_answerers: Vec<TagAnswerer>,
+}
+
+impl TagStats {
+    pub const WEEKS: i32 = 12;
+    pub const MAX_TOP_ANSWERERS: i64 = 5;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagWeek {
+  /// The Monday the week starts on, as `YYYY-MM-DD`.
+  pub week: String,
+  pub questions: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagAnswerer {
+  pub user_uuid: Uuid,
+  pub username: String,
+  pub answers: i64,
+}
+
+/// A public question of a tag with its accepted answer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagAcceptedAnswer {
+  pub question_uuid: Uuid,
+  pub title: String,
+  pub answer_uuid: Uuid,
+  pub answer: String,
+}
+
+/// `GET /tags/:name/kb-export`: the accepted answers of a tag compiled into documentation, one
+/// section per question by title.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct KbExport {
+  pub tag: String,
+  pub sections: Vec<KbSection>,
+}
+
+impl KbExport {
+    pub const MAX_SECTIONS: i64 = 500;
+}
+
+/// A question in `GET /feeds/questions.atom` or `GET /feeds/tag/:tag.atom`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FeedEntry {
+  pub question_uuid: Uuid,
+  pub title: String,
+  /// The question's description, as markdown.
+  pub summary: String,
+  /// Username of the author, if they still have an account.
+  pub author: Option<String>,
+  pub tags: Vec<String>,
+  /// RFC 3339, in UTC.
+  pub published: String,
+}
+
+impl FeedEntry {
+    /// Questions per feed, newest first.
+    pub const MAX_ENTRIES: i64 = 50;
+}
+
+/// A question listed in `GET /sitemaps/questions-:n.xml`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SitemapUrl {
+  pub question_uuid: Uuid,
+  /// When the question was last edited; W3C datetime, in UTC.
+  pub last_modified: String,
+}
+
+impl SitemapUrl {
+    /// The most URLs search engines accept in one sitemap.
+    pub const PER_SITEMAP: i64 = 50_000;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct KbSection {
+  pub question_uuid: Uuid,
+  pub answer_uuid: Uuid,
+  pub title: String,
+  /// Link to the question on `FORUM_URL`.
+  pub source_url: String,
+  /// The title as a heading, then the accepted answer and a link to its source.
+  pub markdown: String,
+}
+
+/// Tags a board's owners require on its questions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct BoardTagRules {
+  /// Questions must use at least one of these, unless there are none.
+  #[serde(default)]
+  pub required_tags: Vec<String>,
+  #[serde(default)]
+  pub forbidden_tags: Vec<String>,
+}
+
+impl BoardTagRules {
+    pub const MAX_TAGS: usize = 100;
+
+    /// Checks a question's normalized tags against the rules.
+    pub fn check(&self, tags: &[String]) -> Result<(), TagRuleViolation> {
+        if !self.required_tags.is_empty() && !tags.iter().any(|tag| self.required_tags.contains(tag)) {
+            return Err(TagRuleViolation {
+                code: TagRuleViolationCode::MissingRequiredTag,
+                message: "Questions on this board need at least one of these tags.".to_owned(),
+                tags: self.required_tags.clone(),
+            });
+        }
+
+        let forbidden: Vec<String> = tags.iter().filter(|tag| self.forbidden_tags.contains(tag)).cloned().collect();
+
+        if !forbidden.is_empty() {
+            return Err(TagRuleViolation {
+                code: TagRuleViolationCode::ForbiddenTag,
+                message: "These tags are not allowed on this board.".to_owned(),
+                tags: forbidden,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BoardTagRulesDetail {
+  pub board_uuid: Uuid,
+  pub required_tags: Vec<String>,
+  pub forbidden_tags: Vec<String>,
+  pub updated_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub updated_at: OffsetDateTime,
+}
+
+impl From<&BoardTagRulesDetail> for BoardTagRules {
+    fn from(detail: &BoardTagRulesDetail) -> Self {
+        BoardTagRules {
+            required_tags: detail.required_tags.clone(),
+            forbidden_tags: detail.forbidden_tags.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TagRuleViolationCode {
+    MissingRequiredTag,
+    ForbiddenTag,
+}
+
+/// Body of every error response, served as `application/problem+json` (RFC 7807). Errors clients
+/// are expected to handle have their own `type` and add members, e.g. the `code` of a conflict;
+/// other errors are `about:blank` with the status phrase as `title`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Problem {
+  #[serde(rename = "type")]
+  pub problem_type: String,
+  pub title: String,
+  pub status: u16,
+  pub detail: String,
+  /// Also sent as the `X-Request-Id` header; quote it when reporting an error.
+  pub request_id: Option<String>,
+}
+
+/// A request body field that failed validation, listed in the `errors` of a 422 problem.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+  pub field: String,
+  pub message: String,
+}
+
+/// Error body for a question whose tags break its board's rules. `tags` lists the options to
+/// choose from for a missing tag, and the offending tags for forbidden ones.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TagRuleViolation {
+  pub code: TagRuleViolationCode,
+  pub message: String,
+  pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictCode {
+    /// The question is an announcement.
+    AnswersDisabled,
+    /// The question is a contest whose answers were revealed.
+    ContestClosed,
+}
+
+/// Error body for a conflict clients are expected to handle, so they need not match on the message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ConflictDetail {
+  pub code: ConflictCode,
+  pub message: String,
+}
+
+/// Error body for a post refused by the content policy, with the blocked terms it contains.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ContentPolicyViolation {
+  pub message: String,
+  pub terms: Vec<String>,
+}
+
+/// Tags no question uses yet can be created by moderators and by users with at least
+/// `min_reputation`. Other users' new tags wait for a moderator as pending tags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewTagPolicy {
+  pub min_reputation: i64,
+}
+
+impl Default for NewTagPolicy {
+    /// The start of the `Trusted` reputation band.
+    fn default() -> Self {
+        NewTagPolicy {
+            min_reputation: 100,
+        }
+    }
+}
+
+/// A question or an answer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PostKind {
+    Question,
+    Answer,
+}
+
+/// Questions and answers an account can post per hour: `questions_per_hour` and `answers_per_hour`
+/// to start with, and one more of each per `reputation_per_extra_post` reputation. Moderators have
+/// no quota; anonymous posts are only rate limited per client IP.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostingQuotaPolicy {
+  pub questions_per_hour: i64,
+  pub answers_per_hour: i64,
+  pub reputation_per_extra_post: i64,
+}
+
+impl Default for PostingQuotaPolicy {
+    fn default() -> Self {
+        PostingQuotaPolicy {
+            questions_per_hour: 5,
+            answers_per_hour: 20,
+            reputation_per_extra_post: 25,
+        }
+    }
+}
+
+impl PostingQuotaPolicy {
+    pub const WINDOW_MINUTES: i32 = 60;
+
+    /// Posts of `kind` an author with `reputation` can make per window.
+    pub fn limit(&self, kind: PostKind, reputation: i64) -> i64 {
+        let base = match kind {
+            PostKind::Question => self.questions_per_hour,
+            PostKind::Answer => self.answers_per_hour,
+        };
+
+        base + reputation.max(0) / self.reputation_per_extra_post.max(1)
+    }
+
+    /// Reputation from which an author with `reputation` gets one more post per window.
+    pub fn next_limit_reputation(&self, reputation: i64) -> i64 {
+        let step = self.reputation_per_extra_post.max(1);
+
+        (reputation.max(0) / step + 1) * step
+    }
+}
+
+/// Error body for a post over its author's quota, answered with 429.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PostingQuotaExceeded {
+  pub message: String,
+  pub kind: PostKind,
+  /// Posts of `kind` allowed per window at the author's reputation.
+  pub limit: i64,
+  pub window_seconds: i64,
+  pub reputation: i64,
+  /// Reputation from which one more post per window is allowed.
+  pub next_limit_reputation: i64,
+  /// Until enough of the author's posts have left the window.
+  pub retry_after_seconds: u64,
+}
+
+/// A proposed tag and the questions it will be added to once approved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PendingTag {
+  pub name: String,
+  pub question_uuids: Vec<Uuid>,
+  /// When the tag was first proposed.
+  #[serde(with = "time::serde::rfc3339")]
+  pub requested_at: OffsetDateTime,
+}
+
+/// Outcome of approving a pending tag, or of rejecting it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PendingTagResolution {
+  pub name: String,
+  /// Questions the tag was added to, or whose request was dropped.
+  pub questions: u64,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlagReason {
+    Spam,
+    Offensive,
+    OffTopic,
+    LowQuality,
+    /// Requires `details` explaining the problem.
+    Other,
+    /// Raised by the forum itself for answers to old questions; see `NecroPostPolicy`.
+    OldQuestion,
+    /// Raised by the forum itself for near-duplicate answers; see `SimilarAnswerPolicy`.
+    DuplicateAnswer,
+}
+
+impl FlagReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::Spam => "spam",
+            FlagReason::Offensive => "offensive",
+            FlagReason::OffTopic => "off-topic",
+            FlagReason::LowQuality => "low-quality",
+            FlagReason::Other => "other",
+            FlagReason::OldQuestion => "old-question",
+            FlagReason::DuplicateAnswer => "duplicate-answer",
+        }
+    }
+
+    /// Reasons only the forum raises, which users cannot choose.
+    pub fn is_automatic(&self) -> bool {
+        matches!(self, FlagReason::OldQuestion | FlagReason::DuplicateAnswer)
+    }
+}
+
+impl FromStr for FlagReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spam" => Ok(FlagReason::Spam),
+            "offensive" => Ok(FlagReason::Offensive),
+            "off-topic" => Ok(FlagReason::OffTopic),
+            "low-quality" => Ok(FlagReason::LowQuality),
+            "other" => Ok(FlagReason::Other),
+            "old-question" => Ok(FlagReason::OldQuestion),
+            "duplicate-answer" => Ok(FlagReason::DuplicateAnswer),
+            other => Err(format!("Unknown flag reason: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlagStatus {
+    #[default]
+    Open,
+    /// A moderator agreed the content breaks the rules.
+    Upheld,
+    Dismissed,
+}
+
+impl FlagStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagStatus::Open => "open",
+            FlagStatus::Upheld => "upheld",
+            FlagStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl FromStr for FlagStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(FlagStatus::Open),
+            "upheld" => Ok(FlagStatus::Upheld),
+            "dismissed" => Ok(FlagStatus::Dismissed),
+            other => Err(format!("Unknown flag status: {}", other)),
+        }
+    }
+}
+
+/// A user's report that a question or answer needs a moderator's attention.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Flag {
+  pub reason: FlagReason,
+  #[serde(default)]
+  pub details: Option<String>,
+}
+
+impl Flag {
+    pub const MAX_DETAILS_CHARS: usize = 1000;
+}
+
+/// `question_uuid` is also set for flags on answers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FlagDetail {
+  pub flag_uuid: Uuid,
+  pub question_uuid: Uuid,
+  pub answer_uuid: Option<Uuid>,
+  pub reporter_uuid: Option<Uuid>,
+  pub reason: FlagReason,
+  pub details: Option<String>,
+  pub status: FlagStatus,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  pub resolved_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub resolved_at: Option<OffsetDateTime>,
+  pub resolution_note: Option<String>,
+}
+
+/// `?status=` of the moderation queue; open flags by default.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FlagsQuery {
+  #[serde(default)]
+  pub status: FlagStatus,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ResolveFlag {
+  pub status: FlagStatus,
+  #[serde(default)]
+  pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModerationQueueKind {
+    /// Both flagged and new-user posts.
+    #[default]
+    All,
+    /// Posts with open flags.
+    Flagged,
+    /// Posts by new users that no moderator has acted on yet.
+    NewUser,
+}
+
+impl ModerationQueueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationQueueKind::All => "all",
+            ModerationQueueKind::Flagged => "flagged",
+            ModerationQueueKind::NewUser => "new-user",
+        }
+    }
+}
+
+/// `?kind=&reason=` of the moderation queue. `reason` only keeps posts with an open flag for it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ModerationQueueQuery {
+  #[serde(default)]
+  pub kind: ModerationQueueKind,
+  #[serde(default)]
+  pub reason: Option<FlagReason>,
+}
+
+/// A post waiting for review; `content` is the question's description or the answer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ModerationItem {
+  pub question_uuid: Uuid,
+  pub answer_uuid: Option<Uuid>,
+  pub author_uuid: Option<Uuid>,
+  pub title: String,
+  pub content: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  pub open_flags: i64,
+  pub flag_reasons: Vec<FlagReason>,
+  /// The author's account was less than `ModerationItem::NEW_USER_DAYS` old when they posted.
+  pub new_author: bool,
+}
+
+impl ModerationItem {
+    pub const NEW_USER_DAYS: i32 = 7;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModerationActionKind {
+    /// Keeps the post as it is and dismisses its open flags.
+    Approve,
+    Delete,
+    /// Replaces the answer, or the question's title and description, with `title` and `content`.
+    Edit,
+    /// Locks the question, or the answer's question.
+    Lock,
+    /// Notifies the author that the post breaks the rules.
+    Warn,
+}
+
+impl ModerationActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationActionKind::Approve => "approve",
+            ModerationActionKind::Delete => "delete",
+            ModerationActionKind::Edit => "edit",
+            ModerationActionKind::Lock => "lock",
+            ModerationActionKind::Warn => "warn",
+        }
+    }
+
+    /// Every action but approval agrees with the post's flags.
+    pub fn resolves_flags_as(&self) -> FlagStatus {
+        match self {
+            ModerationActionKind::Approve => FlagStatus::Dismissed,
+            _ => FlagStatus::Upheld,
+        }
+    }
+}
+
+impl FromStr for ModerationActionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "approve" => Ok(ModerationActionKind::Approve),
+            "delete" => Ok(ModerationActionKind::Delete),
+            "edit" => Ok(ModerationActionKind::Edit),
+            "lock" => Ok(ModerationActionKind::Lock),
+            "warn" => Ok(ModerationActionKind::Warn),
+            other => Err(format!("Unknown moderation action: {}", other)),
+        }
+    }
+}
+
+/// A moderator's review of a question, or of its answer `answer_uuid`.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ModerationAction {
+  pub question_uuid: Uuid,
+  #[serde(default)]
+  pub answer_uuid: Option<Uuid>,
+  pub action: ModerationActionKind,
+  pub reason: String,
+  /// New question title, for edits of questions.
+  #[serde(default)]
+  pub title: Option<String>,
+  /// New question description or answer content, for edits.
+  #[serde(default)]
+  pub content: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ModerationActionDetail {
+  pub action_uuid: Uuid,
+  pub moderator_uuid: Option<Uuid>,
+  pub action: ModerationActionKind,
+  pub question_uuid: Uuid,
+  pub answer_uuid: Option<Uuid>,
+  pub reason: String,
+  /// How many open flags on the post the action resolved.
+  pub resolved_flags: i64,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+// ----------
+
+/// A privileged change recorded in the audit log.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    DeleteQuestion,
+    DeleteAnswer,
+    DeleteQuestions,
+    DeleteAnswers,
+    CloseQuestion,
+    ReopenQuestion,
+    RestoreQuestion,
+    RestoreAnswer,
+    ResolveFlag,
+    ModeratePost,
+    CreateInvitation,
+    CreateJob,
+    RetryDeadLetters,
+    PurgeDeadLetters,
+    SetCleanupPolicy,
+    DeleteCleanupPolicy,
+    ApproveTag,
+    RejectTag,
+    ShadowBanUser,
+    LiftShadowBan,
+    EraseUser,
+    SetFaqEntry,
+    RemoveFaqEntry,
+    CreateWebhook,
+    DeleteWebhook,
+    SetQuerySampling,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::DeleteQuestion => "delete-question",
+            AuditAction::DeleteAnswer => "delete-answer",
+            AuditAction::DeleteQuestions => "delete-questions",
+            AuditAction::DeleteAnswers => "delete-answers",
+            AuditAction::CloseQuestion => "close-question",
+            AuditAction::ReopenQuestion => "reopen-question",
+            AuditAction::RestoreQuestion => "restore-question",
+            AuditAction::RestoreAnswer => "restore-answer",
+            AuditAction::ResolveFlag => "resolve-flag",
+            AuditAction::ModeratePost => "moderate-post",
+            AuditAction::CreateInvitation => "create-invitation",
+            AuditAction::CreateJob => "create-job",
+            AuditAction::RetryDeadLetters => "retry-dead-letters",
+            AuditAction::PurgeDeadLetters => "purge-dead-letters",
+            AuditAction::SetCleanupPolicy => "set-cleanup-policy",
+            AuditAction::DeleteCleanupPolicy => "delete-cleanup-policy",
+            AuditAction::ApproveTag => "approve-tag",
+            AuditAction::RejectTag => "reject-tag",
+            AuditAction::ShadowBanUser => "shadow-ban-user",
+            AuditAction::LiftShadowBan => "lift-shadow-ban",
+            AuditAction::EraseUser => "erase-user",
+            AuditAction::SetFaqEntry => "set-faq-entry",
+            AuditAction::RemoveFaqEntry => "remove-faq-entry",
+            AuditAction::CreateWebhook => "create-webhook",
+            AuditAction::DeleteWebhook => "delete-webhook",
+            AuditAction::SetQuerySampling => "set-query-sampling",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delete-question" => Ok(AuditAction::DeleteQuestion),
+            "delete-answer" => Ok(AuditAction::DeleteAnswer),
+            "delete-questions" => Ok(AuditAction::DeleteQuestions),
+            "delete-answers" => Ok(AuditAction::DeleteAnswers),
+            "close-question" => Ok(AuditAction::CloseQuestion),
+            "reopen-question" => Ok(AuditAction::ReopenQuestion),
+            "restore-question" => Ok(AuditAction::RestoreQuestion),
+            "restore-answer" => Ok(AuditAction::RestoreAnswer),
+            "resolve-flag" => Ok(AuditAction::ResolveFlag),
+            "moderate-post" => Ok(AuditAction::ModeratePost),
+            "create-invitation" => Ok(AuditAction::CreateInvitation),
+            "create-job" => Ok(AuditAction::CreateJob),
+            "retry-dead-letters" => Ok(AuditAction::RetryDeadLetters),
+            "purge-dead-letters" => Ok(AuditAction::PurgeDeadLetters),
+            "set-cleanup-policy" => Ok(AuditAction::SetCleanupPolicy),
+            "delete-cleanup-policy" => Ok(AuditAction::DeleteCleanupPolicy),
+            "approve-tag" => Ok(AuditAction::ApproveTag),
+            "reject-tag" => Ok(AuditAction::RejectTag),
+            "shadow-ban-user" => Ok(AuditAction::ShadowBanUser),
+            "lift-shadow-ban" => Ok(AuditAction::LiftShadowBan),
+            "erase-user" => Ok(AuditAction::EraseUser),
+            "set-faq-entry" => Ok(AuditAction::SetFaqEntry),
+            "remove-faq-entry" => Ok(AuditAction::RemoveFaqEntry),
+            "create-webhook" => Ok(AuditAction::CreateWebhook),
+            "delete-webhook" => Ok(AuditAction::DeleteWebhook),
+            "set-query-sampling" => Ok(AuditAction::SetQuerySampling),
+            other => Err(format!("Unknown audit action: {}", other)),
+        }
+    }
+}
+
+/// What kind of record an audited action changed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditTarget {
+    Question,
+    Answer,
+    Flag,
+    Invitation,
+    Job,
+    DeadLetter,
+    Board,
+    /// Identified by its name.
+    Tag,
+    User,
+    Webhook,
+    /// A server-wide setting, identified by the action.
+    Setting,
+}
+
+impl AuditTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditTarget::Question => "question",
+            AuditTarget::Answer => "answer",
+            AuditTarget::Flag => "flag",
+            AuditTarget::Invitation => "invitation",
+            AuditTarget::Job => "job",
+            AuditTarget::DeadLetter => "dead-letter",
+            AuditTarget::Board => "board",
+            AuditTarget::Tag => "tag",
+            AuditTarget::User => "user",
+            AuditTarget::Webhook => "webhook",
+            AuditTarget::Setting => "setting",
+        }
+    }
+}
+
+impl FromStr for AuditTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "question" => Ok(AuditTarget::Question),
+            "answer" => Ok(AuditTarget::Answer),
+            "flag" => Ok(AuditTarget::Flag),
+            "invitation" => Ok(AuditTarget::Invitation),
+            "job" => Ok(AuditTarget::Job),
+            "dead-letter" => Ok(AuditTarget::DeadLetter),
+            "board" => Ok(AuditTarget::Board),
+            "tag" => Ok(AuditTarget::Tag),
+            "user" => Ok(AuditTarget::User),
+            "webhook" => Ok(AuditTarget::Webhook),
+            "setting" => Ok(AuditTarget::Setting),
+            other => Err(format!("Unknown audit target: {}", other)),
+        }
+    }
+}
+
+/// One action to append to the audit log. `target_uuid` is left out for actions on several
+/// records, which list them in `payload` instead. Tags are targeted by name rather than a UUID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+  pub actor_uuid: Uuid,
+  pub action: AuditAction,
+  pub target_type: AuditTarget,
+  pub target_uuid: Option<String>,
+  pub payload: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AuditRecord {
+  pub audit_uuid: Uuid,
+  /// Kept as it was after the actor's account is deleted.
+  pub actor_uuid: Option<Uuid>,
+  pub action: AuditAction,
+  pub target_type: AuditTarget,
+  /// The name of the tag for tag targets.
+  pub target_uuid: Option<String>,
+  pub payload: serde_json::Value,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+/// `?since=` of the audit log: an RFC 3339 timestamp, entries at or after it are listed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+  #[serde(default)]
+  pub since: Option<String>,
+}
+
+/// `?columns=&from=&to=` of CSV exports: a comma-separated list of the columns to export, all of
+/// them by default, and RFC 3339 timestamps bounding when the posts were created, `from` included
+/// and `to` excluded.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+  #[serde(default)]
+  pub columns: Option<String>,
+  #[serde(default)]
+  pub from: Option<String>,
+  #[serde(default)]
+  pub to: Option<String>,
+}
+
+/// Posts created from `from` and before `to`, without a bound on a side left unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExportRange {
+  pub from: Option<PrimitiveDateTime>,
+  pub to: Option<PrimitiveDateTime>,
+}
+
+// ----------
+
+/// `?offset=&limit=` of listings that can grow without bound, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+  #[serde(default)]
+  pub offset: u32,
+  #[serde(default = "Pagination::default_limit")]
+  pub limit: u32,
+}
+
+impl Pagination {
+    pub const MAX_LIMIT: u32 = 100;
+
+    fn default_limit() -> u32 {
+        20
+    }
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Pagination {
+            offset: 0,
+            limit: Pagination::default_limit(),
+        }
+    }
+}
+
+// ----------
+
+/// Payload of the digest webhook: everything posted publicly after `since`, up to and including `until`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct WebhookDigest {
+  pub since: String,
+  pub until: String,
+  pub questions: Vec<QuestionDetail>,
+  pub answers: Vec<AnswerDetail>,
+}
+
+impl WebhookDigest {
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty() && self.answers.is_empty()
+    }
+}
+
+/// Post events an outgoing webhook can subscribe to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    QuestionCreated,
+    AnswerCreated,
+    AnswerUpdated,
+    /// Only sent to a user's own webhook, for the notifications they send to it.
+    Notification,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::QuestionCreated => "question_created",
+            WebhookEvent::AnswerCreated => "answer_created",
+            WebhookEvent::AnswerUpdated => "answer_updated",
+            WebhookEvent::Notification => "notification",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "question_created" => Ok(WebhookEvent::QuestionCreated),
+            "answer_created" => Ok(WebhookEvent::AnswerCreated),
+            "answer_updated" => Ok(WebhookEvent::AnswerUpdated),
+            "notification" => Ok(WebhookEvent::Notification),
+            other => Err(format!("Unknown webhook event: {}", other)),
+        }
+    }
+}
+
+/// An endpoint to register for post events. Deliveries are signed like the digest webhook's, with
+/// a secret generated on registration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct NewWebhook {
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+}
+
+impl NewWebhook {
+    pub const MAX_URL_LENGTH: usize = 2048;
+}
+
+/// A registered webhook. `secret` is only returned once, when the webhook is registered.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct WebhookDetail {
+  pub webhook_uuid: Uuid,
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub secret: Option<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    /// Claimed by the delivery worker.
+    Sending,
+    Delivered,
+    /// Given up once its retries were exhausted.
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Sending => "sending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for WebhookDeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(WebhookDeliveryStatus::Pending),
+            "sending" => Ok(WebhookDeliveryStatus::Sending),
+            "delivered" => Ok(WebhookDeliveryStatus::Delivered),
+            "failed" => Ok(WebhookDeliveryStatus::Failed),
+            other => Err(format!("Unknown webhook delivery status: {}", other)),
+        }
+    }
+}
+
+/// An entry of a webhook's delivery log. `response_status` and `last_error` are those of the
+/// latest attempt.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct WebhookDelivery {
+  pub delivery_uuid: Uuid,
+  pub event: WebhookEvent,
+  pub status: WebhookDeliveryStatus,
+  pub attempts: i32,
+  pub response_status: Option<i32>,
+  pub last_error: Option<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub next_attempt_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub delivered_at: Option<OffsetDateTime>,
+}
+
+/// A delivery claimed by the webhook delivery worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingWebhookDelivery {
+  pub delivery_uuid: Uuid,
+  pub url: String,
+  pub secret: String,
+  pub event: WebhookEvent,
+  pub payload: serde_json::Value,
+  /// Failed attempts so far.
+  pub attempts: i32,
+}
+
+/// What a dead letter holds, which decides how it is retried.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterKind {
+    /// A `WebhookDigest` the digest webhook kept rejecting.
+    WebhookDigest,
+}
+
+impl DeadLetterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterKind::WebhookDigest => "webhook_digest",
+        }
+    }
+}
+
+impl FromStr for DeadLetterKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "webhook_digest" => Ok(DeadLetterKind::WebhookDigest),
+            other => Err(format!("Unknown dead letter kind: {}", other)),
+        }
+    }
+}
+
+/// An item that failed permanently, with one error per failed attempt, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeadLetter {
+  pub dead_letter_uuid: Uuid,
+  pub kind: DeadLetterKind,
+  pub payload: serde_json::Value,
+  pub attempts: i32,
+  pub errors: Vec<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339")]
+  pub last_failed_at: OffsetDateTime,
+}
+
+/// Dead letters an admin wants to retry or purge.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterSelection {
+  pub dead_letter_uuids: Vec<Uuid>,
+}
+
+/// What a right-to-be-forgotten erasure did to the rows referencing the user.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErasureAction {
+    /// Deleted with the user.
+    Purged,
+    /// Kept with the reference to the user cleared, such as the author of a post.
+    Anonymized,
+    /// Kept unchanged, such as the append-only audit log.
+    Retained,
+}
+
+/// One column checked for references to the erased user, and how many rows it had.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ErasureCheck {
+  pub table: String,
+  pub column: String,
+  pub action: ErasureAction,
+  pub rows: i64,
+}
+
+/// Backups are not rewritten, so the user's data only leaves them once they expire.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ErasureBackups {
+  /// `BACKUP_RETENTION_DAYS`, when configured.
+  pub retention_days: Option<i32>,
+  /// When the last backup taken before the erasure expires.
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub contains_data_until: Option<OffsetDateTime>,
+  pub note: String,
+}
+
+impl ErasureBackups {
+    pub fn new(retention_days: Option<i32>, contains_data_until: Option<OffsetDateTime>) -> Self {
+        let expiry = match contains_data_until.and_then(|until| until.format(&Rfc3339).ok()) {
+            Some(until) => format!("until they expire at {}", until),
+            None => "until they expire; BACKUP_RETENTION_DAYS is not configured, so when is unknown".to_owned(),
+        };
+
+        ErasureBackups {
+            retention_days,
+            note: format!(
+                "Backups taken before the erasure still contain this user's data {}. Erase the user again after restoring one.",
+                expiry
+            ),
+            contains_data_until,
+        }
+    }
+}
+
+/// Verification of a right-to-be-forgotten erasure, kept for compliance records.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ErasureReport {
+  pub report_uuid: Uuid,
+  pub user_uuid: Uuid,
+  pub requested_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub erased_at: OffsetDateTime,
+  /// Every column that can reference a user, including those without matching rows.
+  pub checks: Vec<ErasureCheck>,
+  /// Object store keys deleted with the user, such as their avatar images.
+  pub deleted_objects: Vec<String>,
+  pub backups: ErasureBackups,
+}
+
+/// Data removed by the retention purge once it is older than its configured period.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetentionCategory {
+    AuditLog,
+    IpAddresses,
+    QuestionViewers,
+    DeletedQuestions,
+    DeletedAnswers,
+}
+
+impl RetentionCategory {
+    /// In purge order: answers go before questions, whose purge would take their answers
+    /// along without counting them.
+    pub const ALL: [RetentionCategory; 5] = [
+        RetentionCategory::AuditLog,
+        RetentionCategory::IpAddresses,
+        RetentionCategory::QuestionViewers,
+        RetentionCategory::DeletedAnswers,
+        RetentionCategory::DeletedQuestions,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionCategory::AuditLog => "audit-log",
+            RetentionCategory::IpAddresses => "ip-addresses",
+            RetentionCategory::QuestionViewers => "question-viewers",
+            RetentionCategory::DeletedQuestions => "deleted-questions",
+            RetentionCategory::DeletedAnswers => "deleted-answers",
+        }
+    }
+}
+
+impl FromStr for RetentionCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "audit-log" => Ok(RetentionCategory::AuditLog),
+            "ip-addresses" => Ok(RetentionCategory::IpAddresses),
+            "question-viewers" => Ok(RetentionCategory::QuestionViewers),
+            "deleted-questions" => Ok(RetentionCategory::DeletedQuestions),
+            "deleted-answers" => Ok(RetentionCategory::DeletedAnswers),
+            other => Err(format!("Unknown retention category: {}", other)),
+        }
+    }
+}
+
+/// Days each category is kept. Audit entries and IP addresses are kept forever when unset;
+/// soft-deleted posts always have a period, `SOFT_DELETE_RETENTION_DAYS`. Question viewers are
+/// only kept for the day they are counted on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+  pub audit_log_days: Option<i32>,
+  pub ip_address_days: Option<i32>,
+  pub deleted_post_days: i32,
+}
+
+impl RetentionPolicy {
+    pub fn days(&self, category: RetentionCategory) -> Option<i32> {
+        match category {
+            RetentionCategory::AuditLog => self.audit_log_days,
+            RetentionCategory::IpAddresses => self.ip_address_days,
+            RetentionCategory::QuestionViewers => Some(1),
+            RetentionCategory::DeletedQuestions | RetentionCategory::DeletedAnswers => Some(self.deleted_post_days),
+        }
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            audit_log_days: None,
+            ip_address_days: None,
+            deleted_post_days: 30,
+        }
+    }
+}
+
+/// What the retention purge has removed in one category.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct RetentionStats {
+  pub category: RetentionCategory,
+  /// None when the category is kept forever.
+  pub retention_days: Option<i32>,
+  /// None until the first purge.
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub last_run_at: Option<OffsetDateTime>,
+  pub last_removed: i64,
+  pub total_removed: i64,
+}
+
+/// Counters of a question, kept up to date as it is viewed, answered and edited.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuestionStats {
+  pub question_uuid: Uuid,
+  /// Reads of the question on its own or with its answers.
+  pub views: i64,
+  /// Signed-in viewers counted by account, anonymous ones by IP address, each once a day.
+  pub unique_viewers: i64,
+  pub answers: i64,
+  /// When the question or one of its answers was last posted or edited.
+  #[serde(with = "time::serde::rfc3339")]
+  pub last_activity_at: OffsetDateTime,
+}
+
+/// Who viewed a question, told apart to count unique viewers. Only keyed hashes of it are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuestionViewer {
+    User(Uuid),
+    Ip(IpAddr),
+}
+
+impl fmt::Display for QuestionViewer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestionViewer::User(user_uuid) => write!(f, "user:{}", user_uuid),
+            QuestionViewer::Ip(ip) => write!(f, "ip:{}", ip),
+        }
+    }
+}
+
+/// A view of a question waiting to be counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionView {
+  pub question_uuid: Uuid,
+  pub viewer: QuestionViewer,
+}
+
+/// `?days=` of `GET /admin/stats`: how many days the time series covers, today included.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AdminStatsQuery {
+  #[serde(default)]
+  pub days: Option<i32>,
+}
+
+/// Activity of the whole forum, for the admin dashboard. Deleted posts are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AdminStats {
+  pub questions: i64,
+  pub answers: i64,
+  pub users: i64,
+  /// Share of the questions with an answer, from 0 to 1; None without questions.
+  pub answer_rate: Option<f64>,
+  /// Median time from a question to its first answer, over the answered questions.
+  pub median_seconds_to_first_answer: Option<f64>,
+  /// Each of the last `days` days, oldest first, including today.
+  pub per_day: Vec<ActivityDay>,
+}
+
+impl AdminStats {
+    pub const DEFAULT_DAYS: i32 = 30;
+    pub const MAX_DAYS: i32 = 366;
+}
+
+/// What happened on one day. The answer rate and median are those of the questions asked that
+/// day, so they still change as those questions get answered.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ActivityDay {
+  /// As `YYYY-MM-DD`, in UTC.
+  pub day: String,
+  pub questions: i64,
+  pub answers: i64,
+  pub users: i64,
+  pub answer_rate: Option<f64>,
+  pub median_seconds_to_first_answer: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SloObjective {
+    Availability,
+    Latency,
+}
+
+/// Whether an objective's error budget burns fast enough to alert on; see `GET /admin/slo`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnSeverity {
+    #[default]
+    Ok,
+    /// 5% of a 30-day budget spent in six hours.
+    SlowBurn,
+    /// 2% of a 30-day budget spent in an hour.
+    FastBurn,
+}
+
+/// Requests to a route in the last `minutes`. A burn rate of 1 spends the error budget exactly
+/// as fast as the target allows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SloWindow {
+  pub minutes: u64,
+  pub requests: u64,
+  /// Responses with a 5xx status.
+  pub errors: u64,
+  /// Responses slower than the latency threshold.
+  pub slow: u64,
+  pub availability_burn_rate: f64,
+  /// None without a latency target.
+  pub latency_burn_rate: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SloStatus {
+  /// The method and route, e.g. `GET /api/v1/question/:uuid`.
+  pub route: String,
+  pub availability_target: f64,
+  pub latency_threshold_ms: Option<u64>,
+  /// Fraction of requests that must be faster than `latency_threshold_ms`.
+  pub latency_target: Option<f64>,
+  pub windows: Vec<SloWindow>,
+  pub availability: BurnSeverity,
+  /// None without a latency target.
+  pub latency: Option<BurnSeverity>,
+}
+
+/// Posted to `SLO_ALERT_WEBHOOK_URL` when an objective starts or stops burning its budget.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SloAlert {
+  pub route: String,
+  pub objective: SloObjective,
+  pub severity: BurnSeverity,
+  pub previous: BurnSeverity,
+  pub hourly_burn_rate: f64,
+  /// Seconds since the Unix epoch.
+  pub at: u64,
+}
+
+/// Share of requests, from 0 to 100, whose queries are recorded as query samples. 0 turns sampling off.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+pub struct QuerySampling {
+  pub percent: f64,
+}
+
+/// A query run while serving a sampled request. Statements are recorded without their bound values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SampledQuery {
+  pub statement: String,
+  pub rows_returned: u64,
+  pub rows_affected: u64,
+  pub elapsed_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewQuerySample {
+  /// The method and route, e.g. `GET /api/v1/question/:uuid`.
+  pub route: String,
+  pub status: u16,
+  pub elapsed_ms: f64,
+  pub queries: Vec<SampledQuery>,
+}
+
+/// The queries of a sampled request, in the order they finished.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuerySample {
+  pub sample_uuid: Uuid,
+  pub route: String,
+  pub status: u16,
+  /// Time to the response head; queries run while streaming a body are not sampled.
+  pub elapsed_ms: f64,
+  pub queries: Vec<SampledQuery>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+/// `?route=&min_elapsed_ms=` of query samples, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuerySamplesQuery {
+  /// Exact method and route, e.g. `GET /api/v1/questions`.
+  #[serde(default)]
+  pub route: Option<String>,
+  #[serde(default)]
+  pub min_elapsed_ms: Option<f64>,
+}
+
+/// Internals of the server instance that answered, for `GET /admin/diagnostics`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Diagnostics {
+  pub runtime: RuntimeDiagnostics,
+  pub allocator: AllocatorDiagnostics,
+  pub process: ProcessDiagnostics,
+  pub db_pool: PoolDiagnostics,
+  /// Whether `GET /admin/diagnostics/profile` may capture CPU profiles.
+  pub profiling_enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct RuntimeDiagnostics {
+  pub workers: usize,
+  /// Spawned tasks that have not completed yet.
+  pub alive_tasks: usize,
+  /// Tasks scheduled from outside the runtime's workers, waiting to be picked up.
+  pub global_queue_depth: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AllocatorDiagnostics {
+  /// Bytes currently allocated on the heap.
+  pub allocated_bytes: u64,
+  /// Allocations since the process started.
+  pub allocations: u64,
+  pub deallocations: u64,
+}
+
+/// None where the platform does not report process memory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ProcessDiagnostics {
+  pub resident_bytes: Option<u64>,
+  pub virtual_bytes: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PoolDiagnostics {
+  /// Open connections, idle or in use.
+  pub size: u32,
+  pub idle: usize,
+  pub min_connections: u32,
+  pub max_connections: u32,
+  pub acquire_timeout_ms: u64,
+  /// How long a connection may stay idle before it is closed; `None` keeps it open.
+  pub idle_timeout_ms: Option<u64>,
+  /// How long a connection may stay open before it is closed; `None` keeps it open.
+  pub max_lifetime_ms: Option<u64>,
+  pub closed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+/// `GET /health`: the process serves requests, for liveness probes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Health {
+  pub status: HealthStatus,
+}
+
+/// `GET /ready`: the server instance can reach its database, for readiness probes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Readiness {
+  pub status: HealthStatus,
+  pub database_reachable: bool,
+  /// Migrations of this build the database has not applied; `None` when the database is unreachable
+  /// or keeps no migration history.
+  pub pending_migrations: Option<Vec<i64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileFormat {
+    /// An SVG flame graph, for browsers.
+    #[default]
+    Flamegraph,
+    /// An uncompressed pprof protobuf, for `go tool pprof`.
+    Pprof,
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProfileQuery {
+  /// How long to sample, from 1 to 60 seconds.
+  #[serde(default = "ProfileQuery::default_seconds")]
+  pub seconds: u64,
+  #[serde(default)]
+  pub format: ProfileFormat,
+}
+
+impl ProfileQuery {
+    fn default_seconds() -> u64 {
+        10
+    }
+}
+
+impl DeadLetterSelection {
+    pub const MAX_ITEMS: usize = 100;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeadLetterRetryResult {
+  pub dead_letter_uuid: Uuid,
+  pub delivered: bool,
+  /// Why the retry failed; the dead letter is kept with this error added to its history.
+  pub error: Option<String>,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Hard-deletes posts past the soft-delete retention period now instead of waiting for the hourly purge.
+    PurgeDeletedPosts,
+    /// Imports the dump of another forum; only started by `POST /import`.
+    Import,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::PurgeDeletedPosts => "purge_deleted_posts",
+            JobKind::Import => "import",
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "purge_deleted_posts" => Ok(JobKind::PurgeDeletedPosts),
+            "import" => Ok(JobKind::Import),
+            other => Err(format!("Unknown job kind: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            other => Err(format!("Unknown job status: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct JobRequest {
+  pub kind: JobKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct JobDetail {
+  pub job_uuid: Uuid,
+  pub kind: JobKind,
+  pub status: JobStatus,
+  /// Percentage of the work done, from 0 to 100.
+  pub progress: i32,
+  /// Kind-specific summary, set once the job succeeds.
+  pub result: Option<serde_json::Value>,
+  pub error: Option<String>,
+  pub requested_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub started_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub finished_at: Option<OffsetDateTime>,
+  /// Kind-specific parameters, for the worker running the job.
+  #[serde(skip)]
+  pub input: Option<serde_json::Value>,
+}
+
+/// A record of the dump of another forum, for `POST /import`: one per line of an NDJSON dump.
+/// Original UUIDs and timestamps are kept, so that links to the old forum can be mapped; records
+/// whose UUID is already taken are skipped, so an import can be run again after it failed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImportRecord {
+    User(ImportedUser),
+    Question(ImportedQuestion),
+    Answer(ImportedAnswer),
+}
+
+/// Imported users get no API token; they get one by signing in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ImportedUser {
+  pub user_uuid: Uuid,
+  pub username: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+impl ImportedUser {
+    pub const MAX_USERNAME_CHARS: usize = 255;
+}
+
+/// Imported questions are public and open.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ImportedQuestion {
+  pub question_uuid: Uuid,
+  pub title: String,
+  pub description: String,
+  /// An imported user, or none for an anonymous question.
+  pub author_uuid: Option<Uuid>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  /// `created_at` when left out.
+  #[serde(default, with = "time::serde::rfc3339::option")]
+  pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ImportedAnswer {
+  pub answer_uuid: Uuid,
+  pub question_uuid: Uuid,
+  pub content: String,
+  pub author_uuid: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+/// The `result` of an import job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, ToSchema)]
+pub struct ImportCounts {
+  pub users: u64,
+  pub questions: u64,
+  pub answers: u64,
+  /// Records whose UUID was already taken.
+  pub skipped: u64,
+}
+
+impl std::ops::AddAssign for ImportCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.users += other.users;
+        self.questions += other.questions;
+        self.answers += other.answers;
+        self.skipped += other.skipped;
+    }
+}
+
+// ----------
+
+/// Autosaved work in progress; fields may stay empty until the draft is published.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuestionDraft {
+  #[serde(default)]
+  pub title: String,
+  #[serde(default)]
+  pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DraftDetail {
+  pub draft_uuid: Uuid,
+  pub title: String,
+  pub description: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub updated_at: OffsetDateTime,
+}
+
+// ----------
+
+/// Where one of the user's own posts stands, whether or not others can see it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentState {
+    Published,
+    /// Held by the spam checker until a moderator approves it.
+    Held,
+    /// Soft-deleted, and restorable until it is purged.
+    Deleted,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct MyQuestion {
+  pub question_uuid: Uuid,
+  pub title: String,
+  pub status: QuestionStatus,
+  pub visibility: Visibility,
+  pub state: ContentState,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct MyAnswer {
+  pub answer_uuid: Uuid,
+  pub question_uuid: Uuid,
+  pub question_title: String,
+  pub state: ContentState,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+/// Everything the user wrote, including posts hidden from others. `questions` and `answers` are
+/// each paginated, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct MyContent {
+  pub questions: Vec<MyQuestion>,
+  pub answers: Vec<MyAnswer>,
+  pub drafts: Vec<DraftDetail>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeletedDrafts {
+  pub deleted: u64,
+}
+
+/// What `DELETE /me/subscriptions` stopped: followed questions and the email digest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Unsubscribed {
+  pub unfollowed_questions: u64,
+  pub digest: bool,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Board {
+  pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BoardDetail {
+  pub board_uuid: Uuid,
+  pub name: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BoardRole {
+    /// Can invite, approve and remove members.
+    Owner,
+    #[default]
+    Member,
+}
+
+impl BoardRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardRole::Owner => "owner",
+            BoardRole::Member => "member",
+        }
+    }
+}
+
+impl FromStr for BoardRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(BoardRole::Owner),
+            "member" => Ok(BoardRole::Member),
+            other => Err(format!("Unknown board role: {}", other)),
+        }
+    }
+}
+
+/// Only active members can read a board's private questions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MembershipStatus {
+    /// Invited by an owner; becomes active once the user joins.
+    Invited,
+    /// Asked to join; becomes active once an owner approves.
+    Requested,
+    Active,
+}
+
+impl MembershipStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MembershipStatus::Invited => "invited",
+            MembershipStatus::Requested => "requested",
+            MembershipStatus::Active => "active",
+        }
+    }
+}
+
+impl FromStr for MembershipStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "invited" => Ok(MembershipStatus::Invited),
+            "requested" => Ok(MembershipStatus::Requested),
+            "active" => Ok(MembershipStatus::Active),
+            other => Err(format!("Unknown membership status: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BoardMember {
+  pub board_uuid: Uuid,
+  pub user_uuid: Uuid,
+  pub role: BoardRole,
+  pub status: MembershipStatus,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BoardInvite {
+  pub user_uuid: Uuid,
+  #[serde(default)]
+  pub role: BoardRole,
+}
+
+/// Retention rules for a board's questions. Each rule is off when unset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct BoardCleanupPolicy {
+  /// Open questions without edits or new answers for this many days are closed as inactive.
+  #[serde(default)]
+  pub close_inactive_after_days: Option<i32>,
+  /// Questions still without an answer this many days after they were asked are deleted.
+  #[serde(default)]
+  pub delete_unanswered_after_days: Option<i32>,
+}
+
+impl BoardCleanupPolicy {
+    pub const MAX_DAYS: i32 = 36_500;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BoardCleanupPolicyDetail {
+  pub board_uuid: Uuid,
+  pub close_inactive_after_days: Option<i32>,
+  pub delete_unanswered_after_days: Option<i32>,
+  pub updated_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub updated_at: OffsetDateTime,
+}
+
+impl From<&BoardCleanupPolicyDetail> for BoardCleanupPolicy {
+    fn from(detail: &BoardCleanupPolicyDetail) -> Self {
+        BoardCleanupPolicy {
+            close_inactive_after_days: detail.close_inactive_after_days,
+            delete_unanswered_after_days: detail.delete_unanswered_after_days,
+        }
+    }
+}
+
+/// Questions a cleanup policy closed or deleted, or would with `dry_run`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BoardCleanup {
+  pub board_uuid: Uuid,
+  pub dry_run: bool,
+  pub closed_question_uuids: Vec<Uuid>,
+  pub deleted_question_uuids: Vec<Uuid>,
+}
+
+// ----------
+
+/// An admin's request for a single-use registration link.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Invitation {
+  /// The new user joins this board as an active member.
+  #[serde(default)]
+  pub board_uuid: Option<Uuid>,
+  /// Role granted to the new user instead of the default.
+  #[serde(default)]
+  pub role: Option<Role>,
+  #[serde(default = "Invitation::default_expires_in_seconds")]
+  pub expires_in_seconds: u64,
+}
+
+impl Invitation {
+    pub const MAX_EXPIRES_IN_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+    fn default_expires_in_seconds() -> u64 {
+        7 * 24 * 60 * 60
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum InvitationStatus {
+    Pending,
+    Used,
+    Expired,
+}
+
+impl FromStr for InvitationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(InvitationStatus::Pending),
+            "used" => Ok(InvitationStatus::Used),
+            "expired" => Ok(InvitationStatus::Expired),
+            other => Err(format!("Unknown invitation status: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct InvitationDetail {
+  pub invitation_uuid: Uuid,
+  pub board_uuid: Option<Uuid>,
+  pub role: Option<Role>,
+  pub status: InvitationStatus,
+  pub created_by: Option<Uuid>,
+  pub used_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub expires_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub used_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+/// `url` is the registration endpoint with the signed invitation in its query string.
+#[derive(Serialize, Deserialize, Debug, PartialEq, ToSchema)]
+pub struct InvitationLink {
+  pub invitation: InvitationDetail,
+  pub url: String,
+  pub expires: u64,
+}
+
+/// Query parameters of an invitation link, sent along with the registration.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct InvitationAcceptance {
+  pub invitation_uuid: Uuid,
+  pub expires: u64,
+  pub key_id: u32,
+  pub signature: String,
+}
+
+// ----------
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    NewAnswer,
+    Mention,
+    /// Sent to a question's author when one answer clearly leads but none was accepted.
+    AcceptSuggestion,
+    /// Sent to a post's author when a moderator warns them about it.
+    ModerationWarning,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 4] = [
+        NotificationKind::NewAnswer,
+        NotificationKind::Mention,
+        NotificationKind::AcceptSuggestion,
+        NotificationKind::ModerationWarning,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::NewAnswer => "new-answer",
+            NotificationKind::Mention => "mention",
+            NotificationKind::AcceptSuggestion => "accept-suggestion",
+            NotificationKind::ModerationWarning => "moderation-warning",
+        }
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new-answer" => Ok(NotificationKind::NewAnswer),
+            "mention" => Ok(NotificationKind::Mention),
+            "accept-suggestion" => Ok(NotificationKind::AcceptSuggestion),
+            "moderation-warning" => Ok(NotificationKind::ModerationWarning),
+            other => Err(format!("Unknown notification kind: {}", other)),
+        }
+    }
+}
+
+/// Where notifications of a kind are delivered.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct NotificationChannels {
+    pub in_app: bool,
+    pub email: bool,
+    /// To the user's own webhook, see `NotificationSettings::webhook_url`.
+    pub webhook: bool,
+}
+
+impl Default for NotificationChannels {
+    fn default() -> Self {
+        NotificationChannels {
+            in_app: true,
+            email: true,
+            webhook: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct NotificationKindSettings {
+    pub kind: NotificationKind,
+    #[serde(flatten)]
+    pub channels: NotificationChannels,
+}
+
+/// The user's channels for every notification kind. Kinds left out of an update get the default
+/// channels back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct NotificationSettings {
+  #[serde(default)]
+  pub kinds: Vec<NotificationKindSettings>,
+  /// Receives the notifications sent to the webhook channel, signed like admins' webhooks.
+  #[serde(default)]
+  pub webhook_url: Option<String>,
+  /// Only returned when `webhook_url` changed, with the new signing secret.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub webhook_secret: Option<String>,
+}
+
+/// An entry of the user's in-app inbox, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Notification {
+  pub id: i64,
+  pub kind: NotificationKind,
+  pub question_uuid: Option<Uuid>,
+  pub answer_uuid: Option<Uuid>,
+  /// The user who caused it, if any.
+  pub actor_uuid: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub read_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NotificationsQuery {
+  #[serde(default)]
+  pub unread: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+pub struct NotificationsRead {
+  /// How many notifications were unread.
+  pub read: u64,
+}
+
+/// A notification claimed for delivery by email, see `mailer::notification_email`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEmail {
+  pub id: i64,
+  pub user_uuid: Uuid,
+  pub kind: NotificationKind,
+  pub question_uuid: Option<Uuid>,
+  /// None when the question or answer was deleted since, or is hidden from the recipient.
+  pub question_title: Option<String>,
+  pub answer_uuid: Option<Uuid>,
+  pub actor_username: Option<String>,
+}
+
+/// When an answer "clearly leads": the question is at least `min_age_days` old and the top answer
+/// scores at least `min_score` and `min_lead` more than the runner-up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcceptSuggestionThresholds {
+    pub min_age_days: i32,
+    pub min_score: i64,
+    pub min_lead: i64,
+}
+
+impl Default for AcceptSuggestionThresholds {
+    fn default() -> Self {
+        AcceptSuggestionThresholds {
+            min_age_days: 14,
+            min_score: 5,
+            min_lead: 3,
+        }
+    }
+}
+
+/// Whether the user receives accepted-answer suggestions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, ToSchema)]
+pub struct AcceptSuggestionSettings {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DigestFrequency {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Off => "off",
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+}
+
+impl FromStr for DigestFrequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(DigestFrequency::Off),
+            "daily" => Ok(DigestFrequency::Daily),
+            "weekly" => Ok(DigestFrequency::Weekly),
+            other => Err(format!("Unknown digest frequency: {}", other)),
+        }
+    }
+}
+
+/// How often the user is emailed a digest of their unread notifications and of the top new
+/// questions in the tags they follow.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct DigestSettings {
+  pub frequency: DigestFrequency,
+  /// Normalized like `Question::tags`.
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
+impl DigestSettings {
+    pub const MAX_TAGS: usize = 50;
+}
+
+/// An unread notification listed in a digest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestNotification {
+  pub kind: NotificationKind,
+  pub question_uuid: Uuid,
+  pub question_title: String,
+  pub actor_username: Option<String>,
+}
+
+/// A question asked since the previous digest in one of the followed tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestQuestion {
+  pub question_uuid: Uuid,
+  pub title: String,
+  pub answers: i64,
+}
+
+/// One user's digest, see `mailer::digest_email`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailDigest {
+  pub user_uuid: Uuid,
+  pub frequency: DigestFrequency,
+  /// All of the user's unread notifications, of which `notifications` lists the newest.
+  pub unread_notifications: i64,
+  pub notifications: Vec<DigestNotification>,
+  /// Most answered first.
+  pub questions: Vec<DigestQuestion>,
+}
+
+impl EmailDigest {
+    pub fn is_empty(&self) -> bool {
+        self.unread_notifications == 0 && self.questions.is_empty()
+    }
+}
+
+// ----------
+
+// Types holding PII implement `Debug` by hand so emails, IPs and tokens never end up in logs.
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct User {
+  pub username: String,
+  #[serde(default)]
+  pub email: Option<String>,
+}
+
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("username", &self.username)
+            .field("email", &self.email.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct UserDetail {
+  pub user_uuid: Uuid,
+  pub username: String,
+  pub email: Option<String>,
+  pub role: Role,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+}
+
+impl fmt::Debug for UserDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserDetail")
+            .field("user_uuid", &self.user_uuid)
+            .field("username", &self.username)
+            .field("email", &self.email.as_ref().map(|_| REDACTED))
+            .field("role", &self.role)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct UserIpRecord {
+  pub ip: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub seen_at: OffsetDateTime,
+}
+
+impl fmt::Debug for UserIpRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserIpRecord")
+            .field("ip", &REDACTED)
+            .field("seen_at", &self.seen_at)
+            .finish()
+    }
+}
+
+/// What anyone can see of a user. `avatar_urls` is empty until the user uploads an avatar.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct UserProfile {
+  pub user_uuid: Uuid,
+  pub username: String,
+  pub display_name: Option<String>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  pub avatar_urls: Vec<AvatarUrl>,
+}
+
+/// A square avatar rendering, `size` pixels wide.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AvatarUrl {
+  pub size: u32,
+  pub url: String,
+}
+
+/// Directory sign-in, only available when an auth backend such as LDAP is configured.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SignIn {
+  pub username: String,
+  pub password: String,
+}
+
+impl fmt::Debug for SignIn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignIn")
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .finish()
+    }
+}
+
+/// Returned once on registration or sign-in; only a hash of `api_token` is persisted.
+#[derive(Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct UserCredentials {
+  pub user: UserDetail,
+  pub api_token: String,
+}
+
+impl fmt::Debug for UserCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserCredentials")
+            .field("user", &self.user)
+            .field("api_token", &REDACTED)
+            .finish()
+    }
+}
+
+/// A user as managed by an identity provider, through SCIM or directory sign-in.
+#[derive(Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ProvisionedUser {
+  pub username: String,
+  pub display_name: Option<String>,
+  pub email: Option<String>,
+  pub external_id: Option<String>,
+  pub role: Role,
+  /// Inactive users can no longer authenticate.
+  pub active: bool,
+}
+
+impl fmt::Debug for ProvisionedUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvisionedUser")
+            .field("username", &self.username)
+            .field("display_name", &self.display_name)
+            .field("email", &self.email.as_ref().map(|_| REDACTED))
+            .field("external_id", &self.external_id)
+            .field("role", &self.role)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ProvisionedUserDetail {
+  pub user: UserDetail,
+  pub display_name: Option<String>,
+  pub external_id: Option<String>,
+  pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn can_moderate(&self) -> bool {
+        matches!(self, Role::Moderator | Role::Admin)
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role: {}", other)),
+        }
+    }
+}
+
+// ----------
+
+/// Errors of the DAOs. Driver errors are sorted into these by `From<sqlx::Error>`, so that
+/// handlers can answer each kind with its own status.
+#[derive(Error, Debug)]
+pub enum DBError {
+    #[error("Invalid UUID provided: {0}")]
+    InvalidUUID(String),
+    /// A statement expected a row that does not exist.
+    #[error("Record not found")]
+    NotFound,
+    #[error("Unique constraint violated: {0}")]
+    UniqueViolation(String),
+    /// A row refers to another that does not exist, or is still referred to.
+    #[error("Foreign key constraint violated: {0}")]
+    ForeignKeyViolation(String),
+    /// The database could not be reached, or refused or dropped the connection.
+    #[error("Database connection failed")]
+    ConnectionError,
+    /// A statement was cancelled for running too long.
+    #[error("Database operation timed out")]
+    Timeout,
+    /// Not tried, as the database failed too often lately; see `persistance::circuit_breaker`.
+    #[error("Database operations are suspended after repeated failures")]
+    CircuitOpen,
+    /// A transaction was rolled back for conflicting with a concurrent one, or to break a deadlock.
+    #[error("Database transaction conflicted with a concurrent one")]
+    SerializationFailure,
+    /// A post was not saved, as its author already made as many as the limit it was saved with.
+    #[error("Posting limit reached")]
+    PostingLimitReached,
+    #[error("Database error occurred")]
+    Other(#[from] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl DBError {
+    /// Whether trying the same operation again may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DBError::ConnectionError | DBError::SerializationFailure)
+    }
 }
 
 // source: https://www.postgresql.org/docs/current/errcodes-appendix.html
 pub mod postgres_error_codes {
+    /// Class of the codes of connection exceptions, such as `08006` connection_failure.
+    pub const CONNECTION_EXCEPTION_CLASS: &str = "08";
+    pub const TOO_MANY_CONNECTIONS: &str = "53300";
     pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+    pub const UNIQUE_VIOLATION: &str = "23505";
+    pub const SERIALIZATION_FAILURE: &str = "40001";
+    pub const DEADLOCK_DETECTED: &str = "40P01";
+    pub const QUERY_CANCELED: &str = "57014";
+    pub const ADMIN_SHUTDOWN: &str = "57P01";
+    pub const CRASH_SHUTDOWN: &str = "57P02";
+    pub const CANNOT_CONNECT_NOW: &str = "57P03";
 }
@@ -0,0 +1,251 @@
+use utoipa::{
+    openapi::{
+        security::{Http, HttpAuthScheme, SecurityScheme},
+        Content, Ref, RefOr,
+    },
+    Modify, OpenApi,
+};
+
+use crate::{
+    handlers,
+    models::{AnswerSort, BodyFormat, FaqGrouping, ModerationQueueKind, Problem, ProfileFormat},
+    problem::PROBLEM_CONTENT_TYPE,
+};
+
+/// The OpenAPI 3 document of every route of API version 1, served at `GET /api/v1/openapi.json` and
+/// browsable at `/docs`. Paths are relative to the version's server URL.
+/// Schemas are collected from the request and response types of the listed handlers; enums only
+/// used in query parameters are not, so they are listed under `components`. Error responses are
+/// only described in the handlers; `ProblemResponses` gives them all the `Problem` body.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Rust Programming Forum API",
+        description = "Questions, answers and the moderation, administration and provisioning around them. \
+                       Questions and answers are sent as JSON:API documents to clients accepting `application/vnd.api+json`."
+    ),
+    servers((url = "/api/v1", description = "Version 1")),
+    paths(
+        handlers::create_question,
+        handlers::read_questions,
+        handlers::read_questions_batch,
+        handlers::delete_question,
+        handlers::delete_question_by_body,
+        handlers::bulk_delete_questions,
+        handlers::read_question,
+        handlers::read_question_full,
+        handlers::read_question_stats,
+        handlers::update_question,
+        handlers::read_question_revisions,
+        handlers::create_question_signed_url,
+        handlers::read_shared_question,
+        handlers::follow_question,
+        handlers::unfollow_question,
+        handlers::accept_answer,
+        handlers::close_question,
+        handlers::reopen_question,
+        handlers::restore_question,
+        handlers::set_faq_entry,
+        handlers::remove_faq_entry,
+        handlers::read_faq,
+        handlers::suggest_tags,
+        handlers::read_tag_stats,
+        handlers::export_knowledge_base,
+        handlers::read_questions_feed,
+        handlers::read_tag_feed,
+        handlers::read_sitemap_index,
+        handlers::read_sitemap,
+        handlers::read_pending_tags,
+        handlers::reject_pending_tag,
+        handlers::approve_pending_tag,
+        handlers::flag_question,
+        handlers::create_answer,
+        handlers::read_question_answers,
+        handlers::read_answers,
+        handlers::delete_answer,
+        handlers::delete_answer_by_body,
+        handlers::bulk_delete_answers,
+        handlers::update_answer,
+        handlers::read_answer_revisions,
+        handlers::restore_answer,
+        handlers::flag_answer,
+        handlers::vote_answer,
+        handlers::live_updates,
+        handlers::question_events,
+        handlers::poll_question_events,
+        handlers::create_upload,
+        handlers::read_upload,
+        handlers::create_attachment_signed_url,
+        handlers::read_avatar,
+        handlers::save_question_draft,
+        handlers::publish_draft,
+        handlers::create_board,
+        handlers::join_board,
+        handlers::read_board_members,
+        handlers::invite_board_member,
+        handlers::remove_board_member,
+        handlers::approve_board_member,
+        handlers::read_board_tag_rules,
+        handlers::set_board_tag_rules,
+        handlers::delete_board_tag_rules,
+        handlers::create_user,
+        handlers::read_accept_suggestion_settings,
+        handlers::update_accept_suggestion_settings,
+        handlers::read_digest_settings,
+        handlers::update_digest_settings,
+        handlers::read_my_content,
+        handlers::delete_my_drafts,
+        handlers::unsubscribe_from_everything,
+        handlers::read_notification_settings,
+        handlers::update_notification_settings,
+        handlers::update_avatar,
+        handlers::read_notifications,
+        handlers::mark_all_notifications_read,
+        handlers::mark_notification_read,
+        handlers::read_user_profile,
+        handlers::read_user_questions,
+        handlers::read_user_answers,
+        handlers::sign_in,
+        handlers::read_invitations,
+        handlers::create_invitation,
+        handlers::read_flags,
+        handlers::resolve_flag,
+        handlers::read_moderation_queue,
+        handlers::read_moderation_actions,
+        handlers::moderate_post,
+        handlers::shadow_ban_user,
+        handlers::lift_shadow_ban,
+        handlers::export_questions_ndjson,
+        handlers::export_questions_csv,
+        handlers::export_answers_csv,
+        handlers::read_audit_log,
+        handlers::create_job,
+        handlers::read_job,
+        handlers::start_import,
+        handlers::read_import,
+        handlers::erase_user,
+        handlers::read_erasure_reports,
+        handlers::read_erasure_report,
+        handlers::read_admin_stats,
+        handlers::read_retention_stats,
+        handlers::read_slo_status,
+        handlers::read_diagnostics,
+        handlers::capture_cpu_profile,
+        handlers::read_query_sampling,
+        handlers::update_query_sampling,
+        handlers::read_query_samples,
+        handlers::read_dead_letters,
+        handlers::retry_dead_letters,
+        handlers::purge_dead_letters,
+        handlers::read_webhooks,
+        handlers::create_webhook,
+        handlers::delete_webhook,
+        handlers::read_webhook_deliveries,
+        handlers::read_cleanup_policy,
+        handlers::set_cleanup_policy,
+        handlers::delete_cleanup_policy,
+        handlers::preview_cleanup,
+        handlers::scim_read_users,
+        handlers::scim_create_user,
+        handlers::scim_read_user,
+        handlers::scim_replace_user,
+        handlers::scim_patch_user,
+        handlers::scim_delete_user,
+    ),
+    components(schemas(AnswerSort, BodyFormat, FaqGrouping, ModerationQueueKind, Problem, ProfileFormat)),
+    modifiers(&SecuritySchemes, &ProblemResponses),
+    tags(
+        (name = "questions"),
+        (name = "answers"),
+        (name = "live", description = "Answers to a question as they are posted or edited"),
+        (name = "uploads"),
+        (name = "tags"),
+        (name = "faq"),
+        (name = "feeds", description = "Atom feeds and sitemaps"),
+        (name = "drafts"),
+        (name = "boards"),
+        (name = "users"),
+        (name = "me", description = "Settings and content of the authenticated user"),
+        (name = "notifications"),
+        (name = "invitations"),
+        (name = "moderation"),
+        (name = "admin"),
+        (name = "scim", description = "SCIM 2.0 user provisioning for identity providers"),
+    )
+)]
+pub struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+
+        components.add_security_scheme("api_token", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+        components.add_security_scheme("scim_token", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+    }
+}
+
+struct ProblemResponses;
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let operations = openapi.paths.paths.values_mut().flat_map(|path| {
+            [&mut path.get, &mut path.put, &mut path.post, &mut path.delete, &mut path.patch]
+                .into_iter()
+                .flatten()
+        });
+
+        for operation in operations {
+            for (status, response) in operation.responses.responses.iter_mut() {
+                if !status.starts_with('4') && !status.starts_with('5') {
+                    continue;
+                }
+
+                if let RefOr::T(response) = response {
+                    response.content.insert(
+                        PROBLEM_CONTENT_TYPE.to_owned(),
+                        Content::new(Some(Ref::from_schema_name("Problem"))),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_doc_should_describe_every_route_with_its_schemas() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        assert_eq!(spec["openapi"], "3.1.0");
+        assert_eq!(spec["servers"][0]["url"], crate::routes::API_V1);
+        assert!(spec["paths"]["/question/{uuid}"]["get"].is_object());
+        assert!(spec["paths"]["/question/{uuid}"]["put"].is_object());
+        assert!(spec["paths"]["/question/{uuid}"]["delete"].is_object());
+        assert!(spec["paths"]["/questions/{uuid}/answers"]["get"].is_object());
+        assert!(spec["paths"]["/scim/v2/Users/{uuid}"]["patch"].is_object());
+        assert_eq!(
+            spec["paths"]["/question"]["post"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/QuestionDetail"
+        );
+        assert!(spec["components"]["schemas"]["LiveMessage"].is_object());
+        assert!(spec["components"]["securitySchemes"]["api_token"].is_object());
+        assert_eq!(
+            spec["paths"]["/question/{uuid}"]["get"]["responses"]["404"]["content"][PROBLEM_CONTENT_TYPE]["schema"]["$ref"],
+            "#/components/schemas/Problem"
+        );
+
+        let refs = spec.to_string();
+        for reference in refs.split("\"#/components/schemas/").skip(1) {
+            let name = reference.split('"').next().unwrap();
+            assert!(spec["components"]["schemas"][name].is_object(), "{} has no schema", name);
+        }
+
+        let operations: usize = spec["paths"].as_object().unwrap().values().map(|path| path.as_object().unwrap().len()).sum();
+        assert_eq!(operations, 125);
+    }
+}
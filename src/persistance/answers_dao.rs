@@ -1,96 +1,602 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use sqlx::{types::Uuid, PgPool};
+use tokio::sync::mpsc;
+
+use crate::models::{
+    Answer, AnswerDetail, AnswerRevision, AnswerSignals, AnswerSort, BulkDeleteResult, DBError, ExportRange,
+    Pagination, PostKind, ReputationBand, Viewer,
+};
 
-use crate::models::{postgres_error_codes, Answer, AnswerDetail, DBError};
+use super::{
+    begin, begin_streaming, bulk_delete_results, commit,
+    circuit_breaker::breaker,
+    moderation_dao::require_posting_limit_in,
+    replica::{reading, ReadReplica},
+    retry::retrying,
+    viewer_params,
+};
 
 #[async_trait]
 pub trait AnswersDao {
-    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError>;
+    /// Given a `posting_limit`, fails with `DBError::PostingLimitReached` instead when the author
+    /// already made that many answers in the posting quota window.
+    async fn create_answer(&self, answer: Answer, author_uuid: Option<String>, posting_limit: Option<i64>) -> Result<AnswerDetail, DBError>;
     async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError>;
-    async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError>;
+    /// Soft-deletes every listed answer in one transaction, reporting the outcome per UUID.
+    async fn delete_answers(&self, answer_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError>;
+    async fn restore_answer(&self, answer_uuid: String) -> Result<Option<AnswerDetail>, DBError>;
+    /// Permanently removes answers soft-deleted more than `retention_days` ago.
+    async fn purge_deleted_answers(&self, retention_days: i32) -> Result<u64, DBError>;
+    /// Answers are only readable by viewers who may read their question.
+    async fn get_answer(&self, answer_uuid: String, viewer: Viewer) -> Result<Option<AnswerDetail>, DBError>;
+    /// Answers held for moderation are only listed for their author.
+    async fn get_answers(&self, question_uuid: String, sort: AnswerSort, viewer: Viewer) -> Result<Vec<AnswerDetail>, DBError>;
+    /// Pages through the answers `author_uuid` posted, newest first. Answers on unlisted questions
+    /// and answers held for moderation are only included for the author themselves.
+    async fn get_answers_by_author(&self, author_uuid: String, page: Pagination, viewer: Viewer) -> Result<Vec<AnswerDetail>, DBError>;
+    /// Applies an edit and records it as a new revision in the same transaction.
+    async fn update_answer(
+        &self,
+        answer_uuid: String,
+        content: String,
+        editor_uuid: String,
+    ) -> Result<Option<AnswerDetail>, DBError>;
+    async fn get_answer_revisions(&self, answer_uuid: String, viewer: Viewer) -> Result<Vec<AnswerRevision>, DBError>;
+    /// Records the user's vote on the answer, replacing their earlier one; a `value` of 0 withdraws
+    /// it. Returns the score of the answer.
+    async fn vote_answer(&self, answer_uuid: String, user_uuid: String, value: i16) -> Result<i64, DBError>;
+    /// Sends every answer created in `range` that is not deleted, nor its question, held ones
+    /// included, to `answers` as they are read, oldest first. Stops early once `answers` is closed.
+    async fn stream_answers(&self, range: ExportRange, answers: mpsc::Sender<AnswerDetail>) -> Result<(), DBError>;
 }
 
 pub struct AnswersDaoImpl {
     db: PgPool,
+    replica: Option<Arc<ReadReplica>>,
 }
 
 impl AnswersDaoImpl {
     pub fn new(db: PgPool) -> Self {
       AnswersDaoImpl {
-        db
+        db,
+        replica: None,
       }
     }
+
+    /// Sends the listings to `replica`, which may lag behind the primary. Reads of a single item
+    /// stay on the primary, so that a client reading what it just wrote finds it.
+    pub fn with_replica(self, replica: Arc<ReadReplica>) -> Self {
+      AnswersDaoImpl {
+        replica: Some(replica),
+        ..self
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
 }
 
 #[async_trait]
 impl AnswersDao for AnswersDaoImpl {
-    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
-        let uuid = Uuid::parse_str(&answer.question_uuid)
-          .map_err(|err| {
-            DBError::InvalidUUID(err.to_string())
-          })?;
+    async fn create_answer(&self, answer: Answer, author_uuid: Option<String>, posting_limit: Option<i64>) -> Result<AnswerDetail, DBError> {
+        let (answer, author_uuid) = (&answer, author_uuid.as_deref());
 
-        let record = sqlx::query!("INSERT INTO answers (question_uuid, content) VALUES ($1, $2) RETURNING *", uuid, answer.content)
-          .fetch_one(&self.db)
-          .await
-          .map_err(|err: sqlx::Error| match err {
-            sqlx::Error::Database(db_err) => {
-              if db_err.is_foreign_key_violation() {
-                DBError::InvalidUUID(db_err.to_string())
-              } else {
-                DBError::Other(Box::new(db_err))
-              }
-            },
-            err => {
-              DBError::Other(Box::new(err))
+        retrying("create_answer", || async move {
+            let uuid = answer.question_uuid;
+            let author_uuid = author_uuid.map(parse_uuid).transpose()?;
+
+            let mut tx = begin(&self.db).await?;
+
+            if let (Some(author_uuid), Some(limit)) = (author_uuid, posting_limit) {
+                require_posting_limit_in(&mut tx, author_uuid, PostKind::Answer, limit).await?;
             }
-          })?;
 
-        Ok(AnswerDetail {
-          answer_uuid: record.answer_uuid.to_string(),
-          question_uuid: record.question_uuid.to_string(),
-          content: record.content,
-          created_at: record.created_at.to_string(),
-        })
+            let record = sqlx::query!(
+                "INSERT INTO answers (question_uuid, content, author_uuid) VALUES ($1, $2, $3) RETURNING *",
+                uuid,
+                answer.content,
+                author_uuid
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            commit(tx).await?;
+
+            Ok(AnswerDetail {
+              answer_uuid: record.answer_uuid,
+              question_uuid: record.question_uuid,
+              content: record.content,
+              author_uuid: record.author_uuid,
+              created_at: record.created_at.assume_utc(),
+              content_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              signals: None,
+              question_age_warning: false,
+              similar_answer_uuid: None,
+              held_for_review: false,
+            })
+          })
+          .await
     }
 
     async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError> {
-        let uuid = Uuid::parse_str(&answer_uuid)
-          .map_err(|err| {
-            DBError::InvalidUUID(err.to_string())
-          })?;
+        let answer_uuid = &answer_uuid;
+
+        retrying("delete_answer", || async move {
+            let uuid = Uuid::parse_str(answer_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
 
-        sqlx::query!("DELETE FROM answers WHERE answer_uuid = $1", uuid)
-          .execute(&self.db)
+            sqlx::query!("UPDATE answers SET deleted_at = CURRENT_TIMESTAMP WHERE answer_uuid = $1 AND deleted_at IS NULL", uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
           .await
-          .map_err(|err: sqlx::Error| { DBError::Other(Box::new(err)) })?;
+    }
 
-        Ok(())
+    async fn delete_answers(&self, answer_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+        let answer_uuids = &answer_uuids;
+
+        retrying("delete_answers", || async move {
+            let uuids: Vec<Uuid> = answer_uuids
+              .iter()
+              .filter_map(|uuid| Uuid::parse_str(uuid).ok())
+              .collect();
+
+            let deleted = sqlx::query_scalar!(
+                "UPDATE answers SET deleted_at = CURRENT_TIMESTAMP WHERE answer_uuid = ANY($1) AND deleted_at IS NULL RETURNING answer_uuid",
+                &uuids
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(bulk_delete_results(answer_uuids.clone(), &deleted))
+          })
+          .await
+    }
+
+    async fn restore_answer(&self, answer_uuid: String) -> Result<Option<AnswerDetail>, DBError> {
+        let answer_uuid = &answer_uuid;
+
+        retrying("restore_answer", || async move {
+            let uuid = Uuid::parse_str(answer_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            let record = sqlx::query!("UPDATE answers SET deleted_at = NULL WHERE answer_uuid = $1 AND deleted_at IS NOT NULL RETURNING *", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| {
+              AnswerDetail {
+                answer_uuid: record.answer_uuid,
+                question_uuid: record.question_uuid,
+                content: record.content,
+                author_uuid: record.author_uuid,
+                created_at: record.created_at.assume_utc(),
+                content_html: None,
+                code_blocks: Vec::new(),
+                link_previews: Vec::new(),
+                signals: None,
+                question_age_warning: false,
+                similar_answer_uuid: None,
+                held_for_review: false,
+              }
+            }))
+          })
+          .await
     }
 
-    async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError> {
+    async fn purge_deleted_answers(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_deleted_answers", || async move {
+            let result = sqlx::query!(
+                "DELETE FROM answers WHERE deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+                retention_days
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn get_answer(&self, answer_uuid: String, viewer: Viewer) -> Result<Option<AnswerDetail>, DBError> {
+        let uuid = parse_uuid(&answer_uuid)?;
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
+
+        let db = &self.db;
+        let record = retrying("get_answer", || async move {
+            sqlx::query!(
+                "SELECT a.* FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid WHERE a.answer_uuid = $1 AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2) AND post_visible_to(a.author_uuid, $2)
+                 AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, $2)",
+                uuid,
+                viewer_uuid,
+                signed_link
+              )
+              .fetch_optional(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        Ok(record.map(|record| {
+          AnswerDetail {
+            answer_uuid: record.answer_uuid,
+            question_uuid: record.question_uuid,
+            content: record.content,
+            author_uuid: record.author_uuid,
+            created_at: record.created_at.assume_utc(),
+            content_html: None,
+            code_blocks: Vec::new(),
+            link_previews: Vec::new(),
+            signals: None,
+            question_age_warning: false,
+            similar_answer_uuid: None,
+            held_for_review: false,
+          }
+        }))
+    }
+
+    async fn get_answers(&self, question_uuid: String, sort: AnswerSort, viewer: Viewer) -> Result<Vec<AnswerDetail>, DBError> {
         let uuid = Uuid::parse_str(&question_uuid)
           .map_err(|err| {
             DBError::InvalidUUID(err.to_string())
           })?;
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
 
-        let records = sqlx::query!("SELECT * FROM answers WHERE question_uuid = $1", uuid)
-          .fetch_all(&self.db)
-          .await
-          .map_err(|err: sqlx::Error| { DBError::Other(Box::new(err)) })?;
+        let records = reading("get_answers", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "SELECT a.*, s.score AS \"score!\", p.score_percentile AS \"score_percentile!\", r.reputation,
+                   EXISTS (SELECT 1 FROM answer_revisions ar WHERE ar.answer_uuid = a.answer_uuid) AS \"edited!\",
+                   (CURRENT_DATE - a.created_at::date) AS \"age_days!\"
+                 FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                 LEFT JOIN LATERAL (SELECT COALESCE(SUM(v.value), 0) AS score FROM counted_answer_votes v WHERE v.answer_uuid = a.answer_uuid) s ON TRUE
+                 LEFT JOIN LATERAL (SELECT (100 * COUNT(*) FILTER (WHERE o.score <= s.score) / COUNT(*))::INT AS score_percentile FROM (
+                     SELECT COALESCE(SUM(v.value), 0) AS score FROM answers oa LEFT JOIN counted_answer_votes v ON v.answer_uuid = oa.answer_uuid
+                     WHERE oa.question_uuid = a.question_uuid AND oa.deleted_at IS NULL GROUP BY oa.answer_uuid) o) p ON TRUE
+                 LEFT JOIN LATERAL (SELECT SUM(v.value) AS reputation FROM answers ra JOIN counted_answer_votes v ON v.answer_uuid = ra.answer_uuid
+                     WHERE ra.author_uuid = a.author_uuid AND ra.deleted_at IS NULL) r ON TRUE
+                 WHERE a.question_uuid = $1 AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 AND (a.held_at IS NULL OR a.author_uuid = $2)
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2) AND post_visible_to(a.author_uuid, $2)
+                 AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, $2)
+                 ORDER BY
+                   CASE WHEN $4 = 'accepted_first' THEN a.answer_uuid IS NOT DISTINCT FROM q.accepted_answer_uuid END DESC,
+                   CASE WHEN $4 IN ('votes', 'accepted_first') THEN s.score END DESC,
+                   CASE WHEN $4 = 'newest' THEN a.created_at END DESC,
+                   a.created_at, a.answer_uuid",
+                uuid,
+                viewer_uuid,
+                signed_link,
+                sort.as_str()
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
 
         let answers = records
           .into_iter()
           .map(|record| {
             AnswerDetail {
-              answer_uuid: record.answer_uuid.to_string(),
-              question_uuid: record.question_uuid.to_string(),
+              answer_uuid: record.answer_uuid,
+              question_uuid: record.question_uuid,
               content: record.content,
-              created_at: record.created_at.to_string(),
+              author_uuid: record.author_uuid,
+              created_at: record.created_at.assume_utc(),
+              content_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              signals: Some(AnswerSignals {
+                score: record.score,
+                score_percentile: record.score_percentile,
+                author_reputation_band: record
+                  .author_uuid
+                  .map(|_| ReputationBand::from_reputation(record.reputation.unwrap_or_default())),
+                edited: record.edited,
+                age_days: record.age_days,
+              }),
+              question_age_warning: false,
+              similar_answer_uuid: None,
+              held_for_review: false,
             }
           })
           .collect();
 
         Ok(answers)
     }
+
+    async fn get_answers_by_author(&self, author_uuid: String, page: Pagination, viewer: Viewer) -> Result<Vec<AnswerDetail>, DBError> {
+        let author_uuid = parse_uuid(&author_uuid)?;
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
+
+        let records = reading("get_answers_by_author", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "SELECT a.*, s.score AS \"score!\", p.score_percentile AS \"score_percentile!\", r.reputation,
+                   EXISTS (SELECT 1 FROM answer_revisions ar WHERE ar.answer_uuid = a.answer_uuid) AS \"edited!\",
+                   (CURRENT_DATE - a.created_at::date) AS \"age_days!\"
+                 FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                 LEFT JOIN LATERAL (SELECT COALESCE(SUM(v.value), 0) AS score FROM counted_answer_votes v WHERE v.answer_uuid = a.answer_uuid) s ON TRUE
+                 LEFT JOIN LATERAL (SELECT (100 * COUNT(*) FILTER (WHERE o.score <= s.score) / COUNT(*))::INT AS score_percentile FROM (
+                     SELECT COALESCE(SUM(v.value), 0) AS score FROM answers oa LEFT JOIN counted_answer_votes v ON v.answer_uuid = oa.answer_uuid
+                     WHERE oa.question_uuid = a.question_uuid AND oa.deleted_at IS NULL GROUP BY oa.answer_uuid) o) p ON TRUE
+                 LEFT JOIN LATERAL (SELECT SUM(v.value) AS reputation FROM answers ra JOIN counted_answer_votes v ON v.answer_uuid = ra.answer_uuid
+                     WHERE ra.author_uuid = a.author_uuid AND ra.deleted_at IS NULL) r ON TRUE
+                 WHERE a.author_uuid = $1 AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 AND (a.held_at IS NULL OR a.author_uuid = $2)
+                 AND (q.visibility <> 'unlisted' OR a.author_uuid = $2)
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2) AND post_visible_to(a.author_uuid, $2)
+                 AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, $2)
+                 ORDER BY a.created_at DESC, a.answer_uuid DESC
+                 OFFSET $4 LIMIT $5",
+                author_uuid,
+                viewer_uuid,
+                signed_link,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        let answers = records
+          .into_iter()
+          .map(|record| {
+            AnswerDetail {
+              answer_uuid: record.answer_uuid,
+              question_uuid: record.question_uuid,
+              content: record.content,
+              author_uuid: record.author_uuid,
+              created_at: record.created_at.assume_utc(),
+              content_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              signals: Some(AnswerSignals {
+                score: record.score,
+                score_percentile: record.score_percentile,
+                author_reputation_band: record
+                  .author_uuid
+                  .map(|_| ReputationBand::from_reputation(record.reputation.unwrap_or_default())),
+                edited: record.edited,
+                age_days: record.age_days,
+              }),
+              question_age_warning: false,
+              similar_answer_uuid: None,
+              held_for_review: false,
+            }
+          })
+          .collect();
+
+        Ok(answers)
+    }
+
+    async fn update_answer(
+        &self,
+        answer_uuid: String,
+        content: String,
+        editor_uuid: String,
+    ) -> Result<Option<AnswerDetail>, DBError> {
+        let (answer_uuid, content, editor_uuid) = (&answer_uuid, &content, &editor_uuid);
+
+        retrying("update_answer", || async move {
+            let uuid = parse_uuid(answer_uuid)?;
+            let editor_uuid = parse_uuid(editor_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let current = sqlx::query!(
+                "SELECT answer_uuid FROM answers WHERE answer_uuid = $1 AND deleted_at IS NULL FOR UPDATE",
+                uuid
+              )
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if current.is_none() {
+                return Ok(None);
+            }
+
+            // The first edit also snapshots the original post so the history is complete.
+            sqlx::query!(
+                "INSERT INTO answer_revisions (answer_uuid, revision, content, editor_uuid, created_at)
+                 SELECT answer_uuid, 1, content, author_uuid, created_at FROM answers
+                 WHERE answer_uuid = $1 AND NOT EXISTS (SELECT 1 FROM answer_revisions WHERE answer_uuid = $1)",
+                uuid
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let record = sqlx::query!(
+                "UPDATE answers SET content = $2, updated_at = CURRENT_TIMESTAMP WHERE answer_uuid = $1 RETURNING *",
+                uuid,
+                content
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            sqlx::query!(
+                "INSERT INTO answer_revisions (answer_uuid, revision, content, editor_uuid)
+                 SELECT $1, MAX(revision) + 1, $2, $3 FROM answer_revisions WHERE answer_uuid = $1",
+                uuid,
+                record.content,
+                editor_uuid
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(AnswerDetail {
+              answer_uuid: record.answer_uuid,
+              question_uuid: record.question_uuid,
+              content: record.content,
+              author_uuid: record.author_uuid,
+              created_at: record.created_at.assume_utc(),
+              content_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              signals: None,
+              question_age_warning: false,
+              similar_answer_uuid: None,
+              held_for_review: false,
+            }))
+          })
+          .await
+    }
+
+    async fn get_answer_revisions(&self, answer_uuid: String, viewer: Viewer) -> Result<Vec<AnswerRevision>, DBError> {
+        let (answer_uuid, viewer) = (&answer_uuid, &viewer);
+
+        retrying("get_answer_revisions", || async move {
+            let uuid = parse_uuid(answer_uuid)?;
+            let (viewer_uuid, signed_link) = viewer_params(viewer);
+
+            let records = sqlx::query!(
+                "SELECT r.* FROM answer_revisions r
+                 JOIN answers a ON a.answer_uuid = r.answer_uuid
+                 JOIN questions q ON q.question_uuid = a.question_uuid
+                 WHERE r.answer_uuid = $1 AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2) AND post_visible_to(a.author_uuid, $2)
+                 AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, $2)
+                 ORDER BY r.revision",
+                uuid,
+                viewer_uuid,
+                signed_link
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let revisions = records
+              .into_iter()
+              .map(|record| {
+                AnswerRevision {
+                  answer_uuid: record.answer_uuid,
+                  revision: record.revision,
+                  content: record.content,
+                  editor_uuid: record.editor_uuid,
+                  created_at: record.created_at.assume_utc(),
+                }
+              })
+              .collect();
+
+            Ok(revisions)
+          })
+          .await
+    }
+
+    async fn vote_answer(&self, answer_uuid: String, user_uuid: String, value: i16) -> Result<i64, DBError> {
+        let uuid = parse_uuid(&answer_uuid)?;
+        let user_uuid = parse_uuid(&user_uuid)?;
+
+        retrying("vote_answer", || async move {
+            let mut tx = begin(&self.db).await?;
+
+            if value == 0 {
+                sqlx::query!("DELETE FROM answer_votes WHERE answer_uuid = $1 AND user_uuid = $2", uuid, user_uuid)
+                  .execute(&mut *tx)
+                  .await
+                  .map_err(DBError::from)?;
+            } else {
+                // A changed vote is cast anew, as contests only count the votes cast while they are open.
+                sqlx::query!(
+                    "INSERT INTO answer_votes (answer_uuid, user_uuid, value) VALUES ($1, $2, $3)
+                     ON CONFLICT (answer_uuid, user_uuid) DO UPDATE SET value = EXCLUDED.value, created_at = CURRENT_TIMESTAMP
+                     WHERE answer_votes.value <> EXCLUDED.value",
+                    uuid,
+                    user_uuid,
+                    value
+                  )
+                  .execute(&mut *tx)
+                  .await
+                  .map_err(DBError::from)?;
+            }
+
+            let score = sqlx::query_scalar!(
+                "SELECT COALESCE(SUM(value), 0) AS \"score!\" FROM counted_answer_votes WHERE answer_uuid = $1",
+                uuid
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            commit(tx).await?;
+
+            Ok(score)
+          })
+          .await
+    }
+
+    async fn stream_answers(&self, range: ExportRange, answers: mpsc::Sender<AnswerDetail>) -> Result<(), DBError> {
+        breaker()
+          .call(async {
+            let mut tx = begin_streaming(&self.db).await?;
+            let mut records = sqlx::query!(
+                "SELECT a.*, a.held_at IS NOT NULL AS \"held!\" FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                 WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 AND ($1::TIMESTAMP IS NULL OR a.created_at >= $1) AND ($2::TIMESTAMP IS NULL OR a.created_at < $2)
+                 ORDER BY a.created_at, a.answer_uuid",
+                range.from,
+                range.to
+              )
+              .fetch(&mut *tx);
+
+            while let Some(record) = records
+              .try_next()
+              .await
+              .map_err(DBError::from)?
+            {
+                let answer = AnswerDetail {
+                  answer_uuid: record.answer_uuid,
+                  question_uuid: record.question_uuid,
+                  content: record.content,
+                  author_uuid: record.author_uuid,
+                  created_at: record.created_at.assume_utc(),
+                  content_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  signals: None,
+                  question_age_warning: false,
+                  similar_answer_uuid: None,
+                  held_for_review: record.held,
+                };
+
+                if answers.send(answer).await.is_err() {
+                    break;
+                }
+            }
+
+            drop(records);
+            commit(tx).await
+          })
+          .await
+    }
 }
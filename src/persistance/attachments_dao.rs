@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{Attachment, AttachmentDetail, DBError};
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait AttachmentsDao {
+    async fn create_attachment(&self, attachment: Attachment, uploader_uuid: String) -> Result<AttachmentDetail, DBError>;
+    /// Callers decide who may read the attachment, from its uploader and question.
+    async fn get_attachment(&self, attachment_uuid: String) -> Result<Option<AttachmentDetail>, DBError>;
+}
+
+pub struct AttachmentsDaoImpl {
+    db: PgPool,
+}
+
+impl AttachmentsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      AttachmentsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl AttachmentsDao for AttachmentsDaoImpl {
+    async fn create_attachment(&self, attachment: Attachment, uploader_uuid: String) -> Result<AttachmentDetail, DBError> {
+        let (attachment, uploader_uuid) = (&attachment, &uploader_uuid);
+
+        retrying("create_attachment", || async move {
+            let uploader_uuid = parse_uuid(uploader_uuid)?;
+            let question_uuid = attachment.question_uuid;
+            let answer_uuid = attachment.answer_uuid;
+
+            let record = sqlx::query!(
+                "INSERT INTO attachments (storage_key, filename, content_type, size_bytes, uploader_uuid, question_uuid, answer_uuid)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+                attachment.storage_key,
+                attachment.filename,
+                attachment.content_type,
+                attachment.size_bytes,
+                uploader_uuid,
+                question_uuid,
+                answer_uuid
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(AttachmentDetail {
+              attachment_uuid: record.attachment_uuid,
+              storage_key: record.storage_key,
+              filename: record.filename,
+              content_type: record.content_type,
+              size_bytes: record.size_bytes,
+              uploader_uuid: record.uploader_uuid,
+              question_uuid: record.question_uuid,
+              answer_uuid: record.answer_uuid,
+              created_at: record.created_at.assume_utc(),
+              download: None,
+            })
+          })
+          .await
+    }
+
+    async fn get_attachment(&self, attachment_uuid: String) -> Result<Option<AttachmentDetail>, DBError> {
+        let attachment_uuid = &attachment_uuid;
+
+        retrying("get_attachment", || async move {
+            let uuid = parse_uuid(attachment_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM attachments WHERE attachment_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| AttachmentDetail {
+              attachment_uuid: record.attachment_uuid,
+              storage_key: record.storage_key,
+              filename: record.filename,
+              content_type: record.content_type,
+              size_bytes: record.size_bytes,
+              uploader_uuid: record.uploader_uuid,
+              question_uuid: record.question_uuid,
+              answer_uuid: record.answer_uuid,
+              created_at: record.created_at.assume_utc(),
+              download: None,
+            }))
+          })
+          .await
+    }
+}
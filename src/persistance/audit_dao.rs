@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use sqlx::{types::time::PrimitiveDateTime, PgPool};
+
+use crate::models::{AuditAction, AuditEntry, AuditRecord, AuditTarget, DBError, Pagination};
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait AuditDao {
+    async fn record_audit_entry(&self, entry: AuditEntry) -> Result<(), DBError>;
+    /// Lists entries recorded at or after `since`, in UTC, oldest first, so a reader can page
+    /// forward from where it left off.
+    async fn get_audit_log(&self, since: Option<PrimitiveDateTime>, page: Pagination) -> Result<Vec<AuditRecord>, DBError>;
+}
+
+pub struct AuditDaoImpl {
+    db: PgPool,
+}
+
+impl AuditDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      AuditDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_action(action: &str) -> Result<AuditAction, DBError> {
+    action.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_target(target: &str) -> Result<AuditTarget, DBError> {
+    target.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl AuditDao for AuditDaoImpl {
+    async fn record_audit_entry(&self, entry: AuditEntry) -> Result<(), DBError> {
+        let entry = &entry;
+
+        retrying("record_audit_entry", || async move {
+            sqlx::query!(
+                "INSERT INTO audit_log (actor_uuid, action, target_type, target_uuid, payload) VALUES ($1, $2, $3, $4, $5)",
+                entry.actor_uuid,
+                entry.action.as_str(),
+                entry.target_type.as_str(),
+                entry.target_uuid,
+                entry.payload
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_audit_log(&self, since: Option<PrimitiveDateTime>, page: Pagination) -> Result<Vec<AuditRecord>, DBError> {
+        retrying("get_audit_log", || async move {
+            let records = sqlx::query!(
+                "SELECT * FROM audit_log WHERE ($1::TIMESTAMP IS NULL OR created_at >= $1)
+                 ORDER BY created_at, audit_uuid OFFSET $2 LIMIT $3",
+                since,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(AuditRecord {
+                  audit_uuid: record.audit_uuid,
+                  actor_uuid: record.actor_uuid,
+                  action: parse_action(&record.action)?,
+                  target_type: parse_target(&record.target_type)?,
+                  target_uuid: record.target_uuid,
+                  payload: record.payload,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+}
@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{Board, BoardDetail, BoardMember, BoardRole, DBError, MembershipStatus};
+
+use super::{begin, retry::retrying};
+
+#[async_trait]
+pub trait BoardsDao {
+    /// Creates the board with `owner_uuid` as its first, active owner.
+    async fn create_board(&self, board: Board, owner_uuid: String) -> Result<BoardDetail, DBError>;
+    /// True only for active members; pending invites and join requests do not count.
+    async fn is_board_member(&self, board_uuid: String, user_uuid: String) -> Result<bool, DBError>;
+    async fn get_board_member(&self, board_uuid: String, user_uuid: String) -> Result<Option<BoardMember>, DBError>;
+    async fn get_board_members(&self, board_uuid: String) -> Result<Vec<BoardMember>, DBError>;
+    /// Invites the user, or activates them straight away if they had asked to join.
+    async fn invite_board_member(&self, board_uuid: String, user_uuid: String, role: BoardRole) -> Result<BoardMember, DBError>;
+    /// Asks to join the board, or accepts a pending invite.
+    async fn request_board_membership(&self, board_uuid: String, user_uuid: String) -> Result<BoardMember, DBError>;
+    /// Activates a pending join request; returns `None` when there is none.
+    async fn approve_board_member(&self, board_uuid: String, user_uuid: String) -> Result<Option<BoardMember>, DBError>;
+    async fn remove_board_member(&self, board_uuid: String, user_uuid: String) -> Result<(), DBError>;
+}
+
+pub struct BoardsDaoImpl {
+    db: PgPool,
+}
+
+impl BoardsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      BoardsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_role(role: &str) -> Result<BoardRole, DBError> {
+    role.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_membership_status(status: &str) -> Result<MembershipStatus, DBError> {
+    status.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl BoardsDao for BoardsDaoImpl {
+    async fn create_board(&self, board: Board, owner_uuid: String) -> Result<BoardDetail, DBError> {
+        let (board, owner_uuid) = (&board, &owner_uuid);
+
+        retrying("create_board", || async move {
+            let owner_uuid = parse_uuid(owner_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let record = sqlx::query!(
+                "INSERT INTO boards (name) VALUES ($1) RETURNING *",
+                board.name
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            sqlx::query!(
+                "INSERT INTO board_members (board_uuid, user_uuid, role, status) VALUES ($1, $2, $3, $4)",
+                record.board_uuid,
+                owner_uuid,
+                BoardRole::Owner.as_str(),
+                MembershipStatus::Active.as_str()
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(BoardDetail {
+                board_uuid: record.board_uuid,
+                name: record.name,
+                created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn is_board_member(&self, board_uuid: String, user_uuid: String) -> Result<bool, DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("is_board_member", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT EXISTS (SELECT 1 FROM board_members WHERE board_uuid = $1 AND user_uuid = $2 AND status = 'active') AS \"is_member!\"",
+                uuid,
+                user_uuid
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.is_member)
+          })
+          .await
+    }
+
+    async fn get_board_member(&self, board_uuid: String, user_uuid: String) -> Result<Option<BoardMember>, DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("get_board_member", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT * FROM board_members WHERE board_uuid = $1 AND user_uuid = $2",
+                uuid,
+                user_uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(BoardMember {
+                  board_uuid: record.board_uuid,
+                  user_uuid: record.user_uuid,
+                  role: parse_role(&record.role)?,
+                  status: parse_membership_status(&record.status)?,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn get_board_members(&self, board_uuid: String) -> Result<Vec<BoardMember>, DBError> {
+        let board_uuid = &board_uuid;
+
+        retrying("get_board_members", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let records = sqlx::query!(
+                "SELECT * FROM board_members WHERE board_uuid = $1 ORDER BY created_at",
+                uuid
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(BoardMember {
+                  board_uuid: record.board_uuid,
+                  user_uuid: record.user_uuid,
+                  role: parse_role(&record.role)?,
+                  status: parse_membership_status(&record.status)?,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn invite_board_member(&self, board_uuid: String, user_uuid: String, role: BoardRole) -> Result<BoardMember, DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("invite_board_member", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO board_members (board_uuid, user_uuid, role, status) VALUES ($1, $2, $3, 'invited')
+                 ON CONFLICT (board_uuid, user_uuid) DO UPDATE SET role = EXCLUDED.role,
+                 status = CASE WHEN board_members.status = 'requested' THEN 'active' ELSE board_members.status END
+                 RETURNING *",
+                uuid,
+                user_uuid,
+                role.as_str()
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(BoardMember {
+                board_uuid: record.board_uuid,
+                user_uuid: record.user_uuid,
+                role: parse_role(&record.role)?,
+                status: parse_membership_status(&record.status)?,
+                created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn request_board_membership(&self, board_uuid: String, user_uuid: String) -> Result<BoardMember, DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("request_board_membership", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO board_members (board_uuid, user_uuid, status) VALUES ($1, $2, 'requested')
+                 ON CONFLICT (board_uuid, user_uuid) DO UPDATE SET
+                 status = CASE WHEN board_members.status = 'invited' THEN 'active' ELSE board_members.status END
+                 RETURNING *",
+                uuid,
+                user_uuid
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(BoardMember {
+                board_uuid: record.board_uuid,
+                user_uuid: record.user_uuid,
+                role: parse_role(&record.role)?,
+                status: parse_membership_status(&record.status)?,
+                created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn approve_board_member(&self, board_uuid: String, user_uuid: String) -> Result<Option<BoardMember>, DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("approve_board_member", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "UPDATE board_members SET status = 'active' WHERE board_uuid = $1 AND user_uuid = $2 AND status = 'requested' RETURNING *",
+                uuid,
+                user_uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(BoardMember {
+                  board_uuid: record.board_uuid,
+                  user_uuid: record.user_uuid,
+                  role: parse_role(&record.role)?,
+                  status: parse_membership_status(&record.status)?,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn remove_board_member(&self, board_uuid: String, user_uuid: String) -> Result<(), DBError> {
+        let (board_uuid, user_uuid) = (&board_uuid, &user_uuid);
+
+        retrying("remove_board_member", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            sqlx::query!(
+                "DELETE FROM board_members WHERE board_uuid = $1 AND user_uuid = $2",
+                uuid,
+                user_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    future::Future,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::models::DBError;
+
+use super::timing::timed;
+
+static BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+
+/// When the circuit breaker stops sending operations to the database, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerPolicy {
+    /// Operations in a row that could not reach the database, or timed out, before it trips.
+    pub failure_threshold: u32,
+    /// How long it stays open before letting a trial operation through.
+    pub open_for: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        CircuitBreakerPolicy {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    /// A trial operation went through at `since`. Should it never report back, e.g. because its
+    /// request was cancelled, another is let through once `open_for` has passed again.
+    HalfOpen { since: Instant },
+}
+
+/// Fails operations fast with `DBError::CircuitOpen` while the database is down, rather than
+/// having each one wait for a connection until the pool times out.
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        CircuitBreaker {
+            policy,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether an operation may go to the database now; when it may, it must be `record`ed.
+    fn allow(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { since } | State::HalfOpen { since } if now.duration_since(since) >= self.policy.open_for => {
+                *state = State::HalfOpen { since: now };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    /// Only failures to reach the database count against it; any answer from it, errors included,
+    /// closes the breaker again.
+    fn record<T>(&self, result: &Result<T, DBError>, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let failed = matches!(result, Err(DBError::ConnectionError | DBError::Timeout));
+
+        *state = match (*state, failed) {
+            (State::Closed { .. }, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.policy.failure_threshold => {
+                State::Closed { failures: failures + 1 }
+            }
+            (State::Closed { .. } | State::HalfOpen { .. }, true) => {
+                warn!("Suspending database operations for {:?} after repeated failures.", self.policy.open_for);
+                State::Open { since: now }
+            }
+            (State::HalfOpen { .. }, false) => {
+                info!("Resuming database operations.");
+                State::Closed { failures: 0 }
+            }
+            // Operations let through before it tripped.
+            (open @ State::Open { .. }, _) => open,
+        };
+    }
+
+    /// Runs `op` unless the breaker is open, and records its outcome.
+    pub(crate) async fn call<T, Fut>(&self, op: Fut) -> Result<T, DBError>
+    where
+        Fut: Future<Output = Result<T, DBError>>,
+    {
+        if !self.allow(Instant::now()) {
+            return Err(DBError::CircuitOpen);
+        }
+
+        let result = op.await;
+        self.record(&result, Instant::now());
+
+        result
+    }
+}
+
+/// Sets the policy of the breaker shared by the DAOs for the rest of the process. Only a call
+/// before the first database operation has an effect; without one, the default policy applies.
+pub fn configure(policy: CircuitBreakerPolicy) {
+    if BREAKER.set(CircuitBreaker::new(policy)).is_err() {
+        warn!("The database circuit breaker is already configured.");
+    }
+}
+
+/// The breaker shared by the DAOs, as there is one database behind them all.
+pub(crate) fn breaker() -> &'static CircuitBreaker {
+    BREAKER.get_or_init(|| CircuitBreaker::new(CircuitBreakerPolicy::default()))
+}
+
+/// Runs the DAO operation `op` once through the shared breaker, `timed` under `name`, for the
+/// operations that are not `retrying`: claims of queued work and updates of counters, which a retry
+/// would repeat should the failure have hidden their commit.
+/// Streams and operations over whole tables call the breaker untimed, as they may run for longer.
+pub(crate) async fn guarded<T, Fut>(name: &'static str, op: Fut) -> Result<T, DBError>
+where
+    Fut: Future<Output = Result<T, DBError>>,
+{
+    breaker().call(timed(name, op)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_FOR: Duration = Duration::from_secs(10);
+
+    fn breaker(failure_threshold: u32) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold,
+            open_for: OPEN_FOR,
+        })
+    }
+
+    fn failure() -> Result<(), DBError> {
+        Err(DBError::ConnectionError)
+    }
+
+    #[test]
+    fn breaker_should_trip_after_consecutive_failures() {
+        let breaker = breaker(2);
+        let now = Instant::now();
+
+        breaker.record(&failure(), now);
+        assert!(breaker.allow(now));
+
+        breaker.record(&failure(), now);
+        assert!(!breaker.allow(now));
+    }
+
+    #[test]
+    fn breaker_should_not_count_failures_interrupted_by_answers() {
+        let breaker = breaker(2);
+        let now = Instant::now();
+
+        breaker.record(&failure(), now);
+        breaker.record::<()>(&Err(DBError::UniqueViolation("test".to_owned())), now);
+        breaker.record(&failure(), now);
+
+        assert!(breaker.allow(now));
+    }
+
+    #[test]
+    fn breaker_should_let_one_trial_through_once_half_open() {
+        let breaker = breaker(1);
+        let now = Instant::now();
+
+        breaker.record(&failure(), now);
+
+        let later = now + OPEN_FOR;
+        assert!(breaker.allow(later));
+        assert!(!breaker.allow(later));
+
+        breaker.record(&Ok(()), later);
+        assert!(breaker.allow(later));
+    }
+
+    #[test]
+    fn breaker_should_open_again_when_the_trial_fails() {
+        let breaker = breaker(1);
+        let now = Instant::now();
+
+        breaker.record(&failure(), now);
+
+        let later = now + OPEN_FOR;
+        assert!(breaker.allow(later));
+
+        breaker.record(&failure(), later);
+        assert!(!breaker.allow(later + OPEN_FOR / 2));
+        assert!(breaker.allow(later + OPEN_FOR));
+    }
+
+    #[tokio::test]
+    async fn call_should_fail_fast_while_open() {
+        let breaker = breaker(1);
+
+        let _ = breaker.call(async { failure() }).await;
+        let result = breaker.call(async { Ok(()) }).await;
+
+        assert!(matches!(result, Err(DBError::CircuitOpen)));
+    }
+}
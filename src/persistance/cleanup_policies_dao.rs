@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{BoardCleanup, BoardCleanupPolicy, BoardCleanupPolicyDetail, DBError};
+
+use super::{begin, retry::retrying};
+
+#[async_trait]
+pub trait CleanupPoliciesDao {
+    async fn get_cleanup_policies(&self) -> Result<Vec<BoardCleanupPolicyDetail>, DBError>;
+    async fn get_cleanup_policy(&self, board_uuid: String) -> Result<Option<BoardCleanupPolicyDetail>, DBError>;
+    /// Creates or replaces the board's policy. Returns `None` when the board does not exist.
+    async fn set_cleanup_policy(
+        &self,
+        board_uuid: String,
+        policy: BoardCleanupPolicy,
+        updated_by: String,
+    ) -> Result<Option<BoardCleanupPolicyDetail>, DBError>;
+    /// Returns whether the board had a policy.
+    async fn delete_cleanup_policy(&self, board_uuid: String) -> Result<bool, DBError>;
+    /// Lists the questions `run_cleanup` would close and delete right now, without changing them.
+    async fn preview_cleanup(&self, board_uuid: String, policy: BoardCleanupPolicy) -> Result<BoardCleanup, DBError>;
+    /// Soft-deletes old unanswered questions, then closes inactive ones, so a question matching
+    /// both rules is deleted.
+    async fn run_cleanup(&self, board_uuid: String, policy: BoardCleanupPolicy) -> Result<BoardCleanup, DBError>;
+}
+
+pub struct CleanupPoliciesDaoImpl {
+    db: PgPool,
+}
+
+impl CleanupPoliciesDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      CleanupPoliciesDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn close_reason(days: Option<i32>) -> String {
+    format!("Closed automatically after {} days without activity.", days.unwrap_or_default())
+}
+
+#[async_trait]
+impl CleanupPoliciesDao for CleanupPoliciesDaoImpl {
+    async fn get_cleanup_policies(&self) -> Result<Vec<BoardCleanupPolicyDetail>, DBError> {
+        retrying("get_cleanup_policies", || async move {
+            let records = sqlx::query!("SELECT * FROM board_cleanup_policies ORDER BY board_uuid")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| BoardCleanupPolicyDetail {
+                board_uuid: record.board_uuid,
+                close_inactive_after_days: record.close_inactive_after_days,
+                delete_unanswered_after_days: record.delete_unanswered_after_days,
+                updated_by: record.updated_by,
+                updated_at: record.updated_at.assume_utc(),
+              })
+              .collect())
+          })
+          .await
+    }
+
+    async fn get_cleanup_policy(&self, board_uuid: String) -> Result<Option<BoardCleanupPolicyDetail>, DBError> {
+        let board_uuid = &board_uuid;
+
+        retrying("get_cleanup_policy", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM board_cleanup_policies WHERE board_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| BoardCleanupPolicyDetail {
+                board_uuid: record.board_uuid,
+                close_inactive_after_days: record.close_inactive_after_days,
+                delete_unanswered_after_days: record.delete_unanswered_after_days,
+                updated_by: record.updated_by,
+                updated_at: record.updated_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+
+    async fn set_cleanup_policy(
+        &self,
+        board_uuid: String,
+        policy: BoardCleanupPolicy,
+        updated_by: String,
+    ) -> Result<Option<BoardCleanupPolicyDetail>, DBError> {
+        let (board_uuid, policy, updated_by) = (&board_uuid, &policy, &updated_by);
+
+        retrying("set_cleanup_policy", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let updated_by = parse_uuid(updated_by)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO board_cleanup_policies (board_uuid, close_inactive_after_days, delete_unanswered_after_days, updated_by)
+                 SELECT board_uuid, $2, $3, $4 FROM boards WHERE board_uuid = $1
+                 ON CONFLICT (board_uuid) DO UPDATE
+                 SET close_inactive_after_days = EXCLUDED.close_inactive_after_days,
+                   delete_unanswered_after_days = EXCLUDED.delete_unanswered_after_days,
+                   updated_by = EXCLUDED.updated_by, updated_at = CURRENT_TIMESTAMP
+                 RETURNING *",
+                uuid,
+                policy.close_inactive_after_days,
+                policy.delete_unanswered_after_days,
+                updated_by
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| BoardCleanupPolicyDetail {
+                board_uuid: record.board_uuid,
+                close_inactive_after_days: record.close_inactive_after_days,
+                delete_unanswered_after_days: record.delete_unanswered_after_days,
+                updated_by: record.updated_by,
+                updated_at: record.updated_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+
+    async fn delete_cleanup_policy(&self, board_uuid: String) -> Result<bool, DBError> {
+        let board_uuid = &board_uuid;
+
+        retrying("delete_cleanup_policy", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let result = sqlx::query!("DELETE FROM board_cleanup_policies WHERE board_uuid = $1", uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn preview_cleanup(&self, board_uuid: String, policy: BoardCleanupPolicy) -> Result<BoardCleanup, DBError> {
+        let (board_uuid, policy) = (&board_uuid, &policy);
+
+        retrying("preview_cleanup", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let deleted = sqlx::query!(
+                "SELECT q.question_uuid FROM questions q
+                 WHERE q.board_uuid = $1 AND q.deleted_at IS NULL AND $2::INT IS NOT NULL
+                 AND q.created_at < CURRENT_TIMESTAMP - make_interval(days => $2)
+                 AND NOT EXISTS (SELECT 1 FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL)
+                 ORDER BY q.created_at",
+                uuid,
+                policy.delete_unanswered_after_days
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let closed = sqlx::query!(
+                "SELECT q.question_uuid FROM questions q
+                 WHERE q.board_uuid = $1 AND q.deleted_at IS NULL AND q.status = 'open' AND $2::INT IS NOT NULL
+                 AND GREATEST(q.updated_at, (SELECT MAX(a.updated_at) FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL))
+                   < CURRENT_TIMESTAMP - make_interval(days => $2)
+                 AND NOT (q.question_uuid = ANY($3))
+                 ORDER BY q.created_at",
+                uuid,
+                policy.close_inactive_after_days,
+                &deleted.iter().map(|record| record.question_uuid).collect::<Vec<Uuid>>()
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(BoardCleanup {
+                board_uuid: uuid,
+                dry_run: true,
+                closed_question_uuids: closed.into_iter().map(|record| record.question_uuid).collect(),
+                deleted_question_uuids: deleted.into_iter().map(|record| record.question_uuid).collect(),
+            })
+          })
+          .await
+    }
+
+    async fn run_cleanup(&self, board_uuid: String, policy: BoardCleanupPolicy) -> Result<BoardCleanup, DBError> {
+        let (board_uuid, policy) = (&board_uuid, &policy);
+
+        retrying("run_cleanup", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let deleted = sqlx::query!(
+                "UPDATE questions q SET deleted_at = CURRENT_TIMESTAMP
+                 WHERE q.board_uuid = $1 AND q.deleted_at IS NULL AND $2::INT IS NOT NULL
+                 AND q.created_at < CURRENT_TIMESTAMP - make_interval(days => $2)
+                 AND NOT EXISTS (SELECT 1 FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL)
+                 RETURNING q.question_uuid",
+                uuid,
+                policy.delete_unanswered_after_days
+              )
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let closed = sqlx::query!(
+                "UPDATE questions q SET status = 'closed-inactive', status_reason = $3, updated_at = CURRENT_TIMESTAMP
+                 WHERE q.board_uuid = $1 AND q.deleted_at IS NULL AND q.status = 'open' AND $2::INT IS NOT NULL
+                 AND GREATEST(q.updated_at, (SELECT MAX(a.updated_at) FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL))
+                   < CURRENT_TIMESTAMP - make_interval(days => $2)
+                 RETURNING q.question_uuid",
+                uuid,
+                policy.close_inactive_after_days,
+                close_reason(policy.close_inactive_after_days)
+              )
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(BoardCleanup {
+                board_uuid: uuid,
+                dry_run: false,
+                closed_question_uuids: closed.into_iter().map(|record| record.question_uuid).collect(),
+                deleted_question_uuids: deleted.into_iter().map(|record| record.question_uuid).collect(),
+            })
+          })
+          .await
+    }
+}
@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+use time::PrimitiveDateTime;
+
+use crate::models::{ContentState, DBError, DraftDetail, MyAnswer, MyContent, MyQuestion, Pagination, Unsubscribed};
+
+use super::{
+    begin,
+    retry::retrying,
+    questions_dao::{parse_status, parse_visibility},
+};
+
+#[async_trait]
+pub trait ContentDao {
+    /// The user's questions and answers in every state, and their drafts.
+    async fn get_user_content(&self, user_uuid: String, page: Pagination) -> Result<MyContent, DBError>;
+    /// Returns how many drafts were deleted.
+    async fn delete_user_drafts(&self, user_uuid: String) -> Result<u64, DBError>;
+    /// Unfollows every question and cancels the email digest, in one transaction.
+    async fn unsubscribe_user(&self, user_uuid: String) -> Result<Unsubscribed, DBError>;
+}
+
+pub struct ContentDaoImpl {
+    db: PgPool,
+}
+
+impl ContentDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      ContentDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn state_of(held_at: Option<PrimitiveDateTime>, deleted_at: Option<PrimitiveDateTime>) -> ContentState {
+    match (held_at, deleted_at) {
+        (_, Some(_)) => ContentState::Deleted,
+        (Some(_), None) => ContentState::Held,
+        (None, None) => ContentState::Published,
+    }
+}
+
+#[async_trait]
+impl ContentDao for ContentDaoImpl {
+    async fn get_user_content(&self, user_uuid: String, page: Pagination) -> Result<MyContent, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_user_content", || async move {
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let questions = sqlx::query!(
+                "SELECT question_uuid, title, status, visibility, held_at, deleted_at, created_at FROM questions
+                 WHERE author_uuid = $1
+                 ORDER BY created_at DESC, question_uuid DESC
+                 OFFSET $2 LIMIT $3",
+                user_uuid,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let answers = sqlx::query!(
+                "SELECT a.answer_uuid, a.question_uuid, q.title AS question_title, a.held_at, a.deleted_at, a.created_at
+                 FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                 WHERE a.author_uuid = $1
+                 ORDER BY a.created_at DESC, a.answer_uuid DESC
+                 OFFSET $2 LIMIT $3",
+                user_uuid,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let drafts = sqlx::query!("SELECT * FROM drafts WHERE user_uuid = $1 ORDER BY updated_at DESC", user_uuid)
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(MyContent {
+                questions: questions
+                  .into_iter()
+                  .map(|record| {
+                    Ok(MyQuestion {
+                      question_uuid: record.question_uuid,
+                      title: record.title,
+                      status: parse_status(&record.status)?,
+                      visibility: parse_visibility(&record.visibility)?,
+                      state: state_of(record.held_at, record.deleted_at),
+                      created_at: record.created_at.assume_utc(),
+                    })
+                  })
+                  .collect::<Result<_, DBError>>()?,
+                answers: answers
+                  .into_iter()
+                  .map(|record| MyAnswer {
+                    answer_uuid: record.answer_uuid,
+                    question_uuid: record.question_uuid,
+                    question_title: record.question_title,
+                    state: state_of(record.held_at, record.deleted_at),
+                    created_at: record.created_at.assume_utc(),
+                  })
+                  .collect(),
+                drafts: drafts
+                  .into_iter()
+                  .map(|record| DraftDetail {
+                    draft_uuid: record.draft_uuid,
+                    title: record.title,
+                    description: record.description,
+                    updated_at: record.updated_at.assume_utc(),
+                  })
+                  .collect(),
+            })
+          })
+          .await
+    }
+
+    async fn delete_user_drafts(&self, user_uuid: String) -> Result<u64, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("delete_user_drafts", || async move {
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let result = sqlx::query!("DELETE FROM drafts WHERE user_uuid = $1", user_uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn unsubscribe_user(&self, user_uuid: String) -> Result<Unsubscribed, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("unsubscribe_user", || async move {
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let unfollowed = sqlx::query!("DELETE FROM question_followers WHERE user_uuid = $1", user_uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let digest = sqlx::query!("DELETE FROM digest_subscriptions WHERE user_uuid = $1", user_uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Unsubscribed {
+                unfollowed_questions: unfollowed.rows_affected(),
+                digest: digest.rows_affected() > 0,
+            })
+          })
+          .await
+    }
+}
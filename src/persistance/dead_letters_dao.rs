@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{BulkDeleteResult, DBError, DeadLetter, DeadLetterKind};
+
+use super::{bulk_delete_results, circuit_breaker::guarded, retry::retrying};
+
+#[async_trait]
+pub trait DeadLettersDao {
+    async fn create_dead_letter(
+        &self,
+        kind: DeadLetterKind,
+        payload: serde_json::Value,
+        errors: Vec<String>,
+    ) -> Result<DeadLetter, DBError>;
+    /// Lists every dead letter, most recently failed first.
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, DBError>;
+    async fn get_dead_letter(&self, dead_letter_uuid: String) -> Result<Option<DeadLetter>, DBError>;
+    /// Adds a failed retry to the dead letter's history.
+    async fn record_dead_letter_failure(&self, dead_letter_uuid: String, error: String) -> Result<(), DBError>;
+    async fn delete_dead_letters(&self, dead_letter_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError>;
+}
+
+pub struct DeadLettersDaoImpl {
+    db: PgPool,
+}
+
+impl DeadLettersDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      DeadLettersDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_kind(kind: &str) -> Result<DeadLetterKind, DBError> {
+    kind.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl DeadLettersDao for DeadLettersDaoImpl {
+    async fn create_dead_letter(
+        &self,
+        kind: DeadLetterKind,
+        payload: serde_json::Value,
+        errors: Vec<String>,
+    ) -> Result<DeadLetter, DBError> {
+        let (payload, errors) = (&payload, &errors);
+
+        retrying("create_dead_letter", || async move {
+            let record = sqlx::query!(
+                "INSERT INTO dead_letters (kind, payload, attempts, errors) VALUES ($1, $2, $3, $4) RETURNING *",
+                kind.as_str(),
+                payload,
+                errors.len() as i32,
+                &errors
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(DeadLetter {
+                dead_letter_uuid: record.dead_letter_uuid,
+                kind: parse_kind(&record.kind)?,
+                payload: record.payload,
+                attempts: record.attempts,
+                errors: record.errors,
+                created_at: record.created_at.assume_utc(),
+                last_failed_at: record.last_failed_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, DBError> {
+        retrying("get_dead_letters", || async move {
+            let records = sqlx::query!("SELECT * FROM dead_letters ORDER BY last_failed_at DESC")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(DeadLetter {
+                  dead_letter_uuid: record.dead_letter_uuid,
+                  kind: parse_kind(&record.kind)?,
+                  payload: record.payload,
+                  attempts: record.attempts,
+                  errors: record.errors,
+                  created_at: record.created_at.assume_utc(),
+                  last_failed_at: record.last_failed_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn get_dead_letter(&self, dead_letter_uuid: String) -> Result<Option<DeadLetter>, DBError> {
+        let dead_letter_uuid = &dead_letter_uuid;
+
+        retrying("get_dead_letter", || async move {
+            let uuid = parse_uuid(dead_letter_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM dead_letters WHERE dead_letter_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(DeadLetter {
+                  dead_letter_uuid: record.dead_letter_uuid,
+                  kind: parse_kind(&record.kind)?,
+                  payload: record.payload,
+                  attempts: record.attempts,
+                  errors: record.errors,
+                  created_at: record.created_at.assume_utc(),
+                  last_failed_at: record.last_failed_at.assume_utc(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn record_dead_letter_failure(&self, dead_letter_uuid: String, error: String) -> Result<(), DBError> {
+        guarded("record_dead_letter_failure", async {
+            let uuid = parse_uuid(&dead_letter_uuid)?;
+
+            sqlx::query!(
+                "UPDATE dead_letters SET attempts = attempts + 1, errors = array_append(errors, $2), last_failed_at = CURRENT_TIMESTAMP
+                 WHERE dead_letter_uuid = $1",
+                uuid,
+                error
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn delete_dead_letters(&self, dead_letter_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+        let dead_letter_uuids = &dead_letter_uuids;
+
+        retrying("delete_dead_letters", || async move {
+            let uuids: Vec<Uuid> = dead_letter_uuids
+              .iter()
+              .filter_map(|uuid| Uuid::parse_str(uuid).ok())
+              .collect();
+
+            let deleted = sqlx::query_scalar!(
+                "DELETE FROM dead_letters WHERE dead_letter_uuid = ANY($1) RETURNING dead_letter_uuid",
+                &uuids
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(bulk_delete_results(dead_letter_uuids.clone(), &deleted))
+          })
+          .await
+    }
+}
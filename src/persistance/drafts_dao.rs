@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::{
+    language::detect_language,
+    models::{DBError, DraftDetail, QuestionDetail, QuestionDraft},
+};
+
+use super::{
+    begin,
+    retry::retrying,
+    questions_dao::{contest_of, parse_kind, parse_status, parse_visibility},
+};
+
+#[async_trait]
+pub trait DraftsDao {
+    /// Creates or overwrites the user's question draft.
+    async fn save_question_draft(&self, user_uuid: String, draft: QuestionDraft) -> Result<DraftDetail, DBError>;
+    async fn get_draft(&self, draft_uuid: String, user_uuid: String) -> Result<Option<DraftDetail>, DBError>;
+    /// Turns the draft into a question authored by its owner and removes the draft.
+    async fn publish_draft(&self, draft_uuid: String, user_uuid: String) -> Result<Option<QuestionDetail>, DBError>;
+}
+
+pub struct DraftsDaoImpl {
+    db: PgPool,
+}
+
+impl DraftsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      DraftsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl DraftsDao for DraftsDaoImpl {
+    async fn save_question_draft(&self, user_uuid: String, draft: QuestionDraft) -> Result<DraftDetail, DBError> {
+        let (user_uuid, draft) = (&user_uuid, &draft);
+
+        retrying("save_question_draft", || async move {
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO drafts (user_uuid, title, description) VALUES ($1, $2, $3)
+                 ON CONFLICT (user_uuid) DO UPDATE SET title = EXCLUDED.title, description = EXCLUDED.description, updated_at = CURRENT_TIMESTAMP
+                 RETURNING *",
+                user_uuid,
+                draft.title,
+                draft.description
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(DraftDetail {
+              draft_uuid: record.draft_uuid,
+              title: record.title,
+              description: record.description,
+              updated_at: record.updated_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_draft(&self, draft_uuid: String, user_uuid: String) -> Result<Option<DraftDetail>, DBError> {
+        let (draft_uuid, user_uuid) = (&draft_uuid, &user_uuid);
+
+        retrying("get_draft", || async move {
+            let uuid = parse_uuid(draft_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM drafts WHERE draft_uuid = $1 AND user_uuid = $2", uuid, user_uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| {
+              DraftDetail {
+                draft_uuid: record.draft_uuid,
+                title: record.title,
+                description: record.description,
+                updated_at: record.updated_at.assume_utc(),
+              }
+            }))
+          })
+          .await
+    }
+
+    async fn publish_draft(&self, draft_uuid: String, user_uuid: String) -> Result<Option<QuestionDetail>, DBError> {
+        let (draft_uuid, user_uuid) = (&draft_uuid, &user_uuid);
+
+        retrying("publish_draft", || async move {
+            let uuid = parse_uuid(draft_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let draft = sqlx::query!(
+                "DELETE FROM drafts WHERE draft_uuid = $1 AND user_uuid = $2 RETURNING title, description",
+                uuid,
+                user_uuid
+              )
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let Some(draft) = draft else {
+                return Ok(None);
+            };
+
+            let record = sqlx::query!(
+                "INSERT INTO questions (title, description, author_uuid, language) VALUES ($1, $2, $3, $4) RETURNING *",
+                draft.title,
+                draft.description,
+                user_uuid,
+                detect_language(&draft.title, &draft.description)
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(QuestionDetail {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                description: record.description,
+                status: parse_status(&record.status)?,
+                status_reason: record.status_reason,
+                kind: parse_kind(&record.kind)?,
+                contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                author_uuid: record.author_uuid,
+                visibility: parse_visibility(&record.visibility)?,
+                board_uuid: record.board_uuid,
+                tags: record.tags,
+                language: record.language,
+                created_at: record.created_at.assume_utc(),
+                updated_at: record.updated_at.assume_utc(),
+                description_html: None,
+                code_blocks: Vec::new(),
+                link_previews: Vec::new(),
+                held_for_review: false,
+                pending_tags: Vec::new(),
+            }))
+          })
+          .await
+    }
+}
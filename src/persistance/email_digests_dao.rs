@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use sqlx::{types::{time::PrimitiveDateTime, Uuid}, PgPool};
+
+use crate::models::{DBError, DigestFrequency, DigestNotification, DigestQuestion, DigestSettings, EmailDigest};
+
+use super::{circuit_breaker::guarded, retry::retrying};
+
+/// Notifications and questions listed per digest; the unread count covers the rest.
+const DIGEST_ITEMS: i64 = 10;
+
+#[async_trait]
+pub trait EmailDigestsDao {
+    async fn get_digest_settings(&self, user_uuid: String) -> Result<DigestSettings, DBError>;
+    /// A new subscription gets its first digest after one period; `DigestFrequency::Off` unsubscribes.
+    async fn set_digest_settings(&self, user_uuid: String, settings: DigestSettings) -> Result<(), DBError>;
+    /// Claims up to `limit` subscriptions whose period has passed and collects their digests. The
+    /// period restarts on claim, so a digest that fails to send is skipped rather than sent twice.
+    async fn claim_due_digests(&self, limit: i64) -> Result<Vec<EmailDigest>, DBError>;
+}
+
+pub struct EmailDigestsDaoImpl {
+    db: PgPool,
+}
+
+impl EmailDigestsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      EmailDigestsDaoImpl {
+        db
+      }
+    }
+
+    async fn collect_digest(
+        &self,
+        user_uuid: Uuid,
+        frequency: DigestFrequency,
+        tags: Vec<String>,
+        since: PrimitiveDateTime,
+    ) -> Result<EmailDigest, DBError> {
+        let unread_notifications = sqlx::query_scalar!(
+            "SELECT COUNT(*) AS \"count!\" FROM notifications n
+             JOIN questions q ON q.question_uuid = n.question_uuid
+             WHERE n.user_uuid = $1 AND n.in_app AND n.read_at IS NULL AND q.deleted_at IS NULL AND post_visible_to(q.author_uuid, $1)",
+            user_uuid
+          )
+          .fetch_one(&self.db)
+          .await
+          .map_err(DBError::from)?;
+
+        let notifications = sqlx::query!(
+            "SELECT n.kind, q.question_uuid, q.title, u.username AS \"actor_username?\" FROM notifications n
+             JOIN questions q ON q.question_uuid = n.question_uuid
+             LEFT JOIN users u ON u.user_uuid = n.actor_uuid
+             WHERE n.user_uuid = $1 AND n.in_app AND n.read_at IS NULL AND q.deleted_at IS NULL AND post_visible_to(q.author_uuid, $1)
+             ORDER BY n.created_at DESC, n.id DESC LIMIT $2",
+            user_uuid,
+            DIGEST_ITEMS
+          )
+          .fetch_all(&self.db)
+          .await
+          .map_err(DBError::from)?;
+
+        let questions = sqlx::query!(
+            "SELECT q.question_uuid, q.title,
+               (SELECT COUNT(*) FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL) AS \"answers!\"
+             FROM questions q
+             WHERE q.tags && $2 AND q.created_at > $3 AND q.deleted_at IS NULL AND post_visible_to(q.author_uuid, $1)
+             AND (q.visibility <> 'private' OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $1 AND m.status = 'active'))
+             ORDER BY 3 DESC, q.created_at DESC LIMIT $4",
+            user_uuid,
+            &tags,
+            since,
+            DIGEST_ITEMS
+          )
+          .fetch_all(&self.db)
+          .await
+          .map_err(DBError::from)?;
+
+        Ok(EmailDigest {
+            user_uuid,
+            frequency,
+            unread_notifications,
+            notifications: notifications
+              .into_iter()
+              .map(|record| {
+                Ok(DigestNotification {
+                  kind: record.kind.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  question_uuid: record.question_uuid,
+                  question_title: record.title,
+                  actor_username: record.actor_username,
+                })
+              })
+              .collect::<Result<_, DBError>>()?,
+            questions: questions
+              .into_iter()
+              .map(|record| DigestQuestion {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                answers: record.answers,
+              })
+              .collect(),
+        })
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl EmailDigestsDao for EmailDigestsDaoImpl {
+    async fn get_digest_settings(&self, user_uuid: String) -> Result<DigestSettings, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_digest_settings", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!("SELECT frequency, tags FROM digest_subscriptions WHERE user_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            match record {
+                Some(record) => Ok(DigestSettings {
+                    frequency: record.frequency.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                    tags: record.tags,
+                }),
+                None => Ok(DigestSettings::default()),
+            }
+          })
+          .await
+    }
+
+    async fn set_digest_settings(&self, user_uuid: String, settings: DigestSettings) -> Result<(), DBError> {
+        let (user_uuid, settings) = (&user_uuid, &settings);
+
+        retrying("set_digest_settings", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let query = if settings.frequency == DigestFrequency::Off {
+                sqlx::query!("DELETE FROM digest_subscriptions WHERE user_uuid = $1", uuid)
+            } else {
+                sqlx::query!(
+                    "INSERT INTO digest_subscriptions (user_uuid, frequency, tags) VALUES ($1, $2, $3)
+                     ON CONFLICT (user_uuid) DO UPDATE SET frequency = EXCLUDED.frequency, tags = EXCLUDED.tags",
+                    uuid,
+                    settings.frequency.as_str(),
+                    &settings.tags
+                )
+            };
+
+            query
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn claim_due_digests(&self, limit: i64) -> Result<Vec<EmailDigest>, DBError> {
+        guarded("claim_due_digests", async {
+            let records = sqlx::query!(
+                "WITH due AS (
+                   SELECT user_uuid, last_sent_at FROM digest_subscriptions
+                   WHERE last_sent_at < CURRENT_TIMESTAMP - make_interval(days => CASE frequency WHEN 'weekly' THEN 7 ELSE 1 END)
+                   ORDER BY last_sent_at LIMIT $1 FOR UPDATE SKIP LOCKED
+                 )
+                 UPDATE digest_subscriptions s SET last_sent_at = CURRENT_TIMESTAMP FROM due
+                 WHERE s.user_uuid = due.user_uuid
+                 RETURNING s.user_uuid, s.frequency, s.tags, due.last_sent_at AS since",
+                limit
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let mut digests = Vec::with_capacity(records.len());
+
+            for record in records {
+                let frequency = record.frequency.parse().map_err(|err: String| DBError::Other(err.into()))?;
+
+                digests.push(self.collect_digest(record.user_uuid, frequency, record.tags, record.since).await?);
+            }
+
+            Ok(digests)
+          })
+          .await
+    }
+}
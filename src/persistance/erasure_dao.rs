@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::{
+    avatars::{avatar_object_key, AVATAR_SIZES},
+    models::{DBError, ErasureAction, ErasureBackups, ErasureCheck, ErasureReport, Pagination, QuestionViewer},
+};
+
+use super::{begin, retry::retrying, stats_dao::viewer_hash};
+
+#[async_trait]
+pub trait ErasureDao {
+    /// Deletes the user, which purges their own rows and clears their uuid from everything else,
+    /// and records the verification report in the same transaction. Returns None for unknown users.
+    async fn erase_user(
+        &self,
+        user_uuid: String,
+        requested_by: String,
+        backup_retention_days: Option<i32>,
+    ) -> Result<Option<ErasureReport>, DBError>;
+    /// Lists reports, newest first.
+    async fn get_erasure_reports(&self, page: Pagination) -> Result<Vec<ErasureReport>, DBError>;
+    async fn get_erasure_report(&self, report_uuid: String) -> Result<Option<ErasureReport>, DBError>;
+}
+
+pub struct ErasureDaoImpl {
+    db: PgPool,
+}
+
+impl ErasureDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      ErasureDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn check(table: &str, column: &str, action: ErasureAction, rows: i64) -> ErasureCheck {
+    ErasureCheck {
+        table: table.to_owned(),
+        column: column.to_owned(),
+        action,
+        rows,
+    }
+}
+
+#[async_trait]
+impl ErasureDao for ErasureDaoImpl {
+    async fn erase_user(
+        &self,
+        user_uuid: String,
+        requested_by: String,
+        backup_retention_days: Option<i32>,
+    ) -> Result<Option<ErasureReport>, DBError> {
+        let (user_uuid, requested_by) = (&user_uuid, &requested_by);
+
+        retrying("erase_user", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+            let requested_by = parse_uuid(requested_by)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let Some(avatar_key) = sqlx::query_scalar!("SELECT avatar_key FROM users WHERE user_uuid = $1 FOR UPDATE", uuid)
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?
+            else {
+                return Ok(None);
+            };
+
+            // Counted before the delete, which applies the ON DELETE rule of every reference to users.
+            let counts = sqlx::query!(
+                "SELECT
+                   (SELECT COUNT(*) FROM user_ip_history WHERE user_uuid = $1) AS \"user_ip_history!\",
+                   (SELECT COUNT(*) FROM drafts WHERE user_uuid = $1) AS \"drafts!\",
+                   (SELECT COUNT(*) FROM question_followers WHERE user_uuid = $1) AS \"question_followers!\",
+                   (SELECT COUNT(*) FROM notifications WHERE user_uuid = $1) AS \"notifications!\",
+                   (SELECT COUNT(*) FROM board_members WHERE user_uuid = $1) AS \"board_members!\",
+                   (SELECT COUNT(*) FROM answer_votes WHERE user_uuid = $1) AS \"answer_votes!\",
+                   (SELECT COUNT(*) FROM mentions WHERE user_uuid = $1) AS \"mentions!\",
+                   (SELECT COUNT(*) FROM accept_suggestion_opt_outs WHERE user_uuid = $1) AS \"accept_suggestion_opt_outs!\",
+                   (SELECT COUNT(*) FROM digest_subscriptions WHERE user_uuid = $1) AS \"digest_subscriptions!\",
+                   (SELECT COUNT(*) FROM notification_settings WHERE user_uuid = $1) AS \"notification_settings!\",
+                   (SELECT COUNT(*) FROM webhooks WHERE owner_uuid = $1) AS \"owned_webhooks!\",
+                   (SELECT COUNT(*) FROM questions WHERE author_uuid = $1) AS \"questions!\",
+                   (SELECT COUNT(*) FROM answers WHERE author_uuid = $1) AS \"answers!\",
+                   (SELECT COUNT(*) FROM question_revisions WHERE editor_uuid = $1) AS \"question_revisions!\",
+                   (SELECT COUNT(*) FROM answer_revisions WHERE editor_uuid = $1) AS \"answer_revisions!\",
+                   (SELECT COUNT(*) FROM notifications WHERE actor_uuid = $1) AS \"notification_actors!\",
+                   (SELECT COUNT(*) FROM mentions WHERE actor_uuid = $1) AS \"mention_actors!\",
+                   (SELECT COUNT(*) FROM invitations WHERE created_by = $1) AS \"invitations_created!\",
+                   (SELECT COUNT(*) FROM invitations WHERE used_by = $1) AS \"invitations_used!\",
+                   (SELECT COUNT(*) FROM attachments WHERE uploader_uuid = $1) AS \"attachments!\",
+                   (SELECT COUNT(*) FROM flags WHERE reporter_uuid = $1) AS \"flags_reported!\",
+                   (SELECT COUNT(*) FROM flags WHERE resolved_by = $1) AS \"flags_resolved!\",
+                   (SELECT COUNT(*) FROM moderation_actions WHERE moderator_uuid = $1) AS \"moderation_actions!\",
+                   (SELECT COUNT(*) FROM board_cleanup_policies WHERE updated_by = $1) AS \"board_cleanup_policies!\",
+                   (SELECT COUNT(*) FROM board_tag_rules WHERE updated_by = $1) AS \"board_tag_rules!\",
+                   (SELECT COUNT(*) FROM pending_tags WHERE requested_by = $1) AS \"pending_tags!\",
+                   (SELECT COUNT(*) FROM jobs WHERE requested_by = $1) AS \"jobs!\",
+                   (SELECT COUNT(*) FROM erasure_reports WHERE requested_by = $1) AS \"erasure_reports!\",
+                   (SELECT COUNT(*) FROM faq_entries WHERE curated_by = $1) AS \"faq_entries!\",
+                   (SELECT COUNT(*) FROM webhooks WHERE created_by = $1 AND owner_uuid IS DISTINCT FROM $1) AS \"webhooks!\",
+                   (SELECT COUNT(*) FROM audit_log WHERE actor_uuid = $1) AS \"audit_log_actor!\",
+                   (SELECT COUNT(*) FROM audit_log WHERE target_type = 'user' AND target_uuid = $1::TEXT) AS \"audit_log_target!\"",
+                uuid
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            // Viewers are only stored keyed with the salts of the days not yet purged.
+            let salts = sqlx::query_scalar!("SELECT salt FROM viewer_salts")
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let viewer_hashes: Vec<String> = salts.iter().map(|salt| viewer_hash(salt, &QuestionViewer::User(uuid))).collect();
+
+            let question_viewers = sqlx::query!("DELETE FROM question_viewers WHERE viewer_hash = ANY($1)", &viewer_hashes)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            use ErasureAction::*;
+
+            let checks = vec![
+                check("users", "user_uuid", Purged, 1),
+                check("user_ip_history", "user_uuid", Purged, counts.user_ip_history),
+                check("drafts", "user_uuid", Purged, counts.drafts),
+                check("question_followers", "user_uuid", Purged, counts.question_followers),
+                check("question_viewers", "viewer_hash", Purged, question_viewers.rows_affected() as i64),
+                check("notifications", "user_uuid", Purged, counts.notifications),
+                check("board_members", "user_uuid", Purged, counts.board_members),
+                check("answer_votes", "user_uuid", Purged, counts.answer_votes),
+                check("mentions", "user_uuid", Purged, counts.mentions),
+                check("accept_suggestion_opt_outs", "user_uuid", Purged, counts.accept_suggestion_opt_outs),
+                check("digest_subscriptions", "user_uuid", Purged, counts.digest_subscriptions),
+                check("notification_settings", "user_uuid", Purged, counts.notification_settings),
+                check("webhooks", "owner_uuid", Purged, counts.owned_webhooks),
+                check("questions", "author_uuid", Anonymized, counts.questions),
+                check("answers", "author_uuid", Anonymized, counts.answers),
+                check("question_revisions", "editor_uuid", Anonymized, counts.question_revisions),
+                check("answer_revisions", "editor_uuid", Anonymized, counts.answer_revisions),
+                check("notifications", "actor_uuid", Anonymized, counts.notification_actors),
+                check("mentions", "actor_uuid", Anonymized, counts.mention_actors),
+                check("invitations", "created_by", Anonymized, counts.invitations_created),
+                check("invitations", "used_by", Anonymized, counts.invitations_used),
+                check("attachments", "uploader_uuid", Anonymized, counts.attachments),
+                check("flags", "reporter_uuid", Anonymized, counts.flags_reported),
+                check("flags", "resolved_by", Anonymized, counts.flags_resolved),
+                check("moderation_actions", "moderator_uuid", Anonymized, counts.moderation_actions),
+                check("board_cleanup_policies", "updated_by", Anonymized, counts.board_cleanup_policies),
+                check("board_tag_rules", "updated_by", Anonymized, counts.board_tag_rules),
+                check("pending_tags", "requested_by", Anonymized, counts.pending_tags),
+                check("jobs", "requested_by", Anonymized, counts.jobs),
+                check("erasure_reports", "requested_by", Anonymized, counts.erasure_reports),
+                check("faq_entries", "curated_by", Anonymized, counts.faq_entries),
+                check("webhooks", "created_by", Anonymized, counts.webhooks),
+                check("audit_log", "actor_uuid", Retained, counts.audit_log_actor),
+                check("audit_log", "target_uuid", Retained, counts.audit_log_target),
+            ];
+
+            let deleted_objects: Vec<String> = avatar_key
+              .map(|avatar_key| AVATAR_SIZES.iter().map(|size| avatar_object_key(&avatar_key, *size)).collect())
+              .unwrap_or_default();
+
+            sqlx::query!("DELETE FROM users WHERE user_uuid = $1", uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO erasure_reports (user_uuid, requested_by, checks, deleted_objects, backup_retention_days) VALUES ($1, $2, $3, $4, $5)
+                 RETURNING report_uuid, created_at, created_at + make_interval(days => backup_retention_days) AS backups_until",
+                uuid,
+                requested_by,
+                serde_json::to_value(&checks).map_err(|err| DBError::Other(Box::new(err)))?,
+                &deleted_objects,
+                backup_retention_days
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit().await.map_err(DBError::from)?;
+
+            Ok(Some(ErasureReport {
+                report_uuid: record.report_uuid,
+                user_uuid: uuid,
+                requested_by: Some(requested_by),
+                erased_at: record.created_at.assume_utc(),
+                checks,
+                deleted_objects,
+                backups: ErasureBackups::new(backup_retention_days, record.backups_until.map(|until| until.assume_utc())),
+            }))
+          })
+          .await
+    }
+
+    async fn get_erasure_reports(&self, page: Pagination) -> Result<Vec<ErasureReport>, DBError> {
+        retrying("get_erasure_reports", || async move {
+            let records = sqlx::query!(
+                "SELECT *, created_at + make_interval(days => backup_retention_days) AS backups_until FROM erasure_reports
+                 ORDER BY created_at DESC, report_uuid OFFSET $1 LIMIT $2",
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(ErasureReport {
+                  report_uuid: record.report_uuid,
+                  user_uuid: record.user_uuid,
+                  requested_by: record.requested_by,
+                  erased_at: record.created_at.assume_utc(),
+                  checks: serde_json::from_value(record.checks).map_err(|err| DBError::Other(Box::new(err)))?,
+                  deleted_objects: record.deleted_objects,
+                  backups: ErasureBackups::new(record.backup_retention_days, record.backups_until.map(|until| until.assume_utc())),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn get_erasure_report(&self, report_uuid: String) -> Result<Option<ErasureReport>, DBError> {
+        let report_uuid = &report_uuid;
+
+        retrying("get_erasure_report", || async move {
+            let uuid = parse_uuid(report_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT *, created_at + make_interval(days => backup_retention_days) AS backups_until FROM erasure_reports
+                 WHERE report_uuid = $1",
+                uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(ErasureReport {
+                  report_uuid: record.report_uuid,
+                  user_uuid: record.user_uuid,
+                  requested_by: record.requested_by,
+                  erased_at: record.created_at.assume_utc(),
+                  checks: serde_json::from_value(record.checks).map_err(|err| DBError::Other(Box::new(err)))?,
+                  deleted_objects: record.deleted_objects,
+                  backups: ErasureBackups::new(record.backup_retention_days, record.backups_until.map(|until| until.assume_utc())),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+}
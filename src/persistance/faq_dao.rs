@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, FaqEntry};
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait FaqDao {
+    /// Puts the question in the FAQ with `answer_uuid`, replacing its previous answer. Returns false
+    /// when the answer does not exist or is not on the question.
+    async fn set_faq_entry(&self, question_uuid: String, answer_uuid: String, curated_by: String) -> Result<bool, DBError>;
+    async fn remove_faq_entry(&self, question_uuid: String) -> Result<bool, DBError>;
+    /// Entries anyone may read, by title: public, not deleted or held, and not by shadow-banned users.
+    async fn get_faq_entries(&self) -> Result<Vec<FaqEntry>, DBError>;
+}
+
+pub struct FaqDaoImpl {
+    db: PgPool,
+}
+
+impl FaqDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      FaqDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl FaqDao for FaqDaoImpl {
+    async fn set_faq_entry(&self, question_uuid: String, answer_uuid: String, curated_by: String) -> Result<bool, DBError> {
+        let (question_uuid, answer_uuid, curated_by) = (&question_uuid, &answer_uuid, &curated_by);
+
+        retrying("set_faq_entry", || async move {
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = parse_uuid(answer_uuid)?;
+            let curated_by = parse_uuid(curated_by)?;
+
+            let result = sqlx::query!(
+                "INSERT INTO faq_entries (question_uuid, answer_uuid, curated_by)
+                 SELECT a.question_uuid, a.answer_uuid, $3 FROM answers a
+                 JOIN questions q ON q.question_uuid = a.question_uuid
+                 WHERE a.answer_uuid = $2 AND a.question_uuid = $1 AND a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 ON CONFLICT (question_uuid) DO UPDATE SET answer_uuid = EXCLUDED.answer_uuid, curated_by = EXCLUDED.curated_by,
+                   created_at = CURRENT_TIMESTAMP",
+                question_uuid,
+                answer_uuid,
+                curated_by
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn remove_faq_entry(&self, question_uuid: String) -> Result<bool, DBError> {
+        let question_uuid = &question_uuid;
+
+        retrying("remove_faq_entry", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+
+            let result = sqlx::query!("DELETE FROM faq_entries WHERE question_uuid = $1", uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn get_faq_entries(&self) -> Result<Vec<FaqEntry>, DBError> {
+        retrying("get_faq_entries", || async move {
+            let records = sqlx::query!(
+                "SELECT q.question_uuid, q.title, q.description, q.tags, q.board_uuid, b.name AS \"board_name?\",
+                   a.answer_uuid, a.content, f.created_at
+                 FROM faq_entries f
+                 JOIN questions q ON q.question_uuid = f.question_uuid
+                 JOIN answers a ON a.answer_uuid = f.answer_uuid
+                 LEFT JOIN boards b ON b.board_uuid = q.board_uuid
+                 WHERE q.visibility = 'public' AND q.deleted_at IS NULL AND a.deleted_at IS NULL
+                 AND q.held_at IS NULL AND a.held_at IS NULL
+                 AND post_visible_to(q.author_uuid, NULL) AND post_visible_to(a.author_uuid, NULL)
+                 ORDER BY q.title, q.question_uuid"
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| FaqEntry {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                description: record.description,
+                answer_uuid: record.answer_uuid,
+                answer: record.content,
+                tags: record.tags,
+                board_uuid: record.board_uuid,
+                board_name: record.board_name,
+                curated_at: record.created_at.assume_utc(),
+              })
+              .collect())
+          })
+          .await
+    }
+}
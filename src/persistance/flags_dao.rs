@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, Flag, FlagDetail, FlagReason, FlagStatus, Pagination};
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait FlagsDao {
+    /// Flags the question, or its answer `answer_uuid`. Fails with `UniqueViolation` when the
+    /// reporter already has an open flag on the post. Flags raised by the forum have no reporter.
+    async fn create_flag(
+        &self,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        reporter_uuid: Option<String>,
+        flag: Flag,
+    ) -> Result<FlagDetail, DBError>;
+    /// Lists flags with the given status, oldest first so the queue is worked in order.
+    async fn get_flags(&self, status: FlagStatus, page: Pagination) -> Result<Vec<FlagDetail>, DBError>;
+    /// Closes an open flag. Returns `None` when there is no such flag or it was already resolved.
+    async fn resolve_flag(
+        &self,
+        flag_uuid: String,
+        resolved_by: String,
+        status: FlagStatus,
+        note: Option<String>,
+    ) -> Result<Option<FlagDetail>, DBError>;
+}
+
+pub struct FlagsDaoImpl {
+    db: PgPool,
+}
+
+impl FlagsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      FlagsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_reason(reason: &str) -> Result<FlagReason, DBError> {
+    reason.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_status(status: &str) -> Result<FlagStatus, DBError> {
+    status.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl FlagsDao for FlagsDaoImpl {
+    async fn create_flag(
+        &self,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        reporter_uuid: Option<String>,
+        flag: Flag,
+    ) -> Result<FlagDetail, DBError> {
+        let (question_uuid, answer_uuid, reporter_uuid, flag) = (&question_uuid, answer_uuid.as_deref(), reporter_uuid.as_deref(), &flag);
+
+        retrying("create_flag", || async move {
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+            let reporter_uuid = reporter_uuid.map(parse_uuid).transpose()?;
+
+            let record = sqlx::query!(
+                "INSERT INTO flags (question_uuid, answer_uuid, reporter_uuid, reason, details)
+                 VALUES ($1, $2, $3, $4, $5) RETURNING *",
+                question_uuid,
+                answer_uuid,
+                reporter_uuid,
+                flag.reason.as_str(),
+                flag.details
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(FlagDetail {
+              flag_uuid: record.flag_uuid,
+              question_uuid: record.question_uuid,
+              answer_uuid: record.answer_uuid,
+              reporter_uuid: record.reporter_uuid,
+              reason: parse_reason(&record.reason)?,
+              details: record.details,
+              status: parse_status(&record.status)?,
+              created_at: record.created_at.assume_utc(),
+              resolved_by: record.resolved_by,
+              resolved_at: record.resolved_at.map(|resolved_at| resolved_at.assume_utc()),
+              resolution_note: record.resolution_note,
+            })
+          })
+          .await
+    }
+
+    async fn get_flags(&self, status: FlagStatus, page: Pagination) -> Result<Vec<FlagDetail>, DBError> {
+        retrying("get_flags", || async move {
+            // Joining questions applies their tenant isolation to the flags.
+            let records = sqlx::query!(
+                "SELECT f.* FROM flags f JOIN questions q ON q.question_uuid = f.question_uuid
+                 WHERE f.status = $1 ORDER BY f.created_at, f.flag_uuid OFFSET $2 LIMIT $3",
+                status.as_str(),
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(FlagDetail {
+                  flag_uuid: record.flag_uuid,
+                  question_uuid: record.question_uuid,
+                  answer_uuid: record.answer_uuid,
+                  reporter_uuid: record.reporter_uuid,
+                  reason: parse_reason(&record.reason)?,
+                  details: record.details,
+                  status: parse_status(&record.status)?,
+                  created_at: record.created_at.assume_utc(),
+                  resolved_by: record.resolved_by,
+                  resolved_at: record.resolved_at.map(|resolved_at| resolved_at.assume_utc()),
+                  resolution_note: record.resolution_note,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn resolve_flag(
+        &self,
+        flag_uuid: String,
+        resolved_by: String,
+        status: FlagStatus,
+        note: Option<String>,
+    ) -> Result<Option<FlagDetail>, DBError> {
+        let (flag_uuid, resolved_by, note) = (&flag_uuid, &resolved_by, note.as_deref());
+
+        retrying("resolve_flag", || async move {
+            let flag_uuid = parse_uuid(flag_uuid)?;
+            let resolved_by = parse_uuid(resolved_by)?;
+
+            let record = sqlx::query!(
+                "UPDATE flags SET status = $3, resolved_by = $2, resolved_at = CURRENT_TIMESTAMP, resolution_note = $4
+                 WHERE flag_uuid = $1 AND status = 'open' AND question_uuid IN (SELECT question_uuid FROM questions)
+                 RETURNING *",
+                flag_uuid,
+                resolved_by,
+                status.as_str(),
+                note
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(FlagDetail {
+                  flag_uuid: record.flag_uuid,
+                  question_uuid: record.question_uuid,
+                  answer_uuid: record.answer_uuid,
+                  reporter_uuid: record.reporter_uuid,
+                  reason: parse_reason(&record.reason)?,
+                  details: record.details,
+                  status: parse_status(&record.status)?,
+                  created_at: record.created_at.assume_utc(),
+                  resolved_by: record.resolved_by,
+                  resolved_at: record.resolved_at.map(|resolved_at| resolved_at.assume_utc()),
+                  resolution_note: record.resolution_note,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::DBError;
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait FollowsDao {
+    /// Records the user as a watcher of the question; following twice is a no-op.
+    async fn follow_question(&self, question_uuid: String, user_uuid: String) -> Result<(), DBError>;
+    async fn unfollow_question(&self, question_uuid: String, user_uuid: String) -> Result<(), DBError>;
+}
+
+pub struct FollowsDaoImpl {
+    db: PgPool,
+}
+
+impl FollowsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      FollowsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl FollowsDao for FollowsDaoImpl {
+    async fn follow_question(&self, question_uuid: String, user_uuid: String) -> Result<(), DBError> {
+        let (question_uuid, user_uuid) = (&question_uuid, &user_uuid);
+
+        retrying("follow_question", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            sqlx::query!(
+                "INSERT INTO question_followers (question_uuid, user_uuid) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                uuid,
+                user_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn unfollow_question(&self, question_uuid: String, user_uuid: String) -> Result<(), DBError> {
+        let (question_uuid, user_uuid) = (&question_uuid, &user_uuid);
+
+        retrying("unfollow_question", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            sqlx::query!(
+                "DELETE FROM question_followers WHERE question_uuid = $1 AND user_uuid = $2",
+                uuid,
+                user_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
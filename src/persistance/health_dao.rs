@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use sqlx::{migrate::Migrator, PgPool};
+
+use crate::models::DBError;
+
+use super::{circuit_breaker::breaker, retry::retrying};
+
+/// The migrations this build expects, embedded at compile time.
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[async_trait]
+pub trait HealthDao {
+    /// Runs `SELECT 1` through the circuit breaker, so that readiness probes fail fast while it is
+    /// open, and serve as its trial once it half-opens.
+    async fn ping(&self) -> Result<(), DBError>;
+    /// Versions of the migrations of this build the database has not applied, oldest first; `None`
+    /// when the database keeps no migration history, e.g. when the schema was set up by hand.
+    async fn get_pending_migrations(&self) -> Result<Option<Vec<i64>>, DBError>;
+}
+
+pub struct HealthDaoImpl {
+    db: PgPool,
+}
+
+impl HealthDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      HealthDaoImpl {
+        db
+      }
+    }
+}
+
+#[async_trait]
+impl HealthDao for HealthDaoImpl {
+    async fn ping(&self) -> Result<(), DBError> {
+        breaker()
+          .call(async {
+            sqlx::query("SELECT 1")
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        Ok(())
+    }
+
+    async fn get_pending_migrations(&self) -> Result<Option<Vec<i64>>, DBError> {
+        retrying("get_pending_migrations", || async move {
+            let has_history: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            if !has_history {
+                return Ok(None);
+            }
+
+            let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let pending = MIGRATOR
+              .iter()
+              .filter(|migration| migration.migration_type.is_up_migration() && !applied.contains(&migration.version))
+              .map(|migration| migration.version)
+              .collect();
+
+            Ok(Some(pending))
+          })
+          .await
+    }
+}
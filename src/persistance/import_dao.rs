@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+use crate::models::{DBError, ImportCounts, ImportRecord};
+
+use super::{begin, circuit_breaker::breaker, commit};
+
+#[async_trait]
+pub trait ImportDao {
+    /// Inserts `records` in order in one transaction, keeping their UUIDs and timestamps. Records
+    /// whose UUID is already taken are skipped and counted as such.
+    async fn import_records(&self, records: &[ImportRecord]) -> Result<ImportCounts, DBError>;
+}
+
+pub struct ImportDaoImpl {
+    db: PgPool,
+}
+
+impl ImportDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      ImportDaoImpl {
+        db
+      }
+    }
+}
+
+fn utc(at: OffsetDateTime) -> PrimitiveDateTime {
+    let at = at.to_offset(UtcOffset::UTC);
+
+    PrimitiveDateTime::new(at.date(), at.time())
+}
+
+#[async_trait]
+impl ImportDao for ImportDaoImpl {
+    async fn import_records(&self, records: &[ImportRecord]) -> Result<ImportCounts, DBError> {
+        breaker()
+          .call(async {
+            let mut tx = begin(&self.db).await?;
+            let mut counts = ImportCounts::default();
+
+            for record in records {
+                let inserted = match record {
+                    // The token hash matches no token, so imported users get theirs by signing in.
+                    ImportRecord::User(user) => sqlx::query!(
+                        "INSERT INTO users (user_uuid, username, api_token_hash, created_at)
+                         VALUES ($1, $2, encode(sha256(gen_random_uuid()::text::bytea), 'hex'), $3)
+                         ON CONFLICT (user_uuid) DO NOTHING",
+                        user.user_uuid,
+                        user.username,
+                        utc(user.created_at)
+                      )
+                      .execute(&mut *tx)
+                      .await,
+                    ImportRecord::Question(question) => sqlx::query!(
+                        "INSERT INTO questions (question_uuid, title, description, author_uuid, tags, created_at, updated_at)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7)
+                         ON CONFLICT (question_uuid) DO NOTHING",
+                        question.question_uuid,
+                        question.title,
+                        question.description,
+                        question.author_uuid,
+                        &question.tags,
+                        utc(question.created_at),
+                        utc(question.updated_at.unwrap_or(question.created_at))
+                      )
+                      .execute(&mut *tx)
+                      .await,
+                    ImportRecord::Answer(answer) => sqlx::query!(
+                        "INSERT INTO answers (answer_uuid, question_uuid, content, author_uuid, created_at, updated_at)
+                         VALUES ($1, $2, $3, $4, $5, $5)
+                         ON CONFLICT (answer_uuid) DO NOTHING",
+                        answer.answer_uuid,
+                        answer.question_uuid,
+                        answer.content,
+                        answer.author_uuid,
+                        utc(answer.created_at)
+                      )
+                      .execute(&mut *tx)
+                      .await,
+                }
+                .map_err(DBError::from)?
+                .rows_affected();
+
+                match (record, inserted) {
+                    (_, 0) => counts.skipped += 1,
+                    (ImportRecord::User(_), _) => counts.users += 1,
+                    (ImportRecord::Question(_), _) => counts.questions += 1,
+                    (ImportRecord::Answer(_), _) => counts.answers += 1,
+                }
+            }
+
+            commit(tx).await?;
+
+            Ok(counts)
+          })
+          .await
+    }
+}
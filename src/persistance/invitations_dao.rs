@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{BoardRole, DBError, Invitation, InvitationDetail, InvitationStatus, MembershipStatus, Role};
+
+use super::{begin, retry::retrying};
+
+#[async_trait]
+pub trait InvitationsDao {
+    async fn create_invitation(&self, invitation: Invitation, created_by: String) -> Result<InvitationDetail, DBError>;
+    /// Lists every invitation, newest first.
+    async fn get_invitations(&self) -> Result<Vec<InvitationDetail>, DBError>;
+    /// Returns `None` when the invitation does not exist, was used or has expired.
+    async fn get_pending_invitation(&self, invitation_uuid: String) -> Result<Option<InvitationDetail>, DBError>;
+    /// Marks the invitation used by `user_uuid` and applies its role and board membership in one
+    /// transaction. Returns `None` when it is no longer pending.
+    async fn accept_invitation(&self, invitation_uuid: String, user_uuid: String) -> Result<Option<InvitationDetail>, DBError>;
+}
+
+pub struct InvitationsDaoImpl {
+    db: PgPool,
+}
+
+impl InvitationsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      InvitationsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_role(role: &str) -> Result<Role, DBError> {
+    role.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_invitation_status(status: &str) -> Result<InvitationStatus, DBError> {
+    status.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl InvitationsDao for InvitationsDaoImpl {
+    async fn create_invitation(&self, invitation: Invitation, created_by: String) -> Result<InvitationDetail, DBError> {
+        let (invitation, created_by) = (&invitation, &created_by);
+
+        retrying("create_invitation", || async move {
+            let board_uuid = invitation.board_uuid;
+            let created_by = parse_uuid(created_by)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO invitations (board_uuid, role, created_by, expires_at)
+                 VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(secs => $4))
+                 RETURNING *, 'pending' AS \"status!\"",
+                board_uuid,
+                invitation.role.map(|role| role.as_str()),
+                created_by,
+                invitation.expires_in_seconds as f64
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(InvitationDetail {
+                invitation_uuid: record.invitation_uuid,
+                board_uuid: record.board_uuid,
+                role: record.role.as_deref().map(parse_role).transpose()?,
+                status: parse_invitation_status(&record.status)?,
+                created_by: record.created_by,
+                used_by: record.used_by,
+                expires_at: record.expires_at.assume_utc(),
+                used_at: record.used_at.map(|used_at| used_at.assume_utc()),
+                created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_invitations(&self) -> Result<Vec<InvitationDetail>, DBError> {
+        retrying("get_invitations", || async move {
+            let records = sqlx::query!(
+                "SELECT *, CASE WHEN used_at IS NOT NULL THEN 'used' WHEN expires_at <= CURRENT_TIMESTAMP THEN 'expired' ELSE 'pending' END AS \"status!\"
+                 FROM invitations ORDER BY created_at DESC"
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(InvitationDetail {
+                  invitation_uuid: record.invitation_uuid,
+                  board_uuid: record.board_uuid,
+                  role: record.role.as_deref().map(parse_role).transpose()?,
+                  status: parse_invitation_status(&record.status)?,
+                  created_by: record.created_by,
+                  used_by: record.used_by,
+                  expires_at: record.expires_at.assume_utc(),
+                  used_at: record.used_at.map(|used_at| used_at.assume_utc()),
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn get_pending_invitation(&self, invitation_uuid: String) -> Result<Option<InvitationDetail>, DBError> {
+        let invitation_uuid = &invitation_uuid;
+
+        retrying("get_pending_invitation", || async move {
+            let uuid = parse_uuid(invitation_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT *, 'pending' AS \"status!\" FROM invitations
+                 WHERE invitation_uuid = $1 AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP",
+                uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(InvitationDetail {
+                  invitation_uuid: record.invitation_uuid,
+                  board_uuid: record.board_uuid,
+                  role: record.role.as_deref().map(parse_role).transpose()?,
+                  status: parse_invitation_status(&record.status)?,
+                  created_by: record.created_by,
+                  used_by: record.used_by,
+                  expires_at: record.expires_at.assume_utc(),
+                  used_at: record.used_at.map(|used_at| used_at.assume_utc()),
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn accept_invitation(&self, invitation_uuid: String, user_uuid: String) -> Result<Option<InvitationDetail>, DBError> {
+        let (invitation_uuid, user_uuid) = (&invitation_uuid, &user_uuid);
+
+        retrying("accept_invitation", || async move {
+            let uuid = parse_uuid(invitation_uuid)?;
+            let user_uuid = parse_uuid(user_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let record = sqlx::query!(
+                "UPDATE invitations SET used_by = $2, used_at = CURRENT_TIMESTAMP
+                 WHERE invitation_uuid = $1 AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+                 RETURNING *, 'used' AS \"status!\"",
+                uuid,
+                user_uuid
+              )
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let Some(record) = record else {
+              return Ok(None);
+            };
+
+            if let Some(role) = &record.role {
+              sqlx::query!("UPDATE users SET role = $2 WHERE user_uuid = $1", user_uuid, role)
+                .execute(&mut *tx)
+                .await
+                .map_err(DBError::from)?;
+            }
+
+            if let Some(board_uuid) = record.board_uuid {
+              sqlx::query!(
+                  "INSERT INTO board_members (board_uuid, user_uuid, role, status) VALUES ($1, $2, $3, $4)
+                   ON CONFLICT (board_uuid, user_uuid) DO UPDATE SET status = EXCLUDED.status",
+                  board_uuid,
+                  user_uuid,
+                  BoardRole::Member.as_str(),
+                  MembershipStatus::Active.as_str()
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(DBError::from)?;
+            }
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(InvitationDetail {
+                invitation_uuid: record.invitation_uuid,
+                board_uuid: record.board_uuid,
+                role: record.role.as_deref().map(parse_role).transpose()?,
+                status: parse_invitation_status(&record.status)?,
+                created_by: record.created_by,
+                used_by: record.used_by,
+                expires_at: record.expires_at.assume_utc(),
+                used_at: record.used_at.map(|used_at| used_at.assume_utc()),
+                created_at: record.created_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+}
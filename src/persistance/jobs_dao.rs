@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, JobDetail, JobKind, JobStatus};
+
+use super::{circuit_breaker::guarded, retry::retrying};
+
+#[async_trait]
+pub trait JobsDao {
+    /// `input` is handed to the worker running the job.
+    async fn create_job(&self, kind: JobKind, requested_by: String, input: Option<serde_json::Value>) -> Result<JobDetail, DBError>;
+    async fn get_job(&self, job_uuid: String) -> Result<Option<JobDetail>, DBError>;
+    /// Marks the oldest queued job as running and returns it. Concurrent workers never claim the same job.
+    async fn claim_next_job(&self) -> Result<Option<JobDetail>, DBError>;
+    async fn update_job_progress(&self, job_uuid: String, progress: i32) -> Result<(), DBError>;
+    async fn complete_job(&self, job_uuid: String, result: serde_json::Value) -> Result<(), DBError>;
+    async fn fail_job(&self, job_uuid: String, error: String) -> Result<(), DBError>;
+}
+
+pub struct JobsDaoImpl {
+    db: PgPool,
+}
+
+impl JobsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      JobsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_kind(kind: &str) -> Result<JobKind, DBError> {
+    kind.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_status(status: &str) -> Result<JobStatus, DBError> {
+    status.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl JobsDao for JobsDaoImpl {
+    async fn create_job(&self, kind: JobKind, requested_by: String, input: Option<serde_json::Value>) -> Result<JobDetail, DBError> {
+        let (requested_by, input) = (&requested_by, input.as_ref());
+
+        retrying("create_job", || async move {
+            let requested_by = parse_uuid(requested_by)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO jobs (kind, requested_by, input) VALUES ($1, $2, $3) RETURNING *",
+                kind.as_str(),
+                requested_by,
+                input
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(JobDetail {
+                job_uuid: record.job_uuid,
+                kind: parse_kind(&record.kind)?,
+                status: parse_status(&record.status)?,
+                progress: record.progress,
+                result: record.result,
+                error: record.error,
+                requested_by: record.requested_by,
+                created_at: record.created_at.assume_utc(),
+                started_at: record.started_at.map(|started_at| started_at.assume_utc()),
+                finished_at: record.finished_at.map(|finished_at| finished_at.assume_utc()),
+                input: record.input,
+            })
+          })
+          .await
+    }
+
+    async fn get_job(&self, job_uuid: String) -> Result<Option<JobDetail>, DBError> {
+        let job_uuid = &job_uuid;
+
+        retrying("get_job", || async move {
+            let uuid = parse_uuid(job_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM jobs WHERE job_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(JobDetail {
+                  job_uuid: record.job_uuid,
+                  kind: parse_kind(&record.kind)?,
+                  status: parse_status(&record.status)?,
+                  progress: record.progress,
+                  result: record.result,
+                  error: record.error,
+                  requested_by: record.requested_by,
+                  created_at: record.created_at.assume_utc(),
+                  started_at: record.started_at.map(|started_at| started_at.assume_utc()),
+                  finished_at: record.finished_at.map(|finished_at| finished_at.assume_utc()),
+                  input: record.input,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn claim_next_job(&self) -> Result<Option<JobDetail>, DBError> {
+        guarded("claim_next_job", async {
+            let record = sqlx::query!(
+                "UPDATE jobs SET status = 'running', started_at = CURRENT_TIMESTAMP
+                 WHERE job_uuid = (
+                   SELECT job_uuid FROM jobs WHERE status = 'queued' ORDER BY created_at LIMIT 1 FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING *"
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(JobDetail {
+                  job_uuid: record.job_uuid,
+                  kind: parse_kind(&record.kind)?,
+                  status: parse_status(&record.status)?,
+                  progress: record.progress,
+                  result: record.result,
+                  error: record.error,
+                  requested_by: record.requested_by,
+                  created_at: record.created_at.assume_utc(),
+                  started_at: record.started_at.map(|started_at| started_at.assume_utc()),
+                  finished_at: record.finished_at.map(|finished_at| finished_at.assume_utc()),
+                  input: record.input,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn update_job_progress(&self, job_uuid: String, progress: i32) -> Result<(), DBError> {
+        let job_uuid = &job_uuid;
+
+        retrying("update_job_progress", || async move {
+            let uuid = parse_uuid(job_uuid)?;
+
+            sqlx::query!(
+                "UPDATE jobs SET progress = $2 WHERE job_uuid = $1 AND status = 'running'",
+                uuid,
+                progress.clamp(0, 100)
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn complete_job(&self, job_uuid: String, result: serde_json::Value) -> Result<(), DBError> {
+        let (job_uuid, result) = (&job_uuid, &result);
+
+        retrying("complete_job", || async move {
+            let uuid = parse_uuid(job_uuid)?;
+
+            sqlx::query!(
+                "UPDATE jobs SET status = 'succeeded', progress = 100, result = $2, finished_at = CURRENT_TIMESTAMP
+                 WHERE job_uuid = $1",
+                uuid,
+                result
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn fail_job(&self, job_uuid: String, error: String) -> Result<(), DBError> {
+        let (job_uuid, error) = (&job_uuid, &error);
+
+        retrying("fail_job", || async move {
+            let uuid = parse_uuid(job_uuid)?;
+
+            sqlx::query!(
+                "UPDATE jobs SET status = 'failed', error = $2, finished_at = CURRENT_TIMESTAMP WHERE job_uuid = $1",
+                uuid,
+                error
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
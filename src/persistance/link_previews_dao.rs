@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::models::{DBError, LinkPreview};
+
+use super::{circuit_breaker::guarded, retry::retrying};
+
+#[async_trait]
+pub trait LinkPreviewsDao {
+    /// Returns the previews fetched so far for `urls`, and queues the URLs seen for the first time
+    /// for the fetcher.
+    async fn get_link_previews(&self, urls: Vec<String>) -> Result<Vec<LinkPreview>, DBError>;
+    /// Marks up to `limit` queued URLs as being fetched and returns them. URLs claimed by a fetcher
+    /// that stopped halfway are handed out again after a while.
+    async fn claim_pending_link_previews(&self, limit: i64) -> Result<Vec<String>, DBError>;
+    async fn save_link_preview(&self, preview: LinkPreview) -> Result<(), DBError>;
+    async fn fail_link_preview(&self, url: String, error: String) -> Result<(), DBError>;
+}
+
+pub struct LinkPreviewsDaoImpl {
+    db: PgPool,
+}
+
+impl LinkPreviewsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      LinkPreviewsDaoImpl {
+        db
+      }
+    }
+}
+
+#[async_trait]
+impl LinkPreviewsDao for LinkPreviewsDaoImpl {
+    async fn get_link_previews(&self, urls: Vec<String>) -> Result<Vec<LinkPreview>, DBError> {
+        let urls = &urls;
+
+        retrying("get_link_previews", || async move {
+            if urls.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let records = sqlx::query!(
+                "SELECT url, status, title, description, image_url, site_name FROM link_previews WHERE url = ANY($1)",
+                &urls
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let unseen: Vec<String> = urls
+              .iter()
+              .filter(|url| !records.iter().any(|record| &record.url == *url))
+              .cloned()
+              .collect();
+
+            if !unseen.is_empty() {
+                sqlx::query!(
+                    "INSERT INTO link_previews (url) SELECT * FROM UNNEST($1::TEXT[]) ON CONFLICT DO NOTHING",
+                    &unseen
+                  )
+                  .execute(&self.db)
+                  .await
+                  .map_err(DBError::from)?;
+            }
+
+            Ok(records
+              .into_iter()
+              .filter(|record| record.status == "fetched")
+              .map(|record| LinkPreview {
+                url: record.url,
+                title: record.title,
+                description: record.description,
+                image_url: record.image_url,
+                site_name: record.site_name,
+              })
+              .collect())
+          })
+          .await
+    }
+
+    async fn claim_pending_link_previews(&self, limit: i64) -> Result<Vec<String>, DBError> {
+        guarded("claim_pending_link_previews", async {
+            let records = sqlx::query!(
+                "UPDATE link_previews SET status = 'fetching', claimed_at = CURRENT_TIMESTAMP
+                 WHERE url IN (
+                   SELECT url FROM link_previews
+                   WHERE status = 'pending' OR (status = 'fetching' AND claimed_at < CURRENT_TIMESTAMP - INTERVAL '10 minutes')
+                   ORDER BY created_at LIMIT $1 FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING url",
+                limit
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records.into_iter().map(|record| record.url).collect())
+          })
+          .await
+    }
+
+    async fn save_link_preview(&self, preview: LinkPreview) -> Result<(), DBError> {
+        let preview = &preview;
+
+        retrying("save_link_preview", || async move {
+            sqlx::query!(
+                "UPDATE link_previews
+                 SET status = 'fetched', title = $2, description = $3, image_url = $4, site_name = $5, error = NULL,
+                   fetched_at = CURRENT_TIMESTAMP
+                 WHERE url = $1",
+                preview.url,
+                preview.title,
+                preview.description,
+                preview.image_url,
+                preview.site_name
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn fail_link_preview(&self, url: String, error: String) -> Result<(), DBError> {
+        let (url, error) = (&url, &error);
+
+        retrying("fail_link_preview", || async move {
+            sqlx::query!(
+                "UPDATE link_previews SET status = 'failed', error = $2, fetched_at = CURRENT_TIMESTAMP WHERE url = $1",
+                url,
+                error
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
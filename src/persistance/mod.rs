@@ -1,5 +1,155 @@
 pub mod answers_dao;
+pub mod attachments_dao;
+pub mod audit_dao;
+pub mod boards_dao;
+pub mod circuit_breaker;
+pub mod cleanup_policies_dao;
+pub mod content_dao;
+pub mod dead_letters_dao;
+pub mod drafts_dao;
+pub mod email_digests_dao;
+pub mod erasure_dao;
+pub mod faq_dao;
+pub mod flags_dao;
+pub mod follows_dao;
+pub mod health_dao;
+pub mod import_dao;
+pub mod invitations_dao;
+pub mod jobs_dao;
+pub mod link_previews_dao;
+pub mod moderation_dao;
+pub mod notifications_dao;
+pub mod query_samples_dao;
 pub mod questions_dao;
+pub mod replica;
+pub mod retention_dao;
+pub mod retry;
+pub mod stats_dao;
+pub mod tags_dao;
+pub mod tenants_dao;
+pub mod timing;
+pub mod users_dao;
+pub mod webhooks_dao;
+
+use std::time::Instant;
+
+use sqlx::{types::Uuid, PgPool, Postgres};
+use tracing::warn;
+
+use crate::{
+    metrics,
+    models::{postgres_error_codes, BulkDeleteResult, DBError, Viewer},
+};
+
+impl From<sqlx::Error> for DBError {
+    /// Sorts driver errors by their Postgres error code, or by how the driver failed. The cause of
+    /// connection errors and timeouts is logged here, as they carry none.
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => DBError::NotFound,
+            sqlx::Error::Database(db_err) => match db_err.code().as_deref() {
+                Some(postgres_error_codes::UNIQUE_VIOLATION) => DBError::UniqueViolation(db_err.to_string()),
+                Some(postgres_error_codes::FOREIGN_KEY_VIOLATION) => DBError::ForeignKeyViolation(db_err.to_string()),
+                Some(postgres_error_codes::SERIALIZATION_FAILURE | postgres_error_codes::DEADLOCK_DETECTED) => {
+                    DBError::SerializationFailure
+                }
+                Some(postgres_error_codes::QUERY_CANCELED) => {
+                    warn!("Database statement cancelled: {}", db_err);
+                    DBError::Timeout
+                }
+                Some(
+                    postgres_error_codes::TOO_MANY_CONNECTIONS
+                    | postgres_error_codes::ADMIN_SHUTDOWN
+                    | postgres_error_codes::CRASH_SHUTDOWN
+                    | postgres_error_codes::CANNOT_CONNECT_NOW,
+                ) => {
+                    warn!("Database connection refused: {}", db_err);
+                    DBError::ConnectionError
+                }
+                Some(code) if code.starts_with(postgres_error_codes::CONNECTION_EXCEPTION_CLASS) => {
+                    warn!("Database connection failed: {}", db_err);
+                    DBError::ConnectionError
+                }
+                _ => DBError::Other(Box::new(db_err)),
+            },
+            sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => {
+                warn!("Database connection failed: {}", err);
+                DBError::ConnectionError
+            }
+            err => DBError::Other(Box::new(err)),
+        }
+    }
+}
+
+/// Statements of one or more DAO modules that apply together or not at all.
+///
+/// A DAO method that spans several tables begins one with `begin`, passes it to the `*_in`
+/// functions of the modules involved, such as `tags_dao::add_pending_tags_in`, and ends it with
+/// `commit`. Returning early on an error drops it, which rolls every statement back.
+pub(crate) type Transaction = sqlx::Transaction<'static, Postgres>;
+
+/// Times the wait for a connection, for `db_pool_acquire_duration_seconds`.
+pub(crate) async fn begin(db: &PgPool) -> Result<Transaction, DBError> {
+    let started = Instant::now();
+    let tx = db.begin().await.map_err(DBError::from);
+
+    metrics::observe_pool_acquire(started.elapsed());
+
+    tx
+}
+
+/// Begins a transaction for a read streamed to a client, which takes as long as the client does
+/// to take the rows. `statement_timeout` is lifted for this transaction only.
+pub(crate) async fn begin_streaming(db: &PgPool) -> Result<Transaction, DBError> {
+    let mut tx = begin(db).await?;
+
+    sqlx::query!("SET LOCAL statement_timeout = 0")
+      .execute(&mut *tx)
+      .await
+      .map_err(DBError::from)?;
+
+    Ok(tx)
+}
+
+pub(crate) async fn commit(tx: Transaction) -> Result<(), DBError> {
+    tx.commit().await.map_err(DBError::from)
+}
+
+/// Bind values for the visibility check shared by read queries on questions `q`:
+///
+/// `(q.visibility <> 'private' OR <signed link> OR EXISTS (SELECT 1 FROM board_members m
+///   WHERE m.board_uuid = q.board_uuid AND m.user_uuid = <viewer uuid> AND m.status = 'active'))`
+///
+/// The viewer uuid also goes to `post_visible_to(<author uuid>, <viewer uuid>)`, which hides posts
+/// by shadow-banned users from everyone but the author and moderators.
+pub(crate) fn viewer_params(viewer: &Viewer) -> (Option<Uuid>, bool) {
+    match viewer {
+        Viewer::Anonymous => (None, false),
+        Viewer::User(user_uuid) => (Some(*user_uuid), false),
+        Viewer::SignedLink => (None, true),
+    }
+}
+
+/// Soft-deletes go through one statement, so a bulk delete either applies to every matched row
+/// or to none. Malformed and unmatched UUIDs are reported per item instead of failing the batch.
+pub(crate) fn bulk_delete_results(uuids: Vec<String>, deleted: &[Uuid]) -> Vec<BulkDeleteResult> {
+    uuids
+      .into_iter()
+      .map(|uuid| {
+        let error = match Uuid::parse_str(&uuid) {
+          Ok(parsed) if deleted.contains(&parsed) => None,
+          Ok(_) => Some("Not found or already deleted.".to_owned()),
+          Err(err) => Some(DBError::InvalidUUID(err.to_string()).to_string()),
+        };
+
+        BulkDeleteResult {
+          uuid,
+          deleted: error.is_none(),
+          error,
+        }
+      })
+      .collect()
+}
 
 #[cfg(test)]
 mod tests;
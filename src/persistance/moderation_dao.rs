@@ -0,0 +1,386 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+use time::OffsetDateTime;
+
+use crate::models::{
+    DBError, FlagReason, ModerationActionDetail, ModerationActionKind, ModerationItem, ModerationQueueQuery, Pagination,
+    PostKind, PostingQuotaPolicy,
+};
+
+use super::{begin, commit, retry::retrying, Transaction};
+
+#[async_trait]
+pub trait ModerationDao {
+    /// Lists posts with open flags and posts by new users that no moderator has acted on, oldest
+    /// first. Deleted posts are left out.
+    async fn get_moderation_queue(&self, query: ModerationQueueQuery, page: Pagination) -> Result<Vec<ModerationItem>, DBError>;
+    /// Records a moderator's action on a question, or on its answer `answer_uuid`, and resolves the
+    /// post's open flags accordingly, in one transaction. Approving a post held for moderation
+    /// publishes it.
+    async fn record_moderation_action(
+        &self,
+        moderator_uuid: String,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        action: ModerationActionKind,
+        reason: String,
+    ) -> Result<ModerationActionDetail, DBError>;
+    /// Lists recorded actions, newest first.
+    async fn get_moderation_actions(&self, page: Pagination) -> Result<Vec<ModerationActionDetail>, DBError>;
+    /// The questions (title, then description) and answers `author_uuid` posted in the last `minutes`,
+    /// newest first.
+    async fn get_recent_posts(&self, author_uuid: String, minutes: i32) -> Result<Vec<String>, DBError>;
+    /// When `author_uuid` posted each of their questions or answers of the last `minutes`, oldest
+    /// first. Deleted posts are counted too, so that deleting a post does not free its place in the
+    /// posting quota.
+    async fn get_recent_post_times(&self, author_uuid: String, kind: PostKind, minutes: i32) -> Result<Vec<OffsetDateTime>, DBError>;
+    /// Hides a question, or its answer `answer_uuid`, from listings and flags it as spam with
+    /// `details`, in one transaction.
+    async fn hold_post(&self, question_uuid: String, answer_uuid: Option<String>, details: String) -> Result<(), DBError>;
+}
+
+pub struct ModerationDaoImpl {
+    db: PgPool,
+}
+
+impl ModerationDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      ModerationDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_reason(reason: &str) -> Result<FlagReason, DBError> {
+    reason.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_action(action: &str) -> Result<ModerationActionKind, DBError> {
+    action.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+#[async_trait]
+impl ModerationDao for ModerationDaoImpl {
+    async fn get_moderation_queue(&self, query: ModerationQueueQuery, page: Pagination) -> Result<Vec<ModerationItem>, DBError> {
+        retrying("get_moderation_queue", || async move {
+            let records = sqlx::query!(
+                "WITH posts AS (
+                   SELECT q.question_uuid, NULL::uuid AS answer_uuid, q.author_uuid, q.title, q.description AS content, q.created_at
+                   FROM questions q WHERE q.deleted_at IS NULL
+                   UNION ALL
+                   SELECT a.question_uuid, a.answer_uuid, a.author_uuid, q.title, a.content, a.created_at
+                   FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                   WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL
+                 ),
+                 reviewed AS (
+                   SELECT p.*, f.open_flags, f.flag_reasons,
+                     COALESCE(u.created_at > p.created_at - make_interval(days => $1), false) AS new_author,
+                     EXISTS (SELECT 1 FROM moderation_actions m WHERE m.question_uuid = p.question_uuid
+                             AND m.answer_uuid IS NOT DISTINCT FROM p.answer_uuid) AS acted_on
+                   FROM posts p
+                   LEFT JOIN users u ON u.user_uuid = p.author_uuid
+                   CROSS JOIN LATERAL (
+                     SELECT COUNT(*) AS open_flags, COALESCE(array_agg(DISTINCT reason) FILTER (WHERE reason IS NOT NULL), '{}') AS flag_reasons
+                     FROM flags WHERE question_uuid = p.question_uuid AND answer_uuid IS NOT DISTINCT FROM p.answer_uuid AND status = 'open'
+                   ) f
+                 )
+                 SELECT question_uuid AS \"question_uuid!\", answer_uuid, author_uuid, title AS \"title!\", content AS \"content!\",
+                   created_at AS \"created_at!\", open_flags AS \"open_flags!\", flag_reasons AS \"flag_reasons!\", new_author AS \"new_author!\"
+                 FROM reviewed
+                 WHERE (($2 IN ('all', 'flagged') AND open_flags > 0) OR ($2 IN ('all', 'new-user') AND new_author AND NOT acted_on))
+                 AND ($3::TEXT IS NULL OR $3 = ANY(flag_reasons))
+                 ORDER BY created_at, question_uuid, answer_uuid NULLS FIRST OFFSET $4 LIMIT $5",
+                ModerationItem::NEW_USER_DAYS,
+                query.kind.as_str(),
+                query.reason.map(|reason| reason.as_str()),
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(ModerationItem {
+                  question_uuid: record.question_uuid,
+                  answer_uuid: record.answer_uuid,
+                  author_uuid: record.author_uuid,
+                  title: record.title,
+                  content: record.content,
+                  created_at: record.created_at.assume_utc(),
+                  open_flags: record.open_flags,
+                  flag_reasons: record.flag_reasons.iter().map(|reason| parse_reason(reason)).collect::<Result<_, _>>()?,
+                  new_author: record.new_author,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn record_moderation_action(
+        &self,
+        moderator_uuid: String,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        action: ModerationActionKind,
+        reason: String,
+    ) -> Result<ModerationActionDetail, DBError> {
+        let (moderator_uuid, question_uuid, answer_uuid, reason) = (&moderator_uuid, &question_uuid, answer_uuid.as_deref(), &reason);
+
+        retrying("record_moderation_action", || async move {
+            let moderator_uuid = parse_uuid(moderator_uuid)?;
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let resolved = sqlx::query!(
+                "UPDATE flags SET status = $4, resolved_by = $3, resolved_at = CURRENT_TIMESTAMP, resolution_note = $5
+                 WHERE question_uuid = $1 AND answer_uuid IS NOT DISTINCT FROM $2 AND status = 'open'",
+                question_uuid,
+                answer_uuid,
+                moderator_uuid,
+                action.resolves_flags_as().as_str(),
+                reason
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if action == ModerationActionKind::Approve {
+              release_post(&mut tx, question_uuid, answer_uuid).await?;
+            }
+
+            let record = sqlx::query!(
+                "INSERT INTO moderation_actions (moderator_uuid, action, question_uuid, answer_uuid, reason, resolved_flags)
+                 VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+                moderator_uuid,
+                action.as_str(),
+                question_uuid,
+                answer_uuid,
+                reason,
+                resolved.rows_affected() as i64
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(ModerationActionDetail {
+              action_uuid: record.action_uuid,
+              moderator_uuid: record.moderator_uuid,
+              action: parse_action(&record.action)?,
+              question_uuid: record.question_uuid,
+              answer_uuid: record.answer_uuid,
+              reason: record.reason,
+              resolved_flags: record.resolved_flags,
+              created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_moderation_actions(&self, page: Pagination) -> Result<Vec<ModerationActionDetail>, DBError> {
+        retrying("get_moderation_actions", || async move {
+            // Joining questions applies their tenant isolation to the actions.
+            let records = sqlx::query!(
+                "SELECT m.* FROM moderation_actions m JOIN questions q ON q.question_uuid = m.question_uuid
+                 ORDER BY m.created_at DESC, m.action_uuid OFFSET $1 LIMIT $2",
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(ModerationActionDetail {
+                  action_uuid: record.action_uuid,
+                  moderator_uuid: record.moderator_uuid,
+                  action: parse_action(&record.action)?,
+                  question_uuid: record.question_uuid,
+                  answer_uuid: record.answer_uuid,
+                  reason: record.reason,
+                  resolved_flags: record.resolved_flags,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+    async fn get_recent_posts(&self, author_uuid: String, minutes: i32) -> Result<Vec<String>, DBError> {
+        let author_uuid = &author_uuid;
+
+        retrying("get_recent_posts", || async move {
+            let author_uuid = parse_uuid(author_uuid)?;
+
+            let records = sqlx::query!(
+                "SELECT content AS \"content!\" FROM (
+                   SELECT concat_ws(' ', title, description) AS content, created_at FROM questions
+                   WHERE author_uuid = $1 AND deleted_at IS NULL AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)
+                   UNION ALL
+                   SELECT content, created_at FROM answers
+                   WHERE author_uuid = $1 AND deleted_at IS NULL AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)
+                 ) posts ORDER BY created_at DESC",
+                author_uuid,
+                minutes
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records.into_iter().map(|record| record.content).collect())
+          })
+          .await
+    }
+
+    async fn get_recent_post_times(&self, author_uuid: String, kind: PostKind, minutes: i32) -> Result<Vec<OffsetDateTime>, DBError> {
+        let author_uuid = &author_uuid;
+
+        retrying("get_recent_post_times", || async move {
+            let author_uuid = parse_uuid(author_uuid)?;
+
+            let times = match kind {
+                PostKind::Question => sqlx::query_scalar!(
+                    "SELECT created_at FROM questions
+                     WHERE author_uuid = $1 AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)
+                     ORDER BY created_at",
+                    author_uuid,
+                    minutes
+                  )
+                  .fetch_all(&self.db)
+                  .await,
+                PostKind::Answer => sqlx::query_scalar!(
+                    "SELECT created_at FROM answers
+                     WHERE author_uuid = $1 AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)
+                     ORDER BY created_at",
+                    author_uuid,
+                    minutes
+                  )
+                  .fetch_all(&self.db)
+                  .await,
+            }
+            .map_err(DBError::from)?;
+
+            Ok(times.into_iter().map(|time| time.assume_utc()).collect())
+          })
+          .await
+    }
+
+    async fn hold_post(&self, question_uuid: String, answer_uuid: Option<String>, details: String) -> Result<(), DBError> {
+        let (question_uuid, answer_uuid, details) = (&question_uuid, answer_uuid.as_deref(), &details);
+
+        retrying("hold_post", || async move {
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+
+            let mut tx = begin(&self.db).await?;
+
+            hold_post_in(&mut tx, question_uuid, answer_uuid, details).await?;
+
+            commit(tx).await
+          })
+          .await
+    }
+}
+
+/// Fails with `DBError::PostingLimitReached` when `author_uuid` already made `limit` posts of `kind`
+/// in the posting quota window, deleted ones included, within `tx`. Holds a lock on the author until
+/// `tx` ends, so that concurrent posts by the same author are counted one after the other.
+pub(crate) async fn require_posting_limit_in(
+    tx: &mut Transaction,
+    author_uuid: Uuid,
+    kind: PostKind,
+    limit: i64,
+) -> Result<(), DBError> {
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))", author_uuid.to_string())
+      .execute(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    let posted = match kind {
+        PostKind::Question => sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM questions WHERE author_uuid = $1 AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)",
+            author_uuid,
+            PostingQuotaPolicy::WINDOW_MINUTES
+          )
+          .fetch_one(&mut **tx)
+          .await,
+        PostKind::Answer => sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM answers WHERE author_uuid = $1 AND created_at > CURRENT_TIMESTAMP - make_interval(mins => $2)",
+            author_uuid,
+            PostingQuotaPolicy::WINDOW_MINUTES
+          )
+          .fetch_one(&mut **tx)
+          .await,
+    }
+    .map_err(DBError::from)?;
+
+    if posted.unwrap_or(0) >= limit {
+        return Err(DBError::PostingLimitReached);
+    }
+
+    Ok(())
+}
+
+/// Hides the question, or its answer `answer_uuid`, and flags it as spam with `details`, within `tx`.
+pub(crate) async fn hold_post_in(
+    tx: &mut Transaction,
+    question_uuid: Uuid,
+    answer_uuid: Option<Uuid>,
+    details: &str,
+) -> Result<(), DBError> {
+    match answer_uuid {
+      Some(answer_uuid) => sqlx::query!("UPDATE answers SET held_at = CURRENT_TIMESTAMP WHERE answer_uuid = $1", answer_uuid)
+        .execute(&mut **tx)
+        .await,
+      None => sqlx::query!("UPDATE questions SET held_at = CURRENT_TIMESTAMP WHERE question_uuid = $1", question_uuid)
+        .execute(&mut **tx)
+        .await,
+    }
+    .map_err(DBError::from)?;
+
+    sqlx::query!(
+        "INSERT INTO flags (question_uuid, answer_uuid, reporter_uuid, reason, details) VALUES ($1, $2, NULL, $3, $4)",
+        question_uuid,
+        answer_uuid,
+        FlagReason::Spam.as_str(),
+        details
+      )
+      .execute(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    Ok(())
+}
+
+async fn release_post(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    question_uuid: Uuid,
+    answer_uuid: Option<Uuid>,
+) -> Result<(), DBError> {
+    match answer_uuid {
+      Some(answer_uuid) => sqlx::query!("UPDATE answers SET held_at = NULL WHERE answer_uuid = $1", answer_uuid)
+        .execute(&mut **tx)
+        .await,
+      None => sqlx::query!("UPDATE questions SET held_at = NULL WHERE question_uuid = $1", question_uuid)
+        .execute(&mut **tx)
+        .await,
+    }
+    .map_err(DBError::from)?;
+
+    Ok(())
+}
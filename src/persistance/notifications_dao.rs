@@ -0,0 +1,559 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{
+    AcceptSuggestionThresholds, DBError, Notification, NotificationChannels, NotificationKind, NotificationKindSettings,
+    NotificationSettings, Pagination, PendingEmail, WebhookEvent,
+};
+
+use super::{begin, circuit_breaker::guarded, retry::retrying};
+
+#[async_trait]
+pub trait NotificationsDao {
+    /// Creates one notification per follower who can still read the question, skipping the user who caused it.
+    /// Returns how many notifications were created.
+    async fn notify_question_followers(
+        &self,
+        question_uuid: String,
+        kind: NotificationKind,
+        answer_uuid: Option<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<u64, DBError>;
+    /// Records a mention of each named user who is active and can read the question, other than the
+    /// author, and notifies them. Unknown usernames are ignored. Returns how many users were notified.
+    async fn notify_mentioned_users(
+        &self,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        usernames: Vec<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<u64, DBError>;
+    /// Notifies a single user about a question, or its answer `answer_uuid`.
+    async fn notify_user(
+        &self,
+        user_uuid: String,
+        kind: NotificationKind,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<(), DBError>;
+    /// Suggests accepting the top answer to the author of each unresolved question where it clearly
+    /// leads, once per question. Authors who opted out are skipped. Returns how many were notified.
+    async fn notify_accept_suggestions(&self, thresholds: AcceptSuggestionThresholds) -> Result<u64, DBError>;
+    async fn get_accept_suggestions_enabled(&self, user_uuid: String) -> Result<bool, DBError>;
+    async fn set_accept_suggestions_enabled(&self, user_uuid: String, enabled: bool) -> Result<(), DBError>;
+    /// The kinds the user configured, without the defaults of the others, and their webhook URL.
+    async fn get_notification_settings(&self, user_uuid: String) -> Result<NotificationSettings, DBError>;
+    /// Replaces the user's settings. Their webhook is given `secret` when it is created or its URL
+    /// changes, which is then returned; removing the URL deletes the webhook.
+    async fn set_notification_settings(
+        &self,
+        user_uuid: String,
+        settings: NotificationSettings,
+        secret: String,
+    ) -> Result<Option<String>, DBError>;
+    /// The user's inbox, newest first. Notifications about posts that were deleted since, or that the
+    /// user can no longer see, are left out.
+    async fn get_notifications(&self, user_uuid: String, unread_only: bool, page: Pagination) -> Result<Vec<Notification>, DBError>;
+    /// Returns false when the user has no notification `id`; reading it again keeps the first `read_at`.
+    async fn mark_notification_read(&self, user_uuid: String, id: i64) -> Result<bool, DBError>;
+    /// Returns how many notifications were unread.
+    async fn mark_all_notifications_read(&self, user_uuid: String) -> Result<u64, DBError>;
+    /// Claims up to `limit` notifications waiting to be emailed, oldest first. Claims of a worker that
+    /// died are taken over after 10 minutes, and failed deliveries are retried after 5.
+    async fn claim_pending_emails(&self, limit: i64) -> Result<Vec<PendingEmail>, DBError>;
+    async fn mark_email_sent(&self, id: i64) -> Result<(), DBError>;
+    /// For notifications that are not emailed, e.g. when the recipient has no email address.
+    async fn skip_email(&self, id: i64) -> Result<(), DBError>;
+    /// Records a failed delivery, to be retried until it failed `max_attempts` times.
+    async fn fail_email(&self, id: i64, error: String, max_attempts: i32) -> Result<(), DBError>;
+}
+
+pub struct NotificationsDaoImpl {
+    db: PgPool,
+}
+
+impl NotificationsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      NotificationsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl NotificationsDao for NotificationsDaoImpl {
+    async fn notify_question_followers(
+        &self,
+        question_uuid: String,
+        kind: NotificationKind,
+        answer_uuid: Option<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<u64, DBError> {
+        let (question_uuid, answer_uuid, actor_uuid) = (&question_uuid, answer_uuid.as_deref(), actor_uuid.as_deref());
+
+        retrying("notify_question_followers", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+            let actor_uuid = actor_uuid.map(parse_uuid).transpose()?;
+
+            let result = sqlx::query!(
+                "INSERT INTO notifications (user_uuid, kind, question_uuid, answer_uuid, actor_uuid)
+                 SELECT f.user_uuid, $2, f.question_uuid, $3, $4 FROM question_followers f
+                 JOIN questions q ON q.question_uuid = f.question_uuid
+                 WHERE f.question_uuid = $1 AND f.deleted_at IS NULL AND f.user_uuid IS DISTINCT FROM $4
+                 AND (q.visibility <> 'private' OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = f.user_uuid AND m.status = 'active'))",
+                uuid,
+                kind.as_str(),
+                answer_uuid,
+                actor_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn notify_mentioned_users(
+        &self,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        usernames: Vec<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<u64, DBError> {
+        let (question_uuid, answer_uuid, usernames, actor_uuid) = (&question_uuid, answer_uuid.as_deref(), &usernames, actor_uuid.as_deref());
+
+        retrying("notify_mentioned_users", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+            let actor_uuid = actor_uuid.map(parse_uuid).transpose()?;
+
+            let result = sqlx::query!(
+                "WITH mentioned AS (
+                   INSERT INTO mentions (user_uuid, question_uuid, answer_uuid, actor_uuid)
+                   SELECT u.user_uuid, q.question_uuid, $2, $4 FROM users u
+                   JOIN questions q ON q.question_uuid = $1
+                   WHERE u.username = ANY($3) AND u.active AND u.user_uuid IS DISTINCT FROM $4
+                   AND (q.visibility <> 'private' OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = u.user_uuid AND m.status = 'active'))
+                   RETURNING user_uuid
+                 )
+                 INSERT INTO notifications (user_uuid, kind, question_uuid, answer_uuid, actor_uuid)
+                 SELECT user_uuid, $5, $1, $2, $4 FROM mentioned",
+                uuid,
+                answer_uuid,
+                &usernames,
+                actor_uuid,
+                NotificationKind::Mention.as_str()
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn notify_user(
+        &self,
+        user_uuid: String,
+        kind: NotificationKind,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        actor_uuid: Option<String>,
+    ) -> Result<(), DBError> {
+        let (user_uuid, question_uuid, answer_uuid, actor_uuid) = (&user_uuid, &question_uuid, answer_uuid.as_deref(), actor_uuid.as_deref());
+
+        retrying("notify_user", || async move {
+            let user_uuid = parse_uuid(user_uuid)?;
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+            let actor_uuid = actor_uuid.map(parse_uuid).transpose()?;
+
+            sqlx::query!(
+                "INSERT INTO notifications (user_uuid, kind, question_uuid, answer_uuid, actor_uuid) VALUES ($1, $2, $3, $4, $5)",
+                user_uuid,
+                kind.as_str(),
+                question_uuid,
+                answer_uuid,
+                actor_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn notify_accept_suggestions(&self, thresholds: AcceptSuggestionThresholds) -> Result<u64, DBError> {
+        retrying("notify_accept_suggestions", || async move {
+            let result = sqlx::query!(
+                "WITH ranked AS (
+                   SELECT a.question_uuid, a.answer_uuid, COALESCE(SUM(v.value), 0) AS score,
+                     ROW_NUMBER() OVER (PARTITION BY a.question_uuid ORDER BY COALESCE(SUM(v.value), 0) DESC, a.created_at) AS rank
+                   FROM answers a LEFT JOIN counted_answer_votes v ON v.answer_uuid = a.answer_uuid
+                   WHERE a.deleted_at IS NULL
+                   GROUP BY a.answer_uuid
+                 ),
+                 candidates AS (
+                   SELECT q.question_uuid, q.author_uuid, top.answer_uuid FROM questions q
+                   JOIN users u ON u.user_uuid = q.author_uuid
+                   JOIN ranked top ON top.question_uuid = q.question_uuid AND top.rank = 1
+                   LEFT JOIN ranked runner_up ON runner_up.question_uuid = q.question_uuid AND runner_up.rank = 2
+                   WHERE q.deleted_at IS NULL AND q.accepted_answer_uuid IS NULL AND q.accept_suggested_at IS NULL
+                   AND q.created_at < CURRENT_TIMESTAMP - make_interval(days => $1)
+                   AND u.active AND NOT EXISTS (SELECT 1 FROM accept_suggestion_opt_outs o WHERE o.user_uuid = u.user_uuid)
+                   AND top.score >= $2 AND top.score - COALESCE(runner_up.score, 0) >= $3
+                 ),
+                 suggested AS (
+                   UPDATE questions q SET accept_suggested_at = CURRENT_TIMESTAMP FROM candidates c
+                   WHERE q.question_uuid = c.question_uuid
+                   RETURNING c.question_uuid, c.author_uuid, c.answer_uuid
+                 )
+                 INSERT INTO notifications (user_uuid, kind, question_uuid, answer_uuid)
+                 SELECT author_uuid, $4, question_uuid, answer_uuid FROM suggested",
+                thresholds.min_age_days,
+                thresholds.min_score,
+                thresholds.min_lead,
+                NotificationKind::AcceptSuggestion.as_str()
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn get_accept_suggestions_enabled(&self, user_uuid: String) -> Result<bool, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_accept_suggestions_enabled", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let opted_out = sqlx::query_scalar!(
+                "SELECT EXISTS (SELECT 1 FROM accept_suggestion_opt_outs WHERE user_uuid = $1) AS \"opted_out!\"",
+                uuid
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(!opted_out)
+          })
+          .await
+    }
+
+    async fn set_accept_suggestions_enabled(&self, user_uuid: String, enabled: bool) -> Result<(), DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("set_accept_suggestions_enabled", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let query = if enabled {
+                sqlx::query!("DELETE FROM accept_suggestion_opt_outs WHERE user_uuid = $1", uuid)
+            } else {
+                sqlx::query!("INSERT INTO accept_suggestion_opt_outs (user_uuid) VALUES ($1) ON CONFLICT DO NOTHING", uuid)
+            };
+
+            query
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_notification_settings(&self, user_uuid: String) -> Result<NotificationSettings, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_notification_settings", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let records = sqlx::query!(
+                "SELECT kind, in_app, email, webhook FROM notification_settings WHERE user_uuid = $1 ORDER BY kind",
+                uuid
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let webhook_url = sqlx::query_scalar!("SELECT url FROM webhooks WHERE owner_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let kinds = records
+              .into_iter()
+              .map(|record| {
+                Ok(NotificationKindSettings {
+                  kind: record.kind.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  channels: NotificationChannels {
+                    in_app: record.in_app,
+                    email: record.email,
+                    webhook: record.webhook,
+                  },
+                })
+              })
+              .collect::<Result<_, DBError>>()?;
+
+            Ok(NotificationSettings {
+              kinds,
+              webhook_url,
+              webhook_secret: None,
+            })
+          })
+          .await
+    }
+
+    async fn set_notification_settings(
+        &self,
+        user_uuid: String,
+        settings: NotificationSettings,
+        secret: String,
+    ) -> Result<Option<String>, DBError> {
+        let (user_uuid, settings, secret) = (&user_uuid, &settings, &secret);
+
+        retrying("set_notification_settings", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let mut tx = begin(&self.db).await?;
+
+            sqlx::query!("DELETE FROM notification_settings WHERE user_uuid = $1", uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            for settings in &settings.kinds {
+              sqlx::query!(
+                  "INSERT INTO notification_settings (user_uuid, kind, in_app, email, webhook) VALUES ($1, $2, $3, $4, $5)",
+                  uuid,
+                  settings.kind.as_str(),
+                  settings.channels.in_app,
+                  settings.channels.email,
+                  settings.channels.webhook
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(DBError::from)?;
+            }
+
+            let secret = match &settings.webhook_url {
+                Some(url) => {
+                  // The secret only changes with the URL, so receivers keep verifying unchanged webhooks.
+                  sqlx::query_scalar!(
+                      "INSERT INTO webhooks (url, secret, events, created_by, owner_uuid) VALUES ($1, $2, $3, $4, $4)
+                       ON CONFLICT (owner_uuid) DO UPDATE SET url = EXCLUDED.url, secret = EXCLUDED.secret
+                       WHERE webhooks.url <> EXCLUDED.url
+                       RETURNING secret",
+                      url,
+                      secret,
+                      &[WebhookEvent::Notification.as_str().to_owned()],
+                      uuid
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(DBError::from)?
+                }
+                None => {
+                  sqlx::query!("DELETE FROM webhooks WHERE owner_uuid = $1", uuid)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DBError::from)?;
+
+                  None
+                }
+            };
+
+            tx.commit().await.map_err(DBError::from)?;
+
+            Ok(secret)
+          })
+          .await
+    }
+
+    async fn get_notifications(&self, user_uuid: String, unread_only: bool, page: Pagination) -> Result<Vec<Notification>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_notifications", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let records = sqlx::query!(
+                "SELECT n.* FROM notifications n
+                 LEFT JOIN questions q ON q.question_uuid = n.question_uuid
+                 LEFT JOIN answers a ON a.answer_uuid = n.answer_uuid
+                 WHERE n.user_uuid = $1 AND n.in_app AND (NOT $2 OR n.read_at IS NULL)
+                 AND (n.question_uuid IS NULL OR (q.deleted_at IS NULL AND post_visible_to(q.author_uuid, $1)))
+                 AND (n.answer_uuid IS NULL OR (a.deleted_at IS NULL AND post_visible_to(a.author_uuid, $1)
+                   AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, $1)))
+                 ORDER BY n.created_at DESC, n.id DESC
+                 OFFSET $3 LIMIT $4",
+                uuid,
+                unread_only,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(Notification {
+                  id: record.id,
+                  kind: record.kind.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  question_uuid: record.question_uuid,
+                  answer_uuid: record.answer_uuid,
+                  actor_uuid: record.actor_uuid,
+                  created_at: record.created_at.assume_utc(),
+                  read_at: record.read_at.map(|read_at| read_at.assume_utc()),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn mark_notification_read(&self, user_uuid: String, id: i64) -> Result<bool, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("mark_notification_read", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let result = sqlx::query!(
+                "UPDATE notifications SET read_at = COALESCE(read_at, CURRENT_TIMESTAMP) WHERE id = $1 AND user_uuid = $2 AND in_app",
+                id,
+                uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn mark_all_notifications_read(&self, user_uuid: String) -> Result<u64, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("mark_all_notifications_read", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let result = sqlx::query!(
+                "UPDATE notifications SET read_at = CURRENT_TIMESTAMP WHERE user_uuid = $1 AND in_app AND read_at IS NULL",
+                uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn claim_pending_emails(&self, limit: i64) -> Result<Vec<PendingEmail>, DBError> {
+        guarded("claim_pending_emails", async {
+            let records = sqlx::query!(
+                "WITH claimed AS (
+                   UPDATE notifications SET email_status = 'sending', email_claimed_at = CURRENT_TIMESTAMP
+                   WHERE id IN (
+                     SELECT id FROM notifications
+                     WHERE (email_status = 'pending' AND (email_claimed_at IS NULL OR email_claimed_at < CURRENT_TIMESTAMP - INTERVAL '5 minutes'))
+                     OR (email_status = 'sending' AND email_claimed_at < CURRENT_TIMESTAMP - INTERVAL '10 minutes')
+                     ORDER BY created_at LIMIT $1 FOR UPDATE SKIP LOCKED
+                   )
+                   RETURNING id, user_uuid, kind, question_uuid, answer_uuid, actor_uuid, created_at
+                 )
+                 SELECT c.id, c.user_uuid, c.kind, c.question_uuid, c.answer_uuid, u.username AS \"actor_username?\",
+                   CASE WHEN q.deleted_at IS NULL AND post_visible_to(q.author_uuid, c.user_uuid)
+                     AND (c.answer_uuid IS NULL OR (a.deleted_at IS NULL AND post_visible_to(a.author_uuid, c.user_uuid)
+                       AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, c.user_uuid)))
+                     THEN q.title END AS question_title
+                 FROM claimed c
+                 LEFT JOIN questions q ON q.question_uuid = c.question_uuid
+                 LEFT JOIN answers a ON a.answer_uuid = c.answer_uuid
+                 LEFT JOIN users u ON u.user_uuid = c.actor_uuid
+                 ORDER BY c.created_at, c.id",
+                limit
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(PendingEmail {
+                  id: record.id,
+                  user_uuid: record.user_uuid,
+                  kind: record.kind.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  question_uuid: record.question_uuid,
+                  question_title: record.question_title,
+                  answer_uuid: record.answer_uuid,
+                  actor_username: record.actor_username,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn mark_email_sent(&self, id: i64) -> Result<(), DBError> {
+        retrying("mark_email_sent", || async move {
+            sqlx::query!("UPDATE notifications SET email_status = 'sent', email_error = NULL WHERE id = $1", id)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn skip_email(&self, id: i64) -> Result<(), DBError> {
+        retrying("skip_email", || async move {
+            sqlx::query!("UPDATE notifications SET email_status = 'skipped' WHERE id = $1", id)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn fail_email(&self, id: i64, error: String, max_attempts: i32) -> Result<(), DBError> {
+        guarded("fail_email", async {
+            // The claim time is kept, so the retry waits for the claim to go stale.
+            sqlx::query!(
+                "UPDATE notifications SET email_attempts = email_attempts + 1, email_error = $2,
+                   email_status = CASE WHEN email_attempts + 1 >= $3 THEN 'failed' ELSE 'pending' END
+                 WHERE id = $1",
+                id,
+                error,
+                max_attempts
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, NewQuerySample, Pagination, QuerySample, QuerySamplesQuery, SampledQuery};
+
+use super::retry::retrying;
+
+#[async_trait]
+pub trait QuerySamplesDao {
+    async fn get_sampling_percent(&self) -> Result<f64, DBError>;
+    async fn set_sampling_percent(&self, percent: f64, user_uuid: String) -> Result<(), DBError>;
+    async fn create_query_sample(&self, sample: NewQuerySample) -> Result<(), DBError>;
+    /// Lists samples newest first, optionally of one route or slower than `min_elapsed_ms`.
+    async fn get_query_samples(&self, query: QuerySamplesQuery, page: Pagination) -> Result<Vec<QuerySample>, DBError>;
+    /// Permanently removes samples recorded more than `retention_days` ago.
+    async fn purge_query_samples(&self, retention_days: i32) -> Result<u64, DBError>;
+}
+
+pub struct QuerySamplesDaoImpl {
+    db: PgPool,
+}
+
+impl QuerySamplesDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      QuerySamplesDaoImpl {
+        db
+      }
+    }
+}
+
+#[async_trait]
+impl QuerySamplesDao for QuerySamplesDaoImpl {
+    async fn get_sampling_percent(&self) -> Result<f64, DBError> {
+        retrying("get_sampling_percent", || async move {
+            sqlx::query_scalar!("SELECT percent FROM query_sampling WHERE singleton")
+              .fetch_optional(&self.db)
+              .await
+              .map(Option::unwrap_or_default)
+              .map_err(DBError::from)
+          })
+          .await
+    }
+
+    async fn set_sampling_percent(&self, percent: f64, user_uuid: String) -> Result<(), DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("set_sampling_percent", || async move {
+            let user_uuid = Uuid::parse_str(user_uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))?;
+
+            sqlx::query!(
+                "INSERT INTO query_sampling (singleton, percent, updated_by, updated_at) VALUES (TRUE, $1, $2, CURRENT_TIMESTAMP)
+                 ON CONFLICT (singleton) DO UPDATE SET percent = EXCLUDED.percent, updated_by = EXCLUDED.updated_by,
+                   updated_at = EXCLUDED.updated_at",
+                percent,
+                user_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn create_query_sample(&self, sample: NewQuerySample) -> Result<(), DBError> {
+        let sample = &sample;
+
+        retrying("create_query_sample", || async move {
+            let queries = serde_json::to_value(&sample.queries).map_err(|err| DBError::Other(Box::new(err)))?;
+
+            sqlx::query!(
+                "INSERT INTO query_samples (route, status, elapsed_ms, queries) VALUES ($1, $2, $3, $4)",
+                sample.route,
+                i32::from(sample.status),
+                sample.elapsed_ms,
+                queries
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_query_samples(&self, query: QuerySamplesQuery, page: Pagination) -> Result<Vec<QuerySample>, DBError> {
+        let query = &query;
+
+        retrying("get_query_samples", || async move {
+            let records = sqlx::query!(
+                "SELECT * FROM query_samples
+                 WHERE ($1::TEXT IS NULL OR route = $1) AND ($2::DOUBLE PRECISION IS NULL OR elapsed_ms >= $2)
+                 ORDER BY created_at DESC, sample_uuid OFFSET $3 LIMIT $4",
+                query.route,
+                query.min_elapsed_ms,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                let queries: Vec<SampledQuery> =
+                  serde_json::from_value(record.queries).map_err(|err| DBError::Other(Box::new(err)))?;
+
+                Ok(QuerySample {
+                  sample_uuid: record.sample_uuid,
+                  route: record.route,
+                  status: u16::try_from(record.status).map_err(|err| DBError::Other(Box::new(err)))?,
+                  elapsed_ms: record.elapsed_ms,
+                  queries,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn purge_query_samples(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_query_samples", || async move {
+            let result = sqlx::query!(
+                "DELETE FROM query_samples WHERE created_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+                retention_days
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+}
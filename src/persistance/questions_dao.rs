@@ -1,75 +1,993 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use sqlx::{types::Uuid, PgPool};
+use futures::TryStreamExt;
+use sqlx::{types::{time::PrimitiveDateTime, Uuid}, PgPool};
+use tokio::sync::mpsc;
+
+use crate::{
+    language::detect_language,
+    models::{
+        BulkDeleteResult, ContestDetail, DBError, ExportRange, FeedEntry, Pagination, PostKind, Question,
+        QuestionDeletion, QuestionDetail, QuestionRevision, QuestionKind, QuestionStatus, SitemapUrl, Viewer,
+        Visibility,
+    },
+};
 
-use crate::models::{DBError, Question, QuestionDetail};
+use super::{
+    begin, begin_streaming, bulk_delete_results, commit,
+    circuit_breaker::breaker,
+    moderation_dao::{hold_post_in, require_posting_limit_in},
+    replica::{reading, ReadReplica},
+    retry::retrying,
+    tags_dao::add_pending_tags_in,
+    viewer_params, Transaction,
+};
 
 #[async_trait]
 pub trait QuestionsDao {
-    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError>;
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError>;
-    async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Creates the question with its proposed `pending_tags` and, given `spam_details`, holds it for
+    /// moderation, all in one transaction, so that it is never saved without them. Given a
+    /// `posting_limit`, fails with `DBError::PostingLimitReached` instead when the author already
+    /// made that many questions in the posting quota window.
+    async fn create_question(
+        &self,
+        question: Question,
+        author_uuid: Option<String>,
+        pending_tags: Vec<String>,
+        spam_details: Option<String>,
+        posting_limit: Option<i64>,
+    ) -> Result<QuestionDetail, DBError>;
+    /// Soft-deletes the question with its answers, and drops its follows and tag proposals, in one
+    /// transaction. Returns `None` when the question does not exist or is already deleted.
+    async fn delete_question(&self, question_uuid: String) -> Result<Option<QuestionDeletion>, DBError>;
+    /// Soft-deletes every listed question like `delete_question`, in one transaction, reporting the
+    /// outcome per UUID.
+    async fn delete_questions(&self, question_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError>;
+    /// Restores the question with the answers that were deleted along with it.
+    async fn restore_question(&self, question_uuid: String) -> Result<Option<QuestionDetail>, DBError>;
+    /// Permanently removes questions soft-deleted more than `retention_days` ago.
+    async fn purge_deleted_questions(&self, retention_days: i32) -> Result<u64, DBError>;
+    /// Returns `None` when the question does not exist or `viewer` may not read it.
+    async fn get_question(&self, question_uuid: String, viewer: Viewer) -> Result<Option<QuestionDetail>, DBError>;
+    /// Lists the questions `viewer` may read, leaving out unlisted ones and those held for moderation.
+    /// With `language`, only questions detected to be in that language are listed.
+    async fn get_questions(&self, viewer: Viewer, language: Option<String>) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Fetches the given questions in request order, skipping those that are missing or hidden from `viewer`.
+    async fn get_questions_by_uuid(&self, question_uuids: Vec<String>, viewer: Viewer) -> Result<Vec<QuestionDetail>, DBError>;
+    /// Pages through the questions `author_uuid` posted, newest first. Unlisted ones and those held
+    /// for moderation are only included for the author themselves.
+    async fn get_questions_by_author(&self, author_uuid: String, page: Pagination, viewer: Viewer) -> Result<Vec<QuestionDetail>, DBError>;
+    async fn update_question_status(
+        &self,
+        question_uuid: String,
+        status: QuestionStatus,
+        reason: String,
+    ) -> Result<Option<QuestionDetail>, DBError>;
+    /// Applies an edit and records it as a new revision in the same transaction.
+    async fn update_question(
+        &self,
+        question_uuid: String,
+        question: Question,
+        editor_uuid: String,
+    ) -> Result<Option<QuestionDetail>, DBError>;
+    async fn get_question_revisions(&self, question_uuid: String, viewer: Viewer) -> Result<Vec<QuestionRevision>, DBError>;
+    /// Sets the accepted answer of the question, or clears it with `None`. Returns false when the
+    /// question is deleted, or the answer is not one of its answers that is neither deleted nor held.
+    async fn accept_answer(&self, question_uuid: String, answer_uuid: Option<Uuid>) -> Result<bool, DBError>;
+    /// Picks the winner of every contest whose voting ended, from the votes cast while it was open.
+    /// Contests where no answer scored above 0 are decided without a winner. Returns how many
+    /// contests were decided.
+    async fn decide_contests(&self) -> Result<u64, DBError>;
+    /// The newest public questions anyone may read, optionally only those tagged `tag`.
+    async fn get_feed_entries(&self, tag: Option<String>, limit: i64) -> Result<Vec<FeedEntry>, DBError>;
+    /// How many questions the sitemaps list: the public ones anyone may read.
+    async fn count_sitemap_questions(&self) -> Result<i64, DBError>;
+    /// Sends the URLs of sitemap `page`, counting from 0, to `urls` as they are read, without loading
+    /// the page into memory. Stops early once `urls` is closed.
+    async fn stream_sitemap_urls(&self, page: i64, urls: mpsc::Sender<SitemapUrl>) -> Result<(), DBError>;
+    /// Sends every question created in `range` that is not deleted, private and held ones included,
+    /// to `questions` as they are read, oldest first. Stops early once `questions` is closed.
+    async fn stream_questions(&self, range: ExportRange, questions: mpsc::Sender<QuestionDetail>) -> Result<(), DBError>;
 }
 
 pub struct QuestionsDaoImpl {
     db: PgPool,
+    replica: Option<Arc<ReadReplica>>,
 }
 
 impl QuestionsDaoImpl {
     pub fn new(db: PgPool) -> Self {
       QuestionsDaoImpl {
-        db
+        db,
+        replica: None,
+      }
+    }
+
+    /// Sends the listings to `replica`, which may lag behind the primary. Reads of a single item
+    /// stay on the primary, so that a client reading what it just wrote finds it.
+    pub fn with_replica(self, replica: Arc<ReadReplica>) -> Self {
+      QuestionsDaoImpl {
+        replica: Some(replica),
+        ..self
       }
     }
 }
 
+pub(crate) fn parse_status(status: &str) -> Result<QuestionStatus, DBError> {
+    status.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+pub(crate) fn parse_kind(kind: &str) -> Result<QuestionKind, DBError> {
+    kind.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+pub(crate) fn contest_of(
+    reveal_at: Option<PrimitiveDateTime>,
+    ends_at: Option<PrimitiveDateTime>,
+    winner_uuid: Option<Uuid>,
+) -> Option<ContestDetail> {
+    Some(ContestDetail {
+      reveal_at: reveal_at?.assume_utc(),
+      ends_at: ends_at?.assume_utc(),
+      winner_uuid,
+    })
+}
+
+pub(crate) fn parse_visibility(visibility: &str) -> Result<Visibility, DBError> {
+    visibility.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+/// Soft-deletes the answers of the just deleted `question_uuids`, stamping them like their
+/// questions so that a restore can tell them from answers deleted before, and drops their follows
+/// and tag proposals. The returned deletion has a nil `question_uuid`.
+async fn delete_dependents_in(tx: &mut Transaction, question_uuids: &[Uuid]) -> Result<QuestionDeletion, DBError> {
+    let answers = sqlx::query!(
+        r#"WITH deleted AS (
+             UPDATE answers a SET deleted_at = q.deleted_at FROM questions q
+             WHERE q.question_uuid = ANY($1) AND a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+             RETURNING a.answer_uuid
+           )
+           SELECT count(*) AS "answers!",
+                  (SELECT count(*) FROM answer_votes WHERE answer_uuid IN (SELECT answer_uuid FROM deleted)) AS "votes!"
+           FROM deleted"#,
+        question_uuids
+      )
+      .fetch_one(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    let followers = sqlx::query!(
+        "UPDATE question_followers f SET deleted_at = q.deleted_at FROM questions q
+         WHERE q.question_uuid = ANY($1) AND f.question_uuid = q.question_uuid AND f.deleted_at IS NULL",
+        question_uuids
+      )
+      .execute(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    let pending_tags = sqlx::query!(
+        "UPDATE pending_tags p SET deleted_at = q.deleted_at FROM questions q
+         WHERE q.question_uuid = ANY($1) AND p.question_uuid = q.question_uuid AND p.deleted_at IS NULL",
+        question_uuids
+      )
+      .execute(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    Ok(QuestionDeletion {
+        question_uuid: Uuid::nil(),
+        answers: answers.answers,
+        votes: answers.votes,
+        followers: followers.rows_affected() as i64,
+        pending_tags: pending_tags.rows_affected() as i64,
+    })
+}
+
 #[async_trait]
 impl QuestionsDao for QuestionsDaoImpl {
-    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
-        let record = sqlx::query!("INSERT INTO questions (title, description) VALUES ($1, $2) RETURNING *", question.title, question.description)
-          .fetch_one(&self.db)
-          .await
-          .map_err(|err: sqlx::Error| { DBError::Other(Box::new(err)) })?;
+    async fn create_question(
+        &self,
+        question: Question,
+        author_uuid: Option<String>,
+        pending_tags: Vec<String>,
+        spam_details: Option<String>,
+        posting_limit: Option<i64>,
+    ) -> Result<QuestionDetail, DBError> {
+        let author_uuid = author_uuid.as_deref().map(parse_uuid).transpose()?;
+        let board_uuid = question.board_uuid;
+        let language = detect_language(&question.title, &question.description);
+
+        let db = &self.db;
+        let (question, language, spam_details) = (&question, language.as_deref(), &spam_details);
+        let proposed_tags = &pending_tags;
+
+        let record = retrying("create_question", || async move {
+            let mut tx = begin(db).await?;
+
+            if let (Some(author_uuid), Some(limit)) = (author_uuid, posting_limit) {
+                require_posting_limit_in(&mut tx, author_uuid, PostKind::Question, limit).await?;
+            }
+
+            let record = sqlx::query!(
+                "INSERT INTO questions (title, description, author_uuid, visibility, board_uuid, tags, language, kind, contest_reveal_at, contest_ends_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8,
+                   CURRENT_TIMESTAMP + make_interval(hours => $9), CURRENT_TIMESTAMP + make_interval(hours => $9 + $10))
+                 RETURNING *",
+                question.title,
+                question.description,
+                author_uuid,
+                question.visibility.as_str(),
+                board_uuid,
+                &question.tags,
+                language,
+                question.kind.as_str(),
+                question.contest.map(|contest| contest.answer_hours),
+                question.contest.map(|contest| contest.voting_hours)
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if !proposed_tags.is_empty() {
+                add_pending_tags_in(&mut tx, record.question_uuid, proposed_tags, author_uuid).await?;
+            }
+
+            if let Some(details) = spam_details {
+                hold_post_in(&mut tx, record.question_uuid, None, details).await?;
+            }
+
+            commit(tx).await?;
+
+            Ok(record)
+          })
+          .await?;
 
         Ok(QuestionDetail {
-            question_uuid: record.question_uuid.to_string(),
+            question_uuid: record.question_uuid,
             title: record.title,
             description: record.description,
-            created_at: record.created_at.to_string(),
+            status: parse_status(&record.status)?,
+            status_reason: record.status_reason,
+            kind: parse_kind(&record.kind)?,
+            contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+            author_uuid: record.author_uuid,
+            visibility: parse_visibility(&record.visibility)?,
+            board_uuid: record.board_uuid,
+            tags: record.tags,
+            language: record.language,
+            created_at: record.created_at.assume_utc(),
+            updated_at: record.updated_at.assume_utc(),
+            description_html: None,
+            code_blocks: Vec::new(),
+            link_previews: Vec::new(),
+            held_for_review: spam_details.is_some(),
+            pending_tags,
         })
     }
 
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError> {
+    async fn delete_question(&self, question_uuid: String) -> Result<Option<QuestionDeletion>, DBError> {
+        let question_uuid = &question_uuid;
+
+        retrying("delete_question", || async move {
+            let uuid = Uuid::parse_str(question_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            let mut tx = begin(&self.db).await?;
+
+            let deleted = sqlx::query_scalar!(
+                "UPDATE questions SET deleted_at = CURRENT_TIMESTAMP WHERE question_uuid = $1 AND deleted_at IS NULL RETURNING question_uuid",
+                uuid
+              )
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if deleted.is_none() {
+                return Ok(None);
+            }
+
+            let deletion = delete_dependents_in(&mut tx, &[uuid]).await?;
+
+            commit(tx).await?;
+
+            Ok(Some(QuestionDeletion {
+                question_uuid: uuid,
+                ..deletion
+            }))
+          })
+          .await
+    }
+
+    async fn delete_questions(&self, question_uuids: Vec<String>) -> Result<Vec<BulkDeleteResult>, DBError> {
+        let question_uuids = &question_uuids;
+
+        retrying("delete_questions", || async move {
+            let uuids: Vec<Uuid> = question_uuids
+              .iter()
+              .filter_map(|uuid| Uuid::parse_str(uuid).ok())
+              .collect();
+
+            let mut tx = begin(&self.db).await?;
+
+            let deleted = sqlx::query_scalar!(
+                "UPDATE questions SET deleted_at = CURRENT_TIMESTAMP WHERE question_uuid = ANY($1) AND deleted_at IS NULL RETURNING question_uuid",
+                &uuids
+              )
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            delete_dependents_in(&mut tx, &deleted).await?;
+
+            commit(tx).await?;
+
+            Ok(bulk_delete_results(question_uuids.clone(), &deleted))
+          })
+          .await
+    }
+
+    async fn restore_question(&self, question_uuid: String) -> Result<Option<QuestionDetail>, DBError> {
+        let question_uuid = &question_uuid;
+
+        retrying("restore_question", || async move {
+            let uuid = Uuid::parse_str(question_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            let mut tx = begin(&self.db).await?;
+
+            // Answers deleted with the question share its timestamp; those deleted before stay deleted.
+            // Follows and tag proposals were only ever deleted with it.
+            sqlx::query!(
+                "UPDATE answers a SET deleted_at = NULL FROM questions q
+                 WHERE q.question_uuid = $1 AND a.question_uuid = q.question_uuid AND a.deleted_at = q.deleted_at",
+                uuid
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            sqlx::query!("UPDATE question_followers SET deleted_at = NULL WHERE question_uuid = $1", uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            sqlx::query!("UPDATE pending_tags SET deleted_at = NULL WHERE question_uuid = $1", uuid)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let record = sqlx::query!("UPDATE questions SET deleted_at = NULL WHERE question_uuid = $1 AND deleted_at IS NOT NULL RETURNING *", uuid)
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if record.is_some() {
+                commit(tx).await?;
+            }
+
+            record
+              .map(|record| {
+                Ok(QuestionDetail {
+                  question_uuid: record.question_uuid,
+                  title: record.title,
+                  description: record.description,
+                  status: parse_status(&record.status)?,
+                  status_reason: record.status_reason,
+                  kind: parse_kind(&record.kind)?,
+                  contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                  author_uuid: record.author_uuid,
+                  visibility: parse_visibility(&record.visibility)?,
+                  board_uuid: record.board_uuid,
+                  tags: record.tags,
+                  language: record.language,
+                  created_at: record.created_at.assume_utc(),
+                  updated_at: record.updated_at.assume_utc(),
+                  description_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  held_for_review: false,
+                  pending_tags: Vec::new(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn purge_deleted_questions(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_deleted_questions", || async move {
+            let result = sqlx::query!(
+                "DELETE FROM questions WHERE deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+                retention_days
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn get_question(&self, question_uuid: String, viewer: Viewer) -> Result<Option<QuestionDetail>, DBError> {
         let uuid = Uuid::parse_str(&question_uuid)
           .map_err(|err| {
             DBError::InvalidUUID(err.to_string())
           })?;
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
 
-        sqlx::query!("DELETE FROM questions WHERE question_uuid = $1", uuid)
-          .execute(&self.db)
-          .await
-          .map_err(|err: sqlx::Error| { DBError::Other(Box::new(err)) })?;
+        let db = &self.db;
+        let record = retrying("get_question", || async move {
+            sqlx::query!(
+                "SELECT q.* FROM questions q WHERE q.question_uuid = $1 AND q.deleted_at IS NULL
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2)",
+                uuid,
+                viewer_uuid,
+                signed_link
+              )
+              .fetch_optional(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
 
-        Ok(())
+        record
+          .map(|record| {
+            Ok(QuestionDetail {
+              question_uuid: record.question_uuid,
+              title: record.title,
+              description: record.description,
+              status: parse_status(&record.status)?,
+              status_reason: record.status_reason,
+              kind: parse_kind(&record.kind)?,
+              contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+              author_uuid: record.author_uuid,
+              visibility: parse_visibility(&record.visibility)?,
+              board_uuid: record.board_uuid,
+              tags: record.tags,
+              language: record.language,
+              created_at: record.created_at.assume_utc(),
+              updated_at: record.updated_at.assume_utc(),
+              description_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              held_for_review: false,
+              pending_tags: Vec::new(),
+            })
+          })
+          .transpose()
     }
 
-    async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
-        let records = sqlx::query!("SELECT * FROM questions")
-          .fetch_all(&self.db)
-          .await
-          .map_err(|err: sqlx::Error| { DBError::Other(Box::new(err)) })?;
+    async fn get_questions(&self, viewer: Viewer, language: Option<String>) -> Result<Vec<QuestionDetail>, DBError> {
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
+
+        let language = language.as_deref();
 
-        let questions = records
+        let records = reading("get_questions", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "SELECT q.* FROM questions q WHERE q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility <> 'unlisted'
+                 AND (q.visibility <> 'private' OR $2 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $1 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $1)
+                 AND ($3::TEXT IS NULL OR q.language = $3)",
+                viewer_uuid,
+                signed_link,
+                language
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        records
           .into_iter()
           .map(|record| {
-            QuestionDetail {
-              question_uuid: record.question_uuid.to_string(),
+            Ok(QuestionDetail {
+              question_uuid: record.question_uuid,
               title: record.title,
               description: record.description,
-              created_at: record.created_at.to_string(),
+              status: parse_status(&record.status)?,
+              status_reason: record.status_reason,
+              kind: parse_kind(&record.kind)?,
+              contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+              author_uuid: record.author_uuid,
+              visibility: parse_visibility(&record.visibility)?,
+              board_uuid: record.board_uuid,
+              tags: record.tags,
+              language: record.language,
+              created_at: record.created_at.assume_utc(),
+              updated_at: record.updated_at.assume_utc(),
+              description_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              held_for_review: false,
+              pending_tags: Vec::new(),
+            })
+          })
+          .collect()
+    }
+
+    async fn get_questions_by_uuid(&self, question_uuids: Vec<String>, viewer: Viewer) -> Result<Vec<QuestionDetail>, DBError> {
+        let uuids = question_uuids
+          .iter()
+          .map(|uuid| parse_uuid(uuid))
+          .collect::<Result<Vec<_>, _>>()?;
+        let uuids = uuids.as_slice();
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
+
+        let records = reading("get_questions_by_uuid", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "SELECT q.* FROM questions q WHERE q.question_uuid = ANY($1) AND q.deleted_at IS NULL
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2)
+                 ORDER BY array_position($1, q.question_uuid)",
+                uuids,
+                viewer_uuid,
+                signed_link
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        records
+          .into_iter()
+          .map(|record| {
+            Ok(QuestionDetail {
+              question_uuid: record.question_uuid,
+              title: record.title,
+              description: record.description,
+              status: parse_status(&record.status)?,
+              status_reason: record.status_reason,
+              kind: parse_kind(&record.kind)?,
+              contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+              author_uuid: record.author_uuid,
+              visibility: parse_visibility(&record.visibility)?,
+              board_uuid: record.board_uuid,
+              tags: record.tags,
+              language: record.language,
+              created_at: record.created_at.assume_utc(),
+              updated_at: record.updated_at.assume_utc(),
+              description_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              held_for_review: false,
+              pending_tags: Vec::new(),
+            })
+          })
+          .collect()
+    }
+
+    async fn get_questions_by_author(&self, author_uuid: String, page: Pagination, viewer: Viewer) -> Result<Vec<QuestionDetail>, DBError> {
+        let author_uuid = parse_uuid(&author_uuid)?;
+        let (viewer_uuid, signed_link) = viewer_params(&viewer);
+
+        let records = reading("get_questions_by_author", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "SELECT q.* FROM questions q WHERE q.author_uuid = $1 AND q.deleted_at IS NULL
+                 AND (q.held_at IS NULL OR q.author_uuid = $2)
+                 AND (q.visibility <> 'unlisted' OR q.author_uuid = $2)
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2)
+                 ORDER BY q.created_at DESC, q.question_uuid DESC
+                 OFFSET $4 LIMIT $5",
+                author_uuid,
+                viewer_uuid,
+                signed_link,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        records
+          .into_iter()
+          .map(|record| {
+            Ok(QuestionDetail {
+              question_uuid: record.question_uuid,
+              title: record.title,
+              description: record.description,
+              status: parse_status(&record.status)?,
+              status_reason: record.status_reason,
+              kind: parse_kind(&record.kind)?,
+              contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+              author_uuid: record.author_uuid,
+              visibility: parse_visibility(&record.visibility)?,
+              board_uuid: record.board_uuid,
+              tags: record.tags,
+              language: record.language,
+              created_at: record.created_at.assume_utc(),
+              updated_at: record.updated_at.assume_utc(),
+              description_html: None,
+              code_blocks: Vec::new(),
+              link_previews: Vec::new(),
+              held_for_review: false,
+              pending_tags: Vec::new(),
+            })
+          })
+          .collect()
+    }
+
+    async fn update_question_status(
+        &self,
+        question_uuid: String,
+        status: QuestionStatus,
+        reason: String,
+    ) -> Result<Option<QuestionDetail>, DBError> {
+        let (question_uuid, reason) = (&question_uuid, &reason);
+
+        retrying("update_question_status", || async move {
+            let uuid = Uuid::parse_str(question_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            let record = sqlx::query!(
+                "UPDATE questions SET status = $2, status_reason = $3, updated_at = CURRENT_TIMESTAMP WHERE question_uuid = $1 AND deleted_at IS NULL RETURNING *",
+                uuid,
+                status.as_str(),
+                reason
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(QuestionDetail {
+                  question_uuid: record.question_uuid,
+                  title: record.title,
+                  description: record.description,
+                  status: parse_status(&record.status)?,
+                  status_reason: record.status_reason,
+                  kind: parse_kind(&record.kind)?,
+                  contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                  author_uuid: record.author_uuid,
+                  visibility: parse_visibility(&record.visibility)?,
+                  board_uuid: record.board_uuid,
+                  tags: record.tags,
+                  language: record.language,
+                  created_at: record.created_at.assume_utc(),
+                  updated_at: record.updated_at.assume_utc(),
+                  description_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  held_for_review: false,
+                  pending_tags: Vec::new(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn update_question(
+        &self,
+        question_uuid: String,
+        question: Question,
+        editor_uuid: String,
+    ) -> Result<Option<QuestionDetail>, DBError> {
+        let (question_uuid, question, editor_uuid) = (&question_uuid, &question, &editor_uuid);
+
+        retrying("update_question", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let editor_uuid = parse_uuid(editor_uuid)?;
+            let board_uuid = question.board_uuid;
+
+            let mut tx = begin(&self.db).await?;
+
+            let current = sqlx::query!(
+                "SELECT question_uuid FROM questions WHERE question_uuid = $1 AND deleted_at IS NULL FOR UPDATE",
+                uuid
+              )
+              .fetch_optional(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if current.is_none() {
+                return Ok(None);
+            }
+
+            // The first edit also snapshots the original post so the history is complete.
+            sqlx::query!(
+                "INSERT INTO question_revisions (question_uuid, revision, title, description, editor_uuid, created_at)
+                 SELECT question_uuid, 1, title, description, author_uuid, created_at FROM questions
+                 WHERE question_uuid = $1 AND NOT EXISTS (SELECT 1 FROM question_revisions WHERE question_uuid = $1)",
+                uuid
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let record = sqlx::query!(
+                "UPDATE questions SET title = $2, description = $3, visibility = $4, board_uuid = $5, tags = $6, language = $7, updated_at = CURRENT_TIMESTAMP
+                 WHERE question_uuid = $1 RETURNING *",
+                uuid,
+                question.title,
+                question.description,
+                question.visibility.as_str(),
+                board_uuid,
+                &question.tags,
+                detect_language(&question.title, &question.description)
+              )
+              .fetch_one(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            sqlx::query!(
+                "INSERT INTO question_revisions (question_uuid, revision, title, description, editor_uuid)
+                 SELECT $1, MAX(revision) + 1, $2, $3, $4 FROM question_revisions WHERE question_uuid = $1",
+                uuid,
+                record.title,
+                record.description,
+                editor_uuid
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(QuestionDetail {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                description: record.description,
+                status: parse_status(&record.status)?,
+                status_reason: record.status_reason,
+                kind: parse_kind(&record.kind)?,
+                contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                author_uuid: record.author_uuid,
+                visibility: parse_visibility(&record.visibility)?,
+                board_uuid: record.board_uuid,
+                tags: record.tags,
+                language: record.language,
+                created_at: record.created_at.assume_utc(),
+                updated_at: record.updated_at.assume_utc(),
+                description_html: None,
+                code_blocks: Vec::new(),
+                link_previews: Vec::new(),
+                held_for_review: false,
+                pending_tags: Vec::new(),
+            }))
+          })
+          .await
+    }
+
+    async fn get_question_revisions(&self, question_uuid: String, viewer: Viewer) -> Result<Vec<QuestionRevision>, DBError> {
+        let (question_uuid, viewer) = (&question_uuid, &viewer);
+
+        retrying("get_question_revisions", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+            let (viewer_uuid, signed_link) = viewer_params(viewer);
+
+            let records = sqlx::query!(
+                "SELECT r.* FROM question_revisions r JOIN questions q ON q.question_uuid = r.question_uuid
+                 WHERE r.question_uuid = $1 AND q.deleted_at IS NULL
+                 AND (q.visibility <> 'private' OR $3 OR EXISTS (SELECT 1 FROM board_members m WHERE m.board_uuid = q.board_uuid AND m.user_uuid = $2 AND m.status = 'active'))
+                 AND post_visible_to(q.author_uuid, $2)
+                 ORDER BY r.revision",
+                uuid,
+                viewer_uuid,
+                signed_link
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let revisions = records
+              .into_iter()
+              .map(|record| {
+                QuestionRevision {
+                  question_uuid: record.question_uuid,
+                  revision: record.revision,
+                  title: record.title,
+                  description: record.description,
+                  editor_uuid: record.editor_uuid,
+                  created_at: record.created_at.assume_utc(),
+                }
+              })
+              .collect();
+
+            Ok(revisions)
+          })
+          .await
+    }
+
+    async fn accept_answer(&self, question_uuid: String, answer_uuid: Option<Uuid>) -> Result<bool, DBError> {
+        let uuid = parse_uuid(&question_uuid)?;
+
+        retrying("accept_answer", || async move {
+            let result = sqlx::query!(
+                "UPDATE questions q SET accepted_answer_uuid = $2, updated_at = CURRENT_TIMESTAMP
+                 WHERE q.question_uuid = $1 AND q.deleted_at IS NULL
+                 AND ($2::uuid IS NULL OR EXISTS (
+                   SELECT 1 FROM answers a WHERE a.answer_uuid = $2 AND a.question_uuid = q.question_uuid AND a.deleted_at IS NULL AND a.held_at IS NULL
+                 ))",
+                uuid,
+                answer_uuid
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn decide_contests(&self) -> Result<u64, DBError> {
+        retrying("decide_contests", || async move {
+            let result = sqlx::query!(
+                "UPDATE questions q SET contest_decided_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP, contest_winner_uuid = (
+                   SELECT a.answer_uuid FROM answers a
+                   LEFT JOIN answer_votes v ON v.answer_uuid = a.answer_uuid
+                     AND v.created_at >= q.contest_reveal_at AND v.created_at < q.contest_ends_at
+                   WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL AND a.held_at IS NULL
+                   AND post_visible_to(a.author_uuid, NULL)
+                   GROUP BY a.answer_uuid
+                   HAVING SUM(v.value) > 0
+                   ORDER BY SUM(v.value) DESC, MIN(a.created_at), a.answer_uuid
+                   LIMIT 1
+                 )
+                 WHERE q.contest_ends_at <= CURRENT_TIMESTAMP AND q.contest_decided_at IS NULL AND q.deleted_at IS NULL"
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn get_feed_entries(&self, tag: Option<String>, limit: i64) -> Result<Vec<FeedEntry>, DBError> {
+        let tag = tag.as_deref();
+
+        retrying("get_feed_entries", || async move {
+            let records = sqlx::query!(
+                "SELECT q.question_uuid, q.title, q.description, q.tags, u.username AS \"author?\",
+                   to_char(q.created_at, 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"') AS \"published!\"
+                 FROM questions q LEFT JOIN users u ON u.user_uuid = q.author_uuid
+                 WHERE q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility = 'public' AND post_visible_to(q.author_uuid, NULL)
+                 AND ($1::text IS NULL OR $1 = ANY(q.tags))
+                 ORDER BY q.created_at DESC, q.question_uuid DESC LIMIT $2",
+                tag,
+                limit
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| FeedEntry {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                summary: record.description,
+                author: record.author,
+                tags: record.tags,
+                published: record.published,
+              })
+              .collect())
+          })
+          .await
+    }
+
+    async fn count_sitemap_questions(&self) -> Result<i64, DBError> {
+        retrying("count_sitemap_questions", || async move {
+            let count = sqlx::query_scalar!(
+                "SELECT COUNT(*) AS \"count!\" FROM questions q
+                 WHERE q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility = 'public' AND post_visible_to(q.author_uuid, NULL)"
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(count)
+          })
+          .await
+    }
+
+    async fn stream_sitemap_urls(&self, page: i64, urls: mpsc::Sender<SitemapUrl>) -> Result<(), DBError> {
+        breaker()
+          .call(async {
+            let mut tx = begin_streaming(&self.db).await?;
+            let mut records = sqlx::query!(
+                "SELECT q.question_uuid, to_char(q.updated_at, 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"') AS \"last_modified!\"
+                 FROM questions q
+                 WHERE q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility = 'public' AND post_visible_to(q.author_uuid, NULL)
+                 ORDER BY q.question_uuid OFFSET $1 LIMIT $2",
+                page * SitemapUrl::PER_SITEMAP,
+                SitemapUrl::PER_SITEMAP
+              )
+              .fetch(&mut *tx);
+
+            while let Some(record) = records
+              .try_next()
+              .await
+              .map_err(DBError::from)?
+            {
+                let url = SitemapUrl {
+                  question_uuid: record.question_uuid,
+                  last_modified: record.last_modified,
+                };
+
+                if urls.send(url).await.is_err() {
+                    break;
+                }
             }
+
+            drop(records);
+            commit(tx).await
           })
-          .collect();
+          .await
+    }
+
+    async fn stream_questions(&self, range: ExportRange, questions: mpsc::Sender<QuestionDetail>) -> Result<(), DBError> {
+        breaker()
+          .call(async {
+            let mut tx = begin_streaming(&self.db).await?;
+            let mut records = sqlx::query!(
+                "SELECT q.*, q.held_at IS NOT NULL AS \"held!\" FROM questions q
+                 WHERE q.deleted_at IS NULL
+                 AND ($1::TIMESTAMP IS NULL OR q.created_at >= $1) AND ($2::TIMESTAMP IS NULL OR q.created_at < $2)
+                 ORDER BY q.created_at, q.question_uuid",
+                range.from,
+                range.to
+              )
+              .fetch(&mut *tx);
 
-        Ok(questions)
+            while let Some(record) = records
+              .try_next()
+              .await
+              .map_err(DBError::from)?
+            {
+                let question = QuestionDetail {
+                  question_uuid: record.question_uuid,
+                  title: record.title,
+                  description: record.description,
+                  status: parse_status(&record.status)?,
+                  status_reason: record.status_reason,
+                  kind: parse_kind(&record.kind)?,
+                  contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                  author_uuid: record.author_uuid,
+                  visibility: parse_visibility(&record.visibility)?,
+                  board_uuid: record.board_uuid,
+                  tags: record.tags,
+                  language: record.language,
+                  created_at: record.created_at.assume_utc(),
+                  updated_at: record.updated_at.assume_utc(),
+                  description_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  held_for_review: record.held,
+                  pending_tags: Vec::new(),
+                };
+
+                if questions.send(question).await.is_err() {
+                    break;
+                }
+            }
+
+            drop(records);
+            commit(tx).await
+          })
+          .await
     }
 }
@@ -0,0 +1,113 @@
+use std::future::Future;
+
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::models::DBError;
+
+use super::{
+    circuit_breaker::{CircuitBreaker, CircuitBreakerPolicy},
+    retry::retrying,
+    timing::timed,
+};
+
+/// A read-only copy of the database, from `DATABASE_REPLICA_URL`, for reads that may lag behind
+/// writes. It has a circuit breaker of its own, so that a replica that is down sends reads to the
+/// primary without failing them, or the operations meant for the primary.
+pub struct ReadReplica {
+    pool: PgPool,
+    breaker: CircuitBreaker,
+}
+
+impl ReadReplica {
+    pub fn new(pool: PgPool, policy: CircuitBreakerPolicy) -> Self {
+        ReadReplica {
+            pool,
+            breaker: CircuitBreaker::new(policy),
+        }
+    }
+}
+
+/// Runs the read `op` on `replica`, if any, falling back to `primary` when the replica cannot be
+/// reached or times out. Only reads on the primary are retried, as the fallback already is one.
+pub(crate) async fn reading<'a, T, F, Fut>(
+    name: &'static str,
+    primary: &'a PgPool,
+    replica: Option<&'a ReadReplica>,
+    mut op: F,
+) -> Result<T, DBError>
+where
+    F: FnMut(&'a PgPool) -> Fut,
+    Fut: Future<Output = Result<T, DBError>>,
+{
+    if let Some(replica) = replica {
+        match replica.breaker.call(timed(name, op(&replica.pool))).await {
+            Err(DBError::CircuitOpen) => {}
+            Err(err @ (DBError::ConnectionError | DBError::Timeout)) => {
+                warn!("Reading {} from the primary, as the replica failed: {}", name, err);
+            }
+            result => return result,
+        }
+    }
+
+    retrying(name, || op(primary)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::persistance::circuit_breaker::CircuitBreakerPolicy;
+
+    fn pool() -> PgPool {
+        PgPoolOptions::new().connect_lazy("postgres://localhost/test").unwrap()
+    }
+
+    #[tokio::test]
+    async fn reading_should_use_the_replica_when_there_is_one() {
+        let primary = &pool();
+        let replica = &ReadReplica::new(pool(), CircuitBreakerPolicy::default());
+
+        let result = reading("test", primary, Some(replica), |db| async move {
+            Ok(std::ptr::eq(db, &replica.pool))
+        })
+        .await;
+
+        assert!(result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reading_should_fall_back_to_the_primary_when_the_replica_is_down() {
+        let primary = &pool();
+        let replica = &ReadReplica::new(pool(), CircuitBreakerPolicy::default());
+
+        let result = reading("test", primary, Some(replica), |db| async move {
+            if std::ptr::eq(db, &replica.pool) {
+                Err(DBError::ConnectionError)
+            } else {
+                Ok(std::ptr::eq(db, primary))
+            }
+        })
+        .await;
+
+        assert!(result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reading_should_not_fall_back_on_other_errors() {
+        let primary = &pool();
+        let replica = &ReadReplica::new(pool(), CircuitBreakerPolicy::default());
+
+        let result: Result<(), _> = reading("test", primary, Some(replica), |db| async move {
+            if std::ptr::eq(db, &replica.pool) {
+                Err(DBError::NotFound)
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(matches!(result, Err(DBError::NotFound)));
+    }
+}
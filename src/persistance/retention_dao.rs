@@ -0,0 +1,140 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::models::{DBError, RetentionCategory, RetentionStats};
+
+use super::{begin, retry::retrying};
+
+#[async_trait]
+pub trait RetentionDao {
+    /// Permanently removes audit entries recorded more than `retention_days` ago.
+    async fn purge_audit_log(&self, retention_days: i32) -> Result<u64, DBError>;
+    /// Permanently removes sign-in IP addresses seen more than `retention_days` ago.
+    async fn purge_ip_addresses(&self, retention_days: i32) -> Result<u64, DBError>;
+    /// Permanently removes the question viewers counted `retention_days` or more days ago, along
+    /// with the salts their hashes were keyed with.
+    async fn purge_question_viewers(&self, retention_days: i32) -> Result<u64, DBError>;
+    async fn record_purge(&self, category: RetentionCategory, removed: u64) -> Result<(), DBError>;
+    /// Stats of the categories purged at least once. `retention_days` is left to the caller, which
+    /// knows the configured policy.
+    async fn get_retention_stats(&self) -> Result<Vec<RetentionStats>, DBError>;
+}
+
+pub struct RetentionDaoImpl {
+    db: PgPool,
+}
+
+impl RetentionDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      RetentionDaoImpl {
+        db
+      }
+    }
+}
+
+#[async_trait]
+impl RetentionDao for RetentionDaoImpl {
+    async fn purge_audit_log(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_audit_log", || async move {
+            let mut tx = begin(&self.db).await?;
+
+            // Lifts the append-only trigger for this transaction only.
+            sqlx::query!("SET LOCAL app.audit_log_purge = 'on'")
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let result = sqlx::query!(
+                "DELETE FROM audit_log WHERE created_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+                retention_days
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit().await.map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn purge_ip_addresses(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_ip_addresses", || async move {
+            let result = sqlx::query!(
+                "DELETE FROM user_ip_history WHERE seen_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+                retention_days
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn purge_question_viewers(&self, retention_days: i32) -> Result<u64, DBError> {
+        retrying("purge_question_viewers", || async move {
+            let mut tx = begin(&self.db).await?;
+
+            sqlx::query!("DELETE FROM viewer_salts WHERE day <= CURRENT_DATE - $1::INT", retention_days)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            let result = sqlx::query!("DELETE FROM question_viewers WHERE viewed_on <= CURRENT_DATE - $1::INT", retention_days)
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit().await.map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn record_purge(&self, category: RetentionCategory, removed: u64) -> Result<(), DBError> {
+        retrying("record_purge", || async move {
+            let removed = i64::try_from(removed).map_err(|err| DBError::Other(Box::new(err)))?;
+
+            sqlx::query!(
+                "INSERT INTO retention_purges (category, last_run_at, last_removed, total_removed) VALUES ($1, CURRENT_TIMESTAMP, $2, $2)
+                 ON CONFLICT (category) DO UPDATE SET last_run_at = EXCLUDED.last_run_at, last_removed = EXCLUDED.last_removed,
+                   total_removed = retention_purges.total_removed + EXCLUDED.last_removed",
+                category.as_str(),
+                removed
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_retention_stats(&self) -> Result<Vec<RetentionStats>, DBError> {
+        retrying("get_retention_stats", || async move {
+            let records = sqlx::query!("SELECT * FROM retention_purges ORDER BY category")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(RetentionStats {
+                  category: record.category.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  retention_days: None,
+                  last_run_at: Some(record.last_run_at.assume_utc()),
+                  last_removed: record.last_removed,
+                  total_removed: record.total_removed,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+}
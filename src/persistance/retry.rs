@@ -0,0 +1,193 @@
+use std::{future::Future, sync::OnceLock, time::Duration};
+
+use tracing::warn;
+
+use crate::models::DBError;
+
+use super::{
+    circuit_breaker::{breaker, CircuitBreaker},
+    timing::timed,
+};
+
+static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// How DAO operations are retried when they fail with a transient error: a serialization failure
+/// or deadlock, or a connection that failed, was reset, or could not be had from the pool in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Tries in total, the first included; 1 never retries.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Longest wait before retry `retry`, counting from 1: `base_delay`, doubled for every retry
+    /// before it, up to `max_delay`.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.base_delay
+          .saturating_mul(1 << retry.saturating_sub(1).min(16))
+          .min(self.max_delay)
+    }
+
+    /// A random wait up to the backoff, so that requests failing together do not retry together.
+    fn delay(&self, retry: u32) -> Duration {
+        self.backoff(retry).mul_f64(rand::random::<f64>())
+    }
+}
+
+/// Sets the policy of `retrying` for the rest of the process. Only the first call has an effect;
+/// without one, the default policy applies.
+pub fn configure(policy: RetryPolicy) {
+    if POLICY.set(policy).is_err() {
+        warn!("The database retry policy is already configured.");
+    }
+}
+
+/// Runs `op` until it succeeds, fails with an error that is not transient, or has been tried as
+/// often as the configured policy allows, and returns its last result. Every try goes through the
+/// circuit breaker, so none is made while the database is known to be down, and is `timed`.
+///
+/// `op` must be safe to run again after a failure: a read, or statements in a transaction of their
+/// own, which a failure rolls back. `name` identifies the operation in the logs.
+pub(crate) async fn retrying<T, F, Fut>(name: &'static str, op: F) -> Result<T, DBError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DBError>>,
+{
+    retrying_with(POLICY.get().copied().unwrap_or_default(), breaker(), name, op).await
+}
+
+async fn retrying_with<T, F, Fut>(
+    policy: RetryPolicy,
+    breaker: &CircuitBreaker,
+    name: &'static str,
+    mut op: F,
+) -> Result<T, DBError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DBError>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        match breaker.call(timed(name, op())).await {
+            Err(err) if err.is_transient() && attempt < policy.max_attempts => {
+                let delay = policy.delay(attempt);
+
+                warn!("Retrying {} in {:?} after attempt {} failed: {}", name, delay, attempt, err);
+                tokio::time::sleep(delay).await;
+
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::persistance::circuit_breaker::CircuitBreakerPolicy;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPolicy::default())
+    }
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    #[test]
+    fn backoff_should_double_up_to_the_max_delay() {
+        let backoffs: Vec<_> = (1..=5).map(|retry| policy(5).backoff(retry).as_millis()).collect();
+
+        assert_eq!(backoffs, vec![1, 2, 4, 4, 4]);
+    }
+
+    #[test]
+    fn delay_should_not_exceed_the_backoff() {
+        for retry in 1..=5 {
+            assert!(policy(5).delay(retry) <= policy(5).backoff(retry));
+        }
+    }
+
+    #[tokio::test]
+    async fn retrying_should_retry_transient_errors_until_success() {
+        let attempts = &AtomicU32::new(0);
+
+        let result = retrying_with(policy(3), &breaker(), "test", || async move {
+            match attempts.fetch_add(1, Ordering::Relaxed) {
+                0 => Err(DBError::ConnectionError),
+                1 => Err(DBError::SerializationFailure),
+                _ => Ok(42),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn retrying_should_give_up_after_max_attempts() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<(), _> = retrying_with(policy(3), &breaker(), "test", || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(DBError::ConnectionError)
+        })
+        .await;
+
+        assert!(matches!(result, Err(DBError::ConnectionError)));
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn retrying_should_stop_once_the_breaker_trips() {
+        let attempts = &AtomicU32::new(0);
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            open_for: Duration::from_secs(10),
+        });
+
+        let result: Result<(), _> = retrying_with(policy(3), &breaker, "test", || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(DBError::ConnectionError)
+        })
+        .await;
+
+        assert!(matches!(result, Err(DBError::CircuitOpen)));
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn retrying_should_not_retry_other_errors() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<(), _> = retrying_with(policy(3), &breaker(), "test", || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(DBError::UniqueViolation("test".to_owned()))
+        })
+        .await;
+
+        assert!(matches!(result, Err(DBError::UniqueViolation(_))));
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+}
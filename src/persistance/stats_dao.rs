@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{ActivityDay, AdminStats, DBError, QuestionStats, QuestionView, QuestionViewer};
+
+use super::{
+    circuit_breaker::guarded,
+    retry::retrying,
+    replica::{reading, ReadReplica},
+};
+
+#[async_trait]
+pub trait StatsDao {
+    /// Totals of the forum, and its activity on each of the last `days` days.
+    async fn get_admin_stats(&self, days: i32) -> Result<AdminStats, DBError>;
+    /// Counts the views, and a unique viewer the first time a viewer is seen on a question that
+    /// day. Views of questions that no longer exist are left out.
+    async fn record_question_views(&self, views: Vec<QuestionView>) -> Result<(), DBError>;
+    /// Returns `None` when the question has no counters, as when it does not exist.
+    async fn get_question_stats(&self, question_uuid: String) -> Result<Option<QuestionStats>, DBError>;
+}
+
+pub struct StatsDaoImpl {
+    db: PgPool,
+    replica: Option<Arc<ReadReplica>>,
+}
+
+impl StatsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      StatsDaoImpl {
+        db,
+        replica: None,
+      }
+    }
+
+    /// Sends the aggregate queries to `replica`, which may lag behind the primary. The counters of
+    /// single questions stay on the primary.
+    pub fn with_replica(self, replica: Arc<ReadReplica>) -> Self {
+      StatsDaoImpl {
+        replica: Some(replica),
+        ..self
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// `viewer` as stored in `question_viewers`: keyed with the salt of the day, so the same all day
+/// long, and unrelated to the viewer once the salt is purged with the day's viewers.
+pub(super) fn viewer_hash(salt: &[u8], viewer: &QuestionViewer) -> String {
+    let mut mac = HmacSha256::new_from_slice(salt).expect("HMAC accepts keys of any length");
+    mac.update(viewer.to_string().as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn answer_rate(questions: i64, answered: i64) -> Option<f64> {
+    (questions > 0).then(|| answered as f64 / questions as f64)
+}
+
+#[async_trait]
+impl StatsDao for StatsDaoImpl {
+    async fn get_admin_stats(&self, days: i32) -> Result<AdminStats, DBError> {
+        let totals = reading("get_admin_stats", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "WITH first_answers AS (
+                   SELECT MIN(a.created_at) - q.created_at AS wait
+                   FROM questions q
+                   LEFT JOIN answers a ON a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+                   WHERE q.deleted_at IS NULL
+                   GROUP BY q.question_uuid
+                 )
+                 SELECT COUNT(*) AS \"questions!\", COUNT(wait) AS \"answered!\",
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM wait)::FLOAT8) AS median_seconds,
+                   (SELECT COUNT(*) FROM answers WHERE deleted_at IS NULL) AS \"answers!\",
+                   (SELECT COUNT(*) FROM users) AS \"users!\"
+                 FROM first_answers"
+              )
+              .fetch_one(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        let per_day = reading("get_admin_stats_per_day", &self.db, self.replica.as_deref(), |db| async move {
+            sqlx::query!(
+                "WITH days AS (
+                   SELECT d::DATE AS day FROM generate_series(CURRENT_DATE - ($1::INT - 1), CURRENT_DATE, interval '1 day') d
+                 ), first_answers AS (
+                   SELECT q.created_at::DATE AS day, MIN(a.created_at) - q.created_at AS wait
+                   FROM questions q
+                   LEFT JOIN answers a ON a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+                   WHERE q.deleted_at IS NULL AND q.created_at >= CURRENT_DATE - ($1::INT - 1)
+                   GROUP BY q.question_uuid
+                 )
+                 SELECT days.day AS \"day!\", COUNT(f.day) AS \"questions!\", COUNT(f.wait) AS \"answered!\",
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.wait)::FLOAT8) AS median_seconds,
+                   (SELECT COUNT(*) FROM answers a
+                    WHERE a.deleted_at IS NULL AND a.created_at >= days.day AND a.created_at < days.day + 1) AS \"answers!\",
+                   (SELECT COUNT(*) FROM users u WHERE u.created_at >= days.day AND u.created_at < days.day + 1) AS \"users!\"
+                 FROM days LEFT JOIN first_answers f ON f.day = days.day
+                 GROUP BY days.day ORDER BY days.day",
+                days
+              )
+              .fetch_all(db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await?;
+
+        Ok(AdminStats {
+          questions: totals.questions,
+          answers: totals.answers,
+          users: totals.users,
+          answer_rate: answer_rate(totals.questions, totals.answered),
+          median_seconds_to_first_answer: totals.median_seconds,
+          per_day: per_day
+            .into_iter()
+            .map(|record| ActivityDay {
+              day: record.day.to_string(),
+              questions: record.questions,
+              answers: record.answers,
+              users: record.users,
+              answer_rate: answer_rate(record.questions, record.answered),
+              median_seconds_to_first_answer: record.median_seconds,
+            })
+            .collect(),
+        })
+    }
+
+    async fn record_question_views(&self, views: Vec<QuestionView>) -> Result<(), DBError> {
+        guarded("record_question_views", async {
+            if views.is_empty() {
+              return Ok(());
+            }
+
+            // The first views of a day pick its salt; server instances then all use the stored one.
+            let day = sqlx::query!(
+                "INSERT INTO viewer_salts (day, salt) VALUES (CURRENT_DATE, $1)
+                 ON CONFLICT (day) DO UPDATE SET day = EXCLUDED.day RETURNING day, salt",
+                &rand::random::<[u8; 32]>()[..]
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let (question_uuids, viewer_hashes): (Vec<Uuid>, Vec<String>) = views
+              .iter()
+              .map(|view| (view.question_uuid, viewer_hash(&day.salt, &view.viewer)))
+              .unzip();
+
+            sqlx::query!(
+                "WITH views AS (
+                   SELECT v.question_uuid, v.viewer_hash FROM UNNEST($1::uuid[], $2::TEXT[]) AS v (question_uuid, viewer_hash)
+                   JOIN questions q ON q.question_uuid = v.question_uuid
+                 ), new_viewers AS (
+                   INSERT INTO question_viewers (question_uuid, viewed_on, viewer_hash)
+                   SELECT DISTINCT question_uuid, $3::DATE, viewer_hash FROM views
+                   ON CONFLICT DO NOTHING RETURNING question_uuid
+                 )
+                 INSERT INTO question_stats (question_uuid, views, unique_viewers)
+                 SELECT v.question_uuid, COUNT(*), (SELECT COUNT(*) FROM new_viewers n WHERE n.question_uuid = v.question_uuid)
+                 FROM views v GROUP BY v.question_uuid
+                 ON CONFLICT (question_uuid) DO UPDATE
+                 SET views = question_stats.views + EXCLUDED.views, unique_viewers = question_stats.unique_viewers + EXCLUDED.unique_viewers",
+                &question_uuids,
+                &viewer_hashes,
+                day.day
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_question_stats(&self, question_uuid: String) -> Result<Option<QuestionStats>, DBError> {
+        let question_uuid = &question_uuid;
+
+        retrying("get_question_stats", || async move {
+            let uuid = parse_uuid(question_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM question_stats WHERE question_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| QuestionStats {
+              question_uuid: record.question_uuid,
+              views: record.views,
+              unique_viewers: record.unique_viewers,
+              answers: record.answers,
+              last_activity_at: record.last_activity_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+}
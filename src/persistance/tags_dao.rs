@@ -0,0 +1,404 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{
+    BoardTagRules, BoardTagRulesDetail, DBError, KbExport, Pagination, PendingTag, PendingTagResolution, Question,
+    TagAcceptedAnswer, TagAnswerer, TagStats, TagUsage, TagWeek,
+};
+
+use super::{begin, commit, retry::retrying, Transaction};
+
+#[async_trait]
+pub trait TagsDao {
+    /// Tags starting with `prefix` on public questions, most used first. `prefix` is matched
+    /// literally, so it must not contain `LIKE` wildcards.
+    async fn get_tag_suggestions(&self, prefix: String, limit: i64) -> Result<Vec<TagUsage>, DBError>;
+    /// Usage of `name` on public questions, or `None` if no public question has it.
+    async fn get_tag_stats(&self, name: String) -> Result<Option<TagStats>, DBError>;
+    async fn get_board_tag_rules(&self, board_uuid: String) -> Result<Option<BoardTagRulesDetail>, DBError>;
+    /// Creates or replaces the board's rules. Returns `None` when the board does not exist.
+    async fn set_board_tag_rules(
+        &self,
+        board_uuid: String,
+        rules: BoardTagRules,
+        updated_by: String,
+    ) -> Result<Option<BoardTagRulesDetail>, DBError>;
+    /// Returns whether the board had rules.
+    async fn delete_board_tag_rules(&self, board_uuid: String) -> Result<bool, DBError>;
+    /// The given tags that no question uses yet, in the given order.
+    async fn get_unused_tags(&self, tags: Vec<String>) -> Result<Vec<String>, DBError>;
+    /// Proposes `tags` for the question; proposals it already has are kept as they are.
+    async fn add_pending_tags(&self, question_uuid: String, tags: Vec<String>, requested_by: Option<String>) -> Result<(), DBError>;
+    /// Lists proposed tags, oldest proposal first.
+    async fn get_pending_tags(&self, page: Pagination) -> Result<Vec<PendingTag>, DBError>;
+    /// Adds the tag to the questions that proposed it and still have room for it, in one transaction.
+    /// Returns `None` when the tag was not proposed.
+    async fn approve_pending_tag(&self, name: String) -> Result<Option<PendingTagResolution>, DBError>;
+    /// Drops every proposal of the tag. Returns `None` when the tag was not proposed.
+    async fn reject_pending_tag(&self, name: String) -> Result<Option<PendingTagResolution>, DBError>;
+    /// The tag's public questions with an accepted answer anyone may read, by title, at most
+    /// `KbExport::MAX_SECTIONS`.
+    async fn get_accepted_answers(&self, name: String) -> Result<Vec<TagAcceptedAnswer>, DBError>;
+}
+
+pub struct TagsDaoImpl {
+    db: PgPool,
+}
+
+impl TagsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      TagsDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+#[async_trait]
+impl TagsDao for TagsDaoImpl {
+    async fn get_tag_suggestions(&self, prefix: String, limit: i64) -> Result<Vec<TagUsage>, DBError> {
+        let prefix = &prefix;
+
+        retrying("get_tag_suggestions", || async move {
+            let records = sqlx::query!(
+                "SELECT tag AS \"name!\", COUNT(*) AS \"questions!\" FROM questions q, unnest(q.tags) tag
+                 WHERE q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility = 'public' AND post_visible_to(q.author_uuid, NULL) AND tag LIKE $1 || '%'
+                 GROUP BY tag ORDER BY COUNT(*) DESC, tag LIMIT $2",
+                prefix,
+                limit
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| TagUsage {
+                name: record.name,
+                questions: record.questions,
+              })
+              .collect())
+          })
+          .await
+    }
+    async fn get_tag_stats(&self, name: String) -> Result<Option<TagStats>, DBError> {
+        let name = &name;
+
+        retrying("get_tag_stats", || async move {
+            let totals = sqlx::query!(
+                "SELECT COUNT(*) AS \"questions!\",
+                   COUNT(*) FILTER (WHERE NOT EXISTS (
+                     SELECT 1 FROM answers a WHERE a.question_uuid = q.question_uuid AND a.deleted_at IS NULL
+                   )) AS \"unanswered!\"
+                 FROM questions q WHERE q.deleted_at IS NULL AND q.visibility = 'public' AND q.tags @> ARRAY[$1::TEXT]",
+                name
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            if totals.questions == 0 {
+                return Ok(None);
+            }
+
+            let weeks = sqlx::query!(
+                "SELECT w.week::DATE AS \"week!\", COUNT(q.question_uuid) AS \"questions!\"
+                 FROM generate_series(
+                   date_trunc('week', LOCALTIMESTAMP) - make_interval(weeks => $2 - 1), date_trunc('week', LOCALTIMESTAMP), interval '1 week'
+                 ) w(week)
+                 LEFT JOIN questions q ON date_trunc('week', q.created_at) = w.week
+                   AND q.deleted_at IS NULL AND q.visibility = 'public' AND q.tags @> ARRAY[$1::TEXT]
+                 GROUP BY w.week ORDER BY w.week",
+                name,
+                TagStats::WEEKS
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let answerers = sqlx::query!(
+                "SELECT u.user_uuid, u.username, COUNT(*) AS \"answers!\"
+                 FROM answers a
+                 JOIN questions q ON q.question_uuid = a.question_uuid
+                 JOIN users u ON u.user_uuid = a.author_uuid
+                 WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL AND q.visibility = 'public' AND q.tags @> ARRAY[$1::TEXT]
+                 GROUP BY u.user_uuid, u.username ORDER BY COUNT(*) DESC, u.username LIMIT $2",
+                name,
+                TagStats::MAX_TOP_ANSWERERS
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(TagStats {
+              name: name.clone(),
+              questions: totals.questions,
+              unanswered_rate: totals.unanswered as f64 / totals.questions as f64,
+              questions_per_week: weeks
+                .into_iter()
+                .map(|record| TagWeek {
+                  week: record.week.to_string(),
+                  questions: record.questions,
+                })
+                .collect(),
+              top_answerers: answerers
+                .into_iter()
+                .map(|record| TagAnswerer {
+                  user_uuid: record.user_uuid,
+                  username: record.username,
+                  answers: record.answers,
+                })
+                .collect(),
+            }))
+          })
+          .await
+    }
+    async fn get_board_tag_rules(&self, board_uuid: String) -> Result<Option<BoardTagRulesDetail>, DBError> {
+        let board_uuid = &board_uuid;
+
+        retrying("get_board_tag_rules", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let record = sqlx::query!("SELECT * FROM board_tag_rules WHERE board_uuid = $1", uuid)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| BoardTagRulesDetail {
+                board_uuid: record.board_uuid,
+                required_tags: record.required_tags,
+                forbidden_tags: record.forbidden_tags,
+                updated_by: record.updated_by,
+                updated_at: record.updated_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+
+    async fn set_board_tag_rules(
+        &self,
+        board_uuid: String,
+        rules: BoardTagRules,
+        updated_by: String,
+    ) -> Result<Option<BoardTagRulesDetail>, DBError> {
+        let (board_uuid, rules, updated_by) = (&board_uuid, &rules, &updated_by);
+
+        retrying("set_board_tag_rules", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+            let updated_by = parse_uuid(updated_by)?;
+
+            let record = sqlx::query!(
+                "INSERT INTO board_tag_rules (board_uuid, required_tags, forbidden_tags, updated_by)
+                 SELECT board_uuid, $2, $3, $4 FROM boards WHERE board_uuid = $1
+                 ON CONFLICT (board_uuid) DO UPDATE
+                 SET required_tags = EXCLUDED.required_tags, forbidden_tags = EXCLUDED.forbidden_tags,
+                   updated_by = EXCLUDED.updated_by, updated_at = CURRENT_TIMESTAMP
+                 RETURNING *",
+                uuid,
+                &rules.required_tags,
+                &rules.forbidden_tags,
+                updated_by
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| BoardTagRulesDetail {
+                board_uuid: record.board_uuid,
+                required_tags: record.required_tags,
+                forbidden_tags: record.forbidden_tags,
+                updated_by: record.updated_by,
+                updated_at: record.updated_at.assume_utc(),
+            }))
+          })
+          .await
+    }
+
+    async fn delete_board_tag_rules(&self, board_uuid: String) -> Result<bool, DBError> {
+        let board_uuid = &board_uuid;
+
+        retrying("delete_board_tag_rules", || async move {
+            let uuid = parse_uuid(board_uuid)?;
+
+            let result = sqlx::query!("DELETE FROM board_tag_rules WHERE board_uuid = $1", uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+    async fn get_unused_tags(&self, tags: Vec<String>) -> Result<Vec<String>, DBError> {
+        let tags = &tags;
+
+        retrying("get_unused_tags", || async move {
+            let records = sqlx::query!(
+                "SELECT tag AS \"tag!\" FROM unnest($1::TEXT[]) WITH ORDINALITY t(tag, position)
+                 WHERE NOT EXISTS (SELECT 1 FROM questions q WHERE q.deleted_at IS NULL AND q.tags @> ARRAY[t.tag])
+                 ORDER BY position",
+                &tags
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records.into_iter().map(|record| record.tag).collect())
+          })
+          .await
+    }
+
+    async fn add_pending_tags(&self, question_uuid: String, tags: Vec<String>, requested_by: Option<String>) -> Result<(), DBError> {
+        let (question_uuid, tags, requested_by) = (&question_uuid, &tags, requested_by.as_deref());
+
+        retrying("add_pending_tags", || async move {
+            let question_uuid = parse_uuid(question_uuid)?;
+            let requested_by = requested_by.map(parse_uuid).transpose()?;
+
+            let mut tx = begin(&self.db).await?;
+
+            add_pending_tags_in(&mut tx, question_uuid, tags, requested_by).await?;
+
+            commit(tx).await
+          })
+          .await
+    }
+
+    async fn get_pending_tags(&self, page: Pagination) -> Result<Vec<PendingTag>, DBError> {
+        retrying("get_pending_tags", || async move {
+            let records = sqlx::query!(
+                "SELECT name, array_agg(question_uuid ORDER BY created_at, question_uuid) AS \"question_uuids!\",
+                   MIN(created_at) AS \"requested_at!\"
+                 FROM pending_tags WHERE deleted_at IS NULL GROUP BY name ORDER BY MIN(created_at), name OFFSET $1 LIMIT $2",
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| PendingTag {
+                name: record.name,
+                question_uuids: record.question_uuids,
+                requested_at: record.requested_at.assume_utc(),
+              })
+              .collect())
+          })
+          .await
+    }
+
+    async fn approve_pending_tag(&self, name: String) -> Result<Option<PendingTagResolution>, DBError> {
+        let name = &name;
+
+        retrying("approve_pending_tag", || async move {
+            let mut tx = begin(&self.db).await?;
+
+            // Requests for deleted questions wait for them to be restored.
+            let requests = sqlx::query_scalar!("DELETE FROM pending_tags WHERE name = $1 AND deleted_at IS NULL RETURNING question_uuid", name)
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            if requests.is_empty() {
+              return Ok(None);
+            }
+
+            let tagged = sqlx::query!(
+                "UPDATE questions SET tags = array_append(tags, $1), updated_at = CURRENT_TIMESTAMP
+                 WHERE question_uuid = ANY($2) AND deleted_at IS NULL AND NOT tags @> ARRAY[$1::TEXT] AND cardinality(tags) < $3",
+                name,
+                &requests,
+                Question::MAX_TAGS as i32
+              )
+              .execute(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(Some(PendingTagResolution {
+              name: name.clone(),
+              questions: tagged.rows_affected(),
+            }))
+          })
+          .await
+    }
+
+    async fn reject_pending_tag(&self, name: String) -> Result<Option<PendingTagResolution>, DBError> {
+        let name = &name;
+
+        retrying("reject_pending_tag", || async move {
+            let result = sqlx::query!("DELETE FROM pending_tags WHERE name = $1", name)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok((result.rows_affected() > 0).then(|| PendingTagResolution {
+              name: name.clone(),
+              questions: result.rows_affected(),
+            }))
+          })
+          .await
+    }
+
+    async fn get_accepted_answers(&self, name: String) -> Result<Vec<TagAcceptedAnswer>, DBError> {
+        let name = &name;
+
+        retrying("get_accepted_answers", || async move {
+            let records = sqlx::query!(
+                "SELECT q.question_uuid, q.title, a.answer_uuid, a.content FROM questions q
+                 JOIN answers a ON a.answer_uuid = q.accepted_answer_uuid
+                 WHERE q.tags @> ARRAY[$1::TEXT] AND q.visibility = 'public'
+                 AND q.deleted_at IS NULL AND a.deleted_at IS NULL AND q.held_at IS NULL AND a.held_at IS NULL
+                 AND post_visible_to(q.author_uuid, NULL) AND post_visible_to(a.author_uuid, NULL)
+                 ORDER BY q.title, q.question_uuid
+                 LIMIT $2",
+                name,
+                KbExport::MAX_SECTIONS
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(records
+              .into_iter()
+              .map(|record| TagAcceptedAnswer {
+                question_uuid: record.question_uuid,
+                title: record.title,
+                answer_uuid: record.answer_uuid,
+                answer: record.content,
+              })
+              .collect())
+          })
+          .await
+    }
+}
+
+/// Proposes `tags` for the question within `tx`; tags it already proposed are skipped.
+pub(crate) async fn add_pending_tags_in(
+    tx: &mut Transaction,
+    question_uuid: Uuid,
+    tags: &[String],
+    requested_by: Option<Uuid>,
+) -> Result<(), DBError> {
+    sqlx::query!(
+        "INSERT INTO pending_tags (name, question_uuid, requested_by)
+         SELECT tag, $1, $3 FROM unnest($2::TEXT[]) tag
+         ON CONFLICT (name, question_uuid) DO NOTHING",
+        question_uuid,
+        tags,
+        requested_by
+      )
+      .execute(&mut **tx)
+      .await
+      .map_err(DBError::from)?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::DBError;
+
+use super::retry::retrying;
+
+/// Tenants are added by operators, as rows of `tenants` with the host each is served at.
+#[async_trait]
+pub trait TenantsDao {
+    async fn get_tenants(&self) -> Result<Vec<Uuid>, DBError>;
+    /// The tenant served at `host`, which is matched in lowercase and without a port.
+    async fn get_tenant_by_host(&self, host: String) -> Result<Option<Uuid>, DBError>;
+}
+
+pub struct TenantsDaoImpl {
+    db: PgPool,
+}
+
+impl TenantsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      TenantsDaoImpl {
+        db
+      }
+    }
+}
+
+#[async_trait]
+impl TenantsDao for TenantsDaoImpl {
+    async fn get_tenants(&self) -> Result<Vec<Uuid>, DBError> {
+        retrying("get_tenants", || async move {
+            sqlx::query_scalar!("SELECT tenant_id FROM tenants ORDER BY created_at, tenant_id")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await
+    }
+
+    async fn get_tenant_by_host(&self, host: String) -> Result<Option<Uuid>, DBError> {
+        let host = &host;
+
+        retrying("get_tenant_by_host", || async move {
+            sqlx::query_scalar!("SELECT tenant_id FROM tenants WHERE lower(host) = $1", host)
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await
+    }
+}
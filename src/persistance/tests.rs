@@ -1,8 +1,8 @@
 mod answers_tests {
-  use sqlx::PgPool;
+  use sqlx::{types::Uuid, PgPool};
 
   use crate::{
-      models::{Answer, DBError, Question},
+      models::{Answer, AnswerSignals, AnswerSort, DBError, ExportRange, Question, ReputationBand, Viewer},
       persistance::{
           answers_dao::{AnswersDao, AnswersDaoImpl},
           questions_dao::{QuestionsDao, QuestionsDaoImpl},
@@ -15,9 +15,9 @@ mod answers_tests {
 
       let result = answer_doa
           .create_answer(Answer {
-              question_uuid: "malformed".to_owned(),
+              question_uuid: Uuid::nil(),
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await;
 
       if result.is_ok() {
@@ -27,11 +27,11 @@ mod answers_tests {
           ));
       }
 
-      if let Err(DBError::InvalidUUID(_)) = result {
+      if let Err(DBError::ForeignKeyViolation(_)) = result {
           Ok(())
       } else {
           Err(format!(
-              "Expected an invalid UUID error but got the following error: {:?}",
+              "Expected a foreign key violation but got the following error: {:?}",
               result.err()
           ))
       }
@@ -43,9 +43,9 @@ mod answers_tests {
 
       let result = answer_doa
           .create_answer(Answer {
-              question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+              question_uuid: Uuid::parse_str("a22abcd2-22ab-2222-a22b-2abc2a2b22cc").unwrap(),
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await;
 
       if result.is_ok() {
@@ -55,11 +55,11 @@ mod answers_tests {
           ));
       }
 
-      if let Err(DBError::InvalidUUID(_)) = result {
+      if let Err(DBError::ForeignKeyViolation(_)) = result {
           Ok(())
       } else {
           Err(format!(
-              "Expected an invalid UUID error but got the following error: {:?}",
+              "Expected a foreign key violation but got the following error: {:?}",
               result.err()
           ))
       }
@@ -75,9 +75,9 @@ mod answers_tests {
 
       let result = answer_doa
           .create_answer(Answer {
-              question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+              question_uuid: Uuid::parse_str("a22abcd2-22ab-2222-a22b-2abc2a2b22cc").unwrap(),
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await;
 
       if result.is_ok() {
@@ -87,11 +87,11 @@ mod answers_tests {
           ));
       }
 
-      if let Err(DBError::Other(_)) = result {
+      if let Err(DBError::ConnectionError) = result {
           Ok(())
       } else {
           Err(format!(
-              "Expected an Other error but got the following error: {:?}",
+              "Expected a connection error but got the following error: {:?}",
               result.err()
           ))
       }
@@ -106,7 +106,8 @@ mod answers_tests {
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
@@ -114,11 +115,11 @@ mod answers_tests {
           .create_answer(Answer {
               question_uuid: result.question_uuid,
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      if result.content != "test content".to_owned() {
+      if result.content != "test content" {
           return Err("Incorrect answer content".to_owned());
       }
 
@@ -167,11 +168,11 @@ mod answers_tests {
           ));
       }
 
-      if let Err(DBError::Other(_)) = result {
+      if let Err(DBError::ConnectionError) = result {
           Ok(())
       } else {
           Err(format!(
-              "Expected an Other error but got the following error: {:?}",
+              "Expected a connection error but got the following error: {:?}",
               result.err()
           ))
       }
@@ -186,29 +187,30 @@ mod answers_tests {
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
       let result = answer_doa
           .create_answer(Answer {
-              question_uuid: question.question_uuid.clone(),
+              question_uuid: question.question_uuid,
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
       answer_doa
-          .delete_answer(result.answer_uuid)
+          .delete_answer(result.answer_uuid.to_string())
           .await
           .map_err(|e| format!("{:?}", e))?;
 
       let results = answer_doa
-          .get_answers(question.question_uuid.clone())
+          .get_answers(question.question_uuid.to_string(), AnswerSort::Oldest, Viewer::Anonymous)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      if results.len() != 0 {
+      if !results.is_empty() {
           return Err("Answer was not deleted".to_owned());
       }
 
@@ -219,7 +221,7 @@ mod answers_tests {
   async fn get_answers_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
       let answer_doa = AnswersDaoImpl::new(pool);
 
-      let result = answer_doa.get_answers("malformed".to_owned()).await;
+      let result = answer_doa.get_answers("malformed".to_owned(), AnswerSort::Oldest, Viewer::Anonymous).await;
 
       if result.is_ok() {
           return Err(format!(
@@ -245,7 +247,7 @@ mod answers_tests {
       pool.close().await;
 
       let result = answer_doa
-          .get_answers("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+          .get_answers("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(), AnswerSort::Oldest, Viewer::Anonymous)
           .await;
 
       if result.is_ok() {
@@ -255,11 +257,11 @@ mod answers_tests {
           ));
       }
 
-      if let Err(DBError::Other(_)) = result {
+      if let Err(DBError::ConnectionError) = result {
           Ok(())
       } else {
           Err(format!(
-              "Expected an Other error but got the following error: {:?}",
+              "Expected a connection error but got the following error: {:?}",
               result.err()
           ))
       }
@@ -274,20 +276,21 @@ mod answers_tests {
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
       let result = answer_doa
           .create_answer(Answer {
-              question_uuid: question.question_uuid.clone(),
+              question_uuid: question.question_uuid,
               content: "test content".to_owned(),
-          })
+          }, None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
       let results = answer_doa
-          .get_answers(question.question_uuid.clone())
+          .get_answers(question.question_uuid.to_string(), AnswerSort::Oldest, Viewer::Anonymous)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
@@ -295,199 +298,5518 @@ mod answers_tests {
           return Err("Incorrect number of results returned.".to_owned());
       }
 
-      if results.get(0).unwrap().answer_uuid != result.answer_uuid {
+      if results.first().unwrap().answer_uuid != result.answer_uuid {
           return Err("Incorrect answer returned.".to_owned());
       }
 
       Ok(())
   }
-}
-
-mod questions_tests {
-  use sqlx::PgPool;
-
-  use crate::{
-      models::{DBError, Question},
-      persistance::questions_dao::{QuestionsDao, QuestionsDaoImpl},
-  };
 
   #[sqlx::test]
-  async fn create_question_should_fail_if_database_error_occurs(
-      pool: PgPool,
-  ) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool.clone());
-
-      pool.close().await;
+  async fn get_answers_should_apply_sort(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
 
-      let result = doa
+      let question = question_doa
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
-          .await;
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if result.is_ok() {
-          return Err(format!(
-              "Expected an error but got the following result: {:?}",
-              result.unwrap()
-          ));
+      let mut answers = Vec::new();
+
+      for (days_ago, content) in [(3, "oldest"), (2, "top voted"), (1, "accepted")] {
+          let answer = answer_doa
+              .create_answer(Answer {
+                  question_uuid: question.question_uuid,
+                  content: content.to_owned(),
+              }, None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          sqlx::query("UPDATE answers SET created_at = CURRENT_TIMESTAMP - make_interval(days => $2) WHERE answer_uuid = $1::uuid")
+              .bind(answer.answer_uuid)
+              .bind(days_ago)
+              .execute(&pool)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          answers.push(answer.answer_uuid);
       }
 
-      if let Err(DBError::Other(_)) = result {
-          Ok(())
-      } else {
-          Err(format!(
-              "Expected an Other error but got the following error: {:?}",
-              result.err()
-          ))
+      let voter: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('voter', 'voter') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("INSERT INTO answer_votes (answer_uuid, user_uuid, value) VALUES ($1::uuid, $2, 1)")
+          .bind(answers[1])
+          .bind(voter)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("UPDATE questions SET accepted_answer_uuid = $2::uuid WHERE question_uuid = $1::uuid")
+          .bind(question.question_uuid)
+          .bind(answers[2])
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for (sort, expected) in [
+          (AnswerSort::Oldest, ["oldest", "top voted", "accepted"]),
+          (AnswerSort::Newest, ["accepted", "top voted", "oldest"]),
+          (AnswerSort::Votes, ["top voted", "oldest", "accepted"]),
+          (AnswerSort::AcceptedFirst, ["accepted", "top voted", "oldest"]),
+      ] {
+          let contents: Vec<_> = answer_doa
+              .get_answers(question.question_uuid.to_string(), sort, Viewer::Anonymous)
+              .await
+              .map_err(|e| format!("{:?}", e))?
+              .into_iter()
+              .map(|answer| answer.content)
+              .collect();
+
+          if contents != expected {
+              return Err(format!("Unexpected order for {:?}: {:?}", sort, contents));
+          }
       }
+
+      Ok(())
   }
 
   #[sqlx::test]
-  async fn create_question_should_succeed(pool: PgPool) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool);
+  async fn vote_answer_should_replace_and_withdraw_votes(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
 
-      let result = doa
+      let question = question_doa
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      if result.title != "test title".to_owned()
-          || result.description != "test description".to_owned()
-      {
-          return Err("Incorrect title or description".to_owned());
+      let answer = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let mut voters = Vec::new();
+
+      for name in ["first voter", "second voter"] {
+          let voter: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+              .bind(name)
+              .fetch_one(&pool)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          voters.push(voter.to_string());
+      }
+
+      let mut scores = Vec::new();
+
+      for (voter, value) in [(&voters[0], 1), (&voters[1], 1), (&voters[1], 1), (&voters[1], -1), (&voters[0], 0)] {
+          let score = answer_doa
+              .vote_answer(answer.answer_uuid.to_string(), voter.clone(), value)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          scores.push(score);
+      }
+
+      if scores != [1, 2, 2, 0, -1] {
+          return Err(format!("Unexpected scores: {:?}", scores));
       }
 
       Ok(())
   }
 
   #[sqlx::test]
-  async fn delete_question_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool);
+  async fn accept_answer_should_only_accept_answers_of_the_question(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
 
-      let result = doa.delete_question("malformed".to_owned()).await;
+      let mut questions = Vec::new();
 
-      if result.is_ok() {
-          return Err(format!(
-              "Expected an error but got the following result: {:?}",
-              result.unwrap()
-          ));
+      for title in ["first question", "second question"] {
+          let question = question_doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          questions.push(question.question_uuid);
       }
 
-      if let Err(DBError::InvalidUUID(_)) = result {
-          Ok(())
-      } else {
-          Err(format!(
-              "Expected an invalid UUID error but got the following error: {:?}",
-              result.err()
-          ))
+      let answer = answer_doa
+          .create_answer(Answer {
+              question_uuid: questions[1],
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let accept = |question_uuid: Uuid, answer_uuid: Option<Uuid>| question_doa.accept_answer(question_uuid.to_string(), answer_uuid);
+
+      if accept(questions[0], Some(answer.answer_uuid)).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Accepted the answer of another question".to_owned());
+      }
+
+      if !accept(questions[1], Some(answer.answer_uuid)).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Did not accept the answer of the question".to_owned());
+      }
+
+      let accepted: Option<Uuid> = sqlx::query_scalar("SELECT accepted_answer_uuid FROM questions WHERE question_uuid = $1")
+          .bind(questions[1])
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if accepted != Some(answer.answer_uuid) {
+          return Err(format!("Unexpected accepted answer: {:?}", accepted));
+      }
+
+      if !accept(questions[1], None).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Did not withdraw the acceptance".to_owned());
       }
+
+      Ok(())
   }
 
   #[sqlx::test]
-  async fn delete_question_should_fail_if_database_error_occurs(
-      pool: PgPool,
-  ) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool.clone());
+  async fn get_answers_should_include_signals(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
 
-      pool.close().await;
+      let question = question_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      let result = doa
-          .delete_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
-          .await;
+      let author: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('author', 'author') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if result.is_ok() {
-          return Err(format!(
-              "Expected an error but got the following result: {:?}",
-              result.unwrap()
-          ));
-      }
+      let voted = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "voted".to_owned(),
+          }, Some(author.to_string()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if let Err(DBError::Other(_)) = result {
-          Ok(())
-      } else {
-          Err(format!(
-              "Expected an Other error but got the following error: {:?}",
-              result.err()
-          ))
+      let anonymous = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "anonymous".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("UPDATE answers SET created_at = CURRENT_TIMESTAMP - make_interval(days => 3) WHERE answer_uuid = $1::uuid")
+          .bind(anonymous.answer_uuid)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("INSERT INTO answer_votes (answer_uuid, user_uuid, value) VALUES ($1::uuid, $2, 1)")
+          .bind(voted.answer_uuid)
+          .bind(author)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      answer_doa
+          .update_answer(voted.answer_uuid.to_string(), "voted, edited".to_owned(), author.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let signals: Vec<_> = answer_doa
+          .get_answers(question.question_uuid.to_string(), AnswerSort::Votes, Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .into_iter()
+          .map(|answer| answer.signals)
+          .collect();
+
+      let expected = vec![
+          Some(AnswerSignals {
+              score: 1,
+              score_percentile: 100,
+              author_reputation_band: Some(ReputationBand::New),
+              edited: true,
+              age_days: 0,
+          }),
+          Some(AnswerSignals {
+              score: 0,
+              score_percentile: 50,
+              author_reputation_band: None,
+              edited: false,
+              age_days: 3,
+          }),
+      ];
+
+      if signals != expected {
+          return Err(format!("Expected signals {:?}, got {:?}.", expected, signals));
       }
+
+      Ok(())
   }
 
   #[sqlx::test]
-  async fn delete_question_should_succeed(pool: PgPool) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool);
+  async fn restore_answer_should_succeed(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool);
 
-      let result = doa
+      let question = question_doa
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      doa.delete_question(result.question_uuid)
+      let answer = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+      answer_doa
+          .delete_answer(answer.answer_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if results.len() != 0 {
-          return Err("Question was not deleted".to_owned());
+      let restored = answer_doa
+          .restore_answer(answer.answer_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if restored != Some(answer.clone()) {
+          return Err("Answer was not restored".to_owned());
+      }
+
+      let restored_again = answer_doa
+          .restore_answer(answer.answer_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if restored_again.is_some() {
+          return Err("Restoring a live answer should find nothing".to_owned());
       }
 
       Ok(())
   }
 
   #[sqlx::test]
-  async fn get_questions_should_fail_if_database_error_occurs(
-      pool: PgPool,
-  ) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool.clone());
+  async fn get_answers_should_hide_answers_of_deleted_questions(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool);
 
-      pool.close().await;
+      let question = question_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      let result = doa.get_questions().await;
+      answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if result.is_ok() {
-          return Err(format!(
-              "Expected an error but got the following result: {:?}",
-              result.unwrap()
-          ));
-      }
+      question_doa
+          .delete_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
 
-      if let Err(DBError::Other(_)) = result {
-          Ok(())
-      } else {
-          Err(format!(
-              "Expected an Other error but got the following error: {:?}",
-              result.err()
-          ))
+      let results = answer_doa
+          .get_answers(question.question_uuid.to_string(), AnswerSort::Oldest, Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !results.is_empty() {
+          return Err("Answers of a deleted question should be hidden".to_owned());
       }
+
+      Ok(())
   }
 
   #[sqlx::test]
-  async fn get_questions_should_succeed(pool: PgPool) -> Result<(), String> {
-      let doa = QuestionsDaoImpl::new(pool);
+  async fn purge_deleted_answers_should_only_remove_expired_answers(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
 
-      let result = doa
+      let question = question_doa
           .create_question(Question {
               title: "test title".to_owned(),
               description: "test description".to_owned(),
-          })
+              ..Default::default()
+          }, None, Vec::new(), None, None)
           .await
           .map_err(|e| format!("{:?}", e))?;
 
-      let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+      for _ in 0..2 {
+          let answer = answer_doa
+              .create_answer(Answer {
+                  question_uuid: question.question_uuid,
+                  content: "test content".to_owned(),
+              }, None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
 
-      if results.len() != 1 {
-          return Err("Incorrect number of results returned.".to_owned());
+          answer_doa
+              .delete_answer(answer.answer_uuid.to_string())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
       }
 
-      if results.get(0).unwrap().question_uuid != result.question_uuid {
-          return Err("Incorrect question returned.".to_owned());
+      sqlx::query("UPDATE answers SET deleted_at = CURRENT_TIMESTAMP - INTERVAL '31 days' WHERE answer_uuid IN (SELECT answer_uuid FROM answers LIMIT 1)")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let purged = answer_doa
+          .purge_deleted_answers(30)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if purged != 1 {
+          return Err(format!("Expected 1 purged answer but got {}", purged));
+      }
+
+      Ok(())
+  }
+  #[sqlx::test]
+  async fn update_answer_should_record_original_and_edit_as_revisions(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool.clone());
+
+      let editor_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('editor', 'hash') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = question_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let answer = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, Some(editor_uuid.to_string()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for content in ["second content", "third content"] {
+          answer_doa
+              .update_answer(answer.answer_uuid.to_string(), content.to_owned(), editor_uuid.to_string())
+              .await
+              .map_err(|e| format!("{:?}", e))?
+              .ok_or("Answer should exist")?;
+      }
+
+      let revisions = answer_doa
+          .get_answer_revisions(answer.answer_uuid.to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let contents: Vec<_> = revisions.iter().map(|revision| (revision.revision, revision.content.as_str())).collect();
+
+      if contents != vec![(1, "test content"), (2, "second content"), (3, "third content")] {
+          return Err(format!("Incorrect revisions: {:?}", revisions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_answer_revisions_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+      let answer_doa = AnswersDaoImpl::new(pool);
+
+      let result = answer_doa.get_answer_revisions("123".to_owned(), Viewer::Anonymous).await;
+
+      if result.is_ok() {
+          return Err("Malformed UUID should be rejected".to_owned());
+      }
+
+      if std::mem::discriminant(&result.unwrap_err()) != std::mem::discriminant(&DBError::InvalidUUID("".to_owned())) {
+          return Err("Incorrect error type".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn stream_answers_should_send_the_answers_created_in_the_range(pool: PgPool) -> Result<(), String> {
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool);
+
+      let question = question_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let answer = answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let (answers, mut receiver) = tokio::sync::mpsc::channel(10);
+
+      answer_doa.stream_answers(ExportRange::default(), answers).await.map_err(|e| format!("{:?}", e))?;
+
+      let streamed = receiver.recv().await.map(|streamed| streamed.answer_uuid);
+
+      if streamed != Some(answer.answer_uuid) || receiver.recv().await.is_some() {
+          return Err(format!("Incorrect answers: {:?}", streamed));
+      }
+
+      let after = time::OffsetDateTime::now_utc() + time::Duration::days(1);
+      let range = ExportRange {
+          from: Some(time::PrimitiveDateTime::new(after.date(), after.time())),
+          to: None,
+      };
+      let (answers, mut receiver) = tokio::sync::mpsc::channel(10);
+
+      answer_doa.stream_answers(range, answers).await.map_err(|e| format!("{:?}", e))?;
+
+      if receiver.recv().await.is_some() {
+          return Err("Answers created before the range should be left out".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod questions_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{DBError, ExportRange, Question, QuestionKind, QuestionStatus, Viewer, Visibility},
+      persistance::questions_dao::{QuestionsDao, QuestionsDaoImpl},
+  };
+
+  #[sqlx::test]
+  async fn create_question_should_fail_if_database_error_occurs(
+      pool: PgPool,
+  ) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      pool.close().await;
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await;
+
+      if result.is_ok() {
+          return Err(format!(
+              "Expected an error but got the following result: {:?}",
+              result.unwrap()
+          ));
+      }
+
+      if let Err(DBError::ConnectionError) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected a connection error but got the following error: {:?}",
+              result.err()
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn create_question_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.title != "test title"
+          || result.description != "test description"
+      {
+          return Err("Incorrect title or description".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn create_question_should_save_its_pending_tags_and_hold_in_one_transaction(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+      let question = || Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          ..Default::default()
+      };
+
+      let created = doa
+          .create_question(question(), None, vec!["brand-new".to_owned()], Some("Contains 6 links.".to_owned()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !created.held_for_review || created.pending_tags != vec!["brand-new".to_owned()] {
+          return Err(format!("Expected a held question with a pending tag, got {:?}", created));
+      }
+
+      let (pending, flags): (i64, i64) = sqlx::query_as(
+          "SELECT (SELECT COUNT(*) FROM pending_tags WHERE question_uuid = $1), (SELECT COUNT(*) FROM flags WHERE question_uuid = $1)",
+      )
+      .bind(created.question_uuid)
+      .fetch_one(&pool)
+      .await
+      .map_err(|e| format!("{:?}", e))?;
+
+      if (pending, flags) != (1, 1) {
+          return Err(format!("Expected a pending tag and a spam flag, got {} and {}", pending, flags));
+      }
+
+      let too_long = "x".repeat(Question::MAX_TAG_CHARS + 1);
+      let result = doa.create_question(question(), None, vec![too_long], None, None).await;
+
+      if result.is_ok() {
+          return Err("Expected the pending tag over the column limit to fail the creation.".to_owned());
+      }
+
+      let questions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM questions")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if questions != 1 {
+          return Err(format!("Expected the failed question to be rolled back, got {} questions", questions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn create_question_should_keep_the_kind_through_edits(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let editor_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('editor', 'hash') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let announcement = doa
+          .create_question(Question {
+              title: "Forum rules".to_owned(),
+              description: "Be kind.".to_owned(),
+              kind: QuestionKind::Announcement,
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.update_question(announcement.question_uuid.to_string(), Question {
+              title: "Forum rules".to_owned(),
+              description: "Be kind to each other.".to_owned(),
+              ..Default::default()
+          }, editor_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = doa
+          .get_question(announcement.question_uuid.to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the announcement")?;
+
+      if question.kind != QuestionKind::Announcement {
+          return Err(format!("Expected an announcement, got {:?}", question.kind));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn delete_question_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa.delete_question("malformed".to_owned()).await;
+
+      if result.is_ok() {
+          return Err(format!(
+              "Expected an error but got the following result: {:?}",
+              result.unwrap()
+          ));
+      }
+
+      if let Err(DBError::InvalidUUID(_)) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected an invalid UUID error but got the following error: {:?}",
+              result.err()
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn delete_question_should_fail_if_database_error_occurs(
+      pool: PgPool,
+  ) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      pool.close().await;
+
+      let result = doa
+          .delete_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+          .await;
+
+      if result.is_ok() {
+          return Err(format!(
+              "Expected an error but got the following result: {:?}",
+              result.unwrap()
+          ));
+      }
+
+      if let Err(DBError::ConnectionError) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected a connection error but got the following error: {:?}",
+              result.err()
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn delete_question_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.delete_question(result.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let results = doa.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?;
+
+      if !results.is_empty() {
+          return Err("Question was not deleted".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn delete_questions_should_report_results_per_uuid(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let results = doa
+          .delete_questions(vec![
+              result.question_uuid.to_string(),
+              "00000000-0000-0000-0000-000000000000".to_owned(),
+              "malformed".to_owned(),
+          ])
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let deleted: Vec<_> = results.iter().map(|result| result.deleted).collect();
+
+      if deleted != vec![true, false, false] || results.iter().any(|result| result.deleted == result.error.is_some()) {
+          return Err(format!("Unexpected bulk delete results: {:?}", results));
+      }
+
+      if !doa.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("Question was not deleted".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_questions_should_fail_if_database_error_occurs(
+      pool: PgPool,
+  ) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      pool.close().await;
+
+      let result = doa.get_questions(Viewer::Anonymous, None).await;
+
+      if result.is_ok() {
+          return Err(format!(
+              "Expected an error but got the following result: {:?}",
+              result.unwrap()
+          ));
+      }
+
+      if let Err(DBError::ConnectionError) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected a connection error but got the following error: {:?}",
+              result.err()
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn get_questions_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let results = doa.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?;
+
+      if results.len() != 1 {
+          return Err("Incorrect number of results returned.".to_owned());
+      }
+
+      if results.first().unwrap().question_uuid != result.question_uuid {
+          return Err("Incorrect question returned.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_questions_should_filter_by_detected_language(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      for (title, description) in [
+          ("How do I share data between threads?", "The compiler says the value was moved into the closure."),
+          (
+              "Como compartilhar dados entre threads?",
+              "Estou tentando compartilhar um vetor entre várias threads, mas o compilador reclama que o valor foi movido.",
+          ),
+      ] {
+          doa.create_question(Question {
+                  title: title.to_owned(),
+                  description: description.to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let results = doa
+          .get_questions(Viewer::Anonymous, Some("por".to_owned()))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let languages: Vec<_> = results.iter().map(|question| question.language.as_deref()).collect();
+
+      if languages != [Some("por")] {
+          return Err(format!("Expected only the Portuguese question, got {:?}", languages));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_questions_by_uuid_should_return_questions_in_requested_order(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let mut uuids = Vec::new();
+
+      for title in ["first", "second"] {
+          let question = doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          uuids.push(question.question_uuid);
+      }
+
+      uuids.reverse();
+      uuids.push(Uuid::parse_str("00000000-0000-0000-0000-000000000000").unwrap());
+
+      let titles: Vec<_> = doa
+          .get_questions_by_uuid(uuids.iter().map(Uuid::to_string).collect(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .into_iter()
+          .map(|question| question.title)
+          .collect();
+
+      if titles != vec!["second".to_owned(), "first".to_owned()] {
+          return Err(format!("Unexpected questions returned: {:?}", titles));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_question_should_return_none_for_unknown_uuid(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .get_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err(format!("Expected no question but got: {:?}", result));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_question_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = doa
+          .get_question(result.question_uuid.to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if question != Some(result) {
+          return Err("Incorrect question returned.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn update_question_status_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .update_question_status("malformed".to_owned(), QuestionStatus::Locked, "reason".to_owned())
+          .await;
+
+      if let Err(DBError::InvalidUUID(_)) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected an invalid UUID error but got the following result: {:?}",
+              result
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn update_question_status_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.status != QuestionStatus::Open {
+          return Err("New questions should be open".to_owned());
+      }
+
+      let question = doa
+          .update_question_status(
+              result.question_uuid.to_string(),
+              QuestionStatus::ClosedOffTopic,
+              "not about Rust".to_owned(),
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Question was not found")?;
+
+      if question.status != QuestionStatus::ClosedOffTopic
+          || question.status_reason.as_deref() != Some("not about Rust")
+      {
+          return Err("Incorrect status or reason".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn restore_question_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let question = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.delete_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if doa.get_question(question.question_uuid.to_string(), Viewer::Anonymous).await.map_err(|e| format!("{:?}", e))?.is_some() {
+          return Err("Deleted question should not be readable".to_owned());
+      }
+
+      let restored = doa
+          .restore_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if restored != Some(question) {
+          return Err("Question was not restored".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn purge_deleted_questions_should_only_remove_expired_questions(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      for title in ["expired", "recent"] {
+          let question = doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          doa.delete_question(question.question_uuid.to_string())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      sqlx::query("UPDATE questions SET deleted_at = CURRENT_TIMESTAMP - INTERVAL '31 days' WHERE title = 'expired'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let purged = doa
+          .purge_deleted_questions(30)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if purged != 1 {
+          return Err(format!("Expected 1 purged question but got {}", purged));
+      }
+
+      Ok(())
+  }
+  #[sqlx::test]
+  async fn update_question_should_record_original_and_edit_as_revisions(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let editor_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('editor', 'hash') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let updated = doa
+          .update_question(question.question_uuid.to_string(), Question {
+              title: "new title".to_owned(),
+              description: "new description".to_owned(),
+              ..Default::default()
+          }, editor_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Question should exist")?;
+
+      if updated.title != "new title" || updated.description != "new description" {
+          return Err("Question was not updated".to_owned());
+      }
+
+      let revisions = doa
+          .get_question_revisions(question.question_uuid.to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if revisions.len() != 2
+          || revisions[0].revision != 1
+          || revisions[0].title != "test title"
+          || revisions[0].editor_uuid.is_some()
+          || revisions[1].revision != 2
+          || revisions[1].title != "new title"
+          || revisions[1].editor_uuid != Some(editor_uuid)
+      {
+          return Err(format!("Incorrect revisions: {:?}", revisions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn update_question_should_return_none_for_unknown_question(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      let result = doa
+          .update_question("b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned(), Question {
+              title: "new title".to_owned(),
+              description: "new description".to_owned(),
+              ..Default::default()
+          }, "b068cd2f-edac-479e-98f1-c5f91008dcbd".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Unknown question should not be updated".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_feed_entries_should_list_public_questions_by_tag(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      for (title, tags, visibility) in [
+          ("rust", vec!["rust".to_owned()], Visibility::Public),
+          ("python", vec!["python".to_owned()], Visibility::Public),
+          ("unlisted", vec!["rust".to_owned()], Visibility::Unlisted),
+      ] {
+          doa.create_question(Question {
+              title: title.to_owned(),
+              description: "test description".to_owned(),
+              tags,
+              visibility,
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let all = doa.get_feed_entries(None, 10).await.map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(all.len(), 2);
+      assert!(all.iter().all(|entry| entry.published.ends_with('Z') && entry.published.contains('T')));
+
+      let rust = doa.get_feed_entries(Some("rust".to_owned()), 10).await.map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(rust.iter().map(|entry| entry.title.as_str()).collect::<Vec<_>>(), vec!["rust"]);
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn stream_sitemap_urls_should_send_the_public_questions(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let mut public_uuids = Vec::new();
+      for visibility in [Visibility::Public, Visibility::Unlisted, Visibility::Public] {
+          let question = doa
+              .create_question(Question {
+                  title: "test title".to_owned(),
+                  description: "test description".to_owned(),
+                  visibility,
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if visibility == Visibility::Public {
+              public_uuids.push(question.question_uuid);
+          }
+      }
+
+      assert_eq!(doa.count_sitemap_questions().await.map_err(|e| format!("{:?}", e))?, 2);
+
+      let (urls, mut receiver) = tokio::sync::mpsc::channel(10);
+
+      doa.stream_sitemap_urls(0, urls).await.map_err(|e| format!("{:?}", e))?;
+
+      let mut streamed = Vec::new();
+      while let Some(url) = receiver.recv().await {
+          streamed.push(url.question_uuid);
+      }
+
+      public_uuids.sort();
+
+      assert_eq!(streamed, public_uuids);
+
+      let (urls, mut receiver) = tokio::sync::mpsc::channel(10);
+
+      doa.stream_sitemap_urls(1, urls).await.map_err(|e| format!("{:?}", e))?;
+
+      assert!(receiver.recv().await.is_none());
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn stream_questions_should_send_every_question_that_is_not_deleted(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let mut uuids = Vec::new();
+      for visibility in [Visibility::Public, Visibility::Unlisted, Visibility::Public] {
+          let question = doa
+              .create_question(Question {
+                  title: "test title".to_owned(),
+                  description: "test description".to_owned(),
+                  visibility,
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          uuids.push(question.question_uuid);
+      }
+
+      doa.delete_question(uuids[2].to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let (questions, mut receiver) = tokio::sync::mpsc::channel(10);
+
+      doa.stream_questions(ExportRange::default(), questions).await.map_err(|e| format!("{:?}", e))?;
+
+      let mut streamed = Vec::new();
+      while let Some(question) = receiver.recv().await {
+          streamed.push(question.question_uuid);
+      }
+
+      uuids.truncate(2);
+      uuids.sort();
+      streamed.sort();
+
+      assert_eq!(streamed, uuids);
+
+      Ok(())
+  }
+}
+
+mod streaming_tests {
+  use sqlx::{postgres::PgPoolOptions, PgPool};
+
+  use crate::{
+      models::{ExportRange, Question},
+      persistance::{
+          begin_streaming, commit,
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  #[sqlx::test]
+  async fn streamed_reads_should_outlast_the_statement_timeout(pool: PgPool) -> Result<(), String> {
+      let options = (*pool.connect_options()).clone().options([("statement_timeout", "100")]);
+      let limited = PgPoolOptions::new()
+          .max_connections(1)
+          .connect_with(options)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if sqlx::query("SELECT pg_sleep(0.3)").execute(&limited).await.is_ok() {
+          return Err("Expected the statement to time out outside a streamed read".to_owned());
+      }
+
+      let mut tx = begin_streaming(&limited).await.map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("SELECT pg_sleep(0.3)")
+          .execute(&mut *tx)
+          .await
+          .map_err(|e| format!("Expected no statement timeout in a streamed read: {:?}", e))?;
+
+      commit(tx).await.map_err(|e| format!("{:?}", e))?;
+
+      // The connection goes back to the pool with its timeout.
+      if sqlx::query("SELECT pg_sleep(0.3)").execute(&limited).await.is_ok() {
+          return Err("Expected the statement timeout back after the streamed read".to_owned());
+      }
+
+      let doa = QuestionsDaoImpl::new(limited);
+
+      doa.create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let (questions, mut receiver) = tokio::sync::mpsc::channel(1);
+
+      doa.stream_questions(ExportRange::default(), questions).await.map_err(|e| format!("{:?}", e))?;
+
+      if receiver.recv().await.is_none() {
+          return Err("Expected the question to be streamed".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod users_tests {
+  use std::sync::Arc;
+
+  use sqlx::{PgPool, Row};
+
+  use crate::{
+      crypto::{FieldCipher, StaticKeyProvider},
+      models::{DBError, ProvisionedUser, Role, User},
+      persistance::users_dao::{UsersDao, UsersDaoImpl},
+  };
+
+  const KEY_1: &str = "1:MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+  const KEY_2: &str = "2:ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
+
+  fn users_dao(pool: PgPool, keys: &str) -> UsersDaoImpl {
+      let keys = StaticKeyProvider::parse(keys).expect("test keys should be valid");
+
+      UsersDaoImpl::new(pool, FieldCipher::new(Arc::new(keys)))
+  }
+
+  fn user(username: &str) -> User {
+      User {
+          username: username.to_owned(),
+          email: Some(format!("{}@example.com", username)),
+      }
+  }
+
+  #[sqlx::test]
+  async fn create_user_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let result = doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.username != "ferris"
+          || result.email.as_deref() != Some("ferris@example.com")
+          || result.role != Role::User
+      {
+          return Err("Incorrect username, email or role".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn create_user_should_fail_with_duplicate_username(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      doa.create_user(user("ferris"), "hash 1".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .create_user(user("ferris"), "hash 2".to_owned())
+          .await;
+
+      if let Err(DBError::UniqueViolation(_)) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected a unique violation error but got the following result: {:?}",
+              result
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn get_user_by_token_hash_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let user = doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .get_user_by_token_hash("hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result != Some(user) {
+          return Err("Incorrect user returned.".to_owned());
+      }
+
+      let result = doa
+          .get_user_by_token_hash("other hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected no user for an unknown token hash.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_user_by_token_hash_should_not_find_users_of_other_tenants(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool.clone(), KEY_1);
+
+      let user = doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("UPDATE users SET tenant_id = 'a22abcd2-22ab-2222-a22b-2abc2a2b22cc' WHERE user_uuid = $1")
+          .bind(user.user_uuid)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .get_user_by_token_hash("hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected no user outside of the user's tenant.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn create_user_should_store_email_encrypted(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool.clone(), KEY_1);
+
+      doa.create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let stored: Vec<u8> = sqlx::query("SELECT email_encrypted FROM users")
+          .fetch_one(&pool)
+          .await
+          .map(|row| row.get(0))
+          .map_err(|e| format!("{:?}", e))?;
+
+      if stored.windows(b"ferris".len()).any(|window| window == b"ferris") {
+          return Err("Email was stored in plain text".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn record_user_ip_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let result = doa.record_user_ip("malformed".to_owned(), "127.0.0.1".to_owned()).await;
+
+      if let Err(DBError::InvalidUUID(_)) = result {
+          Ok(())
+      } else {
+          Err(format!(
+              "Expected an invalid UUID error but got the following result: {:?}",
+              result
+          ))
+      }
+  }
+
+  #[sqlx::test]
+  async fn get_user_ip_history_should_succeed(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let user = doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.record_user_ip(user.user_uuid.to_string(), "127.0.0.1".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let results = doa
+          .get_user_ip_history(user.user_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if results.len() != 1 || results[0].ip != "127.0.0.1" {
+          return Err(format!("Incorrect IP history returned: {:?}", results));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn rotate_encryption_keys_should_reencrypt_old_values(pool: PgPool) -> Result<(), String> {
+      let old_doa = users_dao(pool.clone(), KEY_1);
+
+      let user = old_doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      old_doa
+          .record_user_ip(user.user_uuid.to_string(), "127.0.0.1".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let new_doa = users_dao(pool.clone(), &format!("{},{}", KEY_1, KEY_2));
+
+      let rotated = new_doa
+          .rotate_encryption_keys()
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if rotated != 2 {
+          return Err(format!("Expected 2 rotated values but got {}", rotated));
+      }
+
+      // Only the new key is needed once every value has been rotated.
+      let rotated_doa = users_dao(pool, KEY_2);
+
+      let result = rotated_doa
+          .get_user_by_token_hash("hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("User was not found")?;
+
+      if result.email.as_deref() != Some("ferris@example.com") {
+          return Err("Email was not rotated correctly".to_owned());
+      }
+
+      let history = rotated_doa
+          .get_user_ip_history(user.user_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if history.len() != 1 || history[0].ip != "127.0.0.1" {
+          return Err("IP history was not rotated correctly".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn set_user_active_should_block_deactivated_users(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let user = doa
+          .provision_user(
+              ProvisionedUser {
+                  username: "ferris".to_owned(),
+                  display_name: Some("Ferris".to_owned()),
+                  email: Some("ferris@example.com".to_owned()),
+                  external_id: Some("idp-1".to_owned()),
+                  role: Role::Moderator,
+                  active: true,
+              },
+              "hash".to_owned(),
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.set_user_active(user.user.user_uuid.to_string(), false)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .get_user_by_token_hash("hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected a deactivated user to be unable to authenticate.".to_owned());
+      }
+
+      let result = doa
+          .get_provisioned_users(Some("ferris".to_owned()))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.len() != 1 || result[0].active || result[0].user.role != Role::Moderator {
+          return Err(format!("Incorrect provisioned users returned: {}", result.len()));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn upsert_directory_user_should_refresh_existing_user(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let directory_user = |role: Role| ProvisionedUser {
+          username: "ferris".to_owned(),
+          display_name: Some("Ferris".to_owned()),
+          email: None,
+          external_id: Some("uid=ferris,dc=example,dc=com".to_owned()),
+          role,
+          active: true,
+      };
+
+      let created = doa
+          .upsert_directory_user(directory_user(Role::User), "hash 1".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the user to be created.")?;
+
+      let updated = doa
+          .upsert_directory_user(directory_user(Role::Admin), "hash 2".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the user to be updated.")?;
+
+      if updated.user.user_uuid != created.user.user_uuid || updated.user.role != Role::Admin {
+          return Err("Expected the same user with the new role.".to_owned());
+      }
+
+      let result = doa
+          .get_user_by_token_hash("hash 1".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected the previous API token to be replaced.".to_owned());
+      }
+
+      doa.set_user_active(created.user.user_uuid.to_string(), false)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .upsert_directory_user(directory_user(Role::User), "hash 3".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected a deactivated user to stay signed out.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn set_avatar_key_should_return_previous_key_and_update_profile(pool: PgPool) -> Result<(), String> {
+      let doa = users_dao(pool, KEY_1);
+
+      let created = doa
+          .create_user(user("ferris"), "hash".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let profile = doa
+          .get_user_profile(created.user_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected a profile.")?;
+
+      if profile.username != "ferris" || !profile.avatar_urls.is_empty() {
+          return Err("Expected a profile without avatar URLs.".to_owned());
+      }
+
+      for (key, expected_previous) in [("avatar-1", None), ("avatar-2", Some("avatar-1"))] {
+          let previous = doa
+              .set_avatar_key(created.user_uuid.to_string(), Some(key.to_owned()))
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if previous.as_deref() != expected_previous {
+              return Err(format!("Expected previous key {:?}, got {:?}", expected_previous, previous));
+          }
+      }
+
+      let profile = doa
+          .get_user_profile(created.user_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected a profile.")?;
+
+      if profile.avatar_urls.first().map(|avatar| avatar.url.as_str()) != Some("/api/v1/avatars/avatar-2-64") {
+          return Err("Expected URLs of the new avatar.".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod tenancy_tests {
+  use sqlx::{types::Uuid, PgPool, Row};
+
+  use crate::persistance::tenants_dao::{TenantsDao, TenantsDaoImpl};
+
+  async fn count_visible_questions(
+      conn: &mut sqlx::PgConnection,
+      tenant: &str,
+  ) -> Result<i64, String> {
+      sqlx::query("SELECT set_config('app.tenant_id', $1, false)")
+          .bind(tenant)
+          .execute(&mut *conn)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("SELECT COUNT(*) FROM questions")
+          .fetch_one(&mut *conn)
+          .await
+          .map(|row| row.get::<i64, _>(0))
+          .map_err(|e| format!("{:?}", e))
+  }
+
+  #[sqlx::test]
+  async fn tenants_should_only_see_their_own_questions(pool: PgPool) -> Result<(), String> {
+      let tenant_a = "a22abcd2-22ab-2222-a22b-2abc2a2b22cc";
+      let tenant_b = "b33abcd3-33ab-3333-a33b-3abc3a3b33dd";
+
+      let mut conn = pool.acquire().await.map_err(|e| format!("{:?}", e))?;
+
+      // Superusers bypass row-level security, so run the checks as an unprivileged role.
+      for statement in [
+          "DO $$ BEGIN CREATE ROLE forum_tenant_test NOLOGIN; EXCEPTION WHEN duplicate_object THEN NULL; END $$",
+          "GRANT SELECT, INSERT ON questions TO forum_tenant_test",
+          "SET ROLE forum_tenant_test",
+      ] {
+          sqlx::query(statement)
+              .execute(&mut *conn)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      count_visible_questions(&mut conn, tenant_a).await?;
+
+      sqlx::query("INSERT INTO questions (title, description) VALUES ('test title', 'test description')")
+          .execute(&mut *conn)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if count_visible_questions(&mut conn, tenant_b).await? != 0 {
+          return Err("Question leaked to another tenant".to_owned());
+      }
+
+      if count_visible_questions(&mut conn, tenant_a).await? != 1 {
+          return Err("Question is not visible to its own tenant".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_tenant_by_host_should_ignore_case(pool: PgPool) -> Result<(), String> {
+      let tenant: Uuid = sqlx::query_scalar("INSERT INTO tenants (host) VALUES ('Forum.Example.com') RETURNING tenant_id")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let dao = TenantsDaoImpl::new(pool);
+
+      let result = dao
+          .get_tenant_by_host("forum.example.com".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result != Some(tenant) {
+          return Err("Incorrect tenant returned for the host.".to_owned());
+      }
+
+      let result = dao
+          .get_tenant_by_host("other.example.com".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Expected no tenant for an unknown host.".to_owned());
+      }
+
+      let tenants = dao.get_tenants().await.map_err(|e| format!("{:?}", e))?;
+
+      if tenants != vec![tenant] {
+          return Err("Incorrect tenants returned.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn every_table_with_a_tenant_should_force_row_level_security(pool: PgPool) -> Result<(), String> {
+      // Tenants are looked up by host before the tenant is known, and users by token, which matches
+      // it in the query instead.
+      let unisolated: Vec<String> = sqlx::query_scalar(
+          "SELECT c.relname::TEXT FROM pg_class c
+              JOIN pg_attribute a ON a.attrelid = c.oid AND a.attname = 'tenant_id' AND NOT a.attisdropped
+              JOIN pg_namespace n ON n.oid = c.relnamespace AND n.nspname = current_schema()
+              WHERE c.relkind = 'r'
+                  AND c.relname NOT IN ('tenants', 'users')
+                  AND NOT (c.relrowsecurity AND c.relforcerowsecurity)",
+      )
+      .fetch_all(&pool)
+      .await
+      .map_err(|e| format!("{:?}", e))?;
+
+      if !unisolated.is_empty() {
+          return Err(format!("Tables without row-level security: {:?}", unisolated));
+      }
+
+      Ok(())
+  }
+}
+
+mod drafts_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{QuestionDraft, Viewer},
+      persistance::{
+          drafts_dao::{DraftsDao, DraftsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  fn draft(title: &str) -> QuestionDraft {
+      QuestionDraft {
+          title: title.to_owned(),
+          description: "test description".to_owned(),
+      }
+  }
+
+  #[sqlx::test]
+  async fn save_question_draft_should_overwrite_existing_draft(pool: PgPool) -> Result<(), String> {
+      let user_uuid = create_user(&pool, "ferris").await?;
+      let doa = DraftsDaoImpl::new(pool);
+
+      let first = doa
+          .save_question_draft(user_uuid.clone(), draft("first title"))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let second = doa
+          .save_question_draft(user_uuid, draft("second title"))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if first.draft_uuid != second.draft_uuid || second.title != "second title" {
+          return Err("Draft was not updated in place".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn publish_draft_should_create_question_and_remove_draft(pool: PgPool) -> Result<(), String> {
+      let user_uuid = create_user(&pool, "ferris").await?;
+      let doa = DraftsDaoImpl::new(pool.clone());
+      let question_doa = QuestionsDaoImpl::new(pool);
+
+      let saved = doa
+          .save_question_draft(user_uuid.clone(), draft("test title"))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !question_doa.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("Drafts should not be listed as questions".to_owned());
+      }
+
+      let question = doa
+          .publish_draft(saved.draft_uuid.to_string(), user_uuid.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Draft should be published")?;
+
+      if question.title != "test title" || question.author_uuid != Some(Uuid::parse_str(&user_uuid).unwrap()) {
+          return Err("Incorrect question title or author".to_owned());
+      }
+
+      let remaining = doa
+          .get_draft(saved.draft_uuid.to_string(), user_uuid)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if remaining.is_some() {
+          return Err("Draft should be removed after publishing".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn publish_draft_should_ignore_other_users_drafts(pool: PgPool) -> Result<(), String> {
+      let owner_uuid = create_user(&pool, "ferris").await?;
+      let other_uuid = create_user(&pool, "corro").await?;
+      let doa = DraftsDaoImpl::new(pool);
+
+      let saved = doa
+          .save_question_draft(owner_uuid, draft("test title"))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .publish_draft(saved.draft_uuid.to_string(), other_uuid)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_some() {
+          return Err("Another user's draft should not be published".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod follows_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{
+          AcceptSuggestionThresholds, Answer, AnswerSort, DigestFrequency, DigestSettings, NotificationKind, Pagination,
+          Question, QuestionDeletion, Viewer,
+      },
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          email_digests_dao::{EmailDigestsDao, EmailDigestsDaoImpl},
+          follows_dao::{FollowsDao, FollowsDaoImpl},
+          notifications_dao::{NotificationsDao, NotificationsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          tags_dao::{TagsDao, TagsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn notify_question_followers_should_skip_unfollowed_users_and_the_actor(pool: PgPool) -> Result<(), String> {
+      let follows_doa = FollowsDaoImpl::new(pool.clone());
+      let notifications_doa = NotificationsDaoImpl::new(pool.clone());
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let follower = create_user(&pool, "follower").await?;
+      let former = create_user(&pool, "former").await?;
+      let answerer = create_user(&pool, "answerer").await?;
+
+      for user in [&follower, &follower, &former, &answerer] {
+          follows_doa
+              .follow_question(question.question_uuid.to_string(), user.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      follows_doa
+          .unfollow_question(question.question_uuid.to_string(), former)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let answer = AnswersDaoImpl::new(pool.clone())
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "test content".to_owned(),
+          }, Some(answerer.clone()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let notified = notifications_doa
+          .notify_question_followers(
+              question.question_uuid.to_string(),
+              NotificationKind::NewAnswer,
+              Some(answer.answer_uuid.to_string()),
+              Some(answerer),
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if notified != 1 {
+          return Err(format!("Expected 1 notification but got {}", notified));
+      }
+
+      let recipient: Uuid = sqlx::query_scalar("SELECT user_uuid FROM notifications")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if recipient.to_string() != follower {
+          return Err("Notification sent to the wrong user".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn notifications_should_be_listed_and_marked_read_per_user(pool: PgPool) -> Result<(), String> {
+      let reader = create_user(&pool, "reader").await?;
+      let other = create_user(&pool, "other").await?;
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let doa = NotificationsDaoImpl::new(pool.clone());
+
+      let mut question_uuids = Vec::new();
+      for (title, kind) in [("first", NotificationKind::Mention), ("second", NotificationKind::NewAnswer)] {
+          let question = questions_doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          doa.notify_user(reader.clone(), kind, question.question_uuid.to_string(), None, Some(other.clone()))
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          question_uuids.push(question.question_uuid);
+      }
+
+      let unread = |user: &String| doa.get_notifications(user.clone(), true, Pagination::default());
+
+      let notifications = unread(&reader).await.map_err(|e| format!("{:?}", e))?;
+      let kinds: Vec<_> = notifications.iter().map(|notification| notification.kind).collect();
+
+      if kinds != [NotificationKind::NewAnswer, NotificationKind::Mention] {
+          return Err(format!("Expected the newest notification first, got {:?}", kinds));
+      }
+
+      let oldest = notifications[1].id;
+
+      if doa.mark_notification_read(other.clone(), oldest).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Users should not read others' notifications".to_owned());
+      }
+      if !doa.mark_notification_read(reader.clone(), oldest).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Notification should have been marked read".to_owned());
+      }
+      if unread(&reader).await.map_err(|e| format!("{:?}", e))?.len() != 1 {
+          return Err("Expected 1 unread notification".to_owned());
+      }
+
+      let read = doa.mark_all_notifications_read(reader.clone()).await.map_err(|e| format!("{:?}", e))?;
+
+      if read != 1 || !unread(&reader).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err(format!("Expected the last unread notification to be read, got {}", read));
+      }
+
+      sqlx::query("UPDATE questions SET deleted_at = CURRENT_TIMESTAMP WHERE question_uuid = $1::uuid")
+          .bind(question_uuids[1])
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let all = doa
+          .get_notifications(reader, false, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if all.len() != 1 || all[0].read_at.is_none() {
+          return Err(format!("Expected only the read notification on the remaining question, got {:?}", all));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn pending_emails_should_be_claimed_once_and_retried_after_failures(pool: PgPool) -> Result<(), String> {
+      let reader = create_user(&pool, "reader").await?;
+      let actor = create_user(&pool, "actor").await?;
+      let doa = NotificationsDaoImpl::new(pool.clone());
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "Lifetimes in structs".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.notify_user(reader.clone(), NotificationKind::Mention, question.question_uuid.to_string(), None, Some(actor))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let claimed = doa.claim_pending_emails(10).await.map_err(|e| format!("{:?}", e))?;
+
+      if claimed.len() != 1
+          || claimed[0].user_uuid.to_string() != reader
+          || claimed[0].question_title.as_deref() != Some("Lifetimes in structs")
+          || claimed[0].actor_username.as_deref() != Some("actor")
+      {
+          return Err(format!("Expected the mention to be claimed, got {:?}", claimed));
+      }
+      if !doa.claim_pending_emails(10).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("A claimed email should not be claimed again".to_owned());
+      }
+
+      let id = claimed[0].id;
+      doa.fail_email(id, "connection refused".to_owned(), 2).await.map_err(|e| format!("{:?}", e))?;
+
+      let status = |id: i64| {
+          sqlx::query_scalar::<_, String>("SELECT email_status FROM notifications WHERE id = $1")
+              .bind(id)
+              .fetch_one(&pool)
+      };
+
+      if status(id).await.map_err(|e| format!("{:?}", e))? != "pending" {
+          return Err("A failed email should be retried".to_owned());
+      }
+
+      sqlx::query("UPDATE notifications SET email_claimed_at = CURRENT_TIMESTAMP - INTERVAL '6 minutes' WHERE id = $1")
+          .bind(id)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if doa.claim_pending_emails(10).await.map_err(|e| format!("{:?}", e))?.len() != 1 {
+          return Err("A failed email should be claimed again once its claim is stale".to_owned());
+      }
+
+      doa.fail_email(id, "connection refused".to_owned(), 2).await.map_err(|e| format!("{:?}", e))?;
+
+      if status(id).await.map_err(|e| format!("{:?}", e))? != "failed" {
+          return Err("An email should be given up after the last attempt".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn claim_due_digests_should_collect_unread_notifications_and_new_questions_once(pool: PgPool) -> Result<(), String> {
+      let reader = create_user(&pool, "reader").await?;
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let doa = EmailDigestsDaoImpl::new(pool.clone());
+
+      doa.set_digest_settings(reader.clone(), DigestSettings {
+              frequency: DigestFrequency::Daily,
+              tags: vec!["tokio".to_owned()],
+          })
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !doa.claim_due_digests(10).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("A new subscription should wait one period".to_owned());
+      }
+
+      sqlx::query("UPDATE digest_subscriptions SET last_sent_at = CURRENT_TIMESTAMP - INTERVAL '25 hours'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for (title, tags) in [("Spawning tasks", vec!["tokio".to_owned()]), ("Borrowing", vec!["lifetimes".to_owned()])] {
+          questions_doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  tags,
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let answered = questions_doa
+          .get_questions(Viewer::User(Uuid::parse_str(&reader).unwrap()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .into_iter()
+          .find(|question| question.title == "Borrowing")
+          .ok_or("Expected the question")?;
+
+      NotificationsDaoImpl::new(pool.clone())
+          .notify_user(reader.clone(), NotificationKind::NewAnswer, answered.question_uuid.to_string(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let digests = doa.claim_due_digests(10).await.map_err(|e| format!("{:?}", e))?;
+
+      if digests.len() != 1
+          || digests[0].user_uuid.to_string() != reader
+          || digests[0].unread_notifications != 1
+          || digests[0].notifications[0].question_title != "Borrowing"
+          || digests[0].questions.iter().map(|question| question.title.as_str()).collect::<Vec<_>>() != ["Spawning tasks"]
+      {
+          return Err(format!("Unexpected digests {:?}", digests));
+      }
+      if !doa.claim_due_digests(10).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("A digest should not be claimed twice in one period".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn notify_mentioned_users_should_skip_unknown_deactivated_users_and_the_actor(pool: PgPool) -> Result<(), String> {
+      let author = create_user(&pool, "author").await?;
+      let jane = create_user(&pool, "jane").await?;
+      create_user(&pool, "gone").await?;
+
+      sqlx::query("UPDATE users SET active = FALSE WHERE username = 'gone'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "ping @jane @gone @nobody @author".to_owned(),
+              ..Default::default()
+          }, Some(author.clone()), Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let usernames = ["jane", "gone", "nobody", "author"].map(str::to_owned).to_vec();
+
+      let notified = NotificationsDaoImpl::new(pool.clone())
+          .notify_mentioned_users(question.question_uuid.to_string(), None, usernames, Some(author))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if notified != 1 {
+          return Err(format!("Expected 1 notification but got {}", notified));
+      }
+
+      let mentioned: Vec<Uuid> = sqlx::query_scalar("SELECT user_uuid FROM mentions")
+          .fetch_all(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if mentioned.len() != 1 || mentioned[0].to_string() != jane {
+          return Err(format!("Expected only jane to be mentioned, got {:?}", mentioned));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn notify_accept_suggestions_should_notify_each_leading_question_once(pool: PgPool) -> Result<(), String> {
+      let author = create_user(&pool, "author").await?;
+      let opted_out = create_user(&pool, "opted-out").await?;
+      let notifications_doa = NotificationsDaoImpl::new(pool.clone());
+
+      notifications_doa
+          .set_accept_suggestions_enabled(opted_out.clone(), false)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let mut questions = Vec::new();
+
+      // Votes on the top answer and the runner-up for each question.
+      for (asker, votes) in [(&author, (6, 1)), (&author, (6, 4)), (&opted_out, (6, 0))] {
+          let question = QuestionsDaoImpl::new(pool.clone())
+              .create_question(Question {
+                  title: "test title".to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, Some(asker.clone()), Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          for (content, score) in [("top", votes.0), ("runner-up", votes.1)] {
+              let answer = AnswersDaoImpl::new(pool.clone())
+                  .create_answer(Answer {
+                      question_uuid: question.question_uuid,
+                      content: content.to_owned(),
+                  }, None, None)
+                  .await
+                  .map_err(|e| format!("{:?}", e))?;
+
+              for voter in 0..score {
+                  let voter = create_user(&pool, &format!("voter-{}-{}-{}", question.question_uuid, content, voter)).await?;
+
+                  sqlx::query("INSERT INTO answer_votes (answer_uuid, user_uuid, value) VALUES ($1::uuid, $2::uuid, 1)")
+                      .bind(answer.answer_uuid)
+                      .bind(voter)
+                      .execute(&pool)
+                      .await
+                      .map_err(|e| format!("{:?}", e))?;
+              }
+          }
+
+          questions.push(question.question_uuid);
+      }
+
+      sqlx::query("UPDATE questions SET created_at = CURRENT_TIMESTAMP - make_interval(days => 30)")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let thresholds = AcceptSuggestionThresholds::default();
+
+      for expected in [1, 0] {
+          let notified = notifications_doa
+              .notify_accept_suggestions(thresholds)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if notified != expected {
+              return Err(format!("Expected {} notifications but got {}", expected, notified));
+          }
+      }
+
+      let suggested: Uuid = sqlx::query_scalar("SELECT question_uuid FROM notifications WHERE kind = 'accept-suggestion'")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if suggested != questions[0] {
+          return Err(format!("Expected a suggestion for {} but got {}", questions[0], suggested));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn delete_question_should_take_answers_and_follows_until_restored(pool: PgPool) -> Result<(), String> {
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let answers_doa = AnswersDaoImpl::new(pool.clone());
+
+      let question = questions_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, vec!["brand-new".to_owned()], None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let mut answers = Vec::new();
+      for content in ["kept", "deleted before"] {
+          let answer = answers_doa
+              .create_answer(Answer {
+                  question_uuid: question.question_uuid,
+                  content: content.to_owned(),
+              }, None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          answers.push(answer.answer_uuid);
+      }
+
+      answers_doa
+          .delete_answer(answers[1].to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let follower = create_user(&pool, "follower").await?;
+
+      FollowsDaoImpl::new(pool.clone())
+          .follow_question(question.question_uuid.to_string(), follower.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("INSERT INTO answer_votes (answer_uuid, user_uuid, value) VALUES ($1, $2::uuid, 1)")
+          .bind(answers[0])
+          .bind(&follower)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let deletion = questions_doa
+          .delete_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let expected = QuestionDeletion {
+          question_uuid: question.question_uuid,
+          answers: 1,
+          votes: 1,
+          followers: 1,
+          pending_tags: 1,
+      };
+
+      if deletion != Some(expected) {
+          return Err(format!("Unexpected deletion: {:?}", deletion));
+      }
+
+      let deleted_again = questions_doa
+          .delete_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if deleted_again.is_some() {
+          return Err("A deleted question should not be deleted again".to_owned());
+      }
+
+      let hidden: i64 = sqlx::query_scalar("SELECT count(*) FROM answers WHERE question_uuid = $1 AND deleted_at IS NOT NULL")
+          .bind(question.question_uuid)
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if hidden != 2 {
+          return Err(format!("Expected both answers to be deleted but got {}", hidden));
+      }
+
+      let notifications_doa = NotificationsDaoImpl::new(pool.clone());
+      let tags_doa = TagsDaoImpl::new(pool.clone());
+      let page = Pagination { offset: 0, limit: 10 };
+
+      let notified = notifications_doa
+          .notify_question_followers(question.question_uuid.to_string(), NotificationKind::NewAnswer, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let pending = tags_doa.get_pending_tags(page).await.map_err(|e| format!("{:?}", e))?;
+
+      if notified != 0 || !pending.is_empty() {
+          return Err(format!("Expected follows and tag proposals to be hidden but got {} and {:?}", notified, pending));
+      }
+
+      questions_doa
+          .restore_question(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let restored = answers_doa
+          .get_answers(question.question_uuid.to_string(), AnswerSort::Oldest, Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if restored.iter().map(|answer| answer.answer_uuid).collect::<Vec<_>>() != answers[..1] {
+          return Err(format!("Expected only the answer deleted with the question back but got {:?}", restored));
+      }
+
+      let notified = notifications_doa
+          .notify_question_followers(question.question_uuid.to_string(), NotificationKind::NewAnswer, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let pending = tags_doa.get_pending_tags(page).await.map_err(|e| format!("{:?}", e))?;
+
+      if notified != 1 || pending.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>() != ["brand-new"] {
+          return Err(format!("Expected follows and tag proposals back but got {} and {:?}", notified, pending));
+      }
+
+      Ok(())
+  }
+}
+
+mod visibility_tests {
+  use std::sync::Arc;
+
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      crypto::{FieldCipher, StaticKeyProvider},
+      models::{Answer, AnswerSort, Pagination, Question, QuestionDetail, QuestionKind, Viewer, Visibility},
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          users_dao::{UsersDao, UsersDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  async fn create_board(pool: &PgPool, member_uuid: &str) -> Result<String, String> {
+      let board_uuid: Uuid = sqlx::query_scalar("INSERT INTO boards (name) VALUES ('private board') RETURNING board_uuid")
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("INSERT INTO board_members (board_uuid, user_uuid) VALUES ($1, $2::uuid)")
+          .bind(board_uuid)
+          .bind(member_uuid)
+          .execute(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(board_uuid.to_string())
+  }
+
+  async fn create_question(
+      doa: &QuestionsDaoImpl,
+      title: &str,
+      visibility: Visibility,
+      board_uuid: Option<String>,
+  ) -> Result<QuestionDetail, String> {
+      doa.create_question(Question {
+              title: title.to_owned(),
+              description: "test description".to_owned(),
+              visibility,
+              board_uuid: board_uuid.map(|uuid| Uuid::parse_str(&uuid).unwrap()),
+              tags: Vec::new(),
+              kind: QuestionKind::Question,
+              contest: None,
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))
+  }
+
+  #[sqlx::test]
+  async fn get_questions_by_author_should_page_and_hide_unlisted_from_others(pool: PgPool) -> Result<(), String> {
+      let author_uuid = create_user(&pool, "author").await?;
+      let doa = QuestionsDaoImpl::new(pool);
+
+      for (title, visibility) in [
+          ("first", Visibility::Public),
+          ("second", Visibility::Unlisted),
+          ("third", Visibility::Public),
+          ("fourth", Visibility::Public),
+      ] {
+          doa.create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  visibility,
+                  board_uuid: None,
+                  tags: Vec::new(),
+                  kind: QuestionKind::Question,
+                  contest: None,
+              }, Some(author_uuid.clone()), Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+      create_question(&doa, "someone else's", Visibility::Public, None).await?;
+
+      let page = |offset| Pagination { offset, limit: 2 };
+
+      let mut titles = Vec::new();
+      for offset in [0, 2] {
+          let questions = doa
+              .get_questions_by_author(author_uuid.clone(), page(offset), Viewer::Anonymous)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          titles.extend(questions.into_iter().map(|q| q.title));
+      }
+
+      if titles != ["fourth", "third", "first"] {
+          return Err(format!("Expected newest public questions first, got {:?}", titles));
+      }
+
+      let own = doa
+          .get_questions_by_author(author_uuid.clone(), Pagination::default(), Viewer::User(Uuid::parse_str(&author_uuid).unwrap()))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if own.len() != 4 {
+          return Err(format!("Expected the author to see all 4 questions, got {}", own.len()));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn unlisted_questions_should_be_readable_but_not_listed(pool: PgPool) -> Result<(), String> {
+      let doa = QuestionsDaoImpl::new(pool);
+
+      create_question(&doa, "public", Visibility::Public, None).await?;
+      let unlisted = create_question(&doa, "unlisted", Visibility::Unlisted, None).await?;
+
+      let titles: Vec<_> = doa
+          .get_questions(Viewer::Anonymous, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .into_iter()
+          .map(|question| question.title)
+          .collect();
+
+      if titles != vec!["public".to_owned()] {
+          return Err(format!("Unexpected listing: {:?}", titles));
+      }
+
+      let result = doa
+          .get_question(unlisted.question_uuid.to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if result.is_none() {
+          return Err("Unlisted question should be readable by link".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn private_questions_should_only_be_readable_by_board_members(pool: PgPool) -> Result<(), String> {
+      let member = create_user(&pool, "member").await?;
+      let outsider = create_user(&pool, "outsider").await?;
+      let board_uuid = create_board(&pool, &member).await?;
+
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool);
+
+      let private = create_question(&question_doa, "private", Visibility::Private, Some(board_uuid)).await?;
+
+      answer_doa
+          .create_answer(Answer {
+              question_uuid: private.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for (viewer, visible) in [
+          (Viewer::Anonymous, false),
+          (Viewer::User(Uuid::parse_str(&outsider).unwrap()), false),
+          (Viewer::User(Uuid::parse_str(&member).unwrap()), true),
+          (Viewer::SignedLink, true),
+      ] {
+          let question = question_doa
+              .get_question(private.question_uuid.to_string(), viewer.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          let answers = answer_doa
+              .get_answers(private.question_uuid.to_string(), AnswerSort::Oldest, viewer.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if question.is_some() != visible || answers.is_empty() == visible {
+              return Err(format!("Incorrect visibility for {:?}", viewer));
+          }
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn shadow_banned_posts_should_only_be_visible_to_their_author_and_moderators(pool: PgPool) -> Result<(), String> {
+      let banned = create_user(&pool, "banned").await?;
+      let other = create_user(&pool, "other").await?;
+      let moderator = create_user(&pool, "moderator").await?;
+
+      sqlx::query("UPDATE users SET role = 'moderator' WHERE user_uuid = $1::uuid")
+          .bind(&moderator)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let keys = StaticKeyProvider::parse("1:MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").map_err(|e| format!("{:?}", e))?;
+      let users_doa = UsersDaoImpl::new(pool.clone(), FieldCipher::new(Arc::new(keys)));
+      let question_doa = QuestionsDaoImpl::new(pool.clone());
+      let answer_doa = AnswersDaoImpl::new(pool);
+
+      let question = create_question(&question_doa, "open question", Visibility::Public, None).await?;
+      let banned_question = question_doa
+          .create_question(Question {
+              title: "banned question".to_owned(),
+              description: "test description".to_owned(),
+              visibility: Visibility::Public,
+              board_uuid: None,
+              tags: Vec::new(),
+              kind: QuestionKind::Question,
+              contest: None,
+          }, Some(banned.clone()), Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      answer_doa
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "banned answer".to_owned(),
+          }, Some(banned.clone()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if users_doa.set_shadow_banned(moderator.clone(), true).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Moderators should not be shadow-banned".to_owned());
+      }
+      if !users_doa.set_shadow_banned(banned.clone(), true).await.map_err(|e| format!("{:?}", e))? {
+          return Err("User should have been shadow-banned".to_owned());
+      }
+
+      for (viewer, visible) in [
+          (Viewer::Anonymous, false),
+          (Viewer::SignedLink, false),
+          (Viewer::User(Uuid::parse_str(&other).unwrap()), false),
+          (Viewer::User(Uuid::parse_str(&banned).unwrap()), true),
+          (Viewer::User(Uuid::parse_str(&moderator).unwrap()), true),
+      ] {
+          let read = question_doa
+              .get_question(banned_question.question_uuid.to_string(), viewer.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          let answers = answer_doa
+              .get_answers(question.question_uuid.to_string(), AnswerSort::Oldest, viewer.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if read.is_some() != visible || answers.is_empty() == visible {
+              return Err(format!("Incorrect visibility for {:?}", viewer));
+          }
+      }
+
+      Ok(())
+  }
+}
+
+mod boards_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{Board, BoardRole, MembershipStatus, Question, QuestionKind, Viewer, Visibility},
+      persistance::{
+          boards_dao::{BoardsDao, BoardsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn create_board_should_add_creator_as_owner(pool: PgPool) -> Result<(), String> {
+      let owner = create_user(&pool, "owner").await?;
+      let doa = BoardsDaoImpl::new(pool);
+
+      let board = doa
+          .create_board(Board { name: "team".to_owned() }, owner.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let member = doa
+          .get_board_member(board.board_uuid.to_string(), owner)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Owner membership was not created.")?;
+
+      if member.role != BoardRole::Owner || member.status != MembershipStatus::Active {
+          return Err(format!("Unexpected owner membership: {:?}", member));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn membership_should_need_both_invite_and_join_or_approval(pool: PgPool) -> Result<(), String> {
+      let owner = create_user(&pool, "owner").await?;
+      let invited = create_user(&pool, "invited").await?;
+      let requester = create_user(&pool, "requester").await?;
+
+      let doa = BoardsDaoImpl::new(pool.clone());
+      let question_doa = QuestionsDaoImpl::new(pool);
+
+      let board = doa
+          .create_board(Board { name: "team".to_owned() }, owner)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = question_doa
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              visibility: Visibility::Private,
+              board_uuid: Some(board.board_uuid),
+              tags: Vec::new(),
+              kind: QuestionKind::Question,
+              contest: None,
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.invite_board_member(board.board_uuid.to_string(), invited.clone(), BoardRole::Member)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      doa.request_board_membership(board.board_uuid.to_string(), requester.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for user in [&invited, &requester] {
+          let visible = question_doa
+              .get_question(question.question_uuid.to_string(), Viewer::User(Uuid::parse_str(user).unwrap()))
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if visible.is_some() {
+              return Err("Pending members should not read private questions.".to_owned());
+          }
+      }
+
+      let joined = doa
+          .request_board_membership(board.board_uuid.to_string(), invited.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let approved = doa
+          .approve_board_member(board.board_uuid.to_string(), requester.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Join request was not approved.")?;
+
+      if joined.status != MembershipStatus::Active || approved.status != MembershipStatus::Active {
+          return Err("Memberships were not activated.".to_owned());
+      }
+
+      let visible = question_doa
+          .get_question(question.question_uuid.to_string(), Viewer::User(Uuid::parse_str(&requester).unwrap()))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if visible.is_none() {
+          return Err("Active members should read private questions.".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod invitations_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{Board, Invitation, InvitationStatus, Role},
+      persistance::{
+          boards_dao::{BoardsDao, BoardsDaoImpl},
+          invitations_dao::{InvitationsDao, InvitationsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn accept_invitation_should_apply_role_and_board_once(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let invited = create_user(&pool, "invited").await?;
+      let other = create_user(&pool, "other").await?;
+
+      let boards_doa = BoardsDaoImpl::new(pool.clone());
+      let doa = InvitationsDaoImpl::new(pool.clone());
+
+      let board = boards_doa
+          .create_board(Board { name: "team".to_owned() }, admin.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let invitation = doa
+          .create_invitation(Invitation {
+              board_uuid: Some(board.board_uuid),
+              role: Some(Role::Moderator),
+              expires_in_seconds: 60,
+          }, admin)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.accept_invitation(invitation.invitation_uuid.to_string(), invited.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Invitation was not accepted.")?;
+
+      let reused = doa
+          .accept_invitation(invitation.invitation_uuid.to_string(), other)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if reused.is_some() {
+          return Err("Invitation was accepted twice.".to_owned());
+      }
+
+      let role: String = sqlx::query_scalar("SELECT role FROM users WHERE user_uuid = $1::uuid")
+          .bind(&invited)
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if role != "moderator" {
+          return Err(format!("Unexpected role: {}", role));
+      }
+
+      if !boards_doa.is_board_member(board.board_uuid.to_string(), invited).await.map_err(|e| format!("{:?}", e))? {
+          return Err("Invited user did not join the board.".to_owned());
+      }
+
+      let invitations = doa.get_invitations().await.map_err(|e| format!("{:?}", e))?;
+
+      if invitations.len() != 1 || invitations[0].status != InvitationStatus::Used {
+          return Err(format!("Unexpected invitations: {:?}", invitations));
+      }
+
+      Ok(())
+  }
+}
+
+mod webhooks_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{
+          Answer, NewWebhook, Pagination, Question, QuestionKind, Visibility, WebhookDeliveryStatus, WebhookEvent,
+      },
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
+      },
+  };
+
+  fn question(title: &str, visibility: Visibility) -> Question {
+      Question {
+          title: title.to_owned(),
+          description: "test description".to_owned(),
+          visibility,
+          board_uuid: None,
+          tags: Vec::new(),
+          kind: QuestionKind::Question,
+          contest: None,
+      }
+  }
+
+  #[sqlx::test]
+  async fn get_pending_digest_should_batch_public_posts_until_delivered(pool: PgPool) -> Result<(), String> {
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let answers_doa = AnswersDaoImpl::new(pool.clone());
+      let doa = WebhooksDaoImpl::new(pool);
+
+      questions_doa
+          .create_question(question("before the first digest", Visibility::Public), None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let digest = doa
+          .get_pending_digest("digest".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !digest.is_empty() {
+          return Err("Expected the first digest to start now.".to_owned());
+      }
+
+      let public = questions_doa
+          .create_question(question("public", Visibility::Public), None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      questions_doa
+          .create_question(question("unlisted", Visibility::Unlisted), None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      answers_doa
+          .create_answer(Answer {
+              question_uuid: public.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let digest = doa
+          .get_pending_digest("digest".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if digest.questions != vec![public] || digest.answers.len() != 1 {
+          return Err(format!(
+              "Expected only the public question and its answer, got {} questions and {} answers",
+              digest.questions.len(),
+              digest.answers.len()
+          ));
+      }
+
+      doa.mark_digest_delivered("digest".to_owned(), digest.until)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let digest = doa
+          .get_pending_digest("digest".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !digest.is_empty() {
+          return Err("Expected delivered posts not to be sent again.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn queue_webhook_deliveries_should_only_queue_public_posts_for_subscribed_webhooks(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let answers_doa = AnswersDaoImpl::new(pool.clone());
+      let doa = WebhooksDaoImpl::new(pool);
+
+      for (url, events) in [
+          ("https://questions.example.com", vec![WebhookEvent::QuestionCreated]),
+          ("https://answers.example.com", vec![WebhookEvent::AnswerCreated, WebhookEvent::AnswerUpdated]),
+      ] {
+          doa.create_webhook(NewWebhook { url: url.to_owned(), events }, "secret".to_owned(), admin.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let public = questions_doa
+          .create_question(question("public", Visibility::Public), Some(admin.clone()), Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let unlisted = questions_doa
+          .create_question(question("unlisted", Visibility::Unlisted), None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let answer = answers_doa
+          .create_answer(Answer {
+              question_uuid: public.question_uuid,
+              content: "test content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let forum_url = "https://forum.example.com".to_owned();
+      let queued = [
+          (WebhookEvent::QuestionCreated, &public.question_uuid, None),
+          (WebhookEvent::QuestionCreated, &unlisted.question_uuid, None),
+          (WebhookEvent::AnswerCreated, &public.question_uuid, Some(answer.answer_uuid)),
+      ];
+
+      let mut counts = Vec::new();
+      for (event, question_uuid, answer_uuid) in queued {
+          let count = doa
+              .queue_webhook_deliveries(event, question_uuid.to_string(), answer_uuid.map(|uuid| uuid.to_string()), forum_url.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          counts.push(count);
+      }
+
+      assert_eq!(counts, vec![1, 0, 1]);
+
+      let deliveries = doa.claim_webhook_deliveries(10, 600).await.map_err(|e| format!("{:?}", e))?;
+      let sent: Vec<_> = deliveries.iter().map(|delivery| (delivery.url.as_str(), delivery.event)).collect();
+
+      assert_eq!(
+          sent,
+          vec![
+              ("https://questions.example.com", WebhookEvent::QuestionCreated),
+              ("https://answers.example.com", WebhookEvent::AnswerCreated),
+          ]
+      );
+      assert_eq!(deliveries[0].payload["question_url"], format!("{}/question/{}", forum_url, public.question_uuid));
+      assert_eq!(deliveries[0].payload["author"], "admin");
+      assert_eq!(deliveries[1].payload["answer_uuid"], answer.answer_uuid.to_string());
+
+      let claimed_again = doa.claim_webhook_deliveries(10, 600).await.map_err(|e| format!("{:?}", e))?;
+
+      if !claimed_again.is_empty() {
+          return Err("Claimed deliveries should not be claimed again before their lease ends".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn fail_webhook_delivery_should_retry_until_given_up(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let doa = WebhooksDaoImpl::new(pool);
+
+      let webhook = doa
+          .create_webhook(
+              NewWebhook { url: "https://hooks.example.com".to_owned(), events: vec![WebhookEvent::QuestionCreated] },
+              "secret".to_owned(),
+              admin,
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for title in ["first", "second"] {
+          let question = questions_doa
+              .create_question(question(title, Visibility::Public), None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          doa.queue_webhook_deliveries(WebhookEvent::QuestionCreated, question.question_uuid.to_string(), None, String::new())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let claimed = doa.claim_webhook_deliveries(10, 600).await.map_err(|e| format!("{:?}", e))?;
+      let [first, second] = claimed.as_slice() else {
+          return Err(format!("Expected two deliveries, got {:?}", claimed));
+      };
+
+      doa.mark_webhook_delivered(first.delivery_uuid.to_string(), 204).await.map_err(|e| format!("{:?}", e))?;
+      doa.fail_webhook_delivery(second.delivery_uuid.to_string(), Some(503), "unavailable".to_owned(), Some(0))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let retried = doa.claim_webhook_deliveries(10, 600).await.map_err(|e| format!("{:?}", e))?;
+
+      let retried: Vec<_> = retried.into_iter().map(|delivery| (delivery.delivery_uuid, delivery.attempts)).collect();
+
+      assert_eq!(retried, vec![(second.delivery_uuid, 1)]);
+
+      doa.fail_webhook_delivery(second.delivery_uuid.to_string(), None, "timed out".to_owned(), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let log = doa
+          .get_webhook_deliveries(webhook.webhook_uuid.to_string(), Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Webhook should exist")?;
+      let mut log: Vec<_> = log
+          .into_iter()
+          .map(|delivery| {
+              (delivery.delivery_uuid, delivery.status, delivery.attempts, delivery.response_status, delivery.next_attempt_at)
+          })
+          .collect();
+      log.sort_by_key(|delivery| delivery.2);
+
+      assert_eq!(
+          log,
+          vec![
+              (first.delivery_uuid, WebhookDeliveryStatus::Delivered, 1, Some(204), None),
+              (second.delivery_uuid, WebhookDeliveryStatus::Failed, 2, None, None),
+          ]
+      );
+
+      if doa.delete_webhook(webhook.webhook_uuid.to_string()).await.map_err(|e| format!("{:?}", e))? {
+          let log = doa
+              .get_webhook_deliveries(webhook.webhook_uuid.to_string(), Pagination::default())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          assert_eq!(log, None);
+      } else {
+          return Err("Webhook should be deleted".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod dead_letters_tests {
+  use sqlx::PgPool;
+
+  use crate::{
+      models::DeadLetterKind,
+      persistance::dead_letters_dao::{DeadLettersDao, DeadLettersDaoImpl},
+  };
+
+  #[sqlx::test]
+  async fn record_dead_letter_failure_should_extend_error_history(pool: PgPool) -> Result<(), String> {
+      let doa = DeadLettersDaoImpl::new(pool);
+
+      let dead_letter = doa
+          .create_dead_letter(
+              DeadLetterKind::WebhookDigest,
+              serde_json::json!({ "questions": [] }),
+              vec!["timeout".to_owned(), "502 Bad Gateway".to_owned()],
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if dead_letter.attempts != 2 {
+          return Err(format!("Expected 2 attempts, got {}.", dead_letter.attempts));
+      }
+
+      doa.record_dead_letter_failure(dead_letter.dead_letter_uuid.to_string(), "connection refused".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let dead_letters = doa.get_dead_letters().await.map_err(|e| format!("{:?}", e))?;
+
+      if dead_letters.len() != 1 {
+          return Err(format!("Expected 1 dead letter, got {}.", dead_letters.len()));
+      }
+
+      if dead_letters[0].attempts != 3 || dead_letters[0].errors.last().map(String::as_str) != Some("connection refused") {
+          return Err(format!("Expected the retry failure to be recorded, got {:?}.", dead_letters[0]));
+      }
+
+      let results = doa
+          .delete_dead_letters(vec![dead_letter.dead_letter_uuid.to_string(), "00000000-0000-0000-0000-000000000000".to_owned()])
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !results[0].deleted || results[1].deleted {
+          return Err(format!("Expected only the existing dead letter to be deleted, got {:?}.", results));
+      }
+
+      Ok(())
+  }
+}
+
+mod jobs_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{JobKind, JobStatus},
+      persistance::jobs_dao::{JobsDao, JobsDaoImpl},
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn claim_next_job_should_run_each_queued_job_once(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let doa = JobsDaoImpl::new(pool);
+
+      let job = doa
+          .create_job(JobKind::PurgeDeletedPosts, admin, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if job.status != JobStatus::Queued || job.started_at.is_some() {
+          return Err(format!("Expected a queued job, got {:?}.", job));
+      }
+
+      let claimed = doa.claim_next_job().await.map_err(|e| format!("{:?}", e))?;
+
+      if claimed.as_ref().map(|claimed| &claimed.job_uuid) != Some(&job.job_uuid) {
+          return Err(format!("Expected the queued job to be claimed, got {:?}.", claimed));
+      }
+
+      if doa.claim_next_job().await.map_err(|e| format!("{:?}", e))?.is_some() {
+          return Err("Expected a running job not to be claimed again.".to_owned());
+      }
+
+      doa.update_job_progress(job.job_uuid.to_string(), 50)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let running = doa
+          .get_job(job.job_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the job to exist.")?;
+
+      if running.status != JobStatus::Running || running.progress != 50 || running.started_at.is_none() {
+          return Err(format!("Expected a running job at 50%, got {:?}.", running));
+      }
+
+      doa.complete_job(job.job_uuid.to_string(), serde_json::json!({ "purged_answers": 1 }))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let done = doa
+          .get_job(job.job_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the job to exist.")?;
+
+      if done.status != JobStatus::Succeeded || done.progress != 100 || done.finished_at.is_none() || done.result.is_none() {
+          return Err(format!("Expected a finished job, got {:?}.", done));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn fail_job_should_record_error(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let doa = JobsDaoImpl::new(pool);
+
+      let job = doa
+          .create_job(JobKind::PurgeDeletedPosts, admin, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      doa.fail_job(job.job_uuid.to_string(), "statement timeout".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let failed = doa
+          .get_job(job.job_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the job to exist.")?;
+
+      if failed.status != JobStatus::Failed || failed.error.as_deref() != Some("statement timeout") {
+          return Err(format!("Expected a failed job, got {:?}.", failed));
+      }
+
+      Ok(())
+  }
+}
+
+mod link_previews_tests {
+  use sqlx::PgPool;
+
+  use crate::{
+      models::LinkPreview,
+      persistance::link_previews_dao::{LinkPreviewsDao, LinkPreviewsDaoImpl},
+  };
+
+  #[sqlx::test]
+  async fn get_link_previews_should_queue_unseen_urls_once(pool: PgPool) -> Result<(), String> {
+      let doa = LinkPreviewsDaoImpl::new(pool);
+      let urls = vec!["https://a.example".to_owned(), "https://b.example".to_owned()];
+
+      let previews = doa.get_link_previews(urls.clone()).await.map_err(|e| format!("{:?}", e))?;
+
+      if !previews.is_empty() {
+          return Err(format!("Expected no previews before fetching, got {:?}.", previews));
+      }
+
+      doa.get_link_previews(urls.clone()).await.map_err(|e| format!("{:?}", e))?;
+
+      let mut claimed = doa.claim_pending_link_previews(10).await.map_err(|e| format!("{:?}", e))?;
+      claimed.sort();
+
+      if claimed != urls {
+          return Err(format!("Expected each URL to be queued once, got {:?}.", claimed));
+      }
+
+      if !doa.claim_pending_link_previews(10).await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("Expected claimed URLs not to be handed out again right away.".to_owned());
+      }
+
+      let preview = LinkPreview {
+          url: "https://a.example".to_owned(),
+          title: Some("A".to_owned()),
+          ..Default::default()
+      };
+
+      doa.save_link_preview(preview.clone()).await.map_err(|e| format!("{:?}", e))?;
+      doa.fail_link_preview("https://b.example".to_owned(), "Not an HTML page".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let previews = doa.get_link_previews(urls).await.map_err(|e| format!("{:?}", e))?;
+
+      if previews != vec![preview] {
+          return Err(format!("Expected only the fetched preview, got {:?}.", previews));
+      }
+
+      Ok(())
+  }
+}
+
+mod cleanup_policies_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::BoardCleanupPolicy,
+      persistance::cleanup_policies_dao::{CleanupPoliciesDao, CleanupPoliciesDaoImpl},
+  };
+
+  async fn create_question(pool: &PgPool, board_uuid: Uuid, title: &str, age_days: i32) -> Result<String, String> {
+      let question_uuid: Uuid = sqlx::query_scalar(
+              "INSERT INTO questions (title, description, board_uuid, created_at, updated_at)
+               VALUES ($1, 'test description', $2, CURRENT_TIMESTAMP - make_interval(days => $3),
+                 CURRENT_TIMESTAMP - make_interval(days => $3))
+               RETURNING question_uuid",
+          )
+          .bind(title)
+          .bind(board_uuid)
+          .bind(age_days)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(question_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn run_cleanup_should_match_preview(pool: PgPool) -> Result<(), String> {
+      let board_uuid: Uuid = sqlx::query_scalar("INSERT INTO boards (name) VALUES ('team') RETURNING board_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let unanswered = create_question(&pool, board_uuid, "unanswered", 400).await?;
+      let answered = create_question(&pool, board_uuid, "answered", 400).await?;
+      let recent = create_question(&pool, board_uuid, "recent", 5).await?;
+
+      sqlx::query("INSERT INTO answers (question_uuid, content) VALUES ($1::uuid, 'test content')")
+          .bind(&answered)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      // The answer counts as activity, so only an old answer leaves the question inactive.
+      sqlx::query("UPDATE answers SET updated_at = CURRENT_TIMESTAMP - make_interval(days => 400)")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = CleanupPoliciesDaoImpl::new(pool);
+
+      let policy = BoardCleanupPolicy {
+          close_inactive_after_days: Some(365),
+          delete_unanswered_after_days: Some(30),
+      };
+
+      let preview = doa
+          .preview_cleanup(board_uuid.to_string(), policy.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if preview.deleted_question_uuids != vec![unanswered.parse::<Uuid>().unwrap()] || preview.closed_question_uuids != vec![answered.parse::<Uuid>().unwrap()] {
+          return Err(format!("Expected to delete {} and close {}, got {:?}.", unanswered, answered, preview));
+      }
+
+      let cleanup = doa
+          .run_cleanup(board_uuid.to_string(), policy.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if cleanup.dry_run
+          || cleanup.deleted_question_uuids != preview.deleted_question_uuids
+          || cleanup.closed_question_uuids != preview.closed_question_uuids
+      {
+          return Err(format!("Expected the run to match the preview {:?}, got {:?}.", preview, cleanup));
+      }
+
+      let again = doa
+          .preview_cleanup(board_uuid.to_string(), policy)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !again.deleted_question_uuids.is_empty() || !again.closed_question_uuids.is_empty() {
+          return Err(format!("Expected nothing left to clean up besides {}, got {:?}.", recent, again));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn set_cleanup_policy_should_require_existing_board(pool: PgPool) -> Result<(), String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('admin', 'admin') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = CleanupPoliciesDaoImpl::new(pool);
+
+      let policy = BoardCleanupPolicy {
+          close_inactive_after_days: Some(365),
+          delete_unanswered_after_days: None,
+      };
+
+      let saved = doa
+          .set_cleanup_policy("00000000-0000-0000-0000-000000000001".to_owned(), policy, user_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if saved.is_some() {
+          return Err(format!("Expected no policy for a missing board, got {:?}.", saved));
+      }
+
+      Ok(())
+  }
+}
+
+mod attachments_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::Attachment,
+      persistance::attachments_dao::{AttachmentsDao, AttachmentsDaoImpl},
+  };
+
+  #[sqlx::test]
+  async fn create_attachment_should_be_readable_by_uuid(pool: PgPool) -> Result<(), String> {
+      let uploader: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('uploader', 'uploader') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question_uuid: Uuid = sqlx::query_scalar("INSERT INTO questions (title, description) VALUES ('title', 'description') RETURNING question_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = AttachmentsDaoImpl::new(pool);
+
+      let created = doa
+          .create_attachment(Attachment {
+              storage_key: "abc123".to_owned(),
+              filename: "diagram.png".to_owned(),
+              content_type: "image/png".to_owned(),
+              size_bytes: 42,
+              question_uuid: Some(question_uuid),
+              answer_uuid: None,
+          }, uploader.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let read = doa
+          .get_attachment(created.attachment_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if read.as_ref() != Some(&created) || created.storage_key != "abc123" || created.uploader_uuid != Some(uploader) {
+          return Err(format!("Expected {:?} to be read back, got {:?}.", created, read));
+      }
+
+      Ok(())
+  }
+}
+
+mod flags_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{DBError, Flag, FlagReason, FlagStatus, Pagination},
+      persistance::flags_dao::{FlagsDao, FlagsDaoImpl},
+  };
+
+  #[sqlx::test]
+  async fn flags_should_be_unique_while_open_and_leave_queue_when_resolved(pool: PgPool) -> Result<(), String> {
+      let reporter: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('reporter', 'reporter') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question_uuid: Uuid = sqlx::query_scalar("INSERT INTO questions (title, description) VALUES ('title', 'description') RETURNING question_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = FlagsDaoImpl::new(pool);
+      let spam = || Flag {
+          reason: FlagReason::Spam,
+          details: None,
+      };
+
+      let created = doa
+          .create_flag(question_uuid.to_string(), None, Some(reporter.to_string()), spam())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = doa
+          .create_flag(question_uuid.to_string(), None, Some(reporter.to_string()), spam())
+          .await;
+
+      if !matches!(result, Err(DBError::UniqueViolation(_))) {
+          return Err("Expected a second open flag on the same post to be rejected.".to_owned());
+      }
+
+      let open = doa
+          .get_flags(FlagStatus::Open, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if open != vec![created.clone()] {
+          return Err(format!("Expected only the created flag in the queue, got {:?}", open));
+      }
+
+      let resolved = doa
+          .resolve_flag(created.flag_uuid.to_string(), reporter.to_string(), FlagStatus::Upheld, Some("Removed.".to_owned()))
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the flag to be resolved.")?;
+
+      if resolved.status != FlagStatus::Upheld || resolved.resolved_at.is_none() {
+          return Err("Expected an upheld flag with a resolution time.".to_owned());
+      }
+
+      let again = doa
+          .resolve_flag(created.flag_uuid.to_string(), reporter.to_string(), FlagStatus::Dismissed, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if again.is_some() {
+          return Err("Expected a resolved flag to stay resolved.".to_owned());
+      }
+
+      let open = doa
+          .get_flags(FlagStatus::Open, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !open.is_empty() {
+          return Err("Expected the queue to be empty.".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod moderation_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{
+          Answer, DBError, Flag, FlagReason, ModerationActionKind, ModerationQueueKind, ModerationQueueQuery, Pagination,
+          PostKind, Question, Viewer,
+      },
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          flags_dao::{FlagsDao, FlagsDaoImpl},
+          moderation_dao::{ModerationDao, ModerationDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  #[sqlx::test]
+  async fn new_user_posts_should_leave_queue_once_approved(pool: PgPool) -> Result<(), String> {
+      let author: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('author', 'author') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let moderator: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('moderator', 'moderator') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question_uuid: Uuid = sqlx::query_scalar("INSERT INTO questions (title, description, author_uuid) VALUES ('title', 'description', $1) RETURNING question_uuid")
+          .bind(author)
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      FlagsDaoImpl::new(pool.clone())
+          .create_flag(
+              question_uuid.to_string(),
+              None,
+              Some(moderator.to_string()),
+              Flag {
+                  reason: FlagReason::Spam,
+                  details: None,
+              },
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = ModerationDaoImpl::new(pool);
+
+      let queue = doa
+          .get_moderation_queue(ModerationQueueQuery::default(), Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if queue.len() != 1 || queue[0].open_flags != 1 || queue[0].flag_reasons != vec![FlagReason::Spam] || !queue[0].new_author {
+          return Err(format!("Expected the flagged new-user question in the queue, got {:?}", queue));
+      }
+
+      let offensive = ModerationQueueQuery {
+          kind: ModerationQueueKind::Flagged,
+          reason: Some(FlagReason::Offensive),
+      };
+      let queue = doa
+          .get_moderation_queue(offensive, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !queue.is_empty() {
+          return Err("Expected no posts flagged as offensive.".to_owned());
+      }
+
+      let action = doa
+          .record_moderation_action(
+              moderator.to_string(),
+              question_uuid.to_string(),
+              None,
+              ModerationActionKind::Approve,
+              "Looks fine.".to_owned(),
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if action.resolved_flags != 1 {
+          return Err(format!("Expected the open flag to be resolved, got {}", action.resolved_flags));
+      }
+
+      let queue = doa
+          .get_moderation_queue(ModerationQueueQuery::default(), Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !queue.is_empty() {
+          return Err(format!("Expected the approved question to leave the queue, got {:?}", queue));
+      }
+
+      let actions = doa
+          .get_moderation_actions(Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if actions != vec![action] {
+          return Err(format!("Expected the approval in the moderation log, got {:?}", actions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn held_posts_should_stay_out_of_listings_until_approved(pool: PgPool) -> Result<(), String> {
+      let author: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('author', 'author') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let moderator: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('moderator', 'moderator') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(
+              Question {
+                  title: "cheap watches".to_owned(),
+                  description: "buy now".to_owned(),
+                  ..Default::default()
+              },
+              Some(author.to_string()),
+              Vec::new(),
+              None,
+              None,
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = ModerationDaoImpl::new(pool.clone());
+
+      let recent = doa
+          .get_recent_posts(author.to_string(), 10)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if recent != vec!["cheap watches buy now".to_owned()] {
+          return Err(format!("Expected the author's question as a recent post, got {:?}", recent));
+      }
+
+      doa.hold_post(question.question_uuid.to_string(), None, "Contains 6 links.".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let questions = QuestionsDaoImpl::new(pool.clone());
+
+      let listed = questions.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?;
+
+      if !listed.is_empty() {
+          return Err(format!("Expected the held question to be left out, got {:?}", listed));
+      }
+
+      let own = questions
+          .get_questions_by_author(author.to_string(), Pagination::default(), Viewer::User(author))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if own.len() != 1 {
+          return Err(format!("Expected the author to still see their held question, got {:?}", own));
+      }
+
+      let queue = doa
+          .get_moderation_queue(ModerationQueueQuery::default(), Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if queue.len() != 1 || queue[0].flag_reasons != vec![FlagReason::Spam] {
+          return Err(format!("Expected the held question flagged as spam in the queue, got {:?}", queue));
+      }
+
+      doa.record_moderation_action(
+              moderator.to_string(),
+              question.question_uuid.to_string(),
+              None,
+              ModerationActionKind::Approve,
+              "Not spam.".to_owned(),
+          )
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let listed = questions.get_questions(Viewer::Anonymous, None).await.map_err(|e| format!("{:?}", e))?;
+
+      if listed.len() != 1 {
+          return Err(format!("Expected the approved question to be listed, got {:?}", listed));
+      }
+
+      Ok(())
+  }
+  #[sqlx::test]
+  async fn get_recent_post_times_should_skip_old_posts_but_count_deleted_ones(pool: PgPool) -> Result<(), String> {
+      let author: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('author', 'author') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query(
+          "INSERT INTO questions (title, description, author_uuid, created_at, deleted_at) VALUES
+             ('recent', 'description', $1, CURRENT_TIMESTAMP - INTERVAL '10 minutes', NULL),
+             ('old', 'description', $1, CURRENT_TIMESTAMP - INTERVAL '2 hours', NULL),
+             ('deleted', 'description', $1, CURRENT_TIMESTAMP - INTERVAL '5 minutes', CURRENT_TIMESTAMP)",
+      )
+      .bind(author)
+      .execute(&pool)
+      .await
+      .map_err(|e| format!("{:?}", e))?;
+
+      let doa = ModerationDaoImpl::new(pool);
+
+      let questions = doa
+          .get_recent_post_times(author.to_string(), PostKind::Question, 60)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if questions.len() != 2 {
+          return Err(format!("Expected the recent and the deleted question, got {:?}", questions));
+      }
+
+      let answers = doa
+          .get_recent_post_times(author.to_string(), PostKind::Answer, 60)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !answers.is_empty() {
+          return Err(format!("Expected no recent answers, got {:?}", answers));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn create_posts_should_refuse_authors_at_their_posting_limit(pool: PgPool) -> Result<(), String> {
+      let author: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('author', 'author') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let answers = AnswersDaoImpl::new(pool);
+
+      let question = || Question {
+          title: "test title".to_owned(),
+          description: "test description".to_owned(),
+          ..Default::default()
+      };
+
+      let first = questions
+          .create_question(question(), Some(author.to_string()), Vec::new(), None, Some(1))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      questions
+          .delete_question(first.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = questions
+          .create_question(question(), Some(author.to_string()), Vec::new(), None, Some(1))
+          .await;
+
+      if !matches!(result, Err(DBError::PostingLimitReached)) {
+          return Err(format!("Expected the deleted question to count towards the limit, got {:?}", result));
+      }
+
+      let second = questions
+          .create_question(question(), Some(author.to_string()), Vec::new(), None, Some(2))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let answer = || Answer {
+          question_uuid: second.question_uuid,
+          content: "test content".to_owned(),
+      };
+
+      answers
+          .create_answer(answer(), Some(author.to_string()), Some(1))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let result = answers.create_answer(answer(), Some(author.to_string()), Some(1)).await;
+
+      if !matches!(result, Err(DBError::PostingLimitReached)) {
+          return Err(format!("Expected the answer over the limit to be refused, got {:?}", result));
+      }
+
+      Ok(())
+  }
+}
+
+mod tags_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{
+          Answer, Board, BoardTagRules, Pagination, Question, TagAnswerer, TagStats, TagUsage, Viewer, Visibility,
+      },
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          boards_dao::{BoardsDao, BoardsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          tags_dao::{TagsDao, TagsDaoImpl},
+      },
+  };
+
+  #[sqlx::test]
+  async fn get_tag_suggestions_should_count_public_questions_by_prefix(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+
+      for (tags, visibility) in [
+          (vec!["rust", "async"], Visibility::Public),
+          (vec!["rust"], Visibility::Public),
+          (vec!["rustls"], Visibility::Public),
+          (vec!["rustls"], Visibility::Unlisted),
+          (vec!["ruby"], Visibility::Public),
+      ] {
+          questions
+              .create_question(Question {
+                  title: "title".to_owned(),
+                  description: "description".to_owned(),
+                  visibility,
+                  tags: tags.into_iter().map(str::to_owned).collect(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let doa = TagsDaoImpl::new(pool);
+
+      let suggestions = doa
+          .get_tag_suggestions("rus".to_owned(), 10)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let expected = vec![
+          TagUsage { name: "rust".to_owned(), questions: 2 },
+          TagUsage { name: "rustls".to_owned(), questions: 1 },
+      ];
+
+      if suggestions != expected {
+          return Err(format!("Expected {:?}, got {:?}", expected, suggestions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_tag_stats_should_aggregate_public_questions(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let answers = AnswersDaoImpl::new(pool.clone());
+      let answerer: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('answerer', 'hash') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let mut question_uuids = Vec::new();
+
+      for (tags, visibility) in [
+          (vec!["rust"], Visibility::Public),
+          (vec!["rust", "async"], Visibility::Public),
+          (vec!["rust"], Visibility::Unlisted),
+      ] {
+          let question = questions
+              .create_question(Question {
+                  title: "title".to_owned(),
+                  description: "description".to_owned(),
+                  visibility,
+                  tags: tags.into_iter().map(str::to_owned).collect(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          question_uuids.push(question.question_uuid);
+      }
+
+      for question_uuid in [&question_uuids[0], &question_uuids[0], &question_uuids[2]] {
+          answers
+              .create_answer(Answer {
+                  question_uuid: *question_uuid,
+                  content: "content".to_owned(),
+              }, Some(answerer.to_string()), None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let doa = TagsDaoImpl::new(pool);
+
+      let stats = doa
+          .get_tag_stats("rust".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected stats for rust")?;
+
+      if stats.questions != 2 || stats.unanswered_rate != 0.5 {
+          return Err(format!("Expected 2 questions, half unanswered, got {:?}", stats));
+      }
+
+      let weeks: Vec<i64> = stats.questions_per_week.iter().map(|week| week.questions).collect();
+
+      if weeks.len() != TagStats::WEEKS as usize || weeks.last() != Some(&2) || weeks.iter().sum::<i64>() != 2 {
+          return Err(format!("Expected both questions in the current week, got {:?}", weeks));
+      }
+
+      let expected = vec![TagAnswerer {
+          user_uuid: answerer,
+          username: "answerer".to_owned(),
+          answers: 2,
+      }];
+
+      if stats.top_answerers != expected {
+          return Err(format!("Expected {:?}, got {:?}", expected, stats.top_answerers));
+      }
+
+      let unused = doa
+          .get_tag_stats("cobol".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if unused.is_some() {
+          return Err(format!("Expected no stats for an unused tag, got {:?}", unused));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn board_tag_rules_should_be_replaced_and_deleted(pool: PgPool) -> Result<(), String> {
+      let owner: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('owner', 'hash') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let board = BoardsDaoImpl::new(pool.clone())
+          .create_board(Board { name: "rust".to_owned() }, owner.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let doa = TagsDaoImpl::new(pool);
+
+      let missing = doa
+          .set_board_tag_rules(Uuid::nil().to_string(), BoardTagRules::default(), owner.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if missing.is_some() {
+          return Err(format!("Expected no rules for a missing board, got {:?}", missing));
+      }
+
+      for required in [vec!["async"], vec!["rust", "cargo"]] {
+          let rules = BoardTagRules {
+              required_tags: required.into_iter().map(str::to_owned).collect(),
+              forbidden_tags: vec!["off-topic".to_owned()],
+          };
+
+          doa.set_board_tag_rules(board.board_uuid.to_string(), rules, owner.to_string())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let rules = doa
+          .get_board_tag_rules(board.board_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected rules for the board")?;
+
+      if rules.required_tags != vec!["rust", "cargo"] || rules.forbidden_tags != vec!["off-topic"] {
+          return Err(format!("Expected the replaced rules, got {:?}", rules));
+      }
+
+      let overlapping = BoardTagRules {
+          required_tags: vec!["rust".to_owned()],
+          forbidden_tags: vec!["rust".to_owned()],
+      };
+
+      if doa.set_board_tag_rules(board.board_uuid.to_string(), overlapping, owner.to_string()).await.is_ok() {
+          return Err("Expected overlapping rules to be rejected".to_owned());
+      }
+
+      let deleted = doa
+          .delete_board_tag_rules(board.board_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let deleted_again = doa
+          .delete_board_tag_rules(board.board_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !deleted || deleted_again {
+          return Err(format!("Expected one delete, got {} then {}", deleted, deleted_again));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn approved_pending_tags_should_be_added_to_proposing_questions(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let doa = TagsDaoImpl::new(pool);
+
+      let mut created = Vec::new();
+
+      for tags in [vec!["rust"], vec![]] {
+          let question = questions
+              .create_question(
+                  Question {
+                      title: "title".to_owned(),
+                      description: "description".to_owned(),
+                      tags: tags.into_iter().map(str::to_owned).collect(),
+                      ..Default::default()
+                  },
+                  None,
+                  Vec::new(),
+                  None,
+                  None,
+              )
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          created.push(question.question_uuid);
+      }
+
+      let unused = doa
+          .get_unused_tags(vec!["brand-new".to_owned(), "rust".to_owned()])
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if unused != vec!["brand-new".to_owned()] {
+          return Err(format!("Expected only the new tag to be unused, got {:?}", unused));
+      }
+
+      doa.add_pending_tags(created[1].to_string(), vec!["brand-new".to_owned()], None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let pending = doa.get_pending_tags(Pagination::default()).await.map_err(|e| format!("{:?}", e))?;
+
+      if pending.len() != 1 || pending[0].name != "brand-new" || pending[0].question_uuids != vec![created[1]] {
+          return Err(format!("Expected the proposed tag, got {:?}", pending));
+      }
+
+      let approved = doa
+          .approve_pending_tag("brand-new".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if approved.map(|resolution| resolution.questions) != Some(1) {
+          return Err("Expected the tag to be added to one question.".to_owned());
+      }
+
+      let question = questions
+          .get_question(created[1].to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the question to exist.")?;
+
+      if question.tags != vec!["brand-new".to_owned()] {
+          return Err(format!("Expected the approved tag on the question, got {:?}", question.tags));
+      }
+
+      let approved_again = doa
+          .approve_pending_tag("brand-new".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let rejected = doa
+          .reject_pending_tag("brand-new".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if approved_again.is_some() || rejected.is_some() {
+          return Err("Expected no pending tag once approved.".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_accepted_answers_should_list_public_questions_of_the_tag_by_title(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let answers = AnswersDaoImpl::new(pool.clone());
+
+      for (title, tags, visibility, accepted) in [
+          ("b title", vec!["rust"], Visibility::Public, true),
+          ("a title", vec!["rust", "async"], Visibility::Public, true),
+          ("c title", vec!["rust"], Visibility::Public, false),
+          ("d title", vec!["rust"], Visibility::Unlisted, true),
+          ("e title", vec!["ruby"], Visibility::Public, true),
+      ] {
+          let question = questions
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "description".to_owned(),
+                  visibility,
+                  tags: tags.into_iter().map(str::to_owned).collect(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          let answer = answers
+              .create_answer(Answer {
+                  question_uuid: question.question_uuid,
+                  content: format!("answer to {}", title),
+              }, None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          if accepted {
+              sqlx::query("UPDATE questions SET accepted_answer_uuid = $2::uuid WHERE question_uuid = $1::uuid")
+                  .bind(question.question_uuid)
+                  .bind(answer.answer_uuid)
+                  .execute(&pool)
+                  .await
+                  .map_err(|e| format!("{:?}", e))?;
+          }
+      }
+
+      let accepted = TagsDaoImpl::new(pool)
+          .get_accepted_answers("rust".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let listed: Vec<_> = accepted.iter().map(|answer| (answer.title.as_str(), answer.answer.as_str())).collect();
+
+      if listed != [("a title", "answer to a title"), ("b title", "answer to b title")] {
+          return Err(format!("Unexpected accepted answers {:?}", listed));
+      }
+
+      Ok(())
+  }
+}
+
+mod audit_tests {
+  use serde_json::json;
+  use sqlx::PgPool;
+  use time::{Date, Month, PrimitiveDateTime, Time};
+  use uuid::Uuid;
+
+  use crate::{
+      models::{AuditAction, AuditEntry, AuditTarget, Pagination},
+      persistance::audit_dao::{AuditDao, AuditDaoImpl},
+  };
+
+  fn entry(action: AuditAction) -> AuditEntry {
+      AuditEntry {
+          actor_uuid: Uuid::parse_str("00000000-0000-0000-0000-000000000789").unwrap(),
+          action,
+          target_type: AuditTarget::Question,
+          target_uuid: Some("00000000-0000-0000-0000-000000000123".to_owned()),
+          payload: json!({ "reason": "spam" }),
+      }
+  }
+
+  #[sqlx::test]
+  async fn get_audit_log_should_list_entries_since_oldest_first(pool: PgPool) -> Result<(), String> {
+      let doa = AuditDaoImpl::new(pool.clone());
+
+      for action in [AuditAction::CloseQuestion, AuditAction::ReopenQuestion, AuditAction::DeleteQuestions] {
+          doa.record_audit_entry(entry(action))
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      sqlx::query("ALTER TABLE audit_log DISABLE TRIGGER audit_log_no_update_or_delete")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      sqlx::query("UPDATE audit_log SET created_at = '2024-01-01 00:00:00' WHERE action = 'close-question'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let records = doa
+          .get_audit_log(None, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if records.len() != 3 || records[0].action != AuditAction::CloseQuestion {
+          return Err(format!("Expected the backdated entry first, got {:?}", records));
+      }
+
+      if records[0].payload != json!({ "reason": "spam" }) || records[0].target_type != AuditTarget::Question {
+          return Err(format!("Unexpected record: {:?}", records[0]));
+      }
+
+      let since = PrimitiveDateTime::new(
+          Date::from_calendar_date(2025, Month::January, 1).map_err(|e| format!("{:?}", e))?,
+          Time::MIDNIGHT,
+      );
+      let records = doa
+          .get_audit_log(Some(since), Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let actions: Vec<AuditAction> = records.iter().map(|record| record.action).collect();
+
+      if actions.len() != 2 || actions.contains(&AuditAction::CloseQuestion) {
+          return Err(format!("Expected the two recent entries, got {:?}", actions));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn audit_log_should_be_append_only(pool: PgPool) -> Result<(), String> {
+      let doa = AuditDaoImpl::new(pool.clone());
+
+      doa.record_audit_entry(entry(AuditAction::DeleteQuestions))
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for statement in [
+          "UPDATE audit_log SET action = 'restore-question'",
+          "DELETE FROM audit_log",
+          "TRUNCATE audit_log",
+      ] {
+          if sqlx::query(statement).execute(&pool).await.is_ok() {
+              return Err(format!("Expected `{}` to be rejected", statement));
+          }
+      }
+
+      let records = doa
+          .get_audit_log(None, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if records.len() != 1 || records[0].action != AuditAction::DeleteQuestions {
+          return Err(format!("Expected the entry to be kept, got {:?}", records));
+      }
+
+      Ok(())
+  }
+}
+
+mod erasure_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{ErasureAction, Question, QuestionView, QuestionViewer},
+      persistance::{
+          erasure_dao::{ErasureDao, ErasureDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          stats_dao::{StatsDao, StatsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn erase_user_should_purge_and_anonymize_rows_and_keep_a_report(pool: PgPool) -> Result<(), String> {
+      let admin = create_user(&pool, "admin").await?;
+      let leaving = create_user(&pool, "leaving").await?;
+      let doa = ErasureDaoImpl::new(pool.clone());
+
+      sqlx::query("UPDATE users SET avatar_key = 'avatar-1' WHERE user_uuid = $1::uuid")
+          .bind(&leaving)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, Some(leaving.clone()), Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("INSERT INTO question_followers (user_uuid, question_uuid) VALUES ($1::uuid, $2::uuid)")
+          .bind(&leaving)
+          .bind(question.question_uuid)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      StatsDaoImpl::new(pool.clone())
+          .record_question_views(vec![QuestionView {
+              question_uuid: question.question_uuid,
+              viewer: QuestionViewer::User(Uuid::parse_str(&leaving).map_err(|e| format!("{:?}", e))?),
+          }])
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let report = doa
+          .erase_user(leaving.clone(), admin.clone(), Some(30))
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Expected the user to be erased")?;
+
+      let rows = |table: &str, column: &str| {
+          report
+              .checks
+              .iter()
+              .find(|check| check.table == table && check.column == column)
+              .map(|check| (check.action, check.rows))
+      };
+
+      if rows("question_followers", "user_uuid") != Some((ErasureAction::Purged, 1))
+          || rows("question_viewers", "viewer_hash") != Some((ErasureAction::Purged, 1))
+          || rows("questions", "author_uuid") != Some((ErasureAction::Anonymized, 1))
+          || rows("audit_log", "actor_uuid") != Some((ErasureAction::Retained, 0))
+      {
+          return Err(format!("Unexpected checks {:?}", report.checks));
+      }
+      if report.deleted_objects.len() != 3 || report.backups.contains_data_until.is_none() {
+          return Err(format!("Expected avatar objects and backup expiry in {:?}", report));
+      }
+
+      let remaining: (i64, Option<Uuid>) = sqlx::query_as(
+          "SELECT (SELECT COUNT(*) FROM users WHERE user_uuid = $1::uuid), (SELECT author_uuid FROM questions WHERE question_uuid = $2::uuid)",
+        )
+        .bind(&leaving)
+        .bind(question.question_uuid)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+      if remaining != (0, None) {
+          return Err(format!("Expected the user gone and the question kept without author, got {:?}", remaining));
+      }
+
+      let stored = doa
+          .get_erasure_report(report.report_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if stored.as_ref() != Some(&report) {
+          return Err(format!("Expected the stored report to match, got {:?}", stored));
+      }
+
+      if doa.erase_user(leaving, admin, None).await.map_err(|e| format!("{:?}", e))?.is_some() {
+          return Err("Erasing an erased user should find nothing".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod retention_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{RetentionCategory, RetentionStats},
+      persistance::retention_dao::{RetentionDao, RetentionDaoImpl},
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn purges_should_only_remove_rows_past_their_period(pool: PgPool) -> Result<(), String> {
+      let user = create_user(&pool, "user").await?;
+      let doa = RetentionDaoImpl::new(pool.clone());
+
+      for days in [400, 10] {
+          sqlx::query("INSERT INTO audit_log (action, target_type, created_at) VALUES ('close-question', 'question', CURRENT_TIMESTAMP - make_interval(days => $1))")
+              .bind(days)
+              .execute(&pool)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          sqlx::query("INSERT INTO user_ip_history (user_uuid, ip_encrypted, seen_at) VALUES ($1::uuid, '\\x00', CURRENT_TIMESTAMP - make_interval(days => $2))")
+              .bind(&user)
+              .bind(days)
+              .execute(&pool)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let purged_audit_log = doa.purge_audit_log(365).await.map_err(|e| format!("{:?}", e))?;
+      let purged_ip_addresses = doa.purge_ip_addresses(90).await.map_err(|e| format!("{:?}", e))?;
+
+      if purged_audit_log != 1 || purged_ip_addresses != 1 {
+          return Err(format!("Expected one row of each, purged {} and {}", purged_audit_log, purged_ip_addresses));
+      }
+
+      let remaining: i64 = sqlx::query_scalar("SELECT (SELECT COUNT(*) FROM audit_log) + (SELECT COUNT(*) FROM user_ip_history)")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if remaining != 2 {
+          return Err(format!("Expected the recent rows to stay, {} left", remaining));
+      }
+
+      // Outside the purge, the audit log is still append-only.
+      if sqlx::query("DELETE FROM audit_log").execute(&pool).await.is_ok() {
+          return Err("Deleting audit entries should still be refused".to_owned());
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn record_purge_should_keep_the_last_run_and_a_running_total(pool: PgPool) -> Result<(), String> {
+      let doa = RetentionDaoImpl::new(pool.clone());
+
+      for removed in [3, 4] {
+          doa.record_purge(RetentionCategory::IpAddresses, removed)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let stats = doa.get_retention_stats().await.map_err(|e| format!("{:?}", e))?;
+
+      match stats.as_slice() {
+          [RetentionStats { category: RetentionCategory::IpAddresses, last_run_at: Some(_), last_removed: 4, total_removed: 7, .. }] => Ok(()),
+          _ => Err(format!("Unexpected stats {:?}", stats)),
+      }
+  }
+}
+
+mod faq_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::Question,
+      persistance::{
+          faq_dao::{FaqDao, FaqDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  async fn create_answer(pool: &PgPool, question_uuid: &str) -> Result<String, String> {
+      let answer_uuid: Uuid = sqlx::query_scalar("INSERT INTO answers (question_uuid, content) VALUES ($1::uuid, 'test content') RETURNING answer_uuid")
+          .bind(question_uuid)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(answer_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn set_faq_entry_should_only_accept_an_answer_of_the_question(pool: PgPool) -> Result<(), String> {
+      let moderator = create_user(&pool, "moderator").await?;
+      let questions_dao = QuestionsDaoImpl::new(pool.clone());
+      let doa = FaqDaoImpl::new(pool.clone());
+
+      let mut questions = Vec::new();
+      for title in ["first title", "second title"] {
+          let question = questions_dao
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  tags: vec!["rust".to_owned()],
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          questions.push(question.question_uuid);
+      }
+
+      let other_answer = create_answer(&pool, &questions[1].to_string()).await?;
+      let first_answer = create_answer(&pool, &questions[0].to_string()).await?;
+      let second_answer = create_answer(&pool, &questions[0].to_string()).await?;
+
+      let set = |answer_uuid: &String| doa.set_faq_entry(questions[0].to_string(), answer_uuid.clone(), moderator.clone());
+
+      if set(&other_answer).await.map_err(|e| format!("{:?}", e))? {
+          return Err("An answer of another question should be refused".to_owned());
+      }
+
+      for answer_uuid in [&first_answer, &second_answer] {
+          if !set(answer_uuid).await.map_err(|e| format!("{:?}", e))? {
+              return Err("Expected the answer to be curated".to_owned());
+          }
+      }
+
+      let entries = doa.get_faq_entries().await.map_err(|e| format!("{:?}", e))?;
+
+      if entries.len() != 1 || entries[0].answer_uuid.to_string() != second_answer || entries[0].tags != vec!["rust".to_owned()] {
+          return Err(format!("Expected the replaced answer only, got {:?}", entries));
+      }
+
+      sqlx::query("UPDATE answers SET deleted_at = CURRENT_TIMESTAMP WHERE answer_uuid = $1::uuid")
+          .bind(&second_answer)
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if !doa.get_faq_entries().await.map_err(|e| format!("{:?}", e))?.is_empty() {
+          return Err("Entries of deleted answers should not be served".to_owned());
+      }
+
+      let removed = doa.remove_faq_entry(questions[0].to_string()).await.map_err(|e| format!("{:?}", e))?;
+      let removed_again = doa.remove_faq_entry(questions[0].to_string()).await.map_err(|e| format!("{:?}", e))?;
+
+      if !removed || removed_again {
+          return Err(format!("Expected a single removal, got {} and {}", removed, removed_again));
+      }
+
+      Ok(())
+  }
+}
+
+mod contests_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{Answer, AnswerSort, Contest, Question, Viewer},
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  async fn create_contest(pool: &PgPool) -> Result<String, String> {
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "Fastest parser".to_owned(),
+              description: "Post your fastest parser.".to_owned(),
+              tags: vec!["rust".to_owned()],
+              contest: Some(Contest { answer_hours: 24, voting_hours: 24 }),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(question.question_uuid.to_string())
+  }
+
+  /// Moves the contest so that it was revealed `reveal_hours_ago` and ends in `ends_in_hours`.
+  async fn move_contest(pool: &PgPool, question_uuid: &str, reveal_hours_ago: i32, ends_in_hours: i32) -> Result<(), String> {
+      sqlx::query(
+          "UPDATE questions SET contest_reveal_at = CURRENT_TIMESTAMP - make_interval(hours => $2),
+           contest_ends_at = CURRENT_TIMESTAMP + make_interval(hours => $3) WHERE question_uuid = $1::uuid",
+      )
+      .bind(question_uuid)
+      .bind(reveal_hours_ago)
+      .bind(ends_in_hours)
+      .execute(pool)
+      .await
+      .map_err(|e| format!("{:?}", e))?;
+
+      Ok(())
+  }
+
+  async fn vote(pool: &PgPool, answer_uuid: &str, user_uuid: &str, hours_ago: i32) -> Result<(), String> {
+      sqlx::query(
+          "INSERT INTO answer_votes (answer_uuid, user_uuid, value, created_at)
+           VALUES ($1::uuid, $2::uuid, 1, CURRENT_TIMESTAMP - make_interval(hours => $3))",
+      )
+      .bind(answer_uuid)
+      .bind(user_uuid)
+      .bind(hours_ago)
+      .execute(pool)
+      .await
+      .map_err(|e| format!("{:?}", e))?;
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn get_answers_should_hide_contest_answers_from_others_until_reveal(pool: PgPool) -> Result<(), String> {
+      let question_uuid = create_contest(&pool).await?;
+      let doa = AnswersDaoImpl::new(pool.clone());
+
+      let mut authors = Vec::new();
+      for username in ["alice", "bob"] {
+          let author = create_user(&pool, username).await?;
+          doa.create_answer(Answer {
+              question_uuid: Uuid::parse_str(&question_uuid).unwrap(),
+              content: format!("{}'s parser", username),
+          }, Some(author.clone()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+          authors.push(author);
+      }
+
+      let visible = |viewer: Viewer| {
+          let doa = &doa;
+          let question_uuid = question_uuid.clone();
+          async move {
+              doa.get_answers(question_uuid, AnswerSort::Oldest, viewer)
+                  .await
+                  .map(|answers| answers.into_iter().map(|answer| answer.content).collect::<Vec<_>>())
+                  .map_err(|e| format!("{:?}", e))
+          }
+      };
+
+      assert_eq!(visible(Viewer::Anonymous).await?, Vec::<String>::new());
+      assert_eq!(visible(Viewer::User(Uuid::parse_str(&authors[0]).unwrap())).await?, vec!["alice's parser".to_owned()]);
+
+      move_contest(&pool, &question_uuid, 1, 1).await?;
+
+      assert_eq!(visible(Viewer::Anonymous).await?, vec!["alice's parser".to_owned(), "bob's parser".to_owned()]);
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn decide_contests_should_only_count_votes_cast_while_voting(pool: PgPool) -> Result<(), String> {
+      let question_uuid = create_contest(&pool).await?;
+      let answers_dao = AnswersDaoImpl::new(pool.clone());
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let mut answers = Vec::new();
+      for content in ["first", "second"] {
+          let answer = answers_dao
+              .create_answer(Answer {
+                  question_uuid: Uuid::parse_str(&question_uuid).unwrap(),
+                  content: content.to_owned(),
+              }, None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+          answers.push(answer.answer_uuid);
+      }
+
+      // Voting ran from 3 hours ago until an hour ago.
+      move_contest(&pool, &question_uuid, 3, -1).await?;
+
+      for (username, answer_uuid, hours_ago) in [("early", &answers[0], 4), ("late", &answers[0], 0), ("voter", &answers[1], 2)] {
+          let voter = create_user(&pool, username).await?;
+          vote(&pool, &answer_uuid.to_string(), &voter, hours_ago).await?;
+      }
+
+      let decided = doa.decide_contests().await.map_err(|e| format!("{:?}", e))?;
+      assert_eq!(decided, 1);
+
+      let question = doa
+          .get_question(question_uuid.clone(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Question should exist")?;
+      assert_eq!(question.contest.and_then(|contest| contest.winner_uuid), Some(answers[1]));
+
+      let decided = doa.decide_contests().await.map_err(|e| format!("{:?}", e))?;
+      assert_eq!(decided, 0);
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn decide_contests_should_leave_the_winner_unset_without_a_positive_score(pool: PgPool) -> Result<(), String> {
+      let question_uuid = create_contest(&pool).await?;
+      let answers_dao = AnswersDaoImpl::new(pool.clone());
+      let doa = QuestionsDaoImpl::new(pool.clone());
+
+      let answer = answers_dao
+          .create_answer(Answer {
+              question_uuid: Uuid::parse_str(&question_uuid).unwrap(),
+              content: "unvoted".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      move_contest(&pool, &question_uuid, 3, -1).await?;
+
+      // Only a vote cast before the reveal, which does not count.
+      let voter = create_user(&pool, "early").await?;
+      vote(&pool, &answer.answer_uuid.to_string(), &voter, 4).await?;
+
+      let decided = doa.decide_contests().await.map_err(|e| format!("{:?}", e))?;
+      assert_eq!(decided, 1);
+
+      let question = doa
+          .get_question(question_uuid, Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Question should exist")?;
+      assert_eq!(question.contest.map(|contest| contest.winner_uuid), Some(None));
+
+      let decided = doa.decide_contests().await.map_err(|e| format!("{:?}", e))?;
+      assert_eq!(decided, 0);
+
+      Ok(())
+  }
+}
+
+mod notification_settings_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{
+          NotificationChannels, NotificationKind, NotificationKindSettings, NotificationSettings, Pagination, Question,
+      },
+      persistance::{
+          follows_dao::{FollowsDao, FollowsDaoImpl},
+          notifications_dao::{NotificationsDao, NotificationsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  fn settings(webhook_url: &str) -> NotificationSettings {
+      NotificationSettings {
+          kinds: vec![
+              NotificationKindSettings {
+                  kind: NotificationKind::NewAnswer,
+                  channels: NotificationChannels { in_app: false, email: true, webhook: true },
+              },
+              NotificationKindSettings {
+                  kind: NotificationKind::Mention,
+                  channels: NotificationChannels { in_app: false, email: false, webhook: false },
+              },
+          ],
+          webhook_url: Some(webhook_url.to_owned()),
+          webhook_secret: None,
+      }
+  }
+
+  #[sqlx::test]
+  async fn notifications_should_follow_the_recipients_channels(pool: PgPool) -> Result<(), String> {
+      let doa = NotificationsDaoImpl::new(pool.clone());
+      let follower = create_user(&pool, "follower").await?;
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      FollowsDaoImpl::new(pool.clone())
+          .follow_question(question.question_uuid.to_string(), follower.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let secret = doa
+          .set_notification_settings(follower.clone(), settings("https://hooks.example.com/a"), "first".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(secret.as_deref(), Some("first"));
+
+      let notified = doa
+          .notify_question_followers(question.question_uuid.to_string(), NotificationKind::NewAnswer, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(notified, 1);
+
+      let inbox = doa
+          .get_notifications(follower.clone(), false, Pagination::default())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert!(inbox.is_empty(), "Notifications turned off in-app should not be listed");
+
+      let email_status: String = sqlx::query_scalar("SELECT email_status FROM notifications")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(email_status, "pending");
+
+      let events: Vec<String> = sqlx::query_scalar("SELECT event FROM webhook_deliveries")
+          .fetch_all(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(events, vec!["notification".to_owned()]);
+
+      let mentioned = doa
+          .notify_mentioned_users(question.question_uuid.to_string(), None, vec!["follower".to_owned()], None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(mentioned, 0, "Notifications turned off on every channel should be dropped");
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn set_notification_settings_should_only_return_a_secret_for_a_new_url(pool: PgPool) -> Result<(), String> {
+      let doa = NotificationsDaoImpl::new(pool.clone());
+      let user = create_user(&pool, "user").await?;
+
+      for (url, secret, expected) in [
+          ("https://hooks.example.com/a", "first", Some("first")),
+          ("https://hooks.example.com/a", "second", None),
+          ("https://hooks.example.com/b", "third", Some("third")),
+      ] {
+          let result = doa
+              .set_notification_settings(user.clone(), settings(url), secret.to_owned())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          assert_eq!(result.as_deref(), expected);
+      }
+
+      let stored = doa.get_notification_settings(user.clone()).await.map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(stored.kinds.len(), 2);
+      assert_eq!(stored.webhook_url.as_deref(), Some("https://hooks.example.com/b"));
+
+      doa.set_notification_settings(user.clone(), NotificationSettings::default(), "fourth".to_owned())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let webhooks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhooks")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(webhooks, 0, "Removing the URL should delete the user's webhook");
+
+      Ok(())
+  }
+}
+
+mod content_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{Answer, ContentState, DigestFrequency, DigestSettings, Pagination, Question, QuestionDraft},
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          content_dao::{ContentDao, ContentDaoImpl},
+          drafts_dao::{DraftsDao, DraftsDaoImpl},
+          email_digests_dao::{EmailDigestsDao, EmailDigestsDaoImpl},
+          follows_dao::{FollowsDao, FollowsDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  async fn create_user(pool: &PgPool, username: &str) -> Result<String, String> {
+      let user_uuid: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ($1, $1) RETURNING user_uuid")
+          .bind(username)
+          .fetch_one(pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      Ok(user_uuid.to_string())
+  }
+
+  #[sqlx::test]
+  async fn get_user_content_should_include_hidden_posts_with_their_state(pool: PgPool) -> Result<(), String> {
+      let doa = ContentDaoImpl::new(pool.clone());
+      let questions_doa = QuestionsDaoImpl::new(pool.clone());
+      let author = create_user(&pool, "author").await?;
+      let other = create_user(&pool, "other").await?;
+
+      let mut question_uuids = Vec::new();
+      for (title, author) in [("published", &author), ("deleted", &author), ("other", &other)] {
+          let question = questions_doa
+              .create_question(Question {
+                  title: title.to_owned(),
+                  description: "test description".to_owned(),
+                  ..Default::default()
+              }, Some(author.clone()), Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          question_uuids.push(question.question_uuid);
+      }
+
+      sqlx::query("UPDATE questions SET deleted_at = CURRENT_TIMESTAMP WHERE title = 'deleted'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      AnswersDaoImpl::new(pool.clone())
+          .create_answer(Answer {
+              question_uuid: question_uuids[2],
+              content: "test content".to_owned(),
+          }, Some(author.clone()), None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      sqlx::query("UPDATE answers SET held_at = CURRENT_TIMESTAMP")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      DraftsDaoImpl::new(pool.clone())
+          .save_question_draft(author.clone(), QuestionDraft { title: "draft".to_owned(), description: String::new() })
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let content = doa.get_user_content(author.clone(), Pagination::default()).await.map_err(|e| format!("{:?}", e))?;
+
+      let mut questions: Vec<_> = content.questions.iter().map(|question| (question.title.as_str(), question.state)).collect();
+      questions.sort_by_key(|(title, _)| *title);
+
+      assert_eq!(questions, vec![("deleted", ContentState::Deleted), ("published", ContentState::Published)]);
+      assert_eq!(content.answers.len(), 1);
+      assert_eq!(content.answers[0].question_title, "other");
+      assert_eq!(content.answers[0].state, ContentState::Held);
+      assert_eq!(content.drafts.len(), 1);
+
+      assert_eq!(doa.delete_user_drafts(author.clone()).await.map_err(|e| format!("{:?}", e))?, 1);
+      assert_eq!(doa.delete_user_drafts(author).await.map_err(|e| format!("{:?}", e))?, 0);
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn unsubscribe_user_should_unfollow_questions_and_cancel_the_digest(pool: PgPool) -> Result<(), String> {
+      let doa = ContentDaoImpl::new(pool.clone());
+      let follows_doa = FollowsDaoImpl::new(pool.clone());
+      let user = create_user(&pool, "user").await?;
+      let other = create_user(&pool, "other").await?;
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      for follower in [&user, &other] {
+          follows_doa
+              .follow_question(question.question_uuid.to_string(), follower.clone())
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      EmailDigestsDaoImpl::new(pool.clone())
+          .set_digest_settings(user.clone(), DigestSettings { frequency: DigestFrequency::Daily, tags: vec![] })
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let unsubscribed = doa.unsubscribe_user(user.clone()).await.map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(unsubscribed.unfollowed_questions, 1);
+      assert!(unsubscribed.digest);
+
+      let followers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM question_followers")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(followers, 1, "Other users should keep following");
+
+      let again = doa.unsubscribe_user(user).await.map_err(|e| format!("{:?}", e))?;
+
+      assert_eq!(again.unfollowed_questions, 0);
+      assert!(!again.digest);
+
+      Ok(())
+  }
+}
+
+mod query_samples_tests {
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{NewQuerySample, Pagination, QuerySamplesQuery, SampledQuery},
+      persistance::query_samples_dao::{QuerySamplesDao, QuerySamplesDaoImpl},
+  };
+
+  fn sample(route: &str, elapsed_ms: f64) -> NewQuerySample {
+      NewQuerySample {
+        route: route.to_owned(),
+        status: 200,
+        elapsed_ms,
+        queries: vec![SampledQuery {
+          statement: "SELECT * FROM questions".to_owned(),
+          rows_returned: 2,
+          rows_affected: 0,
+          elapsed_ms: elapsed_ms / 2.0,
+        }],
+      }
+  }
+
+  #[sqlx::test]
+  async fn sampling_percent_should_start_off_and_keep_the_last_update(pool: PgPool) -> Result<(), String> {
+      let admin: Uuid = sqlx::query_scalar("INSERT INTO users (username, api_token_hash) VALUES ('admin', 'admin') RETURNING user_uuid")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+      let doa = QuerySamplesDaoImpl::new(pool);
+
+      let initial = doa.get_sampling_percent().await.map_err(|e| format!("{:?}", e))?;
+
+      doa.set_sampling_percent(2.5, admin.to_string()).await.map_err(|e| format!("{:?}", e))?;
+
+      let updated = doa.get_sampling_percent().await.map_err(|e| format!("{:?}", e))?;
+
+      if initial != 0.0 || updated != 2.5 {
+          return Err(format!("Expected 0 then 2.5, got {} and {}", initial, updated));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn query_samples_should_be_filtered_and_purged(pool: PgPool) -> Result<(), String> {
+      let doa = QuerySamplesDaoImpl::new(pool.clone());
+
+      doa.create_query_sample(sample("GET /api/v1/questions", 12.0)).await.map_err(|e| format!("{:?}", e))?;
+      doa.create_query_sample(sample("GET /api/v1/questions", 250.0)).await.map_err(|e| format!("{:?}", e))?;
+      doa.create_query_sample(sample("POST /api/v1/question", 300.0)).await.map_err(|e| format!("{:?}", e))?;
+
+      let query = QuerySamplesQuery { route: Some("GET /api/v1/questions".to_owned()), min_elapsed_ms: Some(100.0) };
+      let samples = doa
+          .get_query_samples(query, Pagination { offset: 0, limit: 10 })
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if samples.len() != 1 || samples[0].elapsed_ms != 250.0 || samples[0].queries != sample("", 250.0).queries {
+          return Err(format!("Expected the slow GET sample with its queries, got {:?}", samples));
+      }
+
+      sqlx::query("UPDATE query_samples SET created_at = CURRENT_TIMESTAMP - make_interval(days => 8) WHERE route LIKE 'POST %'")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let purged = doa.purge_query_samples(7).await.map_err(|e| format!("{:?}", e))?;
+
+      if purged != 1 {
+          return Err(format!("Expected the old sample to be purged, purged {}", purged));
+      }
+
+      Ok(())
+  }
+}
+
+mod health_tests {
+  use sqlx::PgPool;
+
+  use crate::persistance::health_dao::{HealthDao, HealthDaoImpl};
+
+  #[sqlx::test]
+  async fn ping_should_reach_the_database(pool: PgPool) -> Result<(), String> {
+      let doa = HealthDaoImpl::new(pool);
+
+      doa.ping().await.map_err(|e| format!("{:?}", e))
+  }
+
+  #[sqlx::test]
+  async fn get_pending_migrations_should_be_empty_once_migrated(pool: PgPool) -> Result<(), String> {
+      let doa = HealthDaoImpl::new(pool.clone());
+
+      let pending = doa.get_pending_migrations().await.map_err(|e| format!("{:?}", e))?;
+
+      if pending != Some(vec![]) {
+          return Err(format!("Expected no pending migrations, got {:?}", pending));
+      }
+
+      sqlx::query("DELETE FROM _sqlx_migrations WHERE version = (SELECT max(version) FROM _sqlx_migrations)")
+          .execute(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let pending = doa.get_pending_migrations().await.map_err(|e| format!("{:?}", e))?;
+
+      if pending.as_ref().map(Vec::len) != Some(1) {
+          return Err(format!("Expected the latest migration to be pending, got {:?}", pending));
+      }
+
+      Ok(())
+  }
+}
+
+mod import_tests {
+  use sqlx::{types::Uuid, PgPool};
+  use time::macros::datetime;
+
+  use crate::{
+      models::{ImportCounts, ImportRecord, ImportedAnswer, ImportedQuestion, ImportedUser, Viewer},
+      persistance::{
+          import_dao::{ImportDao, ImportDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+      },
+  };
+
+  fn records() -> Vec<ImportRecord> {
+      vec![
+          ImportRecord::User(ImportedUser {
+              user_uuid: Uuid::from_u128(0x1),
+              username: "imported".to_owned(),
+              created_at: datetime!(2015-03-01 10:00:00 UTC),
+          }),
+          ImportRecord::Question(ImportedQuestion {
+              question_uuid: Uuid::from_u128(0x2),
+              title: "test title".to_owned(),
+              description: "test description".to_owned(),
+              author_uuid: Some(Uuid::from_u128(0x1)),
+              tags: vec!["rust".to_owned()],
+              created_at: datetime!(2015-03-02 10:00:00 UTC),
+              updated_at: None,
+          }),
+          ImportRecord::Answer(ImportedAnswer {
+              answer_uuid: Uuid::from_u128(0x3),
+              question_uuid: Uuid::from_u128(0x2),
+              content: "test content".to_owned(),
+              author_uuid: None,
+              created_at: datetime!(2015-03-03 10:00:00 UTC),
+          }),
+      ]
+  }
+
+  #[sqlx::test]
+  async fn import_records_should_keep_uuids_and_timestamps(pool: PgPool) -> Result<(), String> {
+      let doa = ImportDaoImpl::new(pool.clone());
+
+      let counts = doa.import_records(&records()).await.map_err(|e| format!("{:?}", e))?;
+
+      if counts != (ImportCounts { users: 1, questions: 1, answers: 1, skipped: 0 }) {
+          return Err(format!("Incorrect counts: {:?}", counts));
+      }
+
+      let question = QuestionsDaoImpl::new(pool)
+          .get_question(Uuid::from_u128(0x2).to_string(), Viewer::Anonymous)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("Imported question not found")?;
+
+      if question.created_at != datetime!(2015-03-02 10:00:00 UTC) || question.updated_at != question.created_at {
+          return Err(format!("Incorrect timestamps: {:?}", question));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn import_records_should_skip_records_imported_before(pool: PgPool) -> Result<(), String> {
+      let doa = ImportDaoImpl::new(pool);
+
+      doa.import_records(&records()).await.map_err(|e| format!("{:?}", e))?;
+      let counts = doa.import_records(&records()).await.map_err(|e| format!("{:?}", e))?;
+
+      if counts != (ImportCounts { skipped: 3, ..ImportCounts::default() }) {
+          return Err(format!("Incorrect counts: {:?}", counts));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn import_records_should_roll_back_the_batch_on_error(pool: PgPool) -> Result<(), String> {
+      let doa = ImportDaoImpl::new(pool.clone());
+      let mut records = records();
+      records.remove(0);
+
+      if doa.import_records(&records).await.is_ok() {
+          return Err("A question by an unknown author should fail".to_owned());
+      }
+
+      let questions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM questions")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if questions != 0 {
+          return Err("The batch should have been rolled back".to_owned());
+      }
+
+      Ok(())
+  }
+}
+
+mod stats_tests {
+  use sha2::{Digest, Sha256};
+  use sqlx::{types::Uuid, PgPool};
+
+  use crate::{
+      models::{Answer, Question, QuestionView, QuestionViewer},
+      persistance::{
+          answers_dao::{AnswersDao, AnswersDaoImpl},
+          questions_dao::{QuestionsDao, QuestionsDaoImpl},
+          retention_dao::{RetentionDao, RetentionDaoImpl},
+          stats_dao::{StatsDao, StatsDaoImpl},
+      },
+  };
+
+  #[sqlx::test]
+  async fn get_admin_stats_should_count_posts_per_day(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let answers = AnswersDaoImpl::new(pool.clone());
+      let mut question_uuids = Vec::new();
+
+      for _ in 0..2 {
+          let question = questions
+              .create_question(Question {
+                  title: "title".to_owned(),
+                  description: "description".to_owned(),
+                  ..Default::default()
+              }, None, Vec::new(), None, None)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+
+          question_uuids.push(question.question_uuid);
+      }
+
+      answers
+          .create_answer(Answer {
+              question_uuid: question_uuids[0],
+              content: "content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let stats = StatsDaoImpl::new(pool).get_admin_stats(7).await.map_err(|e| format!("{:?}", e))?;
+
+      if stats.questions != 2 || stats.answers != 1 || stats.answer_rate != Some(0.5) {
+          return Err(format!("Incorrect totals: {:?}", stats));
+      }
+
+      if stats.median_seconds_to_first_answer.is_none() {
+          return Err("The answered question should have a time to first answer".to_owned());
+      }
+
+      let today = stats.per_day.last().ok_or("No days returned")?;
+
+      if stats.per_day.len() != 7 || today.questions != 2 || today.answers != 1 || today.answer_rate != Some(0.5) {
+          return Err(format!("Incorrect days: {:?}", stats.per_day));
+      }
+
+      if stats.per_day[..6].iter().any(|day| day.questions != 0 || day.answer_rate.is_some()) {
+          return Err(format!("Earlier days should be empty: {:?}", stats.per_day));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn question_stats_should_follow_views_and_answers(pool: PgPool) -> Result<(), String> {
+      let questions = QuestionsDaoImpl::new(pool.clone());
+      let answers = AnswersDaoImpl::new(pool.clone());
+      let stats_dao = StatsDaoImpl::new(pool);
+
+      let question = questions
+          .create_question(Question {
+              title: "title".to_owned(),
+              description: "description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let question_uuid = question.question_uuid.to_string();
+
+      let answer = answers
+          .create_answer(Answer {
+              question_uuid: question.question_uuid,
+              content: "content".to_owned(),
+          }, None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let first = QuestionViewer::Ip("203.0.113.1".parse().unwrap());
+      let second = QuestionViewer::User(Uuid::from_u128(0x2));
+      let view = |viewer| QuestionView { question_uuid: question.question_uuid, viewer };
+
+      for views in [vec![view(first), view(first)], vec![view(second)]] {
+          stats_dao
+              .record_question_views(views)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      let stats = stats_dao
+          .get_question_stats(question_uuid.clone())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("The question should have stats")?;
+
+      if stats.views != 3 || stats.unique_viewers != 2 || stats.answers != 1 {
+          return Err(format!("Incorrect stats: {:?}", stats));
+      }
+
+      answers
+          .delete_answer(answer.answer_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let stats = stats_dao
+          .get_question_stats(question_uuid)
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("The question should have stats")?;
+
+      if stats.answers != 0 {
+          return Err(format!("Deleted answers should not be counted: {:?}", stats));
+      }
+
+      Ok(())
+  }
+
+  #[sqlx::test]
+  async fn viewers_should_be_unlinkable_once_their_day_is_purged(pool: PgPool) -> Result<(), String> {
+      let stats_dao = StatsDaoImpl::new(pool.clone());
+
+      let question = QuestionsDaoImpl::new(pool.clone())
+          .create_question(Question {
+              title: "title".to_owned(),
+              description: "description".to_owned(),
+              ..Default::default()
+          }, None, Vec::new(), None, None)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let view = QuestionView {
+          question_uuid: question.question_uuid,
+          viewer: QuestionViewer::Ip("203.0.113.1".parse().unwrap()),
+      };
+
+      let viewer_hashes = || {
+          sqlx::query_scalar::<_, String>("SELECT viewer_hash FROM question_viewers ORDER BY viewed_on")
+              .fetch_all(&pool)
+      };
+
+      stats_dao.record_question_views(vec![view.clone()]).await.map_err(|e| format!("{:?}", e))?;
+
+      let stored = viewer_hashes().await.map_err(|e| format!("{:?}", e))?;
+
+      if stored.len() != 1 || stored[0] == hex::encode(Sha256::digest(b"ip:203.0.113.1")) || stored[0].contains("203.0.113") {
+          return Err(format!("Expected the viewer keyed with the salt of the day, got {:?}", stored));
+      }
+
+      // As if the view had been counted yesterday.
+      for statement in ["UPDATE viewer_salts SET day = day - 1", "UPDATE question_viewers SET viewed_on = viewed_on - 1"] {
+          sqlx::query(statement)
+              .execute(&pool)
+              .await
+              .map_err(|e| format!("{:?}", e))?;
+      }
+
+      stats_dao.record_question_views(vec![view]).await.map_err(|e| format!("{:?}", e))?;
+
+      let stored = viewer_hashes().await.map_err(|e| format!("{:?}", e))?;
+      let stats = stats_dao
+          .get_question_stats(question.question_uuid.to_string())
+          .await
+          .map_err(|e| format!("{:?}", e))?
+          .ok_or("The question should have stats")?;
+
+      if stored.len() != 2 || stored[0] == stored[1] || stats.unique_viewers != 2 {
+          return Err(format!("Expected the viewer counted again under a new salt, got {:?} and {:?}", stored, stats));
+      }
+
+      let purged = RetentionDaoImpl::new(pool.clone())
+          .purge_question_viewers(1)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      let salts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM viewer_salts WHERE day < CURRENT_DATE")
+          .fetch_one(&pool)
+          .await
+          .map_err(|e| format!("{:?}", e))?;
+
+      if purged != 1 || salts != 0 || viewer_hashes().await.map_err(|e| format!("{:?}", e))?.len() != 1 {
+          return Err(format!("Expected yesterday's viewers and salt purged, purged {} and {} salts left", purged, salts));
       }
 
       Ok(())
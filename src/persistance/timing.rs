@@ -0,0 +1,62 @@
+use std::{
+    future::Future,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+use crate::{metrics, models::DBError};
+
+static POLICY: OnceLock<TimingPolicy> = OnceLock::new();
+
+/// How long DAO operations may take, and from when on they are logged as slow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingPolicy {
+    /// Set as `statement_timeout` on every connection, so that Postgres cancels longer statements.
+    /// Streamed reads lift it with `begin_streaming`.
+    /// An operation is given up on after as long, should the server stop answering altogether.
+    pub statement_timeout: Duration,
+    pub slow_query_threshold: Duration,
+}
+
+impl Default for TimingPolicy {
+    fn default() -> Self {
+        TimingPolicy {
+            statement_timeout: Duration::from_secs(10),
+            slow_query_threshold: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Sets the policy of `timed` for the rest of the process. Only the first call has an effect;
+/// without one, the default policy applies.
+pub fn configure(policy: TimingPolicy) {
+    if POLICY.set(policy).is_err() {
+        warn!("The database timing policy is already configured.");
+    }
+}
+
+/// Runs `op`, failing it with `DBError::Timeout` once it runs longer than the statement timeout.
+/// Its duration goes to the `db_query_duration_seconds` histogram under `name`, and is logged when
+/// over the slow query threshold.
+pub(crate) async fn timed<T, Fut>(name: &'static str, op: Fut) -> Result<T, DBError>
+where
+    Fut: Future<Output = Result<T, DBError>>,
+{
+    let policy = POLICY.get().copied().unwrap_or_default();
+    let started = Instant::now();
+
+    let result = tokio::time::timeout(policy.statement_timeout, op)
+      .await
+      .unwrap_or(Err(DBError::Timeout));
+
+    let elapsed = started.elapsed();
+    metrics::observe_query(name, elapsed);
+
+    if elapsed >= policy.slow_query_threshold {
+        warn!("Slow database operation {} took {:?}.", name, elapsed);
+    }
+
+    result
+}
@@ -0,0 +1,586 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::{
+    avatars::avatar_urls,
+    crypto::{CryptoError, FieldCipher},
+    models::{
+        DBError, ProvisionedUser, ProvisionedUserDetail, Role, User, UserDetail, UserIpRecord,
+        UserProfile,
+    },
+};
+
+use super::{begin, circuit_breaker::breaker, retry::retrying};
+
+#[async_trait]
+pub trait UsersDao {
+    async fn create_user(&self, user: User, api_token_hash: String) -> Result<UserDetail, DBError>;
+    /// Only finds users of the current tenant, so that a token is rejected at the host of any other.
+    async fn get_user_by_token_hash(&self, api_token_hash: String) -> Result<Option<UserDetail>, DBError>;
+    async fn record_user_ip(&self, user_uuid: String, ip: String) -> Result<(), DBError>;
+    async fn get_user_ip_history(&self, user_uuid: String) -> Result<Vec<UserIpRecord>, DBError>;
+    /// Re-encrypts every PII value that is not under the current key, returning how many were rewritten.
+    async fn rotate_encryption_keys(&self) -> Result<u64, DBError>;
+    async fn provision_user(&self, user: ProvisionedUser, api_token_hash: String) -> Result<ProvisionedUserDetail, DBError>;
+    async fn get_provisioned_user(&self, user_uuid: String) -> Result<Option<ProvisionedUserDetail>, DBError>;
+    /// Lists users, or only the one named `username` when it is given.
+    async fn get_provisioned_users(&self, username: Option<String>) -> Result<Vec<ProvisionedUserDetail>, DBError>;
+    /// Replaces every provisioned attribute of the user.
+    async fn update_provisioned_user(&self, user_uuid: String, user: ProvisionedUser) -> Result<Option<ProvisionedUserDetail>, DBError>;
+    async fn set_user_active(&self, user_uuid: String, active: bool) -> Result<Option<ProvisionedUserDetail>, DBError>;
+    /// Creates or refreshes the user signing in through a directory, matched by `external_id`, and
+    /// replaces their API token. Returns `None` when the existing account has been deactivated.
+    async fn upsert_directory_user(&self, user: ProvisionedUser, api_token_hash: String) -> Result<Option<ProvisionedUserDetail>, DBError>;
+    /// The public profile of an active user.
+    async fn get_user_profile(&self, user_uuid: String) -> Result<Option<UserProfile>, DBError>;
+    /// Points the user at a new set of avatar objects, returning the key of the ones it replaced.
+    async fn set_avatar_key(&self, user_uuid: String, avatar_key: Option<String>) -> Result<Option<String>, DBError>;
+    /// The total score of the user's answers; see `ReputationBand`.
+    async fn get_reputation(&self, user_uuid: String) -> Result<i64, DBError>;
+    /// Shadow-bans the user, or lifts their shadow ban. Moderators and admins cannot be shadow-banned;
+    /// returns false for them and for unknown users.
+    async fn set_shadow_banned(&self, user_uuid: String, banned: bool) -> Result<bool, DBError>;
+    /// The decrypted email address of an active user, for notification emails.
+    async fn get_user_email(&self, user_uuid: String) -> Result<Option<String>, DBError>;
+}
+
+pub struct UsersDaoImpl {
+    db: PgPool,
+    cipher: FieldCipher,
+}
+
+impl UsersDaoImpl {
+    pub fn new(db: PgPool, cipher: FieldCipher) -> Self {
+      UsersDaoImpl {
+        db,
+        cipher,
+      }
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<Vec<u8>, DBError> {
+        self.cipher.encrypt(plaintext).map_err(crypto_error)
+    }
+
+    fn decrypt(&self, ciphertext: Option<Vec<u8>>) -> Result<Option<String>, DBError> {
+        ciphertext
+          .map(|ciphertext| self.cipher.decrypt(&ciphertext).map_err(crypto_error))
+          .transpose()
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_role(role: &str) -> Result<Role, DBError> {
+    role.parse().map_err(|err: String| DBError::Other(err.into()))
+}
+
+fn crypto_error(err: CryptoError) -> DBError {
+    DBError::Other(Box::new(err))
+}
+
+#[async_trait]
+impl UsersDao for UsersDaoImpl {
+    async fn create_user(&self, user: User, api_token_hash: String) -> Result<UserDetail, DBError> {
+        let (user, api_token_hash) = (&user, &api_token_hash);
+
+        retrying("create_user", || async move {
+            let email_encrypted = user.email.as_deref().map(|email| self.encrypt(email)).transpose()?;
+
+            let record = sqlx::query!(
+                "INSERT INTO users (username, email_encrypted, api_token_hash) VALUES ($1, $2, $3) RETURNING user_uuid, username, email_encrypted, role, created_at",
+                user.username,
+                email_encrypted,
+                api_token_hash
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(UserDetail {
+              user_uuid: record.user_uuid,
+              username: record.username,
+              email: self.decrypt(record.email_encrypted)?,
+              role: parse_role(&record.role)?,
+              created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_user_by_token_hash(&self, api_token_hash: String) -> Result<Option<UserDetail>, DBError> {
+        let api_token_hash = &api_token_hash;
+
+        retrying("get_user_by_token_hash", || async move {
+            let record = sqlx::query!(
+                "SELECT user_uuid, username, email_encrypted, role, created_at FROM users
+                 WHERE api_token_hash = $1 AND active
+                   AND tenant_id IS NOT DISTINCT FROM NULLIF(current_setting('app.tenant_id', true), '')::uuid",
+                api_token_hash
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(UserDetail {
+                  user_uuid: record.user_uuid,
+                  username: record.username,
+                  email: self.decrypt(record.email_encrypted)?,
+                  role: parse_role(&record.role)?,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn record_user_ip(&self, user_uuid: String, ip: String) -> Result<(), DBError> {
+        let (user_uuid, ip) = (&user_uuid, &ip);
+
+        retrying("record_user_ip", || async move {
+            let uuid = Uuid::parse_str(user_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            sqlx::query!(
+                "INSERT INTO user_ip_history (user_uuid, ip_encrypted) VALUES ($1, $2)",
+                uuid,
+                self.encrypt(&ip)?
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn get_user_ip_history(&self, user_uuid: String) -> Result<Vec<UserIpRecord>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_user_ip_history", || async move {
+            let uuid = Uuid::parse_str(user_uuid)
+              .map_err(|err| {
+                DBError::InvalidUUID(err.to_string())
+              })?;
+
+            let records = sqlx::query!(
+                "SELECT ip_encrypted, seen_at FROM user_ip_history WHERE user_uuid = $1 ORDER BY seen_at DESC",
+                uuid
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(UserIpRecord {
+                  ip: self.cipher.decrypt(&record.ip_encrypted).map_err(crypto_error)?,
+                  seen_at: record.seen_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn rotate_encryption_keys(&self) -> Result<u64, DBError> {
+        breaker()
+          .call(async {
+            let mut tx = begin(&self.db).await?;
+
+            let mut rotated = 0;
+
+            let users = sqlx::query!("SELECT user_uuid, email_encrypted FROM users WHERE email_encrypted IS NOT NULL FOR UPDATE")
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            for user in users {
+                let Some(email_encrypted) = user.email_encrypted else { continue };
+
+                if !self.cipher.needs_rotation(&email_encrypted).map_err(crypto_error)? {
+                    continue;
+                }
+
+                sqlx::query!(
+                    "UPDATE users SET email_encrypted = $2 WHERE user_uuid = $1",
+                    user.user_uuid,
+                    self.cipher.rotate(&email_encrypted).map_err(crypto_error)?
+                  )
+                  .execute(&mut *tx)
+                  .await
+                  .map_err(DBError::from)?;
+
+                rotated += 1;
+            }
+
+            let ips = sqlx::query!("SELECT id, ip_encrypted FROM user_ip_history FOR UPDATE")
+              .fetch_all(&mut *tx)
+              .await
+              .map_err(DBError::from)?;
+
+            for ip in ips {
+                if !self.cipher.needs_rotation(&ip.ip_encrypted).map_err(crypto_error)? {
+                    continue;
+                }
+
+                sqlx::query!(
+                    "UPDATE user_ip_history SET ip_encrypted = $2 WHERE id = $1",
+                    ip.id,
+                    self.cipher.rotate(&ip.ip_encrypted).map_err(crypto_error)?
+                  )
+                  .execute(&mut *tx)
+                  .await
+                  .map_err(DBError::from)?;
+
+                rotated += 1;
+            }
+
+            tx.commit()
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(rotated)
+          })
+          .await
+    }
+
+    async fn provision_user(&self, user: ProvisionedUser, api_token_hash: String) -> Result<ProvisionedUserDetail, DBError> {
+        let (user, api_token_hash) = (&user, &api_token_hash);
+
+        retrying("provision_user", || async move {
+            let email_encrypted = user.email.as_deref().map(|email| self.encrypt(email)).transpose()?;
+
+            let record = sqlx::query!(
+                "INSERT INTO users (username, display_name, email_encrypted, external_id, role, active, api_token_hash) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 RETURNING user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at",
+                user.username,
+                user.display_name,
+                email_encrypted,
+                user.external_id,
+                user.role.as_str(),
+                user.active,
+                api_token_hash
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(ProvisionedUserDetail {
+              user: UserDetail {
+                user_uuid: record.user_uuid,
+                username: record.username,
+                email: self.decrypt(record.email_encrypted)?,
+                role: parse_role(&record.role)?,
+                created_at: record.created_at.assume_utc(),
+              },
+              display_name: record.display_name,
+              external_id: record.external_id,
+              active: record.active,
+            })
+          })
+          .await
+    }
+
+    async fn get_provisioned_user(&self, user_uuid: String) -> Result<Option<ProvisionedUserDetail>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_provisioned_user", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at FROM users WHERE user_uuid = $1",
+                uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(ProvisionedUserDetail {
+                  user: UserDetail {
+                    user_uuid: record.user_uuid,
+                    username: record.username,
+                    email: self.decrypt(record.email_encrypted)?,
+                    role: parse_role(&record.role)?,
+                    created_at: record.created_at.assume_utc(),
+                  },
+                  display_name: record.display_name,
+                  external_id: record.external_id,
+                  active: record.active,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn get_provisioned_users(&self, username: Option<String>) -> Result<Vec<ProvisionedUserDetail>, DBError> {
+        let username = username.as_deref();
+
+        retrying("get_provisioned_users", || async move {
+            let records = sqlx::query!(
+                "SELECT user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at FROM users
+                 WHERE $1::VARCHAR IS NULL OR username = $1 ORDER BY created_at",
+                username
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(ProvisionedUserDetail {
+                  user: UserDetail {
+                    user_uuid: record.user_uuid,
+                    username: record.username,
+                    email: self.decrypt(record.email_encrypted)?,
+                    role: parse_role(&record.role)?,
+                    created_at: record.created_at.assume_utc(),
+                  },
+                  display_name: record.display_name,
+                  external_id: record.external_id,
+                  active: record.active,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn update_provisioned_user(&self, user_uuid: String, user: ProvisionedUser) -> Result<Option<ProvisionedUserDetail>, DBError> {
+        let (user_uuid, user) = (&user_uuid, &user);
+
+        retrying("update_provisioned_user", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+            let email_encrypted = user.email.as_deref().map(|email| self.encrypt(email)).transpose()?;
+
+            let record = sqlx::query!(
+                "UPDATE users SET username = $2, display_name = $3, email_encrypted = $4, external_id = $5, role = $6, active = $7 WHERE user_uuid = $1
+                 RETURNING user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at",
+                uuid,
+                user.username,
+                user.display_name,
+                email_encrypted,
+                user.external_id,
+                user.role.as_str(),
+                user.active
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(ProvisionedUserDetail {
+                  user: UserDetail {
+                    user_uuid: record.user_uuid,
+                    username: record.username,
+                    email: self.decrypt(record.email_encrypted)?,
+                    role: parse_role(&record.role)?,
+                    created_at: record.created_at.assume_utc(),
+                  },
+                  display_name: record.display_name,
+                  external_id: record.external_id,
+                  active: record.active,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn set_user_active(&self, user_uuid: String, active: bool) -> Result<Option<ProvisionedUserDetail>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("set_user_active", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "UPDATE users SET active = $2 WHERE user_uuid = $1
+                 RETURNING user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at",
+                uuid,
+                active
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(ProvisionedUserDetail {
+                  user: UserDetail {
+                    user_uuid: record.user_uuid,
+                    username: record.username,
+                    email: self.decrypt(record.email_encrypted)?,
+                    role: parse_role(&record.role)?,
+                    created_at: record.created_at.assume_utc(),
+                  },
+                  display_name: record.display_name,
+                  external_id: record.external_id,
+                  active: record.active,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn upsert_directory_user(&self, user: ProvisionedUser, api_token_hash: String) -> Result<Option<ProvisionedUserDetail>, DBError> {
+        let (user, api_token_hash) = (&user, &api_token_hash);
+
+        retrying("upsert_directory_user", || async move {
+            let email_encrypted = user.email.as_deref().map(|email| self.encrypt(email)).transpose()?;
+
+            let record = sqlx::query!(
+                "INSERT INTO users (username, display_name, email_encrypted, external_id, role, api_token_hash) VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (external_id) DO UPDATE SET display_name = EXCLUDED.display_name, email_encrypted = EXCLUDED.email_encrypted,
+                 role = EXCLUDED.role, api_token_hash = EXCLUDED.api_token_hash WHERE users.active
+                 RETURNING user_uuid, username, display_name, email_encrypted, external_id, role, active, created_at",
+                user.username,
+                user.display_name,
+                email_encrypted,
+                user.external_id,
+                user.role.as_str(),
+                api_token_hash
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            record
+              .map(|record| {
+                Ok(ProvisionedUserDetail {
+                  user: UserDetail {
+                    user_uuid: record.user_uuid,
+                    username: record.username,
+                    email: self.decrypt(record.email_encrypted)?,
+                    role: parse_role(&record.role)?,
+                    created_at: record.created_at.assume_utc(),
+                  },
+                  display_name: record.display_name,
+                  external_id: record.external_id,
+                  active: record.active,
+                })
+              })
+              .transpose()
+          })
+          .await
+    }
+
+    async fn get_user_profile(&self, user_uuid: String) -> Result<Option<UserProfile>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_user_profile", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let record = sqlx::query!(
+                "SELECT user_uuid, username, display_name, avatar_key, created_at FROM users WHERE user_uuid = $1 AND active",
+                uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.map(|record| UserProfile {
+              user_uuid: record.user_uuid,
+              username: record.username,
+              display_name: record.display_name,
+              created_at: record.created_at.assume_utc(),
+              avatar_urls: avatar_urls(record.avatar_key.as_deref()),
+            }))
+          })
+          .await
+    }
+
+    async fn set_avatar_key(&self, user_uuid: String, avatar_key: Option<String>) -> Result<Option<String>, DBError> {
+        let (user_uuid, avatar_key) = (&user_uuid, avatar_key.as_deref());
+
+        retrying("set_avatar_key", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            // The subquery reads the row as it was before the update.
+            let record = sqlx::query!(
+                "UPDATE users SET avatar_key = $2 WHERE user_uuid = $1
+                 RETURNING (SELECT avatar_key FROM users WHERE user_uuid = $1) AS previous_key",
+                uuid,
+                avatar_key
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(record.and_then(|record| record.previous_key))
+          })
+          .await
+    }
+
+    async fn get_reputation(&self, user_uuid: String) -> Result<i64, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_reputation", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            sqlx::query_scalar!(
+                "SELECT COALESCE(SUM(v.value), 0) AS \"reputation!\" FROM answers a JOIN counted_answer_votes v ON v.answer_uuid = a.answer_uuid
+                 WHERE a.author_uuid = $1 AND a.deleted_at IS NULL",
+                uuid
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)
+          })
+          .await
+    }
+
+    async fn set_shadow_banned(&self, user_uuid: String, banned: bool) -> Result<bool, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("set_shadow_banned", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            // Banning again keeps the original timestamp.
+            let result = sqlx::query!(
+                "UPDATE users SET shadow_banned_at = CASE WHEN $2 THEN COALESCE(shadow_banned_at, CURRENT_TIMESTAMP) END
+                 WHERE user_uuid = $1 AND role = 'user'",
+                uuid,
+                banned
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn get_user_email(&self, user_uuid: String) -> Result<Option<String>, DBError> {
+        let user_uuid = &user_uuid;
+
+        retrying("get_user_email", || async move {
+            let uuid = parse_uuid(user_uuid)?;
+
+            let email_encrypted = sqlx::query_scalar!(
+                "SELECT email_encrypted FROM users WHERE user_uuid = $1 AND active",
+                uuid
+              )
+              .fetch_optional(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            self.decrypt(email_encrypted.flatten())
+          })
+          .await
+    }
+}
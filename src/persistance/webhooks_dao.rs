@@ -0,0 +1,439 @@
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{
+    AnswerDetail, DBError, NewWebhook, Pagination, PendingWebhookDelivery, QuestionDetail, WebhookDelivery,
+    WebhookDetail, WebhookDigest, WebhookEvent,
+};
+
+use super::{
+    circuit_breaker::guarded,
+    retry::retrying,
+    questions_dao::{contest_of, parse_kind, parse_status, parse_visibility},
+};
+
+#[async_trait]
+pub trait WebhooksDao {
+    /// Collects the public questions and answers posted since `webhook` last delivered. The first
+    /// digest of a webhook starts now rather than replaying the whole history.
+    async fn get_pending_digest(&self, webhook: String) -> Result<WebhookDigest, DBError>;
+    /// Moves the webhook's cursor to `until` once the digest ending there has been delivered.
+    async fn mark_digest_delivered(&self, webhook: String, until: String) -> Result<(), DBError>;
+    async fn create_webhook(&self, webhook: NewWebhook, secret: String, created_by: String) -> Result<WebhookDetail, DBError>;
+    /// Admins' webhooks; users' own webhooks are managed with their notification settings.
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError>;
+    /// Returns false when there was no such webhook. Its deliveries are deleted with it.
+    async fn delete_webhook(&self, webhook_uuid: String) -> Result<bool, DBError>;
+    /// The webhook's delivery log, newest first. None when there is no such webhook.
+    async fn get_webhook_deliveries(&self, webhook_uuid: String, page: Pagination) -> Result<Option<Vec<WebhookDelivery>>, DBError>;
+    /// Queues a delivery of `event` to every webhook subscribed to it, unless the post is hidden
+    /// from anonymous readers. Returns the number of deliveries queued.
+    async fn queue_webhook_deliveries(
+        &self,
+        event: WebhookEvent,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        forum_url: String,
+    ) -> Result<u64, DBError>;
+    /// Claims up to `limit` due deliveries, oldest first, for `lease_seconds`: a delivery still
+    /// claimed then is due again.
+    async fn claim_webhook_deliveries(&self, limit: i64, lease_seconds: i32) -> Result<Vec<PendingWebhookDelivery>, DBError>;
+    async fn mark_webhook_delivered(&self, delivery_uuid: String, response_status: i32) -> Result<(), DBError>;
+    /// Records a failed attempt, retried in `retry_in_seconds` or given up when None.
+    async fn fail_webhook_delivery(
+        &self,
+        delivery_uuid: String,
+        response_status: Option<i32>,
+        error: String,
+        retry_in_seconds: Option<i32>,
+    ) -> Result<(), DBError>;
+}
+
+pub struct WebhooksDaoImpl {
+    db: PgPool,
+}
+
+impl WebhooksDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+      WebhooksDaoImpl {
+        db
+      }
+    }
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, DBError> {
+    Uuid::parse_str(uuid).map_err(|err| DBError::InvalidUUID(err.to_string()))
+}
+
+fn parse_events(events: Vec<String>) -> Result<Vec<WebhookEvent>, DBError> {
+    events
+      .iter()
+      .map(|event| event.parse().map_err(|err: String| DBError::Other(err.into())))
+      .collect()
+}
+
+#[async_trait]
+impl WebhooksDao for WebhooksDaoImpl {
+    async fn get_pending_digest(&self, webhook: String) -> Result<WebhookDigest, DBError> {
+        let webhook = &webhook;
+
+        retrying("get_pending_digest", || async move {
+            sqlx::query!("INSERT INTO webhook_cursors (webhook) VALUES ($1) ON CONFLICT (webhook) DO NOTHING", webhook)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let window = sqlx::query!(
+                "SELECT delivered_until AS since, LOCALTIMESTAMP AS \"until!\" FROM webhook_cursors WHERE webhook = $1",
+                webhook
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            let questions = sqlx::query!(
+                "SELECT * FROM questions WHERE created_at > $1 AND created_at <= $2 AND deleted_at IS NULL AND held_at IS NULL AND visibility = 'public' AND post_visible_to(author_uuid, NULL)
+                 ORDER BY created_at, question_uuid",
+                window.since,
+                window.until
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?
+              .into_iter()
+              .map(|record| {
+                Ok(QuestionDetail {
+                  question_uuid: record.question_uuid,
+                  title: record.title,
+                  description: record.description,
+                  status: parse_status(&record.status)?,
+                  status_reason: record.status_reason,
+                  kind: parse_kind(&record.kind)?,
+                  contest: contest_of(record.contest_reveal_at, record.contest_ends_at, record.contest_winner_uuid),
+                  author_uuid: record.author_uuid,
+                  visibility: parse_visibility(&record.visibility)?,
+                  board_uuid: record.board_uuid,
+                  tags: record.tags,
+                  language: record.language,
+                  created_at: record.created_at.assume_utc(),
+                  updated_at: record.updated_at.assume_utc(),
+                  description_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  held_for_review: false,
+                  pending_tags: Vec::new(),
+                })
+              })
+              .collect::<Result<Vec<_>, DBError>>()?;
+
+            let answers = sqlx::query!(
+                "SELECT a.* FROM answers a JOIN questions q ON q.question_uuid = a.question_uuid
+                 WHERE a.created_at > $1 AND a.created_at <= $2 AND a.deleted_at IS NULL AND a.held_at IS NULL AND q.deleted_at IS NULL AND q.held_at IS NULL AND q.visibility = 'public'
+                 AND post_visible_to(a.author_uuid, NULL) AND post_visible_to(q.author_uuid, NULL)
+                 AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, NULL)
+                 ORDER BY a.created_at, a.answer_uuid",
+                window.since,
+                window.until
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?
+              .into_iter()
+              .map(|record| {
+                AnswerDetail {
+                  answer_uuid: record.answer_uuid,
+                  question_uuid: record.question_uuid,
+                  content: record.content,
+                  author_uuid: record.author_uuid,
+                  created_at: record.created_at.assume_utc(),
+                  content_html: None,
+                  code_blocks: Vec::new(),
+                  link_previews: Vec::new(),
+                  signals: None,
+                  question_age_warning: false,
+                  similar_answer_uuid: None,
+                  held_for_review: false,
+                }
+              })
+              .collect();
+
+            Ok(WebhookDigest {
+              since: window.since.to_string(),
+              until: window.until.to_string(),
+              questions,
+              answers,
+            })
+          })
+          .await
+    }
+
+    async fn mark_digest_delivered(&self, webhook: String, until: String) -> Result<(), DBError> {
+        let (webhook, until) = (&webhook, &until);
+
+        retrying("mark_digest_delivered", || async move {
+            sqlx::query!(
+                "UPDATE webhook_cursors SET delivered_until = $2::VARCHAR::TIMESTAMP WHERE webhook = $1",
+                webhook,
+                until
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn create_webhook(&self, webhook: NewWebhook, secret: String, created_by: String) -> Result<WebhookDetail, DBError> {
+        let (webhook, secret, created_by) = (&webhook, &secret, &created_by);
+
+        retrying("create_webhook", || async move {
+            let created_by = parse_uuid(created_by)?;
+            let events: Vec<String> = webhook.events.iter().map(|event| event.as_str().to_owned()).collect();
+
+            let record = sqlx::query!(
+                "INSERT INTO webhooks (url, secret, events, created_by) VALUES ($1, $2, $3, $4)
+                 RETURNING webhook_uuid, url, events, secret, created_at",
+                webhook.url,
+                secret,
+                &events,
+                created_by
+              )
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(WebhookDetail {
+              webhook_uuid: record.webhook_uuid,
+              url: record.url,
+              events: parse_events(record.events)?,
+              secret: Some(record.secret),
+              created_at: record.created_at.assume_utc(),
+            })
+          })
+          .await
+    }
+
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+        retrying("get_webhooks", || async move {
+            let records = sqlx::query!("SELECT webhook_uuid, url, events, created_at FROM webhooks WHERE owner_uuid IS NULL ORDER BY created_at, webhook_uuid")
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(WebhookDetail {
+                  webhook_uuid: record.webhook_uuid,
+                  url: record.url,
+                  events: parse_events(record.events)?,
+                  secret: None,
+                  created_at: record.created_at.assume_utc(),
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn delete_webhook(&self, webhook_uuid: String) -> Result<bool, DBError> {
+        let webhook_uuid = &webhook_uuid;
+
+        retrying("delete_webhook", || async move {
+            let uuid = parse_uuid(webhook_uuid)?;
+
+            let result = sqlx::query!("DELETE FROM webhooks WHERE webhook_uuid = $1 AND owner_uuid IS NULL", uuid)
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected() > 0)
+          })
+          .await
+    }
+
+    async fn get_webhook_deliveries(&self, webhook_uuid: String, page: Pagination) -> Result<Option<Vec<WebhookDelivery>>, DBError> {
+        let webhook_uuid = &webhook_uuid;
+
+        retrying("get_webhook_deliveries", || async move {
+            let uuid = parse_uuid(webhook_uuid)?;
+
+            let exists = sqlx::query_scalar!("SELECT EXISTS (SELECT 1 FROM webhooks WHERE webhook_uuid = $1 AND owner_uuid IS NULL) AS \"exists!\"", uuid)
+              .fetch_one(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            if !exists {
+              return Ok(None);
+            }
+
+            let records = sqlx::query!(
+                "SELECT delivery_uuid, event, status, attempts, response_status, last_error, created_at, delivered_at,
+                   CASE WHEN status IN ('pending', 'sending') THEN next_attempt_at END AS next_attempt_at
+                 FROM webhook_deliveries WHERE webhook_uuid = $1
+                 ORDER BY created_at DESC, delivery_uuid OFFSET $2 LIMIT $3",
+                uuid,
+                i64::from(page.offset),
+                i64::from(page.limit)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(WebhookDelivery {
+                  delivery_uuid: record.delivery_uuid,
+                  event: record.event.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  status: record.status.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  attempts: record.attempts,
+                  response_status: record.response_status,
+                  last_error: record.last_error,
+                  created_at: record.created_at.assume_utc(),
+                  next_attempt_at: record.next_attempt_at.map(|at| at.assume_utc()),
+                  delivered_at: record.delivered_at.map(|at| at.assume_utc()),
+                })
+              })
+              .collect::<Result<_, _>>()
+              .map(Some)
+          })
+          .await
+    }
+
+    async fn queue_webhook_deliveries(
+        &self,
+        event: WebhookEvent,
+        question_uuid: String,
+        answer_uuid: Option<String>,
+        forum_url: String,
+    ) -> Result<u64, DBError> {
+        let (question_uuid, answer_uuid, forum_url) = (&question_uuid, answer_uuid.as_deref(), &forum_url);
+
+        retrying("queue_webhook_deliveries", || async move {
+            let question_uuid = parse_uuid(question_uuid)?;
+            let answer_uuid = answer_uuid.map(parse_uuid).transpose()?;
+
+            let result = sqlx::query!(
+                "INSERT INTO webhook_deliveries (webhook_uuid, event, payload)
+                 SELECT w.webhook_uuid, $1, jsonb_build_object(
+                   'event', $1::VARCHAR,
+                   'question_uuid', q.question_uuid,
+                   'question_title', q.title,
+                   'question_url', $4 || '/question/' || q.question_uuid,
+                   'answer_uuid', a.answer_uuid,
+                   'author', u.username
+                 )
+                 FROM webhooks w
+                 JOIN questions q ON q.question_uuid = $2
+                 LEFT JOIN answers a ON a.answer_uuid = $3
+                 LEFT JOIN users u ON u.user_uuid = CASE WHEN $3::uuid IS NULL THEN q.author_uuid ELSE a.author_uuid END
+                 WHERE $1 = ANY(w.events)
+                 AND q.visibility = 'public' AND q.deleted_at IS NULL AND q.held_at IS NULL AND post_visible_to(q.author_uuid, NULL)
+                 AND ($3::uuid IS NULL OR (a.deleted_at IS NULL AND a.held_at IS NULL AND post_visible_to(a.author_uuid, NULL)
+                   AND contest_answer_visible_to(q.contest_reveal_at, a.author_uuid, NULL)))",
+                event.as_str(),
+                question_uuid,
+                answer_uuid,
+                forum_url
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(result.rows_affected())
+          })
+          .await
+    }
+
+    async fn claim_webhook_deliveries(&self, limit: i64, lease_seconds: i32) -> Result<Vec<PendingWebhookDelivery>, DBError> {
+        guarded("claim_webhook_deliveries", async {
+            let records = sqlx::query!(
+                "WITH claimed AS (
+                   UPDATE webhook_deliveries SET status = 'sending',
+                     next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+                   WHERE delivery_uuid IN (
+                     SELECT delivery_uuid FROM webhook_deliveries
+                     WHERE status IN ('pending', 'sending') AND next_attempt_at <= CURRENT_TIMESTAMP
+                     ORDER BY next_attempt_at LIMIT $1 FOR UPDATE SKIP LOCKED
+                   )
+                   RETURNING delivery_uuid, webhook_uuid, event, payload, attempts, created_at
+                 )
+                 SELECT c.delivery_uuid, c.event, c.payload, c.attempts, w.url, w.secret
+                 FROM claimed c JOIN webhooks w ON w.webhook_uuid = c.webhook_uuid
+                 ORDER BY c.created_at, c.delivery_uuid",
+                limit,
+                f64::from(lease_seconds)
+              )
+              .fetch_all(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            records
+              .into_iter()
+              .map(|record| {
+                Ok(PendingWebhookDelivery {
+                  delivery_uuid: record.delivery_uuid,
+                  url: record.url,
+                  secret: record.secret,
+                  event: record.event.parse().map_err(|err: String| DBError::Other(err.into()))?,
+                  payload: record.payload,
+                  attempts: record.attempts,
+                })
+              })
+              .collect()
+          })
+          .await
+    }
+
+    async fn mark_webhook_delivered(&self, delivery_uuid: String, response_status: i32) -> Result<(), DBError> {
+        guarded("mark_webhook_delivered", async {
+            let uuid = parse_uuid(&delivery_uuid)?;
+
+            sqlx::query!(
+                "UPDATE webhook_deliveries SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+                   last_error = NULL, delivered_at = CURRENT_TIMESTAMP
+                 WHERE delivery_uuid = $1",
+                uuid,
+                response_status
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+
+    async fn fail_webhook_delivery(
+        &self,
+        delivery_uuid: String,
+        response_status: Option<i32>,
+        error: String,
+        retry_in_seconds: Option<i32>,
+    ) -> Result<(), DBError> {
+        guarded("fail_webhook_delivery", async {
+            let uuid = parse_uuid(&delivery_uuid)?;
+
+            sqlx::query!(
+                "UPDATE webhook_deliveries SET attempts = attempts + 1, response_status = $2, last_error = $3,
+                   status = CASE WHEN $4::INT IS NULL THEN 'failed' ELSE 'pending' END,
+                   next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => COALESCE($4, 0))
+                 WHERE delivery_uuid = $1",
+                uuid,
+                response_status,
+                error,
+                retry_in_seconds
+              )
+              .execute(&self.db)
+              .await
+              .map_err(DBError::from)?;
+
+            Ok(())
+          })
+          .await
+    }
+}
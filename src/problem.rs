@@ -0,0 +1,205 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{Map, Value};
+use tracing::Span;
+
+use crate::models::Problem;
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Name of the span `request_span` opens for each request, and of its field holding the id.
+pub const REQUEST_SPAN: &str = "request";
+pub const REQUEST_ID_FIELD: &str = "request_id";
+
+/// Longest request id accepted from a client; longer ones are replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Plain-text error bodies longer than this are replaced by the status phrase.
+const MAX_PLAIN_ERROR_BYTES: usize = 64 * 1024;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The id of the request being served by the current task, if any.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+/// The span of a request for the `TraceLayer`, which runs within `problem_middleware`: the events
+/// logged while serving the request show its id, method and path. The query string is left out.
+pub fn request_span(request: &Request) -> Span {
+    info_span!(
+        REQUEST_SPAN,
+        request_id = current_request_id().as_deref(),
+        method = %request.method(),
+        path = request.uri().path(),
+    )
+}
+
+/// A problem of the generic `about:blank` type, titled with the status phrase.
+pub fn status_problem(status: StatusCode, detail: String) -> Response {
+    problem(status, "about:blank", status.canonical_reason().unwrap_or("Error"), detail, Map::new())
+}
+
+/// A problem with its own type, and `members` added to the standard ones.
+pub fn problem(status: StatusCode, problem_type: &str, title: &str, detail: String, members: Map<String, Value>) -> Response {
+    let problem = Problem {
+        problem_type: problem_type.to_owned(),
+        title: title.to_owned(),
+        status: status.as_u16(),
+        detail,
+        request_id: current_request_id(),
+    };
+
+    let mut body = match serde_json::to_value(problem) {
+        Ok(Value::Object(body)) => body,
+        _ => Map::new(),
+    };
+    for (name, value) in members {
+        body.entry(name).or_insert(value);
+    }
+
+    (
+        status,
+        [(header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE)],
+        Json(Value::Object(body)),
+    )
+        .into_response()
+}
+
+/// Serves every request with an id, taken from its `X-Request-Id` header when valid, echoed in the
+/// response. Error responses with a plain-text body, such as extractor rejections, are rewritten
+/// as problems, so that clients only ever see one error format.
+pub async fn problem_middleware(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_owned)
+        .unwrap_or_else(|| hex::encode(rand::random::<[u8; 16]>()));
+
+    REQUEST_ID
+        .scope(request_id.clone(), async move {
+            let mut response = next.run(request).await;
+
+            if is_plain_error(&response) {
+                response = plain_error_as_problem(response).await;
+            }
+
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                response.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+
+            response
+        })
+        .await
+}
+
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+fn is_plain_error(response: &Response) -> bool {
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+
+    (status.is_client_error() || status.is_server_error())
+        && content_type.is_none_or(|content_type| content_type.starts_with("text/plain"))
+}
+
+async fn plain_error_as_problem(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let status = parts.status;
+
+    let detail = match to_bytes(body, MAX_PLAIN_ERROR_BYTES).await {
+        Ok(bytes) if !bytes.is_empty() => String::from_utf8_lossy(&bytes).into_owned(),
+        _ => status.canonical_reason().unwrap_or("Error").to_owned(),
+    };
+
+    let (problem_parts, problem_body) = status_problem(status, detail).into_parts();
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.extend(problem_parts.headers);
+
+    Response::from_parts(parts, Body::new(problem_body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/plain", get(|| async { (StatusCode::UNSUPPORTED_MEDIA_TYPE, "Expected JSON") }))
+            .route("/ok", get(|| async { "ok" }))
+            .layer(middleware::from_fn(problem_middleware))
+    }
+
+    async fn json_body(response: Response) -> Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn problem_should_carry_the_request_id_and_extra_members() {
+        let response = REQUEST_ID
+            .scope("abc".to_owned(), async {
+                let mut members = Map::new();
+                members.insert("code".to_owned(), Value::from("contest-closed"));
+                members.insert("status".to_owned(), Value::from(200));
+
+                problem(StatusCode::CONFLICT, "urn:forum:problem:conflict", "Conflict", "Closed.".to_owned(), members)
+            })
+            .await;
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+        assert_eq!(
+            json_body(response).await,
+            serde_json::json!({
+                "type": "urn:forum:problem:conflict",
+                "title": "Conflict",
+                "status": 409,
+                "detail": "Closed.",
+                "request_id": "abc",
+                "code": "contest-closed",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn middleware_should_rewrite_plain_errors_and_echo_valid_request_ids() {
+        let request = Request::get("/plain").header(REQUEST_ID_HEADER, "req-1").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-1");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+
+        let body = json_body(response).await;
+
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["title"], "Unsupported Media Type");
+        assert_eq!(body["detail"], "Expected JSON");
+        assert_eq!(body["request_id"], "req-1");
+
+        let request = Request::get("/ok").header(REQUEST_ID_HEADER, "has space").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER].len(), 32);
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "ok");
+    }
+}
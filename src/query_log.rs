@@ -0,0 +1,273 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use tracing::{
+    field::{Field, Visit},
+    subscriber::Interest,
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    filter::dynamic_filter_fn,
+    layer::Context,
+    registry::LookupSpan,
+    Layer,
+};
+
+use crate::{
+    models::{NewQuerySample, SampledQuery},
+    persistance::query_samples_dao::QuerySamplesDao,
+};
+
+/// Target of the event sqlx emits at debug level for every query it runs.
+pub const SQLX_QUERY_TARGET: &str = "sqlx::query";
+
+tokio::task_local! {
+    static SAMPLE: RefCell<Vec<SampledQuery>>;
+}
+
+/// Whether the current task serves a sampled request, so that sqlx reports its queries to
+/// `QueryCapture`. Sampling never turns on query logging, which `RUST_LOG` alone decides.
+pub fn is_sampling() -> bool {
+    SAMPLE.try_with(|_| ()).is_ok()
+}
+
+/// Adds the queries sqlx reports while a sampled request is served to its sample.
+pub struct QueryCapture;
+
+impl<S: Subscriber> Layer<S> for QueryCapture {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut fields = QueryFields::default();
+
+        event.record(&mut fields);
+
+        if let Some(query) = fields.into_query() {
+            let _ = SAMPLE.try_with(|queries| queries.borrow_mut().push(query));
+        }
+    }
+}
+
+/// `QueryCapture`, enabled for the query events of sampled tasks only: sqlx skips formatting the
+/// statements of the others.
+pub fn capture_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let filter = dynamic_filter_fn(|metadata, _| metadata.target() == SQLX_QUERY_TARGET && is_sampling())
+        .with_callsite_filter(|metadata| match metadata.target() {
+            SQLX_QUERY_TARGET => Interest::sometimes(),
+            _ => Interest::never(),
+        });
+
+    QueryCapture.with_filter(filter)
+}
+
+/// Picks the requests whose queries are recorded, at the rate admins set in `query_sampling`.
+/// Each server instance reloads the rate periodically.
+pub struct QuerySampler {
+    /// The `f64` percentage, as bits.
+    percent: AtomicU64,
+    query_samples_dao: Arc<dyn QuerySamplesDao + Send + Sync>,
+}
+
+impl QuerySampler {
+    pub fn new(query_samples_dao: Arc<dyn QuerySamplesDao + Send + Sync>) -> Self {
+        QuerySampler {
+            percent: AtomicU64::new(0f64.to_bits()),
+            query_samples_dao,
+        }
+    }
+
+    pub fn percent(&self) -> f64 {
+        f64::from_bits(self.percent.load(Ordering::Relaxed))
+    }
+
+    pub fn set_percent(&self, percent: f64) {
+        self.percent.store(percent.clamp(0.0, 100.0).to_bits(), Ordering::Relaxed);
+    }
+
+    fn should_sample(&self) -> bool {
+        let percent = self.percent();
+
+        percent > 0.0 && rand::random::<f64>() * 100.0 < percent
+    }
+}
+
+/// Records the queries run while serving a sampled request, stored once the response head is
+/// ready. Queries of tasks the handler spawns, or run while a body streams, are left out.
+pub async fn sample(State(sampler): State<Arc<QuerySampler>>, request: Request, next: Next) -> Response {
+    if !sampler.should_sample() {
+        return next.run(request).await;
+    }
+
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => format!("{} {}", request.method(), path.as_str()),
+        None => format!("{} {}", request.method(), request.uri().path()),
+    };
+    let started = Instant::now();
+
+    let (response, queries) = SAMPLE
+        .scope(RefCell::new(Vec::new()), async {
+            let response = next.run(request).await;
+            (response, SAMPLE.with(|queries| queries.take()))
+        })
+        .await;
+
+    let sample = NewQuerySample {
+        route,
+        status: response.status().as_u16(),
+        elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+        queries,
+    };
+    let query_samples_dao = sampler.query_samples_dao.clone();
+
+    tokio::spawn(async move {
+        if let Err(err) = query_samples_dao.create_query_sample(sample).await {
+            error!("Error to record query sample: {}", err);
+        }
+    });
+
+    response
+}
+
+/// The fields of a query event, as sqlx emits them: `summary` holds the start of the statement and
+/// `db.statement` the whole statement, or nothing when the summary is the whole statement.
+#[derive(Default)]
+struct QueryFields {
+    summary: Option<String>,
+    statement: Option<String>,
+    rows_returned: Option<u64>,
+    rows_affected: Option<u64>,
+    elapsed_secs: Option<f64>,
+}
+
+impl QueryFields {
+    fn into_query(self) -> Option<SampledQuery> {
+        let statement = self.statement.filter(|statement| !statement.trim().is_empty()).or(self.summary)?;
+
+        Some(SampledQuery {
+            statement: statement.split_whitespace().collect::<Vec<_>>().join(" "),
+            rows_returned: self.rows_returned?,
+            rows_affected: self.rows_affected?,
+            elapsed_ms: self.elapsed_secs? * 1000.0,
+        })
+    }
+}
+
+impl Visit for QueryFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = Some(value.to_owned()),
+            "db.statement" => self.statement = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "rows_returned" => self.rows_returned = Some(value),
+            "rows_affected" => self.rows_affected = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Emits the event sqlx emits for `SELECT 1`, to a subscriber with the capture layer only.
+    fn emit_query_event(target_sqlx: bool) {
+        let subscriber = tracing_subscriber::registry().with(capture_layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            if target_sqlx {
+                debug!(
+                    target: "sqlx::query",
+                    summary = "SELECT 1",
+                    db.statement = "",
+                    rows_affected = 0u64,
+                    rows_returned = 1u64,
+                    elapsed_secs = 0.001,
+                );
+            } else {
+                debug!(summary = "SELECT 1", db.statement = "", rows_affected = 0u64, rows_returned = 1u64, elapsed_secs = 0.001);
+            }
+        });
+    }
+
+    #[test]
+    fn query_fields_should_read_statements_with_their_timings_and_row_counts() {
+        let fields = QueryFields {
+            summary: Some("SELECT q.question_uuid, q.title, …".to_owned()),
+            statement: Some(
+                "\n\nSELECT\n  q.question_uuid,\n  q.title\nFROM\n  questions q\nWHERE\n  q.title = 'say \"hi\"'\n".to_owned(),
+            ),
+            rows_returned: Some(3),
+            rows_affected: Some(0),
+            elapsed_secs: Some(0.0015),
+        };
+
+        assert_eq!(
+            fields.into_query(),
+            Some(SampledQuery {
+                statement: r#"SELECT q.question_uuid, q.title FROM questions q WHERE q.title = 'say "hi"'"#.to_owned(),
+                rows_returned: 3,
+                rows_affected: 0,
+                elapsed_ms: 1.5,
+            })
+        );
+
+        let fields = QueryFields {
+            summary: Some("COMMIT".to_owned()),
+            statement: Some(String::new()),
+            rows_returned: Some(0),
+            rows_affected: Some(0),
+            elapsed_secs: Some(0.00012),
+        };
+
+        assert_eq!(fields.into_query().unwrap().statement, "COMMIT");
+        assert_eq!(QueryFields::default().into_query(), None);
+    }
+
+    #[tokio::test]
+    async fn capture_layer_should_only_collect_queries_of_sampled_tasks() {
+        assert!(!is_sampling());
+        emit_query_event(true);
+
+        let queries = SAMPLE
+            .scope(RefCell::new(Vec::new()), async {
+                assert!(is_sampling());
+                emit_query_event(true);
+                emit_query_event(false);
+
+                SAMPLE.with(|queries| queries.take())
+            })
+            .await;
+
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].statement, "SELECT 1");
+        assert_eq!(queries[0].rows_returned, 1);
+    }
+}
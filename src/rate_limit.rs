@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use redis::{aio::ConnectionManager, Script};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::problem::status_problem;
+
+/// Counts requests per client in fixed windows. Counts are kept in memory, so each server
+/// instance enforces the limit on its own.
+pub struct RateLimiter {
+    limit: u32,
+    window_seconds: u64,
+    /// Start of each client's current window and the requests counted in it.
+    windows: Mutex<HashMap<IpAddr, (u64, u32)>>,
+}
+
+impl RateLimiter {
+    /// Clients that are not seen again are forgotten once this many are tracked.
+    const MAX_CLIENTS: usize = 10_000;
+
+    pub fn new(limit: u32, window_seconds: u64) -> Self {
+        RateLimiter {
+            limit,
+            window_seconds,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request from `client` at `now`, in seconds since the Unix epoch. Over the limit,
+    /// returns how many seconds are left until the client's window ends.
+    pub fn check(&self, client: IpAddr, now: u64) -> Result<(), u64> {
+        let mut windows = self.windows.lock().expect("rate limiter lock is not poisoned");
+
+        if windows.len() >= Self::MAX_CLIENTS {
+            windows.retain(|_, (start, _)| now < *start + self.window_seconds);
+        }
+
+        let (start, count) = windows.entry(client).or_insert((now, 0));
+
+        if now >= *start + self.window_seconds {
+            *start = now;
+            *count = 0;
+        }
+
+        if *count >= self.limit {
+            return Err(*start + self.window_seconds - now);
+        }
+
+        *count += 1;
+
+        Ok(())
+    }
+}
+
+/// Size and refill rate of the token bucket of each client.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    /// Requests a client can make at once, with a full bucket.
+    pub burst: u32,
+    /// Requests a client can keep making each minute.
+    pub per_minute: u32,
+}
+
+impl RateLimitRule {
+    fn tokens_per_ms(&self) -> f64 {
+        f64::from(self.per_minute) / 60_000.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after_ms: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum RateLimitError {
+    #[error("Rate limit store error: {0}")]
+    Store(#[from] redis::RedisError),
+}
+
+/// Where the token buckets are kept.
+#[async_trait]
+pub trait RateLimitStore {
+    /// Takes a token from the bucket `key`, refilled as `rule` says since it was last used.
+    async fn take(&self, key: &str, rule: RateLimitRule, now_ms: u64) -> Result<RateLimitDecision, RateLimitError>;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_ms: u64,
+}
+
+/// Keeps the buckets in memory, so each server instance enforces the limits on its own.
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitStore {
+    /// Full buckets are forgotten once this many are tracked.
+    const MAX_BUCKETS: usize = 100_000;
+
+    pub fn new() -> Self {
+        MemoryRateLimitStore {
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, key: &str, rule: RateLimitRule, now_ms: u64) -> Result<RateLimitDecision, RateLimitError> {
+        let mut buckets = self.buckets.lock().expect("rate limit store lock is not poisoned");
+        let refilled = |bucket: &Bucket| {
+            let elapsed_ms = now_ms.saturating_sub(bucket.updated_ms) as f64;
+
+            (bucket.tokens + elapsed_ms * rule.tokens_per_ms()).min(f64::from(rule.burst))
+        };
+
+        if buckets.len() >= Self::MAX_BUCKETS {
+            buckets.retain(|_, bucket| refilled(bucket) < f64::from(rule.burst));
+        }
+
+        let bucket = buckets
+            .entry(key.to_owned())
+            .or_insert(Bucket { tokens: f64::from(rule.burst), updated_ms: now_ms });
+
+        bucket.tokens = refilled(bucket);
+        bucket.updated_ms = now_ms;
+
+        if bucket.tokens < 1.0 {
+            let retry_after_ms = ((1.0 - bucket.tokens) / rule.tokens_per_ms()).ceil() as u64;
+
+            return Ok(RateLimitDecision::Limited { retry_after_ms });
+        }
+
+        bucket.tokens -= 1.0;
+
+        Ok(RateLimitDecision::Allowed)
+    }
+}
+
+/// Refills and takes from a bucket in one step, on the clock of Redis so that server instances
+/// with skewed clocks share buckets. Returns the milliseconds until a token is available, 0 when
+/// one was taken. Buckets expire once they would be full again.
+const TAKE_SCRIPT: &str = r#"
+local burst = tonumber(ARGV[1])
+local tokens_per_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now_ms = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_ms')
+local tokens = tonumber(bucket[1]) or burst
+local updated_ms = tonumber(bucket[2]) or now_ms
+
+tokens = math.min(burst, tokens + math.max(0, now_ms - updated_ms) * tokens_per_ms)
+
+local retry_after_ms = 0
+if tokens < 1 then
+  retry_after_ms = math.ceil((1 - tokens) / tokens_per_ms)
+else
+  tokens = tokens - 1
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_ms', now_ms)
+redis.call('PEXPIRE', KEYS[1], math.ceil((burst - tokens) / tokens_per_ms) + 1)
+
+return retry_after_ms
+"#;
+
+/// Keeps the buckets in Redis, shared by every server instance.
+pub struct RedisRateLimitStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    /// Prefix of the keys of the buckets.
+    const KEY_PREFIX: &'static str = "forum:rate-limit:";
+
+    pub async fn connect(url: &str) -> Result<Self, RateLimitError> {
+        let client = redis::Client::open(url)?;
+
+        Ok(RedisRateLimitStore {
+            connection: ConnectionManager::new(client).await?,
+            script: Script::new(TAKE_SCRIPT),
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(&self, key: &str, rule: RateLimitRule, _: u64) -> Result<RateLimitDecision, RateLimitError> {
+        let retry_after_ms: u64 = self
+            .script
+            .key(format!("{}{}", Self::KEY_PREFIX, key))
+            .arg(rule.burst)
+            .arg(rule.tokens_per_ms())
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+
+        Ok(match retry_after_ms {
+            0 => RateLimitDecision::Allowed,
+            retry_after_ms => RateLimitDecision::Limited { retry_after_ms },
+        })
+    }
+}
+
+/// Limits the requests of each client IP: routes with a rule of their own, such as
+/// `POST /api/v1/question`, each have a bucket per client; every other route shares one.
+pub struct RateLimits {
+    default_rule: RateLimitRule,
+    /// Keyed by method and route, e.g. `POST /api/v1/question`.
+    route_rules: HashMap<String, RateLimitRule>,
+    store: Arc<dyn RateLimitStore + Send + Sync>,
+}
+
+impl RateLimits {
+    pub fn new(
+        default_rule: RateLimitRule,
+        route_rules: HashMap<String, RateLimitRule>,
+        store: Arc<dyn RateLimitStore + Send + Sync>,
+    ) -> Self {
+        RateLimits {
+            default_rule,
+            route_rules,
+            store,
+        }
+    }
+
+    /// Takes a token for a request from `client` to `route`. Requests are let through when the
+    /// store fails, rather than failing with it.
+    async fn check(&self, client: IpAddr, route: Option<&str>, now_ms: u64) -> RateLimitDecision {
+        let (key, rule) = match route.and_then(|route| self.route_rules.get_key_value(route)) {
+            Some((route, rule)) => (format!("{}|{}", route, client), *rule),
+            None => (client.to_string(), self.default_rule),
+        };
+
+        self.store.take(&key, rule, now_ms).await.unwrap_or_else(|err| {
+            error!("Error to check the rate limit: {}", err);
+            RateLimitDecision::Allowed
+        })
+    }
+}
+
+/// Answers 429 with `Retry-After` to clients out of tokens for the route.
+pub async fn limit(
+    State(limits): State<Arc<RateLimits>>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| format!("{} {}", request.method(), path.as_str()));
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+
+    match limits.check(client_addr.ip(), route.as_deref(), now_ms).await {
+        RateLimitDecision::Allowed => next.run(request).await,
+        RateLimitDecision::Limited { retry_after_ms } => {
+            let retry_after = retry_after_ms.div_ceil(1000).max(1);
+
+            (
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                status_problem(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    format!("Too many requests, retry in {} seconds.", retry_after),
+                ),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_should_limit_each_client_per_window() {
+        let limiter = RateLimiter::new(2, 60);
+        let client: IpAddr = "203.0.113.1".parse().unwrap();
+        let other: IpAddr = "203.0.113.2".parse().unwrap();
+
+        assert_eq!(limiter.check(client, 1000), Ok(()));
+        assert_eq!(limiter.check(client, 1010), Ok(()));
+        assert_eq!(limiter.check(client, 1020), Err(40));
+        assert_eq!(limiter.check(other, 1020), Ok(()));
+        assert_eq!(limiter.check(client, 1060), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn memory_store_should_refill_buckets_at_the_rule_rate() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule { burst: 2, per_minute: 60 };
+
+        assert_eq!(store.take("a", rule, 0).await.unwrap(), RateLimitDecision::Allowed);
+        assert_eq!(store.take("a", rule, 0).await.unwrap(), RateLimitDecision::Allowed);
+        assert_eq!(store.take("a", rule, 250).await.unwrap(), RateLimitDecision::Limited { retry_after_ms: 750 });
+        assert_eq!(store.take("b", rule, 250).await.unwrap(), RateLimitDecision::Allowed);
+        assert_eq!(store.take("a", rule, 1000).await.unwrap(), RateLimitDecision::Allowed);
+        assert_eq!(store.take("a", rule, 1000).await.unwrap(), RateLimitDecision::Limited { retry_after_ms: 1000 });
+    }
+
+    #[tokio::test]
+    async fn limit_should_answer_429_with_retry_after_and_keep_route_buckets_apart() {
+        use axum::{body::Body, extract::connect_info::MockConnectInfo, routing::get, Router};
+        use tower::ServiceExt;
+
+        let limits = RateLimits::new(
+            RateLimitRule { burst: 1, per_minute: 1 },
+            HashMap::from([("POST /question".to_owned(), RateLimitRule { burst: 1, per_minute: 2 })]),
+            Arc::new(MemoryRateLimitStore::new()),
+        );
+        let app = Router::new()
+            .route("/question", get(|| async { "read" }).post(|| async { "posted" }))
+            .route_layer(axum::middleware::from_fn_with_state(Arc::new(limits), limit))
+            .layer(MockConnectInfo(SocketAddr::from(([203, 0, 113, 1], 4000))));
+        let send = |method: &str| {
+            app.clone()
+                .oneshot(Request::builder().method(method).uri("/question").body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(send("POST").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send("GET").await.unwrap().status(), StatusCode::OK);
+
+        let limited = send("POST").await.unwrap();
+
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers()[header::RETRY_AFTER].to_str().unwrap().parse::<u64>().unwrap() <= 30);
+
+        let limited = send("GET").await.unwrap();
+
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers()[header::RETRY_AFTER].to_str().unwrap().parse::<u64>().unwrap() > 30);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use tracing::{
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    Event, Subscriber,
+};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::{
+    field::RecordFields,
+    filter::{filter_fn, EnvFilter, FilterExt},
+    fmt::{format::Writer, FormatFields},
+    layer::{Context, SubscriberExt},
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::{
+    log_shipping::{self, LogShipper},
+    problem, query_log,
+};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+struct Patterns {
+    email: Regex,
+    bearer: Regex,
+    token: Regex,
+    ipv4: Regex,
+    ipv6: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+    PATTERNS.get_or_init(|| Patterns {
+        email: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+        bearer: Regex::new(r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]+").unwrap(),
+        token: Regex::new(r"\b[0-9A-Fa-f]{32,}\b").unwrap(),
+        ipv4: Regex::new(r"\d{1,3}(?:\.\d{1,3}){3}").unwrap(),
+        ipv6: Regex::new(r"[0-9A-Fa-f]{0,4}(?::[0-9A-Fa-f]{0,4}){2,7}").unwrap(),
+    })
+}
+
+/// Masks emails, bearer/API tokens and IP addresses in free-form text.
+pub fn redact(input: &str) -> String {
+    let patterns = patterns();
+
+    let output = patterns.email.replace_all(input, "[REDACTED EMAIL]");
+    let output = patterns.bearer.replace_all(&output, "Bearer [REDACTED TOKEN]");
+    let output = patterns.token.replace_all(&output, "[REDACTED TOKEN]");
+    let output = mask_ip_addresses(&patterns.ipv4, &output, |ip| ip.parse::<Ipv4Addr>().is_ok());
+
+    mask_ip_addresses(&patterns.ipv6, &output, |ip| ip.parse::<Ipv6Addr>().is_ok())
+}
+
+/// Only masks candidates that stand on their own and actually parse as an address,
+/// so timestamps such as `12:30:45` and paths such as `crate::models` are left alone.
+fn mask_ip_addresses(regex: &Regex, input: &str, is_ip: fn(&str) -> bool) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == ':' || c == '.';
+
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+
+    for candidate in regex.find_iter(input) {
+        let before = input[..candidate.start()].chars().next_back();
+        let after = input[candidate.end()..].chars().next();
+
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) || !is_ip(candidate.as_str()) {
+            continue;
+        }
+
+        output.push_str(&input[last..candidate.start()]);
+        output.push_str("[REDACTED IP]");
+        last = candidate.end();
+    }
+
+    output.push_str(&input[last..]);
+    output
+}
+
+/// Formats the fields of events and spans as the default formatter does, with every value
+/// redacted except the request id, which was generated or checked by `problem_middleware`.
+pub struct RedactingFields;
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = RedactingVisitor { writer, separator: "", result: Ok(()) };
+
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct RedactingVisitor<'writer> {
+    writer: Writer<'writer>,
+    separator: &'static str,
+    result: fmt::Result,
+}
+
+impl RedactingVisitor<'_> {
+    fn write(&mut self, field: &Field, value: &str) {
+        // Fields of records bridged from the `log` crate, which the formatter already shows.
+        if self.result.is_err() || field.name().starts_with("log.") {
+            return;
+        }
+
+        let value = match field.name() {
+            problem::REQUEST_ID_FIELD => Cow::Borrowed(value),
+            _ => Cow::Owned(redact(value)),
+        };
+
+        self.result = match field.name() {
+            "message" => write!(self.writer, "{}{}", self.separator, value),
+            name => write!(self.writer, "{}{}={}", self.separator, name, value),
+        };
+        self.separator = " ";
+    }
+}
+
+impl Visit for RedactingVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.write(field, value)
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.write(field, &format!("{:?}", value))
+    }
+}
+
+/// Ships events to the log sink, redacted and with the id of the request they were logged for.
+struct ShippingLayer {
+    shipper: LogShipper,
+}
+
+impl<S: Subscriber> Layer<S> for ShippingLayer {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut message = String::new();
+
+        if RedactingFields.format_fields(Writer::new(&mut message), event).is_err() {
+            return;
+        }
+        if let Some(request_id) = problem::current_request_id() {
+            message.push_str(&format!(" {}={}", problem::REQUEST_ID_FIELD, request_id));
+        }
+
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        self.shipper.ship(*metadata.level(), metadata.target(), &message);
+    }
+}
+
+/// Installs the subscriber, so that no PII reaches the log output: events are written to stdout
+/// and shipped to the log sink if one is configured, redacted and filtered by `RUST_LOG` (errors
+/// only by default), within the span of the request they were logged for. Records of the `log`
+/// crate, used by some dependencies, go through it too. Queries of requests picked for query
+/// sampling are captured into their sample whatever `RUST_LOG` allows.
+pub fn init_tracing() {
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::ERROR.into())
+        .from_env_lossy();
+    let shipper = log_shipping::shipper_from_env().expect("Failed to configure log shipping!");
+
+    // Request spans are kept whatever the filter, so every event of a request shows its id.
+    let output = tracing_subscriber::fmt::layer()
+        .fmt_fields(RedactingFields)
+        .and_then(shipper.map(|shipper| ShippingLayer { shipper }))
+        .with_filter(filter.or(filter_fn(|metadata| metadata.is_span() && metadata.name() == problem::REQUEST_SPAN)));
+
+    tracing_subscriber::registry()
+        .with(output)
+        .with(query_log::capture_layer())
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::models::{Role, User, UserCredentials, UserDetail, UserIpRecord};
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn redact_should_mask_emails() {
+        assert_eq!(
+            redact("failed to notify ferris.crab+forum@example.com"),
+            "failed to notify [REDACTED EMAIL]"
+        );
+    }
+
+    #[test]
+    fn redact_should_mask_tokens() {
+        let token = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+        assert_eq!(
+            redact(&format!("Authorization: Bearer {}", token)),
+            "Authorization: Bearer [REDACTED TOKEN]"
+        );
+        assert_eq!(redact(&format!("token {} rejected", token)), "token [REDACTED TOKEN] rejected");
+    }
+
+    #[test]
+    fn redact_should_mask_ip_addresses() {
+        assert_eq!(
+            redact("request from 192.168.0.12 and 2001:db8::ff00:42:8329"),
+            "request from [REDACTED IP] and [REDACTED IP]"
+        );
+    }
+
+    #[test]
+    fn redact_should_keep_uuids_timestamps_and_paths() {
+        let message = "question a22abcd2-22ab-2222-a22b-2abc2a2b22cc created at 2022-12-03 23:18:17.123 in crate::models";
+
+        assert_eq!(redact(message), message);
+    }
+
+    #[test]
+    fn formatted_fields_should_be_redacted_except_the_request_id() {
+        let output = Output::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .fmt_fields(RedactingFields)
+                .with_ansi(false)
+                .with_writer({
+                    let output = output.clone();
+                    move || output.clone()
+                }),
+        );
+        let request_id = "9f86d081884c7d659a2feaa0c55ad015";
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("request", request_id, path = "/users/ferris@example.com");
+            let _entered = span.enter();
+
+            error!(token = "9f86d081884c7d659a2feaa0c55ad015a3bf", "failed to notify ferris@example.com");
+        });
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+
+        assert!(
+            output.contains(&format!(
+                "request{{request_id={} path=/users/[REDACTED EMAIL]}}: {}: failed to notify [REDACTED EMAIL] token=[REDACTED TOKEN]",
+                request_id,
+                module_path!()
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn debug_output_should_mask_sensitive_fields() {
+        let user = UserDetail {
+            user_uuid: Uuid::from_u128(0x123),
+            username: "ferris".to_owned(),
+            email: Some("ferris@example.com".to_owned()),
+            role: Role::User,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let credentials = UserCredentials {
+            user: user.clone(),
+            api_token: "secret-token".to_owned(),
+        };
+        let new_user = User {
+            username: "ferris".to_owned(),
+            email: Some("ferris@example.com".to_owned()),
+        };
+        let ip = UserIpRecord {
+            ip: "10.0.0.1".to_owned(),
+            seen_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        for output in [
+            format!("{:?}", user),
+            format!("{:?}", credentials),
+            format!("{:?}", new_user),
+            format!("{:?}", ip),
+        ] {
+            assert!(!output.contains("ferris@example.com"), "{}", output);
+            assert!(!output.contains("secret-token"), "{}", output);
+            assert!(!output.contains("10.0.0.1"), "{}", output);
+        }
+    }
+}
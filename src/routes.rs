@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::StatusCode,
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    avatars,
+    config::Settings,
+    handlers::*,
+    http_cache::{self, CachePolicies},
+    import, models,
+    openapi::ApiDoc,
+    query_log, rate_limit, slo, AppState,
+};
+
+/// Prefix of every route of the first API version.
+///
+/// Each version is a router of its own, nested under its prefix. A version with changed schemas
+/// gets a new prefix and router, which reuses the handlers of the routes it leaves unchanged; the
+/// routers of older versions stay as they are, so their clients keep working.
+pub const API_V1: &str = "/api/v1";
+
+/// Room for the multipart boundaries and text fields around an upload's file part.
+const UPLOAD_FORM_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// Every API version under its prefix, the probes at `/health` and `/ready`, the Prometheus metrics
+/// at `/metrics`, and the Swagger UI at `/docs`. The probes and metrics are added after the route
+/// layers, so they are not rate limited, never cached, and count towards neither the SLOs nor the
+/// query samples.
+pub fn router(app_state: AppState) -> Router {
+    let cache_policies = Arc::new(CachePolicies::new(app_state.settings.cache_control_routes.clone()));
+
+    Router::new()
+        .nest(API_V1, api_v1(&app_state.settings))
+        .route_layer(middleware::from_fn_with_state(cache_policies, http_cache::cache_headers))
+        .route_layer(middleware::from_fn_with_state(app_state.query_sampler.clone(), query_log::sample))
+        .route_layer(middleware::from_fn_with_state(app_state.slo_tracker.clone(), slo::track))
+        .route_layer(middleware::from_fn_with_state(app_state.rate_limits.clone(), rate_limit::limit))
+        .route("/health", get(read_health))
+        .route("/ready", get(read_readiness))
+        .route("/metrics", get(read_metrics))
+        .with_state(app_state)
+        .merge(SwaggerUi::new("/docs").url(format!("{}/openapi.json", API_V1), ApiDoc::openapi()))
+}
+
+/// Bodies over `request_body_limit_bytes` are rejected with 413, and requests still running after
+/// `request_timeout_seconds` with 408, so that oversized or trickling requests do not hold on to a
+/// worker. Uploads get larger body limits of their own; long polls and CPU profiles wait longer on
+/// purpose, bounded by their own settings, so they get no timeout.
+fn api_v1(settings: &Settings) -> Router<AppState> {
+    let router = Router::new()
+        .route("/question", post(create_question))
+        .route("/questions", get(read_questions))
+        .route("/questions/batch", post(read_questions_batch))
+        .route("/questions/bulk-delete", post(bulk_delete_questions))
+        .route("/question/:uuid", get(read_question).put(update_question).delete(delete_question))
+        .route("/question/:uuid/full", get(read_question_full))
+        .route("/question/:uuid/stats", get(read_question_stats))
+        .route("/question/:uuid/revisions", get(read_question_revisions))
+        .route("/question/:uuid/signed-url", post(create_question_signed_url))
+        .route("/shared/question/:uuid", get(read_shared_question))
+        .route("/question/:uuid/follow", post(follow_question).delete(unfollow_question))
+        .route("/question/:uuid/accept", post(accept_answer))
+        .route("/question/:uuid/close", post(close_question))
+        .route("/question/:uuid/reopen", post(reopen_question))
+        .route("/question/:uuid/restore", post(restore_question))
+        .route("/question/:uuid/faq", put(set_faq_entry).delete(remove_faq_entry))
+        .route("/faq", get(read_faq))
+        .route("/tags/suggest", get(suggest_tags))
+        .route("/tags/:name/stats", get(read_tag_stats))
+        .route("/tags/:name/kb-export", get(export_knowledge_base))
+        .route("/feeds/questions.atom", get(read_questions_feed))
+        .route("/feeds/tag/:file", get(read_tag_feed))
+        .route("/sitemap.xml", get(read_sitemap_index))
+        .route("/sitemaps/:file", get(read_sitemap))
+        .route("/tags/pending", get(read_pending_tags))
+        .route("/tags/pending/:name", delete(reject_pending_tag))
+        .route("/tags/pending/:name/approve", post(approve_pending_tag))
+        .route("/question/:uuid/flag", post(flag_question))
+        .route("/answer", post(create_answer))
+        .route("/answers/bulk-delete", post(bulk_delete_answers))
+        .route("/answer/:uuid", put(update_answer).delete(delete_answer))
+        .route("/answer/:uuid/revisions", get(read_answer_revisions))
+        .route("/answer/:uuid/restore", post(restore_answer))
+        .route("/answer/:uuid/flag", post(flag_answer))
+        .route("/answer/:uuid/vote", post(vote_answer))
+        .route("/ws", get(live_updates))
+        .route("/questions/:uuid/answers", get(read_question_answers))
+        .route("/questions/:uuid/events", get(question_events))
+        .route("/uploads/:uuid", get(read_upload))
+        .route("/uploads/:uuid/signed-url", post(create_attachment_signed_url))
+        .route("/avatars/:key", get(read_avatar))
+        .route("/drafts/question", put(save_question_draft))
+        .route("/drafts/:uuid/publish", post(publish_draft))
+        .route("/boards", post(create_board))
+        .route("/boards/:uuid/join", post(join_board))
+        .route("/boards/:uuid/members", get(read_board_members).post(invite_board_member))
+        .route("/boards/:uuid/members/:user_uuid", delete(remove_board_member))
+        .route("/boards/:uuid/members/:user_uuid/approve", post(approve_board_member))
+        .route(
+            "/boards/:uuid/tag-rules",
+            get(read_board_tag_rules).put(set_board_tag_rules).delete(delete_board_tag_rules),
+        )
+        .route("/users", post(create_user))
+        .route(
+            "/users/me/accept-suggestions",
+            get(read_accept_suggestion_settings).put(update_accept_suggestion_settings),
+        )
+        .route("/users/me/digest", get(read_digest_settings).put(update_digest_settings))
+        .route("/me/content", get(read_my_content))
+        .route("/me/drafts", delete(delete_my_drafts))
+        .route("/me/subscriptions", delete(unsubscribe_from_everything))
+        .route(
+            "/users/me/notification-settings",
+            get(read_notification_settings).put(update_notification_settings),
+        )
+        .route("/notifications", get(read_notifications))
+        .route("/notifications/read-all", post(mark_all_notifications_read))
+        .route("/notifications/:id/read", post(mark_notification_read))
+        .route("/users/:uuid", get(read_user_profile))
+        .route("/users/:uuid/questions", get(read_user_questions))
+        .route("/users/:uuid/answers", get(read_user_answers))
+        .route("/sessions", post(sign_in))
+        .route("/invitations", get(read_invitations).post(create_invitation))
+        .route("/moderation/flags", get(read_flags))
+        .route("/moderation/flags/:uuid/resolve", post(resolve_flag))
+        .route("/moderation/queue", get(read_moderation_queue))
+        .route("/moderation/actions", get(read_moderation_actions).post(moderate_post))
+        .route("/moderation/users/:uuid/shadow-ban", put(shadow_ban_user).delete(lift_shadow_ban))
+        .route("/export/questions.ndjson", get(export_questions_ndjson))
+        .route("/export/questions.csv", get(export_questions_csv))
+        .route("/export/answers.csv", get(export_answers_csv))
+        .route("/admin/audit", get(read_audit_log))
+        .route("/admin/jobs", post(create_job))
+        .route("/admin/jobs/:uuid", get(read_job))
+        .route("/import/:uuid", get(read_import))
+        .route("/admin/users/:uuid/erase", post(erase_user))
+        .route("/admin/erasure-reports", get(read_erasure_reports))
+        .route("/admin/erasure-reports/:uuid", get(read_erasure_report))
+        .route("/admin/stats", get(read_admin_stats))
+        .route("/admin/retention", get(read_retention_stats))
+        .route("/admin/slo", get(read_slo_status))
+        .route("/admin/diagnostics", get(read_diagnostics))
+        .route("/admin/query-sampling", get(read_query_sampling).put(update_query_sampling))
+        .route("/admin/query-samples", get(read_query_samples))
+        .route("/admin/dead-letters", get(read_dead_letters))
+        .route("/admin/dead-letters/retry", post(retry_dead_letters))
+        .route("/admin/dead-letters/purge", post(purge_dead_letters))
+        .route("/admin/webhooks", get(read_webhooks).post(create_webhook))
+        .route("/admin/webhooks/:uuid", delete(delete_webhook))
+        .route("/admin/webhooks/:uuid/deliveries", get(read_webhook_deliveries))
+        .route(
+            "/admin/boards/:uuid/cleanup-policy",
+            get(read_cleanup_policy).put(set_cleanup_policy).delete(delete_cleanup_policy),
+        )
+        .route("/admin/boards/:uuid/cleanup-policy/preview", post(preview_cleanup))
+        .route("/scim/v2/Users", get(scim_read_users).post(scim_create_user))
+        .route(
+            "/scim/v2/Users/:uuid",
+            get(scim_read_user).put(scim_replace_user).patch(scim_patch_user).delete(scim_delete_user),
+        );
+
+    let router = if settings.legacy_body_routes {
+        legacy_body_routes_v1(router)
+    } else {
+        router
+    };
+
+    router
+        .layer(RequestBodyLimitLayer::new(settings.request_body_limit_bytes))
+        .layer(DefaultBodyLimit::disable())
+        .merge(upload_routes_v1())
+        .layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, settings.request_timeout()))
+        .merge(long_running_routes_v1())
+}
+
+/// Routes that take files, with body limits sized for them.
+fn upload_routes_v1() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/uploads",
+            post(create_upload).layer(DefaultBodyLimit::max(models::Upload::MAX_BYTES + UPLOAD_FORM_OVERHEAD_BYTES)),
+        )
+        .route(
+            "/users/me/avatar",
+            put(update_avatar).layer(DefaultBodyLimit::max(avatars::MAX_AVATAR_BYTES)),
+        )
+}
+
+/// Routes that answer after a wait of their own: `long_poll_max_wait_seconds` for the long poll,
+/// at most `MAX_PROFILE_SECONDS` for the CPU profile. Imports take as long as uploading and
+/// checking their dump, which is bounded by its size.
+fn long_running_routes_v1() -> Router<AppState> {
+    Router::new()
+        .route("/questions/:uuid/poll", get(poll_question_events))
+        .route("/admin/diagnostics/profile", get(capture_cpu_profile))
+        .route("/import", post(start_import).layer(DefaultBodyLimit::max(import::MAX_DUMP_BYTES)))
+}
+
+/// Routes that take the UUID in a request body instead of the path, for clients that predate the
+/// path-based routes. Many clients and proxies drop the body of a GET or DELETE request.
+fn legacy_body_routes_v1(router: Router<AppState>) -> Router<AppState> {
+    router
+        .route("/question", delete(delete_question_by_body))
+        .route("/answers", get(read_answers))
+        .route("/answer", delete(delete_answer_by_body))
+}
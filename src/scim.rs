@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    auth::GroupRoleMap,
+    models::{ProvisionedUser, ProvisionedUserDetail},
+    routes::API_V1,
+};
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+
+/// Settings for `/scim/v2`, which is only served when a provisioning token is configured.
+pub struct ScimConfig {
+    /// SHA-256 digest of the bearer token identity providers must present.
+    pub token_hash: String,
+    pub group_roles: GroupRoleMap,
+}
+
+/// The subset of the SCIM 2.0 core User resource the forum understands (RFC 7643).
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default = "ScimUser::default_active")]
+    pub active: bool,
+    #[serde(default)]
+    pub emails: Vec<ScimEmail>,
+    /// Only read on writes, to pick the user's role through the configured group mapping.
+    #[serde(default, skip_serializing)]
+    pub groups: Vec<ScimGroupRef>,
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+impl ScimUser {
+    fn default_active() -> bool {
+        true
+    }
+
+    pub fn into_provisioned(self, group_roles: &GroupRoleMap) -> ProvisionedUser {
+        let role = group_roles.role_for(
+            self.groups
+                .iter()
+                .filter_map(|group| group.display.as_deref().or(group.value.as_deref())),
+        );
+        let email = self
+            .emails
+            .iter()
+            .find(|email| email.primary)
+            .or_else(|| self.emails.first())
+            .map(|email| email.value.clone());
+
+        ProvisionedUser {
+            username: self.user_name,
+            display_name: self.display_name,
+            email,
+            external_id: self.external_id,
+            role,
+            active: self.active,
+        }
+    }
+}
+
+impl From<ProvisionedUserDetail> for ScimUser {
+    fn from(detail: ProvisionedUserDetail) -> Self {
+        ScimUser {
+            schemas: vec![USER_SCHEMA.to_owned()],
+            meta: Some(ScimMeta {
+                resource_type: "User".to_owned(),
+                created: detail.user.created_at,
+                location: format!("{}/scim/v2/Users/{}", API_V1, detail.user.user_uuid),
+            }),
+            id: Some(detail.user.user_uuid.to_string()),
+            external_id: detail.external_id,
+            user_name: detail.user.username,
+            display_name: detail.display_name,
+            active: detail.active,
+            emails: detail
+                .user
+                .email
+                .map(|value| vec![ScimEmail { value, primary: true }])
+                .unwrap_or_default(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ScimGroupRef {
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub display: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    pub location: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<ScimUser>,
+}
+
+impl From<Vec<ScimUser>> for ScimListResponse {
+    fn from(resources: Vec<ScimUser>) -> Self {
+        ScimListResponse {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_owned()],
+            total_results: resources.len(),
+            start_index: 1,
+            items_per_page: resources.len(),
+            resources,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScimListQuery {
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ScimPatch {
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ScimPatchOperation {
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// Parses the only filter identity providers need for matching, `userName eq "<name>"`.
+pub fn parse_user_name_filter(filter: &str) -> Result<String, String> {
+    let mut parts = filter.trim().splitn(3, ' ');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(attribute), Some(operator), Some(value))
+            if attribute.eq_ignore_ascii_case("userName") && operator.eq_ignore_ascii_case("eq") =>
+        {
+            value
+                .trim()
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .map(str::to_owned)
+                .ok_or_else(|| format!("Unsupported filter value: {}", value))
+        }
+        _ => Err(format!("Unsupported filter: {}", filter)),
+    }
+}
+
+/// Reads the `active` flag set by a PATCH, which is how identity providers deprovision users.
+/// Other attributes are changed with PUT.
+pub fn patched_active(patch: &ScimPatch) -> Result<bool, String> {
+    let mut active = None;
+
+    for operation in &patch.operations {
+        if !operation.op.eq_ignore_ascii_case("replace") && !operation.op.eq_ignore_ascii_case("add") {
+            return Err(format!("Unsupported patch operation: {}", operation.op));
+        }
+
+        let value = match operation.path.as_deref() {
+            Some(path) if path.eq_ignore_ascii_case("active") => &operation.value,
+            Some(path) => return Err(format!("Unsupported patch path: {}", path)),
+            None => &operation.value["active"],
+        };
+
+        // Some providers send booleans as the strings "True" and "False".
+        active = Some(match value {
+            serde_json::Value::Bool(value) => *value,
+            serde_json::Value::String(value) if value.eq_ignore_ascii_case("true") => true,
+            serde_json::Value::String(value) if value.eq_ignore_ascii_case("false") => false,
+            other => return Err(format!("Invalid value for active: {}", other)),
+        });
+    }
+
+    active.ok_or_else(|| "Patch does not change any supported attribute.".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Role;
+
+    #[test]
+    fn parse_user_name_filter_should_accept_eq_filter() {
+        assert_eq!(parse_user_name_filter("userName eq \"jane doe\""), Ok("jane doe".to_owned()));
+        assert!(parse_user_name_filter("emails co \"example.com\"").is_err());
+    }
+
+    #[test]
+    fn patched_active_should_read_path_and_value_forms() {
+        let patch: ScimPatch = serde_json::from_value(serde_json::json!({
+            "Operations": [{ "op": "Replace", "path": "active", "value": "False" }]
+        }))
+        .unwrap();
+
+        assert_eq!(patched_active(&patch), Ok(false));
+
+        let patch: ScimPatch = serde_json::from_value(serde_json::json!({
+            "Operations": [{ "op": "replace", "value": { "active": true } }]
+        }))
+        .unwrap();
+
+        assert_eq!(patched_active(&patch), Ok(true));
+    }
+
+    #[test]
+    fn into_provisioned_should_map_groups_to_roles() {
+        let user: ScimUser = serde_json::from_value(serde_json::json!({
+            "userName": "jane",
+            "emails": [{ "value": "work@example.com" }, { "value": "jane@example.com", "primary": true }],
+            "groups": [{ "display": "Forum-Mods" }, { "value": "staff" }]
+        }))
+        .unwrap();
+
+        let group_roles = GroupRoleMap::parse("forum-mods:moderator,forum-admins:admin").unwrap();
+        let user = user.into_provisioned(&group_roles);
+
+        assert_eq!(user.role, Role::Moderator);
+        assert_eq!(user.email.as_deref(), Some("jane@example.com"));
+        assert!(user.active);
+    }
+}
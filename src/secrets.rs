@@ -0,0 +1,206 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SecretsError {
+    #[error("Secret {0} is not set")]
+    Missing(String),
+    #[error("Failed to read secret {name}: {source}")]
+    Io { name: String, source: io::Error },
+    #[error("Vault request for secret {name} failed: {message}")]
+    Vault { name: String, message: String },
+}
+
+/// Source of credentials such as `DATABASE_URL` or `PII_ENCRYPTION_KEYS`. Implement this to
+/// fetch secrets from a KMS or another secret manager.
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// Returns `None` when this provider has no value for `name`.
+    async fn get(&self, name: &str) -> Result<Option<String>, SecretsError>;
+
+    async fn require(&self, name: &str) -> Result<String, SecretsError> {
+        self.get(name)
+            .await?
+            .ok_or_else(|| SecretsError::Missing(name.to_owned()))
+    }
+}
+
+fn read_secret_file(name: &str, path: &Path) -> Result<String, SecretsError> {
+    std::fs::read_to_string(path)
+        .map(|value| value.trim_end_matches(['\r', '\n']).to_owned())
+        .map_err(|source| SecretsError::Io {
+            name: name.to_owned(),
+            source,
+        })
+}
+
+/// Reads `NAME`, or the file at `NAME_FILE` (the Docker/Kubernetes convention) when that is set.
+pub struct EnvSecrets;
+
+#[async_trait]
+impl SecretsProvider for EnvSecrets {
+    async fn get(&self, name: &str) -> Result<Option<String>, SecretsError> {
+        if let Ok(path) = std::env::var(format!("{}_FILE", name)) {
+            return read_secret_file(name, Path::new(&path)).map(Some);
+        }
+
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// Reads one file per secret from a directory such as `/run/secrets`.
+pub struct FileSecrets {
+    dir: PathBuf,
+}
+
+impl FileSecrets {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileSecrets { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for FileSecrets {
+    async fn get(&self, name: &str) -> Result<Option<String>, SecretsError> {
+        let path = self.dir.join(name);
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        read_secret_file(name, &path).map(Some)
+    }
+}
+
+/// Reads secrets from one HashiCorp Vault KV v2 entry, where each key is a secret name.
+pub struct VaultSecrets {
+    client: reqwest::Client,
+    address: String,
+    token: String,
+    mount: String,
+    path: String,
+}
+
+impl VaultSecrets {
+    pub fn new(address: String, token: String, mount: String, path: String) -> Self {
+        VaultSecrets {
+            client: reqwest::Client::new(),
+            address: address.trim_end_matches('/').to_owned(),
+            token,
+            mount,
+            path,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultSecrets {
+    async fn get(&self, name: &str) -> Result<Option<String>, SecretsError> {
+        let vault_error = |message: String| SecretsError::Vault {
+            name: name.to_owned(),
+            message,
+        };
+
+        let response = self
+            .client
+            .get(format!("{}/v1/{}/data/{}", self.address, self.mount, self.path))
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| vault_error(err.to_string()))?;
+
+        let body: serde_json::Value = response.json().await.map_err(|err| vault_error(err.to_string()))?;
+
+        Ok(body["data"]["data"][name].as_str().map(str::to_owned))
+    }
+}
+
+/// Asks each provider in turn and returns the first value found.
+pub struct ChainedSecrets {
+    providers: Vec<Box<dyn SecretsProvider>>,
+}
+
+#[async_trait]
+impl SecretsProvider for ChainedSecrets {
+    async fn get(&self, name: &str) -> Result<Option<String>, SecretsError> {
+        for provider in &self.providers {
+            if let Some(value) = provider.get(name).await? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Builds the provider chain from the environment: Vault when `VAULT_ADDR` is set, then
+/// files in `SECRETS_DIR`, then plain and `*_FILE` environment variables.
+pub async fn from_env() -> Result<ChainedSecrets, SecretsError> {
+    let mut providers: Vec<Box<dyn SecretsProvider>> = Vec::new();
+
+    if let Ok(address) = std::env::var("VAULT_ADDR") {
+        providers.push(Box::new(VaultSecrets::new(
+            address,
+            EnvSecrets.require("VAULT_TOKEN").await?,
+            std::env::var("VAULT_MOUNT").unwrap_or_else(|_| "secret".to_owned()),
+            EnvSecrets.require("VAULT_SECRET_PATH").await?,
+        )));
+    }
+
+    if let Ok(dir) = std::env::var("SECRETS_DIR") {
+        providers.push(Box::new(FileSecrets::new(dir)));
+    }
+
+    providers.push(Box::new(EnvSecrets));
+
+    Ok(ChainedSecrets { providers })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("forum-secrets-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn env_secrets_should_prefer_file_variant() {
+        let dir = temp_dir("env");
+        std::fs::write(dir.join("db_url"), "postgres://from-file\n").unwrap();
+
+        std::env::set_var("SECRETS_TEST_DB_URL", "postgres://from-env");
+        std::env::set_var("SECRETS_TEST_DB_URL_FILE", dir.join("db_url"));
+
+        let value = EnvSecrets.get("SECRETS_TEST_DB_URL").await.unwrap();
+
+        assert_eq!(value.as_deref(), Some("postgres://from-file"));
+    }
+
+    #[tokio::test]
+    async fn chained_secrets_should_fall_back_to_later_providers() {
+        let dir = temp_dir("chain");
+        std::fs::write(dir.join("SECRETS_TEST_FROM_DIR"), "from-dir").unwrap();
+
+        std::env::set_var("SECRETS_TEST_FROM_ENV", "from-env");
+
+        let secrets = ChainedSecrets {
+            providers: vec![Box::new(FileSecrets::new(&dir)), Box::new(EnvSecrets)],
+        };
+
+        assert_eq!(secrets.require("SECRETS_TEST_FROM_DIR").await.unwrap(), "from-dir");
+        assert_eq!(secrets.require("SECRETS_TEST_FROM_ENV").await.unwrap(), "from-env");
+        assert!(matches!(
+            secrets.require("SECRETS_TEST_UNSET").await,
+            Err(SecretsError::Missing(_))
+        ));
+    }
+}
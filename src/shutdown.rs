@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+    task::{AbortHandle, JoinHandle},
+    time::Interval,
+};
+
+/// Requests the shutdown of the background tasks holding a `ShutdownSignal` of the same channel.
+pub struct Shutdown {
+    sender: watch::Sender<bool>,
+}
+
+/// Lets a background task finish what it is doing before it stops: tasks poll with `tick` and
+/// stop at the first tick after shutdown was requested.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+pub fn channel() -> (Shutdown, ShutdownSignal) {
+    let (sender, receiver) = watch::channel(false);
+
+    (Shutdown { sender }, ShutdownSignal { receiver })
+}
+
+impl Shutdown {
+    pub fn request(&self) {
+        self.sender.send_replace(true);
+    }
+}
+
+impl ShutdownSignal {
+    /// Resolves once shutdown is requested, or the `Shutdown` is dropped.
+    pub async fn requested(&mut self) {
+        let _ = self.receiver.wait_for(|requested| *requested).await;
+    }
+
+    /// Waits for the next tick of `interval`. Returns false instead once shutdown is requested.
+    pub async fn tick(&mut self, interval: &mut Interval) -> bool {
+        tokio::select! {
+            biased;
+            _ = self.requested() => false,
+            _ = interval.tick() => true,
+        }
+    }
+}
+
+/// Resolves on SIGINT or on the SIGTERM sent by orchestrators ahead of stopping the process.
+pub async fn termination() {
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM!");
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT, shutting down."),
+        _ = terminate.recv() => info!("Received SIGTERM, shutting down."),
+    }
+}
+
+/// Waits up to `grace_period` for `tasks` to finish, then aborts the ones still running.
+pub async fn drain(tasks: Vec<JoinHandle<()>>, grace_period: Duration) {
+    let count = tasks.len();
+    let abort_handles: Vec<_> = tasks.iter().map(JoinHandle::abort_handle).collect();
+
+    if tokio::time::timeout(grace_period, futures::future::join_all(tasks)).await.is_ok() {
+        info!("Stopped {} background tasks.", count);
+    } else {
+        warn!("Background tasks still running after {:?}, aborting them.", grace_period);
+        abort_handles.iter().for_each(AbortHandle::abort);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tick_should_stop_once_shutdown_is_requested() {
+        let (shutdown, mut signal) = channel();
+        let mut interval = tokio::time::interval(Duration::from_millis(1));
+
+        assert!(signal.tick(&mut interval).await);
+        assert!(signal.tick(&mut interval).await);
+
+        shutdown.request();
+
+        assert!(!signal.tick(&mut interval).await);
+        assert!(!signal.clone().tick(&mut interval).await);
+    }
+
+    #[tokio::test]
+    async fn drain_should_abort_the_tasks_still_running_after_the_grace_period() {
+        let (shutdown, signal) = channel();
+        let finished = tokio::spawn({
+            let mut signal = signal.clone();
+            async move { signal.requested().await }
+        });
+        let stuck = tokio::spawn(std::future::pending::<()>());
+        let stuck_handle = stuck.abort_handle();
+
+        shutdown.request();
+
+        drain(vec![finished, stuck], Duration::from_millis(50)).await;
+        tokio::task::yield_now().await;
+
+        assert!(stuck_handle.is_finished());
+    }
+}
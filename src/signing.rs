@@ -0,0 +1,158 @@
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use utoipa::IntoParams;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SigningError {
+    #[error("Invalid URL signing key configuration: {0}")]
+    InvalidKeys(String),
+    #[error("Signed URL has expired")]
+    Expired,
+    #[error("Signed URL signature is invalid")]
+    InvalidSignature,
+}
+
+/// Query parameters carried by a signed URL.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UrlSignature {
+    pub expires: u64,
+    pub key_id: u32,
+    pub signature: String,
+}
+
+/// Signs paths with HMAC-SHA256 so they can be opened without authentication until they expire.
+///
+/// Every key stays valid for verification while it is configured, so removing a key id
+/// revokes all URLs signed with it.
+pub struct UrlSigner {
+    keys: BTreeMap<u32, Vec<u8>>,
+}
+
+impl UrlSigner {
+    /// Parses `id:secret` pairs separated by commas, e.g. `1:...,2:...`.
+    /// The highest id signs new URLs.
+    pub fn parse(value: &str) -> Result<Self, SigningError> {
+        let mut keys = BTreeMap::new();
+
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (id, secret) = entry
+                .split_once(':')
+                .ok_or_else(|| SigningError::InvalidKeys(format!("expected id:secret, got {}", entry)))?;
+            let id: u32 = id
+                .parse()
+                .map_err(|_| SigningError::InvalidKeys(format!("invalid key id {}", id)))?;
+
+            if secret.len() < 32 {
+                return Err(SigningError::InvalidKeys(format!("key {} must be at least 32 characters", id)));
+            }
+
+            keys.insert(id, secret.as_bytes().to_vec());
+        }
+
+        if keys.is_empty() {
+            return Err(SigningError::InvalidKeys("no keys configured".to_owned()));
+        }
+
+        Ok(UrlSigner { keys })
+    }
+
+    pub fn sign(&self, path: &str, expires: u64) -> UrlSignature {
+        let (key_id, key) = self.keys.iter().next_back().expect("at least one key is configured");
+
+        UrlSignature {
+            expires,
+            key_id: *key_id,
+            signature: hex::encode(mac(key, *key_id, path, expires).finalize().into_bytes()),
+        }
+    }
+
+    pub fn verify(&self, path: &str, signature: &UrlSignature, now: u64) -> Result<(), SigningError> {
+        let key = self.keys.get(&signature.key_id).ok_or(SigningError::InvalidSignature)?;
+        let expected = hex::decode(&signature.signature).map_err(|_| SigningError::InvalidSignature)?;
+
+        mac(key, signature.key_id, path, signature.expires)
+            .verify_slice(&expected)
+            .map_err(|_| SigningError::InvalidSignature)?;
+
+        if now >= signature.expires {
+            return Err(SigningError::Expired);
+        }
+
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch, the unit signed URLs expire in.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is after the Unix epoch")
+        .as_secs()
+}
+
+fn mac(key: &[u8], key_id: u32, path: &str, expires: u64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}", key_id, expires, path).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_1: &str = "1:first-url-signing-key-0123456789abcdef";
+    const KEY_2: &str = "2:second-url-signing-key-0123456789abcdef";
+
+    #[test]
+    fn verify_should_accept_signed_path() {
+        let signer = UrlSigner::parse(KEY_1).unwrap();
+        let signature = signer.sign("/shared/question/123", 1_000);
+
+        assert_eq!(signer.verify("/shared/question/123", &signature, 999), Ok(()));
+    }
+
+    #[test]
+    fn verify_should_reject_tampered_or_expired_urls() {
+        let signer = UrlSigner::parse(KEY_1).unwrap();
+        let signature = signer.sign("/shared/question/123", 1_000);
+
+        assert_eq!(
+            signer.verify("/shared/question/456", &signature, 999),
+            Err(SigningError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify("/shared/question/123", &UrlSignature { expires: 2_000, ..signature.clone() }, 999),
+            Err(SigningError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify("/shared/question/123", &signature, 1_000),
+            Err(SigningError::Expired)
+        );
+    }
+
+    #[test]
+    fn removing_a_key_should_revoke_its_urls() {
+        let signer = UrlSigner::parse(&format!("{},{}", KEY_1, KEY_2)).unwrap();
+        let old = UrlSigner::parse(KEY_1).unwrap().sign("/shared/question/123", 1_000);
+
+        assert_eq!(signer.sign("/shared/question/123", 1_000).key_id, 2);
+        assert_eq!(signer.verify("/shared/question/123", &old, 999), Ok(()));
+
+        let rotated = UrlSigner::parse(KEY_2).unwrap();
+
+        assert_eq!(
+            rotated.verify("/shared/question/123", &old, 999),
+            Err(SigningError::InvalidSignature)
+        );
+    }
+}
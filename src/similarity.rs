@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+/// Words per shingle. Three keeps reworded sentences apart while still matching copies with a
+/// few words changed.
+const SHINGLE_WORDS: usize = 3;
+
+/// The overlapping word sequences of `text`, ignoring case, punctuation and Markdown markup.
+/// Texts shorter than one shingle are a single shingle of all their words.
+fn shingles(text: &str) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    if words.len() < SHINGLE_WORDS {
+        return [words.join(" ")].into_iter().filter(|shingle| !shingle.is_empty()).collect();
+    }
+
+    words.windows(SHINGLE_WORDS).map(|window| window.join(" ")).collect()
+}
+
+/// Jaccard similarity of the two texts' shingles, from 0 (nothing in common) to 1 (the same words
+/// in the same order). Texts without words are not similar to anything.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (shingles(a), shingles(b));
+    let union = a.union(&b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The candidate most similar to `text`, if it reaches `threshold`.
+pub fn most_similar<'a, T>(
+    text: &str,
+    candidates: impl IntoIterator<Item = (T, &'a str)>,
+    threshold: f64,
+) -> Option<(T, f64)> {
+    candidates
+        .into_iter()
+        .map(|(candidate, content)| (candidate, similarity(text, content)))
+        .filter(|(_, score)| *score >= threshold)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_should_ignore_case_and_markup() {
+        let a = "Use `Arc<Mutex<T>>` to share state between threads.";
+        let b = "use arc mutex t to share state between threads";
+
+        assert_eq!(similarity(a, b), 1.0);
+        assert_eq!(similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn most_similar_should_only_return_near_duplicates() {
+        let answer = "You can clone the Rc to get another owner of the same value without copying it.";
+        let candidates = [
+            ("copy", "You can clone the Rc to get another owner of the same value without copying it!"),
+            ("edited", "You can clone the Rc to get another owner of the value without copying it."),
+            ("other", "Try a Box instead, it gives you a single owner on the heap."),
+        ];
+
+        assert_eq!(most_similar(answer, candidates, 0.6).map(|(name, _)| name), Some("copy"));
+        assert_eq!(most_similar(answer, candidates[1..].iter().copied(), 0.6).map(|(name, _)| name), Some("edited"));
+        assert_eq!(most_similar(answer, candidates[2..].iter().copied(), 0.6), None);
+    }
+}
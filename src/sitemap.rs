@@ -0,0 +1,79 @@
+use crate::{feeds::xml_escape, models::SitemapUrl, routes::API_V1};
+
+pub const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+pub const URLSET_START: &str =
+    "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n";
+pub const URLSET_END: &str = "</urlset>\n";
+
+/// Path of sitemap `page`, counting from 0; pages are named from 1.
+pub fn sitemap_path(page: i64) -> String {
+    format!("{}/sitemaps/questions-{}.xml", API_V1, page + 1)
+}
+
+/// The page of a `questions-:n.xml` file name, or None for any other file.
+pub fn parse_sitemap_file(file: &str) -> Option<i64> {
+    let number: i64 = file.strip_prefix("questions-")?.strip_suffix(".xml")?.parse().ok()?;
+
+    (number >= 1 && file == format!("questions-{}.xml", number)).then_some(number - 1)
+}
+
+/// The sitemap index, listing `pages` sitemaps. One is always listed, even without questions.
+pub fn sitemap_index(forum_url: &str, pages: i64) -> String {
+    let forum_url = forum_url.trim_end_matches('/');
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+
+    for page in 0..pages.max(1) {
+        let url = format!("{}{}", forum_url, sitemap_path(page));
+
+        xml.push_str(&format!("  <sitemap><loc>{}</loc></sitemap>\n", xml_escape(&url)));
+    }
+
+    xml.push_str("</sitemapindex>\n");
+    xml
+}
+
+/// One `<url>` of a sitemap, linking to the question on the forum.
+pub fn url_element(forum_url: &str, url: &SitemapUrl) -> String {
+    let loc = format!("{}/question/{}", forum_url.trim_end_matches('/'), url.question_uuid);
+
+    format!("  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n", xml_escape(&loc), url.last_modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn sitemap_index_should_list_every_page() {
+        let xml = sitemap_index("https://forum.example.com/", 2);
+
+        assert!(xml.contains("<sitemap><loc>https://forum.example.com/api/v1/sitemaps/questions-1.xml</loc></sitemap>"));
+        assert!(xml.contains("<sitemap><loc>https://forum.example.com/api/v1/sitemaps/questions-2.xml</loc></sitemap>"));
+        assert_eq!(xml.matches("<sitemap>").count(), 2);
+        assert_eq!(sitemap_index("https://forum.example.com", 0).matches("<sitemap>").count(), 1);
+    }
+
+    #[test]
+    fn parse_sitemap_file_should_only_accept_sitemap_pages() {
+        assert_eq!(parse_sitemap_file("questions-1.xml"), Some(0));
+        assert_eq!(parse_sitemap_file("questions-12.xml"), Some(11));
+
+        for file in ["questions-0.xml", "questions-01.xml", "questions--1.xml", "questions-1.atom", "answers-1.xml"] {
+            assert_eq!(parse_sitemap_file(file), None, "{}", file);
+        }
+    }
+
+    #[test]
+    fn url_element_should_link_to_the_question() {
+        let url = SitemapUrl { question_uuid: Uuid::from_u128(0x123), last_modified: "2026-10-17T10:00:00Z".to_owned() };
+
+        assert_eq!(
+            url_element("https://forum.example.com", &url),
+            "  <url><loc>https://forum.example.com/question/00000000-0000-0000-0000-000000000123</loc><lastmod>2026-10-17T10:00:00Z</lastmod></url>\n"
+        );
+    }
+}
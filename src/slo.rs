@@ -0,0 +1,374 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use thiserror::Error;
+
+use crate::{
+    models::{BurnSeverity, SloAlert, SloObjective, SloStatus, SloWindow},
+    signing::unix_timestamp,
+};
+
+#[derive(Error, Debug)]
+pub enum SloError {
+    #[error("Invalid SLO on line {0}: {1}")]
+    InvalidTarget(usize, String),
+    #[error("Failed to read SLO file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Windows burn rates are reported over, in minutes. The longest bounds what is kept per route.
+pub const WINDOWS: [u64; 4] = [5, 30, 60, 360];
+
+/// Multiwindow burn-rate alerts: a fast burn spends 2% of a 30-day budget in an hour, a slow burn
+/// 5% in six hours. Each fires only while its short window burns as fast, so that an alert clears
+/// soon after the burn stops.
+const FAST_BURN: BurnRule = BurnRule { long_minutes: 60, short_minutes: 5, rate: 14.4 };
+const SLOW_BURN: BurnRule = BurnRule { long_minutes: 360, short_minutes: 30, rate: 6.0 };
+
+struct BurnRule {
+    long_minutes: u64,
+    short_minutes: u64,
+    rate: f64,
+}
+
+/// Objectives of one route, as matched by the router, e.g. `GET /api/v1/question/:uuid`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloTarget {
+    pub route: String,
+    /// Fraction of requests that must not fail with a server error.
+    pub availability: f64,
+    /// Requests slower than `threshold_ms` that the `target` fraction must beat, if any.
+    pub latency: Option<LatencyTarget>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyTarget {
+    pub threshold_ms: u64,
+    pub target: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Bucket {
+    minute: u64,
+    requests: u64,
+    errors: u64,
+    slow: u64,
+}
+
+/// Counts the requests, server errors and slow responses of each route with a target, per minute.
+/// Counts are kept in memory, so each server instance tracks its own traffic.
+#[derive(Default)]
+pub struct SloTracker {
+    targets: Vec<SloTarget>,
+    buckets: Mutex<HashMap<String, VecDeque<Bucket>>>,
+}
+
+impl SloTracker {
+    pub fn new(targets: Vec<SloTarget>) -> Self {
+        SloTracker {
+            targets,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads the targets at `SLO_FILE`; without one, no route is tracked.
+    pub fn from_env() -> Result<Self, SloError> {
+        match std::env::var("SLO_FILE") {
+            Ok(path) => Ok(SloTracker::new(parse_targets(&std::fs::read_to_string(path)?)?)),
+            Err(_) => Ok(SloTracker::default()),
+        }
+    }
+
+    /// Counts a response to `route` that took `elapsed_ms`, at `now` in seconds since the Unix epoch.
+    pub fn record(&self, route: &str, status: u16, elapsed_ms: u64, now: u64) {
+        let Some(target) = self.targets.iter().find(|target| target.route == route) else {
+            return;
+        };
+
+        let minute = now / 60;
+        let mut buckets = self.buckets.lock().expect("SLO lock is not poisoned");
+        let buckets = buckets.entry(target.route.clone()).or_default();
+
+        if buckets.back().is_none_or(|bucket| bucket.minute != minute) {
+            buckets.push_back(Bucket { minute, ..Bucket::default() });
+        }
+
+        while buckets.front().is_some_and(|bucket| bucket.minute + WINDOWS[WINDOWS.len() - 1] <= minute) {
+            buckets.pop_front();
+        }
+
+        let bucket = buckets.back_mut().expect("the current minute was just pushed");
+        bucket.requests += 1;
+        bucket.errors += u64::from(status >= 500);
+        bucket.slow += u64::from(target.latency.is_some_and(|latency| elapsed_ms > latency.threshold_ms));
+    }
+
+    /// Burn rates of every target over each of `WINDOWS`, ending at `now`.
+    pub fn status(&self, now: u64) -> Vec<SloStatus> {
+        let buckets = self.buckets.lock().expect("SLO lock is not poisoned");
+        let minute = now / 60;
+
+        self.targets
+            .iter()
+            .map(|target| {
+                let buckets = buckets.get(&target.route);
+                let window = |minutes: u64| window(target, buckets, minute, minutes);
+
+                SloStatus {
+                    route: target.route.clone(),
+                    availability_target: target.availability,
+                    latency_threshold_ms: target.latency.map(|latency| latency.threshold_ms),
+                    latency_target: target.latency.map(|latency| latency.target),
+                    windows: WINDOWS.iter().map(|minutes| window(*minutes)).collect(),
+                    availability: severity(|minutes| window(minutes).availability_burn_rate),
+                    latency: target
+                        .latency
+                        .map(|_| severity(|minutes| window(minutes).latency_burn_rate.unwrap_or_default())),
+                }
+            })
+            .collect()
+    }
+}
+
+fn window(target: &SloTarget, buckets: Option<&VecDeque<Bucket>>, minute: u64, minutes: u64) -> SloWindow {
+    let mut totals = Bucket::default();
+
+    for bucket in buckets.into_iter().flatten().filter(|bucket| bucket.minute + minutes > minute) {
+        totals.requests += bucket.requests;
+        totals.errors += bucket.errors;
+        totals.slow += bucket.slow;
+    }
+
+    SloWindow {
+        minutes,
+        requests: totals.requests,
+        errors: totals.errors,
+        slow: totals.slow,
+        availability_burn_rate: burn_rate(totals.errors, totals.requests, target.availability),
+        latency_burn_rate: target.latency.map(|latency| burn_rate(totals.slow, totals.requests, latency.target)),
+    }
+}
+
+/// How many times faster than sustainable the error budget is spent: 1 spends exactly the budget.
+fn burn_rate(bad: u64, requests: u64, target: f64) -> f64 {
+    if requests == 0 {
+        return 0.0;
+    }
+
+    (bad as f64 / requests as f64) / (1.0 - target)
+}
+
+fn severity(burn_rate: impl Fn(u64) -> f64) -> BurnSeverity {
+    let burning = |rule: &BurnRule| burn_rate(rule.long_minutes) >= rule.rate && burn_rate(rule.short_minutes) >= rule.rate;
+
+    if burning(&FAST_BURN) {
+        BurnSeverity::FastBurn
+    } else if burning(&SLOW_BURN) {
+        BurnSeverity::SlowBurn
+    } else {
+        BurnSeverity::Ok
+    }
+}
+
+/// Alerts for the objectives whose severity changed since `previous`, which is updated to `status`.
+pub fn alerts(previous: &mut HashMap<(String, SloObjective), BurnSeverity>, status: &[SloStatus], now: u64) -> Vec<SloAlert> {
+    let mut alerts = Vec::new();
+
+    for route in status {
+        let hourly = route.windows.iter().find(|window| window.minutes == FAST_BURN.long_minutes);
+        let objectives = [
+            (SloObjective::Availability, Some(route.availability), hourly.map(|window| window.availability_burn_rate)),
+            (SloObjective::Latency, route.latency, hourly.and_then(|window| window.latency_burn_rate)),
+        ];
+
+        for (objective, severity, burn_rate) in objectives {
+            let Some(severity) = severity else {
+                continue;
+            };
+
+            let previous = previous.insert((route.route.clone(), objective), severity).unwrap_or_default();
+
+            if previous != severity {
+                alerts.push(SloAlert {
+                    route: route.route.clone(),
+                    objective,
+                    severity,
+                    previous,
+                    hourly_burn_rate: burn_rate.unwrap_or_default(),
+                    at: now,
+                });
+            }
+        }
+    }
+
+    alerts
+}
+
+/// One target per line: the method and route, the availability in percent and, optionally, a
+/// latency threshold with the percent of requests that must beat it. Blank lines and `#` comments
+/// are skipped.
+///
+/// ```text
+/// GET /api/v1/question/:uuid 99.9 300ms 99
+/// POST /api/v1/answer 99.5
+/// ```
+pub fn parse_targets(file: &str) -> Result<Vec<SloTarget>, SloError> {
+    file.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| parse_target(line).map_err(|reason| SloError::InvalidTarget(number, reason)))
+        .collect()
+}
+
+fn parse_target(line: &str) -> Result<SloTarget, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+
+    let (method, path, availability, latency) = match fields[..] {
+        [method, path, availability] => (method, path, availability, None),
+        [method, path, availability, threshold, target] => (method, path, availability, Some((threshold, target))),
+        _ => return Err("expected a method, a route, an availability and an optional latency".to_owned()),
+    };
+
+    if !path.starts_with('/') {
+        return Err(format!("route {} does not start with /", path));
+    }
+
+    let latency = match latency {
+        Some((threshold, target)) => Some(LatencyTarget {
+            threshold_ms: threshold
+                .strip_suffix("ms")
+                .and_then(|threshold| threshold.parse().ok())
+                .ok_or_else(|| format!("latency threshold {} is not in milliseconds, like 300ms", threshold))?,
+            target: fraction(target)?,
+        }),
+        None => None,
+    };
+
+    Ok(SloTarget {
+        route: format!("{} {}", method.to_uppercase(), path),
+        availability: fraction(availability)?,
+        latency,
+    })
+}
+
+fn fraction(percent: &str) -> Result<f64, String> {
+    match percent.trim_end_matches('%').parse::<f64>() {
+        Ok(percent) if percent > 0.0 && percent < 100.0 => Ok(percent / 100.0),
+        _ => Err(format!("{} is not a percentage between 0 and 100, exclusive", percent)),
+    }
+}
+
+/// Records the outcome of each request to a route with a target.
+pub async fn track(State(tracker): State<Arc<SloTracker>>, request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| format!("{} {}", request.method(), path.as_str()));
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    if let Some(route) = route {
+        let elapsed_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+        tracker.record(&route, response.status().as_u16(), elapsed_ms, unix_timestamp());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> SloTracker {
+        SloTracker::new(parse_targets("GET /questions 99 300ms 90").unwrap())
+    }
+
+    #[test]
+    fn parse_targets_should_read_availability_and_optional_latency() {
+        let targets = parse_targets("# reads\nget /question/:uuid 99.5 300ms 99%\n\nPOST /answer 99\n").unwrap();
+
+        assert_eq!(
+            targets,
+            vec![
+                SloTarget {
+                    route: "GET /question/:uuid".to_owned(),
+                    availability: 0.995,
+                    latency: Some(LatencyTarget { threshold_ms: 300, target: 0.99 }),
+                },
+                SloTarget { route: "POST /answer".to_owned(), availability: 0.99, latency: None },
+            ]
+        );
+
+        for invalid in ["GET /answer", "GET answer 99", "GET /answer 100", "GET /answer 99 300 99", "GET /answer 99 300ms"] {
+            assert!(parse_targets(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn status_should_report_burn_rates_per_window() {
+        let tracker = tracker();
+        let now = 1_000_000 * 60;
+
+        for elapsed_ms in [100, 100, 100, 400] {
+            tracker.record("GET /questions", 200, elapsed_ms, now - 20 * 60);
+        }
+        tracker.record("GET /questions", 503, 100, now);
+        tracker.record("GET /other", 503, 100, now);
+
+        let status = tracker.status(now);
+        let windows: Vec<_> = status[0].windows.iter().map(|window| (window.minutes, window.requests, window.errors, window.slow)).collect();
+
+        assert_eq!(status.len(), 1);
+        assert_eq!(windows, vec![(5, 1, 1, 0), (30, 5, 1, 1), (60, 5, 1, 1), (360, 5, 1, 1)]);
+        assert!((status[0].windows[1].availability_burn_rate - 20.0).abs() < 1e-9);
+        assert!((status[0].windows[1].latency_burn_rate.unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn status_should_need_both_windows_burning_to_alert() {
+        let tracker = tracker();
+        let now = 1_000_000 * 60;
+
+        tracker.record("GET /questions", 500, 100, now - 40 * 60);
+        tracker.record("GET /questions", 200, 100, now - 40 * 60);
+        assert_eq!(tracker.status(now)[0].availability, BurnSeverity::Ok);
+
+        tracker.record("GET /questions", 500, 100, now);
+        assert_eq!(tracker.status(now)[0].availability, BurnSeverity::FastBurn);
+        assert_eq!(tracker.status(now)[0].latency, Some(BurnSeverity::Ok));
+        assert_eq!(tracker.status(now + 6 * 60)[0].availability, BurnSeverity::SlowBurn);
+        assert_eq!(tracker.status(now + 6 * 60 * 60)[0].availability, BurnSeverity::Ok);
+    }
+
+    #[test]
+    fn alerts_should_only_report_changes() {
+        let tracker = tracker();
+        let now = 1_000_000 * 60;
+        let mut previous = HashMap::new();
+
+        tracker.record("GET /questions", 500, 100, now);
+
+        let raised = alerts(&mut previous, &tracker.status(now), now);
+        assert_eq!(raised.len(), 1);
+        assert_eq!(
+            (raised[0].objective, raised[0].severity, raised[0].previous),
+            (SloObjective::Availability, BurnSeverity::FastBurn, BurnSeverity::Ok)
+        );
+
+        assert!(alerts(&mut previous, &tracker.status(now), now).is_empty());
+
+        let cleared = alerts(&mut previous, &tracker.status(now + 7 * 60 * 60), now);
+        assert_eq!((cleared[0].severity, cleared[0].previous), (BurnSeverity::Ok, BurnSeverity::FastBurn));
+    }
+}
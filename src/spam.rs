@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::{
+    markdown,
+    secrets::{SecretsError, SecretsProvider},
+    similarity,
+};
+
+const AKISMET_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How far back an author's posts are passed to checkers as `SpamCandidate::recent_posts`.
+pub const RECENT_POSTS_WINDOW_MINUTES: i32 = 10;
+
+#[derive(Error, Debug)]
+pub enum SpamError {
+    #[error("Invalid spam checker configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Failed to read spam checker secret: {0}")]
+    Secrets(#[from] SecretsError),
+    #[error("Spam check request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+/// A new question or answer about to be published.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpamCandidate {
+    /// A question's title and description, or an answer's content.
+    pub content: String,
+    pub author_uuid: Option<String>,
+    pub client_ip: String,
+    /// The author's other posts from the last `RECENT_POSTS_WINDOW_MINUTES`, newest first.
+    /// Always empty for anonymous posts.
+    pub recent_posts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpamVerdict {
+    Ham,
+    /// Likely spam, with the reason shown to moderators.
+    Spam(String),
+}
+
+/// Decides whether a new post is held for moderation. Implement this to add another spam service.
+#[async_trait]
+pub trait SpamChecker: Send + Sync {
+    async fn check(&self, candidate: &SpamCandidate) -> Result<SpamVerdict, SpamError>;
+}
+
+/// Builds the checker selected by `SPAM_CHECKER`: `heuristic` (the default), `akismet` or `off`.
+pub async fn checker_from_env(secrets: &dyn SecretsProvider) -> Result<Box<dyn SpamChecker>, SpamError> {
+    match std::env::var("SPAM_CHECKER").as_deref() {
+        Err(_) | Ok("heuristic") => Ok(Box::new(HeuristicSpamChecker::default())),
+        Ok("akismet") => Ok(Box::new(AkismetSpamChecker::from_env(secrets).await?)),
+        Ok("off") => Ok(Box::new(NoSpamChecker)),
+        Ok(other) => Err(SpamError::InvalidConfig(format!("unknown SPAM_CHECKER {}", other))),
+    }
+}
+
+/// Lets every post through.
+pub struct NoSpamChecker;
+
+#[async_trait]
+impl SpamChecker for NoSpamChecker {
+    async fn check(&self, _: &SpamCandidate) -> Result<SpamVerdict, SpamError> {
+        Ok(SpamVerdict::Ham)
+    }
+}
+
+/// Catches the usual link spam without an external service: too many links, the same text posted
+/// again, or too many posts in a short time.
+pub struct HeuristicSpamChecker {
+    pub max_links: usize,
+    /// `similarity::similarity` from which a post counts as a repeat of a recent one.
+    pub duplicate_threshold: f64,
+    /// Posts allowed per `RECENT_POSTS_WINDOW_MINUTES`, counting the new one.
+    pub max_recent_posts: usize,
+}
+
+impl Default for HeuristicSpamChecker {
+    fn default() -> Self {
+        HeuristicSpamChecker {
+            max_links: 5,
+            duplicate_threshold: 0.9,
+            max_recent_posts: 5,
+        }
+    }
+}
+
+#[async_trait]
+impl SpamChecker for HeuristicSpamChecker {
+    async fn check(&self, candidate: &SpamCandidate) -> Result<SpamVerdict, SpamError> {
+        let links = markdown::link_count(&candidate.content);
+
+        if links > self.max_links {
+            return Ok(SpamVerdict::Spam(format!("Contains {} links.", links)));
+        }
+
+        if candidate.recent_posts.len() >= self.max_recent_posts {
+            return Ok(SpamVerdict::Spam(format!(
+                "{} posts within {} minutes.",
+                candidate.recent_posts.len() + 1,
+                RECENT_POSTS_WINDOW_MINUTES
+            )));
+        }
+
+        let recent_posts = candidate.recent_posts.iter().map(|post| ((), post.as_str()));
+
+        if let Some((_, score)) = similarity::most_similar(&candidate.content, recent_posts, self.duplicate_threshold) {
+            return Ok(SpamVerdict::Spam(format!("{:.0}% similar to a recent post.", score * 100.0)));
+        }
+
+        Ok(SpamVerdict::Ham)
+    }
+}
+
+/// Asks Akismet's `comment-check` API, for sites with an Akismet key.
+pub struct AkismetSpamChecker {
+    api_key: String,
+    /// The forum's front page, registered with the key.
+    blog: String,
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl AkismetSpamChecker {
+    pub async fn from_env(secrets: &dyn SecretsProvider) -> Result<Self, SpamError> {
+        let api_key = secrets.require("AKISMET_API_KEY").await?;
+
+        Ok(AkismetSpamChecker {
+            endpoint: format!("https://{}.rest.akismet.com/1.1/comment-check", api_key),
+            api_key,
+            blog: std::env::var("AKISMET_BLOG_URL")
+                .map_err(|_| SpamError::InvalidConfig("AKISMET_BLOG_URL must be set".to_owned()))?,
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl SpamChecker for AkismetSpamChecker {
+    async fn check(&self, candidate: &SpamCandidate) -> Result<SpamVerdict, SpamError> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .timeout(AKISMET_TIMEOUT)
+            .form(&[
+                ("api_key", self.api_key.as_str()),
+                ("blog", self.blog.as_str()),
+                ("user_ip", candidate.client_ip.as_str()),
+                ("comment_type", "forum-post"),
+                ("comment_content", candidate.content.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        // Anything but `true` or `false` is an error message, e.g. for an invalid key.
+        match response.trim() {
+            "true" => Ok(SpamVerdict::Spam("Reported as spam by Akismet.".to_owned())),
+            "false" => Ok(SpamVerdict::Ham),
+            other => Err(SpamError::InvalidConfig(format!("unexpected Akismet response {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(content: &str, recent_posts: &[&str]) -> SpamCandidate {
+        SpamCandidate {
+            content: content.to_owned(),
+            author_uuid: Some("789".to_owned()),
+            client_ip: "203.0.113.1".to_owned(),
+            recent_posts: recent_posts.iter().map(|post| post.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn heuristic_checker_should_let_ordinary_posts_through() {
+        let checker = HeuristicSpamChecker::default();
+        let post = candidate(
+            "How do I share state between threads? See https://doc.rust-lang.org/book/ch16-03-shared-state.html",
+            &["What is the difference between Rc and Arc?"],
+        );
+
+        assert_eq!(checker.check(&post).await.unwrap(), SpamVerdict::Ham);
+    }
+
+    #[tokio::test]
+    async fn heuristic_checker_should_hold_posts_with_many_links() {
+        let checker = HeuristicSpamChecker::default();
+        let content = (1..=6).map(|n| format!("https://example.com/{}", n)).collect::<Vec<_>>().join(" ");
+
+        assert_eq!(
+            checker.check(&candidate(&content, &[])).await.unwrap(),
+            SpamVerdict::Spam("Contains 6 links.".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn heuristic_checker_should_hold_repeated_posts() {
+        let checker = HeuristicSpamChecker::default();
+        let post = candidate("Buy cheap watches at our store today", &["Buy cheap watches at our store today!"]);
+
+        assert_eq!(
+            checker.check(&post).await.unwrap(),
+            SpamVerdict::Spam("100% similar to a recent post.".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn heuristic_checker_should_hold_fast_posters() {
+        let checker = HeuristicSpamChecker::default();
+        let post = candidate("Another question", &["one", "two", "three", "four", "five"]);
+
+        assert_eq!(
+            checker.check(&post).await.unwrap(),
+            SpamVerdict::Spam("6 posts within 10 minutes.".to_owned())
+        );
+    }
+}
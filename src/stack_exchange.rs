@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+
+use quick_xml::{events::Event, Reader};
+use time::{macros::format_description, OffsetDateTime, PrimitiveDateTime};
+use uuid::Uuid;
+
+use crate::{
+    import::{invalid, DumpError},
+    models::{ImportRecord, ImportedAnswer, ImportedQuestion, ImportedUser},
+    validation::Validate,
+};
+
+/// Files of a Stack Exchange data dump that are imported; the others, such as `Comments.xml` and
+/// `Votes.xml`, have nothing to map to.
+pub const POSTS_FILE: &str = "Posts.xml";
+pub const USERS_FILE: &str = "Users.xml";
+
+/// Users and posts are given UUIDs derived from their Stack Exchange ids under this namespace, so
+/// importing a dump again skips what it imported before.
+const NAMESPACE: Uuid = Uuid::from_u128(0x5e0d_ab1e_4c1b_4f3a_9d2e_7a6b_c3f1_e8d4);
+
+const QUESTION_POST_TYPE: &str = "1";
+const ANSWER_POST_TYPE: &str = "2";
+
+/// The users of `users_xml` followed by the questions and answers of `posts_xml`. Other post types,
+/// such as tag wikis, are left out, as are answers to questions missing from the dump. Posts of
+/// users missing from the dump, usually deleted accounts, are imported without an author.
+pub fn read_dump(users_xml: Option<&[u8]>, posts_xml: &[u8]) -> Result<Vec<ImportRecord>, DumpError> {
+    let mut records = Vec::new();
+    let mut user_uuids = HashSet::new();
+    let mut usernames = HashSet::new();
+
+    for row in rows(USERS_FILE, users_xml.unwrap_or_default())? {
+        let user = ImportedUser {
+            user_uuid: derived_uuid("users", row.require("Id")?),
+            username: unique_username(&mut usernames, row.require("DisplayName")?, row.require("Id")?),
+            created_at: row.date("CreationDate")?,
+        };
+
+        user_uuids.insert(user.user_uuid);
+        records.push(row.validated(ImportRecord::User(user))?);
+    }
+
+    let posts = rows(POSTS_FILE, posts_xml)?;
+    let author = |row: &Row| {
+        row.get("OwnerUserId")
+            .map(|id| derived_uuid("users", id))
+            .filter(|user_uuid| user_uuids.contains(user_uuid))
+    };
+    let mut question_uuids = HashSet::new();
+
+    for row in posts.iter().filter(|row| row.get("PostTypeId") == Some(QUESTION_POST_TYPE)) {
+        let question = ImportedQuestion {
+            question_uuid: derived_uuid("posts", row.require("Id")?),
+            title: row.require("Title")?.to_owned(),
+            description: row.require("Body")?.to_owned(),
+            author_uuid: author(row),
+            tags: tags(row.get("Tags").unwrap_or_default()),
+            created_at: row.date("CreationDate")?,
+            updated_at: row.get("LastEditDate").map(|_| row.date("LastEditDate")).transpose()?,
+        };
+
+        question_uuids.insert(question.question_uuid);
+        records.push(row.validated(ImportRecord::Question(question))?);
+    }
+
+    for row in posts.iter().filter(|row| row.get("PostTypeId") == Some(ANSWER_POST_TYPE)) {
+        let question_uuid = derived_uuid("posts", row.require("ParentId")?);
+
+        if !question_uuids.contains(&question_uuid) {
+            continue;
+        }
+
+        let answer = ImportedAnswer {
+            answer_uuid: derived_uuid("posts", row.require("Id")?),
+            question_uuid,
+            content: row.require("Body")?.to_owned(),
+            author_uuid: author(row),
+            created_at: row.date("CreationDate")?,
+        };
+
+        records.push(row.validated(ImportRecord::Answer(answer))?);
+    }
+
+    Ok(records)
+}
+
+fn derived_uuid(kind: &str, id: &str) -> Uuid {
+    Uuid::new_v5(&NAMESPACE, format!("{}/{}", kind, id).as_bytes())
+}
+
+/// Display names are not unique on Stack Exchange; a name taken by an earlier user of the dump
+/// gets the user's id appended.
+fn unique_username(usernames: &mut HashSet<String>, display_name: &str, id: &str) -> String {
+    let mut username = display_name.trim().to_owned();
+
+    if !usernames.insert(username.clone()) {
+        username = format!("{}-{}", username, id);
+        usernames.insert(username.clone());
+    }
+
+    username
+}
+
+/// Tags are listed as `<rust><lifetimes>` in older dumps and as `|rust|lifetimes|` in newer ones.
+fn tags(tags: &str) -> Vec<String> {
+    tags.split(['<', '>', '|'])
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A `<row>` of a dump file, by its attributes.
+struct Row {
+    file: &'static str,
+    number: usize,
+    attributes: HashMap<String, String>,
+}
+
+impl Row {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn error(&self, message: String) -> DumpError {
+        DumpError::Record { file: self.file, record: self.number, message }
+    }
+
+    fn require(&self, name: &str) -> Result<&str, DumpError> {
+        self.get(name).ok_or_else(|| self.error(format!("{} is missing.", name)))
+    }
+
+    /// Dates are written in UTC without an offset, as in `2008-07-31T21:42:52.667`.
+    fn date(&self, name: &str) -> Result<OffsetDateTime, DumpError> {
+        let format = format_description!("[year]-[month]-[day]T[hour]:[minute]:[second][optional [.[subsecond]]]");
+
+        PrimitiveDateTime::parse(self.require(name)?, format)
+            .map(PrimitiveDateTime::assume_utc)
+            .map_err(|err| self.error(format!("{} is not a date: {}", name, err)))
+    }
+
+    fn validated(&self, record: ImportRecord) -> Result<ImportRecord, DumpError> {
+        record.validate().map_err(|errors| self.error(invalid(errors)))?;
+
+        Ok(record)
+    }
+}
+
+fn rows(file: &'static str, xml: &[u8]) -> Result<Vec<Row>, DumpError> {
+    let mut reader = Reader::from_reader(xml);
+    let mut rows = Vec::new();
+    let malformed = |message: String| DumpError::File { file, message };
+
+    loop {
+        match reader.read_event().map_err(|err| malformed(err.to_string()))? {
+            Event::Empty(element) | Event::Start(element) if element.name().as_ref() == b"row" => {
+                let mut attributes = HashMap::new();
+
+                for attribute in element.attributes() {
+                    let attribute = attribute.map_err(|err| malformed(err.to_string()))?;
+                    let value = attribute.unescape_value().map_err(|err| malformed(err.to_string()))?;
+
+                    attributes.insert(String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value.into_owned());
+                }
+
+                rows.push(Row { file, number: rows.len() + 1, attributes });
+            }
+            Event::Eof => return Ok(rows),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    const USERS: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<users>
+  <row Id="1" Reputation="101" CreationDate="2010-07-28T16:38:27.683" DisplayName="ada" />
+  <row Id="2" Reputation="1" CreationDate="2010-07-29T10:00:00" DisplayName="ada" />
+</users>"#;
+
+    const POSTS: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<posts>
+  <row Id="10" PostTypeId="1" CreationDate="2010-08-01T10:00:00.000" LastEditDate="2010-08-02T10:00:00.000" OwnerUserId="1" Title="Borrowing &amp; moving" Body="&lt;p&gt;Why?&lt;/p&gt;" Tags="&lt;rust&gt;&lt;borrow-checker&gt;" />
+  <row Id="11" PostTypeId="2" ParentId="10" CreationDate="2010-08-01T11:00:00.000" OwnerUserId="99" Body="&lt;p&gt;Because.&lt;/p&gt;" />
+  <row Id="12" PostTypeId="2" ParentId="404" CreationDate="2010-08-01T12:00:00.000" OwnerUserId="2" Body="&lt;p&gt;Orphan.&lt;/p&gt;" />
+  <row Id="13" PostTypeId="4" CreationDate="2010-08-01T13:00:00.000" Body="Tag wiki excerpt" />
+</posts>"#;
+
+    #[test]
+    fn read_dump_should_map_users_questions_and_answers() {
+        let records = read_dump(Some(USERS), POSTS).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                ImportRecord::User(ImportedUser {
+                    user_uuid: derived_uuid("users", "1"),
+                    username: "ada".to_owned(),
+                    created_at: datetime!(2010-07-28 16:38:27.683 UTC),
+                }),
+                ImportRecord::User(ImportedUser {
+                    user_uuid: derived_uuid("users", "2"),
+                    username: "ada-2".to_owned(),
+                    created_at: datetime!(2010-07-29 10:00:00 UTC),
+                }),
+                ImportRecord::Question(ImportedQuestion {
+                    question_uuid: derived_uuid("posts", "10"),
+                    title: "Borrowing & moving".to_owned(),
+                    description: "<p>Why?</p>".to_owned(),
+                    author_uuid: Some(derived_uuid("users", "1")),
+                    tags: vec!["rust".to_owned(), "borrow-checker".to_owned()],
+                    created_at: datetime!(2010-08-01 10:00:00 UTC),
+                    updated_at: Some(datetime!(2010-08-02 10:00:00 UTC)),
+                }),
+                ImportRecord::Answer(ImportedAnswer {
+                    answer_uuid: derived_uuid("posts", "11"),
+                    question_uuid: derived_uuid("posts", "10"),
+                    content: "<p>Because.</p>".to_owned(),
+                    author_uuid: None,
+                    created_at: datetime!(2010-08-01 11:00:00 UTC),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_should_read_both_formats() {
+        assert_eq!(tags("<rust><traits>"), vec!["rust", "traits"]);
+        assert_eq!(tags("|rust|traits|"), vec!["rust", "traits"]);
+        assert!(tags("").is_empty());
+    }
+
+    #[test]
+    fn read_dump_should_report_the_invalid_row() {
+        let posts = br#"<posts><row Id="10" PostTypeId="1" CreationDate="yesterday" Title="t" Body="b" /></posts>"#;
+
+        assert!(matches!(
+            read_dump(None, posts),
+            Err(DumpError::Record { file: POSTS_FILE, record: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{future::Future, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    http::{header::HOST, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::{
+    postgres::{PgConnection, PgPoolOptions},
+    types::Uuid,
+};
+
+use crate::{
+    cache::TtlCache,
+    models::DBError,
+    persistance::tenants_dao::TenantsDao,
+    signing::unix_timestamp,
+};
+
+/// How long the tenant of a host is remembered, so that a tenant added for a host that was already
+/// requested is served within a minute.
+const TENANT_HOSTS_TTL_SECONDS: u64 = 60;
+const TENANT_HOSTS_CAPACITY: usize = 1_000;
+
+tokio::task_local! {
+    static CURRENT_TENANT: Uuid;
+}
+
+/// The tenant of the request being served by the current task, if any.
+pub fn current_tenant() -> Option<Uuid> {
+    CURRENT_TENANT.try_with(|tenant| *tenant).ok()
+}
+
+/// Runs `future` with `tenant` set, as background work done for a tenant has no request to take
+/// it from.
+pub async fn with_tenant<F: Future>(tenant: Option<Uuid>, future: F) -> F::Output {
+    match tenant {
+        Some(tenant) => CURRENT_TENANT.scope(tenant, future).await,
+        None => future.await,
+    }
+}
+
+/// The tenants background jobs run for, each in turn with `with_tenant`, as row-level security only
+/// shows them the rows of the tenant they run for: every tenant, then `None` for the rows without
+/// one, which are all of them unless multi-tenancy is enabled. Empty when the tenants cannot be
+/// listed, so that the jobs wait for their next run.
+pub async fn job_tenants(tenants_dao: &(dyn TenantsDao + Send + Sync)) -> Vec<Option<Uuid>> {
+    match tenants_dao.get_tenants().await {
+        Ok(tenants) => tenants.into_iter().map(Some).chain([None]).collect(),
+        Err(err) => {
+            error!("Error to list tenants for background jobs: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Finds the tenant served at each host, remembering hosts for a minute so that most requests skip
+/// the lookup.
+pub struct TenantHosts {
+    tenants_dao: Arc<dyn TenantsDao + Send + Sync>,
+    hosts: TtlCache<Option<Uuid>>,
+}
+
+impl TenantHosts {
+    pub fn new(tenants_dao: Arc<dyn TenantsDao + Send + Sync>) -> Self {
+        TenantHosts {
+            tenants_dao,
+            hosts: TtlCache::new(TENANT_HOSTS_TTL_SECONDS, TENANT_HOSTS_CAPACITY),
+        }
+    }
+
+    async fn tenant_of(&self, host: &str) -> Result<Option<Uuid>, DBError> {
+        let now = unix_timestamp();
+
+        if let Some(tenant) = self.hosts.get(host, now) {
+            return Ok(tenant);
+        }
+
+        let tenant = self.tenants_dao.get_tenant_by_host(host.to_owned()).await?;
+        self.hosts.insert(host.to_owned(), tenant, now);
+
+        Ok(tenant)
+    }
+}
+
+/// Runs the rest of the stack with the tenant served at the request's host set, so every connection
+/// it acquires is scoped to it, and rejects requests to hosts that serve no tenant. API tokens are
+/// only valid at the host of their user's tenant.
+pub async fn tenant_middleware(State(hosts): State<Arc<TenantHosts>>, request: Request, next: Next) -> Response {
+    let host = request
+        .headers()
+        .get(HOST)
+        .and_then(|value| value.to_str().ok())
+        .map(host_name)
+        .unwrap_or_default();
+
+    match hosts.tenant_of(&host).await {
+        Ok(Some(tenant)) => CURRENT_TENANT.scope(tenant, next.run(request)).await,
+        Ok(None) => (StatusCode::NOT_FOUND, "No tenant is served at this host.".to_owned()).into_response(),
+        Err(err) => {
+            error!("Error to find the tenant of host {}: {}", host, err);
+            (StatusCode::SERVICE_UNAVAILABLE, "The tenant of this host could not be found.".to_owned()).into_response()
+        }
+    }
+}
+
+/// The host of a `Host` header, without its port and in lowercase.
+fn host_name(host: &str) -> String {
+    let end = if host.starts_with('[') {
+        host.find(']').map_or(host.len(), |end| end + 1)
+    } else {
+        host.find(':').unwrap_or(host.len())
+    };
+
+    host[..end].to_ascii_lowercase()
+}
+
+/// Installs pool hooks that set `app.tenant_id` whenever a connection is handed out,
+/// which the row-level security policies in the migrations rely on.
+pub fn with_tenant_hooks(options: PgPoolOptions) -> PgPoolOptions {
+    options
+        .after_connect(|conn, _| Box::pin(async move { apply_current_tenant(conn).await }))
+        .before_acquire(|conn, _| {
+            Box::pin(async move { apply_current_tenant(conn).await.map(|_| true) })
+        })
+}
+
+async fn apply_current_tenant(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let tenant = current_tenant().map(|tenant| tenant.to_string()).unwrap_or_default();
+
+    sqlx::query("SELECT set_config('app.tenant_id', $1, false)")
+        .bind(tenant)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_name_should_drop_the_port_and_lowercase() {
+        assert_eq!(host_name("Forum.Example.com"), "forum.example.com");
+        assert_eq!(host_name("forum.example.com:8080"), "forum.example.com");
+        assert_eq!(host_name("[::1]:8080"), "[::1]");
+        assert_eq!(host_name("[::1]"), "[::1]");
+    }
+}
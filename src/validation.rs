@@ -0,0 +1,138 @@
+use crate::models::{Answer, AnswerUpdate, FieldError, ImportRecord, ImportedUser, Question, QuestionDraft};
+
+/// Checks of a request body beyond what deserializing it ensures. Every invalid field is reported,
+/// so that clients can flag them all at once.
+pub trait Validate {
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+impl Validate for Question {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        require_text(&mut errors, "title", &self.title, Question::MAX_TITLE_CHARS);
+        require_text(&mut errors, "description", &self.description, Question::MAX_DESCRIPTION_CHARS);
+
+        into_result(errors)
+    }
+}
+
+impl Validate for Answer {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        require_text(&mut errors, "content", &self.content, Answer::MAX_CONTENT_CHARS);
+
+        into_result(errors)
+    }
+}
+
+impl Validate for AnswerUpdate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        require_text(&mut errors, "content", &self.content, Answer::MAX_CONTENT_CHARS);
+
+        into_result(errors)
+    }
+}
+
+/// Drafts may be incomplete, but must fit the question they become.
+impl Validate for QuestionDraft {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        limit_text(&mut errors, "title", &self.title, Question::MAX_TITLE_CHARS);
+        limit_text(&mut errors, "description", &self.description, Question::MAX_DESCRIPTION_CHARS);
+
+        into_result(errors)
+    }
+}
+
+/// Imported posts must fit like the ones posted here.
+impl Validate for ImportRecord {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        match self {
+            ImportRecord::User(user) => {
+                require_text(&mut errors, "username", &user.username, ImportedUser::MAX_USERNAME_CHARS);
+            }
+            ImportRecord::Question(question) => {
+                require_text(&mut errors, "title", &question.title, Question::MAX_TITLE_CHARS);
+                require_text(&mut errors, "description", &question.description, Question::MAX_DESCRIPTION_CHARS);
+            }
+            ImportRecord::Answer(answer) => {
+                require_text(&mut errors, "content", &answer.content, Answer::MAX_CONTENT_CHARS);
+            }
+        }
+
+        into_result(errors)
+    }
+}
+
+fn require_text(errors: &mut Vec<FieldError>, field: &str, value: &str, max_chars: usize) {
+    if value.trim().is_empty() {
+        errors.push(FieldError {
+            field: field.to_owned(),
+            message: "Must not be empty.".to_owned(),
+        });
+    } else {
+        limit_text(errors, field, value, max_chars);
+    }
+}
+
+fn limit_text(errors: &mut Vec<FieldError>, field: &str, value: &str, max_chars: usize) {
+    if value.chars().count() > max_chars {
+        errors.push(FieldError {
+            field: field.to_owned(),
+            message: format!("Must be at most {} characters.", max_chars),
+        });
+    }
+}
+
+fn into_result(errors: Vec<FieldError>) -> Result<(), Vec<FieldError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(title: &str, description: &str) -> Question {
+        serde_json::from_value(serde_json::json!({ "title": title, "description": description })).unwrap()
+    }
+
+    #[test]
+    fn question_should_report_every_invalid_field() {
+        assert_eq!(question("How?", "Like this.").validate(), Ok(()));
+        assert_eq!(
+            question(" ", &"a".repeat(Question::MAX_DESCRIPTION_CHARS + 1)).validate(),
+            Err(vec![
+                FieldError { field: "title".to_owned(), message: "Must not be empty.".to_owned() },
+                FieldError { field: "description".to_owned(), message: "Must be at most 30000 characters.".to_owned() },
+            ])
+        );
+    }
+
+    #[test]
+    fn lengths_should_count_characters_rather_than_bytes() {
+        assert_eq!(question(&"é".repeat(Question::MAX_TITLE_CHARS), "body").validate(), Ok(()));
+        assert!(question(&"é".repeat(Question::MAX_TITLE_CHARS + 1), "body").validate().is_err());
+    }
+
+    #[test]
+    fn drafts_may_be_empty_but_not_too_long() {
+        let draft = QuestionDraft { title: "".to_owned(), description: "".to_owned() };
+
+        assert_eq!(draft.validate(), Ok(()));
+
+        let draft = QuestionDraft { title: "a".repeat(Question::MAX_TITLE_CHARS + 1), description: "".to_owned() };
+
+        assert_eq!(draft.validate().unwrap_err()[0].field, "title");
+    }
+}
@@ -0,0 +1,75 @@
+use std::{collections::HashMap, net::IpAddr, sync::Mutex};
+
+use uuid::Uuid;
+
+use crate::{
+    models::{QuestionView, QuestionViewer, Viewer},
+    tenancy,
+};
+
+/// Views queued between two flushes; more are dropped rather than kept in memory.
+const MAX_QUEUED_VIEWS: usize = 10_000;
+
+/// Question views waiting to be counted. Reads only queue them, and `jobs::spawn_view_counting`
+/// writes them in batches, so a read never waits on the counters.
+#[derive(Default)]
+pub struct ViewCounter {
+    queued: Mutex<Vec<(Option<Uuid>, QuestionView)>>,
+}
+
+impl ViewCounter {
+    /// Queues a view of the question by `viewer`, or by `client_ip` when anonymous, for the
+    /// tenant of the request.
+    pub fn record(&self, question_uuid: Uuid, viewer: &Viewer, client_ip: IpAddr) {
+        let viewer = match viewer {
+            Viewer::User(user_uuid) => QuestionViewer::User(*user_uuid),
+            Viewer::Anonymous | Viewer::SignedLink => QuestionViewer::Ip(client_ip),
+        };
+
+        let mut queued = self.queued.lock().unwrap();
+
+        if queued.len() >= MAX_QUEUED_VIEWS {
+            debug!("Dropped a view of question {}: too many views queued.", question_uuid);
+            return;
+        }
+
+        queued.push((tenancy::current_tenant(), QuestionView { question_uuid, viewer }));
+    }
+
+    /// Takes the queued views, by tenant.
+    pub fn take(&self) -> HashMap<Option<Uuid>, Vec<QuestionView>> {
+        let queued = std::mem::take(&mut *self.queued.lock().unwrap());
+        let mut by_tenant: HashMap<Option<Uuid>, Vec<QuestionView>> = HashMap::new();
+
+        for (tenant, view) in queued {
+            by_tenant.entry(tenant).or_default().push(view);
+        }
+
+        by_tenant
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn take_should_group_views_by_tenant_and_empty_the_queue() {
+        let counter = ViewCounter::default();
+        let question_uuid = Uuid::from_u128(0x123);
+        let tenant = Uuid::from_u128(0x1);
+        let ip: IpAddr = "203.0.113.1".parse().unwrap();
+
+        counter.record(question_uuid, &Viewer::Anonymous, ip);
+        tenancy::with_tenant(Some(tenant), async { counter.record(question_uuid, &Viewer::User(Uuid::from_u128(0x2)), ip) }).await;
+
+        let views = counter.take();
+
+        assert_eq!(views[&None], vec![QuestionView { question_uuid, viewer: QuestionViewer::Ip(ip) }]);
+        assert_eq!(
+            views[&Some(tenant)],
+            vec![QuestionView { question_uuid, viewer: QuestionViewer::User(Uuid::from_u128(0x2)) }]
+        );
+        assert!(counter.take().is_empty());
+    }
+}
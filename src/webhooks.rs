@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::models::{PendingWebhookDelivery, SloAlert, WebhookDigest};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a webhook secret is configured.
+pub const SIGNATURE_HEADER: &str = "X-Forum-Signature";
+/// Header naming the event of an outgoing webhook delivery, e.g. `question_created`.
+pub const EVENT_HEADER: &str = "X-Forum-Event";
+/// Header carrying the delivery UUID, unchanged across retries so receivers can drop duplicates.
+pub const DELIVERY_HEADER: &str = "X-Forum-Delivery";
+
+/// Delay before the first retry of an outgoing webhook delivery; it doubles after each attempt.
+const RETRY_BASE_DELAY_SECONDS: i32 = 30;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts batched events to a consumer that prefers fewer, larger deliveries over one request per event.
+pub struct DigestWebhook {
+    url: String,
+    secret: Option<String>,
+    pub interval: Duration,
+    client: reqwest::Client,
+}
+
+impl DigestWebhook {
+    pub fn new(url: String, secret: Option<String>, interval: Duration) -> Self {
+        DigestWebhook {
+            url,
+            secret,
+            interval,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn deliver(&self, digest: &WebhookDigest) -> Result<(), reqwest::Error> {
+        let body = serde_json::to_vec(digest).expect("digest serializes to JSON");
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+        }
+
+        request.body(body).send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// Posts SLO burn-rate alerts to an on-call or chat integration, one request per alert.
+pub struct SloAlertWebhook {
+    url: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+}
+
+impl SloAlertWebhook {
+    pub fn new(url: String, secret: Option<String>) -> Self {
+        SloAlertWebhook {
+            url,
+            secret,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn deliver(&self, alert: &SloAlert) -> Result<(), reqwest::Error> {
+        let body = serde_json::to_vec(alert).expect("alert serializes to JSON");
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, "slo_alert");
+
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+        }
+
+        request.body(body).send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum DeliveryError {
+    #[error("Webhook responded with status {0}")]
+    Status(u16),
+    #[error("Webhook request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+impl DeliveryError {
+    pub fn status(&self) -> Option<i32> {
+        match self {
+            DeliveryError::Status(status) => Some(i32::from(*status)),
+            DeliveryError::Request(err) => err.status().map(|status| i32::from(status.as_u16())),
+        }
+    }
+}
+
+/// Posts single events to the webhooks admins registered, each signed with its own secret.
+#[derive(Default)]
+pub struct EventWebhooks {
+    client: reqwest::Client,
+}
+
+impl EventWebhooks {
+    /// Returns the response status of a successful delivery.
+    pub async fn deliver(&self, delivery: &PendingWebhookDelivery) -> Result<i32, DeliveryError> {
+        let body = serde_json::to_vec(&delivery.payload).expect("payload serializes to JSON");
+
+        let response = self
+            .client
+            .post(&delivery.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, delivery.event.as_str())
+            .header(DELIVERY_HEADER, delivery.delivery_uuid.to_string())
+            .header(SIGNATURE_HEADER, signature(&delivery.secret, &body))
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(DeliveryError::Status(response.status().as_u16()));
+        }
+
+        Ok(i32::from(response.status().as_u16()))
+    }
+}
+
+/// Seconds to wait before retrying a delivery that has now failed `attempts` times, or None once
+/// `max_attempts` were made.
+pub fn retry_delay(attempts: i32, max_attempts: i32) -> Option<i32> {
+    if attempts >= max_attempts {
+        return None;
+    }
+
+    Some(RETRY_BASE_DELAY_SECONDS.saturating_mul(1 << (attempts - 1).clamp(0, 16)))
+}
+
+fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_should_be_hmac_sha256_of_body() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn retry_delay_should_double_until_attempts_are_exhausted() {
+        let delays: Vec<_> = (1..=5).map(|attempts| retry_delay(attempts, 5)).collect();
+
+        assert_eq!(delays, vec![Some(30), Some(60), Some(120), Some(240), None]);
+    }
+}